@@ -0,0 +1,216 @@
+//! Per-stage pipeline benchmarks.
+//!
+//! Each stage is measured in isolation at three sensor sizes so regressions
+//! in a single stage are not hidden by the cost of the whole pipeline.
+//!
+//! Usage: cargo bench --bench stages
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+use physical_ccd_glitch::ccd::adc::{self, AdcOverflow, AdcParams, CdsMode, DualGain};
+use physical_ccd_glitch::ccd::blooming::{self, Bloom};
+use physical_ccd_glitch::ccd::readout;
+use physical_ccd_glitch::ccd::transfer::{
+    self, ClockPhases, GlitchSchedule, Modulation, ReadoutDirection,
+};
+use physical_ccd_glitch::ccd::units::{AdcGain, Adu, Electrons};
+use physical_ccd_glitch::color::bayer::{self, BayerPattern};
+use physical_ccd_glitch::color::demosaic::{self, DemosaicAlgo};
+use physical_ccd_glitch::color::dither::Dither;
+use physical_ccd_glitch::spice::{self, SpiceParams, transfer_function};
+
+const FULL_WELL: f64 = 40_000.0;
+
+/// Small (ICX059-ish), medium, and KAF-6303 sized frames.
+const SIZES: &[(usize, usize)] = &[(512, 384), (1024, 1024), (3072, 2048)];
+
+fn size_label(w: usize, h: usize) -> String {
+    format!("{}x{}", w, h)
+}
+
+/// Horizontal gradient with a few saturated spots so blooming has work to do.
+fn test_electrons(width: usize, height: usize) -> Vec<[f64; 3]> {
+    let mut electrons = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let ramp = x as f64 / (width - 1) as f64 * FULL_WELL;
+            let hot = (x % 128 == 64 && y % 128 == 64) as u8 as f64 * FULL_WELL * 3.0;
+            let v = ramp + hot;
+            electrons.push([v, v * 0.8, v * 0.6]);
+        }
+    }
+    electrons
+}
+
+fn test_mosaic(width: usize, height: usize) -> Vec<f64> {
+    bayer::apply_bayer(&test_electrons(width, height), width, height, BayerPattern::Rggb)
+}
+
+fn bench_bayer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bayer");
+    group.sample_size(10);
+    for &(w, h) in SIZES {
+        let electrons = test_electrons(w, h);
+        group.bench_with_input(BenchmarkId::from_parameter(size_label(w, h)), &electrons, |b, e| {
+            b.iter(|| bayer::apply_bayer(black_box(e), w, h, BayerPattern::Rggb))
+        });
+    }
+    group.finish();
+}
+
+fn bench_blooming(c: &mut Criterion) {
+    let mut group = c.benchmark_group("blooming");
+    group.sample_size(10);
+    for &(w, h) in SIZES {
+        let mosaic = test_mosaic(w, h);
+        group.bench_with_input(BenchmarkId::from_parameter(size_label(w, h)), &mosaic, |b, m| {
+            b.iter_batched_ref(
+                || m.clone(),
+                |grid| {
+                    let bloom = Bloom {
+                        full_well: Electrons(FULL_WELL),
+                        abg_strength: 0.2,
+                        threshold: 0.8,
+                        vertical: true,
+                    };
+                    blooming::apply_blooming(grid, w, h, &bloom, false)
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_transfer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transfer");
+    group.sample_size(10);
+    for &(w, h) in SIZES {
+        let mosaic = test_mosaic(w, h);
+        group.bench_with_input(BenchmarkId::new("vertical", size_label(w, h)), &mosaic, |b, m| {
+            b.iter_batched_ref(
+                || m.clone(),
+                |grid| {
+                    transfer::vertical_transfer(
+                        grid,
+                        w,
+                        h,
+                        0.9999,
+                        0.01,
+                        0.1,
+                        &Modulation::default(),
+                        0.01,
+                        false,
+                        &GlitchSchedule::default(),
+                        ClockPhases::Three,
+                    )
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("horizontal", size_label(w, h)), &mosaic, |b, m| {
+            b.iter_batched_ref(
+                || m.clone(),
+                |grid| {
+                    let mut serial =
+                        transfer::SerialTransfer::new(0.9999, 0.001, 0.2, false, rand::rng());
+                    let direction = ReadoutDirection::LeftToRight;
+                    readout::stream(grid, w, h, direction, &mut [&mut serial])
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_adc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("adc");
+    group.sample_size(10);
+    for &(w, h) in SIZES {
+        let mosaic = test_mosaic(w, h);
+        group.bench_with_input(BenchmarkId::from_parameter(size_label(w, h)), &mosaic, |b, m| {
+            b.iter_batched_ref(
+                || m.clone(),
+                |grid| {
+                    let converter = AdcParams {
+                        bit_depth: 16,
+                        cds_mode: CdsMode::Partial,
+                        gain: AdcGain::e_per_adu(1.0),
+                        bias: Adu(100.0),
+                        reset_noise: Electrons(20.0),
+                        dnl_errors: 0.1,
+                        bit_errors: 0.01,
+                        dither: Dither::None,
+                        overflow: AdcOverflow::Clip,
+                    };
+                    let dual = DualGain::default();
+                    let mut converter = adc::Converter::new(&converter, &dual, rand::rng());
+                    let direction = ReadoutDirection::LeftToRight;
+                    readout::stream(grid, w, h, direction, &mut [&mut converter])
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_demosaic(c: &mut Criterion) {
+    let mut group = c.benchmark_group("demosaic");
+    group.sample_size(10);
+    for &(w, h) in SIZES {
+        let mosaic = test_mosaic(w, h);
+        for &algo in DemosaicAlgo::ALL {
+            let id = BenchmarkId::new(algo.name(), size_label(w, h));
+            group.bench_with_input(id, &mosaic, |b, m| {
+                b.iter(|| demosaic::demosaic(black_box(m), w, h, BayerPattern::Rggb, algo, false))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_spice_curve(c: &mut Criterion) {
+    let params = SpiceParams::default();
+    let mut cache = None;
+    let sensor = spice::SpiceSensor { full_well: FULL_WELL, ..Default::default() };
+    spice::simulate_or_cache(&params, &sensor, &mut cache);
+    let cache = cache.expect("SPICE simulation produced no cache");
+
+    let mut group = c.benchmark_group("spice_curve");
+    group.sample_size(10);
+    for &(w, h) in SIZES {
+        let mosaic = test_mosaic(w, h);
+        group.bench_with_input(BenchmarkId::new("transfer", size_label(w, h)), &mosaic, |b, m| {
+            b.iter_batched_ref(
+                || m.clone(),
+                |grid| {
+                    let curve = &cache.transfer_curve;
+                    transfer_function::apply_transfer_function(grid, curve, Electrons(FULL_WELL))
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("ringing", size_label(w, h)), &mosaic, |b, m| {
+            b.iter_batched_ref(
+                || m.clone(),
+                |grid| transfer_function::apply_ringing(grid, w, h, &cache.ringing_kernel, false),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    stages,
+    bench_bayer,
+    bench_blooming,
+    bench_transfer,
+    bench_adc,
+    bench_demosaic,
+    bench_spice_curve,
+);
+criterion_main!(stages);
@@ -0,0 +1,21 @@
+//! Regenerates `include/physical_ccd_glitch.h` from `src/capi.rs` when the
+//! `capi` feature is enabled; otherwise does nothing.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "capi")]
+    {
+        println!("cargo:rerun-if-changed=src/capi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+            .expect("Failed to read cbindgen.toml");
+        cbindgen::Builder::new()
+            .with_crate(&crate_dir)
+            .with_config(config)
+            .generate()
+            .expect("Failed to generate the C header")
+            .write_to_file(format!("{crate_dir}/include/physical_ccd_glitch.h"));
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * Plugin ABI for physical_ccd_glitch external glitch stages.
+ *
+ * A plugin is a shared library (.so / .dylib / .dll) placed in the plugin
+ * directory ($CCD_GLITCH_PLUGIN_DIR, else ./plugins). It exports one
+ * function, ccd_glitch_plugin_v1, returning a pointer to a descriptor that
+ * stays valid while the library is loaded.
+ *
+ * process() edits the buffer in place and returns 0 on success. It may be
+ * called from any thread, and concurrently for different frames, so it must
+ * not keep mutable global state. Any randomness should derive from `seed` so
+ * renders are repeatable.
+ *
+ *   CCD_GLITCH_STAGE_MOSAIC  Bayer mosaic after the built-in glitch stages:
+ *                            channels = 1, values are ADU codes in
+ *                            0..max_value.
+ *   CCD_GLITCH_STAGE_RGB     Linear RGB after the channel effects:
+ *                            channels = 3 (interleaved), values in 0..1,
+ *                            max_value = 1.
+ *
+ * `amount` comes from the UI slider; 1.0 is the plugin's nominal strength
+ * and 0.0 should leave the buffer unchanged.
+ */
+
+#ifndef CCD_GLITCH_PLUGIN_H
+#define CCD_GLITCH_PLUGIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define CCD_GLITCH_PLUGIN_ABI 1
+
+#define CCD_GLITCH_STAGE_MOSAIC 0
+#define CCD_GLITCH_STAGE_RGB 1
+
+typedef int32_t (*ccd_glitch_process_fn)(double *data, size_t width, size_t height,
+                                         size_t channels, double max_value, double amount,
+                                         uint64_t seed);
+
+typedef struct {
+    uint32_t abi_version;    /* CCD_GLITCH_PLUGIN_ABI */
+    uint32_t stage;          /* CCD_GLITCH_STAGE_* */
+    const char *name;        /* shown in the UI; NULL uses the file name */
+    const char *description; /* tooltip; may be NULL */
+    ccd_glitch_process_fn process;
+} ccd_glitch_plugin;
+
+#if defined(_WIN32)
+#define CCD_GLITCH_EXPORT __declspec(dllexport)
+#else
+#define CCD_GLITCH_EXPORT __attribute__((visibility("default")))
+#endif
+
+CCD_GLITCH_EXPORT const ccd_glitch_plugin *ccd_glitch_plugin_v1(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CCD_GLITCH_PLUGIN_H */
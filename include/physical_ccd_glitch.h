@@ -0,0 +1,112 @@
+#ifndef PHYSICAL_CCD_GLITCH_H
+#define PHYSICAL_CCD_GLITCH_H
+
+/* Generated by cbindgen from src/capi.rs; do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define CCD_OK 0
+
+// A required pointer was null.
+#define CCD_ERR_NULL -1
+
+// Unknown parameter name or unparsable value.
+#define CCD_ERR_INVALID -2
+
+// An image is too small or has an unsupported channel count.
+#define CCD_ERR_SIZE -3
+
+// The pipeline panicked; the handles are still usable.
+#define CCD_ERR_PANIC -4
+
+// Opaque pipeline parameters.
+typedef struct CcdParams CcdParams;
+
+// Opaque SPICE simulation results.
+typedef struct CcdSpiceCache CcdSpiceCache;
+
+// An 8-bit interleaved image. `stride` is bytes per row; 0 means tightly
+// packed. `channels` is 3 (RGB) or 4 (RGBA).
+typedef struct CcdImage {
+  uint8_t *data;
+  uint32_t width;
+  uint32_t height;
+  uint32_t stride;
+  uint32_t channels;
+} CcdImage;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message for the last failed call on this thread. Valid until the next
+// failing call on the same thread.
+const char *ccd_last_error(void);
+
+// Parameters at their defaults. Free with `ccd_params_free`.
+CcdParams *ccd_params_new(void);
+
+// Copy of `params`, or null if `params` is null.
+//
+// # Safety
+// `params` must be null or a live handle from this API.
+CcdParams *ccd_params_clone(const CcdParams *params);
+
+// # Safety
+// `params` must be null or a handle from this API not already freed.
+void ccd_params_free(CcdParams *params);
+
+// Set a parameter by name (as listed by `--set` and the "Changed from
+// Default" readout) from its text form, e.g. `"v_glitch_rate"`, `"0.02"`.
+//
+// # Safety
+// `params` must be a live handle; `name` and `value` nul-terminated strings.
+int32_t ccd_params_set(CcdParams *params, const char *name, const char *value);
+
+// Write a parameter's current value as text into `buf` (`len` bytes,
+// always nul-terminated when `len > 0`). Returns the full length of the
+// text, excluding the nul, so a return `>= len` means it was truncated.
+//
+// # Safety
+// `params` must be a live handle, `name` a nul-terminated string, and `buf`
+// null or valid for `len` bytes.
+int32_t ccd_params_get(const CcdParams *params, const char *name, char *buf, size_t len);
+
+// Size of the image `ccd_process` produces: the sensor size, widened for
+// anamorphic output and scaled up when a stack is drizzled.
+//
+// # Safety
+// `params` must be a live handle; `width` and `height` writable.
+int32_t ccd_output_size(const CcdParams *params, uint32_t *width, uint32_t *height);
+
+// Run the SPICE circuit simulations for `params` (slow: seconds). The
+// cache stays valid while the `spice.*` parameters and full well are
+// unchanged. Free with `ccd_spice_cache_free`.
+//
+// # Safety
+// `params` must be null or a live handle.
+CcdSpiceCache *ccd_spice_cache_new(const CcdParams *params);
+
+// # Safety
+// `cache` must be null or a handle from this API not already freed.
+void ccd_spice_cache_free(CcdSpiceCache *cache);
+
+// Process `input` into `output`. `output` must be allocated at
+// `ccd_output_size`; for RGBA output the alpha channel follows the
+// `alpha_mode` parameter, or is opaque when the input has none. `cache`
+// may be null, in which case SPICE modes fall back to the math stages.
+//
+// # Safety
+// `params` must be a live handle, `cache` null or a live handle, and each
+// image's `data` valid for `height` rows of `stride` bytes.
+int32_t ccd_process(const CcdParams *params,
+                    const CcdSpiceCache *cache,
+                    const CcdImage *input,
+                    CcdImage *output);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PHYSICAL_CCD_GLITCH_H */
@@ -0,0 +1,613 @@
+//! OpenFX image effect wrapping `pipeline::process`, for use on timelines in
+//! Resolve, Nuke, Natron and other OFX hosts.
+//!
+//! Each frame is processed at its own resolution (the sensor takes the
+//! source size, stretched, with square photosites, and drizzle is off).
+//! Every other parameter in the pipeline schema is exposed as an animatable
+//! OFX parameter, grouped like the app's sections. SPICE simulations are
+//! cached per effect instance and rerun only when their inputs change.
+//! Glitch patterns follow the temporal parameters, keyed on the frame time
+//! so renders are repeatable in any order.
+
+mod sys;
+
+use std::ffi::{CStr, CString, c_char, c_int, c_void};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use physical_ccd_glitch::image_io::FitMode;
+use physical_ccd_glitch::pipeline::schema::{CATEGORIES, ParamKind, ParamSpec};
+use physical_ccd_glitch::pipeline::{self, ParamValue, PipelineParams, TemporalState};
+use physical_ccd_glitch::spice::{self, SpiceCache, SpiceMode};
+
+use sys::*;
+
+type Result<T = ()> = std::result::Result<T, OfxStatus>;
+
+/// Schema parameters exposed to the host. Framing is left out: each frame
+/// takes the source size, stretched, with square photosites. So is drizzle,
+/// which would render larger than the output clip.
+fn host_params() -> impl Iterator<Item = &'static ParamSpec> {
+    const DRIZZLE: [&str; 3] = ["stack.drizzle", "stack.pixfrac", "stack.drizzle_scale"];
+    PipelineParams::schema()
+        .iter()
+        .filter(|p| p.category != "framing" && !DRIZZLE.contains(&p.name))
+}
+
+/// OFX name; hosts such as Nuke reject dots in knob names.
+fn ofx_name(param: &ParamSpec) -> CString {
+    CString::new(param.name.replace('.', "_")).unwrap()
+}
+
+/// Host suites, fetched on load.
+struct Suites {
+    prop: &'static OfxPropertySuiteV1,
+    param: &'static OfxParameterSuiteV1,
+    effect: &'static OfxImageEffectSuiteV1,
+}
+
+static HOST: AtomicPtr<OfxHost> = AtomicPtr::new(null_mut());
+static SUITES: OnceLock<Suites> = OnceLock::new();
+
+fn suites() -> &'static Suites {
+    SUITES.get().expect("OfxActionLoad has not run")
+}
+
+/// Per-instance state, stored in the effect's instance data.
+struct Instance {
+    spice_cache: Mutex<Option<SpiceCache>>,
+    /// Base seed of this instance's glitch patterns.
+    temporal_seed: u64,
+}
+
+fn check(status: OfxStatus) -> Result {
+    if status == STAT_OK {
+        Ok(())
+    } else {
+        Err(status)
+    }
+}
+
+// Property helpers
+
+unsafe fn set_string(
+    props: OfxPropertySetHandle,
+    name: &CStr,
+    index: c_int,
+    value: &CStr,
+) -> Result {
+    check(unsafe { (suites().prop.prop_set_string)(props, name.as_ptr(), index, value.as_ptr()) })
+}
+
+unsafe fn set_int(props: OfxPropertySetHandle, name: &CStr, value: c_int) -> Result {
+    check(unsafe { (suites().prop.prop_set_int)(props, name.as_ptr(), 0, value) })
+}
+
+unsafe fn set_double(props: OfxPropertySetHandle, name: &CStr, value: f64) -> Result {
+    check(unsafe { (suites().prop.prop_set_double)(props, name.as_ptr(), 0, value) })
+}
+
+unsafe fn get_int(props: OfxPropertySetHandle, name: &CStr) -> Result<c_int> {
+    let mut value = 0;
+    check(unsafe { (suites().prop.prop_get_int)(props, name.as_ptr(), 0, &mut value) })?;
+    Ok(value)
+}
+
+unsafe fn get_int4(props: OfxPropertySetHandle, name: &CStr) -> Result<[c_int; 4]> {
+    let mut value = [0; 4];
+    check(unsafe { (suites().prop.prop_get_int_n)(props, name.as_ptr(), 4, value.as_mut_ptr()) })?;
+    Ok(value)
+}
+
+unsafe fn get_double(props: OfxPropertySetHandle, name: &CStr) -> Result<f64> {
+    let mut value = 0.0;
+    check(unsafe { (suites().prop.prop_get_double)(props, name.as_ptr(), 0, &mut value) })?;
+    Ok(value)
+}
+
+unsafe fn get_pointer(props: OfxPropertySetHandle, name: &CStr) -> Result<*mut c_void> {
+    let mut value = null_mut();
+    check(unsafe { (suites().prop.prop_get_pointer)(props, name.as_ptr(), 0, &mut value) })?;
+    Ok(value)
+}
+
+unsafe fn get_string(props: OfxPropertySetHandle, name: &CStr) -> Result<String> {
+    let mut value: *mut c_char = null_mut();
+    check(unsafe { (suites().prop.prop_get_string)(props, name.as_ptr(), 0, &mut value) })?;
+    if value.is_null() {
+        return Err(STAT_FAILED);
+    }
+    Ok(unsafe { CStr::from_ptr(value) }
+        .to_string_lossy()
+        .into_owned())
+}
+
+unsafe fn effect_props(effect: OfxImageEffectHandle) -> Result<OfxPropertySetHandle> {
+    let mut props = null_mut();
+    check(unsafe { (suites().effect.get_property_set)(effect, &mut props) })?;
+    Ok(props)
+}
+
+// Actions
+
+unsafe fn load() -> Result {
+    let host = HOST.load(Ordering::Acquire);
+    if host.is_null() {
+        return Err(STAT_ERR_MISSING_HOST_FEATURE);
+    }
+    let host = unsafe { &*host };
+    let fetch = |name: &CStr| unsafe { (host.fetch_suite)(host.host, name.as_ptr(), 1) };
+    let (prop, param, effect) = (
+        fetch(PROPERTY_SUITE).cast::<OfxPropertySuiteV1>(),
+        fetch(PARAMETER_SUITE).cast::<OfxParameterSuiteV1>(),
+        fetch(IMAGE_EFFECT_SUITE).cast::<OfxImageEffectSuiteV1>(),
+    );
+    if prop.is_null() || param.is_null() || effect.is_null() {
+        return Err(STAT_ERR_MISSING_HOST_FEATURE);
+    }
+    // SAFETY: suites live for as long as the host keeps the plugin loaded.
+    let _ = SUITES.set(unsafe {
+        Suites {
+            prop: &*prop,
+            param: &*param,
+            effect: &*effect,
+        }
+    });
+    Ok(())
+}
+
+unsafe fn describe(effect: OfxImageEffectHandle) -> Result {
+    let props = unsafe { effect_props(effect) }?;
+    unsafe {
+        set_string(props, PROP_LABEL, 0, c"Physical CCD Glitch")?;
+        set_string(props, PROP_GROUPING, 0, c"Glitch")?;
+        set_string(props, PROP_SUPPORTED_CONTEXTS, 0, CONTEXT_FILTER)?;
+        set_string(props, PROP_SUPPORTED_CONTEXTS, 1, CONTEXT_GENERAL)?;
+        set_string(props, PROP_SUPPORTED_PIXEL_DEPTHS, 0, BIT_DEPTH_FLOAT)?;
+        set_string(props, PROP_SUPPORTED_PIXEL_DEPTHS, 1, BIT_DEPTH_BYTE)?;
+        // Readout effects span the whole frame
+        set_int(props, PROP_SUPPORTS_TILES, 0)?;
+        set_int(props, PROP_SUPPORTS_MULTI_RESOLUTION, 0)?;
+        set_string(props, PROP_RENDER_THREAD_SAFETY, 0, RENDER_INSTANCE_SAFE)?;
+    }
+    Ok(())
+}
+
+unsafe fn describe_in_context(effect: OfxImageEffectHandle) -> Result {
+    let s = suites();
+    for clip in [SOURCE_CLIP, OUTPUT_CLIP] {
+        let mut props = null_mut();
+        unsafe {
+            check((s.effect.clip_define)(effect, clip.as_ptr(), &mut props))?;
+            set_string(props, PROP_SUPPORTED_COMPONENTS, 0, COMPONENT_RGBA)?;
+            set_string(props, PROP_SUPPORTED_COMPONENTS, 1, COMPONENT_RGB)?;
+            set_int(props, PROP_SUPPORTS_TILES, 0)?;
+        }
+    }
+
+    let mut param_set = null_mut();
+    unsafe { check((s.effect.get_param_set)(effect, &mut param_set))? };
+    let define = |kind: &CStr, name: &CStr| -> Result<OfxPropertySetHandle> {
+        let mut props = null_mut();
+        unsafe {
+            check((s.param.param_define)(
+                param_set,
+                kind.as_ptr(),
+                name.as_ptr(),
+                &mut props,
+            ))?
+        };
+        Ok(props)
+    };
+
+    for &(name, label) in CATEGORIES.iter().filter(|(name, _)| *name != "framing") {
+        let props = define(PARAM_TYPE_GROUP, &CString::new(name).unwrap())?;
+        unsafe { set_string(props, PROP_LABEL, 0, &CString::new(label).unwrap())? };
+    }
+
+    let defaults = PipelineParams::default().values();
+    for param in host_params() {
+        let default = defaults
+            .iter()
+            .find(|(n, _)| *n == param.name)
+            .map(|(_, v)| v.clone())
+            .expect("OFX parameter missing from PipelineParams::values()");
+        let type_name = match param.kind {
+            ParamKind::Float { .. } => PARAM_TYPE_DOUBLE,
+            ParamKind::Int { .. } => PARAM_TYPE_INTEGER,
+            ParamKind::Bool => PARAM_TYPE_BOOLEAN,
+            ParamKind::Choice(_) => PARAM_TYPE_CHOICE,
+        };
+        let props = define(type_name, &ofx_name(param))?;
+        unsafe {
+            set_string(props, PROP_LABEL, 0, &CString::new(param.label).unwrap())?;
+            set_string(props, PROP_PARAM_HINT, 0, &CString::new(param.doc).unwrap())?;
+            set_string(
+                props,
+                PROP_PARAM_PARENT,
+                0,
+                &CString::new(param.category).unwrap(),
+            )?;
+        }
+        match (&param.kind, default) {
+            (&ParamKind::Float { min, max }, ParamValue::Float(v)) => unsafe {
+                set_double(props, PROP_PARAM_DEFAULT, v)?;
+                set_double(props, PROP_PARAM_MIN, min)?;
+                set_double(props, PROP_PARAM_MAX, max)?;
+                set_double(props, PROP_PARAM_DISPLAY_MIN, min)?;
+                set_double(props, PROP_PARAM_DISPLAY_MAX, max)?;
+            },
+            (&ParamKind::Int { min, max }, ParamValue::Int(v)) => unsafe {
+                set_int(props, PROP_PARAM_DEFAULT, v as c_int)?;
+                set_int(props, PROP_PARAM_MIN, min)?;
+                set_int(props, PROP_PARAM_MAX, max)?;
+                set_int(props, PROP_PARAM_DISPLAY_MIN, min)?;
+                set_int(props, PROP_PARAM_DISPLAY_MAX, max)?;
+            },
+            (ParamKind::Bool, ParamValue::Bool(v)) => unsafe {
+                set_int(props, PROP_PARAM_DEFAULT, v as c_int)?;
+            },
+            (ParamKind::Choice(options), ParamValue::Choice(v)) => {
+                let options = options();
+                for (i, option) in options.iter().enumerate() {
+                    let option = CString::new(*option).unwrap();
+                    unsafe { set_string(props, PROP_PARAM_CHOICE_OPTION, i as c_int, &option)? };
+                }
+                let index = options.iter().position(|o| *o == v).unwrap_or(0);
+                unsafe { set_int(props, PROP_PARAM_DEFAULT, index as c_int)? };
+            }
+            _ => panic!(
+                "OFX parameter {} does not match its pipeline type",
+                param.name
+            ),
+        }
+    }
+    Ok(())
+}
+
+unsafe fn create_instance(effect: OfxImageEffectHandle) -> Result {
+    let instance = Box::new(Instance {
+        spice_cache: Mutex::new(None),
+        temporal_seed: TemporalState::default().seed,
+    });
+    let props = unsafe { effect_props(effect) }?;
+    let ptr = Box::into_raw(instance).cast::<c_void>();
+    check(unsafe { (suites().prop.prop_set_pointer)(props, PROP_INSTANCE_DATA.as_ptr(), 0, ptr) })
+}
+
+unsafe fn destroy_instance(effect: OfxImageEffectHandle) -> Result {
+    let props = unsafe { effect_props(effect) }?;
+    let ptr = unsafe { get_pointer(props, PROP_INSTANCE_DATA) }?;
+    if !ptr.is_null() {
+        drop(unsafe { Box::from_raw(ptr.cast::<Instance>()) });
+    }
+    Ok(())
+}
+
+/// Read every exposed parameter at `time` into pipeline parameters.
+unsafe fn params_at(effect: OfxImageEffectHandle, time: OfxTime) -> Result<PipelineParams> {
+    let s = suites();
+    let mut param_set = null_mut();
+    unsafe { check((s.effect.get_param_set)(effect, &mut param_set))? };
+
+    let mut params = PipelineParams::default();
+    for param in host_params() {
+        let mut handle = null_mut();
+        let name = ofx_name(param);
+        unsafe {
+            check((s.param.param_get_handle)(
+                param_set,
+                name.as_ptr(),
+                &mut handle,
+                null_mut(),
+            ))?
+        };
+        let text = match &param.kind {
+            ParamKind::Float { .. } => {
+                let mut v = 0.0f64;
+                unsafe {
+                    check((s.param.param_get_value_at_time)(
+                        handle,
+                        time,
+                        &mut v as *mut f64,
+                    ))?
+                };
+                v.to_string()
+            }
+            kind => {
+                let mut v: c_int = 0;
+                unsafe {
+                    check((s.param.param_get_value_at_time)(
+                        handle,
+                        time,
+                        &mut v as *mut c_int,
+                    ))?
+                };
+                match kind {
+                    ParamKind::Bool => (v != 0).to_string(),
+                    ParamKind::Choice(options) => options()
+                        .get(v as usize)
+                        .copied()
+                        .unwrap_or_default()
+                        .to_string(),
+                    _ => v.to_string(),
+                }
+            }
+        };
+        params
+            .set_value(param.name, &text)
+            .map_err(|_| STAT_FAILED)?;
+    }
+    Ok(params)
+}
+
+/// A fetched clip image; released on drop.
+struct ClipImage {
+    handle: OfxPropertySetHandle,
+    data: *mut u8,
+    bounds: [c_int; 4],
+    row_bytes: isize,
+    float: bool,
+    channels: usize,
+}
+
+impl ClipImage {
+    unsafe fn fetch(effect: OfxImageEffectHandle, clip: &CStr, time: OfxTime) -> Result<Self> {
+        let s = suites();
+        let mut clip_handle = null_mut();
+        let mut handle = null_mut();
+        unsafe {
+            check((s.effect.clip_get_handle)(
+                effect,
+                clip.as_ptr(),
+                &mut clip_handle,
+                null_mut(),
+            ))?;
+            check((s.effect.clip_get_image)(
+                clip_handle,
+                time,
+                std::ptr::null(),
+                &mut handle,
+            ))?;
+        }
+        // Wrap first so a failure below still releases the image
+        let mut image = Self {
+            handle,
+            data: null_mut(),
+            bounds: [0; 4],
+            row_bytes: 0,
+            float: false,
+            channels: 0,
+        };
+        unsafe {
+            image.data = get_pointer(handle, PROP_IMAGE_DATA)?.cast::<u8>();
+            image.bounds = get_int4(handle, PROP_IMAGE_BOUNDS)?;
+            image.row_bytes = get_int(handle, PROP_IMAGE_ROW_BYTES)? as isize;
+            image.float = match get_string(handle, PROP_PIXEL_DEPTH)?.as_str() {
+                "OfxBitDepthFloat" => true,
+                "OfxBitDepthByte" => false,
+                _ => return Err(STAT_ERR_IMAGE_FORMAT),
+            };
+            image.channels = match get_string(handle, PROP_COMPONENTS)?.as_str() {
+                "OfxImageComponentRGBA" => 4,
+                "OfxImageComponentRGB" => 3,
+                _ => return Err(STAT_ERR_IMAGE_FORMAT),
+            };
+        }
+        if image.data.is_null() {
+            return Err(STAT_FAILED);
+        }
+        Ok(image)
+    }
+
+    fn width(&self) -> usize {
+        (self.bounds[2] - self.bounds[0]).max(0) as usize
+    }
+
+    fn height(&self) -> usize {
+        (self.bounds[3] - self.bounds[1]).max(0) as usize
+    }
+
+    /// Pointer to pixel (`x`, `y`) in host coordinates (y up).
+    unsafe fn pixel(&self, x: c_int, y: c_int) -> *mut u8 {
+        let bytes = if self.float { 4 } else { 1 } * self.channels;
+        let row = (y - self.bounds[1]) as isize * self.row_bytes;
+        let col = (x - self.bounds[0]) as isize * bytes as isize;
+        unsafe { self.data.offset(row + col) }
+    }
+
+    /// Copy into a top-down image for the pipeline.
+    unsafe fn to_image(&self) -> image::DynamicImage {
+        let (w, h) = (self.width(), self.height());
+        let c = self.channels;
+        let mut values = Vec::with_capacity(w * h * c);
+        for row in 0..h {
+            let y = self.bounds[3] - 1 - row as c_int;
+            let start = unsafe { self.pixel(self.bounds[0], y) };
+            if self.float {
+                let px = unsafe { std::slice::from_raw_parts(start.cast::<f32>(), w * c) };
+                values.extend_from_slice(px);
+            } else {
+                let px = unsafe { std::slice::from_raw_parts(start, w * c) };
+                values.extend(px.iter().map(|&v| v as f32 / 255.0));
+            }
+        }
+        let (w, h) = (w as u32, h as u32);
+        if c == 4 {
+            image::DynamicImage::ImageRgba32F(image::ImageBuffer::from_raw(w, h, values).unwrap())
+        } else {
+            image::DynamicImage::ImageRgb32F(image::ImageBuffer::from_raw(w, h, values).unwrap())
+        }
+    }
+}
+
+impl Drop for ClipImage {
+    fn drop(&mut self) {
+        unsafe { (suites().effect.clip_release_image)(self.handle) };
+    }
+}
+
+unsafe fn render(effect: OfxImageEffectHandle, in_args: OfxPropertySetHandle) -> Result {
+    let time = unsafe { get_double(in_args, PROP_TIME) }?;
+    let window = unsafe { get_int4(in_args, PROP_RENDER_WINDOW) }?;
+    let props = unsafe { effect_props(effect) }?;
+    let instance = unsafe { get_pointer(props, PROP_INSTANCE_DATA) }?.cast::<Instance>();
+    if instance.is_null() {
+        return Err(STAT_ERR_BAD_HANDLE);
+    }
+    let instance = unsafe { &*instance };
+
+    let mut params = unsafe { params_at(effect, time) }?;
+    let source = unsafe { ClipImage::fetch(effect, SOURCE_CLIP, time) }?;
+    let output = unsafe { ClipImage::fetch(effect, OUTPUT_CLIP, time) }?;
+    let (w, h) = (source.width(), source.height());
+    if w == 0 || h == 0 {
+        return Ok(());
+    }
+    params.sensor_width = w as u32;
+    params.sensor_height = h as u32;
+    params.fit_mode = FitMode::Stretch;
+    params.pixel_aspect = 1.0;
+
+    let input = unsafe { source.to_image() };
+    let mut cache = instance
+        .spice_cache
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if params.spice.mode != SpiceMode::Off {
+        spice::simulate_or_cache(&params.spice, &params.spice_sensor(), &mut cache);
+    }
+    let mut temporal = TemporalState {
+        seed: instance.temporal_seed,
+        frame: time.floor() as i64 as u64,
+    };
+    let (out_w, out_h, rgb) = pipeline::process_temporal(&input, &params, &cache, &mut temporal);
+    drop(cache);
+    debug_assert_eq!((out_w, out_h), (w, h));
+
+    // Write the render window, flipping back to the host's bottom-up rows
+    let [x1, y1, x2, y2] = window;
+    let (x1, x2) = (x1.max(output.bounds[0]), x2.min(output.bounds[2]));
+    let (y1, y2) = (y1.max(output.bounds[1]), y2.min(output.bounds[3]));
+    for y in y1..y2 {
+        let row = source.bounds[3] - 1 - y;
+        if row < 0 || row as usize >= out_h {
+            continue;
+        }
+        for x in x1..x2 {
+            let col = x - source.bounds[0];
+            if col < 0 || col as usize >= out_w {
+                continue;
+            }
+            let i = (row as usize * out_w + col as usize) * 3;
+            let alpha = (source.channels == 4 && x < source.bounds[2] && y >= source.bounds[1])
+                .then(|| unsafe { source.pixel(x, y) });
+            let dst = unsafe { output.pixel(x, y) };
+            if output.float {
+                let dst =
+                    unsafe { std::slice::from_raw_parts_mut(dst.cast::<f32>(), output.channels) };
+                for c in 0..3 {
+                    dst[c] = rgb[i + c] as f32 / 255.0;
+                }
+                if output.channels == 4 {
+                    dst[3] = alpha.map_or(1.0, |a| unsafe { alpha_f32(a, source.float) });
+                }
+            } else {
+                let dst = unsafe { std::slice::from_raw_parts_mut(dst, output.channels) };
+                dst[..3].copy_from_slice(&rgb[i..i + 3]);
+                if output.channels == 4 {
+                    dst[3] = alpha.map_or(255, |a| {
+                        (unsafe { alpha_f32(a, source.float) } * 255.0)
+                            .round()
+                            .clamp(0.0, 255.0) as u8
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Alpha of an RGBA source pixel, 0..1.
+unsafe fn alpha_f32(pixel: *const u8, float: bool) -> f32 {
+    if float {
+        unsafe { *pixel.cast::<f32>().add(3) }
+    } else {
+        (unsafe { *pixel.add(3) }) as f32 / 255.0
+    }
+}
+
+// Entry points
+
+unsafe extern "C" fn set_host(host: *mut OfxHost) {
+    HOST.store(host, Ordering::Release);
+}
+
+unsafe extern "C" fn main_entry(
+    action: *const c_char,
+    handle: *const c_void,
+    in_args: OfxPropertySetHandle,
+    _out_args: OfxPropertySetHandle,
+) -> OfxStatus {
+    if action.is_null() {
+        return STAT_ERR_BAD_HANDLE;
+    }
+    let action = unsafe { CStr::from_ptr(action) };
+    let effect = handle as OfxImageEffectHandle;
+    let result = catch_unwind(AssertUnwindSafe(|| unsafe {
+        if action == ACTION_LOAD {
+            load()
+        } else if action == ACTION_DESCRIBE {
+            describe(effect)
+        } else if action == ACTION_DESCRIBE_IN_CONTEXT {
+            describe_in_context(effect)
+        } else if action == ACTION_CREATE_INSTANCE {
+            create_instance(effect)
+        } else if action == ACTION_DESTROY_INSTANCE {
+            destroy_instance(effect)
+        } else if action == ACTION_RENDER {
+            render(effect, in_args)
+        } else {
+            Err(STAT_REPLY_DEFAULT)
+        }
+    }));
+    match result {
+        Ok(Ok(())) => STAT_OK,
+        Ok(Err(status)) => status,
+        Err(_) => STAT_ERR_FATAL,
+    }
+}
+
+/// `OfxPlugin` holds raw pointers, so it needs a wrapper to be a static.
+struct PluginDescriptor(OfxPlugin);
+
+// SAFETY: the descriptor is immutable and its pointers are to static strings.
+unsafe impl Sync for PluginDescriptor {}
+
+static PLUGIN: PluginDescriptor = PluginDescriptor(OfxPlugin {
+    plugin_api: IMAGE_EFFECT_PLUGIN_API.as_ptr(),
+    api_version: 1,
+    plugin_identifier: c"io.github.mgolub2.PhysicalCcdGlitch".as_ptr(),
+    plugin_version_major: 0,
+    plugin_version_minor: 1,
+    set_host,
+    main_entry,
+});
+
+#[allow(non_snake_case)]
+#[unsafe(no_mangle)]
+pub extern "C" fn OfxGetNumberOfPlugins() -> c_int {
+    1
+}
+
+#[allow(non_snake_case)]
+#[unsafe(no_mangle)]
+pub extern "C" fn OfxGetPlugin(nth: c_int) -> *const OfxPlugin {
+    if nth == 0 {
+        &PLUGIN.0
+    } else {
+        std::ptr::null()
+    }
+}
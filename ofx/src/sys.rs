@@ -0,0 +1,192 @@
+//! The subset of the OpenFX 1.4 C API this plugin uses, transcribed from
+//! `ofxCore.h`, `ofxProperty.h`, `ofxParam.h` and `ofxImageEffect.h`.
+//! Suite layouts must match the headers field for field.
+
+#![allow(dead_code)]
+
+use std::ffi::{CStr, c_char, c_int, c_void};
+
+pub type OfxStatus = c_int;
+pub type OfxTime = f64;
+pub type OfxPropertySetHandle = *mut c_void;
+pub type OfxParamSetHandle = *mut c_void;
+pub type OfxParamHandle = *mut c_void;
+pub type OfxImageEffectHandle = *mut c_void;
+pub type OfxImageClipHandle = *mut c_void;
+pub type OfxImageMemoryHandle = *mut c_void;
+
+pub const STAT_OK: OfxStatus = 0;
+pub const STAT_FAILED: OfxStatus = 1;
+pub const STAT_ERR_FATAL: OfxStatus = 2;
+pub const STAT_ERR_MISSING_HOST_FEATURE: OfxStatus = 4;
+pub const STAT_ERR_UNSUPPORTED: OfxStatus = 5;
+pub const STAT_ERR_BAD_HANDLE: OfxStatus = 9;
+pub const STAT_ERR_IMAGE_FORMAT: OfxStatus = 1000;
+pub const STAT_REPLY_DEFAULT: OfxStatus = 14;
+
+#[repr(C)]
+pub struct OfxHost {
+    pub host: OfxPropertySetHandle,
+    pub fetch_suite: unsafe extern "C" fn(
+        host: OfxPropertySetHandle,
+        suite_name: *const c_char,
+        suite_version: c_int,
+    ) -> *const c_void,
+}
+
+pub type OfxPluginEntryPoint = unsafe extern "C" fn(
+    action: *const c_char,
+    handle: *const c_void,
+    in_args: OfxPropertySetHandle,
+    out_args: OfxPropertySetHandle,
+) -> OfxStatus;
+
+#[repr(C)]
+pub struct OfxPlugin {
+    pub plugin_api: *const c_char,
+    pub api_version: c_int,
+    pub plugin_identifier: *const c_char,
+    pub plugin_version_major: u32,
+    pub plugin_version_minor: u32,
+    pub set_host: unsafe extern "C" fn(host: *mut OfxHost),
+    pub main_entry: OfxPluginEntryPoint,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OfxRectD {
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+}
+
+type H = OfxPropertySetHandle;
+type S = *const c_char;
+
+#[repr(C)]
+pub struct OfxPropertySuiteV1 {
+    pub prop_set_pointer: unsafe extern "C" fn(H, S, c_int, *mut c_void) -> OfxStatus,
+    pub prop_set_string: unsafe extern "C" fn(H, S, c_int, S) -> OfxStatus,
+    pub prop_set_double: unsafe extern "C" fn(H, S, c_int, f64) -> OfxStatus,
+    pub prop_set_int: unsafe extern "C" fn(H, S, c_int, c_int) -> OfxStatus,
+    pub prop_set_pointer_n: unsafe extern "C" fn(H, S, c_int, *const *mut c_void) -> OfxStatus,
+    pub prop_set_string_n: unsafe extern "C" fn(H, S, c_int, *const S) -> OfxStatus,
+    pub prop_set_double_n: unsafe extern "C" fn(H, S, c_int, *const f64) -> OfxStatus,
+    pub prop_set_int_n: unsafe extern "C" fn(H, S, c_int, *const c_int) -> OfxStatus,
+    pub prop_get_pointer: unsafe extern "C" fn(H, S, c_int, *mut *mut c_void) -> OfxStatus,
+    pub prop_get_string: unsafe extern "C" fn(H, S, c_int, *mut *mut c_char) -> OfxStatus,
+    pub prop_get_double: unsafe extern "C" fn(H, S, c_int, *mut f64) -> OfxStatus,
+    pub prop_get_int: unsafe extern "C" fn(H, S, c_int, *mut c_int) -> OfxStatus,
+    pub prop_get_pointer_n: unsafe extern "C" fn(H, S, c_int, *mut *mut c_void) -> OfxStatus,
+    pub prop_get_string_n: unsafe extern "C" fn(H, S, c_int, *mut *mut c_char) -> OfxStatus,
+    pub prop_get_double_n: unsafe extern "C" fn(H, S, c_int, *mut f64) -> OfxStatus,
+    pub prop_get_int_n: unsafe extern "C" fn(H, S, c_int, *mut c_int) -> OfxStatus,
+    pub prop_reset: unsafe extern "C" fn(H, S) -> OfxStatus,
+    pub prop_get_dimension: unsafe extern "C" fn(H, S, *mut c_int) -> OfxStatus,
+}
+
+#[repr(C)]
+pub struct OfxParameterSuiteV1 {
+    pub param_define: unsafe extern "C" fn(OfxParamSetHandle, S, S, *mut H) -> OfxStatus,
+    pub param_get_handle:
+        unsafe extern "C" fn(OfxParamSetHandle, S, *mut OfxParamHandle, *mut H) -> OfxStatus,
+    pub param_set_get_property_set: unsafe extern "C" fn(OfxParamSetHandle, *mut H) -> OfxStatus,
+    pub param_get_property_set: unsafe extern "C" fn(OfxParamHandle, *mut H) -> OfxStatus,
+    pub param_get_value: unsafe extern "C" fn(OfxParamHandle, ...) -> OfxStatus,
+    pub param_get_value_at_time: unsafe extern "C" fn(OfxParamHandle, OfxTime, ...) -> OfxStatus,
+    pub param_get_derivative: unsafe extern "C" fn(OfxParamHandle, OfxTime, ...) -> OfxStatus,
+    pub param_get_integral:
+        unsafe extern "C" fn(OfxParamHandle, OfxTime, OfxTime, ...) -> OfxStatus,
+    pub param_set_value: unsafe extern "C" fn(OfxParamHandle, ...) -> OfxStatus,
+    pub param_set_value_at_time: unsafe extern "C" fn(OfxParamHandle, OfxTime, ...) -> OfxStatus,
+    pub param_get_num_keys: unsafe extern "C" fn(OfxParamHandle, *mut u32) -> OfxStatus,
+    pub param_get_key_time: unsafe extern "C" fn(OfxParamHandle, u32, *mut OfxTime) -> OfxStatus,
+    pub param_get_key_index:
+        unsafe extern "C" fn(OfxParamHandle, OfxTime, c_int, *mut c_int) -> OfxStatus,
+    pub param_delete_key: unsafe extern "C" fn(OfxParamHandle, OfxTime) -> OfxStatus,
+    pub param_delete_all_keys: unsafe extern "C" fn(OfxParamHandle) -> OfxStatus,
+    pub param_copy:
+        unsafe extern "C" fn(OfxParamHandle, OfxParamHandle, OfxTime, *const c_void) -> OfxStatus,
+    pub param_edit_begin: unsafe extern "C" fn(OfxParamSetHandle, S) -> OfxStatus,
+    pub param_edit_end: unsafe extern "C" fn(OfxParamSetHandle) -> OfxStatus,
+}
+
+#[repr(C)]
+pub struct OfxImageEffectSuiteV1 {
+    pub get_property_set: unsafe extern "C" fn(OfxImageEffectHandle, *mut H) -> OfxStatus,
+    pub get_param_set:
+        unsafe extern "C" fn(OfxImageEffectHandle, *mut OfxParamSetHandle) -> OfxStatus,
+    pub clip_define: unsafe extern "C" fn(OfxImageEffectHandle, S, *mut H) -> OfxStatus,
+    pub clip_get_handle:
+        unsafe extern "C" fn(OfxImageEffectHandle, S, *mut OfxImageClipHandle, *mut H) -> OfxStatus,
+    pub clip_get_property_set: unsafe extern "C" fn(OfxImageClipHandle, *mut H) -> OfxStatus,
+    pub clip_get_image:
+        unsafe extern "C" fn(OfxImageClipHandle, OfxTime, *const OfxRectD, *mut H) -> OfxStatus,
+    pub clip_release_image: unsafe extern "C" fn(H) -> OfxStatus,
+    pub clip_get_region_of_definition:
+        unsafe extern "C" fn(OfxImageClipHandle, OfxTime, *mut OfxRectD) -> OfxStatus,
+    pub abort: unsafe extern "C" fn(OfxImageEffectHandle) -> c_int,
+    pub image_memory_alloc:
+        unsafe extern "C" fn(OfxImageEffectHandle, usize, *mut OfxImageMemoryHandle) -> OfxStatus,
+    pub image_memory_free: unsafe extern "C" fn(OfxImageMemoryHandle) -> OfxStatus,
+    pub image_memory_lock:
+        unsafe extern "C" fn(OfxImageMemoryHandle, *mut *mut c_void) -> OfxStatus,
+    pub image_memory_unlock: unsafe extern "C" fn(OfxImageMemoryHandle) -> OfxStatus,
+}
+
+// Suites
+pub const PROPERTY_SUITE: &CStr = c"OfxPropertySuite";
+pub const PARAMETER_SUITE: &CStr = c"OfxParameterSuite";
+pub const IMAGE_EFFECT_SUITE: &CStr = c"OfxImageEffectSuite";
+pub const IMAGE_EFFECT_PLUGIN_API: &CStr = c"OfxImageEffectPluginAPI";
+
+// Actions
+pub const ACTION_LOAD: &CStr = c"OfxActionLoad";
+pub const ACTION_DESCRIBE: &CStr = c"OfxActionDescribe";
+pub const ACTION_CREATE_INSTANCE: &CStr = c"OfxActionCreateInstance";
+pub const ACTION_DESTROY_INSTANCE: &CStr = c"OfxActionDestroyInstance";
+pub const ACTION_DESCRIBE_IN_CONTEXT: &CStr = c"OfxImageEffectActionDescribeInContext";
+pub const ACTION_RENDER: &CStr = c"OfxImageEffectActionRender";
+
+// Properties
+pub const PROP_LABEL: &CStr = c"OfxPropLabel";
+pub const PROP_TIME: &CStr = c"OfxPropTime";
+pub const PROP_INSTANCE_DATA: &CStr = c"OfxPropInstanceData";
+pub const PROP_GROUPING: &CStr = c"OfxImageEffectPluginPropGrouping";
+pub const PROP_SUPPORTED_CONTEXTS: &CStr = c"OfxImageEffectPropSupportedContexts";
+pub const PROP_SUPPORTED_PIXEL_DEPTHS: &CStr = c"OfxImageEffectPropSupportedPixelDepths";
+pub const PROP_SUPPORTED_COMPONENTS: &CStr = c"OfxImageEffectPropSupportedComponents";
+pub const PROP_SUPPORTS_TILES: &CStr = c"OfxImageEffectPropSupportsTiles";
+pub const PROP_SUPPORTS_MULTI_RESOLUTION: &CStr = c"OfxImageEffectPropSupportsMultiResolution";
+pub const PROP_RENDER_THREAD_SAFETY: &CStr = c"OfxImageEffectPluginRenderThreadSafety";
+pub const PROP_RENDER_WINDOW: &CStr = c"OfxImageEffectPropRenderWindow";
+pub const PROP_PIXEL_DEPTH: &CStr = c"OfxImageEffectPropPixelDepth";
+pub const PROP_COMPONENTS: &CStr = c"OfxImageEffectPropComponents";
+pub const PROP_IMAGE_DATA: &CStr = c"OfxImagePropData";
+pub const PROP_IMAGE_BOUNDS: &CStr = c"OfxImagePropBounds";
+pub const PROP_IMAGE_ROW_BYTES: &CStr = c"OfxImagePropRowBytes";
+pub const PROP_PARAM_DEFAULT: &CStr = c"OfxParamPropDefault";
+pub const PROP_PARAM_MIN: &CStr = c"OfxParamPropMin";
+pub const PROP_PARAM_MAX: &CStr = c"OfxParamPropMax";
+pub const PROP_PARAM_DISPLAY_MIN: &CStr = c"OfxParamPropDisplayMin";
+pub const PROP_PARAM_DISPLAY_MAX: &CStr = c"OfxParamPropDisplayMax";
+pub const PROP_PARAM_CHOICE_OPTION: &CStr = c"OfxParamPropChoiceOption";
+pub const PROP_PARAM_PARENT: &CStr = c"OfxParamPropParent";
+pub const PROP_PARAM_HINT: &CStr = c"OfxParamPropHint";
+
+// Values
+pub const CONTEXT_FILTER: &CStr = c"OfxImageEffectContextFilter";
+pub const CONTEXT_GENERAL: &CStr = c"OfxImageEffectContextGeneral";
+pub const BIT_DEPTH_BYTE: &CStr = c"OfxBitDepthByte";
+pub const BIT_DEPTH_FLOAT: &CStr = c"OfxBitDepthFloat";
+pub const COMPONENT_RGBA: &CStr = c"OfxImageComponentRGBA";
+pub const COMPONENT_RGB: &CStr = c"OfxImageComponentRGB";
+pub const RENDER_INSTANCE_SAFE: &CStr = c"OfxImageEffectRenderInstanceSafe";
+pub const SOURCE_CLIP: &CStr = c"Source";
+pub const OUTPUT_CLIP: &CStr = c"Output";
+pub const PARAM_TYPE_DOUBLE: &CStr = c"OfxParamTypeDouble";
+pub const PARAM_TYPE_INTEGER: &CStr = c"OfxParamTypeInteger";
+pub const PARAM_TYPE_BOOLEAN: &CStr = c"OfxParamTypeBoolean";
+pub const PARAM_TYPE_CHOICE: &CStr = c"OfxParamTypeChoice";
+pub const PARAM_TYPE_GROUP: &CStr = c"OfxParamTypeGroup";
@@ -0,0 +1,51 @@
+/*
+ * Example plugin: repeats random runs of mosaic rows, like a line buffer
+ * that missed its write enable.
+ *
+ *   cc -shared -fPIC -O2 -Iinclude plugins/row_echo.c -o plugins/librow_echo.so
+ */
+
+#include "ccd_glitch_plugin.h"
+
+#include <string.h>
+
+static uint64_t next(uint64_t *state) {
+    /* splitmix64 */
+    uint64_t z = (*state += 0x9e3779b97f4a7c15ull);
+    z = (z ^ (z >> 30)) * 0xbf58476d1ce4e5b9ull;
+    z = (z ^ (z >> 27)) * 0x94d049bb133111ebull;
+    return z ^ (z >> 31);
+}
+
+static int32_t process(double *data, size_t width, size_t height, size_t channels,
+                       double max_value, double amount, uint64_t seed) {
+    (void)max_value;
+    if (amount <= 0.0 || height < 4) {
+        return 0;
+    }
+    size_t stride = width * channels;
+    uint64_t state = seed;
+    size_t runs = (size_t)(height * amount * 0.01) + 1;
+    for (size_t i = 0; i < runs; i++) {
+        /* Repeat a row pair so each row keeps its CFA colors */
+        size_t src = next(&state) % (height - 2);
+        size_t len = 2 * (1 + next(&state) % 8);
+        for (size_t y = src + 2; y < src + 2 + len && y < height; y++) {
+            size_t from = src + (y - src) % 2;
+            memcpy(data + y * stride, data + from * stride, stride * sizeof(double));
+        }
+    }
+    return 0;
+}
+
+static const ccd_glitch_plugin descriptor = {
+    CCD_GLITCH_PLUGIN_ABI,
+    CCD_GLITCH_STAGE_MOSAIC,
+    "Row Echo",
+    "Repeat random runs of rows, as from a stuck line buffer",
+    process,
+};
+
+const ccd_glitch_plugin *ccd_glitch_plugin_v1(void) {
+    return &descriptor;
+}
@@ -1,31 +1,120 @@
 use eframe::egui;
 use image::DynamicImage;
 
-use crate::ccd::adc::CdsMode;
-use crate::ccd::transfer::ReadoutDirection;
-use crate::ccd::{SensorConfig, SensorPreset};
-use crate::color::bayer::BayerPattern;
-use crate::color::demosaic::DemosaicAlgo;
-use crate::glitch::channel::ChannelSwap;
-use crate::pipeline::{self, PipelineParams};
+use crate::ccd::noise::NoiseSource;
+use crate::ccd::sensor::ShutterMode;
+use crate::ccd::transfer::{ClockPhases, Modulation, ModulationShape, ScheduleMode};
+use crate::ccd::{ReadoutSpeed, SensorConfig, SensorPreset};
+use crate::color::optics::Trajectory;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::color::lut::Lut3d;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::color::palette::Palette;
+use crate::color::palette::PaletteMode;
+use crate::color::spectral::ToneCurves;
+use crate::glitch::plugin::{self, Plugin, PluginSlot};
+use crate::glitch::raw_pack::RawPacking;
+use crate::image_io::FitMode;
+use crate::log_console::LogConsole;
+use crate::pipeline::analysis::{NoiseReport, PtcSweep};
+use crate::pipeline::automation::Automation;
+use crate::pipeline::memory;
+use crate::pipeline::morph;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pipeline::preset::Preset;
+use crate::pipeline::preset::PresetGroup;
+use crate::pipeline::mtf::MtfReport;
+use crate::pipeline::test_patterns::{self, TestPattern};
+use crate::pipeline::three_chip;
+use crate::pipeline::schema::{self, ParamKind};
+use crate::pipeline::{self, ParamValue, PipelineParams, Roi, StageBypass, StageTimings};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::pipeline::ArtifactLayer;
+use crate::session::{self, Session, SessionSource};
+use crate::snapshot_gallery::{self, GalleryAction, Snapshot};
+use crate::spice::validation::Severity;
+
+/// Flat levels in the PTC sweep run from the UI.
+const PTC_STEPS: usize = 16;
 
 pub struct CcdGlitchApp {
     source_image: Option<DynamicImage>,
+    /// Where `source_image` came from, for autosave.
+    session_source: Option<SessionSource>,
+    /// A session left by a run that crashed, until restored or discarded.
+    restore_offer: Option<Session>,
+    /// Set once the window is asked to close, so the last save marks the
+    /// session as ended cleanly.
+    closing: bool,
     preview_texture: Option<egui::TextureHandle>,
+    /// The image last uploaded to `preview_texture`, kept to undo ROI patches.
+    preview_image: Option<std::sync::Arc<egui::ColorImage>>,
     preview_width: usize,
     preview_height: usize,
+    /// RGB8 of the last full-frame render with the current parameters.
+    preview_rgb: Vec<u8>,
+    snapshots: Vec<Snapshot>,
     params: PipelineParams,
     sensor_preset: SensorPreset,
     needs_process: bool,
     auto_process: bool,
+    /// While on, auto-process re-renders only `roi`; Process still renders the full frame.
+    roi_mode: bool,
+    roi: Option<Roi>,
+    /// Sensor-space corner where the current ROI drag started.
+    roi_drag: Option<egui::Pos2>,
+    /// ROI render drawn over the preview, when it cannot be patched in.
+    roi_texture: Option<(Roi, egui::TextureHandle)>,
+    /// ROI whose render is patched into `preview_texture` in place.
+    roi_patch: Option<Roi>,
+    needs_roi_process: bool,
+    /// Params pinned for comparison; with `split_preview` the right half of
+    /// the preview renders with them.
+    pinned_params: Option<PipelineParams>,
+    pinned_spice_cache: Option<crate::spice::SpiceCache>,
+    split_preview: bool,
+    /// Show the render's amplified difference from its clean reference.
+    difference_view: bool,
+    difference_gain: f64,
+    /// Measure each render against its clean reference.
+    show_metrics: bool,
+    metrics: Option<pipeline::Metrics>,
     processing_time_ms: f64,
+    stage_timings: StageTimings,
+    show_timings: bool,
+    show_log: bool,
+    log_console: LogConsole,
+    /// Why the last render was refused or downscaled to fit in memory.
+    memory_warning: Option<String>,
+    test_pattern: TestPattern,
+    noise_level: f64,
+    noise_report: Option<NoiseReport>,
+    ptc_sweep: Option<PtcSweep>,
+    mtf_report: Option<MtfReport>,
+    automation: Option<Automation>,
+    /// Set while recording into `automation`.
+    recording_since: Option<web_time::Instant>,
+    automation_fps: f64,
+    /// Ends of the morph, captured from the parameters or loaded presets.
+    morph_from: Option<PipelineParams>,
+    morph_to: Option<PipelineParams>,
+    morph_t: f64,
+    morph_frames: usize,
+    /// Size and first seed of the next batch of variations.
+    variation_count: usize,
+    variation_seed: u64,
+    /// Parameter group written by preset export.
+    preset_group: PresetGroup,
     #[cfg(target_arch = "wasm32")]
     pending_file: std::sync::Arc<std::sync::Mutex<Option<Vec<u8>>>>,
     spice_cache: Option<crate::spice::SpiceCache>,
+    /// Plugins found in the plugin directory, and the libraries that failed to load.
+    plugins: Vec<std::sync::Arc<Plugin>>,
+    plugin_errors: Vec<String>,
 }
 
 impl CcdGlitchApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         #[cfg(not(target_arch = "wasm32"))]
         let preset = SensorPreset::Kaf6303;
         #[cfg(target_arch = "wasm32")]
@@ -34,36 +123,79 @@ impl CcdGlitchApp {
         let config = preset.config();
         let mut params = PipelineParams::default();
         apply_sensor_config(&mut params, &config);
+        let (plugins, plugin_errors) = plugin::discover(&plugin::plugin_dir());
 
         Self {
             source_image: None,
+            session_source: None,
+            restore_offer: cc.storage.and_then(session::crashed_session),
+            closing: false,
             preview_texture: None,
+            preview_image: None,
             preview_width: 0,
             preview_height: 0,
+            preview_rgb: Vec::new(),
+            snapshots: Vec::new(),
             params,
             sensor_preset: preset,
             needs_process: false,
             auto_process: false,
+            roi_mode: false,
+            roi: None,
+            roi_drag: None,
+            roi_texture: None,
+            roi_patch: None,
+            needs_roi_process: false,
+            pinned_params: None,
+            pinned_spice_cache: None,
+            split_preview: false,
+            difference_view: false,
+            difference_gain: 4.0,
+            show_metrics: false,
+            metrics: None,
             processing_time_ms: 0.0,
+            stage_timings: StageTimings::default(),
+            show_timings: false,
+            show_log: false,
+            log_console: LogConsole::default(),
+            memory_warning: None,
+            test_pattern: TestPattern::SiemensStar,
+            noise_level: 0.5,
+            noise_report: None,
+            ptc_sweep: None,
+            mtf_report: None,
+            automation: None,
+            recording_since: None,
+            automation_fps: 25.0,
+            morph_from: None,
+            morph_to: None,
+            morph_t: 0.0,
+            morph_frames: 50,
+            variation_count: 9,
+            variation_seed: 1,
+            preset_group: PresetGroup::All,
             #[cfg(target_arch = "wasm32")]
             pending_file: std::sync::Arc::new(std::sync::Mutex::new(None)),
             spice_cache: None,
+            plugins,
+            plugin_errors,
         }
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     fn open_image(&mut self) {
         if let Some(path) = rfd::FileDialog::new()
-            .add_filter("Images", &["png", "jpg", "jpeg", "tiff", "tif", "bmp", "webp"])
+            .add_filter("Images", &["png", "jpg", "jpeg", "tiff", "tif", "bmp", "webp", "exr"])
             .pick_file()
         {
             match crate::image_io::load_image(&path) {
                 Ok(img) => {
                     self.source_image = Some(img);
+                    self.session_source = Some(SessionSource::Path(path));
                     self.needs_process = true;
                 }
                 Err(e) => {
-                    eprintln!("Error loading image: {e}");
+                    log::error!("Error loading image: {e}");
                 }
             }
         }
@@ -95,7 +227,7 @@ impl CcdGlitchApp {
             Err(_) => return,
         };
         input.set_type("file");
-        input.set_accept("image/png,image/jpeg,image/bmp,image/webp");
+        input.set_accept("image/png,image/jpeg,image/bmp,image/webp,image/tiff,.exr");
         let _ = input.style().set_property("display", "none");
         let _ = body.append_child(&input);
 
@@ -157,15 +289,51 @@ impl CcdGlitchApp {
                     &self.params,
                     &self.spice_cache,
                 );
-                let img = image::RgbImage::from_raw(w as u32, h as u32, bytes)
-                    .expect("Failed to create image buffer");
-                if let Err(e) = crate::image_io::save_image(&img, &path) {
-                    eprintln!("Error saving image: {e}");
+                let mismatch = || "Rendered frame does not match its size".to_string();
+                let result = match pipeline::process_alpha(source, &self.params) {
+                    Some(alpha) => pipeline::merge_alpha(&bytes, &alpha).and_then(|rgba| {
+                        let img = image::RgbaImage::from_raw(w as u32, h as u32, rgba)
+                            .ok_or_else(mismatch)?;
+                        crate::image_io::save_image_rgba(&img, &path)
+                    }),
+                    None => image::RgbImage::from_raw(w as u32, h as u32, bytes)
+                        .ok_or_else(mismatch)
+                        .and_then(|img| crate::image_io::save_image(&img, &path)),
+                };
+                if let Err(e) = result {
+                    log::error!("Error saving image: {e}");
                 }
             }
         }
     }
 
+    /// Capture the current render's artifacts against its clean reference
+    /// and save them as a noise print.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_noise_print(&self) {
+        let Some(source) = &self.source_image else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("PNG", &["png"])
+            .set_file_name("noise_print.png")
+            .save_file()
+        else {
+            return;
+        };
+        let result = ArtifactLayer::capture(
+            &self.preview_rgb,
+            self.preview_width,
+            self.preview_height,
+            source,
+            &self.params,
+        )
+        .and_then(|layer| layer.save(&path));
+        if let Err(e) = result {
+            log::error!("Error saving noise print: {e}");
+        }
+    }
+
     #[cfg(target_arch = "wasm32")]
     fn save_result(&self) {
         if self.preview_texture.is_none() {
@@ -177,7 +345,19 @@ impl CcdGlitchApp {
                 &self.params,
                 &self.spice_cache,
             );
-            if let Some(img) = image::RgbImage::from_raw(w as u32, h as u32, bytes) {
+            let img = match pipeline::process_alpha(source, &self.params) {
+                Some(alpha) => match pipeline::merge_alpha(&bytes, &alpha) {
+                    Ok(rgba) => image::RgbaImage::from_raw(w as u32, h as u32, rgba)
+                        .map(DynamicImage::ImageRgba8),
+                    Err(e) => {
+                        log::error!("Error saving image: {e}");
+                        None
+                    }
+                },
+                None => image::RgbImage::from_raw(w as u32, h as u32, bytes)
+                    .map(DynamicImage::ImageRgb8),
+            };
+            if let Some(img) = img {
                 let mut buf = std::io::Cursor::new(Vec::new());
                 if img.write_to(&mut buf, image::ImageFormat::Png).is_ok() {
                     download_bytes(&buf.into_inner(), "ccd_glitch.png", "image/png");
@@ -186,43 +366,724 @@ impl CcdGlitchApp {
         }
     }
 
+    /// The parameters to render with: the current ones, or a copy with the
+    /// sensor downscaled to fit in memory when the render would not. `None`
+    /// when even that cannot help and the render is refused.
+    fn fit_in_memory(&mut self) -> Option<PipelineParams> {
+        let source = self.source_image.as_ref()?;
+        let estimate = memory::estimate(&self.params, Some(source), &self.spice_cache);
+        let budget = memory::budget();
+        self.memory_warning = None;
+        let Err(message) = memory::check(&self.params, &estimate, budget) else {
+            return Some(self.params.clone());
+        };
+        match memory::fit_sensor(&self.params, &estimate, budget) {
+            Some((w, h)) => {
+                self.memory_warning = Some(format!("{message}. Downscaled to {w}x{h}."));
+                Some(PipelineParams {
+                    sensor_width: w,
+                    sensor_height: h,
+                    ..self.params.clone()
+                })
+            }
+            None => {
+                self.memory_warning = Some(format!("{message}. Render refused."));
+                None
+            }
+        }
+    }
+
     fn process_image(&mut self, ctx: &egui::Context) {
+        let Some(params) = self.fit_in_memory() else {
+            return;
+        };
         if let Some(source) = &self.source_image {
             // Run SPICE simulation if needed
             {
                 use crate::spice::SpiceMode;
-                if self.params.spice.mode != SpiceMode::Off {
+                if params.spice.mode != SpiceMode::Off {
                     crate::spice::simulate_or_cache(
-                        &self.params.spice,
-                        self.params.full_well,
+                        &params.spice,
+                        &params.spice_sensor(),
                         &mut self.spice_cache,
                     );
                 }
             }
 
             let start = web_time::Instant::now();
-            let (w, h, bytes) = pipeline::process(
-                source,
-                &self.params,
-                &self.spice_cache,
-            );
+            let (w, h, bytes, timings) =
+                pipeline::process_timed(source, &params, &self.spice_cache);
             self.processing_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            log::debug!("Rendered {w}x{h} in {:.1} ms", self.processing_time_ms);
+            if timings.issues != self.stage_timings.issues {
+                for issue in &timings.issues {
+                    log::warn!("Invalid stage output: {issue}");
+                }
+            }
+            self.stage_timings = timings;
             self.preview_width = w;
             self.preview_height = h;
+            self.preview_rgb.clone_from(&bytes);
+
+            let mut bytes = bytes;
+            let reference = (self.difference_view || self.show_metrics).then(|| {
+                let (rw, rh, reference) = pipeline::process_reference(source, &params);
+                if (rw, rh) != (w, h) {
+                    log::warn!("Reference rendered at {rw}x{rh}, not {w}x{h}");
+                }
+                ((rw, rh) == (w, h)).then_some(reference)
+            });
+            self.metrics = match &reference {
+                Some(Some(reference)) if self.show_metrics => {
+                    Some(pipeline::metrics(&bytes, reference, w, h))
+                }
+                _ => None,
+            };
+            if let Some(Some(reference)) = reference.as_ref().filter(|_| self.difference_view) {
+                let gain = self.difference_gain;
+                if let Err(e) = pipeline::difference_view(&mut bytes, reference, gain) {
+                    log::warn!("{e}");
+                }
+            }
+            if self.split_preview
+                && let Some(pinned) = &self.pinned_params
+            {
+                // Downscaled with the current parameters when it matched them
+                let pinned_size = (pinned.sensor_width, pinned.sensor_height);
+                let (sensor_width, sensor_height) =
+                    if pinned_size == (self.params.sensor_width, self.params.sensor_height) {
+                        (params.sensor_width, params.sensor_height)
+                    } else {
+                        pinned_size
+                    };
+                let pinned = &PipelineParams { sensor_width, sensor_height, ..pinned.clone() };
+                if pinned.spice.mode != crate::spice::SpiceMode::Off {
+                    crate::spice::simulate_or_cache(
+                        &pinned.spice,
+                        &pinned.spice_sensor(),
+                        &mut self.pinned_spice_cache,
+                    );
+                }
+                let (pw, ph, mut pinned_bytes) =
+                    pipeline::process(source, pinned, &self.pinned_spice_cache);
+                if let Some(Some(reference)) = reference.as_ref().filter(|_| self.difference_view) {
+                    let gain = self.difference_gain;
+                    if let Err(e) = pipeline::difference_view(&mut pinned_bytes, reference, gain) {
+                        log::warn!("Pinned render: {e}");
+                    }
+                }
+                if (pw, ph) == (w, h) {
+                    pipeline::split_composite(&mut bytes, &pinned_bytes, w, w / 2);
+                }
+            }
+
+            let rgba = pipeline::process_alpha(source, &params)
+                .map(|alpha| pipeline::merge_alpha(&bytes, &alpha))
+                .transpose()
+                .unwrap_or_else(|e| {
+                    // Show the frame opaque rather than not at all
+                    log::error!("{e}");
+                    None
+                });
+            let color_image = match rgba {
+                Some(rgba) => egui::ColorImage::from_rgba_unmultiplied([w, h], &rgba),
+                None => egui::ColorImage::from_rgb([w, h], &bytes),
+            };
+            let color_image = std::sync::Arc::new(color_image);
+            // Upload into the existing texture rather than allocating a new one
+            // per render
+            match &mut self.preview_texture {
+                Some(texture) => texture.set(color_image.clone(), egui::TextureOptions::LINEAR),
+                None => {
+                    self.preview_texture = Some(ctx.load_texture(
+                        "preview",
+                        color_image.clone(),
+                        egui::TextureOptions::LINEAR,
+                    ));
+                }
+            }
+            self.preview_image = Some(color_image);
+            self.roi_patch = None;
+        }
+    }
+
+    /// Re-render just the ROI into an overlay on the last full-frame preview.
+    /// The difference and split views and the alpha merge work on the whole
+    /// frame, so while any is on this renders the full frame instead.
+    fn process_roi(&mut self, ctx: &egui::Context) {
+        let (Some(source), Some(roi)) = (&self.source_image, self.roi) else {
+            return;
+        };
+        let split = self.split_preview && self.pinned_params.is_some();
+        let alpha = source.color().has_alpha()
+            && self.params.alpha_mode != pipeline::AlphaMode::Discard;
+        if self.difference_view || split || alpha {
+            self.process_image(ctx);
+            self.roi_texture = None;
+            return;
+        }
+        if self.params.spice.mode != crate::spice::SpiceMode::Off {
+            crate::spice::simulate_or_cache(
+                &self.params.spice,
+                &self.params.spice_sensor(),
+                &mut self.spice_cache,
+            );
+        }
+
+        let roi = roi.fit(self.params.sensor_width, self.params.sensor_height);
+        let start = web_time::Instant::now();
+        let (w, h, bytes, timings) =
+            pipeline::process_roi(source, &self.params, &self.spice_cache, roi);
+        self.processing_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        self.stage_timings = timings;
+
+        let image = egui::ColorImage::from_rgb([w, h], &bytes);
+        // With one preview pixel per photosite the render replaces its region
+        // of the preview texture, and only that region is uploaded
+        let sensor_size = (self.params.sensor_width as usize, self.params.sensor_height as usize);
+        let in_place = (w, h) == (roi.width as usize, roi.height as usize)
+            && (self.preview_width, self.preview_height) == sensor_size;
+        if self.roi_patch.is_some_and(|patch| !in_place || patch != roi) {
+            self.clear_roi();
+        }
+        if in_place && let Some(texture) = &mut self.preview_texture {
+            let pos = [roi.x as usize, roi.y as usize];
+            texture.set_partial(pos, image, egui::TextureOptions::LINEAR);
+            self.roi_texture = None;
+            self.roi_patch = Some(roi);
+            return;
+        }
+        match &mut self.roi_texture {
+            Some((shown, texture)) => {
+                texture.set(image, egui::TextureOptions::LINEAR);
+                *shown = roi;
+            }
+            None => {
+                let texture = ctx.load_texture("roi_preview", image, egui::TextureOptions::LINEAR);
+                self.roi_texture = Some((roi, texture));
+            }
+        }
+    }
+
+    /// Drop the ROI render, restoring the preview pixels a patch covered.
+    fn clear_roi(&mut self) {
+        self.roi_texture = None;
+        let (Some(roi), Some(texture), Some(image)) =
+            (self.roi_patch.take(), &mut self.preview_texture, &self.preview_image)
+        else {
+            return;
+        };
+        let region = egui::Rect::from_min_size(
+            egui::pos2(roi.x as f32, roi.y as f32),
+            egui::vec2(roi.width as f32, roi.height as f32),
+        );
+        let pos = [roi.x as usize, roi.y as usize];
+        texture.set_partial(pos, image.region(&region, None), egui::TextureOptions::LINEAR);
+    }
+
+    /// ROI selection on the preview image: drag to draw a new region. The
+    /// latest ROI render is drawn over the full-frame preview at its location.
+    fn ui_roi_overlay(&mut self, ui: &egui::Ui, response: &egui::Response, scale: f32) {
+        let rect = response.rect;
+        // Preview pixels per photosite horizontally (anamorphic output)
+        let x_stretch = self.preview_width as f32 / self.params.sensor_width.max(1) as f32;
+        let scale_xy = egui::vec2(scale * x_stretch, scale);
+        let to_sensor = |pos: egui::Pos2| {
+            let p = (pos - rect.min) / scale_xy;
+            egui::pos2(
+                p.x.clamp(0.0, self.params.sensor_width as f32),
+                p.y.clamp(0.0, self.preview_height as f32),
+            )
+        };
+        let to_screen = |roi: Roi| {
+            egui::Rect::from_min_size(
+                rect.min + egui::vec2(roi.x as f32, roi.y as f32) * scale_xy,
+                egui::vec2(roi.width as f32, roi.height as f32) * scale_xy,
+            )
+        };
+
+        if response.drag_started()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            self.roi_drag = Some(to_sensor(pos));
+        }
+        let dragged = match (self.roi_drag, response.interact_pointer_pos()) {
+            (Some(start), Some(pos)) => {
+                let area = egui::Rect::from_two_pos(start, to_sensor(pos));
+                Some(Roi {
+                    x: area.min.x as u32,
+                    y: area.min.y as u32,
+                    width: area.width() as u32,
+                    height: area.height() as u32,
+                })
+            }
+            _ => None,
+        };
+        if response.drag_stopped() {
+            self.roi_drag = None;
+            if let Some(roi) = dragged.filter(|r| r.width >= 8 && r.height >= 8) {
+                self.roi = Some(roi);
+                self.clear_roi();
+                self.needs_roi_process = true;
+            }
+        }
+
+        let painter = ui.painter_at(rect);
+        if let Some((roi, texture)) = &self.roi_texture {
+            let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+            painter.image(texture.id(), to_screen(*roi), uv, egui::Color32::WHITE);
+        }
+        let outline = egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 200, 0));
+        let shown = if self.roi_drag.is_some() { dragged } else { self.roi };
+        if let Some(roi) = shown {
+            painter.rect_stroke(to_screen(roi), 0.0, outline, egui::StrokeKind::Outside);
+        }
+    }
 
-            let color_image = egui::ColorImage::from_rgb([w, h], &bytes);
-            self.preview_texture = Some(ctx.load_texture(
-                "preview",
-                color_image,
-                egui::TextureOptions::LINEAR,
-            ));
+    /// Make sure the SPICE cache matches the current settings before an
+    /// analysis run (analysis bypasses the normal processing path).
+    fn prepare_spice(&mut self) {
+        if self.params.spice.mode != crate::spice::SpiceMode::Off {
+            crate::spice::simulate_or_cache(
+                &self.params.spice,
+                &self.params.spice_sensor(),
+                &mut self.spice_cache,
+            );
         }
     }
 
+    /// Flat-field noise characterization of the current settings.
+    fn ui_noise_analysis(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Noise Analysis")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.noise_level, 0.01..=1.0)
+                        .text("Flat Level (x full well)"),
+                );
+                if ui.button("Run on Flat Field").clicked() {
+                    self.prepare_spice();
+                    self.noise_report = Some(pipeline::analysis::noise_report(
+                        &self.params,
+                        &self.spice_cache,
+                        self.noise_level,
+                    ));
+                }
+
+                if let Some(report) = &self.noise_report {
+                    Self::ui_noise_report(ui, report);
+                }
+
+                ui.separator();
+                if ui.button("Run PTC Sweep").clicked() {
+                    self.prepare_spice();
+                    self.ptc_sweep = Some(pipeline::analysis::ptc_sweep(
+                        &self.params,
+                        &self.spice_cache,
+                        PTC_STEPS,
+                    ));
+                }
+                if let Some(sweep) = &self.ptc_sweep {
+                    Self::ui_ptc_sweep(ui, sweep);
+                }
+            });
+    }
+
+    fn ui_noise_report(ui: &mut egui::Ui, report: &NoiseReport) {
+        egui::Grid::new("noise_stages").striped(true).show(ui, |ui| {
+            for stage in &report.stages {
+                ui.label(egui::RichText::new(stage.stage).small().monospace());
+                ui.label(
+                    egui::RichText::new(format!("{:.3} {}", stage.sigma, stage.units.name()))
+                        .small()
+                        .monospace(),
+                );
+                ui.end_row();
+            }
+        });
+        ui.label(
+            egui::RichText::new(format!(
+                "Gain {:.3} e-/ADU\nRead noise {:.2} e-\nFull well {:.0} e-",
+                report.ptc.gain_e_per_adu, report.ptc.read_noise_e, report.ptc.full_well_e
+            ))
+            .small()
+            .monospace(),
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.horizontal(|ui| {
+            if ui.button("Save CSV...").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("CSV", &["csv"])
+                    .set_file_name("noise_report.csv")
+                    .save_file()
+                && let Err(e) = std::fs::write(&path, report.to_csv())
+            {
+                log::error!("Error saving report: {e}");
+            }
+            if ui.button("Save Plot...").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("PNG", &["png"])
+                    .set_file_name("noise_spectra.png")
+                    .save_file()
+                && let Err(e) = crate::image_io::save_image(&report.plot(), &path)
+            {
+                log::error!("Error saving plot: {e}");
+            }
+        });
+    }
+
+    /// Fitted vs. configured sensor specs; mismatches over 10% are highlighted.
+    fn ui_ptc_sweep(ui: &mut egui::Ui, sweep: &PtcSweep) {
+        egui::Grid::new("ptc_specs").striped(true).show(ui, |ui| {
+            ui.label("");
+            ui.label(egui::RichText::new("Measured").small());
+            ui.label(egui::RichText::new("Expected").small());
+            ui.end_row();
+            for (name, measured, expected) in sweep.spec_rows() {
+                let off = expected > 0.0 && ((measured - expected) / expected).abs() > 0.1;
+                ui.label(egui::RichText::new(name).small().monospace());
+                let value = egui::RichText::new(format!("{measured:.3}")).small().monospace();
+                let value = if off {
+                    value.color(egui::Color32::from_rgb(255, 140, 60))
+                } else {
+                    value
+                };
+                ui.label(value);
+                ui.label(egui::RichText::new(format!("{expected:.3}")).small().monospace());
+                ui.end_row();
+            }
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.horizontal(|ui| {
+            if ui.button("Save PTC CSV...").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("CSV", &["csv"])
+                    .set_file_name("ptc.csv")
+                    .save_file()
+                && let Err(e) = std::fs::write(&path, sweep.to_csv())
+            {
+                log::error!("Error saving PTC: {e}");
+            }
+            if ui.button("Save PTC Plot...").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("PNG", &["png"])
+                    .set_file_name("ptc.png")
+                    .save_file()
+                && let Err(e) = crate::image_io::save_image(&sweep.plot(), &path)
+            {
+                log::error!("Error saving plot: {e}");
+            }
+        });
+    }
+
+    /// Replace the source with the selected test pattern at the sensor size.
+    /// Slanted-edge sharpness measurement of the current settings.
+    fn ui_mtf(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Sharpness (MTF)")
+            .default_open(false)
+            .show(ui, |ui| {
+                if ui.button("Measure Slanted Edge").clicked() {
+                    self.prepare_spice();
+                    match pipeline::mtf::measure_mtf(&self.params, &self.spice_cache) {
+                        Ok(report) => self.mtf_report = Some(report),
+                        Err(e) => log::error!("MTF measurement failed: {e}"),
+                    }
+                }
+                let Some(report) = &self.mtf_report else {
+                    return;
+                };
+
+                draw_mtf_curves(ui, report);
+                egui::Grid::new("mtf_values").striped(true).show(ui, |ui| {
+                    ui.label("");
+                    ui.label(egui::RichText::new("MTF50 cy/px").small());
+                    ui.label(egui::RichText::new("@ Nyquist").small());
+                    ui.end_row();
+                    for curve in &report.curves {
+                        let mtf50 = curve.mtf50.map_or("-".to_string(), |v| format!("{v:.3}"));
+                        ui.label(egui::RichText::new(curve.channel).small().monospace());
+                        ui.label(egui::RichText::new(mtf50).small().monospace());
+                        ui.label(
+                            egui::RichText::new(format!("{:.3}", curve.at_nyquist))
+                                .small()
+                                .monospace(),
+                        );
+                        ui.end_row();
+                    }
+                });
+
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.horizontal(|ui| {
+                    if ui.button("Save CSV...").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name("mtf.csv")
+                            .save_file()
+                        && let Err(e) = std::fs::write(&path, report.to_csv())
+                    {
+                        log::error!("Error saving MTF: {e}");
+                    }
+                    if ui.button("Save Plot...").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("PNG", &["png"])
+                            .set_file_name("mtf.png")
+                            .save_file()
+                        && let Err(e) = crate::image_io::save_image(&report.plot(), &path)
+                    {
+                        log::error!("Error saving plot: {e}");
+                    }
+                });
+            });
+    }
+
+    /// Record control moves with timestamps, then export them replayed over
+    /// a frame sequence.
+    fn ui_automation(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Automation")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if self.recording_since.is_some() {
+                        if ui.button("Stop").clicked() {
+                            self.recording_since = None;
+                        }
+                    } else if ui.button("Record").on_hover_text(
+                        "Capture every control change with its time until Stop",
+                    ).clicked() {
+                        self.automation = Some(Automation::start(&self.params));
+                        self.recording_since = Some(web_time::Instant::now());
+                    }
+                    if self.automation.is_some()
+                        && self.recording_since.is_none()
+                        && ui.button("Clear").clicked()
+                    {
+                        self.automation = None;
+                    }
+                });
+                if let Some(automation) = &self.automation {
+                    ui.label(format!(
+                        "{} changes over {:.1} s",
+                        automation.events.len(),
+                        automation.duration(),
+                    ));
+                }
+                ui.add(egui::Slider::new(&mut self.automation_fps, 1.0..=60.0).text("Frame Rate"));
+                #[cfg(not(target_arch = "wasm32"))]
+                if self.recording_since.is_none()
+                    && let (Some(automation), Some(source)) = (&self.automation, &self.source_image)
+                    && ui.button("Export Frames...").on_hover_text(
+                        "Replay the recording over a PNG sequence, starting from the \
+                         parameters it was recorded from",
+                    ).clicked()
+                    && let Some(dir) = rfd::FileDialog::new().pick_folder()
+                {
+                    let result = automation.render(
+                        source,
+                        self.automation_fps,
+                        &mut self.spice_cache,
+                        |index, w, h, bytes| save_sequence_frame(&dir, index, w, h, bytes),
+                    );
+                    if let Err(e) = result {
+                        log::error!("Error exporting automation: {e}");
+                    }
+                }
+            });
+    }
+
+    /// Morph every parameter between two captured sets, live with a slider
+    /// or exported as a transition.
+    fn ui_morph(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        egui::CollapsingHeader::new("Morph")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Set A")
+                        .on_hover_text("Morph from the current parameters")
+                        .clicked()
+                    {
+                        self.morph_from = Some(self.params.clone());
+                    }
+                    if ui
+                        .button("Set B")
+                        .on_hover_text("Morph to the current parameters")
+                        .clicked()
+                    {
+                        self.morph_to = Some(self.params.clone());
+                    }
+                });
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.horizontal(|ui| {
+                    let ends = [
+                        ("Load A...", &mut self.morph_from),
+                        ("Load B...", &mut self.morph_to),
+                    ];
+                    for (label, end) in ends {
+                        if ui
+                            .button(label)
+                            .on_hover_text("Layer a preset file onto the current parameters")
+                            .clicked()
+                            && let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Preset", &["preset"])
+                                .pick_file()
+                        {
+                            let mut params = self.params.clone();
+                            match Preset::load(&path).and_then(|p| p.apply(&mut params)) {
+                                Ok(()) => *end = Some(params),
+                                Err(e) => log::error!("Error loading preset: {e}"),
+                            }
+                        }
+                    }
+                });
+                let (Some(from), Some(to)) = (&self.morph_from, &self.morph_to) else {
+                    ui.label("Set both ends to morph");
+                    return;
+                };
+                ui.label(format!("{} parameters differ", to.diff(from).len()));
+                if ui
+                    .add(egui::Slider::new(&mut self.morph_t, 0.0..=1.0).text("A → B"))
+                    .changed()
+                {
+                    self.params = morph::interpolate(from, to, self.morph_t);
+                    changed = true;
+                }
+                ui.add(egui::Slider::new(&mut self.morph_frames, 2..=500).text("Frames"));
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(source) = &self.source_image
+                    && ui
+                        .button("Export Frames...")
+                        .on_hover_text("Render the morph from A to B as a PNG sequence")
+                        .clicked()
+                    && let Some(dir) = rfd::FileDialog::new().pick_folder()
+                {
+                    let result = morph::render(
+                        source,
+                        from,
+                        to,
+                        self.morph_frames,
+                        &mut self.spice_cache,
+                        |index, w, h, bytes| save_sequence_frame(&dir, index, w, h, bytes),
+                    );
+                    if let Err(e) = result {
+                        log::error!("Error exporting morph: {e}");
+                    }
+                }
+            });
+        changed
+    }
+
+    /// Export the current parameters rendered under a run of seeds, plus a
+    /// contact sheet of them, to pick the best roll from.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn ui_variations(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Variations")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.add(egui::Slider::new(&mut self.variation_count, 1..=64).text("Count"));
+                ui.horizontal(|ui| {
+                    ui.label("First seed");
+                    ui.add(egui::DragValue::new(&mut self.variation_seed));
+                });
+                if let Some(source) = &self.source_image
+                    && ui
+                        .button("Export Variations...")
+                        .on_hover_text(
+                            "Render each seed to seed_<N>.png plus sheet.png; \
+                             the next batch continues from the last seed",
+                        )
+                        .clicked()
+                    && let Some(dir) = rfd::FileDialog::new().pick_folder()
+                {
+                    let result = pipeline::variations::render(
+                        source,
+                        &self.params,
+                        self.variation_count,
+                        self.variation_seed,
+                        256,
+                        &self.spice_cache,
+                        |_, seed, img| {
+                            crate::image_io::save_image(img, &dir.join(format!("seed_{seed}.png")))
+                        },
+                    )
+                    .and_then(|sheet| crate::image_io::save_image(&sheet, &dir.join("sheet.png")));
+                    match result {
+                        Ok(()) => self.variation_seed += self.variation_count as u64,
+                        Err(e) => log::error!("Error exporting variations: {e}"),
+                    }
+                }
+            });
+    }
+
+    /// Export the non-default parameters of one group as a preset file, or
+    /// layer preset files onto the current parameters.
+    fn ui_presets(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Presets")
+            .default_open(false)
+            .show(ui, |ui| {
+                egui::ComboBox::from_label("Group")
+                    .selected_text(self.preset_group.name())
+                    .show_ui(ui, |ui| {
+                        for &group in PresetGroup::ALL {
+                            ui.selectable_value(&mut self.preset_group, group, group.name());
+                        }
+                    });
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.horizontal(|ui| {
+                    if ui.button("Export Preset...").on_hover_text(
+                        "Save only this group's parameters that differ from the defaults",
+                    ).clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Preset", &["preset"])
+                            .set_file_name(format!("{}.preset", self.preset_group.name()))
+                            .save_file()
+                    {
+                        let name = path.file_stem().map_or(String::new(), |s| {
+                            s.to_string_lossy().into_owned()
+                        });
+                        let preset = Preset::from_params(&name, &self.params, self.preset_group);
+                        if let Err(e) = preset.save(&path) {
+                            log::error!("Error saving preset: {e}");
+                        }
+                        for change in Preset::not_exportable(&self.params) {
+                            log::warn!("Preset {name} leaves out {change}");
+                        }
+                    }
+                    if ui.button("Load Presets...").on_hover_text(
+                        "Layer preset files onto the current parameters, later files winning",
+                    ).clicked()
+                        && let Some(paths) = rfd::FileDialog::new()
+                            .add_filter("Preset", &["preset"])
+                            .pick_files()
+                    {
+                        for path in paths {
+                            match Preset::load(&path).and_then(|p| p.apply(&mut self.params)) {
+                                Ok(()) => self.needs_process = true,
+                                Err(e) => log::error!("Error loading preset: {e}"),
+                            }
+                        }
+                    }
+                });
+            });
+    }
+
+    fn load_test_pattern(&mut self) {
+        self.source_image = Some(test_patterns::generate(
+            self.test_pattern,
+            self.params.sensor_width,
+            self.params.sensor_height,
+        ));
+        self.session_source = Some(SessionSource::Pattern(self.test_pattern));
+        self.needs_process = true;
+    }
+
     fn load_image_from_bytes(&mut self, bytes: &[u8]) {
         match image::load_from_memory(bytes) {
             Ok(img) => {
                 self.source_image = Some(img);
+                self.session_source = Some(SessionSource::Bytes(bytes.to_vec()));
                 self.needs_process = true;
             }
             Err(e) => {
@@ -230,19 +1091,139 @@ impl CcdGlitchApp {
             }
         }
     }
+
+    /// Take up the parameters and source of a crashed session.
+    fn restore_session(&mut self, session: &Session) {
+        let params = match session.params() {
+            Ok(params) => params,
+            Err(e) => {
+                log::error!("Failed to restore session: {e}");
+                return;
+            }
+        };
+        match session.load_source(&params) {
+            Ok(image) => {
+                self.source_image = image;
+                self.session_source = session.source.clone();
+            }
+            // Keep the parameters even when the image has moved
+            Err(e) => log::warn!("Failed to restore session image: {e}"),
+        }
+        if let Some(preset) = session.sensor_preset {
+            self.sensor_preset = preset;
+        }
+        self.params = params;
+        self.spice_cache = None;
+        self.needs_process = true;
+    }
+
+    fn ui_restore_offer(&mut self, ctx: &egui::Context) {
+        let Some(offer) = &self.restore_offer else {
+            return;
+        };
+        let mut choice = None;
+        egui::Window::new("Restore Session")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("The last session did not close normally.");
+                ui.label(egui::RichText::new(offer.summary()).small());
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+        if let Some(restore) = choice {
+            let offer = self.restore_offer.take();
+            if restore && let Some(offer) = offer {
+                self.restore_session(&offer);
+            }
+        }
+    }
+}
+
+/// Divider and captions for the split preview.
+fn draw_split_labels(ui: &egui::Ui, rect: egui::Rect) {
+    let painter = ui.painter_at(rect);
+    let x = rect.center().x;
+    painter.line_segment(
+        [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+        (1.0, egui::Color32::from_rgb(255, 200, 0)),
+    );
+    let font = egui::FontId::proportional(12.0);
+    let color = egui::Color32::from_rgb(255, 200, 0);
+    painter.text(
+        egui::pos2(x - 6.0, rect.top() + 6.0),
+        egui::Align2::RIGHT_TOP,
+        "Current",
+        font.clone(),
+        color,
+    );
+    painter.text(egui::pos2(x + 6.0, rect.top() + 6.0), egui::Align2::LEFT_TOP, "Pinned", font, color);
+}
+
+/// MTF curves from DC to 1 cycle/pixel, with Nyquist and 50% guides.
+fn draw_mtf_curves(ui: &mut egui::Ui, report: &MtfReport) {
+    const COLORS: [egui::Color32; 4] = [
+        egui::Color32::from_rgb(230, 230, 230),
+        egui::Color32::from_rgb(255, 80, 80),
+        egui::Color32::from_rgb(80, 220, 80),
+        egui::Color32::from_rgb(90, 140, 255),
+    ];
+    let guide = egui::Color32::from_rgb(60, 60, 75);
+    let width = ui.available_width().min(280.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(width, 110.0), egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, egui::Color32::from_rgb(6, 8, 16));
+
+    let max_f = report.frequencies.last().copied().unwrap_or(1.0).max(f64::EPSILON);
+    let to_screen = |f: f64, m: f64| {
+        egui::pos2(
+            rect.left() + (f / max_f) as f32 * rect.width(),
+            rect.bottom() - (m.clamp(0.0, 1.1) / 1.1) as f32 * rect.height(),
+        )
+    };
+    painter.line_segment([to_screen(0.5, 0.0), to_screen(0.5, 1.1)], (1.0, guide));
+    painter.line_segment([to_screen(0.0, 0.5), to_screen(max_f, 0.5)], (1.0, guide));
+    for (curve, color) in report.curves.iter().zip(COLORS).rev() {
+        let points: Vec<egui::Pos2> = report
+            .frequencies
+            .iter()
+            .zip(&curve.mtf)
+            .map(|(&f, &m)| to_screen(f, m))
+            .collect();
+        painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, color)));
+    }
 }
 
 fn apply_sensor_config(params: &mut PipelineParams, config: &SensorConfig) {
     params.sensor_width = config.width;
     params.sensor_height = config.height;
-    params.full_well = if params.use_abg {
+    params.pixel_aspect = config.pixel_size_um.0 / config.pixel_size_um.1;
+    params.v_phases = ClockPhases::from_count(config.v_phases);
+    params.full_well = rated_full_well(params, config);
+    params.conversion_gain = config.gain_uv_per_e;
+    params.fast_readout = config.fast_readout;
+    params.read_noise = 0.0;
+    params.v_cte = config.cte_vertical;
+    params.h_cte = config.cte_horizontal;
+}
+
+/// Preset full well for the current ABG setting, scaled by well capacity
+/// when the clock phase count differs from the sensor's own.
+fn rated_full_well(params: &PipelineParams, config: &SensorConfig) -> f64 {
+    let rated = if params.use_abg {
         config.full_well_abg
     } else {
         config.full_well_no_abg
     };
-    params.read_noise = 0.0;
-    params.v_cte = config.cte_vertical;
-    params.h_cte = config.cte_horizontal;
+    let native = ClockPhases::from_count(config.v_phases);
+    rated * params.v_phases.well_fraction() / native.well_fraction()
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -283,6 +1264,11 @@ fn download_bytes(bytes: &[u8], filename: &str, mime: &str) {
 
 impl eframe::App for CcdGlitchApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if ctx.input(|i| i.viewport().close_requested()) {
+            self.closing = true;
+        }
+        self.ui_restore_offer(ctx);
+
         // Check for pending file from WASM file dialog
         #[cfg(target_arch = "wasm32")]
         {
@@ -304,6 +1290,7 @@ impl eframe::App for CcdGlitchApp {
             if let Some(path) = &file.path {
                 if let Ok(img) = crate::image_io::load_image(path) {
                     self.source_image = Some(img);
+                    self.session_source = Some(SessionSource::Path(path.clone()));
                     self.needs_process = true;
                 }
             }
@@ -325,9 +1312,33 @@ impl eframe::App for CcdGlitchApp {
                     );
                 }
 
+                egui::ComboBox::from_id_salt("test_pattern")
+                    .selected_text(format!("Pattern: {}", self.test_pattern.name()))
+                    .show_ui(ui, |ui| {
+                        for &pattern in TestPattern::ALL {
+                            if ui
+                                .selectable_value(&mut self.test_pattern, pattern, pattern.name())
+                                .clicked()
+                            {
+                                self.load_test_pattern();
+                            }
+                        }
+                    });
+
                 if ui.button("Save Result").clicked() {
                     self.save_result();
                 }
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui
+                    .add_enabled(!self.preview_rgb.is_empty(), egui::Button::new("Noise Print"))
+                    .on_hover_text(
+                        "Save what the noise and glitch stages changed as a noise print, to \
+                         reuse over other images",
+                    )
+                    .clicked()
+                {
+                    self.save_noise_print();
+                }
                 ui.separator();
 
                 ui.label("Preset:");
@@ -354,25 +1365,154 @@ impl eframe::App for CcdGlitchApp {
                 if ui.button("Process").clicked() {
                     self.needs_process = true;
                 }
-                if ui.button("Reset").clicked() {
-                    let config = self.sensor_preset.config();
-                    self.params = PipelineParams::default();
-                    apply_sensor_config(&mut self.params, &config);
-                    self.needs_process = true;
+                if ui
+                    .add_enabled(!self.preview_rgb.is_empty(), egui::Button::new("Snapshot"))
+                    .on_hover_text("Add the current render and its parameters to the gallery")
+                    .clicked()
+                    && let Some(snapshot) = Snapshot::new(
+                        ctx,
+                        &self.params,
+                        self.preview_width,
+                        self.preview_height,
+                        &self.preview_rgb,
+                    )
+                {
+                    self.snapshots.push(snapshot);
+                }
+                if ui
+                    .button("Pin")
+                    .on_hover_text("Pin the current parameters for split-preview comparison")
+                    .clicked()
+                {
+                    self.pinned_params = Some(self.params.clone());
+                    self.needs_process |= self.split_preview;
+                }
+                let can_split = self.pinned_params.is_some();
+                if ui
+                    .add_enabled(can_split, egui::Checkbox::new(&mut self.split_preview, "Split"))
+                    .on_hover_text("Left half: current parameters. Right half: pinned parameters.")
+                    .changed()
+                {
+                    self.needs_process = true;
+                }
+                if ui
+                    .checkbox(&mut self.difference_view, "Diff")
+                    .on_hover_text(
+                        "Show the amplified difference from a clean reference render with \
+                         every glitch and noise source off",
+                    )
+                    .changed()
+                {
+                    self.needs_process = true;
+                }
+                if self.difference_view
+                    && ui
+                        .add(
+                            egui::DragValue::new(&mut self.difference_gain)
+                                .range(1.0..=64.0)
+                                .speed(0.1)
+                                .prefix("x"),
+                        )
+                        .on_hover_text("Difference amplification")
+                        .changed()
+                {
+                    self.needs_process = true;
+                }
+                if ui
+                    .checkbox(&mut self.roi_mode, "ROI")
+                    .on_hover_text(
+                        "Drag a region on the preview; Auto re-renders only that region. \
+                         Process renders the full frame.",
+                    )
+                    .changed()
+                    && !self.roi_mode
+                {
+                    self.roi = None;
+                    self.clear_roi();
+                }
+                if ui.button("Reset").clicked() {
+                    let config = self.sensor_preset.config();
+                    self.params = PipelineParams::default();
+                    apply_sensor_config(&mut self.params, &config);
+                    self.needs_process = true;
                 }
 
                 ui.separator();
-                if self.source_image.is_some() {
+                if let Some(source) = &self.source_image {
+                    let estimate = memory::estimate(&self.params, Some(source), &self.spice_cache);
                     ui.label(format!(
-                        "{}x{} | {:.0}ms",
+                        "{}x{} | ~{} | {:.0}ms",
                         self.params.sensor_width,
                         self.params.sensor_height,
+                        memory::format_bytes(estimate.total()),
                         self.processing_time_ms
-                    ));
+                    ))
+                    .on_hover_text(format!("Estimated peak memory\n{}", estimate.breakdown()));
+                    ui.checkbox(&mut self.show_timings, "Timings");
+                    let problems = crate::log_console::problem_count();
+                    let log_label =
+                        if problems > 0 { format!("Log ({problems})") } else { "Log".into() };
+                    ui.checkbox(&mut self.show_log, log_label)
+                        .on_hover_text("SPICE fallbacks, warnings and timings");
+                    if ui
+                        .checkbox(&mut self.show_metrics, "Metrics")
+                        .on_hover_text("Measure PSNR and SSIM against a clean reference render")
+                        .changed()
+                    {
+                        self.needs_process = true;
+                    }
+                    if let Some(metrics) = &self.metrics {
+                        ui.label(metrics.summary()).on_hover_text(
+                            "Against the same render with every glitch and noise source off",
+                        );
+                    }
+                    if let Some(warning) = &self.memory_warning {
+                        ui.colored_label(egui::Color32::from_rgb(230, 160, 60), "⚠ Memory")
+                            .on_hover_text(warning);
+                    }
+                    if let Some(issue) = self.stage_timings.issues.first() {
+                        let all: Vec<String> =
+                            self.stage_timings.issues.iter().map(|i| i.to_string()).collect();
+                        ui.colored_label(
+                            egui::Color32::from_rgb(230, 80, 60),
+                            format!("⚠ {}", issue.stage),
+                        )
+                        .on_hover_text(format!("Invalid stage output\n{}", all.join("\n")));
+                    }
                 }
             });
         });
 
+        // Bottom panel: per-stage timing breakdown
+        if self.show_timings && self.source_image.is_some() {
+            egui::TopBottomPanel::bottom("stage_timings").show(ctx, |ui| {
+                crate::timing_display::draw_timing_bar(ui, &self.stage_timings);
+            });
+        }
+
+        // Bottom panel: captured log records
+        if self.show_log {
+            egui::TopBottomPanel::bottom("log_console").resizable(true).show(ctx, |ui| {
+                self.log_console.draw(ui);
+            });
+        }
+
+        // Bottom panel: snapshot gallery
+        if !self.snapshots.is_empty() {
+            egui::TopBottomPanel::bottom("snapshots").show(ctx, |ui| {
+                match snapshot_gallery::draw_gallery(ui, &self.snapshots, &self.params) {
+                    Some(GalleryAction::Restore(i)) => {
+                        self.params = self.snapshots[i].params.clone();
+                        self.needs_process = true;
+                    }
+                    Some(GalleryAction::Remove(i)) => {
+                        self.snapshots.remove(i);
+                    }
+                    None => {}
+                }
+            });
+        }
+
         // Left panel: controls
         egui::SidePanel::left("controls")
             .default_width(300.0)
@@ -415,6 +1555,8 @@ impl eframe::App for CcdGlitchApp {
                         }
                     }
 
+                    changed |= ui_optics(ui, &mut self.params);
+                    changed |= ui_camera_osd(ui, &mut self.params);
                     changed |= ui_exposure_noise(ui, &mut self.params);
                     changed |= ui_blooming(ui, &mut self.params);
                     changed |= ui_v_clock(ui, &mut self.params);
@@ -422,11 +1564,38 @@ impl eframe::App for CcdGlitchApp {
                     changed |= ui_amplifier(ui, &mut self.params);
                     changed |= ui_adc(ui, &mut self.params);
                     changed |= ui_glitch(ui, &mut self.params);
+                    changed |= ui_plugins(
+                        ui,
+                        &mut self.plugins,
+                        &mut self.plugin_errors,
+                        &mut self.params,
+                    );
                     changed |= ui_channel(ui, &mut self.params);
                     changed |= ui_color_output(ui, &mut self.params);
+                    changed |= ui_stack(ui, &mut self.params);
+                    changed |= ui_three_chip(ui, &mut self.params);
+                    changed |= ui_determinism(ui, &mut self.params);
+                    changed |= self.ui_morph(ui);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    self.ui_variations(ui);
+                    if changed
+                        && let Some(start) = self.recording_since
+                        && let Some(automation) = &mut self.automation
+                    {
+                        automation.capture(&self.params, start.elapsed().as_secs_f64());
+                    }
+                    self.ui_automation(ui);
+                    self.ui_presets(ui);
+                    ui_param_diff(ui, &self.params, self.sensor_preset);
+                    self.ui_noise_analysis(ui);
+                    self.ui_mtf(ui);
 
                     if changed && self.auto_process {
-                        self.needs_process = true;
+                        if self.roi_mode && self.roi.is_some() {
+                            self.needs_roi_process = true;
+                        } else {
+                            self.needs_process = true;
+                        }
                     }
                 });
             });
@@ -435,11 +1604,16 @@ impl eframe::App for CcdGlitchApp {
         if self.needs_process && self.source_image.is_some() {
             self.process_image(ctx);
             self.needs_process = false;
+            self.needs_roi_process = false;
+            self.roi_texture = None;
+        } else if self.needs_roi_process && self.source_image.is_some() {
+            self.process_roi(ctx);
+            self.needs_roi_process = false;
         }
 
         // Central panel: image preview
         egui::CentralPanel::default().show(ctx, |ui| {
-            if let Some(tex) = &self.preview_texture {
+            if let Some(tex_id) = self.preview_texture.as_ref().map(|t| t.id()) {
                 egui::ScrollArea::both().show(ui, |ui| {
                     let available = ui.available_size();
                     let img_w = self.preview_width as f32;
@@ -449,7 +1623,21 @@ impl eframe::App for CcdGlitchApp {
                         available.y / img_h,
                     ).min(1.0);
                     let display_size = egui::vec2(img_w * scale, img_h * scale);
-                    ui.image(egui::load::SizedTexture::new(tex.id(), display_size));
+                    let sense = if self.roi_mode {
+                        egui::Sense::drag()
+                    } else {
+                        egui::Sense::hover()
+                    };
+                    let response = ui.add(
+                        egui::Image::new(egui::load::SizedTexture::new(tex_id, display_size))
+                            .sense(sense),
+                    );
+                    if self.split_preview && self.pinned_params.is_some() {
+                        draw_split_labels(ui, response.rect);
+                    }
+                    if self.roi_mode {
+                        self.ui_roi_overlay(ui, &response, scale);
+                    }
                 });
             } else {
                 ui.centered_and_justified(|ui| {
@@ -479,6 +1667,20 @@ impl eframe::App for CcdGlitchApp {
             }
         });
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // Leave a crashed session in place until the user has decided on it
+        if self.restore_offer.is_some() {
+            return;
+        }
+        let session =
+            Session::capture(&self.params, self.sensor_preset, self.session_source.as_ref());
+        session::store(storage, &session, self.closing);
+    }
+
+    fn auto_save_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(10)
+    }
 }
 
 // --- UI Section Builders ---
@@ -489,30 +1691,129 @@ fn ui_sensor_config(ui: &mut egui::Ui, params: &mut PipelineParams, preset: Sens
         .default_open(true)
         .show(ui, |ui| {
             if preset == SensorPreset::Custom {
-                let mut w = params.sensor_width;
-                let mut h = params.sensor_height;
-                changed |= ui.add(egui::Slider::new(&mut w, 64..=8192).text("Width")).changed();
-                changed |= ui.add(egui::Slider::new(&mut h, 64..=8192).text("Height")).changed();
-                params.sensor_width = w;
-                params.sensor_height = h;
-                changed |= ui.add(
-                    egui::Slider::new(&mut params.full_well, 1000.0..=500_000.0)
-                        .logarithmic(true)
-                        .text("Full Well (e-)"),
-                ).changed();
+                changed |= ui_params(ui, params, &["sensor_width", "sensor_height", "full_well"]);
+                changed |= ui_param(ui, params, "conversion_gain").changed();
+                changed |= ui_param(ui, params, "pixel_aspect").changed();
             } else {
                 ui.label(format!("Resolution: {}x{}", params.sensor_width, params.sensor_height));
                 ui.label(format!("Full Well: {:.0} e-", params.full_well));
+                ui.label(format!("Conversion Gain: {:.1} µV/e-", params.conversion_gain));
+                ui.label(format!("Pixel Aspect: {:.3}", params.pixel_aspect));
+            }
+            changed |= ui_param(ui, params, "anamorphic_output").changed();
+            changed |= ui_params(ui, params, &["use_abg", "readout_speed"]);
+            if params.readout_speed == ReadoutSpeed::Fast {
+                changed |= ui_params(ui, params, &[
+                    "fast_readout.read_noise",
+                    "fast_readout.cte_loss",
+                    "fast_readout.ringing",
+                    "fast_readout.bit_depth",
+                ]);
+            }
+            changed |= ui_params(ui, params, &["aging.age_years", "aging.radiation_dose"]);
+            if !params.aging.is_new() {
+                let aging = params.aging;
+                ui.label(format!(
+                    "Aged: CTI +{:.1e}, dark +{:.0}% +{:.1} e-, {:.3}% hot, {} serial traps",
+                    aging.cti(),
+                    (aging.dark_scale() - 1.0) * 100.0,
+                    aging.dark_rate(),
+                    aging.hot_pixels() * 100.0,
+                    aging.serial_traps(),
+                ));
+            }
+
+            let old_phases = params.v_phases;
+            changed |= ui_param(ui, params, "v_phases").changed();
+            if params.v_phases != old_phases {
+                // Same pixel, different share of it under collecting gates
+                params.full_well *= params.v_phases.well_fraction() / old_phases.well_fraction();
             }
-            changed |= ui.checkbox(&mut params.use_abg, "Anti-Blooming Gate").changed();
             if changed && preset != SensorPreset::Custom {
-                let config = preset.config();
-                params.full_well = if params.use_abg {
-                    config.full_well_abg
-                } else {
-                    config.full_well_no_abg
-                };
+                params.full_well = rated_full_well(params, &preset.config());
+            }
+            changed |= ui_params(ui, params, &["sensor_orientation", "fit_mode"]);
+            if params.fit_mode == FitMode::Letterbox {
+                let max_pad = params.full_well;
+                changed |= ui.add(
+                    egui::Slider::new(&mut params.letterbox_pad, 0.0..=max_pad).text("Pad (e-)"),
+                ).changed();
             }
+            changed |= ui_params(
+                ui,
+                params,
+                &["area_sum_downscale", "tile_seamless", "alpha_mode"],
+            );
+        });
+    changed
+}
+
+fn ui_optics(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+    let mut changed = false;
+    egui::CollapsingHeader::new("Optics")
+        .default_open(false)
+        .show(ui, |ui| {
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::OPTICS);
+            changed |= ui_param(ui, params, "motion_blur.length").changed();
+            if params.motion_blur.length > 0.0 {
+                changed |= ui_param(ui, params, "motion_blur.trajectory").changed();
+                match params.motion_blur.trajectory {
+                    Trajectory::Linear => {
+                        changed |= ui_param(ui, params, "motion_blur.angle").changed();
+                    }
+                    Trajectory::Arc => {
+                        changed |= ui_params(
+                            ui,
+                            params,
+                            &["motion_blur.pivot_x", "motion_blur.pivot_y"],
+                        );
+                    }
+                }
+            }
+            ui.separator();
+            changed |= ui_params(ui, params, &["lens_distortion", "defocus_radius"]);
+            ui.separator();
+            changed |= ui_param(ui, params, "halation.strength").changed();
+            if params.halation.strength > 0.0 {
+                changed |= ui_params(ui, params, &["halation.threshold", "halation.radius"]);
+                ui.horizontal(|ui| {
+                    ui.label("Tint");
+                    for (value, label) in params.halation.tint.iter_mut().zip(["R ", "G ", "B "]) {
+                        changed |= ui.add(
+                            egui::DragValue::new(value).speed(0.01).range(0.0..=2.0).prefix(label),
+                        ).changed();
+                    }
+                });
+            }
+            ui.separator();
+            changed |= ui_param(ui, params, "scan_skew").changed();
+            changed |= ui_param(ui, params, "scan_scale_error").changed();
+            changed |= ui_param(ui, params, "scan_wobble").changed();
+            if params.scan_wobble > 0.0 {
+                changed |= ui_param(ui, params, "scan_wobble_frequency").changed();
+            }
+        });
+    changed
+}
+
+fn ui_camera_osd(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+    let mut changed = false;
+    egui::CollapsingHeader::new("Camera OSD")
+        .default_open(false)
+        .show(ui, |ui| {
+            changed |= ui_param(ui, params, "camera_osd.date").changed();
+            if params.camera_osd.date {
+                changed |= ui_params(
+                    ui,
+                    params,
+                    &["camera_osd.year", "camera_osd.month", "camera_osd.day"],
+                );
+            }
+            changed |= ui_param(ui, params, "camera_osd.battery").changed();
+            if params.camera_osd.battery {
+                changed |= ui_param(ui, params, "camera_osd.battery_level").changed();
+            }
+            changed |= ui_params(ui, params, &["camera_osd.rec", "camera_osd.brightness"]);
         });
     changed
 }
@@ -522,16 +1823,78 @@ fn ui_exposure_noise(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
     egui::CollapsingHeader::new("Exposure & Noise")
         .default_open(false)
         .show(ui, |ui| {
-            changed |= ui.add(
-                egui::Slider::new(&mut params.dark_current_rate, 0.0..=1000.0)
-                    .logarithmic(true)
-                    .text("Dark Current (e-)"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.read_noise, 0.0..=100.0)
-                    .text("Read Noise (e-)"),
-            ).changed();
-            changed |= ui.checkbox(&mut params.shot_noise_enabled, "Shot Noise").changed();
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::EXPOSURE_NOISE);
+            changed |= ui_param(ui, params, "dark_current_rate").changed();
+            if params.dark_current_rate > 0.0 {
+                changed |= ui_param(ui, params, "dark_structure.edge_glow").changed();
+                if params.dark_structure.edge_glow > 0.0 {
+                    changed |= ui_param(ui, params, "dark_structure.edge_width").changed();
+                }
+                changed |= ui_param(ui, params, "dark_structure.clusters").changed();
+                if params.dark_structure.clusters > 0 {
+                    changed |= ui_params(ui, params, &[
+                        "dark_structure.cluster_gain",
+                        "dark_structure.cluster_radius",
+                    ]);
+                }
+                changed |= ui_param(ui, params, "dark_structure.rings").changed();
+                if params.dark_structure.rings > 0.0 {
+                    changed |= ui_param(ui, params, "dark_structure.ring_period").changed();
+                }
+                changed |= ui_param(ui, params, "dark_structure.hot_pixels").changed();
+                changed |= ui_param(ui, params, "dark_structure.seed").changed();
+            }
+            changed |= ui_params(ui, params, &[
+                "long_exposure.slices",
+                "long_exposure.cosmic_rate",
+            ]);
+            let long = params.long_exposure;
+            if long.slices > 1 {
+                changed |= ui_param(ui, params, "long_exposure.clock_glitch_rate").changed();
+                if long.clock_glitch_rate > 0.0 {
+                    changed |= ui_params(ui, params, &[
+                        "long_exposure.traps",
+                        "long_exposure.pump_charge",
+                    ]);
+                }
+            }
+            if long.cosmic_rate > 0.0 || (long.slices > 1 && long.clock_glitch_rate > 0.0) {
+                changed |= ui_param(ui, params, "long_exposure.seed").changed();
+            }
+            changed |= ui_params(ui, params, &["read_noise", "shot_noise_enabled"]);
+            changed |= ui_param(ui, params, "hdr_merge.enabled").changed();
+            if params.hdr_merge.enabled {
+                changed |= ui_params(ui, params, &[
+                    "hdr_merge.exposures",
+                    "hdr_merge.ev_step",
+                    "hdr_merge.boost",
+                ]);
+            }
+            changed |= ui_param(ui, params, "noise.source").changed();
+            if params.noise.source != NoiseSource::Random {
+                changed |= ui_param(ui, params, "noise.seed").changed();
+            }
+            if params.noise.source == NoiseSource::ValueNoise {
+                changed |= ui_param(ui, params, "noise.scale").changed();
+            }
+            ui.separator();
+            changed |= ui_param(ui, params, "row_exposure_depth").changed();
+            if params.row_exposure_depth > 0.0 {
+                changed |= ui_modulation(ui, params, "row_exposure", |p| &mut p.row_exposure);
+            }
+            changed |= ui_param(ui, params, "flicker.depth").changed();
+            if params.flicker.depth > 0.0 {
+                changed |= ui_params(ui, params, &[
+                    "flicker.shutter",
+                    "flicker.frequency_hz",
+                    "flicker.duty_cycle",
+                    "flicker.phase",
+                    "flicker.exposure_ms",
+                ]);
+                if params.flicker.shutter == ShutterMode::Rolling {
+                    changed |= ui_param(ui, params, "flicker.row_time_us").changed();
+                }
+            }
         });
     changed
 }
@@ -541,15 +1904,8 @@ fn ui_blooming(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
     egui::CollapsingHeader::new("Blooming")
         .default_open(false)
         .show(ui, |ui| {
-            changed |= ui.add(
-                egui::Slider::new(&mut params.abg_strength, 0.0..=1.0)
-                    .text("ABG Strength"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.bloom_threshold, 0.1..=1.0)
-                    .text("Bloom Threshold"),
-            ).changed();
-            changed |= ui.checkbox(&mut params.bloom_vertical, "Vertical Bloom").changed();
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::BLOOMING);
+            changed |= ui_category(ui, params, "blooming");
         });
     changed
 }
@@ -559,72 +1915,128 @@ fn ui_v_clock(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
     egui::CollapsingHeader::new("V-Clock (Parallel)")
         .default_open(false)
         .show(ui, |ui| {
-            changed |= ui.add(
-                egui::Slider::new(&mut params.v_cte, 0.99..=1.0)
-                    .min_decimals(6)
-                    .max_decimals(6)
-                    .text("CTE"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.v_glitch_rate, 0.0..=0.5)
-                    .text("Glitch Rate"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.v_waveform_distortion, 0.0..=1.0)
-                    .text("Waveform Distortion"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.parallel_smear, 0.0..=1.0)
-                    .text("Parallel Smear"),
-            ).changed();
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::V_CLOCK);
+            changed |= ui_params(ui, params, &["v_cte", "v_glitch_rate", "v_glitch_schedule.mode"]);
+            match params.v_glitch_schedule.mode {
+                ScheduleMode::Burst => {
+                    changed |= ui_param(ui, params, "v_glitch_schedule.burst_length").changed();
+                }
+                ScheduleMode::Periodic => {
+                    changed |= ui_param(ui, params, "v_glitch_schedule.period").changed();
+                    let schedule = &mut params.v_glitch_schedule;
+                    let max_offset = schedule.period.saturating_sub(1);
+                    changed |= ui.add(
+                        egui::Slider::new(&mut schedule.offset, 0..=max_offset).text("Offset (rows)"),
+                    ).changed();
+                }
+                ScheduleMode::Random | ScheduleMode::Ramp => {}
+            }
+            changed |= ui_param(ui, params, "v_glitch_schedule.seed").changed();
+            changed |= ui_param(ui, params, "saturation_clock_coupling").changed();
+            changed |= ui_param(ui, params, "v_waveform_distortion").changed();
+            changed |= ui_modulation(ui, params, "v_modulation", |p| &mut p.v_modulation);
+            changed |= ui_params(ui, params, &["parallel_smear", "vertical_direction"]);
+            changed |= ui_param(ui, params, "line_scan.enabled").changed();
+            if params.line_scan.enabled {
+                changed |= ui_params(ui, params, &[
+                    "line_scan.speed",
+                    "line_scan.wobble",
+                    "line_scan.wobble_frequency",
+                    "line_scan.exposure_jitter",
+                    "line_scan.line_noise",
+                ]);
+            } else {
+                changed |= ui_param(ui, params, "tdi.enabled").changed();
+                if params.tdi.enabled {
+                    changed |=
+                        ui_params(ui, params, &["tdi.stages", "tdi.sync_error", "tdi.drift"]);
+                }
+            }
         });
     changed
 }
 
+/// Shape, frequency and phase controls for the modulation source under
+/// `prefix`, with a curve loader for the Curve shape.
+fn ui_modulation(
+    ui: &mut egui::Ui,
+    params: &mut PipelineParams,
+    prefix: &str,
+    modulation: fn(&mut PipelineParams) -> &mut Modulation,
+) -> bool {
+    let mut changed = false;
+    for field in ["shape", "frequency", "phase"] {
+        changed |= ui_param(ui, params, &format!("{prefix}.{field}")).changed();
+    }
+    let modulation = modulation(params);
+    if modulation.shape == ModulationShape::Curve {
+        ui.horizontal(|ui| {
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Load Curve...").clicked()
+                && let Some(curve) = load_modulation_curve()
+            {
+                modulation.curve = curve;
+                changed = true;
+            }
+            ui.label(format!("{} points", modulation.curve.len()));
+        });
+    }
+    changed
+}
+
+/// Pick a text/CSV file of curve samples, or a WAV recording, for a
+/// modulation source.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_modulation_curve() -> Option<Vec<f64>> {
+    let path = rfd::FileDialog::new()
+        .add_filter("Curve", &["csv", "txt", "wav"])
+        .pick_file()?;
+    let is_wav = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("wav"));
+    let result = if is_wav {
+        std::fs::read(&path)
+            .map_err(|e| format!("Failed to read curve: {e}"))
+            .and_then(|bytes| Modulation::parse_wav(&bytes))
+    } else {
+        std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read curve: {e}"))
+            .and_then(|text| Modulation::parse_curve(&text))
+    };
+    match result {
+        Ok(curve) => Some(curve),
+        Err(e) => {
+            log::error!("Error loading curve: {e}");
+            None
+        }
+    }
+}
+
 fn ui_h_clock(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
     let mut changed = false;
     egui::CollapsingHeader::new("H-Clock (Serial)")
         .default_open(false)
         .show(ui, |ui| {
-            changed |= ui.add(
-                egui::Slider::new(&mut params.h_cte, 0.99..=1.0)
-                    .min_decimals(6)
-                    .max_decimals(6)
-                    .text("CTE"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.h_glitch_rate, 0.0..=0.1)
-                    .text("Glitch Rate"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.h_ringing, 0.0..=1.0)
-                    .text("Ringing"),
-            ).changed();
-
-            let current_dir = match params.readout_direction {
-                ReadoutDirection::LeftToRight => "Left to Right",
-                ReadoutDirection::RightToLeft => "Right to Left",
-                ReadoutDirection::Alternating => "Alternating",
-            };
-            egui::ComboBox::from_label("Readout Dir")
-                .selected_text(current_dir)
-                .show_ui(ui, |ui| {
-                    changed |= ui.selectable_value(
-                        &mut params.readout_direction,
-                        ReadoutDirection::LeftToRight,
-                        "Left to Right",
-                    ).changed();
-                    changed |= ui.selectable_value(
-                        &mut params.readout_direction,
-                        ReadoutDirection::RightToLeft,
-                        "Right to Left",
-                    ).changed();
-                    changed |= ui.selectable_value(
-                        &mut params.readout_direction,
-                        ReadoutDirection::Alternating,
-                        "Alternating",
-                    ).changed();
-                });
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::H_CLOCK);
+            changed |= ui_params(
+                ui,
+                params,
+                &["h_cte", "h_glitch_rate", "h_ringing", "readout_direction"],
+            );
+
+            ui.separator();
+            ui.label("Serial Register Defects");
+            changed |= ui_param(ui, params, "serial_defects.count").changed();
+            if params.serial_defects.count > 0 {
+                changed |= ui_params(ui, params, &[
+                    "serial_defects.trap_cte",
+                    "serial_defects.block_level",
+                    "serial_defects.seed",
+                ]);
+            }
+
+            changed |= ui_param(ui, params, "readout_abort").changed();
+            if params.readout_abort < 1.0 {
+                changed |= ui_param(ui, params, "abort_fill").changed();
+            }
         });
     changed
 }
@@ -634,23 +2046,38 @@ fn ui_amplifier(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
     egui::CollapsingHeader::new("Amplifier")
         .default_open(false)
         .show(ui, |ui| {
-            changed |= ui.add(
-                egui::Slider::new(&mut params.amp_gain, 0.1..=10.0)
-                    .logarithmic(true)
-                    .text("Gain"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.nonlinearity, 0.0..=1.0)
-                    .text("Nonlinearity"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.reset_noise, 0.0..=500.0)
-                    .text("Reset Noise (e-)"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.amp_glow, 0.0..=1.0)
-                    .text("Amp Glow"),
-            ).changed();
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::AMPLIFIER);
+            changed |= ui_params(ui, params, &[
+                "amp_gain",
+                "nonlinearity",
+                "reset_noise",
+                "reset_lag",
+                "amp_glow",
+                "amp_drift",
+            ]);
+
+            ui.separator();
+            changed |= ui_param(ui, params, "interference.amplitude").changed();
+            if params.interference.amplitude > 0.0 {
+                changed |= ui_params(
+                    ui,
+                    params,
+                    &["interference.frequency", "interference.jitter"],
+                );
+                changed |= ui_param(ui, params, "interference.locked").changed();
+            }
+
+            ui.separator();
+            changed |= ui_param(ui, params, "supply.load").changed();
+            if params.supply.load > 0.0 {
+                changed |= ui_param(ui, params, "supply.recovery").changed();
+            }
+
+            ui.separator();
+            changed |= ui_params(ui, params, &["thermal.readout_dark", "thermal.heating"]);
+            if params.thermal.heating > 0.0 {
+                changed |= ui_param(ui, params, "thermal.diffusion").changed();
+            }
         });
     changed
 }
@@ -660,46 +2087,151 @@ fn ui_adc(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
     egui::CollapsingHeader::new("ADC")
         .default_open(false)
         .show(ui, |ui| {
-            let mut bd = params.bit_depth as i32;
-            changed |= ui.add(
-                egui::Slider::new(&mut bd, 4..=16).text("Bit Depth"),
-            ).changed();
-            params.bit_depth = bd as u8;
-
-            let cds_name = match params.cds_mode {
-                CdsMode::On => "On",
-                CdsMode::Off => "Off",
-                CdsMode::Partial => "Partial",
-            };
-            egui::ComboBox::from_label("CDS Mode")
-                .selected_text(cds_name)
-                .show_ui(ui, |ui| {
-                    changed |= ui.selectable_value(&mut params.cds_mode, CdsMode::On, "On").changed();
-                    changed |= ui.selectable_value(&mut params.cds_mode, CdsMode::Off, "Off").changed();
-                    changed |= ui.selectable_value(&mut params.cds_mode, CdsMode::Partial, "Partial").changed();
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::ADC);
+            changed |= ui_params(ui, params, &[
+                "bit_depth",
+                "cds_mode",
+                "adc_gain",
+                "bias",
+                "dnl_errors",
+                "bit_errors",
+            ]);
+            changed |= ui_param(ui, params, "adc_jitter").changed();
+            changed |= ui_params(ui, params, &["adc_dither", "adc_overflow", "adc_encoding"]);
+            changed |= ui_param(ui, params, "adc_wrong_decode").changed();
+            changed |= ui_param(ui, params, "column_adc.enabled").changed();
+            if params.column_adc.enabled {
+                changed |= ui_params(ui, params, &[
+                    "column_adc.offset",
+                    "column_adc.gain",
+                    "column_adc.dead_rate",
+                    "column_adc.seed",
+                ]);
+            }
+            changed |= ui_param(ui, params, "black_clamp.enabled").changed();
+            if params.black_clamp.enabled {
+                changed |= ui_param(ui, params, "black_clamp.loop_gain").changed();
+                changed |= ui_params(ui, params, &["black_clamp.leak", "black_clamp.noise"]);
+            }
+            changed |= ui_param(ui, params, "dual_gain.enabled").changed();
+            if params.dual_gain.enabled {
+                changed |= ui_params(ui, params, &["dual_gain.ratio", "dual_gain.threshold"]);
+                changed |= ui_param(ui, params, "dual_gain.stitch_error").changed();
+            }
+            changed |= ui_param(ui, params, "compand.knees").changed();
+            if params.compand.knees > 0 {
+                changed |= ui_param(ui, params, "compand.ratio").changed();
+                changed |= ui_param(ui, params, "compand.mismatch").changed();
+            }
+            changed |= ui_param(ui, params, "adc_ref_droop").changed();
+            if params.adc_ref_droop > 0.0 {
+                changed |= ui_param(ui, params, "adc_ref_recovery").changed();
+            }
+            changed |= ui_param(ui, params, "adc_clock_error").changed();
+            if params.adc_clock_error != 0.0 {
+                changed |= ui_param(ui, params, "adc_line_lock").changed();
+            }
+            changed |= ui_param(ui, params, "auto_exposure").changed();
+            if params.auto_exposure {
+                changed |= ui_params(
+                    ui,
+                    params,
+                    &["auto_exposure_percentile", "auto_exposure_target"],
+                );
+            }
+        });
+    changed
+}
+
+fn ui_stack(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+    let mut changed = false;
+    egui::CollapsingHeader::new("Stacking")
+        .default_open(false)
+        .show(ui, |ui| {
+            changed |= ui_param(ui, params, "stack.frames").changed();
+            if params.stack.frames > 1 {
+                changed |= ui_param(ui, params, "stack.drizzle").changed();
+                if params.stack.drizzle {
+                    changed |= ui_params(ui, params, &["stack.pixfrac", "stack.drizzle_scale"]);
+                } else {
+                    changed |= ui_param(ui, params, "stack.combiner").changed();
+                }
+                changed |= ui_params(
+                    ui,
+                    params,
+                    &["stack.translate_jitter", "stack.rotate_jitter"],
+                );
+            }
+        });
+    changed
+}
+
+fn ui_three_chip(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+    const LABELS: [&str; 3] = ["Red", "Green", "Blue"];
+    let mut changed = false;
+    egui::CollapsingHeader::new("3-CCD")
+        .default_open(false)
+        .show(ui, |ui| {
+            changed |= ui_param(ui, params, "three_chip.enabled").changed();
+            if !params.three_chip.enabled {
+                return;
+            }
+            changed |= ui_params(ui, params, &[
+                "three_chip.red_dx",
+                "three_chip.red_dy",
+                "three_chip.red_rotation",
+                "three_chip.red_skew",
+                "three_chip.blue_dx",
+                "three_chip.blue_dy",
+                "three_chip.blue_rotation",
+                "three_chip.blue_skew",
+            ]);
+
+            ui.separator();
+            // Edits live in a buffer until they parse, so typing is not undone
+            for (chip, channel) in three_chip::CHIPS.iter().enumerate() {
+                let id = ui.id().with(("three_chip_overrides", chip));
+                let mut text: String = ui
+                    .data(|d| d.get_temp(id))
+                    .unwrap_or_else(|| params.three_chip.overrides[chip].clone());
+                let mut error = None;
+                ui.horizontal(|ui| {
+                    ui.label(LABELS[chip]);
+                    let response = ui.text_edit_singleline(&mut text).on_hover_text(
+                        "Settings for this chip only, as name=value pairs separated by commas, \
+                         e.g. h_cte=0.999, v_glitch_rate=0.02",
+                    );
+                    if response.changed() {
+                        match params.set_value(&format!("three_chip.{channel}"), &text) {
+                            Ok(()) => changed = true,
+                            Err(e) => error = Some(e),
+                        }
+                    } else if text != params.three_chip.overrides[chip] {
+                        let mut trial = params.clone();
+                        error = trial.set_value(&format!("three_chip.{channel}"), &text).err();
+                    }
+                    if let Some(error) = &error {
+                        ui.colored_label(egui::Color32::from_rgb(230, 160, 60), "⚠")
+                            .on_hover_text(error);
+                    }
                 });
+                ui.data_mut(|d| d.insert_temp(id, text));
+            }
+        });
+    changed
+}
 
-            changed |= ui.add(
-                egui::Slider::new(&mut params.adc_gain, 0.1..=10.0)
-                    .logarithmic(true)
-                    .text("Gain (e-/ADU)"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.bias, 0.0..=1000.0)
-                    .text("Bias"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.dnl_errors, 0.0..=1.0)
-                    .text("DNL Errors"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.bit_errors, 0.0..=1.0)
-                    .text("Bit Errors"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.adc_jitter, 0.0..=500.0)
-                    .text("ADC Jitter"),
-            ).changed();
+fn ui_determinism(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+    let mut changed = false;
+    egui::CollapsingHeader::new("Determinism")
+        .default_open(false)
+        .show(ui, |ui| {
+            changed |= ui_param(ui, params, "determinism.strict").changed();
+            if params.determinism.strict {
+                changed |= ui_param(ui, params, "determinism.seed").changed();
+            }
+            ui.separator();
+            changed |= ui_param(ui, params, "validate_buffers").changed();
         });
     changed
 }
@@ -709,96 +2241,120 @@ fn ui_glitch(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
     egui::CollapsingHeader::new("Glitch Effects")
         .default_open(false)
         .show(ui, |ui| {
-            changed |= ui.add(
-                egui::Slider::new(&mut params.pixel_shift_amount, 0.0..=2.0)
-                    .text("Pixel Shift"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.block_shift_amount, 0.0..=2.0)
-                    .text("Block Shift"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.scan_line_frequency, 0.0..=2.0)
-                    .text("Scan Line Corruption"),
-            ).changed();
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::GLITCH);
+            changed |= ui_params(ui, params, &[
+                "pixel_shift_amount",
+                "block_shift_amount",
+                "scan_line_frequency",
+                "per_plane_glitch",
+            ]);
+
+            ui.separator();
+            ui.label("Video Sync");
+
+            changed |= ui_param(ui, params, "sync.loss_rate").changed();
+            changed |= ui_param(ui, params, "sync.loss_length").changed();
+            changed |= ui_param(ui, params, "sync.dropout_rate").changed();
+            changed |= ui_params(ui, params, &["sync.dropout_length", "sync.burst"]);
+
+            ui.separator();
+            ui.label("Defect Repair");
+            changed |= ui_params(ui, params, &["defect_repair_amount", "defect_repair_mode"]);
 
             ui.separator();
             ui.label("Bit Manipulation");
+            changed |= ui_params(ui, params, &["bit_xor_mask", "bit_rotation", "bit_plane_swaps"]);
+
+            ui.separator();
+            changed |= ui_param(ui, params, "raw_packing").changed();
+            if params.raw_packing != RawPacking::Off {
+                changed |= ui_params(ui, params, &["raw_bit_offset", "raw_stride_error"]);
+            }
 
-            let mut xor = params.bit_xor_mask as i32;
-            changed |= ui.add(
-                egui::Slider::new(&mut xor, 0..=65535).text("XOR Mask"),
-            ).changed();
-            params.bit_xor_mask = xor as u16;
-
-            changed |= ui.add(
-                egui::Slider::new(&mut params.bit_rotation, -8..=8)
-                    .text("Bit Rotation"),
-            ).changed();
-
-            let mut swaps = params.bit_plane_swaps as i32;
-            changed |= ui.add(
-                egui::Slider::new(&mut swaps, 0..=8).text("Bit Plane Swaps"),
-            ).changed();
-            params.bit_plane_swaps = swaps as u32;
+            ui.separator();
+            changed |= ui_param(ui, params, "compression.enabled").changed();
+            if params.compression.enabled {
+                changed |= ui_params(ui, params, &[
+                    "compression.quality",
+                    "compression.mosquito",
+                    "compression.dc_flicker",
+                    "compression.block_smear",
+                ]);
+            }
         });
     changed
 }
 
-fn ui_channel(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+/// Discovered plugins with enable toggles and amounts. Enabled plugins run in
+/// the order they were switched on.
+fn ui_plugins(
+    ui: &mut egui::Ui,
+    plugins: &mut Vec<std::sync::Arc<Plugin>>,
+    errors: &mut Vec<String>,
+    params: &mut PipelineParams,
+) -> bool {
     let mut changed = false;
-    egui::CollapsingHeader::new("Channel Effects")
+    egui::CollapsingHeader::new("Plugins")
         .default_open(false)
         .show(ui, |ui| {
-            let swap_name = params.channel_swap.name();
-            egui::ComboBox::from_label("Channel Swap")
-                .selected_text(swap_name)
-                .show_ui(ui, |ui| {
-                    for &swap in ChannelSwap::ALL {
-                        changed |= ui.selectable_value(
-                            &mut params.channel_swap,
-                            swap,
-                            swap.name(),
-                        ).changed();
+            ui.label(format!("Directory: {}", plugin::plugin_dir().display()));
+            if plugins.is_empty() {
+                ui.label("No plugins found");
+            }
+            for p in plugins.iter() {
+                let slot = params.plugins.iter().position(|s| std::sync::Arc::ptr_eq(&s.plugin, p));
+                let mut enabled = slot.is_some();
+                let label = format!("{} ({})", p.name, p.stage.name());
+                let toggle = ui.checkbox(&mut enabled, label);
+                let toggle = if p.description.is_empty() {
+                    toggle
+                } else {
+                    toggle.on_hover_text(&p.description)
+                };
+                if toggle.changed() {
+                    match slot {
+                        Some(i) => {
+                            params.plugins.remove(i);
+                        }
+                        None => params.plugins.push(PluginSlot::new(p.clone())),
                     }
-                });
-
-            ui.label("Channel Gain");
-            changed |= ui.add(
-                egui::Slider::new(&mut params.channel_r_gain, 0.0..=3.0).text("R Gain"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.channel_g_gain, 0.0..=3.0).text("G Gain"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.channel_b_gain, 0.0..=3.0).text("B Gain"),
-            ).changed();
-
-            ui.label("Channel Offset");
-            changed |= ui.add(
-                egui::Slider::new(&mut params.channel_r_offset, -0.5..=0.5).text("R Offset"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.channel_g_offset, -0.5..=0.5).text("G Offset"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.channel_b_offset, -0.5..=0.5).text("B Offset"),
-            ).changed();
+                    changed = true;
+                }
+                let slot = params.plugins.iter_mut().find(|s| std::sync::Arc::ptr_eq(&s.plugin, p));
+                if let Some(slot) = slot {
+                    changed |= ui.add(
+                        egui::Slider::new(&mut slot.amount, 0.0..=2.0).text("Amount"),
+                    ).changed();
+                    changed |= ui.add(
+                        egui::DragValue::new(&mut slot.seed).prefix("Seed: "),
+                    ).changed();
+                }
+            }
+            for e in errors.iter() {
+                ui.colored_label(egui::Color32::LIGHT_RED, e);
+            }
+            if ui.button("Rescan").clicked() {
+                (*plugins, *errors) = plugin::discover(&plugin::plugin_dir());
+                // Keep enabled plugins that are still present, now from the fresh load
+                let old = std::mem::take(&mut params.plugins);
+                for slot in old {
+                    if let Some(p) = plugins.iter().find(|p| p.path == slot.plugin.path) {
+                        params.plugins.push(PluginSlot { plugin: p.clone(), ..slot });
+                    }
+                }
+                changed = true;
+            }
+        });
+    changed
+}
 
-            ui.separator();
-            ui.label("Chromatic Aberration");
-            changed |= ui.add(
-                egui::Slider::new(&mut params.chromatic_r_x, -20..=20).text("R shift X"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.chromatic_r_y, -20..=20).text("R shift Y"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.chromatic_b_x, -20..=20).text("B shift X"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.chromatic_b_y, -20..=20).text("B shift Y"),
-            ).changed();
+fn ui_channel(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+    let mut changed = false;
+    egui::CollapsingHeader::new("Channel Effects")
+        .default_open(false)
+        .show(ui, |ui| {
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::CHANNEL);
+            changed |= ui_category(ui, params, "channel");
         });
     changed
 }
@@ -818,92 +2374,41 @@ fn ui_spice_mode(
     )
     .default_open(false)
     .show(ui, |ui| {
-        // Mode selector
-        let mode_name = params.spice.mode.name();
-        egui::ComboBox::from_label("Mode")
-            .selected_text(mode_name)
-            .show_ui(ui, |ui| {
-                for &mode in SpiceMode::ALL {
-                    changed |= ui
-                        .selectable_value(&mut params.spice.mode, mode, mode.name())
-                        .changed();
-                }
-            });
+        changed |= ui_param(ui, params, "spice.mode").changed();
 
         let is_active = params.spice.mode != SpiceMode::Off;
 
         if is_active {
             ui.separator();
             ui.label("Circuit Parameters");
-
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut params.spice.vdd, 5.0..=20.0)
-                        .text("VDD (V)"),
-                )
-                .changed();
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut params.spice.clock_freq_mhz, 0.1..=50.0)
-                        .text("Clock (MHz)"),
-                )
-                .changed();
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut params.spice.temperature_k, 200.0..=400.0)
-                        .text("Temp (K)"),
-                )
-                .changed();
-
-            let mut stages = params.spice.shift_register_stages as i32;
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut stages, 2..=16).text("SR Stages"),
-                )
-                .changed();
-            params.spice.shift_register_stages = stages as usize;
-
-            let mut res = params.spice.transfer_function_resolution as i32;
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut res, 8..=128).text("TF Resolution"),
-                )
-                .changed();
-            params.spice.transfer_function_resolution = res as usize;
+            changed |= ui_params(ui, params, &[
+                "spice.vdd",
+                "spice.clock_freq_mhz",
+                "spice.temperature_k",
+                "spice.shift_register_stages",
+                "spice.transfer_function_resolution",
+            ]);
 
             ui.separator();
             ui.label("Glitch Parameters");
-
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut params.spice.supply_droop, 0.0..=0.8)
-                        .text("Supply Droop"),
-                )
-                .changed();
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut params.spice.phase_overlap_ns, 0.0..=100.0)
-                        .text("Phase Overlap (ns)"),
-                )
-                .changed();
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut params.spice.missing_pulse_rate, 0.0..=0.5)
-                        .text("Missing Pulses"),
-                )
-                .changed();
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut params.spice.charge_injection, 0.0..=2.0)
-                        .text("Charge Injection"),
-                )
-                .changed();
-            changed |= ui
-                .add(
-                    egui::Slider::new(&mut params.spice.substrate_noise, 0.0..=1.0)
-                        .text("Substrate Noise"),
-                )
-                .changed();
+            changed |= ui_params(ui, params, &[
+                "spice.supply_droop",
+                "spice.phase_overlap_ns",
+                "spice.missing_pulse_rate",
+                "spice.charge_injection",
+                "spice.substrate_noise",
+            ]);
+
+            // Settings the circuits cannot run, which would fall back silently
+            for warning in params.spice.validate(&params.spice_sensor()) {
+                let color = match warning.severity {
+                    Severity::Warning => egui::Color32::from_rgb(230, 160, 60),
+                    Severity::Error => egui::Color32::from_rgb(230, 80, 60),
+                };
+                ui.label(
+                    egui::RichText::new(format!("⚠ {}", warning.message)).small().color(color),
+                );
+            }
 
             ui.separator();
 
@@ -945,54 +2450,336 @@ fn ui_color_output(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
     egui::CollapsingHeader::new("Color / Output")
         .default_open(false)
         .show(ui, |ui| {
-            let bayer_name = params.bayer_pattern.name();
-            egui::ComboBox::from_label("Bayer Pattern")
-                .selected_text(bayer_name)
-                .show_ui(ui, |ui| {
-                    for &pattern in BayerPattern::ALL {
-                        changed |= ui.selectable_value(
-                            &mut params.bayer_pattern,
-                            pattern,
-                            pattern.name(),
-                        ).changed();
-                    }
-                });
+            changed |= ui_bypass(ui, &mut params.bypass, StageBypass::COLOR);
+            changed |= ui_category(ui, params, "color");
 
-            let demosaic_name = params.demosaic_algo.name();
-            egui::ComboBox::from_label("Demosaic")
-                .selected_text(demosaic_name)
-                .show_ui(ui, |ui| {
-                    for &algo in DemosaicAlgo::ALL {
-                        changed |= ui.selectable_value(
-                            &mut params.demosaic_algo,
-                            algo,
-                            algo.name(),
-                        ).changed();
-                    }
-                });
+            ui.separator();
+            changed |= ui_tone_curves(ui, &mut params.tone_curves);
+
+            ui.separator();
+            changed |= ui_output_lut(ui, params);
+            changed |= ui_custom_palette(ui, params);
 
             ui.separator();
-            ui.label("White Balance");
-            changed |= ui.add(
-                egui::Slider::new(&mut params.white_balance_r, 0.0..=3.0).text("R"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.white_balance_g, 0.0..=3.0).text("G"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.white_balance_b, 0.0..=3.0).text("B"),
-            ).changed();
+            changed |= ui_category(ui, params, "composite");
+            changed |= ui_noise_print(ui, params);
 
             ui.separator();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.gamma, 0.1..=4.0).text("Gamma"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.brightness, -1.0..=1.0).text("Brightness"),
-            ).changed();
-            changed |= ui.add(
-                egui::Slider::new(&mut params.contrast, 0.0..=3.0).text("Contrast"),
-            ).changed();
+            changed |= ui_category(ui, params, "overlay");
         });
     changed
 }
+
+/// Tone curve editor with a channel selector (Master / R / G / B).
+fn ui_tone_curves(ui: &mut egui::Ui, curves: &mut ToneCurves) -> bool {
+    const CHANNELS: [(&str, egui::Color32); 4] = [
+        ("Master", egui::Color32::from_rgb(220, 220, 230)),
+        ("R", egui::Color32::from_rgb(255, 90, 90)),
+        ("G", egui::Color32::from_rgb(90, 230, 110)),
+        ("B", egui::Color32::from_rgb(90, 150, 255)),
+    ];
+    let mut changed = false;
+    let channel_id = ui.id().with("tone_curve_channel");
+    let mut channel: usize = ui.data(|d| d.get_temp(channel_id)).unwrap_or(0);
+
+    ui.horizontal(|ui| {
+        ui.label("Curves");
+        for (i, (name, _)) in CHANNELS.iter().enumerate() {
+            ui.selectable_value(&mut channel, i, *name);
+        }
+        if ui.small_button("Reset").clicked() {
+            *curves = ToneCurves::default();
+            changed = true;
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(channel_id, channel));
+
+    let curve = match channel {
+        1 => &mut curves.red,
+        2 => &mut curves.green,
+        3 => &mut curves.blue,
+        _ => &mut curves.master,
+    };
+    changed |= crate::curve_editor::curve_editor(ui, curve, CHANNELS[channel].1);
+    changed
+}
+
+/// Output LUT status with load/clear, plus export of the current color pipeline.
+fn ui_output_lut(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+    let mut changed = false;
+    match &params.output_lut {
+        Some(lut) => {
+            let name = if lut.title.is_empty() { "untitled" } else { lut.title.as_str() };
+            ui.label(format!("LUT: {} ({}-point)", name, lut.size));
+        }
+        None => {
+            ui.label("LUT: none");
+        }
+    }
+    ui.horizontal(|ui| {
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Load LUT...").clicked()
+            && let Some(lut) = load_cube_lut()
+        {
+            params.output_lut = Some(std::sync::Arc::new(lut));
+            changed = true;
+        }
+        if params.output_lut.is_some() && ui.button("Clear").clicked() {
+            params.output_lut = None;
+            changed = true;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Export LUT...").clicked() {
+            export_cube_lut(params);
+        }
+    });
+    changed
+}
+
+/// The loaded palette for the Custom palette mode, with load and clear.
+fn ui_custom_palette(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+    let mut changed = false;
+    match &params.palette.custom {
+        Some(palette) => {
+            let name = if palette.title.is_empty() { "untitled" } else { palette.title.as_str() };
+            ui.label(format!("Palette: {} ({} colors)", name, palette.colors.len()));
+        }
+        None => {
+            ui.label("Palette: none");
+        }
+    }
+    ui.horizontal(|ui| {
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Load Palette...").clicked()
+            && let Some(palette) = load_palette()
+        {
+            params.palette.custom = Some(std::sync::Arc::new(palette));
+            params.palette.mode = PaletteMode::Custom;
+            changed = true;
+        }
+        if params.palette.custom.is_some() && ui.button("Clear").clicked() {
+            params.palette.custom = None;
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// The loaded noise print, with load and clear.
+fn ui_noise_print(ui: &mut egui::Ui, params: &mut PipelineParams) -> bool {
+    let mut changed = false;
+    match &params.artifact_layer.layer {
+        Some(layer) => {
+            ui.label(format!("Noise print: {}x{}", layer.width, layer.height));
+        }
+        None => {
+            ui.label("Noise print: none");
+        }
+    }
+    ui.horizontal(|ui| {
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Load Noise Print...").clicked()
+            && let Some(layer) = load_noise_print()
+        {
+            params.artifact_layer.layer = Some(std::sync::Arc::new(layer));
+            changed = true;
+        }
+        if params.artifact_layer.layer.is_some() && ui.button("Clear").clicked() {
+            params.artifact_layer.layer = None;
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Pick a saved noise print to add over the output.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_noise_print() -> Option<ArtifactLayer> {
+    let path = rfd::FileDialog::new()
+        .add_filter("Image", &["png", "tif", "tiff"])
+        .pick_file()?;
+    match ArtifactLayer::load(&path) {
+        Ok(layer) => Some(layer),
+        Err(e) => {
+            log::error!("Error loading noise print: {e}");
+            None
+        }
+    }
+}
+
+/// Pick a GIMP `.gpl` or hex list file to use as the custom palette.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_palette() -> Option<Palette> {
+    let path = rfd::FileDialog::new()
+        .add_filter("Palette", &["gpl", "hex", "txt"])
+        .pick_file()?;
+    let result = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read palette: {e}"))
+        .and_then(|text| Palette::parse(&text));
+    match result {
+        Ok(palette) => Some(palette),
+        Err(e) => {
+            log::error!("Error loading palette: {e}");
+            None
+        }
+    }
+}
+
+/// Pick a `.cube` file to use as the output LUT.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_cube_lut() -> Option<Lut3d> {
+    let path = rfd::FileDialog::new()
+        .add_filter("Cube LUT", &["cube"])
+        .pick_file()?;
+    let result = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read LUT: {e}"))
+        .and_then(|text| Lut3d::parse_cube(&text));
+    match result {
+        Ok(lut) => Some(lut),
+        Err(e) => {
+            log::error!("Error loading LUT: {e}");
+            None
+        }
+    }
+}
+
+/// Save the current color pipeline (without the output LUT) as a 33-point `.cube`.
+#[cfg(not(target_arch = "wasm32"))]
+fn export_cube_lut(params: &PipelineParams) {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("Cube LUT", &["cube"])
+        .set_file_name("ccd_glitch.cube")
+        .save_file()
+    else {
+        return;
+    };
+    let lut = crate::pipeline::color_transform_lut(params, 33);
+    if let Err(e) = std::fs::write(&path, lut.to_cube()) {
+        log::error!("Error saving LUT: {e}");
+    }
+}
+
+/// Control for the schema parameter `name`: a slider for numbers, a
+/// checkbox for flags, a combo box for choices, labeled and ranged by the
+/// schema, with the schema's description on hover. Edits go through
+/// `set_value`, which keeps linked fields in step.
+fn ui_param(ui: &mut egui::Ui, params: &mut PipelineParams, name: &str) -> egui::Response {
+    let spec =
+        schema::find(name).unwrap_or_else(|| panic!("{name} is not in the parameter schema"));
+    let value = params
+        .values()
+        .into_iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, value)| value)
+        .unwrap_or_else(|| panic!("{name} is not in PipelineParams::values()"));
+    let (response, value) = match (spec.kind, value) {
+        (ParamKind::Float { min, max }, ParamValue::Float(mut v)) => {
+            let mut slider = egui::Slider::new(&mut v, min..=max)
+                .logarithmic(spec.logarithmic)
+                .text(spec.label);
+            if let Some(decimals) = spec.decimals {
+                slider = slider.min_decimals(decimals).max_decimals(decimals);
+            }
+            (ui.add(slider), ParamValue::Float(v))
+        }
+        (ParamKind::Int { min, max }, ParamValue::Int(mut v)) => {
+            let slider = egui::Slider::new(&mut v, min as i64..=max as i64)
+                .logarithmic(spec.logarithmic)
+                .text(spec.label);
+            (ui.add(slider), ParamValue::Int(v))
+        }
+        (ParamKind::Bool, ParamValue::Bool(mut v)) => {
+            (ui.checkbox(&mut v, spec.label), ParamValue::Bool(v))
+        }
+        (ParamKind::Choice(options), ParamValue::Choice(mut v)) => {
+            let mut picked = false;
+            let mut response = egui::ComboBox::new(spec.name, spec.label)
+                .selected_text(v)
+                .show_ui(ui, |ui| {
+                    for option in options() {
+                        if ui.selectable_label(option == v, option).clicked() && option != v {
+                            v = option;
+                            picked = true;
+                        }
+                    }
+                })
+                .response;
+            if picked {
+                response.mark_changed();
+            }
+            (response, ParamValue::Choice(v))
+        }
+        _ => panic!("{name} does not match its schema type"),
+    };
+    if response.changed()
+        && let Err(e) = params.set_value(name, &value.to_string())
+    {
+        log::error!("Error setting {name}: {e}");
+    }
+    response.on_hover_text(spec.doc)
+}
+
+/// Controls for several schema parameters, in order.
+fn ui_params(ui: &mut egui::Ui, params: &mut PipelineParams, names: &[&str]) -> bool {
+    let mut changed = false;
+    for name in names {
+        changed |= ui_param(ui, params, name).changed();
+    }
+    changed
+}
+
+/// Controls for every parameter in a schema category, in schema order.
+fn ui_category(ui: &mut egui::Ui, params: &mut PipelineParams, category: &str) -> bool {
+    let mut changed = false;
+    for spec in schema::category(category) {
+        changed |= ui_param(ui, params, spec.name).changed();
+    }
+    changed
+}
+
+/// Write frame `index` of an exported sequence into `dir`.
+#[cfg(not(target_arch = "wasm32"))]
+fn save_sequence_frame(
+    dir: &std::path::Path,
+    index: usize,
+    w: usize,
+    h: usize,
+    bytes: Vec<u8>,
+) -> Result<(), String> {
+    let img = image::RgbImage::from_raw(w as u32, h as u32, bytes)
+        .ok_or("Rendered frame does not match its size")?;
+    crate::image_io::save_image(&img, &dir.join(format!("frame_{index:05}.png")))
+}
+
+/// Bypass checkbox at the top of a stage group.
+fn ui_bypass(ui: &mut egui::Ui, bypass: &mut StageBypass, stage: StageBypass) -> bool {
+    let mut bypassed = bypass.contains(stage);
+    let changed = ui.checkbox(&mut bypassed, "Bypass").on_hover_text(
+        "Skip this stage at runtime, keeping its settings",
+    ).changed();
+    bypass.set(stage, bypassed);
+    changed
+}
+
+/// Compact read-only list of parameters changed from the preset defaults.
+fn ui_param_diff(ui: &mut egui::Ui, params: &PipelineParams, preset: SensorPreset) {
+    let mut baseline = PipelineParams::default();
+    apply_sensor_config(&mut baseline, &preset.config());
+    let changes = params.diff(&baseline);
+
+    egui::CollapsingHeader::new(format!("Changed from Default ({})", changes.len()))
+        .id_salt("param_diff")
+        .default_open(false)
+        .show(ui, |ui| {
+            if changes.is_empty() {
+                ui.label(
+                    egui::RichText::new("All parameters at preset defaults")
+                        .small()
+                        .color(egui::Color32::from_rgb(120, 120, 140)),
+                );
+            }
+            for change in &changes {
+                ui.label(egui::RichText::new(change.to_string()).small().monospace());
+            }
+        });
+}
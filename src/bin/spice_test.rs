@@ -6,8 +6,9 @@
 //! Usage: cargo run --bin spice_test --features spice
 
 // Reuse the library crate
+use physical_ccd_glitch::pipeline::test_patterns::{self, TestPattern};
 use physical_ccd_glitch::pipeline::{self, PipelineParams};
-use physical_ccd_glitch::spice::{self, SpiceCache, SpiceMode, SpiceParams};
+use physical_ccd_glitch::spice::{self, SpiceCache, SpiceMode, SpiceParams, SpiceSensor};
 
 use image::{DynamicImage, Rgb, RgbImage};
 use std::path::Path;
@@ -22,8 +23,8 @@ fn main() {
     println!("=== SPICE Simulation Test Harness ===\n");
 
     // Generate test images
-    let gradient = generate_gradient_image(512, 384);
-    let checkerboard = generate_checkerboard_image(512, 384);
+    let gradient = test_patterns::generate(TestPattern::Gradient, 512, 384);
+    let checkerboard = test_patterns::generate(TestPattern::Checkerboard, 512, 384);
     let test_image = load_or_generate_test_image();
 
     println!("Test images generated:");
@@ -133,33 +134,6 @@ fn main() {
 
 // === Image Generation ===
 
-fn generate_gradient_image(width: u32, height: u32) -> DynamicImage {
-    let mut img = RgbImage::new(width, height);
-    for y in 0..height {
-        for x in 0..width {
-            let val = (x as f64 / (width - 1) as f64 * 255.0) as u8;
-            // Add some vertical variation
-            let row_mod = ((y as f64 / height as f64 * 4.0).sin() * 20.0) as i16;
-            let v = (val as i16 + row_mod).clamp(0, 255) as u8;
-            img.put_pixel(x, y, Rgb([v, v, v]));
-        }
-    }
-    DynamicImage::ImageRgb8(img)
-}
-
-fn generate_checkerboard_image(width: u32, height: u32) -> DynamicImage {
-    let mut img = RgbImage::new(width, height);
-    let block = 32;
-    for y in 0..height {
-        for x in 0..width {
-            let checker = ((x / block) + (y / block)) % 2 == 0;
-            let val = if checker { 220u8 } else { 30u8 };
-            img.put_pixel(x, y, Rgb([val, val, val]));
-        }
-    }
-    DynamicImage::ImageRgb8(img)
-}
-
 fn load_or_generate_test_image() -> DynamicImage {
     let path = Path::new("test_data/test_image.png");
     if path.exists() {
@@ -212,7 +186,7 @@ fn process_with_spice(
 
     // Run simulation
     if spice_params.mode != SpiceMode::Off {
-        spice::simulate_or_cache(&params.spice, params.full_well, &mut cache);
+        spice::simulate_or_cache(&params.spice, &params.spice_sensor(), &mut cache);
     }
 
     let (w, h, bytes) = pipeline::process(img, &params, &cache);
@@ -220,7 +194,7 @@ fn process_with_spice(
         pixel_transfer: vec![],
         effective_cte: 1.0,
         clock_ringing_kernel: vec![],
-        clock_waveforms: [vec![], vec![], vec![]],
+        clock_waveforms: vec![],
         amp_transfer_curve: vec![],
         amp_noise_sigma: 0.0,
         cds_rejection: 0.0,
@@ -283,9 +257,10 @@ fn test_cache_validity() -> bool {
     let p2 = SpiceParams { vdd: 12.0, ..SpiceParams::default() };
     let p3 = SpiceParams::default();
 
-    let h1 = p1.param_hash();
-    let h2 = p2.param_hash();
-    let h3 = p3.param_hash();
+    let sensor = SpiceSensor::default();
+    let h1 = p1.param_hash(&sensor);
+    let h2 = p2.param_hash(&sensor);
+    let h3 = p3.param_hash(&sensor);
 
     let hash_differ = h1 != h2;
     let hash_match = h1 == h3;
@@ -303,14 +278,13 @@ fn test_cache_validity() -> bool {
 
     // Test cache invalidation
     let mut cache = None;
-    let full_well = 40_000.0;
     let mut p = SpiceParams { mode: SpiceMode::FullReadout, ..SpiceParams::default() };
 
-    spice::simulate_or_cache(&p, full_well, &mut cache);
-    let valid_before = cache.as_ref().map(|c| c.is_valid_for(&p)).unwrap_or(false);
+    spice::simulate_or_cache(&p, &sensor, &mut cache);
+    let valid_before = cache.as_ref().map(|c| c.is_valid_for(&p, &sensor)).unwrap_or(false);
 
     p.vdd = 10.0;
-    let valid_after = cache.as_ref().map(|c| c.is_valid_for(&p)).unwrap_or(false);
+    let valid_after = cache.as_ref().map(|c| c.is_valid_for(&p, &sensor)).unwrap_or(false);
 
     print_result(
         "cache validity",
@@ -329,10 +303,11 @@ fn test_transfer_function_extraction() -> bool {
         transfer_function_resolution: 32,
         ..SpiceParams::default()
     };
-    let full_well = 40_000.0;
+    let sensor = SpiceSensor::default();
+    let full_well = sensor.full_well;
     let mut cache = None;
 
-    spice::simulate_or_cache(&params, full_well, &mut cache);
+    spice::simulate_or_cache(&params, &sensor, &mut cache);
     let c = cache.as_ref().unwrap();
 
     let has_points = c.transfer_curve.len() == 32;
@@ -386,7 +361,7 @@ fn test_ringing_kernel() -> bool {
         ..SpiceParams::default()
     };
     let mut cache = None;
-    spice::simulate_or_cache(&params, 40_000.0, &mut cache);
+    spice::simulate_or_cache(&params, &SpiceSensor::default(), &mut cache);
     let c = cache.as_ref().unwrap();
 
     let has_kernel = !c.ringing_kernel.is_empty();
@@ -730,12 +705,12 @@ fn test_simulation_timing() -> bool {
 
     let start = web_time::Instant::now();
     let mut cache = None;
-    spice::simulate_or_cache(&params, 40_000.0, &mut cache);
+    spice::simulate_or_cache(&params, &SpiceSensor::default(), &mut cache);
     let first_run_ms = start.elapsed().as_secs_f64() * 1000.0;
 
     // Second run should be cached
     let start = web_time::Instant::now();
-    spice::simulate_or_cache(&params, 40_000.0, &mut cache);
+    spice::simulate_or_cache(&params, &SpiceSensor::default(), &mut cache);
     let cached_run_ms = start.elapsed().as_secs_f64() * 1000.0;
 
     let cache_faster = cached_run_ms < first_run_ms * 0.5 || cached_run_ms < 0.1;
@@ -0,0 +1,324 @@
+//! C API for embedding the pipeline (`capi` feature).
+//!
+//! Hosts such as OBS filters or After Effects plugins create a `CcdParams`,
+//! set fields by their `values()` names, optionally build a `CcdSpiceCache`,
+//! and process 8-bit RGB or RGBA buffers. `include/physical_ccd_glitch.h` is
+//! generated from this file by the build script.
+//!
+//! Functions return `CCD_OK` or a negative status; `ccd_last_error` then
+//! describes the failure. Handles may be used from any thread but not from
+//! two threads at once.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::pipeline::{self, PipelineParams};
+use crate::spice::{self, SpiceCache};
+
+pub const CCD_OK: i32 = 0;
+/// A required pointer was null.
+pub const CCD_ERR_NULL: i32 = -1;
+/// Unknown parameter name or unparsable value.
+pub const CCD_ERR_INVALID: i32 = -2;
+/// An image is too small or has an unsupported channel count.
+pub const CCD_ERR_SIZE: i32 = -3;
+/// The pipeline panicked; the handles are still usable.
+pub const CCD_ERR_PANIC: i32 = -4;
+
+/// Opaque pipeline parameters.
+pub struct CcdParams(PipelineParams);
+
+/// Opaque SPICE simulation results.
+pub struct CcdSpiceCache(Option<SpiceCache>);
+
+/// An 8-bit interleaved image. `stride` is bytes per row; 0 means tightly
+/// packed. `channels` is 3 (RGB) or 4 (RGBA).
+#[repr(C)]
+pub struct CcdImage {
+    pub data: *mut u8,
+    pub width: u32,
+    pub height: u32,
+    pub stride: u32,
+    pub channels: u32,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn fail(status: i32, message: impl Into<String>) -> i32 {
+    let message = CString::new(message.into()).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+    status
+}
+
+/// Run `f`, turning a panic into `CCD_ERR_PANIC`.
+fn guard(f: impl FnOnce() -> i32) -> i32 {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| fail(CCD_ERR_PANIC, "pipeline panicked"))
+}
+
+/// Message for the last failed call on this thread. Valid until the next
+/// failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn ccd_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+/// Parameters at their defaults. Free with `ccd_params_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn ccd_params_new() -> *mut CcdParams {
+    Box::into_raw(Box::new(CcdParams(PipelineParams::default())))
+}
+
+/// Copy of `params`, or null if `params` is null.
+///
+/// # Safety
+/// `params` must be null or a live handle from this API.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ccd_params_clone(params: *const CcdParams) -> *mut CcdParams {
+    match unsafe { params.as_ref() } {
+        Some(p) => Box::into_raw(Box::new(CcdParams(p.0.clone()))),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+/// `params` must be null or a handle from this API not already freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ccd_params_free(params: *mut CcdParams) {
+    if !params.is_null() {
+        drop(unsafe { Box::from_raw(params) });
+    }
+}
+
+/// Set a parameter by name (as listed by `--set` and the "Changed from
+/// Default" readout) from its text form, e.g. `"v_glitch_rate"`, `"0.02"`.
+///
+/// # Safety
+/// `params` must be a live handle; `name` and `value` nul-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ccd_params_set(
+    params: *mut CcdParams,
+    name: *const c_char,
+    value: *const c_char,
+) -> i32 {
+    let Some(params) = (unsafe { params.as_mut() }) else {
+        return fail(CCD_ERR_NULL, "params is null");
+    };
+    let (Some(name), Some(value)) = (unsafe { c_str(name) }, unsafe { c_str(value) }) else {
+        return fail(CCD_ERR_NULL, "name or value is null");
+    };
+    match params.0.set_value(&name, &value) {
+        Ok(()) => CCD_OK,
+        Err(e) => fail(CCD_ERR_INVALID, e),
+    }
+}
+
+/// Write a parameter's current value as text into `buf` (`len` bytes,
+/// always nul-terminated when `len > 0`). Returns the full length of the
+/// text, excluding the nul, so a return `>= len` means it was truncated.
+///
+/// # Safety
+/// `params` must be a live handle, `name` a nul-terminated string, and `buf`
+/// null or valid for `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ccd_params_get(
+    params: *const CcdParams,
+    name: *const c_char,
+    buf: *mut c_char,
+    len: usize,
+) -> i32 {
+    let Some(params) = (unsafe { params.as_ref() }) else {
+        return fail(CCD_ERR_NULL, "params is null");
+    };
+    let Some(name) = (unsafe { c_str(name) }) else {
+        return fail(CCD_ERR_NULL, "name is null");
+    };
+    let Some((_, value)) = params.0.values().into_iter().find(|(n, _)| *n == name) else {
+        return fail(CCD_ERR_INVALID, format!("Unknown parameter: {name}"));
+    };
+    let text = value.to_string();
+    if !buf.is_null() && len > 0 {
+        let n = text.len().min(len - 1);
+        // SAFETY: `buf` holds `len` bytes and `n < len`.
+        unsafe {
+            std::ptr::copy_nonoverlapping(text.as_ptr(), buf.cast::<u8>(), n);
+            *buf.add(n) = 0;
+        }
+    }
+    text.len() as i32
+}
+
+/// Size of the image `ccd_process` produces: the sensor size, widened for
+/// anamorphic output and scaled up when a stack is drizzled.
+///
+/// # Safety
+/// `params` must be a live handle; `width` and `height` writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ccd_output_size(
+    params: *const CcdParams,
+    width: *mut u32,
+    height: *mut u32,
+) -> i32 {
+    let Some(params) = (unsafe { params.as_ref() }) else {
+        return fail(CCD_ERR_NULL, "params is null");
+    };
+    if width.is_null() || height.is_null() {
+        return fail(CCD_ERR_NULL, "width or height is null");
+    }
+    let p = &params.0;
+    let w = pipeline::anamorphic_width(p).unwrap_or(p.sensor_width as usize);
+    let (w, h) = pipeline::output_size(p, w, p.sensor_height as usize);
+    unsafe {
+        *width = w as u32;
+        *height = h as u32;
+    }
+    CCD_OK
+}
+
+/// Run the SPICE circuit simulations for `params` (slow: seconds). The
+/// cache stays valid while the `spice.*` parameters and full well are
+/// unchanged. Free with `ccd_spice_cache_free`.
+///
+/// # Safety
+/// `params` must be null or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ccd_spice_cache_new(params: *const CcdParams) -> *mut CcdSpiceCache {
+    let Some(params) = (unsafe { params.as_ref() }) else {
+        fail(CCD_ERR_NULL, "params is null");
+        return std::ptr::null_mut();
+    };
+    let p = &params.0;
+    let mut cache = None;
+    let status = guard(|| {
+        spice::simulate_or_cache(&p.spice, &p.spice_sensor(), &mut cache);
+        CCD_OK
+    });
+    if status != CCD_OK {
+        return std::ptr::null_mut();
+    }
+    Box::into_raw(Box::new(CcdSpiceCache(cache)))
+}
+
+/// # Safety
+/// `cache` must be null or a handle from this API not already freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ccd_spice_cache_free(cache: *mut CcdSpiceCache) {
+    if !cache.is_null() {
+        drop(unsafe { Box::from_raw(cache) });
+    }
+}
+
+/// Process `input` into `output`. `output` must be allocated at
+/// `ccd_output_size`; for RGBA output the alpha channel follows the
+/// `alpha_mode` parameter, or is opaque when the input has none. `cache`
+/// may be null, in which case SPICE modes fall back to the math stages.
+///
+/// # Safety
+/// `params` must be a live handle, `cache` null or a live handle, and each
+/// image's `data` valid for `height` rows of `stride` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ccd_process(
+    params: *const CcdParams,
+    cache: *const CcdSpiceCache,
+    input: *const CcdImage,
+    output: *mut CcdImage,
+) -> i32 {
+    let (Some(params), Some(input), Some(output)) =
+        (unsafe { params.as_ref() }, unsafe { input.as_ref() }, unsafe { output.as_mut() })
+    else {
+        return fail(CCD_ERR_NULL, "params, input or output is null");
+    };
+    if input.data.is_null() || output.data.is_null() {
+        return fail(CCD_ERR_NULL, "image data is null");
+    }
+    let no_cache = None;
+    let cache = unsafe { cache.as_ref() }.map_or(&no_cache, |c| &c.0);
+
+    let Some(source) = (unsafe { read_image(input) }) else {
+        return fail(CCD_ERR_SIZE, "input must be 3 or 4 channels with stride >= width * channels");
+    };
+    let (mut out_w, mut out_h) = (0, 0);
+    unsafe { ccd_output_size(params, &mut out_w, &mut out_h) };
+    if output.width != out_w || output.height != out_h || !matches!(output.channels, 3 | 4) {
+        return fail(
+            CCD_ERR_SIZE,
+            format!("output must be {out_w}x{out_h} with 3 or 4 channels"),
+        );
+    }
+    let out_channels = output.channels as usize;
+    let out_stride = row_stride(output);
+    if out_stride < out_w as usize * out_channels {
+        return fail(CCD_ERR_SIZE, "output stride is smaller than a row");
+    }
+
+    guard(|| {
+        let (w, h, rgb) = pipeline::process(&source, &params.0, cache);
+        // The rows below are only checked to hold the size promised above
+        if (w, h) != (out_w as usize, out_h as usize) {
+            return fail(CCD_ERR_SIZE, format!("rendered {w}x{h}, not {out_w}x{out_h}"));
+        }
+        let alpha = if out_channels == 4 {
+            pipeline::process_alpha(&source, &params.0)
+        } else {
+            None
+        };
+        for y in 0..h {
+            // SAFETY: checked above that each row holds `w * out_channels` bytes.
+            let row = unsafe {
+                std::slice::from_raw_parts_mut(output.data.add(y * out_stride), w * out_channels)
+            };
+            for x in 0..w {
+                let i = y * w + x;
+                let px = &mut row[x * out_channels..(x + 1) * out_channels];
+                px[..3].copy_from_slice(&rgb[i * 3..i * 3 + 3]);
+                if out_channels == 4 {
+                    px[3] = alpha.as_ref().map_or(255, |a| a[i]);
+                }
+            }
+        }
+        CCD_OK
+    })
+}
+
+fn row_stride(image: &CcdImage) -> usize {
+    if image.stride == 0 {
+        image.width as usize * image.channels as usize
+    } else {
+        image.stride as usize
+    }
+}
+
+/// Copy a host image into an owned `DynamicImage`.
+unsafe fn read_image(image: &CcdImage) -> Option<image::DynamicImage> {
+    let (w, h) = (image.width, image.height);
+    let channels = image.channels as usize;
+    let stride = row_stride(image);
+    if !matches!(channels, 3 | 4) || stride < w as usize * channels || w == 0 || h == 0 {
+        return None;
+    }
+    let mut data = Vec::with_capacity(w as usize * h as usize * channels);
+    for y in 0..h as usize {
+        // SAFETY: the caller guarantees `height` rows of `stride` bytes.
+        let row = unsafe {
+            std::slice::from_raw_parts(image.data.add(y * stride), w as usize * channels)
+        };
+        data.extend_from_slice(row);
+    }
+    if channels == 3 {
+        image::RgbImage::from_raw(w, h, data).map(image::DynamicImage::ImageRgb8)
+    } else {
+        image::RgbaImage::from_raw(w, h, data).map(image::DynamicImage::ImageRgba8)
+    }
+}
+
+/// Borrow a C string as UTF-8 (lossily), or `None` when null.
+unsafe fn c_str(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        // SAFETY: caller guarantees a nul-terminated string.
+        Some(unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned())
+    }
+}
@@ -1,5 +1,12 @@
-use rand::Rng;
-use rand_distr::{Distribution, Normal};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::ccd::amplifier::H_BLANK_PIXELS;
+use crate::ccd::noise::gaussian;
+use crate::ccd::readout::{RowClock, RowStage};
+use crate::ccd::transfer::ReadoutDirection;
+use crate::ccd::units::{AdcGain, Adu, Electrons};
+use crate::color::dither::{self, Dither};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CdsMode {
@@ -8,86 +15,687 @@ pub enum CdsMode {
     Partial,
 }
 
-/// Simulate ADC conversion: voltage → digital counts.
-pub fn apply_adc(
-    grid: &mut [f64],
-    _width: usize,
-    _height: usize,
-    bit_depth: u8,
-    cds_mode: CdsMode,
-    adc_gain: f64,
-    bias: f64,
-    reset_noise_sigma: f64,
-    dnl_errors: f64,
-    bit_errors: f64,
-    jitter: f64,
-) {
-    let mut rng = rand::rng();
-    let max_code = ((1u64 << bit_depth) - 1) as f64;
+impl CdsMode {
+    pub const ALL: &[CdsMode] = &[CdsMode::On, CdsMode::Off, CdsMode::Partial];
 
-    // Pre-generate DNL lookup if needed
-    let dnl_table = if dnl_errors > 0.0 {
-        generate_dnl_table(bit_depth, dnl_errors, &mut rng)
-    } else {
-        Vec::new()
-    };
+    pub fn name(self) -> &'static str {
+        match self {
+            CdsMode::On => "On",
+            CdsMode::Off => "Off",
+            CdsMode::Partial => "Partial",
+        }
+    }
+}
 
-    for pixel in grid.iter_mut() {
-        let mut val = *pixel;
+/// What the ADC does with an input beyond full scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdcOverflow {
+    /// Saturate at the top code.
+    Clip,
+    /// Reflect back down from full scale, as a comparator past its range
+    /// inverts: the brightest highlights read darker than their surround.
+    FoldOver,
+    /// Roll over to the bottom of the range and count up again, as a bipolar
+    /// converter wrapping from positive to negative full scale.
+    NegativeWrap,
+}
 
-        // CDS: remove (or partially remove) reset noise
-        match cds_mode {
-            CdsMode::On => {
-                // CDS removes reset noise — no extra noise added
+impl AdcOverflow {
+    pub const ALL: &[AdcOverflow] =
+        &[AdcOverflow::Clip, AdcOverflow::FoldOver, AdcOverflow::NegativeWrap];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AdcOverflow::Clip => "Clip",
+            AdcOverflow::FoldOver => "Fold-Over",
+            AdcOverflow::NegativeWrap => "Negative Wrap",
+        }
+    }
+
+    /// Bring `code` above `max_code` back into range. Codes in range, and
+    /// clipped ones, are left for quantization to clamp.
+    fn apply(self, code: f64, max_code: f64) -> f64 {
+        if code <= max_code {
+            return code;
+        }
+        match self {
+            AdcOverflow::Clip => code,
+            AdcOverflow::FoldOver => {
+                let t = code.rem_euclid(2.0 * max_code);
+                if t > max_code { 2.0 * max_code - t } else { t }
             }
-            CdsMode::Off => {
-                // Without CDS, reset noise dominates
-                if reset_noise_sigma > 0.0 {
-                    let noise = Normal::new(0.0, reset_noise_sigma).unwrap();
-                    val += noise.sample(&mut rng);
+            AdcOverflow::NegativeWrap => code.rem_euclid(max_code + 1.0),
+        }
+    }
+}
+
+/// Dual-gain readout: each pixel is digitized through a high-gain and a
+/// low-gain path and the two are stitched into one word in high-gain codes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DualGain {
+    pub enabled: bool,
+    /// Gain ratio between the paths; low-gain codes are this many high-gain
+    /// codes wide.
+    pub ratio: f64,
+    /// Switch point as a fraction of full scale: above it the low-gain
+    /// reading replaces the high-gain one.
+    pub threshold: f64,
+    /// Fractional gain error of the low-gain path after scaling, which puts
+    /// a step in the response at the switch point.
+    pub stitch_error: f64,
+}
+
+impl Default for DualGain {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ratio: 8.0,
+            threshold: 0.5,
+            stitch_error: 0.0,
+        }
+    }
+}
+
+/// Replace charge above the dual-gain switch point with the low-gain path's
+/// reading: quantized `ratio` codes at a time and off by the stitch error.
+/// Shadows keep the fine high-gain steps, highlights posterize, and a gain
+/// mismatch leaves a seam along every contour at the switch level. Runs on
+/// electrons just before the `Converter`.
+pub fn apply_dual_gain(grid: &mut [f64], adc_gain: AdcGain, bit_depth: u8, dual: &DualGain) {
+    if !dual.enabled {
+        return;
+    }
+    let max_code = ((1u64 << bit_depth) - 1) as f64;
+    let switch = dual.threshold.clamp(0.0, 1.0) * max_code * adc_gain.e_per_adu;
+    let step = dual.ratio.max(1.0) * adc_gain.e_per_adu;
+    let scale = 1.0 + dual.stitch_error;
+    for pixel in grid.iter_mut() {
+        if *pixel > switch {
+            *pixel = (*pixel / step).round() * step * scale;
+        }
+    }
+}
+
+/// How a `Converter` turns electrons into codes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdcParams {
+    pub bit_depth: u8,
+    pub cds_mode: CdsMode,
+    pub gain: AdcGain,
+    /// Offset added to every code.
+    pub bias: Adu,
+    /// Reset noise on the sense node, removed as far as `cds_mode` allows.
+    pub reset_noise: Electrons,
+    /// Differential nonlinearity strength; 0 for evenly spaced codes.
+    pub dnl_errors: f64,
+    /// Chance of each bit flipping, in percent.
+    pub bit_errors: f64,
+    pub dither: Dither,
+    pub overflow: AdcOverflow,
+}
+
+impl AdcParams {
+    /// The same converter as an ideal quantizer: no reset noise, nonlinearity
+    /// or bit errors, clipping at full scale.
+    pub fn ideal(self) -> Self {
+        Self {
+            reset_noise: Electrons(0.0),
+            dnl_errors: 0.0,
+            bit_errors: 0.0,
+            overflow: AdcOverflow::Clip,
+            ..self
+        }
+    }
+}
+
+/// Simulate ADC conversion, a row at a time: electron-referred signal
+/// through `apply_dual_gain`, then to digital counts.
+pub struct Converter<R> {
+    adc: AdcParams,
+    dual: DualGain,
+    max_code: f64,
+    /// DNL lookup, empty for evenly spaced codes.
+    dnl_table: Vec<u32>,
+    /// Quantization error diffused down into the next row.
+    diffused: Vec<f64>,
+    rng: R,
+}
+
+impl<R: Rng> Converter<R> {
+    pub fn new(adc: &AdcParams, dual: &DualGain, mut rng: R) -> Self {
+        let dnl_table = if adc.dnl_errors > 0.0 {
+            generate_dnl_table(adc.bit_depth, adc.dnl_errors, &mut rng)
+        } else {
+            Vec::new()
+        };
+        Self {
+            adc: *adc,
+            dual: *dual,
+            max_code: ((1u64 << adc.bit_depth) - 1) as f64,
+            dnl_table,
+            diffused: Vec::new(),
+            rng,
+        }
+    }
+}
+
+impl<R: Rng> RowStage for Converter<R> {
+    fn read_row(&mut self, row: &mut [f64], clock: &RowClock) {
+        let AdcParams {
+            bit_depth,
+            cds_mode,
+            gain: adc_gain,
+            bias,
+            reset_noise,
+            bit_errors,
+            dither,
+            overflow,
+            ..
+        } = self.adc;
+        let (max_code, rng) = (self.max_code, &mut self.rng);
+        apply_dual_gain(row, adc_gain, bit_depth, &self.dual);
+
+        let reset_noise_sigma = reset_noise.0;
+        for pixel in row.iter_mut() {
+            let mut val = *pixel;
+
+            // CDS: remove (or partially remove) reset noise
+            match cds_mode {
+                CdsMode::On => {
+                    // CDS removes reset noise — no extra noise added
+                }
+                CdsMode::Off => {
+                    // Without CDS, reset noise dominates
+                    if reset_noise_sigma > 0.0 {
+                        val += reset_noise_sigma * gaussian(rng);
+                    }
                 }
+                CdsMode::Partial => {
+                    // Partial CDS: some reset noise leaks through
+                    if reset_noise_sigma > 0.0 {
+                        val += reset_noise_sigma * 0.3 * gaussian(rng);
+                    }
+                }
+            }
+
+            // Apply ADC gain (electrons per ADU) and bias, then the input range
+            let code = adc_gain.adu(Electrons(val)).0 + bias.0;
+            *pixel = overflow.apply(code, max_code);
+        }
+
+        // Quantize to integer codes
+        self.diffused.resize(row.len(), 0.0);
+        dither::quantize_row(row, clock.y, &mut self.diffused, max_code, dither, rng);
+
+        for pixel in row.iter_mut() {
+            let mut val = *pixel;
+
+            // Apply DNL (differential nonlinearity) errors
+            if let Some(&code) = self.dnl_table.get(val as usize) {
+                val = code as f64;
             }
-            CdsMode::Partial => {
-                // Partial CDS: some reset noise leaks through
-                if reset_noise_sigma > 0.0 {
-                    let noise = Normal::new(0.0, reset_noise_sigma * 0.3).unwrap();
-                    val += noise.sample(&mut rng);
+
+            // Apply bit errors: random flips in specific bit planes
+            if bit_errors > 0.0 {
+                let mut code = val as u64;
+                for bit in 0..bit_depth {
+                    if rng.random::<f64>() < bit_errors * 0.01 {
+                        code ^= 1 << bit;
+                    }
                 }
+                val = (code as f64).min(max_code);
             }
+
+            *pixel = val;
         }
+    }
+}
 
-        // ADC jitter: random timing variation smears the digitization
-        if jitter > 0.0 {
-            let jitter_noise = Normal::new(0.0, jitter).unwrap();
-            val += jitter_noise.sample(&mut rng);
+/// Column-parallel conversion: one converter per column instead of a single
+/// serial ADC, each with its own offset and gain error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnAdc {
+    pub enabled: bool,
+    /// Standard deviation of the per-column offset, in codes.
+    pub offset: f64,
+    /// Standard deviation of the per-column gain error, as a fraction.
+    pub gain: f64,
+    /// Fraction of columns whose converter has failed, stuck at zero or at
+    /// full scale.
+    pub dead_rate: f64,
+    /// Pattern seed; the same seed always gives the same columns.
+    pub seed: u64,
+}
+
+impl Default for ColumnAdc {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            offset: 2.0,
+            gain: 0.005,
+            dead_rate: 0.0,
+            seed: 1,
         }
+    }
+}
 
-        // Apply ADC gain (electrons per ADU) and bias
-        val = val / adc_gain.max(0.001) + bias;
+/// Convert each column through its own converter: a fixed pattern of
+/// vertical stripes from the offset and gain spread, plus solid black or
+/// white lines where a converter is dead. Runs on codes in readout frame
+/// columns.
+pub fn apply_column_adc(grid: &mut [f64], width: usize, bit_depth: u8, column_adc: &ColumnAdc) {
+    if !column_adc.enabled || width == 0 {
+        return;
+    }
+    let max_code = ((1u64 << bit_depth) - 1) as f64;
+    let mut rng = StdRng::seed_from_u64(column_adc.seed);
+    let (offset, gain) = (column_adc.offset.max(0.0), column_adc.gain.max(0.0));
+    // (gain, offset, stuck code) per column
+    let columns: Vec<(f64, f64, Option<f64>)> = (0..width)
+        .map(|_| {
+            let dead = rng.random::<f64>() < column_adc.dead_rate;
+            let stuck = if rng.random::<bool>() { max_code } else { 0.0 };
+            let gain = 1.0 + gain * gaussian(&mut rng);
+            (gain, offset * gaussian(&mut rng), dead.then_some(stuck))
+        })
+        .collect();
+    for row in grid.chunks_mut(width) {
+        for (pixel, &(gain, offset, stuck)) in row.iter_mut().zip(&columns) {
+            *pixel = stuck.unwrap_or_else(|| (*pixel * gain + offset).round().clamp(0.0, max_code));
+        }
+    }
+}
 
-        // Quantize to integer codes
-        val = val.round().clamp(0.0, max_code);
+/// Optical-black clamp servo. After each row the overscan pixels are averaged
+/// and the black estimate is pulled toward them; the next row is shifted by
+/// the difference from bias.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlackClamp {
+    pub enabled: bool,
+    /// Fraction of the estimate error corrected per row: below 1 the loop is
+    /// sluggish, between 1 and 2 it overshoots and rings.
+    pub loop_gain: f64,
+    /// Fraction of the row's signal above bias that leaks into its overscan.
+    pub leak: f64,
+    /// Noise on the overscan average, in codes.
+    pub noise: f64,
+}
 
-        // Apply DNL (differential nonlinearity) errors
-        if !dnl_table.is_empty() {
-            let code = val as usize;
-            if code < dnl_table.len() {
-                val = dnl_table[code] as f64;
-            }
+impl Default for BlackClamp {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            loop_gain: 1.0,
+            leak: 0.02,
+            noise: 0.5,
         }
+    }
+}
+
+/// Run the black clamp down the frame. A slow loop leaves dark tails below
+/// bright rows while the estimate recovers; an oscillating loop turns every
+/// disturbance into alternating row banding.
+pub struct ClampLoop<R> {
+    max_code: f64,
+    bias: f64,
+    clamp: BlackClamp,
+    /// The loop's current estimate of the black level.
+    black: f64,
+    rng: R,
+}
 
-        // Apply bit errors: random flips in specific bit planes
-        if bit_errors > 0.0 {
-            let mut code = val as u64;
-            for bit in 0..bit_depth {
-                if rng.random::<f64>() < bit_errors * 0.01 {
-                    code ^= 1 << bit;
+impl<R: Rng> ClampLoop<R> {
+    pub fn new(bit_depth: u8, bias: Adu, clamp: &BlackClamp, rng: R) -> Self {
+        Self {
+            max_code: ((1u64 << bit_depth) - 1) as f64,
+            bias: bias.0,
+            clamp: *clamp,
+            black: bias.0,
+            rng,
+        }
+    }
+}
+
+impl<R: Rng> RowStage for ClampLoop<R> {
+    fn read_row(&mut self, row: &mut [f64], _clock: &RowClock) {
+        let clamp = &self.clamp;
+        if !clamp.enabled || row.is_empty() {
+            return;
+        }
+        let offset = self.black - self.bias;
+        let mean = row.iter().sum::<f64>() / row.len() as f64;
+        for pixel in row.iter_mut() {
+            *pixel = (*pixel - offset).round().clamp(0.0, self.max_code);
+        }
+        let noise = clamp.noise.max(0.0) * gaussian(&mut self.rng);
+        let overscan = self.bias + clamp.leak * (mean - self.bias) + noise;
+        self.black += clamp.loop_gain.clamp(0.0, 2.0) * (overscan - self.black);
+    }
+}
+
+/// Piecewise-linear companding of the output word, as sensors use to fit a
+/// wide linear range into fewer bits.
+///
+/// The companded range is split into `knees + 1` equal segments, each
+/// `ratio` times shallower than the one below, so shadows keep fine steps
+/// and highlights share coarse ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Companding {
+    /// Knee points; 0 leaves the output linear.
+    pub knees: usize,
+    /// Slope ratio between consecutive segments.
+    pub ratio: f64,
+    /// Fractional error of the decompanding knee levels: output jumps at each
+    /// knee and contour bands form around it.
+    pub mismatch: f64,
+}
+
+impl Default for Companding {
+    fn default() -> Self {
+        Self {
+            knees: 0,
+            ratio: 4.0,
+            mismatch: 0.0,
+        }
+    }
+}
+
+impl Companding {
+    /// Segment ends as (linear code, companded code), from zero to full
+    /// scale. The `receiver`'s knee levels are off by the mismatch.
+    fn points(&self, max_code: f64, receiver: bool) -> Vec<(f64, f64)> {
+        let n = self.knees;
+        let ratio = self.ratio.max(1.0);
+        let step = max_code / (n + 1) as f64;
+        // First slope chosen so the linear range ends at full scale
+        let first = (0..=n).map(|k| ratio.powi(k as i32)).sum::<f64>() / (n + 1) as f64;
+        let scale = if receiver { 1.0 + self.mismatch.clamp(-0.5, 0.5) } else { 1.0 };
+        let (mut x, mut y) = (0.0, 0.0);
+        let mut points = vec![(x, y)];
+        for k in 0..=n {
+            let slope = first / ratio.powi(k as i32);
+            let top = if k == n {
+                max_code
+            } else {
+                ((k + 1) as f64 * step * scale).clamp(y, max_code)
+            };
+            x += (top - y) / slope;
+            y = top;
+            points.push((x, y));
+        }
+        points
+    }
+}
+
+/// Interpolate along `points`, from the linear side when `forward` and from
+/// the companded side otherwise, extending the last segment past its end.
+fn follow_knees(points: &[(f64, f64)], v: f64, forward: bool) -> f64 {
+    let key = |p: &(f64, f64)| if forward { p.0 } else { p.1 };
+    let i = points[1..points.len() - 1]
+        .iter()
+        .position(|p| v <= key(p))
+        .unwrap_or(points.len() - 2);
+    let (a, b) = (points[i], points[i + 1]);
+    let (from, to) = if forward { ((a.0, b.0), (a.1, b.1)) } else { ((a.1, b.1), (a.0, b.0)) };
+    let span = from.1 - from.0;
+    if span <= 0.0 {
+        return to.0;
+    }
+    to.0 + (v - from.0) * (to.1 - to.0) / span
+}
+
+/// Compress linear codes onto the companding knees and requantize.
+pub fn compand(grid: &mut [f64], bit_depth: u8, companding: &Companding) {
+    if companding.knees == 0 {
+        return;
+    }
+    let max_code = ((1u64 << bit_depth) - 1) as f64;
+    let points = companding.points(max_code, false);
+    for pixel in grid.iter_mut() {
+        *pixel = follow_knees(&points, *pixel, true).round().clamp(0.0, max_code);
+    }
+}
+
+/// Expand companded codes back to linear ones with the receiver's knees.
+pub fn decompand(grid: &mut [f64], bit_depth: u8, companding: &Companding) {
+    if companding.knees == 0 {
+        return;
+    }
+    let max_code = ((1u64 << bit_depth) - 1) as f64;
+    let points = companding.points(max_code, true);
+    for pixel in grid.iter_mut() {
+        *pixel = follow_knees(&points, *pixel, false).round().clamp(0.0, max_code);
+    }
+}
+
+/// How the ADC writes each code onto its output bits.
+///
+/// For a unipolar signal binary and offset binary words are bit-identical;
+/// offset binary only differs in reading mid-scale as zero. Two's complement
+/// is offset binary with the top bit inverted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdcEncoding {
+    Binary,
+    OffsetBinary,
+    Gray,
+    TwosComplement,
+}
+
+impl AdcEncoding {
+    pub const ALL: &[AdcEncoding] = &[
+        AdcEncoding::Binary,
+        AdcEncoding::OffsetBinary,
+        AdcEncoding::Gray,
+        AdcEncoding::TwosComplement,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AdcEncoding::Binary => "Binary",
+            AdcEncoding::OffsetBinary => "Offset Binary",
+            AdcEncoding::Gray => "Gray Code",
+            AdcEncoding::TwosComplement => "Two's Complement",
+        }
+    }
+
+    /// The encoding a receiver is most likely to mistake this one for.
+    fn confused_with(self) -> AdcEncoding {
+        match self {
+            AdcEncoding::Binary => AdcEncoding::Gray,
+            AdcEncoding::OffsetBinary => AdcEncoding::TwosComplement,
+            AdcEncoding::Gray => AdcEncoding::Binary,
+            AdcEncoding::TwosComplement => AdcEncoding::OffsetBinary,
+        }
+    }
+
+    fn encode(self, code: u64, msb: u64) -> u64 {
+        match self {
+            AdcEncoding::Binary | AdcEncoding::OffsetBinary => code,
+            AdcEncoding::Gray => code ^ (code >> 1),
+            AdcEncoding::TwosComplement => code ^ msb,
+        }
+    }
+
+    fn decode(self, word: u64, msb: u64) -> u64 {
+        match self {
+            AdcEncoding::Binary | AdcEncoding::OffsetBinary => word,
+            AdcEncoding::Gray => {
+                let mut code = word;
+                let mut shift = word >> 1;
+                while shift != 0 {
+                    code ^= shift;
+                    shift >>= 1;
                 }
+                code
             }
-            val = (code as f64).min(max_code);
+            AdcEncoding::TwosComplement => word ^ msb,
+        }
+    }
+}
+
+/// Write codes out in `encoding` and read them back. A receiver decoding
+/// them correctly sees no change; with `wrong_decode` it assumes the
+/// encoding most easily confused with it, so two's complement read as offset
+/// binary tears the frame at mid-scale and Gray code read as binary scrambles
+/// every level into bands.
+pub fn apply_output_encoding(
+    grid: &mut [f64],
+    bit_depth: u8,
+    encoding: AdcEncoding,
+    wrong_decode: bool,
+) {
+    if !wrong_decode {
+        return;
+    }
+    let max_code = (1u64 << bit_depth) - 1;
+    let msb = 1u64 << (bit_depth.max(1) - 1);
+    let decoding = encoding.confused_with();
+    for pixel in grid.iter_mut() {
+        let code = (pixel.round().max(0.0) as u64).min(max_code);
+        *pixel = decoding.decode(encoding.encode(code, msb), msb) as f64;
+    }
+}
+
+/// Sag of the ADC reference as conversions draw charge from it.
+///
+/// Each sample takes charge in proportion to its code and the reference
+/// recovers with a time constant of `recovery` pixels, so codes read after
+/// bright content come out low and a bright object leaves a dark wake along
+/// the row in serial readout order. `droop` is the steady sag, as a fraction
+/// of the code, under a run of full-scale samples. The reference keeps
+/// recovering through each row's blanking: a short time constant settles
+/// between rows, a long one carries the sag into the start of the next.
+pub struct ReferenceDroop {
+    decay: f64,
+    draw: f64,
+    sag: f64,
+}
+
+impl ReferenceDroop {
+    pub fn new(bit_depth: u8, droop: f64, recovery: f64) -> Self {
+        let max_code = ((1u64 << bit_depth) - 1) as f64;
+        let decay = libm::exp(-1.0 / recovery.max(0.1));
+        Self {
+            decay,
+            draw: droop.clamp(0.0, 1.0) * (1.0 - decay) / max_code,
+            sag: 0.0,
         }
+    }
+}
+
+impl RowStage for ReferenceDroop {
+    fn read_row(&mut self, row: &mut [f64], clock: &RowClock) {
+        if self.draw <= 0.0 {
+            return;
+        }
+        self.sag *= libm::pow(self.decay, H_BLANK_PIXELS as f64);
+        let (decay, draw) = (self.decay, self.draw);
+        let sag = &mut self.sag;
+        clock.for_each(row, |_, code| {
+            let read = *code;
+            *code = (read * (1.0 - *sag)).round().max(0.0);
+            *sag = *sag * decay + draw * read;
+        });
+    }
+}
 
-        *pixel = val;
+/// Sample each pixel at a randomly displaced instant, as an ADC clock with
+/// phase noise would. Every sample moves along its row by a Gaussian offset
+/// of `jitter` pixel periods (standard deviation) and reads the signal
+/// interpolated between its neighbors: flat areas are untouched while edges
+/// turn ragged.
+pub struct SampleJitter<R> {
+    jitter: f64,
+    /// The row as it arrived, read from while the row is rewritten.
+    held: Vec<f64>,
+    rng: R,
+}
+
+impl<R: Rng> SampleJitter<R> {
+    pub fn new(jitter: f64, rng: R) -> Self {
+        Self {
+            jitter,
+            held: Vec::new(),
+            rng,
+        }
+    }
+}
+
+impl<R: Rng> RowStage for SampleJitter<R> {
+    fn read_row(&mut self, row: &mut [f64], _clock: &RowClock) {
+        let width = row.len();
+        if self.jitter <= 0.0 || width < 2 {
+            return;
+        }
+        self.held.clear();
+        self.held.extend_from_slice(row);
+        for (x, code) in row.iter_mut().enumerate() {
+            let t = x as f64 + self.jitter * gaussian(&mut self.rng);
+            let t = t.clamp(0.0, (width - 1) as f64);
+            let x0 = (t.floor() as usize).min(width - 2);
+            let f = t - x0 as f64;
+            *code = (self.held[x0] * (1.0 - f) + self.held[x0 + 1] * f).round();
+        }
+    }
+}
+
+/// Resample the code stream as an ADC whose sample clock runs `error` fast
+/// (negative: slow) against the pixel clock would capture it.
+///
+/// Sample `i` of a row lands `i * (1 + error)` pixel clocks into the row,
+/// counted in serial readout order and interpolated between neighboring
+/// codes, so rows shrink or stretch. The phase also accumulates over the
+/// frame, each row lasting `width` plus the blanking interval: a free-running
+/// clock slides later rows sideways and columns lean, sampling the `blank`
+/// level past the row end. With `line_lock` the row sync absorbs whole
+/// samples and only the sub-sample phase carries over, so columns shimmer in
+/// place instead.
+pub fn apply_sample_clock_error(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    error: f64,
+    line_lock: bool,
+    direction: ReadoutDirection,
+    blank: f64,
+) {
+    if error == 0.0 || width == 0 {
+        return;
+    }
+    let src = grid.to_vec();
+    let row_clocks = width + H_BLANK_PIXELS;
+    let period = row_clocks as f64;
+    let rate = (1.0 + error).max(0.01);
+    let last_first = |y: usize| match direction {
+        ReadoutDirection::LeftToRight => true,
+        ReadoutDirection::RightToLeft => false,
+        ReadoutDirection::Alternating => y.is_multiple_of(2),
+    };
+    // Code at whole pixel clock `q` of the frame's serial stream
+    let code_at = |q: i64| -> f64 {
+        if q < 0 {
+            return blank;
+        }
+        let (row, i) = (q as usize / row_clocks, q as usize % row_clocks);
+        if row >= height || i >= width {
+            return blank;
+        }
+        let x = if last_first(row) { width - 1 - i } else { i };
+        src[row * width + x]
+    };
+
+    for y in 0..height {
+        let drift = y as f64 * period * error;
+        let phase = if line_lock { drift.rem_euclid(1.0) } else { drift };
+        let start = y as f64 * period + phase;
+        for i in 0..width {
+            let q = start + i as f64 * rate;
+            let q0 = q.floor();
+            let f = q - q0;
+            let code = code_at(q0 as i64) * (1.0 - f) + code_at(q0 as i64 + 1) * f;
+            let x = if last_first(y) { width - 1 - i } else { i };
+            grid[y * width + x] = code.round();
+        }
     }
 }
 
@@ -1,55 +1,225 @@
-use rand_distr::{Distribution, Normal};
+use rand::Rng;
 
-/// Apply output amplifier simulation.
+use super::noise::gaussian;
+use super::readout::{RowClock, RowStage};
+use super::units::{ConversionGain, Volts};
+
+/// Floating diffusion swing the output amplifier follows before it
+/// compresses: 40 ke- at the default 16 µV/e-.
+pub const LINEAR_RANGE: Volts = Volts(0.64);
+
+/// Output amplifier simulation, a row at a time.
 ///
 /// Converts electrons to voltage-like values, applies gain/nonlinearity/noise.
-pub fn apply_amplifier(
-    grid: &mut [f64],
-    width: usize,
+/// The nonlinearity spans the amplifier's input swing, so a sensor with a
+/// higher conversion gain reaches the compressed end with less charge.
+pub struct OutputAmplifier<R> {
     height: usize,
+    /// Charge that fills the amplifier's linear range
+    range: f64,
     gain: f64,
     nonlinearity: f64,
     reset_noise: f64,
     amp_glow: f64,
-) {
-    let mut rng = rand::rng();
+    /// Per readout row: how much brighter the glow is than when cold.
+    glow_scale: Vec<f64>,
+    drift: f64,
+    /// Baseline offset the drift has wandered to, in electrons.
+    offset: f64,
+    rng: R,
+}
 
-    // Find max value for normalization in nonlinearity
-    let max_val = grid.iter().cloned().fold(0.0f64, f64::max).max(1.0);
+impl<R: Rng> OutputAmplifier<R> {
+    pub fn new(
+        height: usize,
+        conversion_gain: ConversionGain,
+        gain: f64,
+        nonlinearity: f64,
+        reset_noise: f64,
+        amp_glow: f64,
+        rng: R,
+    ) -> Self {
+        Self {
+            height,
+            range: conversion_gain.electrons(LINEAR_RANGE).0.max(1.0),
+            gain,
+            nonlinearity,
+            reset_noise,
+            amp_glow,
+            glow_scale: Vec::new(),
+            drift: 0.0,
+            offset: 0.0,
+            rng,
+        }
+    }
 
-    for y in 0..height {
-        for x in 0..width {
-            let idx = y * width + x;
-            let mut val = grid[idx];
+    /// Let the output baseline wander as a random walk in readout time,
+    /// `drift` electrons RMS per row time, blanking included. The walk runs
+    /// on through the blanking between rows, so rows read close in time sit
+    /// at close levels and the frame fills with soft horizontal banding.
+    pub fn with_drift(mut self, drift: f64) -> Self {
+        self.drift = drift.max(0.0);
+        self
+    }
 
-            // Apply gain (linear scaling)
-            val *= gain;
-
-            // Apply nonlinearity: polynomial distortion
-            // 0.0 = perfectly linear, higher = more S-curve compression
-            if nonlinearity > 0.0 {
-                let normalized = (val / (max_val * gain)).clamp(0.0, 1.0);
-                let curved = apply_s_curve(normalized, nonlinearity);
-                val = curved * max_val * gain;
+    /// Scale the glow row by row as the amplifier warms through the readout,
+    /// by `thermal::HeatMap::amp_scale`.
+    pub fn with_glow_scale(mut self, scale: &[f64]) -> Self {
+        self.glow_scale = scale.to_vec();
+        self
+    }
+}
+
+impl<R: Rng> RowStage for OutputAmplifier<R> {
+    fn read_row(&mut self, row: &mut [f64], clock: &RowClock) {
+        let (width, height) = (clock.width, self.height);
+        // Drift steps per pixel clock, and over the blanking before this row
+        let period = RowClock::period(width) as f64;
+        let step = self.drift / period.sqrt();
+        if step > 0.0 && clock.y > 0 {
+            let blanking = step * (H_BLANK_PIXELS as f64).sqrt();
+            self.offset += blanking * gaussian(&mut self.rng);
+        }
+        for i in 0..width {
+            let x = clock.x(i);
+            let mut val = row[x];
+
+            // Apply nonlinearity: polynomial distortion over the FD swing
+            // 0.0 = perfectly linear, higher = more S-curve compression.
+            // Charge past the range continues at unit slope.
+            if self.nonlinearity > 0.0 {
+                let swing = (val / self.range).max(0.0);
+                let curved =
+                    apply_s_curve(swing.min(1.0), self.nonlinearity) + (swing - 1.0).max(0.0);
+                val = curved * self.range;
             }
 
+            // Apply gain (linear scaling)
+            val *= self.gain;
+
             // Reset noise (kTC): random offset per pixel
-            if reset_noise > 0.0 {
-                let noise_dist = Normal::new(0.0, reset_noise).unwrap();
-                val += noise_dist.sample(&mut rng);
+            if self.reset_noise > 0.0 {
+                val += self.reset_noise * gaussian(&mut self.rng);
             }
 
             // Amplifier glow: gradient from bottom-right corner (typical amp location)
-            if amp_glow > 0.0 {
+            if self.amp_glow > 0.0 {
                 let dx = (width as f64 - x as f64) / width as f64;
-                let dy = (height as f64 - y as f64) / height as f64;
+                let dy = (height as f64 - clock.y as f64) / height as f64;
                 let dist_sq = dx * dx + dy * dy;
-                let glow = amp_glow * 1000.0 / (1.0 + dist_sq * 50.0);
-                val += glow;
+                let glow = self.amp_glow * 1000.0 / (1.0 + dist_sq * 50.0);
+                val += glow * self.glow_scale.get(clock.y).copied().unwrap_or(1.0);
             }
 
-            grid[idx] = val.max(0.0);
+            // Baseline drift, one step per pixel clock
+            if step > 0.0 {
+                self.offset += step * gaussian(&mut self.rng);
+                val += self.offset;
+            }
+
+            row[x] = val.max(0.0);
+        }
+    }
+}
+
+/// Incomplete reset of the floating diffusion.
+///
+/// A short reset pulse cannot fully drain a heavily charged FD node: `lag`
+/// of the previous sample's excess over the new one is left behind, so the
+/// pixels read after a bright one carry a decaying echo of it. Unlike CTE
+/// trailing no charge is moved: the bright pixel keeps its value, flat areas
+/// are untouched, and only darker pixels that follow gain a comet tail.
+/// Each row starts clean, as the reset settles during the line overscan.
+pub struct ResetLag {
+    lag: f64,
+}
+
+impl ResetLag {
+    pub fn new(lag: f64) -> Self {
+        Self { lag: lag.min(0.99) }
+    }
+}
+
+impl RowStage for ResetLag {
+    fn read_row(&mut self, row: &mut [f64], clock: &RowClock) {
+        if self.lag <= 0.0 {
+            return;
+        }
+        let mut fd = 0.0f64;
+        clock.for_each(row, |_, v| {
+            *v += self.lag * (fd - *v).max(0.0);
+            fd = *v;
+        });
+    }
+}
+
+/// Pixel periods of horizontal blanking between rows, part of the row time
+/// an interference source or free-running clock keeps running through.
+pub(crate) const H_BLANK_PIXELS: usize = 32;
+
+/// Periodic pickup on the video line from a disturbance not synchronized to
+/// the readout clock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interference {
+    /// Peak amplitude in electrons; 0 disables.
+    pub amplitude: f64,
+    /// Disturbance frequency in cycles per row time (including blanking).
+    /// The fractional part sets how far the bands slide each row.
+    pub frequency: f64,
+    /// Per-row phase jitter, standard deviation in cycles.
+    pub jitter: f64,
+    /// Restart the disturbance at the same phase every frame, so the bands
+    /// stay put; otherwise they drift between renders.
+    pub locked: bool,
+}
+
+impl Default for Interference {
+    fn default() -> Self {
+        Self {
+            amplitude: 0.0,
+            frequency: 12.3,
+            jitter: 0.0,
+            locked: true,
+        }
+    }
+}
+
+/// Add interference banding in readout order.
+///
+/// A sine at `frequency` cycles per row beats against the row rate: an
+/// integer frequency gives vertical bars, a fractional one diagonal bands
+/// whose slope follows the fractional part. The disturbance runs on its own
+/// clock, its phase set by each sample's time in the readout.
+pub struct Pickup<R> {
+    interference: Interference,
+    frame_phase: f64,
+    rng: R,
+}
+
+impl<R: Rng> Pickup<R> {
+    pub fn new(interference: &Interference, mut rng: R) -> Self {
+        let frame_phase = if interference.locked { 0.0 } else { rng.random::<f64>() };
+        Self {
+            interference: *interference,
+            frame_phase,
+            rng,
+        }
+    }
+}
+
+impl<R: Rng> RowStage for Pickup<R> {
+    fn read_row(&mut self, row: &mut [f64], clock: &RowClock) {
+        let interference = &self.interference;
+        if interference.amplitude <= 0.0 {
+            return;
         }
+        let row_time = RowClock::period(clock.width) as f64;
+        let jitter = interference.jitter.max(0.0) * gaussian(&mut self.rng);
+        clock.for_each(row, |i, v| {
+            let phase = self.frame_phase + jitter;
+            let cycles = phase + interference.frequency * clock.time(i) / row_time;
+            *v += interference.amplitude * libm::sin(std::f64::consts::TAU * cycles);
+        });
     }
 }
 
@@ -57,7 +227,7 @@ pub fn apply_amplifier(
 fn apply_s_curve(x: f64, amount: f64) -> f64 {
     let linear = x;
     // Sigmoid-like S-curve centered at 0.5
-    let s = 1.0 / (1.0 + (-(x - 0.5) * (2.0 + amount * 10.0)).exp());
+    let s = 1.0 / (1.0 + libm::exp(-(x - 0.5) * (2.0 + amount * 10.0)));
     // Blend between linear and S-curve
     linear * (1.0 - amount) + s * amount
 }
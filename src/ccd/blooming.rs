@@ -1,25 +1,31 @@
+use crate::ccd::units::Electrons;
+
+/// Where and how hard excess charge spills.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bloom {
+    /// Full well capacity.
+    pub full_well: Electrons,
+    /// 0.0 = no anti-blooming drain (full bloom), 1.0 = perfect drain (no bloom).
+    pub abg_strength: f64,
+    /// Fraction of full_well at which blooming starts (0.0 to 1.0).
+    pub threshold: f64,
+    /// Bloom vertically (column direction) rather than horizontally.
+    pub vertical: bool,
+}
+
 /// Simulate blooming: excess charge spills vertically (or horizontally).
 ///
-/// - `abg_strength`: 0.0 = no anti-blooming drain (full bloom), 1.0 = perfect drain (no bloom)
-/// - `bloom_threshold`: fraction of full_well at which blooming starts (0.0 to 1.0)
-/// - `full_well`: full well capacity in electrons
-/// - `vertical`: if true, bloom vertically (column direction); if false, horizontally
-pub fn apply_blooming(
-    grid: &mut [f64],
-    width: usize,
-    height: usize,
-    full_well: f64,
-    abg_strength: f64,
-    bloom_threshold: f64,
-    vertical: bool,
-) {
-    let threshold = full_well * bloom_threshold.clamp(0.0, 1.0);
-    let drain_fraction = abg_strength.clamp(0.0, 1.0);
+/// With `wrap`, charge spills across the image edges onto the opposite side
+/// (seamless tiling).
+pub fn apply_blooming(grid: &mut [f64], width: usize, height: usize, bloom: &Bloom, wrap: bool) {
+    let full_well = bloom.full_well.0;
+    let threshold = full_well * bloom.threshold.clamp(0.0, 1.0);
+    let drain_fraction = bloom.abg_strength.clamp(0.0, 1.0);
 
-    if vertical {
-        bloom_vertical(grid, width, height, threshold, full_well, drain_fraction);
+    if bloom.vertical {
+        bloom_vertical(grid, width, height, threshold, full_well, drain_fraction, wrap);
     } else {
-        bloom_horizontal(grid, width, height, threshold, full_well, drain_fraction);
+        bloom_horizontal(grid, width, height, threshold, full_well, drain_fraction, wrap);
     }
 }
 
@@ -30,6 +36,7 @@ fn bloom_vertical(
     threshold: f64,
     full_well: f64,
     drain_fraction: f64,
+    wrap: bool,
 ) {
     // Process each column independently
     for x in 0..width {
@@ -46,12 +53,12 @@ fn bloom_vertical(
                     if spill > 0.0 {
                         // Split spill between upper and lower neighbors
                         let spill_each = spill * 0.5;
-                        if y > 0 {
-                            let above = (y - 1) * width + x;
+                        if let Some(above_y) = neighbor(y, -1, height, wrap) {
+                            let above = above_y * width + x;
                             grid[above] = (grid[above] + spill_each).min(full_well);
                         }
-                        if y + 1 < height {
-                            let below = (y + 1) * width + x;
+                        if let Some(below_y) = neighbor(y, 1, height, wrap) {
+                            let below = below_y * width + x;
                             grid[below] = (grid[below] + spill_each).min(full_well);
                         }
                     }
@@ -68,6 +75,7 @@ fn bloom_horizontal(
     threshold: f64,
     full_well: f64,
     drain_fraction: f64,
+    wrap: bool,
 ) {
     for y in 0..height {
         for _pass in 0..3 {
@@ -81,11 +89,13 @@ fn bloom_horizontal(
 
                     if spill > 0.0 {
                         let spill_each = spill * 0.5;
-                        if x > 0 {
-                            grid[idx - 1] = (grid[idx - 1] + spill_each).min(full_well);
+                        if let Some(left_x) = neighbor(x, -1, width, wrap) {
+                            let left = y * width + left_x;
+                            grid[left] = (grid[left] + spill_each).min(full_well);
                         }
-                        if x + 1 < width {
-                            grid[idx + 1] = (grid[idx + 1] + spill_each).min(full_well);
+                        if let Some(right_x) = neighbor(x, 1, width, wrap) {
+                            let right = y * width + right_x;
+                            grid[right] = (grid[right] + spill_each).min(full_well);
                         }
                     }
                 }
@@ -93,3 +103,16 @@ fn bloom_horizontal(
         }
     }
 }
+
+/// Index of the neighbor `step` away from `pos` along an axis of length `len`,
+/// wrapping around the edges when `wrap` is set.
+fn neighbor(pos: usize, step: isize, len: usize, wrap: bool) -> Option<usize> {
+    let n = pos as isize + step;
+    if wrap && len > 1 {
+        Some(n.rem_euclid(len as isize) as usize)
+    } else if n >= 0 && n < len as isize {
+        Some(n as usize)
+    } else {
+        None
+    }
+}
@@ -1,8 +1,14 @@
 pub mod adc;
+pub mod aging;
 pub mod amplifier;
 pub mod blooming;
+pub mod noise;
+pub mod readout;
 pub mod sensor;
+pub mod supply;
+pub mod thermal;
 pub mod transfer;
+pub mod units;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CcdArchitecture {
@@ -53,6 +59,12 @@ impl SensorPreset {
                 cte_vertical: 0.999995,
                 cte_horizontal: 0.999999,
                 gain_uv_per_e: 7.5,
+                fast_readout: ReadoutProfile {
+                    read_noise: 2.5,
+                    cte_loss: 8.0,
+                    ringing: 0.15,
+                    bit_depth: 12,
+                },
             },
             SensorPreset::Kaf4320 => SensorConfig {
                 width: 2048,
@@ -67,6 +79,12 @@ impl SensorPreset {
                 cte_vertical: 0.999995,
                 cte_horizontal: 0.999999,
                 gain_uv_per_e: 4.5,
+                fast_readout: ReadoutProfile {
+                    read_noise: 2.0,
+                    cte_loss: 5.0,
+                    ringing: 0.1,
+                    bit_depth: 12,
+                },
             },
             SensorPreset::Kaf16803 => SensorConfig {
                 width: 4096,
@@ -81,6 +99,12 @@ impl SensorPreset {
                 cte_vertical: 0.999998,
                 cte_horizontal: 0.999999,
                 gain_uv_per_e: 8.0,
+                fast_readout: ReadoutProfile {
+                    read_noise: 2.5,
+                    cte_loss: 8.0,
+                    ringing: 0.15,
+                    bit_depth: 12,
+                },
             },
             SensorPreset::Icx059cl => SensorConfig {
                 width: 500,
@@ -95,6 +119,12 @@ impl SensorPreset {
                 cte_vertical: 0.99999,
                 cte_horizontal: 0.99999,
                 gain_uv_per_e: 10.0,
+                fast_readout: ReadoutProfile {
+                    read_noise: 1.5,
+                    cte_loss: 2.0,
+                    ringing: 0.1,
+                    bit_depth: 8,
+                },
             },
             SensorPreset::Custom => SensorConfig {
                 width: 1024,
@@ -109,6 +139,7 @@ impl SensorPreset {
                 cte_vertical: 0.999995,
                 cte_horizontal: 0.999999,
                 gain_uv_per_e: 8.0,
+                fast_readout: ReadoutProfile::default(),
             },
         }
     }
@@ -118,13 +149,12 @@ impl SensorPreset {
 pub struct SensorConfig {
     pub width: u32,
     pub height: u32,
-    #[allow(dead_code)]
+    /// Photosite (width, height); non-square on video sensors.
     pub pixel_size_um: (f64, f64),
     pub full_well_no_abg: f64,
     pub full_well_abg: f64,
     #[allow(dead_code)]
     pub architecture: CcdArchitecture,
-    #[allow(dead_code)]
     pub v_phases: u8,
     #[allow(dead_code)]
     pub read_noise_e: f64,
@@ -132,6 +162,52 @@ pub struct SensorConfig {
     pub dark_current_pa_cm2: f64,
     pub cte_vertical: f64,
     pub cte_horizontal: f64,
-    #[allow(dead_code)]
     pub gain_uv_per_e: f64,
+    /// How the fast readout mode departs from the slow one.
+    pub fast_readout: ReadoutProfile,
+}
+
+/// Readout mode, as on a camera's speed menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReadoutSpeed {
+    /// Parameters as set: the sensor's low-noise mode.
+    Slow,
+    /// Parameters adjusted by the fast readout profile.
+    Fast,
+}
+
+impl ReadoutSpeed {
+    pub const ALL: &[ReadoutSpeed] = &[ReadoutSpeed::Slow, ReadoutSpeed::Fast];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReadoutSpeed::Slow => "Slow",
+            ReadoutSpeed::Fast => "Fast",
+        }
+    }
+}
+
+/// Cost of a faster pixel clock: more amplifier noise, less time for charge
+/// to transfer, more ringing and a shorter conversion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadoutProfile {
+    /// Read noise multiplier.
+    pub read_noise: f64,
+    /// Multiplier on the charge left behind per transfer (1 - CTE).
+    pub cte_loss: f64,
+    /// Horizontal ringing added to `h_ringing`.
+    pub ringing: f64,
+    /// ADC bit depth cap.
+    pub bit_depth: u8,
+}
+
+impl Default for ReadoutProfile {
+    fn default() -> Self {
+        Self {
+            read_noise: 2.0,
+            cte_loss: 5.0,
+            ringing: 0.1,
+            bit_depth: 12,
+        }
+    }
 }
@@ -0,0 +1,268 @@
+//! Noise generators behind the dark current, shot and read noise stages.
+//!
+//! The random sources draw true Poisson and Gaussian deviates; the textured
+//! ones map a deterministic per-pixel field through the Gaussian
+//! approximation, trading exact statistics for a chosen look. The seeded
+//! source draws through `gaussian` and `poisson`, which use `libm` so a seed
+//! gives the same grain on every platform.
+
+use rand::rngs::ThreadRng;
+use rand::{Rng, RngCore};
+use rand_distr::{Distribution, Poisson, StandardNormal};
+
+use crate::color::dither;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseSource {
+    /// Fresh entropy every frame.
+    Random,
+    /// Seeded PCG stream: random statistics, the same grain every run and
+    /// on every platform.
+    Pcg,
+    /// Tiled blue-noise mask: even, clump-free grain.
+    BlueNoise,
+    /// Hash-based value noise: smooth blotches `scale` pixels across.
+    ValueNoise,
+}
+
+impl NoiseSource {
+    pub const ALL: &[NoiseSource] = &[
+        NoiseSource::Random,
+        NoiseSource::Pcg,
+        NoiseSource::BlueNoise,
+        NoiseSource::ValueNoise,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NoiseSource::Random => "Random",
+            NoiseSource::Pcg => "PCG (Seeded)",
+            NoiseSource::BlueNoise => "Blue Noise",
+            NoiseSource::ValueNoise => "Value Noise",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseTexture {
+    pub source: NoiseSource,
+    /// Seed for every source but `Random`; each stage derives its own stream.
+    pub seed: u64,
+    /// Value noise cell size in pixels.
+    pub scale: f64,
+}
+
+impl Default for NoiseTexture {
+    fn default() -> Self {
+        Self {
+            source: NoiseSource::Random,
+            seed: 1,
+            scale: 2.0,
+        }
+    }
+}
+
+/// The stage drawing noise, so each gets an independent texture.
+#[derive(Debug, Clone, Copy)]
+pub enum NoiseStage {
+    Dark,
+    Shot,
+    Read,
+}
+
+impl NoiseStage {
+    fn salt(self) -> u64 {
+        match self {
+            NoiseStage::Dark => 0x6461_726b,
+            NoiseStage::Shot => 0x7368_6f74,
+            NoiseStage::Read => 0x7265_6164,
+        }
+    }
+}
+
+enum Generator {
+    Thread(ThreadRng),
+    Pcg(Pcg32),
+    BlueNoise { x: usize, y: usize },
+    ValueNoise { seed: u64, scale: f64 },
+}
+
+/// Per-pixel deviates for one stage over a row-major plane `width` wide.
+pub struct NoiseSampler {
+    generator: Generator,
+    width: usize,
+}
+
+impl NoiseSampler {
+    pub fn new(texture: &NoiseTexture, stage: NoiseStage, width: usize) -> Self {
+        let seed = hash(texture.seed ^ hash(stage.salt()));
+        let generator = match texture.source {
+            NoiseSource::Random => Generator::Thread(rand::rng()),
+            NoiseSource::Pcg => Generator::Pcg(Pcg32::new(seed, stage.salt())),
+            NoiseSource::BlueNoise => Generator::BlueNoise {
+                x: seed as usize % dither::MASK_SIZE,
+                y: (seed >> 32) as usize % dither::MASK_SIZE,
+            },
+            NoiseSource::ValueNoise => Generator::ValueNoise {
+                seed,
+                scale: texture.scale.max(1.0),
+            },
+        };
+        Self {
+            generator,
+            width: width.max(1),
+        }
+    }
+
+    fn rng(&mut self) -> Option<&mut dyn RngCore> {
+        match &mut self.generator {
+            Generator::Thread(rng) => Some(rng),
+            Generator::Pcg(rng) => Some(rng),
+            _ => None,
+        }
+    }
+
+    /// Standard normal deviate for pixel `i`.
+    pub fn normal(&mut self, i: usize) -> f64 {
+        let (x, y) = (i % self.width, i / self.width);
+        match &mut self.generator {
+            Generator::Thread(rng) => StandardNormal.sample(rng),
+            Generator::Pcg(rng) => gaussian(rng),
+            Generator::BlueNoise { x: ox, y: oy } => {
+                inverse_normal(dither::blue_noise(x + *ox, y + *oy) + 0.5)
+            }
+            Generator::ValueNoise { seed, scale } => value_noise(*seed, x, y, *scale),
+        }
+    }
+
+    /// Poisson deviate of mean `lambda` for pixel `i`. Very large means and
+    /// the textured sources use the Gaussian approximation.
+    pub fn poisson(&mut self, i: usize, lambda: f64) -> f64 {
+        if let Generator::Pcg(rng) = &mut self.generator {
+            return poisson(rng, lambda);
+        }
+        if lambda < 1e6
+            && let Some(rng) = self.rng()
+            && let Ok(dist) = Poisson::new(lambda)
+        {
+            return dist.sample(rng);
+        }
+        (lambda + lambda.sqrt() * self.normal(i)).max(0.0)
+    }
+}
+
+/// Standard normal deviate (Box-Muller), rounding alike on every platform.
+pub fn gaussian(rng: &mut impl Rng) -> f64 {
+    let u = 1.0 - rng.random::<f64>();
+    let angle = std::f64::consts::TAU * rng.random::<f64>();
+    (-2.0 * libm::log(u)).sqrt() * libm::cos(angle)
+}
+
+/// Poisson deviate of mean `lambda`, rounding alike on every platform: by
+/// inversion for small means, the Gaussian approximation above
+/// `POISSON_INVERSION_LIMIT`.
+pub fn poisson(rng: &mut impl Rng, lambda: f64) -> f64 {
+    if lambda <= 0.0 {
+        return 0.0;
+    }
+    if lambda >= POISSON_INVERSION_LIMIT {
+        return (lambda + lambda.sqrt() * gaussian(rng)).max(0.0);
+    }
+    // Walk the CDF up from zero until it passes a uniform draw
+    let u = rng.random::<f64>();
+    let mut p = libm::exp(-lambda);
+    let mut cdf = p;
+    let mut k = 0.0;
+    while u > cdf && p > 0.0 {
+        k += 1.0;
+        p *= lambda / k;
+        cdf += p;
+    }
+    k
+}
+
+/// Mean above which `poisson` switches to the Gaussian approximation; the
+/// Poisson skewness it drops is 1/8 there.
+const POISSON_INVERSION_LIMIT: f64 = 64.0;
+
+/// PCG-XSH-RR 32-bit generator (O'Neill 2014).
+struct Pcg32 {
+    state: u64,
+    inc: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6364136223846793005;
+
+    fn new(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            inc: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+}
+
+impl RngCore for Pcg32 {
+    fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(Self::MULTIPLIER).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        ((self.next_u32() as u64) << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// SplitMix64 finalizer.
+fn hash(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Value noise: Gaussian values on a lattice `scale` pixels apart, blended
+/// with smoothstep weights and renormalized to unit variance.
+fn value_noise(seed: u64, x: usize, y: usize, scale: f64) -> f64 {
+    let (fx, fy) = (x as f64 / scale, y as f64 / scale);
+    let (cx, cy) = (fx.floor(), fy.floor());
+    let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(fx - cx), smooth(fy - cy));
+    let lattice = |dx: u64, dy: u64| {
+        let h = hash(seed ^ hash((cx as u64 + dx) ^ hash(cy as u64 + dy)));
+        inverse_normal(((h >> 11) as f64 + 0.5) / (1u64 << 53) as f64)
+    };
+    let corners = [
+        ((1.0 - tx) * (1.0 - ty), lattice(0, 0)),
+        (tx * (1.0 - ty), lattice(1, 0)),
+        ((1.0 - tx) * ty, lattice(0, 1)),
+        (tx * ty, lattice(1, 1)),
+    ];
+    let sum: f64 = corners.iter().map(|(w, v)| w * v).sum();
+    let norm: f64 = corners.iter().map(|(w, _)| w * w).sum();
+    sum / norm.sqrt()
+}
+
+/// Standard normal quantile of `p` in (0, 1) (Abramowitz & Stegun 26.2.23).
+fn inverse_normal(p: f64) -> f64 {
+    let q = p.min(1.0 - p).max(1e-300);
+    let t = (-2.0 * libm::log(q)).sqrt();
+    let z = t
+        - (2.515517 + 0.802853 * t + 0.010328 * t * t)
+            / (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t);
+    if p < 0.5 { -z } else { z }
+}
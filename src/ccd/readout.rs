@@ -0,0 +1,93 @@
+//! Serial readout as a time sequence.
+//!
+//! The serial register empties one row at a time: every pixel of a row
+//! passes through the register, the output amplifier and the converter
+//! before the next row is clocked down, with a horizontal blanking interval
+//! between rows. `stream` drives the serial stages in that order, handing
+//! each row to each stage together with its `RowClock`, so a stage with
+//! memory (an amplifier baseline wandering, a converter reference recovering,
+//! a disturbance's phase) carries it from the last sample of one row through
+//! the blanking into the next instead of restarting every row.
+//!
+//! A stage sees only the rows already read, so streaming a frame through
+//! several stages at once and through each in turn give the same result; the
+//! pipeline streams each stage group in turn so it can time and inspect them.
+
+use super::amplifier::H_BLANK_PIXELS;
+use super::transfer::ReadoutDirection;
+
+/// Where a row sits in the readout sequence.
+#[derive(Debug, Clone, Copy)]
+pub struct RowClock {
+    /// Row in the readout frame.
+    pub y: usize,
+    pub width: usize,
+    /// The row's last column is read first.
+    pub last_first: bool,
+    /// Pixel clocks from the start of the frame to the row's first sample,
+    /// including the blanking of every earlier row.
+    pub start: f64,
+}
+
+impl RowClock {
+    pub fn new(y: usize, width: usize, direction: ReadoutDirection) -> Self {
+        let last_first = match direction {
+            ReadoutDirection::LeftToRight => true,
+            ReadoutDirection::RightToLeft => false,
+            ReadoutDirection::Alternating => y.is_multiple_of(2),
+        };
+        Self {
+            y,
+            width,
+            last_first,
+            start: (y * Self::period(width)) as f64,
+        }
+    }
+
+    /// Pixel clocks per row, blanking included.
+    pub fn period(width: usize) -> usize {
+        width + H_BLANK_PIXELS
+    }
+
+    /// Column of the `i`th sample read out.
+    pub fn x(&self, i: usize) -> usize {
+        if self.last_first { self.width - 1 - i } else { i }
+    }
+
+    /// Pixel clocks from the start of the frame to the `i`th sample.
+    pub fn time(&self, i: usize) -> f64 {
+        self.start + i as f64
+    }
+
+    /// Visit the row's samples in readout order.
+    pub fn for_each(&self, row: &mut [f64], mut f: impl FnMut(usize, &mut f64)) {
+        for i in 0..self.width {
+            f(i, &mut row[self.x(i)]);
+        }
+    }
+}
+
+/// One serial stage, fed the frame a row at a time in readout order.
+pub trait RowStage {
+    fn read_row(&mut self, row: &mut [f64], clock: &RowClock);
+}
+
+/// Read the frame out row by row, passing each row through `stages` in
+/// order before the next.
+pub fn stream(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    direction: ReadoutDirection,
+    stages: &mut [&mut dyn RowStage],
+) {
+    if width == 0 {
+        return;
+    }
+    for (y, row) in grid.chunks_exact_mut(width).take(height).enumerate() {
+        let clock = RowClock::new(y, width, direction);
+        for stage in stages.iter_mut() {
+            stage.read_row(row, &clock);
+        }
+    }
+}
@@ -1,5 +1,10 @@
 use image::RgbImage;
-use rand_distr::{Distribution, Normal, Poisson};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::ccd::noise::{self, NoiseSampler, NoiseStage, NoiseTexture};
+use crate::ccd::transfer::{Modulation, ReadoutFrame};
+use crate::ccd::units::Electrons;
 
 /// Convert an RGB image to a 3-channel electron grid.
 /// Each pixel's channel value is scaled by full_well_capacity.
@@ -20,49 +25,451 @@ pub fn image_to_electrons(img: &RgbImage, full_well: f64) -> (Vec<[f64; 3]>, usi
     (electrons, w, h)
 }
 
+/// Exposure-series HDR: bracketed 8-bit captures of the scene merged back to
+/// radiance, then pushed past full well into the readout chain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HdrMerge {
+    pub enabled: bool,
+    /// Captures in the bracket, centred on the source exposure.
+    pub exposures: usize,
+    /// Exposure difference between neighbouring captures, in stops.
+    pub ev_step: f64,
+    /// Gain applied to the merged radiance before readout, in stops.
+    pub boost: f64,
+}
+
+impl Default for HdrMerge {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            exposures: 3,
+            ev_step: 2.0,
+            boost: 2.0,
+        }
+    }
+}
+
+/// Simulate the bracket and merge it with hat weights (Debevec-Malik), so
+/// each pixel is taken from the captures where it was neither clipped nor in
+/// the quantization floor. Highlights keep their gradation instead of
+/// flattening at full well, and after the boost they reach blooming with
+/// that detail intact.
+pub fn merge_exposure_series(grid: &mut [[f64; 3]], full_well: f64, hdr: &HdrMerge) {
+    if !hdr.enabled || full_well <= 0.0 {
+        return;
+    }
+    let n = hdr.exposures.max(1);
+    // Exposure times relative to the source, shortest first
+    let times: Vec<f64> = (0..n)
+        .map(|k| libm::exp2(hdr.ev_step * (k as f64 - (n - 1) as f64 / 2.0)))
+        .collect();
+    let gain = libm::exp2(hdr.boost) * full_well;
+    for pixel in grid.iter_mut() {
+        for v in pixel.iter_mut() {
+            let radiance = *v / full_well;
+            let capture = |t: f64| ((radiance * t).clamp(0.0, 1.0) * 255.0).round() / 255.0;
+            let (mut sum, mut weights) = (0.0, 0.0);
+            for &t in &times {
+                let code = capture(t);
+                let w = 1.0 - (2.0 * code - 1.0).abs();
+                sum += w * code / t;
+                weights += w;
+            }
+            // Clipped or black in every capture: trust the shortest one
+            let merged = if weights > 0.0 { sum / weights } else { capture(times[0]) / times[0] };
+            *v = merged * gain;
+        }
+    }
+}
+
+/// Scale each row's charge by `1 + depth * modulation`, sampled at the row's
+/// position in readout order: a light source or shutter varying while rows
+/// integrate in turn, as in flicker banding or slit-scan exposure.
+pub fn apply_exposure_modulation(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    frame: &ReadoutFrame,
+    depth: f64,
+    modulation: &Modulation,
+) {
+    if depth <= 0.0 {
+        return;
+    }
+    let (_, rows) = frame.dims();
+    let span = rows.saturating_sub(1).max(1) as f64;
+    let scale: Vec<f64> = (0..rows)
+        .map(|r| (1.0 + depth * modulation.sample(r as f64 / span)).max(0.0))
+        .collect();
+    for y in 0..height {
+        for x in 0..width {
+            grid[y * width + x] *= scale[frame.readout_row(x, y)];
+        }
+    }
+}
+
+/// How row exposure windows line up under a flickering light.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShutterMode {
+    /// Every row integrates over the same window, as with a mechanical
+    /// shutter or an interline transfer: the whole frame catches the same
+    /// part of the flicker cycle.
+    Global,
+    /// Each row's window starts one row time after the row read before it,
+    /// so the flicker lands on the frame as bands.
+    Rolling,
+}
+
+impl ShutterMode {
+    pub const ALL: &[ShutterMode] = &[ShutterMode::Global, ShutterMode::Rolling];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShutterMode::Global => "Global",
+            ShutterMode::Rolling => "Rolling",
+        }
+    }
+}
+
+/// A PWM-dimmed LED or mains-driven lamp: a square wave of light, on for
+/// `duty_cycle` of each period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlickerParams {
+    /// Fraction of the light that flickers; 0 disables it, 1 turns the
+    /// light fully off between pulses.
+    pub depth: f64,
+    pub frequency_hz: f64,
+    /// Fraction of each period the light is on.
+    pub duty_cycle: f64,
+    /// Where in the cycle the first row's exposure starts, in cycles.
+    pub phase: f64,
+    /// Integration time of each row.
+    pub exposure_ms: f64,
+    /// Delay between the exposure starts of consecutive rows (rolling only).
+    pub row_time_us: f64,
+    pub shutter: ShutterMode,
+}
+
+impl Default for FlickerParams {
+    fn default() -> Self {
+        Self {
+            depth: 0.0,
+            frequency_hz: 100.0,
+            duty_cycle: 0.5,
+            phase: 0.0,
+            exposure_ms: 1.0,
+            row_time_us: 64.0,
+            shutter: ShutterMode::Rolling,
+        }
+    }
+}
+
+impl FlickerParams {
+    /// Charge scale for an exposure starting at `start` cycles: the light
+    /// collected relative to a steady source of the same mean brightness.
+    fn gain(&self, start: f64) -> f64 {
+        let duty = self.duty_cycle.clamp(0.01, 1.0);
+        // Cycles of on-time from 0 to u
+        let on_time = |u: f64| u.floor() * duty + (u - u.floor()).min(duty);
+        let span = self.exposure_ms.max(0.0) * 1e-3 * self.frequency_hz.max(0.0);
+        let lit = if span < 1e-9 {
+            // Instantaneous exposure: the light is either on or off
+            if start - start.floor() < duty { 1.0 } else { 0.0 }
+        } else {
+            (on_time(start + span) - on_time(start)) / span
+        };
+        let depth = self.depth.clamp(0.0, 1.0);
+        1.0 - depth + depth * lit / duty
+    }
+}
+
+/// Scale each pixel's charge by the light a flickering source delivers
+/// during its row's exposure window. Rows are timed in readout order, so a
+/// rolling shutter bands along the readout direction.
+pub fn apply_flicker(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    frame: &ReadoutFrame,
+    flicker: &FlickerParams,
+) {
+    if flicker.depth <= 0.0 {
+        return;
+    }
+    let (_, rows) = frame.dims();
+    let row_cycles = match flicker.shutter {
+        ShutterMode::Global => 0.0,
+        ShutterMode::Rolling => flicker.row_time_us * 1e-6 * flicker.frequency_hz,
+    };
+    let scale: Vec<f64> = (0..rows)
+        .map(|r| flicker.gain(flicker.phase + r as f64 * row_cycles))
+        .collect();
+    for y in 0..height {
+        for x in 0..width {
+            grid[y * width + x] *= scale[frame.readout_row(x, y)];
+        }
+    }
+}
+
+/// Spatial structure in the dark rate, as multiples of the uniform rate
+/// added on top of it. All zero leaves dark current flat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DarkStructure {
+    /// Extra rate at the sensor edges, fading toward the centre.
+    pub edge_glow: f64,
+    /// Width of the edge falloff as a fraction of the shorter side.
+    pub edge_width: f64,
+    /// Number of hot regions.
+    pub clusters: usize,
+    /// Extra rate at the centre of a hot region.
+    pub cluster_gain: f64,
+    /// Typical hot region radius in pixels; each one varies around it.
+    pub cluster_radius: f64,
+    /// Depth of the concentric "tree ring" modulation from wafer growth
+    /// striations.
+    pub rings: f64,
+    /// Ring spacing in pixels.
+    pub ring_period: f64,
+    /// Fraction of isolated hot pixels, each 10 to 1000 times the rate.
+    pub hot_pixels: f64,
+    /// Seed for cluster and hot pixel placement and the ring centre.
+    pub seed: u64,
+}
+
+impl Default for DarkStructure {
+    fn default() -> Self {
+        Self {
+            edge_glow: 0.0,
+            edge_width: 0.1,
+            clusters: 0,
+            cluster_gain: 20.0,
+            cluster_radius: 8.0,
+            rings: 0.0,
+            ring_period: 40.0,
+            hot_pixels: 0.0,
+            seed: 1,
+        }
+    }
+}
+
+impl DarkStructure {
+    fn is_flat(&self) -> bool {
+        self.edge_glow <= 0.0
+            && (self.clusters == 0 || self.cluster_gain <= 0.0)
+            && self.rings <= 0.0
+            && self.hot_pixels <= 0.0
+    }
+
+    /// Per-pixel multiplier of the uniform dark rate.
+    fn rate_map(&self, width: usize, height: usize) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let (w, h) = (width as f64, height as f64);
+        let hot: Vec<(f64, f64, f64)> = (0..self.clusters)
+            .map(|_| {
+                let radius = self.cluster_radius.max(0.5) * rng.random_range(0.5..1.5);
+                (rng.random::<f64>() * w, rng.random::<f64>() * h, radius)
+            })
+            .collect();
+        // The wafer centre is usually off the die, so the rings arc across it
+        let (ring_x, ring_y) = (
+            rng.random_range(-1.0..2.0) * w,
+            rng.random_range(-1.0..2.0) * h,
+        );
+        let hot_pixels = self.hot_pixels.clamp(0.0, 1.0);
+        let falloff = self.edge_width.max(1e-3) * w.min(h);
+        let mut map = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+                let edge = px.min(w - px).min(py).min(h - py);
+                let mut rate = 1.0 + self.edge_glow * libm::exp(-edge / falloff);
+                for &(cx, cy, radius) in &hot {
+                    let d2 = (px - cx).powi(2) + (py - cy).powi(2);
+                    rate += self.cluster_gain * libm::exp(-d2 / (2.0 * radius * radius));
+                }
+                let r = libm::hypot(px - ring_x, py - ring_y);
+                let phase = std::f64::consts::TAU * r / self.ring_period.max(1.0);
+                rate += self.rings * 0.5 * (1.0 + libm::cos(phase));
+                if hot_pixels > 0.0 && rng.random::<f64>() < hot_pixels {
+                    rate += libm::pow(10.0, rng.random_range(1.0..3.0));
+                }
+                map.push(rate);
+            }
+        }
+        map
+    }
+}
+
 /// Add dark current noise (Poisson-distributed).
-/// `dark_rate` is in electrons (already scaled by temperature/exposure).
-pub fn add_dark_current(grid: &mut [f64], dark_rate: f64) {
+/// `dark_rate` is in electrons (already scaled by temperature/exposure);
+/// `structure` raises it near the edges, in hot clusters and pixels and
+/// along rings.
+pub fn add_dark_current(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    dark_rate: Electrons,
+    structure: &DarkStructure,
+    texture: &NoiseTexture,
+) {
+    let dark_rate = dark_rate.0;
     if dark_rate <= 0.0 {
         return;
     }
-    let mut rng = rand::rng();
-    let dist = Poisson::new(dark_rate).unwrap_or_else(|_| Poisson::new(1.0).unwrap());
-    for pixel in grid.iter_mut() {
-        let dark: f64 = dist.sample(&mut rng);
-        *pixel += dark;
+    let mut noise = NoiseSampler::new(texture, NoiseStage::Dark, width);
+    if structure.is_flat() {
+        for (i, pixel) in grid.iter_mut().enumerate() {
+            *pixel += noise.poisson(i, dark_rate);
+        }
+        return;
+    }
+    let map = structure.rate_map(width, height);
+    for (i, (pixel, scale)) in grid.iter_mut().zip(map).enumerate() {
+        *pixel += noise.poisson(i, dark_rate * scale);
+    }
+}
+
+/// A long exposure integrated in time slices, so events during it land in
+/// the charge collected by then rather than in a single draw at the end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LongExposure {
+    /// Time slices; 1 integrates the exposure in one step.
+    pub slices: usize,
+    /// Expected cosmic ray hits per megapixel over the exposure.
+    pub cosmic_rate: f64,
+    /// Chance of a stray parallel clock pulse at each slice boundary.
+    pub clock_glitch_rate: f64,
+    /// Trap sites pumped by each stray pulse.
+    pub traps: usize,
+    /// Electrons a trap moves to the next row per stray pulse.
+    pub pump_charge: f64,
+    /// RNG seed for hits, traps and pulses; 0 draws a fresh pattern every run.
+    pub seed: u64,
+}
+
+impl Default for LongExposure {
+    fn default() -> Self {
+        Self {
+            slices: 1,
+            cosmic_rate: 0.0,
+            clock_glitch_rate: 0.0,
+            traps: 50,
+            pump_charge: 500.0,
+            seed: 0,
+        }
+    }
+}
+
+/// Integrate the collected charge (signal and dark current) over the
+/// exposure's time slices, adding cosmic ray tracks in the slice they strike.
+///
+/// Between slices a stray parallel clock pulse may fire: everything
+/// collected so far steps one row toward the serial register, so the early
+/// part of the exposure ghosts a row off the rest, and each trap site pumps
+/// `pump_charge` into the row behind it, leaving bright/dark dipoles that
+/// grow with every pulse. Hits before a pulse move with it.
+pub fn integrate_long_exposure(
+    grid: &mut Vec<f64>,
+    width: usize,
+    height: usize,
+    frame: &ReadoutFrame,
+    full_well: f64,
+    long: &LongExposure,
+) {
+    let slices = long.slices.max(1);
+    let pulses = slices > 1 && long.clock_glitch_rate > 0.0;
+    if (long.cosmic_rate <= 0.0 && !pulses) || width == 0 || height == 0 {
+        return;
+    }
+    let mut rng = if long.seed == 0 {
+        StdRng::from_rng(&mut rand::rng())
+    } else {
+        StdRng::seed_from_u64(long.seed)
+    };
+
+    let expected = long.cosmic_rate * (width * height) as f64 / 1e6;
+    let hits = noise::poisson(&mut rng, expected) as usize;
+    let mut tracks = vec![Vec::new(); slices];
+    for _ in 0..hits {
+        let slice = rng.random_range(0..slices);
+        tracks[slice].push(cosmic_track(&mut rng, width, height, full_well));
+    }
+
+    let (frame_w, frame_h) = frame.dims();
+    let traps: Vec<usize> = (0..long.traps)
+        .map(|_| rng.random_range(0..frame_w * frame_h))
+        .collect();
+
+    let per_slice: Vec<f64> = grid.iter().map(|&e| e / slices as f64).collect();
+    let mut collected = vec![0.0; grid.len()];
+    for (slice, hits) in tracks.iter().enumerate() {
+        for (c, e) in collected.iter_mut().zip(&per_slice) {
+            *c += e;
+        }
+        for &(i, deposit) in hits.iter().flatten() {
+            collected[i] += deposit;
+        }
+        if pulses && slice + 1 < slices && rng.random::<f64>() < long.clock_glitch_rate {
+            frame.enter(&mut collected);
+            collected.drain(..frame_w);
+            collected.resize(frame_w * frame_h, 0.0);
+            for &trap in &traps {
+                if trap + frame_w < collected.len() {
+                    let pumped = collected[trap].min(long.pump_charge.max(0.0));
+                    collected[trap] -= pumped;
+                    collected[trap + frame_w] += pumped;
+                }
+            }
+            frame.exit(&mut collected);
+        }
     }
+    *grid = collected;
+}
+
+/// One cosmic ray track as (index, electrons) deposits: mostly near-normal
+/// hits a pixel or two long, with rare grazing ones streaking further.
+fn cosmic_track(
+    rng: &mut StdRng,
+    width: usize,
+    height: usize,
+    full_well: f64,
+) -> Vec<(usize, f64)> {
+    let (x0, y0) = (rng.random::<f64>() * width as f64, rng.random::<f64>() * height as f64);
+    let angle = rng.random::<f64>() * std::f64::consts::TAU;
+    let length = 1.0 + 30.0 * rng.random::<f64>().powi(4);
+    let deposit = full_well * rng.random_range(0.05..0.6);
+    let steps = length.ceil() as usize;
+    (0..steps)
+        .filter_map(|step| {
+            let t = step as f64;
+            let (sin, cos) = libm::sincos(angle);
+            let (x, y) = (x0 + t * cos, y0 + t * sin);
+            (x >= 0.0 && y >= 0.0 && x < width as f64 && y < height as f64)
+                .then(|| (y as usize * width + x as usize, deposit))
+        })
+        .collect()
 }
 
 /// Add photon shot noise (replace signal with Poisson sample of that signal).
-pub fn add_shot_noise(grid: &mut [f64]) {
-    let mut rng = rand::rng();
-    for pixel in grid.iter_mut() {
+pub fn add_shot_noise(grid: &mut [f64], width: usize, texture: &NoiseTexture) {
+    let mut noise = NoiseSampler::new(texture, NoiseStage::Shot, width);
+    for (i, pixel) in grid.iter_mut().enumerate() {
         if *pixel > 0.0 {
             let lambda = (*pixel).min(1e8); // cap to avoid overflow
-            if lambda < 1e6 {
-                if let Ok(dist) = Poisson::new(lambda) {
-                    *pixel = dist.sample(&mut rng);
-                }
-            } else {
-                // For very large values, use Gaussian approximation
-                let sigma = lambda.sqrt();
-                let normal = Normal::new(lambda, sigma).unwrap();
-                *pixel = normal.sample(&mut rng).max(0.0);
-            }
+            *pixel = noise.poisson(i, lambda);
         }
     }
 }
 
 /// Add read noise (Gaussian-distributed).
-pub fn add_read_noise(grid: &mut [f64], sigma: f64) {
+pub fn add_read_noise(grid: &mut [f64], width: usize, sigma: Electrons, texture: &NoiseTexture) {
+    let sigma = sigma.0;
     if sigma <= 0.0 {
         return;
     }
-    let mut rng = rand::rng();
-    let dist = Normal::new(0.0, sigma).unwrap();
-    for pixel in grid.iter_mut() {
-        *pixel += dist.sample(&mut rng);
+    let mut noise = NoiseSampler::new(texture, NoiseStage::Read, width);
+    for (i, pixel) in grid.iter_mut().enumerate() {
+        *pixel += sigma * noise.normal(i);
         if *pixel < 0.0 {
             *pixel = 0.0;
         }
@@ -0,0 +1,82 @@
+//! Supply current feedback during readout.
+//!
+//! Clocking charge out of the register and driving it through the output
+//! amplifier draws current from the supply in proportion to the signal: a
+//! bright row loads the supply harder than a dark one. Through the supply's
+//! source impedance that current pulls VDD down, and the rail recovers with
+//! its decoupling time constant. The output stage's bias and swing follow
+//! the rail, so rows read while the supply is down come out dimmer in
+//! proportion and clip early, and a shadow trails a bright band in readout
+//! order while the rail recovers.
+//!
+//! The nominal rail is `SpiceParams::effective_vdd`, so the static supply
+//! droop the SPICE models see and this per-row sag add up.
+
+use super::readout::{RowClock, RowStage};
+
+/// The supply's response to readout current; zero load disables it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SupplyLoad {
+    /// Volts the rail settles down by while rows at full well are read.
+    pub load: f64,
+    /// Rows for the rail to recover 63% of a step in load.
+    pub recovery: f64,
+}
+
+impl Default for SupplyLoad {
+    fn default() -> Self {
+        Self {
+            load: 0.0,
+            recovery: 20.0,
+        }
+    }
+}
+
+/// Lowest rail the sag can reach, where the source follower is about to
+/// cut off.
+const MIN_VDD: f64 = 2.0;
+
+/// Sag the supply under the readout current of the electron rows streamed
+/// through, scaling each row by the rail it was read at and tracking the
+/// rail from row to row.
+pub struct SupplyFeedback {
+    load: f64,
+    /// Fraction of the gap to its target the rail closes per row.
+    settle: f64,
+    nominal: f64,
+    full_well: f64,
+    /// The rail at the current row.
+    vdd: f64,
+}
+
+impl SupplyFeedback {
+    pub fn new(supply: &SupplyLoad, vdd: f64, full_well: f64) -> Self {
+        Self {
+            load: supply.load.max(0.0),
+            settle: 1.0 - libm::exp(-1.0 / supply.recovery.max(0.1)),
+            nominal: vdd,
+            full_well: full_well.max(1.0),
+            vdd,
+        }
+    }
+}
+
+impl RowStage for SupplyFeedback {
+    fn read_row(&mut self, row: &mut [f64], _clock: &RowClock) {
+        if self.load <= 0.0 || row.is_empty() {
+            return;
+        }
+        // Current drawn clocking this row, as a fraction of a full-well row
+        let mean = row.iter().sum::<f64>() / row.len() as f64;
+        let current = (mean / self.full_well).clamp(0.0, 1.0);
+        let target = (self.nominal - self.load * current).max(MIN_VDD.min(self.nominal));
+        self.vdd += (target - self.vdd) * self.settle;
+
+        // Output level and swing both scale with the rail
+        let rail = self.vdd / self.nominal;
+        let ceiling = if rail < 1.0 { self.full_well * rail } else { f64::INFINITY };
+        for v in row.iter_mut() {
+            *v = (*v * rail).min(ceiling);
+        }
+    }
+}
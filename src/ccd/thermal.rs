@@ -0,0 +1,211 @@
+//! Output amplifier self-heating during readout.
+//!
+//! The amplifier dissipates power the whole time it is reading, and the heat
+//! spreads from its corner of the die into the array. Rows still waiting to
+//! be read keep generating dark current, and dark current doubles every few
+//! kelvin, so the rows read last and the pixels nearest the amplifier, which
+//! sits by the last row, collect the most: a warm haze that grows toward the
+//! amplifier corner in readout order. The same heating brightens the
+//! amplifier's own glow as the readout goes on.
+//!
+//! Heat flow is solved on a coarse grid over the readout frame with an
+//! explicit diffusion step, the amplifier a constant source in its corner
+//! cell. The source is scaled so that cell ends the readout `heating` kelvin
+//! above the rest of the die.
+
+use rand::Rng;
+
+use super::noise;
+
+/// Readout dark current and amplifier heating; zero dark and heating
+/// disable both.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmpHeating {
+    /// Dark electrons per pixel generated over one full readout at the
+    /// array's own temperature.
+    pub readout_dark: f64,
+    /// Kelvin the amplifier corner rises by the end of the readout.
+    pub heating: f64,
+    /// How far the heat spreads by the end of the readout, as a fraction of
+    /// the frame diagonal.
+    pub diffusion: f64,
+}
+
+impl Default for AmpHeating {
+    fn default() -> Self {
+        Self {
+            readout_dark: 0.0,
+            heating: 0.0,
+            diffusion: 0.2,
+        }
+    }
+}
+
+impl AmpHeating {
+    pub fn is_off(&self) -> bool {
+        self.readout_dark <= 0.0 && self.heating <= 0.0
+    }
+}
+
+/// Kelvin per doubling of silicon dark current near room temperature.
+const DARK_DOUBLING: f64 = 6.3;
+/// Grid cells along the longer side of the frame.
+const GRID: usize = 32;
+
+/// The temperature rise over the frame through the readout, on a coarse
+/// grid in readout-frame orientation.
+pub struct HeatMap {
+    cols: usize,
+    /// Per row read: time integral, in readouts, of the dark current
+    /// multiplier along the row up to its read time, one value per column of
+    /// cells.
+    exposure: Vec<Vec<f64>>,
+    /// Per row read: the amplifier cell's dark current multiplier.
+    amp_scale: Vec<f64>,
+}
+
+impl HeatMap {
+    /// Run the diffusion over a readout of `height` rows of `width` pixels,
+    /// the amplifier at the end of the last row like its glow.
+    pub fn simulate(width: usize, height: usize, heating: &AmpHeating) -> Self {
+        let scale = GRID as f64 / width.max(height).max(1) as f64;
+        let cols = ((width as f64 * scale).ceil() as usize).clamp(1, GRID);
+        let rows = ((height as f64 * scale).ceil() as usize).clamp(1, GRID);
+        let amp = cols * rows - 1;
+
+        // Diffusivity in cells² per readout, and steps small enough for the
+        // explicit scheme to stay stable
+        let diagonal = ((cols * cols + rows * rows) as f64).sqrt();
+        let length = heating.diffusion.max(0.0) * diagonal;
+        let alpha = length * length / 4.0;
+        let steps = ((alpha * 5.0).ceil() as usize).max(height.max(1));
+        let dt = 1.0 / steps as f64;
+        let mut solver = Diffusion::new(cols, rows, amp, alpha * dt, dt);
+
+        // A unit source first, to scale it to reach `heating` at the end
+        for _ in 0..steps {
+            solver.step();
+        }
+        let peak = solver.field[amp];
+        let kelvin = if peak > 0.0 { heating.heating.max(0.0) / peak } else { 0.0 };
+        let multiplier = |rise: f64| libm::exp2(rise * kelvin / DARK_DOUBLING);
+
+        // Integrate each cell's multiplier, recording it at each row's read time
+        let mut solver = Diffusion::new(cols, rows, amp, alpha * dt, dt);
+        let mut integral = vec![0.0; cols * rows];
+        let mut exposure = Vec::with_capacity(height);
+        let mut amp_scale = Vec::with_capacity(height);
+        let mut step = 0;
+        for y in 0..height {
+            let until = y * steps / height;
+            while step < until {
+                for (sum, &rise) in integral.iter_mut().zip(&solver.field) {
+                    *sum += multiplier(rise) * dt;
+                }
+                solver.step();
+                step += 1;
+            }
+            let gy = ((y as f64 + 0.5) / height as f64 * rows as f64 - 0.5)
+                .clamp(0.0, (rows - 1) as f64);
+            let (r0, fy) = (gy as usize, gy.fract());
+            let r1 = (r0 + 1).min(rows - 1);
+            let row = (0..cols)
+                .map(|c| integral[r0 * cols + c] * (1.0 - fy) + integral[r1 * cols + c] * fy)
+                .collect();
+            exposure.push(row);
+            amp_scale.push(multiplier(solver.field[amp]));
+        }
+        Self {
+            cols,
+            exposure,
+            amp_scale,
+        }
+    }
+
+    /// The amplifier's dark current multiplier as each readout row is read,
+    /// which its glow follows.
+    pub fn amp_scale(&self) -> &[f64] {
+        &self.amp_scale
+    }
+
+    /// Readouts' worth of dark current pixel (`x`, `y`) of a `width`-wide
+    /// frame collects before it is read, heating included.
+    fn exposure(&self, x: usize, y: usize, width: usize) -> f64 {
+        let Some(cells) = self.exposure.get(y) else {
+            return 0.0;
+        };
+        let gx = ((x as f64 + 0.5) / width as f64 * self.cols as f64 - 0.5)
+            .clamp(0.0, (self.cols - 1) as f64);
+        let (c0, fx) = (gx as usize, gx.fract());
+        let c1 = (c0 + 1).min(self.cols - 1);
+        cells[c0] * (1.0 - fx) + cells[c1] * fx
+    }
+}
+
+/// Add the dark current each pixel collects while it waits to be read, in
+/// the readout frame. Poisson-distributed.
+pub fn add_readout_dark(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    heating: &AmpHeating,
+    map: &HeatMap,
+    rng: &mut impl Rng,
+) {
+    if heating.readout_dark <= 0.0 {
+        return;
+    }
+    for y in 0..height {
+        for x in 0..width {
+            let expected = heating.readout_dark * map.exposure(x, y, width);
+            grid[y * width + x] += noise::poisson(rng, expected);
+        }
+    }
+}
+
+/// Explicit finite-difference heat flow with insulated edges and a constant
+/// source in one cell.
+struct Diffusion {
+    cols: usize,
+    rows: usize,
+    source: usize,
+    /// Diffusivity times the time step, in cells².
+    rate: f64,
+    /// Heat the source adds per step.
+    dt: f64,
+    field: Vec<f64>,
+    next: Vec<f64>,
+}
+
+impl Diffusion {
+    fn new(cols: usize, rows: usize, source: usize, rate: f64, dt: f64) -> Self {
+        Self {
+            cols,
+            rows,
+            source,
+            rate,
+            dt,
+            field: vec![0.0; cols * rows],
+            next: vec![0.0; cols * rows],
+        }
+    }
+
+    fn step(&mut self) {
+        let (cols, rows) = (self.cols, self.rows);
+        let field = &self.field;
+        for r in 0..rows {
+            for c in 0..cols {
+                let at = |rr: usize, cc: usize| field[rr * cols + cc];
+                let t = at(r, c);
+                // Missing neighbors mirror the cell, so no heat leaves
+                let up = if r > 0 { at(r - 1, c) } else { t };
+                let down = if r + 1 < rows { at(r + 1, c) } else { t };
+                let left = if c > 0 { at(r, c - 1) } else { t };
+                let right = if c + 1 < cols { at(r, c + 1) } else { t };
+                self.next[r * cols + c] = t + self.rate * (up + down + left + right - 4.0 * t);
+            }
+        }
+        self.next[self.source] += self.dt;
+        std::mem::swap(&mut self.field, &mut self.next);
+    }
+}
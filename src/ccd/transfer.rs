@@ -1,9 +1,386 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::noise::gaussian;
+use super::readout::{self, RowClock, RowStage};
+
+/// How V-clock glitch rows are distributed over the frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScheduleMode {
+    /// Each row glitches independently with the glitch rate.
+    Random,
+    /// A glitch starts with the glitch rate and continues for `burst_length` rows.
+    Burst,
+    /// Every `period`-th row (from `offset`) glitches; the rate only switches it on.
+    Periodic,
+    /// Rate ramps linearly from 0 at the top of the frame to the full rate at the bottom.
+    Ramp,
+}
+
+impl ScheduleMode {
+    pub const ALL: &[ScheduleMode] = &[
+        ScheduleMode::Random,
+        ScheduleMode::Burst,
+        ScheduleMode::Periodic,
+        ScheduleMode::Ramp,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ScheduleMode::Random => "Random",
+            ScheduleMode::Burst => "Burst",
+            ScheduleMode::Periodic => "Periodic",
+            ScheduleMode::Ramp => "Ramp",
+        }
+    }
+}
+
+/// Placement of V-clock glitch rows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlitchSchedule {
+    pub mode: ScheduleMode,
+    pub burst_length: usize,
+    pub period: usize,
+    pub offset: usize,
+    /// RNG seed for reproducible glitches; 0 draws a fresh pattern every run.
+    pub seed: u64,
+}
+
+impl Default for GlitchSchedule {
+    fn default() -> Self {
+        Self {
+            mode: ScheduleMode::Random,
+            burst_length: 8,
+            period: 64,
+            offset: 0,
+            seed: 0,
+        }
+    }
+}
+
+impl GlitchSchedule {
+    fn rng(&self) -> StdRng {
+        if self.seed == 0 {
+            StdRng::from_rng(&mut rand::rng())
+        } else {
+            StdRng::seed_from_u64(self.seed)
+        }
+    }
+
+    /// Whether each of `height` rows glitches at the given base rate.
+    fn rows(&self, height: usize, rate: f64, rng: &mut impl Rng) -> Vec<bool> {
+        match self.mode {
+            ScheduleMode::Random => (0..height).map(|_| rng.random::<f64>() < rate).collect(),
+            ScheduleMode::Burst => {
+                let mut rows = vec![false; height];
+                let mut remaining = 0;
+                for row in rows.iter_mut() {
+                    if remaining == 0 && rng.random::<f64>() < rate {
+                        remaining = self.burst_length.max(1);
+                    }
+                    if remaining > 0 {
+                        *row = true;
+                        remaining -= 1;
+                    }
+                }
+                rows
+            }
+            ScheduleMode::Periodic => {
+                let period = self.period.max(1);
+                (0..height).map(|y| y % period == self.offset % period).collect()
+            }
+            ScheduleMode::Ramp => {
+                let span = height.saturating_sub(1).max(1) as f64;
+                (0..height)
+                    .map(|y| rng.random::<f64>() < rate * y as f64 / span)
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Number of vertical clock phases per pixel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockPhases {
+    /// Implant barriers make transfer one-way: a bad pulse can only stall a
+    /// row or push it ahead.
+    Two,
+    /// Symmetric gates: rows can also be torn sideways or mirrored.
+    Three,
+    /// A packet can rest between two rows under a gate pair, so faults can
+    /// split a row across its neighbor or step it backward.
+    Four,
+}
+
+impl ClockPhases {
+    pub const ALL: &[ClockPhases] = &[ClockPhases::Two, ClockPhases::Three, ClockPhases::Four];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ClockPhases::Two => "2-Phase",
+            ClockPhases::Three => "3-Phase",
+            ClockPhases::Four => "4-Phase",
+        }
+    }
+
+    pub fn count(self) -> usize {
+        match self {
+            ClockPhases::Two => 2,
+            ClockPhases::Three => 3,
+            ClockPhases::Four => 4,
+        }
+    }
+
+    pub fn from_count(count: u8) -> Self {
+        match count {
+            0..=2 => ClockPhases::Two,
+            3 => ClockPhases::Three,
+            _ => ClockPhases::Four,
+        }
+    }
+
+    /// Fraction of the pixel under collecting gates during integration,
+    /// which sets full well for a given pixel size.
+    pub fn well_fraction(self) -> f64 {
+        match self {
+            ClockPhases::Two => 0.5,
+            ClockPhases::Three => 2.0 / 3.0,
+            ClockPhases::Four => 0.75,
+        }
+    }
+
+    fn row_faults(self) -> &'static [RowFault] {
+        match self {
+            ClockPhases::Two => &[RowFault::Skip, RowFault::Repeat, RowFault::Shift],
+            ClockPhases::Three => {
+                &[RowFault::Skip, RowFault::Repeat, RowFault::Reverse, RowFault::Shift]
+            }
+            ClockPhases::Four => &[
+                RowFault::Skip,
+                RowFault::Repeat,
+                RowFault::Reverse,
+                RowFault::Shift,
+                RowFault::Split,
+                RowFault::Backstep,
+            ],
+        }
+    }
+}
+
+/// Row-level V-clock faults.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RowFault {
+    /// Copy from the adjacent row.
+    Skip,
+    /// Duplicate this row into the next.
+    Repeat,
+    /// Mirror the row horizontally.
+    Reverse,
+    /// Shift the row horizontally.
+    Shift,
+    /// Packet stranded between rows: half of it merges into the next row.
+    Split,
+    /// Mis-sequenced phases push the row back past its predecessor.
+    Backstep,
+}
+
+/// Shape of the waveform distortion applied across the frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModulationShape {
+    Sine,
+    Sawtooth,
+    Noise,
+    /// Fast attack and exponential decay each cycle, like a strobe flash or
+    /// a struck note.
+    Envelope,
+    /// User-loaded 1D curve, see `Modulation::curve`.
+    Curve,
+}
+
+impl ModulationShape {
+    pub const ALL: &[ModulationShape] = &[
+        ModulationShape::Sine,
+        ModulationShape::Sawtooth,
+        ModulationShape::Noise,
+        ModulationShape::Envelope,
+        ModulationShape::Curve,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ModulationShape::Sine => "Sine",
+            ModulationShape::Sawtooth => "Sawtooth",
+            ModulationShape::Noise => "Noise",
+            ModulationShape::Envelope => "Envelope",
+            ModulationShape::Curve => "Curve",
+        }
+    }
+}
+
+/// Modulation source for V-clock waveform distortion and row exposure.
+///
+/// `frequency` is in cycles per frame and `phase` in cycles, for every shape;
+/// a loaded curve is one cycle, so frequency 1 stretches it over the frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Modulation {
+    pub shape: ModulationShape,
+    pub frequency: f64,
+    pub phase: f64,
+    /// Curve samples in -1..1, evenly spaced over one cycle.
+    pub curve: Vec<f64>,
+}
+
+impl Default for Modulation {
+    fn default() -> Self {
+        Self {
+            shape: ModulationShape::Sine,
+            frequency: 4.0,
+            phase: 0.0,
+            curve: Vec::new(),
+        }
+    }
+}
+
+impl Modulation {
+    /// Modulation value in -1..1 at frame position `t` (0 = first row, 1 = last).
+    pub fn sample(&self, t: f64) -> f64 {
+        let x = t * self.frequency + self.phase;
+        match self.shape {
+            ModulationShape::Sine => libm::sin(x * std::f64::consts::TAU),
+            ModulationShape::Sawtooth => 2.0 * x.rem_euclid(1.0) - 1.0,
+            ModulationShape::Noise => {
+                // Smooth value noise: random knots at integer x, cosine-interpolated
+                let i = x.floor();
+                let f = x - i;
+                let blend = (1.0 - libm::cos(f * std::f64::consts::PI)) * 0.5;
+                let a = knot_value(i as i64);
+                let b = knot_value(i as i64 + 1);
+                a + (b - a) * blend
+            }
+            ModulationShape::Envelope => {
+                const ATTACK: f64 = 0.05;
+                let f = x.rem_euclid(1.0);
+                let level = if f < ATTACK {
+                    f / ATTACK
+                } else {
+                    libm::exp(-(f - ATTACK) * 6.0)
+                };
+                2.0 * level - 1.0
+            }
+            ModulationShape::Curve => {
+                let n = self.curve.len();
+                if n == 0 {
+                    return 0.0;
+                }
+                let pos = x.rem_euclid(1.0) * n as f64;
+                let i = pos.floor() as usize % n;
+                let f = pos - pos.floor();
+                self.curve[i] + (self.curve[(i + 1) % n] - self.curve[i]) * f
+            }
+        }
+    }
+
+    /// Parse a curve from text: numbers separated by commas, whitespace or newlines.
+    /// Values are rescaled to -1..1 if they fall outside it.
+    pub fn parse_curve(text: &str) -> Result<Vec<f64>, String> {
+        let values = text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<f64>().map_err(|_| format!("Invalid curve value: {s}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.len() < 2 {
+            return Err("Curve needs at least 2 values".to_string());
+        }
+        let peak = values.iter().fold(0.0f64, |m, v| m.max(v.abs()));
+        if peak > 1.0 {
+            Ok(values.iter().map(|v| v / peak).collect())
+        } else {
+            Ok(values)
+        }
+    }
+
+    /// Parse a curve from a PCM or float WAV file: channels are mixed down,
+    /// long recordings are decimated to `MAX_CURVE_POINTS` (keeping each
+    /// span's peak so transients survive), and the result is normalized to
+    /// -1..1.
+    pub fn parse_wav(bytes: &[u8]) -> Result<Vec<f64>, String> {
+        const MAX_CURVE_POINTS: usize = 4096;
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err("Not a WAV file".to_string());
+        }
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+        let u32_at = |i: usize| {
+            u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+        };
+
+        // (format, channels, bits per sample)
+        let mut format = None;
+        let mut data = None;
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let len = u32_at(pos + 4) as usize;
+            let body = pos + 8..(pos + 8 + len).min(bytes.len());
+            match &bytes[pos..pos + 4] {
+                b"fmt " if len >= 16 => {
+                    format = Some((u16_at(pos + 8), u16_at(pos + 10), u16_at(pos + 22)))
+                }
+                b"data" => data = Some(&bytes[body]),
+                _ => {}
+            }
+            // Chunks are padded to even lengths
+            pos += 8 + len + (len & 1);
+        }
+        let (tag, channels, bits) = format.ok_or("WAV file has no fmt chunk")?;
+        let data = data.ok_or("WAV file has no data chunk")?;
+        let channels = channels.max(1) as usize;
+
+        let sample: fn(&[u8]) -> f64 = match (tag, bits) {
+            (1, 8) => |b| (b[0] as f64 - 128.0) / 128.0,
+            (1, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f64 / 32768.0,
+            (1, 24) => |b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f64 / 2_147_483_648.0,
+            (1, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64 / 2_147_483_648.0,
+            (3, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            _ => return Err(format!("Unsupported WAV encoding (format {tag}, {bits}-bit)")),
+        };
+        let frame_bytes = channels * bits as usize / 8;
+        let mono: Vec<f64> = data
+            .chunks_exact(frame_bytes)
+            .map(|frame| {
+                let sum: f64 = frame.chunks_exact(bits as usize / 8).map(sample).sum();
+                sum / channels as f64
+            })
+            .collect();
+        if mono.len() < 2 {
+            return Err("WAV file needs at least 2 samples".to_string());
+        }
+
+        let span = mono.len().div_ceil(MAX_CURVE_POINTS);
+        let curve: Vec<f64> = mono
+            .chunks(span)
+            .map(|c| c.iter().copied().fold(0.0f64, |m, v| if v.abs() > m.abs() { v } else { m }))
+            .collect();
+        let peak = curve.iter().fold(0.0f64, |m, v| m.max(v.abs()));
+        if peak == 0.0 {
+            return Err("WAV file is silent".to_string());
+        }
+        Ok(curve.iter().map(|v| v / peak).collect())
+    }
+}
+
+/// Deterministic pseudo-random value in -1..1 for a noise knot.
+fn knot_value(i: i64) -> f64 {
+    let mut h = (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h ^= h >> 31;
+    h = h.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h ^= h >> 29;
+    (h >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+}
 
 /// Simulate vertical (parallel) charge transfer.
 ///
 /// Processes row-by-row from bottom to top, simulating the physical transfer
-/// of charge toward the serial register.
+/// of charge toward the serial register. With `wrap`, charge trailing off the
+/// top row lands on the bottom row so the output tiles vertically.
 pub fn vertical_transfer(
     grid: &mut [f64],
     width: usize,
@@ -11,9 +388,12 @@ pub fn vertical_transfer(
     cte: f64,
     glitch_rate: f64,
     waveform_distortion: f64,
+    modulation: &Modulation,
     parallel_smear: f64,
+    wrap: bool,
+    schedule: &GlitchSchedule,
+    phases: ClockPhases,
 ) {
-    let mut rng = rand::rng();
     let cti = 1.0 - cte.clamp(0.0, 1.0);
 
     // Simulate charge trailing from CTE loss
@@ -26,6 +406,11 @@ pub fn vertical_transfer(
                 grid[idx] -= lost;
                 grid[prev_idx] += lost;
             }
+            if wrap && height > 1 {
+                let lost = grid[x] * cti;
+                grid[x] -= lost;
+                grid[(height - 1) * width + x] += lost;
+            }
         }
     }
 
@@ -43,65 +428,294 @@ pub fn vertical_transfer(
         }
     }
 
-    // Waveform distortion: sinusoidal modulation of transfer amounts
+    // Waveform distortion: row-wise modulation of transfer amounts
     if waveform_distortion > 0.0 {
         for y in 0..height {
-            let phase = (y as f64 / height as f64) * std::f64::consts::TAU * 4.0;
-            let modulation = 1.0 + waveform_distortion * phase.sin();
+            let t = y as f64 / height as f64;
+            let scale = 1.0 + waveform_distortion * modulation.sample(t);
             for x in 0..width {
-                grid[y * width + x] *= modulation.max(0.0);
+                grid[y * width + x] *= scale.max(0.0);
             }
         }
     }
 
-    // V-clock glitches: random per-row faults
     if glitch_rate > 0.0 {
-        let mut temp_row = vec![0.0f64; width];
-        for y in 0..height {
-            if rng.random::<f64>() < glitch_rate {
-                let glitch_type = rng.random_range(0u32..4);
-                match glitch_type {
-                    0 => {
-                        // Row skip: copy from adjacent row
-                        let src_y = if y > 0 { y - 1 } else { y + 1 }.min(height - 1);
-                        for x in 0..width {
-                            grid[y * width + x] = grid[src_y * width + x];
-                        }
-                    }
-                    1 => {
-                        // Row repeat: duplicate this row to next
-                        if y + 1 < height {
-                            for x in 0..width {
-                                grid[(y + 1) * width + x] = grid[y * width + x];
-                            }
-                        }
-                    }
-                    2 => {
-                        // Row reverse
-                        for x in 0..width {
-                            temp_row[x] = grid[y * width + x];
-                        }
-                        for x in 0..width {
-                            grid[y * width + x] = temp_row[width - 1 - x];
-                        }
-                    }
-                    3 => {
-                        // Row horizontal shift
-                        let shift = rng.random_range(1..width.max(2).min(64));
-                        for x in 0..width {
-                            temp_row[x] = grid[y * width + x];
-                        }
-                        for x in 0..width {
-                            grid[y * width + x] = temp_row[(x + width - shift) % width];
-                        }
-                    }
-                    _ => {}
+        apply_v_glitches(grid, width, height, glitch_rate, schedule, phases, &mut schedule.rng());
+    }
+}
+
+/// V-clock glitches: per-row faults placed by the schedule, drawn from the
+/// faults the phase count allows. `vertical_transfer` runs this with the
+/// schedule's own RNG; sequences pass one held across frames instead.
+pub fn apply_v_glitches(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    glitch_rate: f64,
+    schedule: &GlitchSchedule,
+    phases: ClockPhases,
+    rng: &mut impl Rng,
+) {
+    let glitch_rows = schedule.rows(height, glitch_rate, rng);
+    let faults = phases.row_faults();
+    for (y, &glitched) in glitch_rows.iter().enumerate() {
+        if glitched {
+            let fault = faults[rng.random_range(0..faults.len() as u32) as usize];
+            apply_row_fault(grid, width, height, y, 0..width, fault, rng);
+        }
+    }
+}
+
+/// Apply `fault` to columns `span` of row `y`.
+fn apply_row_fault(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    y: usize,
+    span: std::ops::Range<usize>,
+    fault: RowFault,
+    rng: &mut impl Rng,
+) {
+    let row = y * width;
+    match fault {
+        RowFault::Skip => {
+            // Copy from the adjacent row
+            let src = if y > 0 { y - 1 } else { y + 1 }.min(height - 1) * width;
+            for x in span {
+                grid[row + x] = grid[src + x];
+            }
+        }
+        RowFault::Repeat => {
+            if y + 1 < height {
+                for x in span {
+                    grid[row + width + x] = grid[row + x];
+                }
+            }
+        }
+        RowFault::Reverse => {
+            grid[row + span.start..row + span.end].reverse();
+        }
+        RowFault::Shift => {
+            let len = span.len();
+            let shift = rng.random_range(1..len.max(2).min(64));
+            grid[row + span.start..row + span.end].rotate_right(shift % len.max(1));
+        }
+        RowFault::Split => {
+            if y + 1 < height {
+                for x in span {
+                    let half = grid[row + x] * 0.5;
+                    grid[row + x] -= half;
+                    grid[row + width + x] += half;
+                }
+            }
+        }
+        RowFault::Backstep => {
+            if y > 0 {
+                for x in span {
+                    grid.swap(row + x, row - width + x);
                 }
             }
         }
     }
 }
 
+/// Clock faults triggered by saturated charge: packets at the bloom limit
+/// dump charge onto the V-clock lines as they are clocked, so the fault rate
+/// rises on rows carrying many saturated pixels and stays high for a few rows
+/// after while the clocks recover.
+///
+/// Faults cover only the stretch of the row around its saturated pixels, so
+/// the damage clusters around overexposed regions. `coupling` scales the
+/// fault probability per unit of saturated fraction; 0 disables.
+pub fn apply_saturation_faults(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    coupling: f64,
+    saturation: f64,
+    phases: ClockPhases,
+    rng: &mut impl Rng,
+) {
+    if coupling <= 0.0 || width == 0 {
+        return;
+    }
+    // Rows of clock recovery after a saturated row, as a decay factor
+    const RECOVERY: f64 = 0.85;
+    const MARGIN: usize = 8;
+    let faults = phases.row_faults();
+    let level = saturation * 0.98;
+    let mut stress = 0.0f64;
+    let mut span = 0..0;
+
+    for y in 0..height {
+        let row = &grid[y * width..(y + 1) * width];
+        let mut count = 0;
+        let (mut first, mut last) = (width, 0);
+        for (x, &v) in row.iter().enumerate() {
+            if v >= level {
+                count += 1;
+                first = first.min(x);
+                last = x;
+            }
+        }
+        if count > 0 {
+            span = first.saturating_sub(MARGIN)..(last + MARGIN + 1).min(width);
+        }
+        stress = stress * RECOVERY + coupling * count as f64 / width as f64;
+        if span.is_empty() || rng.random::<f64>() >= stress.min(1.0) {
+            continue;
+        }
+        let fault = faults[rng.random_range(0..faults.len())];
+        apply_row_fault(grid, width, height, y, span.clone(), fault, rng);
+    }
+}
+
+/// A line-scan sensor: one row of photosites swept across the scene, read
+/// out straight into the serial register after every line, as in
+/// photo-finish and document scanners. Each output row is a separate
+/// exposure of the same line taken as the scan reaches its position, so the
+/// parallel register and its artifacts are gone and what varies from line to
+/// line in time shows as rows instead: scan speed wobble stretches and
+/// squeezes the image into bands, and each line's own exposure and noise
+/// streak across the frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineScan {
+    pub enabled: bool,
+    /// Scene rows the scan advances per line; below 1 stretches the image
+    /// along the scan, above 1 squeezes it.
+    pub speed: f64,
+    /// Peak fractional deviation of the scan speed from `speed`.
+    pub wobble: f64,
+    /// Cycles of speed wobble per frame.
+    pub wobble_frequency: f64,
+    /// RMS fractional variation of each line's exposure.
+    pub exposure_jitter: f64,
+    /// RMS offset added to all of a line's pixels, in electrons.
+    pub line_noise: f64,
+}
+
+impl Default for LineScan {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speed: 1.0,
+            wobble: 0.0,
+            wobble_frequency: 3.0,
+            exposure_jitter: 0.0,
+            line_noise: 0.0,
+        }
+    }
+}
+
+/// Read the frame out as a line-scan sensor, in place of the parallel
+/// transfer: row `t` of the readout frame becomes the line exposed when the
+/// scan reached its position at time `t`. Rows interpolate between scene
+/// rows of the same CFA color, so the mosaic keeps its pattern.
+pub fn line_scan_readout(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    scan: &LineScan,
+    rng: &mut impl Rng,
+) {
+    if !scan.enabled || width == 0 || height == 0 {
+        return;
+    }
+    let scene = grid.to_vec();
+    let last = (height - 1) as f64;
+    let mut position = 0.0;
+    for t in 0..height {
+        // Same-color rows sit two apart; stay on this row's parity
+        let parity = t % 2;
+        let top = if (height - 1) % 2 == parity { height - 1 } else { height - 2 };
+        let u = ((position - parity as f64) / 2.0).max(0.0);
+        let y0 = (u as usize * 2 + parity).min(top);
+        let y1 = (y0 + 2).min(top);
+        let frac = if y1 > y0 { u.fract() } else { 0.0 };
+
+        let gain = (1.0 + scan.exposure_jitter * gaussian(rng)).max(0.0);
+        let offset = scan.line_noise * gaussian(rng);
+        for x in 0..width {
+            let a = scene[y0 * width + x];
+            let b = scene[y1 * width + x];
+            grid[t * width + x] = ((a + (b - a) * frac) * gain + offset).max(0.0);
+        }
+
+        let phase = std::f64::consts::TAU * scan.wobble_frequency * t as f64 / height as f64;
+        let speed = scan.speed * (1.0 + scan.wobble * libm::sin(phase));
+        position = (position + speed.max(0.0)).min(last);
+    }
+}
+
+/// Time-delay integration: the scene moves down the columns while the
+/// parallel clocks shift each packet along with it, so a packet collects the
+/// same scene point under `stages` rows of photosites before it is read, as
+/// in scanners and satellite push-broom imagers. When the clocks and the
+/// scene fall out of step the point slides under the packet and the image
+/// smears in the direction it slips.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tdi {
+    pub enabled: bool,
+    /// Rows each packet integrates over.
+    pub stages: usize,
+    /// Fractional error of the scene speed against the row shift rate; the
+    /// along-column smear is `stages * sync_error` rows.
+    pub sync_error: f64,
+    /// Photosites the scene moves across the columns per row shift, as from
+    /// a sensor turned against the motion.
+    pub drift: f64,
+}
+
+impl Default for Tdi {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stages: 32,
+            sync_error: 0.0,
+            drift: 0.0,
+        }
+    }
+}
+
+/// Integrate the frame through a TDI sensor: each packet averages the scene
+/// at the points it passed under over its stages, which in step are all the
+/// same point. Sampling stays on photosites of the packet's CFA color.
+pub fn tdi_integrate(grid: &mut [f64], width: usize, height: usize, tdi: &Tdi) {
+    let in_step = tdi.sync_error == 0.0 && tdi.drift == 0.0;
+    if !tdi.enabled || tdi.stages <= 1 || in_step || width == 0 || height == 0 {
+        return;
+    }
+    let stages = tdi.stages;
+    let scene = grid.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = 0.0;
+            // The packet sat `k` shifts short of its final row; the scene
+            // has slipped by the error in every shift since
+            for k in 0..stages {
+                let (x0, x1, fx) = same_color(x, k as f64 * tdi.drift, width);
+                let (y0, y1, fy) = same_color(y, k as f64 * tdi.sync_error, height);
+                let top = scene[y0 * width + x0] * (1.0 - fx) + scene[y0 * width + x1] * fx;
+                let bottom = scene[y1 * width + x0] * (1.0 - fx) + scene[y1 * width + x1] * fx;
+                sum += top * (1.0 - fy) + bottom * fy;
+            }
+            grid[y * width + x] = sum / stages as f64;
+        }
+    }
+}
+
+/// The two photosites of `at`'s CFA color, two apart, around `at + offset`
+/// along a line of `len`, and the weight of the second.
+fn same_color(at: usize, offset: f64, len: usize) -> (usize, usize, f64) {
+    let parity = at % 2;
+    let last = if (len - 1) % 2 == parity { len - 1 } else { len - 2 };
+    let u = offset / 2.0;
+    let step = u.floor();
+    let clamp = |i: f64| (at as f64 + 2.0 * i).clamp(parity as f64, last as f64) as usize;
+    let (a, b) = (clamp(step), clamp(step + 1.0));
+    (a, b, if b > a { u - step } else { 0.0 })
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ReadoutDirection {
     LeftToRight,
@@ -109,101 +723,255 @@ pub enum ReadoutDirection {
     Alternating,
 }
 
-/// Simulate horizontal (serial) charge transfer.
-pub fn horizontal_transfer(
-    grid: &mut [f64],
+impl ReadoutDirection {
+    pub const ALL: &[ReadoutDirection] = &[
+        ReadoutDirection::LeftToRight,
+        ReadoutDirection::RightToLeft,
+        ReadoutDirection::Alternating,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReadoutDirection::LeftToRight => "Left to Right",
+            ReadoutDirection::RightToLeft => "Right to Left",
+            ReadoutDirection::Alternating => "Alternating",
+        }
+    }
+}
+
+/// Direction of parallel (vertical) charge transfer toward the serial register.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerticalDirection {
+    /// Serial register along the top edge; charge shifts upward.
+    BottomToTop,
+    /// Serial register along the bottom edge; charge shifts downward.
+    TopToBottom,
+}
+
+impl VerticalDirection {
+    pub const ALL: &[VerticalDirection] = &[VerticalDirection::BottomToTop, VerticalDirection::TopToBottom];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VerticalDirection::BottomToTop => "Bottom to Top",
+            VerticalDirection::TopToBottom => "Top to Bottom",
+        }
+    }
+}
+
+/// Rotation of the sensor relative to the image, in degrees clockwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SensorOrientation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl SensorOrientation {
+    pub const ALL: &[SensorOrientation] = &[
+        SensorOrientation::Deg0,
+        SensorOrientation::Deg90,
+        SensorOrientation::Deg180,
+        SensorOrientation::Deg270,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SensorOrientation::Deg0 => "0",
+            SensorOrientation::Deg90 => "90",
+            SensorOrientation::Deg180 => "180",
+            SensorOrientation::Deg270 => "270",
+        }
+    }
+}
+
+/// Mapping between image coordinates and the sensor's readout frame.
+///
+/// The readout stages (blooming, transfer, amplifier, ADC) always work with
+/// the serial register along row 0 and the amplifier at the bottom-right, so
+/// orienting the sensor is a matter of rotating/flipping the grid into that
+/// frame and back out afterwards.
+#[derive(Debug, Clone, Copy)]
+pub struct ReadoutFrame {
     width: usize,
     height: usize,
-    cte: f64,
+    orientation: SensorOrientation,
+    vertical: VerticalDirection,
+}
+
+impl ReadoutFrame {
+    pub fn new(
+        width: usize,
+        height: usize,
+        orientation: SensorOrientation,
+        vertical: VerticalDirection,
+    ) -> Self {
+        Self { width, height, orientation, vertical }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.orientation == SensorOrientation::Deg0 && self.vertical == VerticalDirection::BottomToTop
+    }
+
+    /// Width and height of the grid in the readout frame.
+    pub fn dims(&self) -> (usize, usize) {
+        match self.orientation {
+            SensorOrientation::Deg0 | SensorOrientation::Deg180 => (self.width, self.height),
+            SensorOrientation::Deg90 | SensorOrientation::Deg270 => (self.height, self.width),
+        }
+    }
+
+    /// Image-space index of readout-frame pixel (rx, ry).
+    fn image_index(&self, rx: usize, ry: usize) -> usize {
+        let (_, rh) = self.dims();
+        let ry = match self.vertical {
+            VerticalDirection::BottomToTop => ry,
+            VerticalDirection::TopToBottom => rh - 1 - ry,
+        };
+        let (w, h) = (self.width, self.height);
+        let (x, y) = match self.orientation {
+            SensorOrientation::Deg0 => (rx, ry),
+            SensorOrientation::Deg90 => (w - 1 - ry, rx),
+            SensorOrientation::Deg180 => (w - 1 - rx, h - 1 - ry),
+            SensorOrientation::Deg270 => (ry, h - 1 - rx),
+        };
+        y * w + x
+    }
+
+    /// Readout-frame row of image pixel (`x`, `y`). Row 0 sits next to the
+    /// serial register and is read first.
+    pub fn readout_row(&self, x: usize, y: usize) -> usize {
+        let (w, h) = (self.width, self.height);
+        let row = match self.orientation {
+            SensorOrientation::Deg0 => y,
+            SensorOrientation::Deg90 => w - 1 - x,
+            SensorOrientation::Deg180 => h - 1 - y,
+            SensorOrientation::Deg270 => x,
+        };
+        match self.vertical {
+            VerticalDirection::BottomToTop => row,
+            VerticalDirection::TopToBottom => self.dims().1 - 1 - row,
+        }
+    }
+
+    /// Rearrange an image-space grid into the readout frame.
+    pub fn enter(&self, grid: &mut Vec<f64>) {
+        if self.is_identity() {
+            return;
+        }
+        let (rw, rh) = self.dims();
+        let mut frame = Vec::with_capacity(grid.len());
+        for ry in 0..rh {
+            for rx in 0..rw {
+                frame.push(grid[self.image_index(rx, ry)]);
+            }
+        }
+        *grid = frame;
+    }
+
+    /// Rearrange a readout-frame grid back into image space.
+    pub fn exit(&self, grid: &mut Vec<f64>) {
+        if self.is_identity() {
+            return;
+        }
+        let (rw, rh) = self.dims();
+        let mut image = vec![0.0; grid.len()];
+        for ry in 0..rh {
+            for rx in 0..rw {
+                image[self.image_index(rx, ry)] = grid[ry * rw + rx];
+            }
+        }
+        *grid = image;
+    }
+}
+
+/// Horizontal (serial) charge transfer, as a row stage for `readout::stream`.
+///
+/// With `wrap`, CTE trailing and ringing carry across the row ends so the
+/// output tiles horizontally.
+pub struct SerialTransfer<R> {
+    cti: f64,
     glitch_rate: f64,
     ringing: f64,
-    direction: ReadoutDirection,
-) {
-    let mut rng = rand::rng();
-    let cti = 1.0 - cte.clamp(0.0, 1.0);
+    wrap: bool,
+    rng: R,
+}
 
-    for y in 0..height {
-        let left_to_right = match direction {
-            ReadoutDirection::LeftToRight => true,
-            ReadoutDirection::RightToLeft => false,
-            ReadoutDirection::Alternating => y % 2 == 0,
-        };
+impl<R: Rng> SerialTransfer<R> {
+    pub fn new(cte: f64, glitch_rate: f64, ringing: f64, wrap: bool, rng: R) -> Self {
+        Self {
+            cti: 1.0 - cte.clamp(0.0, 1.0),
+            glitch_rate,
+            ringing,
+            wrap,
+            rng,
+        }
+    }
+}
+
+impl<R: Rng> RowStage for SerialTransfer<R> {
+    fn read_row(&mut self, row: &mut [f64], clock: &RowClock) {
+        let (cti, wrap, width) = (self.cti, self.wrap, row.len());
 
         // CTE trailing in readout direction
         if cti > 0.0 {
-            if left_to_right {
+            if clock.last_first {
                 for x in (1..width).rev() {
-                    let idx = y * width + x;
-                    let prev = y * width + x - 1;
-                    let lost = grid[idx] * cti;
-                    grid[idx] -= lost;
-                    grid[prev] += lost;
+                    let lost = row[x] * cti;
+                    row[x] -= lost;
+                    row[x - 1] += lost;
+                }
+                if wrap && width > 1 {
+                    let lost = row[0] * cti;
+                    row[0] -= lost;
+                    row[width - 1] += lost;
                 }
             } else {
                 for x in 0..width.saturating_sub(1) {
-                    let idx = y * width + x;
-                    let next = y * width + x + 1;
-                    let lost = grid[idx] * cti;
-                    grid[idx] -= lost;
-                    grid[next] += lost;
+                    let lost = row[x] * cti;
+                    row[x] -= lost;
+                    row[x + 1] += lost;
+                }
+                if wrap && width > 1 {
+                    let lost = row[width - 1] * cti;
+                    row[width - 1] -= lost;
+                    row[0] += lost;
                 }
             }
         }
 
         // Ringing: damped oscillation after bright pixels
-        if ringing > 0.0 {
-            let row_start = y * width;
-            let mut ring_energy = 0.0f64;
-            if left_to_right {
-                for x in 0..width {
-                    let idx = row_start + x;
-                    let bright = grid[idx] > 10000.0;
-                    if bright {
-                        ring_energy = grid[idx] * ringing * 0.01;
-                    }
-                    if ring_energy.abs() > 0.1 {
-                        grid[idx] += ring_energy;
-                        ring_energy *= -0.7; // damped oscillation
-                    }
-                }
+        if self.ringing > 0.0 {
+            if clock.last_first {
+                ring_row(row, 0..width, self.ringing, wrap);
             } else {
-                for x in (0..width).rev() {
-                    let idx = row_start + x;
-                    let bright = grid[idx] > 10000.0;
-                    if bright {
-                        ring_energy = grid[idx] * ringing * 0.01;
-                    }
-                    if ring_energy.abs() > 0.1 {
-                        grid[idx] += ring_energy;
-                        ring_energy *= -0.7;
-                    }
-                }
+                ring_row(row, (0..width).rev(), self.ringing, wrap);
             }
         }
 
         // H-clock glitches
-        if glitch_rate > 0.0 {
+        if self.glitch_rate > 0.0 {
+            let rng = &mut self.rng;
             for x in 0..width {
-                if rng.random::<f64>() < glitch_rate {
-                    let idx = y * width + x;
+                if rng.random::<f64>() < self.glitch_rate {
                     let glitch_type = rng.random_range(0u32..3);
                     match glitch_type {
                         0 => {
                             // Pixel skip: replace with neighbor
                             let src_x = if x > 0 { x - 1 } else { x + 1 }.min(width - 1);
-                            grid[idx] = grid[y * width + src_x];
+                            row[x] = row[src_x];
                         }
                         1 => {
                             // Pixel repeat
                             if x + 1 < width {
-                                grid[y * width + x + 1] = grid[idx];
+                                row[x + 1] = row[x];
                             }
                         }
                         2 => {
                             // Pixel offset: shift value from a nearby pixel
                             let offset = rng.random_range(1..8.min(width));
-                            let src_x = (x + offset) % width;
-                            grid[idx] = grid[y * width + src_x];
+                            row[x] = row[(x + offset) % width];
                         }
                         _ => {}
                     }
@@ -212,3 +980,223 @@ pub fn horizontal_transfer(
         }
     }
 }
+
+/// Defective serial register stages. Each defect sits under the column
+/// whose charge it receives from the parallel register, so it damages that
+/// column in every row: a vertical line that does not move with the image
+/// or the parallel-register defects above it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SerialDefects {
+    /// Number of defective stages.
+    pub count: usize,
+    /// Fraction of the packet a trapping stage passes on; the rest leaks out
+    /// into the following pixels in readout order.
+    pub trap_cte: f64,
+    /// Capacity of a blocked stage as a fraction of full well; charge above
+    /// it is drained.
+    pub block_level: f64,
+    /// Placement seed; the same seed always picks the same columns.
+    pub seed: u64,
+}
+
+impl Default for SerialDefects {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            trap_cte: 0.6,
+            block_level: 0.15,
+            seed: 1,
+        }
+    }
+}
+
+impl SerialDefects {
+    /// Defective columns, each paired with whether it traps (true) or
+    /// blocks (false).
+    pub fn columns(&self, width: usize) -> Vec<(usize, bool)> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut columns: Vec<(usize, bool)> = Vec::with_capacity(self.count);
+        for _ in 0..self.count.min(width) {
+            let x = loop {
+                let x = rng.random_range(0..width);
+                if columns.iter().all(|&(c, _)| c != x) {
+                    break x;
+                }
+            };
+            columns.push((x, rng.random()));
+        }
+        columns
+    }
+}
+
+/// Apply serial register defects after horizontal transfer.
+///
+/// A trapping stage keeps `1 - trap_cte` of its column's packet and releases
+/// it over the next pixels read out, halving each time, leaving a dark line
+/// with a bright trail. A blocked stage clips its column at `block_level`
+/// of full well.
+pub struct SerialDefectStage {
+    columns: Vec<(usize, bool)>,
+    keep: f64,
+    capacity: f64,
+}
+
+impl SerialDefectStage {
+    pub fn new(defects: &SerialDefects, width: usize, full_well: f64) -> Self {
+        Self {
+            columns: defects.columns(width),
+            keep: defects.trap_cte.clamp(0.0, 1.0),
+            capacity: defects.block_level.max(0.0) * full_well,
+        }
+    }
+}
+
+impl RowStage for SerialDefectStage {
+    fn read_row(&mut self, row: &mut [f64], clock: &RowClock) {
+        const TRAIL: usize = 12;
+        let width = row.len();
+        for &(x, traps) in &self.columns {
+            if !traps {
+                row[x] = row[x].min(self.capacity);
+                continue;
+            }
+            // Pixels read after this one, matching the CTE trailing direction
+            let mut held = row[x] * (1.0 - self.keep);
+            row[x] -= held;
+            for step in 1..=TRAIL {
+                let next = if clock.last_first { x.checked_sub(step) } else { Some(x + step) };
+                let Some(next) = next.filter(|&n| n < width) else {
+                    break;
+                };
+                let release = if step == TRAIL { held } else { held * 0.5 };
+                row[next] += release;
+                held -= release;
+            }
+        }
+    }
+}
+
+/// Apply damped ringing along one row, visiting pixels in `order`.
+///
+/// With `wrap`, a dry run over the row first finds the energy still ringing
+/// at its end, which then seeds the start so the oscillation continues across
+/// the seam.
+fn ring_row(row: &mut [f64], order: impl Iterator<Item = usize> + Clone, ringing: f64, wrap: bool) {
+    let mut ring_energy = 0.0f64;
+    if wrap {
+        for x in order.clone() {
+            if row[x] > 10000.0 {
+                ring_energy = row[x] * ringing * 0.01;
+            }
+            if ring_energy.abs() > 0.1 {
+                ring_energy *= -0.7;
+            }
+        }
+    }
+
+    for x in order {
+        let bright = row[x] > 10000.0;
+        if bright {
+            ring_energy = row[x] * ringing * 0.01;
+        }
+        if ring_energy.abs() > 0.1 {
+            row[x] += ring_energy;
+            ring_energy *= -0.7; // damped oscillation
+        }
+    }
+}
+
+/// What fills the rows that were never read after a readout abort.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AbortFill {
+    /// No charge: the ADC outputs its bias level.
+    Bias,
+    /// Gaussian static around zero charge.
+    Noise,
+    /// The last row read out repeated to the bottom of the frame.
+    RepeatLastRow,
+}
+
+impl AbortFill {
+    pub const ALL: &[AbortFill] = &[AbortFill::Bias, AbortFill::Noise, AbortFill::RepeatLastRow];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AbortFill::Bias => "Bias",
+            AbortFill::Noise => "Noise",
+            AbortFill::RepeatLastRow => "Repeat Last Row",
+        }
+    }
+}
+
+/// Simulate power loss mid-readout: only the first `fraction` of rows (in
+/// readout order, starting at the serial register on row 0) are read, and the
+/// rest of the frame is filled according to `fill`.
+pub fn apply_readout_abort(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    fraction: f64,
+    fill: AbortFill,
+    noise_sigma: f64,
+    rng: &mut impl Rng,
+) {
+    if fraction >= 1.0 || height == 0 {
+        return;
+    }
+    let mut stage = ReadoutAbort::new(height, fraction, fill, noise_sigma, rng);
+    readout::stream(grid, width, height, ReadoutDirection::LeftToRight, &mut [&mut stage]);
+}
+
+/// `apply_readout_abort` as a row stage: rows after the power loss keep the
+/// fill instead of their charge.
+pub struct ReadoutAbort<R> {
+    rows_read: usize,
+    fill: AbortFill,
+    sigma: f64,
+    last: Vec<f64>,
+    rng: R,
+}
+
+impl<R: Rng> ReadoutAbort<R> {
+    pub fn new(height: usize, fraction: f64, fill: AbortFill, noise_sigma: f64, rng: R) -> Self {
+        let rows_read = if fraction >= 1.0 {
+            height
+        } else {
+            ((height as f64 * fraction.max(0.0)).round() as usize).min(height)
+        };
+        Self {
+            rows_read,
+            fill,
+            sigma: noise_sigma.max(0.0),
+            last: Vec::new(),
+            rng,
+        }
+    }
+}
+
+impl<R: Rng> RowStage for ReadoutAbort<R> {
+    fn read_row(&mut self, row: &mut [f64], clock: &RowClock) {
+        if clock.y < self.rows_read {
+            if self.fill == AbortFill::RepeatLastRow && clock.y + 1 == self.rows_read {
+                self.last = row.to_vec();
+            }
+            return;
+        }
+        match self.fill {
+            AbortFill::Bias => row.fill(0.0),
+            AbortFill::Noise => {
+                for v in row.iter_mut() {
+                    *v = (self.sigma * gaussian(&mut self.rng)).max(0.0);
+                }
+            }
+            AbortFill::RepeatLastRow => {
+                if self.last.is_empty() {
+                    row.fill(0.0);
+                } else {
+                    row.copy_from_slice(&self.last);
+                }
+            }
+        }
+    }
+}
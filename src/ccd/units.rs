@@ -0,0 +1,62 @@
+//! Physical units carried through the readout chain.
+//!
+//! Stage buffers stay plain `f64` grids, but the scalars that set their scale
+//! (full well, noise, gains, bias) are typed, so every conversion between
+//! charge, voltage and codes goes through one of the gains below.
+
+/// Charge of an electron in coulombs.
+pub const ELEMENTARY_CHARGE: f64 = 1.602_176_634e-19;
+
+/// Charge in electrons.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Electrons(pub f64);
+
+/// Signal voltage in volts.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Volts(pub f64);
+
+/// Digital output in ADC codes.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Adu(pub f64);
+
+/// Charge-to-voltage conversion gain of a sense node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConversionGain {
+    pub uv_per_e: f64,
+}
+
+impl ConversionGain {
+    pub fn uv_per_e(uv_per_e: f64) -> Self {
+        Self { uv_per_e }
+    }
+
+    /// Capacitance of a node with this gain, in farads: q / gain.
+    pub fn capacitance(self) -> f64 {
+        ELEMENTARY_CHARGE / (self.uv_per_e.max(1e-6) * 1e-6)
+    }
+
+    pub fn volts(self, charge: Electrons) -> Volts {
+        Volts(charge.0 * self.uv_per_e * 1e-6)
+    }
+
+    pub fn electrons(self, signal: Volts) -> Electrons {
+        Electrons(signal.0 / (self.uv_per_e.max(1e-6) * 1e-6))
+    }
+}
+
+/// ADC system gain in electrons per code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdcGain {
+    pub e_per_adu: f64,
+}
+
+impl AdcGain {
+    /// Gains below 0.001 e-/ADU are treated as 0.001, so codes stay finite.
+    pub fn e_per_adu(e_per_adu: f64) -> Self {
+        Self { e_per_adu: e_per_adu.max(0.001) }
+    }
+
+    pub fn adu(self, charge: Electrons) -> Adu {
+        Adu(charge.0 / self.e_per_adu)
+    }
+}
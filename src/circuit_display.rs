@@ -26,6 +26,16 @@ fn pipeline_stages(p: &PipelineParams, cache: &Option<SpiceCache>) -> Vec<Pipeli
     let fb = cache.as_ref().map(|c| &c.fallbacks);
 
     vec![
+        PipelineStage {
+            label: "OPTICS",
+            active: p.lens_distortion != 0.0 || p.defocus_radius > 0.0,
+            effects: vec![
+                ("Distort", p.lens_distortion != 0.0),
+                ("Defocus", p.defocus_radius > 0.0),
+            ],
+            spice_driven: false,
+            spice_fallback: false,
+        },
         PipelineStage {
             label: "SENSOR",
             active: true,
@@ -73,12 +83,16 @@ fn pipeline_stages(p: &PipelineParams, cache: &Option<SpiceCache>) -> Vec<Pipeli
             active: p.v_cte < d.v_cte
                 || p.v_glitch_rate > 0.0
                 || p.v_waveform_distortion > 0.0
-                || p.parallel_smear > 0.0,
+                || p.parallel_smear > 0.0
+                || p.line_scan.enabled
+                || p.tdi.enabled,
             effects: vec![
                 ("CTE", p.v_cte < d.v_cte),
                 ("Glitch", p.v_glitch_rate > 0.0),
                 ("Wave", p.v_waveform_distortion > 0.0),
                 ("Smear", p.parallel_smear > 0.0),
+                ("Line", p.line_scan.enabled),
+                ("TDI", p.tdi.enabled),
             ],
             spice_driven: spice_full,
             spice_fallback: spice_full && fb.is_some_and(|f| f.shift_register || f.clock_driver),
@@ -102,12 +116,18 @@ fn pipeline_stages(p: &PipelineParams, cache: &Option<SpiceCache>) -> Vec<Pipeli
             active: (p.amp_gain - d.amp_gain).abs() > 0.001
                 || p.nonlinearity > 0.0
                 || p.reset_noise > 0.0
-                || p.amp_glow > 0.0,
+                || p.amp_glow > 0.0
+                || p.amp_drift > 0.0
+                || p.supply.load > 0.0
+                || !p.thermal.is_off(),
             effects: vec![
                 ("Gain", (p.amp_gain - d.amp_gain).abs() > 0.001),
                 ("NL", p.nonlinearity > 0.0),
                 ("kTC", p.reset_noise > 0.0),
                 ("Glow", p.amp_glow > 0.0),
+                ("Drift", p.amp_drift > 0.0),
+                ("Sag", p.supply.load > 0.0),
+                ("Heat", !p.thermal.is_off()),
             ],
             spice_driven: spice_amp || spice_tf,
             spice_fallback: (spice_amp || spice_tf) && fb.is_some_and(|f| f.amplifier),
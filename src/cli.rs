@@ -0,0 +1,1677 @@
+//! Command-line entry points for headless runs.
+//!
+//! The GUI launches when no arguments are given; any arguments are routed
+//! through `run` instead.
+
+use crate::ccd::SensorPreset;
+use crate::image_io;
+use crate::pipeline::graph::{json_string, json_value};
+use crate::pipeline::memory;
+use crate::pipeline::preset::Preset;
+use crate::pipeline::schema::{CATEGORIES, ParamKind};
+use crate::pipeline::test_patterns::{self, TestPattern};
+use crate::pipeline::{self, PipelineParams, SheetAxis, TemporalState};
+use crate::spice::validation::Severity;
+
+const USAGE: &str = "\
+Usage:
+  physical_ccd_glitch                         Launch the GUI
+  physical_ccd_glitch --bench <image> [opts]  Print a per-stage timing breakdown
+  physical_ccd_glitch --contact-sheet <image> --cols <axis> --rows <axis> [opts]
+                                              Render a labeled grid sweeping two parameters
+  physical_ccd_glitch --noise-report [opts]   Characterize noise on a flat field
+  physical_ccd_glitch --ptc [opts]            Sweep flats and fit a photon transfer curve
+  physical_ccd_glitch --mtf [opts]            Measure slanted-edge MTF of the processed output
+  physical_ccd_glitch --graph [opts]          Export the resolved stage graph as JSON or dot
+  physical_ccd_glitch --morph <image> --to <preset> [opts]
+                                              Render a PNG sequence morphing between two settings
+  physical_ccd_glitch --variations <image> [opts]
+                                              Render one setting under several seeds, with a sheet
+  physical_ccd_glitch --metrics <image> [opts]
+                                              Print PSNR and SSIM against a clean reference
+  physical_ccd_glitch --stream [opts]         Process frames from stdin to stdout
+  physical_ccd_glitch --list-presets [--json] List sensor and glitch presets
+  physical_ccd_glitch --show-preset <name> [--json]
+                                              Print one preset's settings
+  physical_ccd_glitch --describe-params [--json]
+                                              Print every parameter's type, default and range
+
+Any <image> may instead be pattern:<name>, a synthetic source generated at
+the sensor size: gradient, checkerboard, slanted-edge, siemens-star,
+color-bars, hot-points, usaf.
+
+Bench options:
+  --size <W>x<H>   Sensor size to process at (default: 3072x2048)
+  --runs <N>       Number of runs to average over (default: 3)
+  --validate       Also scan each stage's output for NaN, infinite and
+                   negative electron values and report where they start
+
+Contact sheet options:
+  --cols <axis>    Parameter varied across columns
+  --rows <axis>    Parameter varied down rows
+  --size <W>x<H>   Sensor size to process at (default: 512x384)
+  --cell <W>       Width of each cell in pixels (default: 256)
+  -o <path>        Output image (default: contact_sheet.png)
+
+  An axis is name=a,b,c or name=start:end:steps, e.g. spice.vdd=10:20:5.
+
+Noise report options:
+  --size <W>x<H>   Sensor size to process at (default: 512x384)
+  --level <F>      Flat exposure as a fraction of full well (default: 0.5)
+  --set <name=v>   Override a parameter (repeatable)
+  --preset <path>  Apply a preset file (repeatable, in order with --set)
+  -o <path>        Output CSV (default: noise_report.csv)
+  --plot <path>    Also save a spectrum plot image
+
+PTC options:
+  --size <W>x<H>   Sensor size to process at (default: 512x384)
+  --steps <N>      Number of flat levels in the sweep (default: 16)
+  --set <name=v>   Override a parameter (repeatable)
+  --preset <path>  Apply a preset file (repeatable, in order with --set)
+  -o <path>        Output CSV (default: ptc.csv)
+  --plot <path>    Also save a log-log PTC plot image
+
+MTF options:
+  --size <W>x<H>   Sensor size to process at (default: 512x384)
+  --set <name=v>   Override a parameter (repeatable)
+  --preset <path>  Apply a preset file (repeatable, in order with --set)
+  -o <path>        Output CSV (default: mtf.csv)
+  --plot <path>    Also save an MTF plot image
+
+Graph options:
+  --size <W>x<H>   Sensor size to resolve at (default: 512x384)
+  --set <name=v>   Override a parameter (repeatable)
+  --preset <path>  Apply a preset file (repeatable, in order with --set)
+  -o <path>        Output file; a .dot extension writes Graphviz
+                   (default: pipeline_graph.json)
+
+Morph options:
+  --to <path>      Preset layered onto the start for the end (repeatable)
+  --frames <N>     Number of frames, both ends included (default: 25)
+  --size <W>x<H>   Sensor size to process at (default: 512x384)
+  --set <name=v>   Override a start parameter (repeatable)
+  --preset <path>  Apply a preset file to the start (repeatable, in order with --set)
+  -o <dir>         Output directory for frame_00000.png... (default: morph)
+
+Variations options:
+  --count <N>      Number of variations (default: 9)
+  --seed <N>       Seed of the first variation; the rest count up (default: random)
+  --size <W>x<H>   Sensor size to process at (default: 512x384)
+  --cell <W>       Width of each contact sheet cell in pixels (default: 256)
+  --set <name=v>   Override a parameter (repeatable)
+  --preset <path>  Apply a preset file (repeatable, in order with --set)
+  -o <dir>         Output directory for seed_<N>.png and sheet.png (default: variations)
+
+Metrics options:
+  --size <W>x<H>   Sensor size to process at (default: 512x384)
+  --set <name=v>   Override a parameter (repeatable)
+  --preset <path>  Apply a preset file (repeatable, in order with --set)
+  -o <path>        Also save the render
+  --diff <path>    Also save the difference from the reference, amplified
+  --gain <F>       Difference amplification for --diff (default: 4)
+
+  The reference renders the same framing, levels and color with every
+  glitch and noise source off.
+
+Stream options:
+  --in <fmt>           Input frames: png (concatenated PNGs) or rgb (raw
+                       rgb24, needs --input-size) (default: png)
+  --input-size <W>x<H> Frame size of raw rgb input
+  --out <fmt>          Output stream: y4m (4:4:4) or png (default: y4m)
+  --fps <N[/D]>        Frame rate in the y4m header (default: 25)
+  --size <W>x<H>       Sensor size (default: the input frame size)
+  --set <name=v>       Override a parameter (repeatable)
+  --preset <path>      Apply a preset file (repeatable, in order with --set)
+  --seed <N>           Base seed of the glitch patterns (default: random)
+
+  Glitches persist, drift and decay across frames per the temporal.*
+  parameters, e.g. --set temporal.persistence=12 --set temporal.drift=4.
+
+  e.g. ffmpeg -i in.mp4 -f image2pipe -c:v png - |
+         physical_ccd_glitch --stream --set v_glitch_rate=0.02 |
+         ffmpeg -i - out.mp4";
+
+/// Run the CLI with the given arguments (program name excluded).
+/// Returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("--bench") => match parse_bench_args(&args[1..]) {
+            Ok(opts) => bench(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--contact-sheet") => match parse_sheet_args(&args[1..]) {
+            Ok(opts) => contact_sheet(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--noise-report") => match parse_noise_args(&args[1..]) {
+            Ok(opts) => noise_report(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--ptc") => match parse_ptc_args(&args[1..]) {
+            Ok(opts) => ptc(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--mtf") => match parse_mtf_args(&args[1..]) {
+            Ok(opts) => mtf(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--graph") => match parse_graph_args(&args[1..]) {
+            Ok(opts) => graph(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--morph") => match parse_morph_args(&args[1..]) {
+            Ok(opts) => morph(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--variations") => match parse_variations_args(&args[1..]) {
+            Ok(opts) => variations(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--metrics") => match parse_metrics_args(&args[1..]) {
+            Ok(opts) => metrics(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--list-presets") => match parse_info_args(&args[1..], false) {
+            Ok(opts) => list_presets(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--show-preset") => match parse_info_args(&args[1..], true) {
+            Ok(opts) => show_preset(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--describe-params") => match parse_info_args(&args[1..], false) {
+            Ok(opts) => describe_params(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--stream") => match parse_stream_args(&args[1..]) {
+            Ok(opts) => stream(&opts),
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                2
+            }
+        },
+        Some("--help") | Some("-h") => {
+            println!("{USAGE}");
+            0
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            2
+        }
+    }
+}
+
+struct BenchOptions {
+    image: std::path::PathBuf,
+    width: u32,
+    height: u32,
+    runs: usize,
+    validate: bool,
+}
+
+fn parse_bench_args(args: &[String]) -> Result<BenchOptions, String> {
+    let mut image = None;
+    let mut width = 3072;
+    let mut height = 2048;
+    let mut runs = 3;
+    let mut validate = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--size" => {
+                let value = iter.next().ok_or("--size needs a value")?;
+                (width, height) = parse_size(value)?;
+            }
+            "--runs" => {
+                let value = iter.next().ok_or("--runs needs a value")?;
+                runs = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid run count: {value}"))?
+                    .max(1);
+            }
+            "--validate" => validate = true,
+            other if image.is_none() && !other.starts_with("--") => {
+                image = Some(std::path::PathBuf::from(other));
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+
+    Ok(BenchOptions {
+        image: image.ok_or("--bench needs an input image")?,
+        width,
+        height,
+        runs,
+        validate,
+    })
+}
+
+/// Load an image file, or generate a test pattern for `pattern:<name>`.
+fn load_source(
+    path: &std::path::Path,
+    width: u32,
+    height: u32,
+) -> Result<image::DynamicImage, String> {
+    match path.to_str().and_then(|p| p.strip_prefix("pattern:")) {
+        Some(name) => {
+            let pattern = TestPattern::from_cli_name(name)?;
+            Ok(test_patterns::generate(pattern, width, height))
+        }
+        None => image_io::load_image(path),
+    }
+}
+
+/// Sensor size the analysis subcommands render at without `--size`.
+const ANALYSIS_SIZE: (u32, u32) = (512, 384);
+
+/// The options the analysis subcommands share: `--size`, `--set`, `--preset`
+/// and `-o`.
+#[derive(Default)]
+struct CommonOptions {
+    size: Option<(u32, u32)>,
+    overrides: Vec<(String, String)>,
+    output: Option<std::path::PathBuf>,
+}
+
+impl CommonOptions {
+    /// Consume `arg`, and its value from `iter`, if it is a shared option;
+    /// `false` leaves it to the subcommand's own parser.
+    fn take(
+        &mut self,
+        arg: &str,
+        iter: &mut std::slice::Iter<'_, String>,
+    ) -> Result<bool, String> {
+        match arg {
+            "--size" => {
+                let value = iter.next().ok_or("--size needs a value")?;
+                self.size = Some(parse_size(value)?);
+            }
+            "--set" => {
+                let value = iter.next().ok_or("--set needs a value")?;
+                let (name, v) = value
+                    .split_once('=')
+                    .ok_or_else(|| format!("Invalid override (expected name=value): {value}"))?;
+                self.overrides.push((name.to_string(), v.to_string()));
+            }
+            "--preset" => {
+                let value = iter.next().ok_or("--preset needs a value")?;
+                self.overrides.extend(Preset::load(std::path::Path::new(value))?.values);
+            }
+            "-o" | "--output" => {
+                let value = iter.next().ok_or("-o needs a value")?;
+                self.output = Some(std::path::PathBuf::from(value));
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let (w, h) = value
+        .split_once('x')
+        .ok_or_else(|| format!("Invalid size (expected WxH): {value}"))?;
+    let w = w
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid width: {w}"))?;
+    let h = h
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid height: {h}"))?;
+    if w < 2 || h < 2 {
+        return Err(format!("Size too small: {value}"));
+    }
+    Ok((w, h))
+}
+
+fn bench(opts: &BenchOptions) -> i32 {
+    let params = PipelineParams {
+        sensor_width: opts.width,
+        sensor_height: opts.height,
+        validate_buffers: opts.validate,
+        ..PipelineParams::default()
+    };
+    let source = match load_source(&opts.image, opts.width, opts.height) {
+        Ok(img) => img,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    if let Err(e) = check_memory(&params, Some(&source)) {
+        eprintln!("{e}");
+        return 1;
+    }
+
+    println!(
+        "Benchmarking {} at {}x{} ({} runs)",
+        opts.image.display(),
+        opts.width,
+        opts.height,
+        opts.runs
+    );
+    for change in params.diff(&PipelineParams::default()) {
+        println!("  {}", change);
+    }
+    println!();
+
+    let mut totals: Vec<(&'static str, f64)> = Vec::new();
+    let mut issues = Vec::new();
+    for _ in 0..opts.runs {
+        let (_, _, _, timings) = pipeline::process_timed(&source, &params, &None);
+        issues = timings.issues;
+        for (i, (name, ms)) in timings.stages.into_iter().enumerate() {
+            if i < totals.len() {
+                totals[i].1 += ms;
+            } else {
+                totals.push((name, ms));
+            }
+        }
+    }
+
+    let total_ms: f64 = totals.iter().map(|(_, ms)| ms).sum::<f64>() / opts.runs as f64;
+    for (name, ms) in &totals {
+        let avg = ms / opts.runs as f64;
+        let pct = if total_ms > 0.0 {
+            avg / total_ms * 100.0
+        } else {
+            0.0
+        };
+        println!("  {:<12} {:>10.2} ms  {:>5.1}%", name, avg, pct);
+    }
+    println!("  {:<12} {:>10.2} ms", "total", total_ms);
+    if !issues.is_empty() {
+        println!();
+        for issue in &issues {
+            println!("  invalid output: {issue}");
+        }
+    }
+    0
+}
+
+struct SheetOptions {
+    image: std::path::PathBuf,
+    columns: SheetAxis,
+    rows: SheetAxis,
+    width: u32,
+    height: u32,
+    cell_width: u32,
+    output: std::path::PathBuf,
+}
+
+fn parse_sheet_args(args: &[String]) -> Result<SheetOptions, String> {
+    let mut image = None;
+    let mut columns = None;
+    let mut rows = None;
+    let mut width = 512;
+    let mut height = 384;
+    let mut cell_width = 256;
+    let mut output = std::path::PathBuf::from("contact_sheet.png");
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--cols" => {
+                let value = iter.next().ok_or("--cols needs a value")?;
+                columns = Some(SheetAxis::parse(value)?);
+            }
+            "--rows" => {
+                let value = iter.next().ok_or("--rows needs a value")?;
+                rows = Some(SheetAxis::parse(value)?);
+            }
+            "--size" => {
+                let value = iter.next().ok_or("--size needs a value")?;
+                (width, height) = parse_size(value)?;
+            }
+            "--cell" => {
+                let value = iter.next().ok_or("--cell needs a value")?;
+                cell_width = value
+                    .parse::<u32>()
+                    .map_err(|_| format!("Invalid cell width: {value}"))?;
+            }
+            "-o" | "--output" => {
+                let value = iter.next().ok_or("-o needs a value")?;
+                output = std::path::PathBuf::from(value);
+            }
+            other if image.is_none() && !other.starts_with('-') => {
+                image = Some(std::path::PathBuf::from(other));
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+
+    Ok(SheetOptions {
+        image: image.ok_or("--contact-sheet needs an input image")?,
+        columns: columns.ok_or("--contact-sheet needs --cols")?,
+        rows: rows.ok_or("--contact-sheet needs --rows")?,
+        width,
+        height,
+        cell_width,
+        output,
+    })
+}
+
+fn contact_sheet(opts: &SheetOptions) -> i32 {
+    let params = PipelineParams {
+        sensor_width: opts.width,
+        sensor_height: opts.height,
+        ..PipelineParams::default()
+    };
+    let source = match load_source(&opts.image, opts.width, opts.height) {
+        Ok(img) => img,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    if let Err(e) = check_memory(&params, Some(&source)) {
+        eprintln!("{e}");
+        return 1;
+    }
+
+    println!(
+        "Rendering {} x {} contact sheet ({} x {} cells)",
+        opts.columns.param,
+        opts.rows.param,
+        opts.columns.values.len(),
+        opts.rows.values.len()
+    );
+
+    let result =
+        pipeline::contact_sheet(&source, &params, &opts.columns, &opts.rows, opts.cell_width)
+            .and_then(|sheet| image_io::save_image(&sheet, &opts.output));
+    match result {
+        Ok(()) => {
+            println!("Saved {}", opts.output.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+struct NoiseOptions {
+    width: u32,
+    height: u32,
+    level: f64,
+    overrides: Vec<(String, String)>,
+    output: std::path::PathBuf,
+    plot: Option<std::path::PathBuf>,
+}
+
+fn parse_noise_args(args: &[String]) -> Result<NoiseOptions, String> {
+    let mut level = 0.5;
+    let mut plot = None;
+
+    let mut common = CommonOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if common.take(arg, &mut iter)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--level" => {
+                let value = iter.next().ok_or("--level needs a value")?;
+                level = value
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid level: {value}"))?;
+            }
+            "--plot" => {
+                let value = iter.next().ok_or("--plot needs a value")?;
+                plot = Some(std::path::PathBuf::from(value));
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+
+    let (width, height) = common.size.unwrap_or(ANALYSIS_SIZE);
+    Ok(NoiseOptions {
+        width,
+        height,
+        level,
+        overrides: common.overrides,
+        output: common.output.unwrap_or_else(|| "noise_report.csv".into()),
+        plot,
+    })
+}
+
+/// Default params at the given size with overrides applied, plus the SPICE
+/// cache when SPICE is enabled.
+fn analysis_setup(
+    width: u32,
+    height: u32,
+    overrides: &[(String, String)],
+) -> Result<(PipelineParams, Option<crate::spice::SpiceCache>), String> {
+    let mut params = PipelineParams {
+        sensor_width: width,
+        sensor_height: height,
+        ..PipelineParams::default()
+    };
+    for (name, value) in overrides {
+        params.set_value(name, value)?;
+    }
+    check_memory(&params, None)?;
+
+    let mut spice_cache = None;
+    if params.spice.mode != crate::spice::SpiceMode::Off {
+        let sensor = params.spice_sensor();
+        check_spice(&mut params.spice, &sensor)?;
+        crate::spice::simulate_or_cache(&params.spice, &sensor, &mut spice_cache);
+    }
+    Ok((params, spice_cache))
+}
+
+/// Report SPICE parameter problems, clamping the degenerate ones that have a
+/// safe value and refusing the rest.
+fn check_spice(
+    spice: &mut crate::spice::SpiceParams,
+    sensor: &crate::spice::SpiceSensor,
+) -> Result<(), String> {
+    for cleared in spice.clamp_to_safe(sensor) {
+        if let Some(value) = cleared.safe_value {
+            eprintln!("{cleared}; clamped to {value:.3}");
+        }
+    }
+    let mut refused = Vec::new();
+    for warning in spice.validate(sensor) {
+        match warning.severity {
+            Severity::Warning => eprintln!("{warning}"),
+            Severity::Error => refused.push(warning.to_string()),
+        }
+    }
+    if !refused.is_empty() {
+        return Err(refused.join("\n"));
+    }
+    Ok(())
+}
+
+/// Refuse a render of `source` that would not fit in memory, before
+/// allocating for it; `None` stands for a source the size of the sensor.
+fn check_memory(
+    params: &PipelineParams,
+    source: Option<&image::DynamicImage>,
+) -> Result<(), String> {
+    let estimate = memory::estimate(params, source, &None);
+    memory::check(params, &estimate, memory::budget())
+}
+
+fn noise_report(opts: &NoiseOptions) -> i32 {
+    let (params, spice_cache) = match analysis_setup(opts.width, opts.height, &opts.overrides) {
+        Ok(setup) => setup,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+
+    println!(
+        "Noise report at {}x{}, flat at {:.0}% full well",
+        opts.width,
+        opts.height,
+        opts.level * 100.0
+    );
+    let report = pipeline::analysis::noise_report(&params, &spice_cache, opts.level);
+
+    for stage in &report.stages {
+        println!(
+            "  {:<12} mean {:>12.3} {:<4}  sigma {:>10.3}",
+            stage.stage,
+            stage.mean,
+            stage.units.name(),
+            stage.sigma
+        );
+    }
+    println!(
+        "  gain {:.3} e-/ADU, read noise {:.2} e-, full well {:.0} e-",
+        report.ptc.gain_e_per_adu, report.ptc.read_noise_e, report.ptc.full_well_e
+    );
+
+    if let Err(e) = std::fs::write(&opts.output, report.to_csv()) {
+        eprintln!("Failed to write {}: {e}", opts.output.display());
+        return 1;
+    }
+    println!("Wrote {}", opts.output.display());
+
+    if let Some(plot) = &opts.plot {
+        if let Err(e) = image_io::save_image(&report.plot(), plot) {
+            eprintln!("{e}");
+            return 1;
+        }
+        println!("Wrote {}", plot.display());
+    }
+    0
+}
+
+struct PtcOptions {
+    width: u32,
+    height: u32,
+    steps: usize,
+    overrides: Vec<(String, String)>,
+    output: std::path::PathBuf,
+    plot: Option<std::path::PathBuf>,
+}
+
+fn parse_ptc_args(args: &[String]) -> Result<PtcOptions, String> {
+    let mut steps = 16;
+    let mut plot = None;
+
+    let mut common = CommonOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if common.take(arg, &mut iter)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--steps" => {
+                let value = iter.next().ok_or("--steps needs a value")?;
+                steps = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n >= 4)
+                    .ok_or_else(|| format!("Invalid step count (need at least 4): {value}"))?;
+            }
+            "--plot" => {
+                let value = iter.next().ok_or("--plot needs a value")?;
+                plot = Some(std::path::PathBuf::from(value));
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+
+    let (width, height) = common.size.unwrap_or(ANALYSIS_SIZE);
+    Ok(PtcOptions {
+        width,
+        height,
+        steps,
+        overrides: common.overrides,
+        output: common.output.unwrap_or_else(|| "ptc.csv".into()),
+        plot,
+    })
+}
+
+fn ptc(opts: &PtcOptions) -> i32 {
+    let (params, spice_cache) = match analysis_setup(opts.width, opts.height, &opts.overrides) {
+        Ok(setup) => setup,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+
+    println!("PTC sweep at {}x{}, {} flat pairs", opts.width, opts.height, opts.steps);
+    let sweep = pipeline::analysis::ptc_sweep(&params, &spice_cache, opts.steps);
+
+    for point in &sweep.points {
+        println!(
+            "  level {:>7.4}  signal {:>12.3} ADU  noise {:>10.3} ADU",
+            point.level,
+            point.signal_adu,
+            point.variance_adu2.max(0.0).sqrt()
+        );
+    }
+    println!("  {:<16} {:>12} {:>12}", "", "measured", "expected");
+    for (name, measured, expected) in sweep.spec_rows() {
+        println!("  {name:<16} {measured:>12.3} {expected:>12.3}");
+    }
+
+    if let Err(e) = std::fs::write(&opts.output, sweep.to_csv()) {
+        eprintln!("Failed to write {}: {e}", opts.output.display());
+        return 1;
+    }
+    println!("Wrote {}", opts.output.display());
+
+    if let Some(plot) = &opts.plot {
+        if let Err(e) = image_io::save_image(&sweep.plot(), plot) {
+            eprintln!("{e}");
+            return 1;
+        }
+        println!("Wrote {}", plot.display());
+    }
+    0
+}
+
+struct MtfOptions {
+    width: u32,
+    height: u32,
+    overrides: Vec<(String, String)>,
+    output: std::path::PathBuf,
+    plot: Option<std::path::PathBuf>,
+}
+
+fn parse_mtf_args(args: &[String]) -> Result<MtfOptions, String> {
+    let mut plot = None;
+
+    let mut common = CommonOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if common.take(arg, &mut iter)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--plot" => {
+                let value = iter.next().ok_or("--plot needs a value")?;
+                plot = Some(std::path::PathBuf::from(value));
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+
+    let (width, height) = common.size.unwrap_or(ANALYSIS_SIZE);
+    Ok(MtfOptions {
+        width,
+        height,
+        overrides: common.overrides,
+        output: common.output.unwrap_or_else(|| "mtf.csv".into()),
+        plot,
+    })
+}
+
+fn mtf(opts: &MtfOptions) -> i32 {
+    let (params, spice_cache) = match analysis_setup(opts.width, opts.height, &opts.overrides) {
+        Ok(setup) => setup,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+
+    println!("Slanted-edge MTF at {}x{}", opts.width, opts.height);
+    let report = match pipeline::mtf::measure_mtf(&params, &spice_cache) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+
+    println!("  edge angle {:.2} deg", report.edge_angle_deg);
+    for curve in &report.curves {
+        let mtf50 = curve.mtf50.map_or("-".to_string(), |v| format!("{v:.3}"));
+        println!(
+            "  {:<6} MTF50 {:>6} cy/px  at Nyquist {:.3}",
+            curve.channel, mtf50, curve.at_nyquist
+        );
+    }
+
+    if let Err(e) = std::fs::write(&opts.output, report.to_csv()) {
+        eprintln!("Failed to write {}: {e}", opts.output.display());
+        return 1;
+    }
+    println!("Wrote {}", opts.output.display());
+
+    if let Some(plot) = &opts.plot {
+        if let Err(e) = image_io::save_image(&report.plot(), plot) {
+            eprintln!("{e}");
+            return 1;
+        }
+        println!("Wrote {}", plot.display());
+    }
+    0
+}
+
+struct GraphOptions {
+    width: u32,
+    height: u32,
+    overrides: Vec<(String, String)>,
+    output: std::path::PathBuf,
+}
+
+fn parse_graph_args(args: &[String]) -> Result<GraphOptions, String> {
+    let mut common = CommonOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if !common.take(arg, &mut iter)? {
+            return Err(format!("Unexpected argument: {arg}"));
+        }
+    }
+
+    let (width, height) = common.size.unwrap_or(ANALYSIS_SIZE);
+    Ok(GraphOptions {
+        width,
+        height,
+        overrides: common.overrides,
+        output: common.output.unwrap_or_else(|| "pipeline_graph.json".into()),
+    })
+}
+
+fn graph(opts: &GraphOptions) -> i32 {
+    let (params, spice_cache) = match analysis_setup(opts.width, opts.height, &opts.overrides) {
+        Ok(setup) => setup,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+
+    let graph = pipeline::graph::resolve(&params, &spice_cache);
+    println!("Pipeline graph (SPICE mode: {})", graph.spice_mode);
+    for node in &graph.nodes {
+        println!("  {:<14} {:<8} {}", node.name, node.engine.name(), node.note);
+    }
+
+    let dot = opts.output.extension().is_some_and(|e| e.eq_ignore_ascii_case("dot"));
+    let text = if dot { graph.to_dot() } else { graph.to_json() };
+    if let Err(e) = std::fs::write(&opts.output, text) {
+        eprintln!("Failed to write {}: {e}", opts.output.display());
+        return 1;
+    }
+    println!("Wrote {}", opts.output.display());
+    0
+}
+
+struct MorphOptions {
+    image: std::path::PathBuf,
+    width: u32,
+    height: u32,
+    frames: usize,
+    overrides: Vec<(String, String)>,
+    targets: Vec<(String, String)>,
+    output: std::path::PathBuf,
+}
+
+fn parse_morph_args(args: &[String]) -> Result<MorphOptions, String> {
+    let mut image = None;
+    let mut frames = 25;
+    let mut targets = Vec::new();
+
+    let mut common = CommonOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if common.take(arg, &mut iter)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--to" => {
+                let value = iter.next().ok_or("--to needs a value")?;
+                targets.extend(Preset::load(std::path::Path::new(value))?.values);
+            }
+            "--frames" => {
+                let value = iter.next().ok_or("--frames needs a value")?;
+                frames = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n >= 2)
+                    .ok_or_else(|| format!("Invalid frame count: {value}"))?;
+            }
+            other if image.is_none() && !other.starts_with("--") => {
+                image = Some(std::path::PathBuf::from(other));
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+
+    if targets.is_empty() {
+        return Err("--morph needs --to".to_string());
+    }
+
+    let (width, height) = common.size.unwrap_or(ANALYSIS_SIZE);
+
+    Ok(MorphOptions {
+        image: image.ok_or("--morph needs an input image")?,
+        width,
+        height,
+        frames,
+        overrides: common.overrides,
+        targets,
+        output: common.output.unwrap_or_else(|| "morph".into()),
+    })
+}
+
+fn morph(opts: &MorphOptions) -> i32 {
+    let source = match load_source(&opts.image, opts.width, opts.height) {
+        Ok(img) => img,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let (from, mut spice_cache) = match analysis_setup(opts.width, opts.height, &opts.overrides) {
+        Ok(setup) => setup,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+    let mut to = from.clone();
+    for (name, value) in &opts.targets {
+        if let Err(e) = to.set_value(name, value) {
+            eprintln!("{e}");
+            return 2;
+        }
+    }
+    if let Err(e) = std::fs::create_dir_all(&opts.output) {
+        eprintln!("Failed to create {}: {e}", opts.output.display());
+        return 1;
+    }
+
+    let changes = to.diff(&from);
+    println!("Morphing {} parameters over {} frames", changes.len(), opts.frames);
+    for change in &changes {
+        println!("  {}", change);
+    }
+    let result = pipeline::morph::render(
+        &source,
+        &from,
+        &to,
+        opts.frames,
+        &mut spice_cache,
+        |index, w, h, bytes| {
+            let img = image::RgbImage::from_raw(w as u32, h as u32, bytes)
+                .ok_or("Rendered frame does not match its size")?;
+            image_io::save_image(&img, &opts.output.join(format!("frame_{index:05}.png")))
+        },
+    );
+    match result {
+        Ok(()) => {
+            println!("Saved {} frames to {}", opts.frames, opts.output.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+struct VariationsOptions {
+    image: std::path::PathBuf,
+    width: u32,
+    height: u32,
+    count: usize,
+    seed: u64,
+    cell_width: u32,
+    overrides: Vec<(String, String)>,
+    output: std::path::PathBuf,
+}
+
+fn parse_variations_args(args: &[String]) -> Result<VariationsOptions, String> {
+    let mut image = None;
+    let mut count = 9;
+    let mut seed = None;
+    let mut cell_width = 256;
+
+    let mut common = CommonOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if common.take(arg, &mut iter)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--count" => {
+                let value = iter.next().ok_or("--count needs a value")?;
+                count = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n >= 1)
+                    .ok_or_else(|| format!("Invalid variation count: {value}"))?;
+            }
+            "--seed" => {
+                let value = iter.next().ok_or("--seed needs a value")?;
+                seed = Some(value.parse::<u64>().map_err(|_| format!("Invalid seed: {value}"))?);
+            }
+            "--cell" => {
+                let value = iter.next().ok_or("--cell needs a value")?;
+                cell_width = value
+                    .parse::<u32>()
+                    .map_err(|_| format!("Invalid cell width: {value}"))?;
+            }
+            other if image.is_none() && !other.starts_with("--") => {
+                image = Some(std::path::PathBuf::from(other));
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+
+    let (width, height) = common.size.unwrap_or(ANALYSIS_SIZE);
+    Ok(VariationsOptions {
+        image: image.ok_or("--variations needs an input image")?,
+        width,
+        height,
+        count,
+        // Keep seeds small enough to retype when picking a roll
+        seed: seed.unwrap_or_else(|| rand::random::<u32>() as u64),
+        cell_width,
+        overrides: common.overrides,
+        output: common.output.unwrap_or_else(|| "variations".into()),
+    })
+}
+
+fn variations(opts: &VariationsOptions) -> i32 {
+    let source = match load_source(&opts.image, opts.width, opts.height) {
+        Ok(img) => img,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let (params, spice_cache) = match analysis_setup(opts.width, opts.height, &opts.overrides) {
+        Ok(setup) => setup,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(&opts.output) {
+        eprintln!("Failed to create {}: {e}", opts.output.display());
+        return 1;
+    }
+
+    println!(
+        "Rendering {} variations, seeds {}..={}",
+        opts.count,
+        opts.seed,
+        opts.seed.wrapping_add(opts.count as u64 - 1)
+    );
+    let result = pipeline::variations::render(
+        &source,
+        &params,
+        opts.count,
+        opts.seed,
+        opts.cell_width,
+        &spice_cache,
+        |_, seed, img| image_io::save_image(img, &opts.output.join(format!("seed_{seed}.png"))),
+    )
+    .and_then(|sheet| image_io::save_image(&sheet, &opts.output.join("sheet.png")));
+    match result {
+        Ok(()) => {
+            println!("Saved {} variations and sheet.png to {}", opts.count, opts.output.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+struct MetricsOptions {
+    image: std::path::PathBuf,
+    width: u32,
+    height: u32,
+    overrides: Vec<(String, String)>,
+    output: Option<std::path::PathBuf>,
+    diff: Option<std::path::PathBuf>,
+    gain: f64,
+}
+
+fn parse_metrics_args(args: &[String]) -> Result<MetricsOptions, String> {
+    let mut image = None;
+    let mut diff = None;
+    let mut gain = 4.0;
+
+    let mut common = CommonOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if common.take(arg, &mut iter)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--diff" => {
+                let value = iter.next().ok_or("--diff needs a value")?;
+                diff = Some(std::path::PathBuf::from(value));
+            }
+            "--gain" => {
+                let value = iter.next().ok_or("--gain needs a value")?;
+                gain = value
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid gain: {value}"))?;
+            }
+            other if image.is_none() && !other.starts_with("--") => {
+                image = Some(std::path::PathBuf::from(other));
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+
+    let (width, height) = common.size.unwrap_or(ANALYSIS_SIZE);
+    Ok(MetricsOptions {
+        image: image.ok_or("--metrics needs an input image")?,
+        width,
+        height,
+        overrides: common.overrides,
+        output: common.output,
+        diff,
+        gain,
+    })
+}
+
+fn metrics(opts: &MetricsOptions) -> i32 {
+    let source = match load_source(&opts.image, opts.width, opts.height) {
+        Ok(img) => img,
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let (params, spice_cache) = match analysis_setup(opts.width, opts.height, &opts.overrides) {
+        Ok(setup) => setup,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+
+    let (w, h, mut bytes) = pipeline::process(&source, &params, &spice_cache);
+    let (rw, rh, reference) = pipeline::process_reference(&source, &params);
+    if (rw, rh) != (w, h) {
+        eprintln!("Reference rendered at {rw}x{rh}, not {w}x{h}");
+        return 1;
+    }
+    let metrics = pipeline::metrics(&bytes, &reference, w, h);
+    if metrics.psnr.is_finite() {
+        println!("PSNR {:.2} dB", metrics.psnr);
+    } else {
+        println!("PSNR inf (identical to the reference)");
+    }
+    println!("SSIM {:.4}", metrics.ssim);
+
+    let save = |bytes: Vec<u8>, path: &std::path::Path| -> Result<(), String> {
+        let img = image::RgbImage::from_raw(w as u32, h as u32, bytes)
+            .ok_or("Rendered buffer does not match its size")?;
+        image_io::save_image(&img, path)?;
+        println!("Wrote {}", path.display());
+        Ok(())
+    };
+    let mut result = Ok(());
+    if let Some(output) = &opts.output {
+        result = save(bytes.clone(), output);
+    }
+    if let Some(diff) = &opts.diff
+        && result.is_ok()
+    {
+        result = pipeline::difference_view(&mut bytes, &reference, opts.gain)
+            .and_then(|()| save(bytes, diff));
+    }
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+struct InfoOptions {
+    name: Option<String>,
+    json: bool,
+}
+
+/// Options shared by the introspection commands: `--json`, and a preset
+/// name when `takes_name`.
+fn parse_info_args(args: &[String], takes_name: bool) -> Result<InfoOptions, String> {
+    let mut name = None;
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            other if takes_name && name.is_none() && !other.starts_with('-') => {
+                name = Some(other.to_string());
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+    if takes_name && name.is_none() {
+        return Err("--show-preset needs a preset name".to_string());
+    }
+    Ok(InfoOptions { name, json })
+}
+
+fn list_presets(opts: &InfoOptions) -> i32 {
+    let glitch = Preset::glitch_presets();
+    if opts.json {
+        let sensor: Vec<_> = SensorPreset::ALL.iter().map(|p| json_string(p.name())).collect();
+        let glitch: Vec<_> = glitch.iter().map(|p| json_string(&p.name)).collect();
+        println!(
+            "{{\n  \"sensor_presets\": [{}],\n  \"glitch_presets\": [{}]\n}}",
+            sensor.join(", "),
+            glitch.join(", ")
+        );
+        return 0;
+    }
+
+    println!("Sensor presets:");
+    for &preset in SensorPreset::ALL {
+        let config = preset.config();
+        println!(
+            "  {:<14} {}x{}, {:?}, {:.0} e- full well",
+            preset.name(),
+            config.width,
+            config.height,
+            config.architecture,
+            config.full_well_no_abg
+        );
+    }
+    println!("Glitch presets:");
+    for preset in &glitch {
+        let names: Vec<_> = preset.values.iter().map(|(name, _)| name.as_str()).collect();
+        println!("  {:<14} {}", preset.name, names.join(", "));
+    }
+    0
+}
+
+fn show_preset(opts: &InfoOptions) -> i32 {
+    let name = opts.name.as_deref().unwrap_or_default();
+    if let Some(&preset) = SensorPreset::ALL.iter().find(|p| p.name().eq_ignore_ascii_case(name)) {
+        let c = preset.config();
+        let fast = c.fast_readout;
+        if opts.json {
+            println!(
+                "{{\n  \"name\": {},\n  \"kind\": \"sensor\",\n  \"width\": {},\n  \
+                 \"height\": {},\n  \"pixel_size_um\": [{}, {}],\n  \"full_well_no_abg\": {},\n  \
+                 \"full_well_abg\": {},\n  \"architecture\": {},\n  \"v_phases\": {},\n  \
+                 \"read_noise_e\": {},\n  \"dark_current_pa_cm2\": {},\n  \"cte_vertical\": {},\n  \
+                 \"cte_horizontal\": {},\n  \"gain_uv_per_e\": {},\n  \"fast_readout\": {{\n    \
+                 \"read_noise\": {},\n    \"cte_loss\": {},\n    \"ringing\": {},\n    \
+                 \"bit_depth\": {}\n  }}\n}}",
+                json_string(preset.name()),
+                c.width,
+                c.height,
+                c.pixel_size_um.0,
+                c.pixel_size_um.1,
+                c.full_well_no_abg,
+                c.full_well_abg,
+                json_string(&format!("{:?}", c.architecture)),
+                c.v_phases,
+                c.read_noise_e,
+                c.dark_current_pa_cm2,
+                c.cte_vertical,
+                c.cte_horizontal,
+                c.gain_uv_per_e,
+                fast.read_noise,
+                fast.cte_loss,
+                fast.ringing,
+                fast.bit_depth,
+            );
+            return 0;
+        }
+        println!("Sensor preset {}", preset.name());
+        println!("  size           {} x {}", c.width, c.height);
+        println!("  pixel size     {} x {} um", c.pixel_size_um.0, c.pixel_size_um.1);
+        println!("  full well      {} e- ({} e- with ABG)", c.full_well_no_abg, c.full_well_abg);
+        println!("  architecture   {:?}, {} clock phases", c.architecture, c.v_phases);
+        println!("  read noise     {} e-", c.read_noise_e);
+        println!("  dark current   {} pA/cm2", c.dark_current_pa_cm2);
+        println!("  CTE            {} vertical, {} horizontal", c.cte_vertical, c.cte_horizontal);
+        println!("  gain           {} uV/e-", c.gain_uv_per_e);
+        println!(
+            "  fast readout   x{} read noise, x{} CTE loss, +{} ringing, {}-bit cap",
+            fast.read_noise, fast.cte_loss, fast.ringing, fast.bit_depth
+        );
+        return 0;
+    }
+
+    let glitch = Preset::glitch_presets();
+    let Some(preset) = glitch.iter().find(|p| p.name.eq_ignore_ascii_case(name)) else {
+        eprintln!("Unknown preset: {name} (see --list-presets)");
+        return 2;
+    };
+    if opts.json {
+        let values: Vec<_> = preset
+            .values
+            .iter()
+            .map(|(name, value)| format!("    {}: {}", json_string(name), json_string(value)))
+            .collect();
+        println!(
+            "{{\n  \"name\": {},\n  \"kind\": \"glitch\",\n  \"values\": {{\n{}\n  }}\n}}",
+            json_string(&preset.name),
+            values.join(",\n")
+        );
+    } else {
+        print!("{}", preset.to_text());
+    }
+    0
+}
+
+/// Every settable parameter from the schema, with its type, default, range,
+/// category and label.
+fn describe_params(opts: &InfoOptions) -> i32 {
+    let defaults = PipelineParams::default().values();
+    let default = |name: &str| {
+        defaults
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value.clone())
+            .expect("schema parameter missing from PipelineParams::values()")
+    };
+    let type_name = |kind: ParamKind| match kind {
+        ParamKind::Float { .. } => "float",
+        ParamKind::Int { .. } => "int",
+        ParamKind::Bool => "bool",
+        ParamKind::Choice(_) => "choice",
+    };
+
+    if opts.json {
+        let categories: Vec<_> = CATEGORIES
+            .iter()
+            .map(|(name, label)| {
+                format!("{{\"name\": {}, \"label\": {}}}", json_string(name), json_string(label))
+            })
+            .collect();
+        let params: Vec<_> = PipelineParams::schema()
+            .iter()
+            .map(|spec| {
+                let range = match spec.kind {
+                    ParamKind::Float { min, max } => format!(", \"min\": {min}, \"max\": {max}"),
+                    ParamKind::Int { min, max } => format!(", \"min\": {min}, \"max\": {max}"),
+                    ParamKind::Bool => String::new(),
+                    ParamKind::Choice(options) => {
+                        let options: Vec<_> = options().into_iter().map(json_string).collect();
+                        format!(", \"options\": [{}]", options.join(", "))
+                    }
+                };
+                format!(
+                    "    {{\"name\": {}, \"type\": \"{}\", \"default\": {}{range}, \
+                     \"category\": {}, \"label\": {}, \"doc\": {}}}",
+                    json_string(spec.name),
+                    type_name(spec.kind),
+                    json_value(&default(spec.name)),
+                    json_string(spec.category),
+                    json_string(spec.label),
+                    json_string(spec.doc)
+                )
+            })
+            .collect();
+        println!(
+            "{{\n  \"categories\": [{}],\n  \"params\": [\n{}\n  ]\n}}",
+            categories.join(", "),
+            params.join(",\n")
+        );
+        return 0;
+    }
+
+    println!("{:<34} {:<6} {:<12} {:<20} Label", "Name", "Type", "Default", "Range");
+    for spec in PipelineParams::schema() {
+        let range = match spec.kind {
+            ParamKind::Float { min, max } => format!("{min} .. {max}"),
+            ParamKind::Int { min, max } => format!("{min} .. {max}"),
+            ParamKind::Bool => "on | off".to_string(),
+            ParamKind::Choice(options) => options().join(" | "),
+        };
+        let category = CATEGORIES
+            .iter()
+            .find(|(name, _)| *name == spec.category)
+            .map_or(spec.category, |(_, label)| *label);
+        println!(
+            "{:<34} {:<6} {:<12} {range:<20} {category}: {}",
+            spec.name,
+            type_name(spec.kind),
+            default(spec.name).to_string(),
+            spec.label
+        );
+        println!("    {}", spec.doc);
+    }
+    0
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum StreamInput {
+    Png,
+    Rgb { width: u32, height: u32 },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum StreamOutput {
+    Y4m,
+    Png,
+}
+
+struct StreamOptions {
+    input: StreamInput,
+    output: StreamOutput,
+    fps: (u32, u32),
+    size: Option<(u32, u32)>,
+    overrides: Vec<(String, String)>,
+    seed: Option<u64>,
+}
+
+fn parse_stream_args(args: &[String]) -> Result<StreamOptions, String> {
+    let mut raw = false;
+    let mut input_size = None;
+    let mut output = StreamOutput::Y4m;
+    let mut fps = (25, 1);
+    let mut seed = None;
+
+    let mut common = CommonOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if common.take(arg, &mut iter)? {
+            continue;
+        }
+        match arg.as_str() {
+            "--in" => {
+                let value = iter.next().ok_or("--in needs a value")?;
+                raw = match value.as_str() {
+                    "png" => false,
+                    "rgb" | "rgb24" => true,
+                    other => return Err(format!("Unknown input format: {other}")),
+                };
+            }
+            "--input-size" => {
+                let value = iter.next().ok_or("--input-size needs a value")?;
+                input_size = Some(parse_size(value)?);
+            }
+            "--out" => {
+                let value = iter.next().ok_or("--out needs a value")?;
+                output = match value.as_str() {
+                    "y4m" => StreamOutput::Y4m,
+                    "png" => StreamOutput::Png,
+                    other => return Err(format!("Unknown output format: {other}")),
+                };
+            }
+            "--fps" => {
+                let value = iter.next().ok_or("--fps needs a value")?;
+                let (n, d) = value.split_once('/').unwrap_or((value, "1"));
+                fps = match (n.parse::<u32>(), d.parse::<u32>()) {
+                    (Ok(n), Ok(d)) if n > 0 && d > 0 => (n, d),
+                    _ => return Err(format!("Invalid frame rate: {value}")),
+                };
+            }
+            "--seed" => {
+                let value = iter.next().ok_or("--seed needs a value")?;
+                seed = Some(value.parse::<u64>().map_err(|_| format!("Invalid seed: {value}"))?);
+            }
+            other => return Err(format!("Unexpected argument: {other}")),
+        }
+    }
+    if common.output.is_some() {
+        return Err("--stream writes to stdout; -o is not supported".to_string());
+    }
+    let input = match (raw, input_size) {
+        (false, _) => StreamInput::Png,
+        (true, Some((width, height))) => StreamInput::Rgb { width, height },
+        (true, None) => return Err("--in rgb needs --input-size".to_string()),
+    };
+    Ok(StreamOptions {
+        input,
+        output,
+        fps,
+        size: common.size,
+        overrides: common.overrides,
+        seed,
+    })
+}
+
+/// Read the next frame from `reader`, or `None` at a clean end of stream.
+fn read_frame(
+    reader: &mut impl std::io::BufRead,
+    input: StreamInput,
+) -> Result<Option<image::DynamicImage>, String> {
+    let bytes = match input {
+        StreamInput::Png => read_png_bytes(reader)?,
+        StreamInput::Rgb { width, height } => {
+            read_exact_or_eof(reader, width as usize * height as usize * 3)?
+        }
+    };
+    let Some(bytes) = bytes else { return Ok(None) };
+    match input {
+        StreamInput::Png => image::load_from_memory_with_format(&bytes, image::ImageFormat::Png)
+            .map(Some)
+            .map_err(|e| format!("Failed to decode PNG frame: {e}")),
+        StreamInput::Rgb { width, height } => Ok(image::RgbImage::from_raw(width, height, bytes)
+            .map(image::DynamicImage::ImageRgb8)),
+    }
+}
+
+/// Fill `len` bytes, or return `None` if the stream ends before the first.
+fn read_exact_or_eof(
+    reader: &mut impl std::io::BufRead,
+    len: usize,
+) -> Result<Option<Vec<u8>>, String> {
+    if reader.fill_buf().map_err(|e| e.to_string())?.is_empty() {
+        return Ok(None);
+    }
+    let mut buf = vec![0; len];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|e| format!("Truncated frame: {e}"))?;
+    Ok(Some(buf))
+}
+
+/// Split one PNG off a concatenated stream by walking its chunks to `IEND`.
+fn read_png_bytes(reader: &mut impl std::io::BufRead) -> Result<Option<Vec<u8>>, String> {
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    let Some(mut png) = read_exact_or_eof(reader, 8)? else { return Ok(None) };
+    if png != SIGNATURE {
+        return Err("Input is not a PNG stream".to_string());
+    }
+    loop {
+        let mut header = [0; 8];
+        reader
+            .read_exact(&mut header)
+            .map_err(|e| format!("Truncated PNG frame: {e}"))?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        // Chunk data plus CRC
+        let mut body = vec![0; len + 4];
+        reader
+            .read_exact(&mut body)
+            .map_err(|e| format!("Truncated PNG frame: {e}"))?;
+        png.extend_from_slice(&header);
+        png.extend_from_slice(&body);
+        if &header[4..] == b"IEND" {
+            return Ok(Some(png));
+        }
+    }
+}
+
+/// Write a YUV4MPEG2 frame as BT.601 limited-range 4:4:4.
+fn write_y4m_frame(out: &mut impl std::io::Write, rgb: &[u8]) -> std::io::Result<()> {
+    let n = rgb.len() / 3;
+    let mut planes = vec![0u8; n * 3];
+    for (i, px) in rgb.chunks_exact(3).enumerate() {
+        let [r, g, b] = [px[0], px[1], px[2]].map(|v| v as f32 / 255.0);
+        planes[i] = (16.0 + 65.481 * r + 128.553 * g + 24.966 * b).round() as u8;
+        planes[n + i] = (128.0 - 37.797 * r - 74.203 * g + 112.0 * b).round() as u8;
+        planes[2 * n + i] = (128.0 + 112.0 * r - 93.786 * g - 18.214 * b).round() as u8;
+    }
+    out.write_all(b"FRAME\n")?;
+    out.write_all(&planes)
+}
+
+fn stream(opts: &StreamOptions) -> i32 {
+    use std::io::Write;
+
+    let mut input = std::io::stdin().lock();
+    let mut output = std::io::BufWriter::new(std::io::stdout().lock());
+
+    let first = match read_frame(&mut input, opts.input) {
+        Ok(Some(frame)) => frame,
+        Ok(None) => {
+            eprintln!("No frames on stdin");
+            return 1;
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    };
+    let (width, height) = opts.size.unwrap_or((first.width(), first.height()));
+    let (params, spice_cache) = match analysis_setup(width, height, &opts.overrides) {
+        Ok(setup) => setup,
+        Err(e) => {
+            eprintln!("{e}");
+            return 2;
+        }
+    };
+
+    let mut temporal = opts.seed.map_or_else(TemporalState::default, TemporalState::seeded);
+    let mut frame = Some(first);
+    let mut count = 0usize;
+    while let Some(source) = frame {
+        let (w, h, rgb) = pipeline::process_temporal(&source, &params, &spice_cache, &mut temporal);
+        let written = match opts.output {
+            StreamOutput::Y4m => {
+                if count == 0 {
+                    let (n, d) = opts.fps;
+                    let header = format!("YUV4MPEG2 W{w} H{h} F{n}:{d} Ip A1:1 C444\n");
+                    if let Err(e) = output.write_all(header.as_bytes()) {
+                        eprintln!("Failed to write stdout: {e}");
+                        return 1;
+                    }
+                }
+                write_y4m_frame(&mut output, &rgb).map_err(|e| e.to_string())
+            }
+            StreamOutput::Png => {
+                // The PNG encoder needs Seek, which stdout lacks
+                let mut png = std::io::Cursor::new(Vec::new());
+                image::RgbImage::from_raw(w as u32, h as u32, rgb)
+                    .expect("pipeline output matches its size")
+                    .write_to(&mut png, image::ImageFormat::Png)
+                    .map_err(|e| e.to_string())
+                    .and_then(|()| output.write_all(png.get_ref()).map_err(|e| e.to_string()))
+            }
+        };
+        if let Err(e) = written.and_then(|()| output.flush().map_err(|e| e.to_string())) {
+            eprintln!("Failed to write stdout: {e}");
+            return 1;
+        }
+        count += 1;
+
+        frame = match read_frame(&mut input, opts.input) {
+            Ok(next) => next,
+            Err(e) => {
+                eprintln!("{e}");
+                return 1;
+            }
+        };
+    }
+    eprintln!("Processed {count} frames at {width}x{height}");
+    0
+}
@@ -1,4 +1,5 @@
 use super::bayer::BayerPattern;
+use super::optics::{crop, wrap_pad};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DemosaicAlgo {
@@ -17,14 +18,26 @@ impl DemosaicAlgo {
     }
 }
 
-/// Demosaic a single-channel Bayer mosaic into 3-channel RGB.
+/// Demosaic a single-channel Bayer mosaic into 3-channel RGB. Neighbors
+/// past the edges repeat the edge, or come from the opposite edge when
+/// `wrap` is set (seamless only for even sizes, which keep the CFA phase
+/// across the seam).
 pub fn demosaic(
     mosaic: &[f64],
     width: usize,
     height: usize,
     pattern: BayerPattern,
     algo: DemosaicAlgo,
+    wrap: bool,
 ) -> Vec<[f64; 3]> {
+    if wrap {
+        // The kernels reach two photosites; an even margin keeps the CFA phase
+        const MARGIN: usize = 2;
+        let padded = wrap_pad(mosaic, width, height, MARGIN);
+        let (padded_w, padded_h) = (width + 2 * MARGIN, height + 2 * MARGIN);
+        let rgb = demosaic(&padded, padded_w, padded_h, pattern, algo, false);
+        return crop(&rgb, width, height, MARGIN);
+    }
     match algo {
         DemosaicAlgo::Bilinear => demosaic_bilinear(mosaic, width, height, pattern),
         DemosaicAlgo::MalvarHeCutler => demosaic_malvar(mosaic, width, height, pattern),
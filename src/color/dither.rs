@@ -0,0 +1,210 @@
+//! Dithered quantization shared by the ADC and the 8-bit output conversion.
+
+use std::sync::OnceLock;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dither {
+    /// Plain rounding (hard banding at low bit depths).
+    None,
+    /// Triangular-PDF noise of +/-1 code before rounding.
+    Triangular,
+    /// Tiled blue-noise threshold mask: fine-grained, no low-frequency clumps.
+    BlueNoise,
+    /// Floyd-Steinberg error diffusion.
+    ErrorDiffusion,
+}
+
+impl Dither {
+    pub const ALL: &[Dither] = &[
+        Dither::None,
+        Dither::Triangular,
+        Dither::BlueNoise,
+        Dither::ErrorDiffusion,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dither::None => "None",
+            Dither::Triangular => "Triangular",
+            Dither::BlueNoise => "Blue Noise",
+            Dither::ErrorDiffusion => "Error Diffusion",
+        }
+    }
+}
+
+/// Round a row-major plane of values to integer codes in [0, max_code].
+/// Triangular dither draws from `rng`.
+pub fn quantize(
+    values: &mut [f64],
+    width: usize,
+    max_code: f64,
+    dither: Dither,
+    rng: &mut impl Rng,
+) {
+    if width == 0 {
+        return;
+    }
+    let mut diffused = vec![0.0; width];
+    for (y, row) in values.chunks_mut(width).enumerate() {
+        quantize_row(row, y, &mut diffused, max_code, dither, rng);
+    }
+}
+
+/// Round row `y` of a plane as `quantize` does, one row at a time.
+/// `diffused` is as wide as the row and starts at zero: error diffusion
+/// takes the error pushed down from the row above from it and leaves this
+/// row's error for the next.
+pub fn quantize_row(
+    row: &mut [f64],
+    y: usize,
+    diffused: &mut [f64],
+    max_code: f64,
+    dither: Dither,
+    rng: &mut impl Rng,
+) {
+    match dither {
+        Dither::None => {
+            for v in row.iter_mut() {
+                *v = v.round().clamp(0.0, max_code);
+            }
+        }
+        Dither::Triangular => {
+            for v in row.iter_mut() {
+                let tpdf = rng.random::<f64>() - rng.random::<f64>();
+                *v = (*v + tpdf).round().clamp(0.0, max_code);
+            }
+        }
+        Dither::BlueNoise => {
+            for (x, v) in row.iter_mut().enumerate() {
+                *v = (*v + blue_noise(x, y)).round().clamp(0.0, max_code);
+            }
+        }
+        Dither::ErrorDiffusion => {
+            let width = row.len();
+            for (v, carried) in row.iter_mut().zip(diffused.iter_mut()) {
+                *v += std::mem::take(carried);
+            }
+            for x in 0..width {
+                let old = row[x];
+                let new = old.round().clamp(0.0, max_code);
+                row[x] = new;
+                let err = old - new;
+                if x + 1 < width {
+                    row[x + 1] += err * 7.0 / 16.0;
+                    diffused[x + 1] += err * 1.0 / 16.0;
+                }
+                if x > 0 {
+                    diffused[x - 1] += err * 3.0 / 16.0;
+                }
+                diffused[x] += err * 5.0 / 16.0;
+            }
+        }
+    }
+}
+
+pub(crate) const MASK_SIZE: usize = 64;
+
+/// Blue-noise mask value in [-0.5, 0.5) at (`x`, `y`), tiled.
+pub(crate) fn blue_noise(x: usize, y: usize) -> f64 {
+    blue_noise_mask()[(y % MASK_SIZE) * MASK_SIZE + x % MASK_SIZE]
+}
+
+/// Blue-noise offsets in [-0.5, 0.5), built once with void-and-cluster.
+fn blue_noise_mask() -> &'static [f64] {
+    static MASK: OnceLock<Vec<f64>> = OnceLock::new();
+    MASK.get_or_init(|| {
+        let ranks = void_and_cluster(MASK_SIZE);
+        let n = ranks.len() as f64;
+        ranks.iter().map(|&r| (r as f64 + 0.5) / n - 0.5).collect()
+    })
+}
+
+/// Ulichney's void-and-cluster: rank every cell of a toroidal `size` x `size`
+/// grid so that any threshold of the ranks gives an evenly spread pattern.
+fn void_and_cluster(size: usize) -> Vec<usize> {
+    let n = size * size;
+    let sigma: f64 = 1.5;
+    let radius = (3.0 * sigma).ceil() as isize;
+    let kernel: Vec<(isize, isize, f64)> = (-radius..=radius)
+        .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+        .map(|(dx, dy)| {
+            let d2 = (dx * dx + dy * dy) as f64;
+            (dx, dy, libm::exp(-d2 / (2.0 * sigma * sigma)))
+        })
+        .collect();
+
+    let toggle = |energy: &mut [f64], idx: usize, sign: f64| {
+        let (x, y) = ((idx % size) as isize, (idx / size) as isize);
+        let s = size as isize;
+        for &(dx, dy, w) in &kernel {
+            let nx = (x + dx).rem_euclid(s) as usize;
+            let ny = (y + dy).rem_euclid(s) as usize;
+            energy[ny * size + nx] += sign * w;
+        }
+    };
+    let tightest_cluster = |pattern: &[bool], energy: &[f64]| {
+        (0..n)
+            .filter(|&i| pattern[i])
+            .max_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+            .unwrap()
+    };
+    let largest_void = |pattern: &[bool], energy: &[f64]| {
+        (0..n)
+            .filter(|&i| !pattern[i])
+            .min_by(|&a, &b| energy[a].total_cmp(&energy[b]))
+            .unwrap()
+    };
+
+    // Initial pattern: ~10% random points, relaxed until evenly spread. The
+    // seed is fixed so the mask, and noise textures tiled from it, repeat
+    // exactly between runs
+    let mut rng = StdRng::seed_from_u64(size as u64);
+    let mut pattern = vec![false; n];
+    let mut energy = vec![0.0; n];
+    let initial = n / 10;
+    let mut placed = 0;
+    while placed < initial {
+        let i = rng.random_range(0..n);
+        if !pattern[i] {
+            pattern[i] = true;
+            toggle(&mut energy, i, 1.0);
+            placed += 1;
+        }
+    }
+    for _ in 0..n {
+        let cluster = tightest_cluster(&pattern, &energy);
+        pattern[cluster] = false;
+        toggle(&mut energy, cluster, -1.0);
+        let void = largest_void(&pattern, &energy);
+        pattern[void] = true;
+        toggle(&mut energy, void, 1.0);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0usize; n];
+
+    // Phase 1: rank the initial points by removing tightest clusters
+    let mut work = pattern.clone();
+    let mut work_energy = energy.clone();
+    for rank in (0..initial).rev() {
+        let cluster = tightest_cluster(&work, &work_energy);
+        work[cluster] = false;
+        toggle(&mut work_energy, cluster, -1.0);
+        ranks[cluster] = rank;
+    }
+
+    // Phase 2: fill the largest voids until every cell is ranked
+    for rank in initial..n {
+        let void = largest_void(&pattern, &energy);
+        pattern[void] = true;
+        toggle(&mut energy, void, 1.0);
+        ranks[void] = rank;
+    }
+
+    ranks
+}
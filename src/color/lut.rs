@@ -0,0 +1,157 @@
+//! 3D color lookup tables in the Adobe/Resolve `.cube` format.
+
+/// A 3D LUT: `size`^3 RGB entries with red varying fastest, as stored in `.cube` files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3d {
+    pub title: String,
+    pub size: usize,
+    pub domain_min: [f64; 3],
+    pub domain_max: [f64; 3],
+    pub data: Vec<[f64; 3]>,
+}
+
+impl Lut3d {
+    /// Build a LUT over the unit cube by sampling `f` at every lattice point.
+    pub fn from_fn(title: &str, size: usize, mut f: impl FnMut([f64; 3]) -> [f64; 3]) -> Self {
+        let size = size.max(2);
+        let step = 1.0 / (size - 1) as f64;
+        let mut data = Vec::with_capacity(size * size * size);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.push(f([r as f64 * step, g as f64 * step, b as f64 * step]));
+                }
+            }
+        }
+        Self {
+            title: title.to_string(),
+            size,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            data,
+        }
+    }
+
+    /// Parse a `.cube` file. Only 3D LUTs are supported.
+    pub fn parse_cube(text: &str) -> Result<Self, String> {
+        let mut title = String::new();
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut data = Vec::new();
+
+        let triple = |parts: &[&str], line_no: usize| -> Result<[f64; 3], String> {
+            if parts.len() != 3 {
+                return Err(format!("Line {line_no}: expected 3 values"));
+            }
+            let mut out = [0.0; 3];
+            for (o, p) in out.iter_mut().zip(parts) {
+                *o = p
+                    .parse()
+                    .map_err(|_| format!("Line {line_no}: invalid number '{p}'"))?;
+            }
+            Ok(out)
+        };
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts[0] {
+                "TITLE" => title = line["TITLE".len()..].trim().trim_matches('"').to_string(),
+                "LUT_3D_SIZE" => {
+                    let n: usize = parts
+                        .get(1)
+                        .and_then(|v| v.parse().ok())
+                        .ok_or_else(|| format!("Line {line_no}: invalid LUT_3D_SIZE"))?;
+                    if !(2..=256).contains(&n) {
+                        return Err(format!("Line {line_no}: LUT_3D_SIZE {n} out of range"));
+                    }
+                    size = Some(n);
+                }
+                "LUT_1D_SIZE" => return Err("1D LUTs are not supported".to_string()),
+                "DOMAIN_MIN" => domain_min = triple(&parts[1..], line_no)?,
+                "DOMAIN_MAX" => domain_max = triple(&parts[1..], line_no)?,
+                keyword if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+                    // Unknown keywords (e.g. LUT_3D_INPUT_RANGE from other tools) are ignored
+                }
+                _ => data.push(triple(&parts, line_no)?),
+            }
+        }
+
+        let size = size.ok_or("Missing LUT_3D_SIZE")?;
+        if data.len() != size * size * size {
+            return Err(format!(
+                "Expected {} entries for size {size}, found {}",
+                size * size * size,
+                data.len()
+            ));
+        }
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err("DOMAIN_MAX must be greater than DOMAIN_MIN".to_string());
+        }
+        Ok(Self { title, size, domain_min, domain_max, data })
+    }
+
+    /// Serialize as a `.cube` file.
+    pub fn to_cube(&self) -> String {
+        let mut out = String::new();
+        if !self.title.is_empty() {
+            out.push_str(&format!("TITLE \"{}\"\n", self.title));
+        }
+        out.push_str(&format!("LUT_3D_SIZE {}\n", self.size));
+        let [a, b, c] = self.domain_min;
+        out.push_str(&format!("DOMAIN_MIN {a:.6} {b:.6} {c:.6}\n"));
+        let [a, b, c] = self.domain_max;
+        out.push_str(&format!("DOMAIN_MAX {a:.6} {b:.6} {c:.6}\n"));
+        for [r, g, b] in &self.data {
+            out.push_str(&format!("{r:.6} {g:.6} {b:.6}\n"));
+        }
+        out
+    }
+
+    /// Look up a color with trilinear interpolation.
+    pub fn sample(&self, rgb: [f64; 3]) -> [f64; 3] {
+        let n = self.size;
+        let max = (n - 1) as f64;
+        let mut base = [0usize; 3];
+        let mut frac = [0.0f64; 3];
+        for c in 0..3 {
+            let t = (rgb[c] - self.domain_min[c]) / (self.domain_max[c] - self.domain_min[c]);
+            let pos = t.clamp(0.0, 1.0) * max;
+            base[c] = (pos as usize).min(n - 2);
+            frac[c] = pos - base[c] as f64;
+        }
+
+        let at = |r: usize, g: usize, b: usize| self.data[(b * n + g) * n + r];
+        let mut out = [0.0; 3];
+        for corner in 0..8 {
+            let dr = corner & 1;
+            let dg = (corner >> 1) & 1;
+            let db = (corner >> 2) & 1;
+            let weight = [dr, dg, db]
+                .iter()
+                .zip(&frac)
+                .map(|(&d, &f)| if d == 1 { f } else { 1.0 - f })
+                .product::<f64>();
+            if weight == 0.0 {
+                continue;
+            }
+            let v = at(base[0] + dr, base[1] + dg, base[2] + db);
+            for c in 0..3 {
+                out[c] += v[c] * weight;
+            }
+        }
+        out
+    }
+}
+
+/// Replace every pixel with its LUT lookup.
+pub fn apply_lut(rgb: &mut [[f64; 3]], lut: &Lut3d) {
+    for pixel in rgb.iter_mut() {
+        *pixel = lut.sample(*pixel);
+    }
+}
@@ -1,3 +1,8 @@
 pub mod bayer;
 pub mod demosaic;
+pub mod dither;
+pub mod lut;
+pub mod optics;
+pub mod osd;
+pub mod palette;
 pub mod spectral;
@@ -0,0 +1,370 @@
+//! Optical and scan geometry effects applied to the focused image as it lands
+//! on the sensor.
+
+/// Apply radial lens distortion.
+///
+/// `k` is the radial coefficient over the normalized half-diagonal:
+/// positive = barrel, negative = pincushion. Samples that fall outside
+/// the image read as black, like light missing the lens image circle. The
+/// warp is centered on the frame, so it does not tile even with
+/// `tile_seamless`.
+pub fn apply_lens_distortion(rgb: &mut [[f64; 3]], width: usize, height: usize, k: f64) {
+    if k == 0.0 || width < 2 || height < 2 {
+        return;
+    }
+    let src = rgb.to_vec();
+    let cx = (width - 1) as f64 / 2.0;
+    let cy = (height - 1) as f64 / 2.0;
+    let norm = (cx * cx + cy * cy).sqrt();
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = (x as f64 - cx) / norm;
+            let dy = (y as f64 - cy) / norm;
+            let scale = 1.0 + k * (dx * dx + dy * dy);
+            let sx = cx + dx * scale * norm;
+            let sy = cy + dy * scale * norm;
+            rgb[y * width + x] = sample_bilinear(&src, width, height, sx, sy, false);
+        }
+    }
+}
+
+/// Path the image takes across the sensor during the exposure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trajectory {
+    /// Straight drift, as from a tracking rate error.
+    Linear,
+    /// Rotation about a pivot, as stars trail around the celestial pole on
+    /// an untracked or badly aligned mount.
+    Arc,
+}
+
+impl Trajectory {
+    pub const ALL: &[Trajectory] = &[Trajectory::Linear, Trajectory::Arc];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Trajectory::Linear => "Linear",
+            Trajectory::Arc => "Arc",
+        }
+    }
+}
+
+/// Motion of the scene during the exposure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionBlur {
+    /// Trail length in sensor pixels; 0 disables it. For an arc, the length
+    /// at half the frame diagonal from the pivot.
+    pub length: f64,
+    /// Direction of a linear trail in degrees, counterclockwise from +x.
+    pub angle: f64,
+    pub trajectory: Trajectory,
+    /// Arc pivot as fractions of the frame width and height; may lie outside.
+    pub pivot: (f64, f64),
+}
+
+impl Default for MotionBlur {
+    fn default() -> Self {
+        Self {
+            length: 0.0,
+            angle: 0.0,
+            trajectory: Trajectory::Linear,
+            pivot: (0.5, 0.5),
+        }
+    }
+}
+
+/// Smear the image along its motion during the exposure.
+///
+/// Each pixel averages the scene at evenly spaced points of the trajectory,
+/// so a star draws a trail of constant brightness. Light from outside the
+/// frame reads as black, or comes from the opposite edge when `wrap` is set.
+pub fn apply_motion_blur(
+    rgb: &mut [[f64; 3]],
+    width: usize,
+    height: usize,
+    blur: &MotionBlur,
+    wrap: bool,
+) {
+    if blur.length < 0.5 || width < 2 || height < 2 {
+        return;
+    }
+    let src = rgb.to_vec();
+    let samples = (blur.length.ceil() as usize + 1).min(256);
+    let (sin, cos) = libm::sincos(blur.angle.to_radians());
+    let (dir_x, dir_y) = (cos, -sin);
+    let (px, py) = (blur.pivot.0 * width as f64, blur.pivot.1 * height as f64);
+    let sweep = blur.length / ((width * width + height * height) as f64).sqrt() * 2.0;
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0f64; 3];
+            for k in 0..samples {
+                let t = k as f64 / (samples - 1) as f64;
+                let (sx, sy) = match blur.trajectory {
+                    Trajectory::Linear => {
+                        (x as f64 - dir_x * blur.length * t, y as f64 - dir_y * blur.length * t)
+                    }
+                    Trajectory::Arc => {
+                        let (sin, cos) = libm::sincos(-sweep * t);
+                        let (dx, dy) = (x as f64 - px, y as f64 - py);
+                        (px + dx * cos - dy * sin, py + dx * sin + dy * cos)
+                    }
+                };
+                let p = sample_bilinear(&src, width, height, sx, sy, wrap);
+                for (s, v) in sum.iter_mut().zip(p) {
+                    *s += v;
+                }
+            }
+            rgb[y * width + x] = sum.map(|s| s / samples as f64);
+        }
+    }
+}
+
+/// Resample rows as a tilted or misaligned scan would lay them down.
+///
+/// Each row starts `skew` pixels further right than the row above it (negative:
+/// left), plus a sinusoidal `wobble` of that amplitude in pixels repeating
+/// `wobble_frequency` times over the frame. Its horizontal scale drifts
+/// progressively to `1 + scale_error` by the last row, about the start of the
+/// line, as a scan clock running off frequency would. Samples that miss the
+/// image read as black; the offsets change down the frame, so it does not
+/// tile even with `tile_seamless`.
+pub fn apply_scan_skew(
+    rgb: &mut [[f64; 3]],
+    width: usize,
+    height: usize,
+    skew: f64,
+    scale_error: f64,
+    wobble: f64,
+    wobble_frequency: f64,
+) {
+    if (skew == 0.0 && scale_error == 0.0 && wobble == 0.0) || width < 2 || height < 2 {
+        return;
+    }
+    let src = rgb.to_vec();
+    let span = (height - 1) as f64;
+    let tau = std::f64::consts::TAU;
+
+    for y in 0..height {
+        let t = y as f64 / span;
+        let offset = skew * y as f64 + wobble * libm::sin(tau * wobble_frequency * t);
+        let scale = (1.0 + scale_error * t).max(0.01);
+        for x in 0..width {
+            let sx = (x as f64 - offset) / scale;
+            rgb[y * width + x] = sample_bilinear(&src, width, height, sx, y as f64, false);
+        }
+    }
+}
+
+/// Glow around highlights from light scattered back off the sensor cover
+/// glass and substrate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Halation {
+    /// Fraction of the highlight charge returned as glow; 0 disables it.
+    pub strength: f64,
+    /// Charge, as a fraction of full well, above which pixels glow.
+    pub threshold: f64,
+    /// Reach of the glow in sensor pixels.
+    pub radius: f64,
+    /// Per-channel weight of the glow; longer wavelengths scatter further.
+    pub tint: [f64; 3],
+}
+
+impl Default for Halation {
+    fn default() -> Self {
+        Self {
+            strength: 0.0,
+            threshold: 0.8,
+            radius: 24.0,
+            tint: [1.0, 0.45, 0.25],
+        }
+    }
+}
+
+/// Add halation glow in electrons.
+///
+/// Charge above the threshold (channel mean) is spread over the radius with
+/// three box blur passes, an approximate Gaussian, and added back tinted.
+/// It lands before the readout, so a bright glow saturates and blooms like
+/// any other light. With `wrap` the glow spreads across the edges.
+pub fn apply_halation(
+    rgb: &mut [[f64; 3]],
+    width: usize,
+    height: usize,
+    halation: &Halation,
+    full_well: f64,
+    wrap: bool,
+) {
+    if halation.strength <= 0.0 || halation.radius < 1.0 || width == 0 || height == 0 {
+        return;
+    }
+    let threshold = halation.threshold.max(0.0) * full_well;
+    let mut glow: Vec<f64> = rgb
+        .iter()
+        .map(|p| ((p[0] + p[1] + p[2]) / 3.0 - threshold).max(0.0))
+        .collect();
+    if glow.iter().all(|&v| v == 0.0) {
+        return;
+    }
+
+    let r = (halation.radius / 3.0).round().max(1.0) as usize;
+    let mut line = Vec::new();
+    for _ in 0..3 {
+        for y in 0..height {
+            box_blur_line(&mut glow, y * width, 1, width, r, wrap, &mut line);
+        }
+        for x in 0..width {
+            box_blur_line(&mut glow, x, width, height, r, wrap, &mut line);
+        }
+    }
+
+    for (p, g) in rgb.iter_mut().zip(glow) {
+        for (v, tint) in p.iter_mut().zip(halation.tint) {
+            *v += halation.strength * tint * g;
+        }
+    }
+}
+
+/// Box blur of `len` samples at `start`, `stride` apart, with half-width `r`
+/// and edges clamped, or wrapped around when `wrap` is set. `line` is
+/// scratch space.
+pub(crate) fn box_blur_line(
+    data: &mut [f64],
+    start: usize,
+    stride: usize,
+    len: usize,
+    r: usize,
+    wrap: bool,
+    line: &mut Vec<f64>,
+) {
+    line.clear();
+    line.extend((0..len).map(|i| data[start + i * stride]));
+    let at = |i: isize| {
+        let i = if wrap { i.rem_euclid(len as isize) } else { i.clamp(0, len as isize - 1) };
+        line[i as usize]
+    };
+    let r = r as isize;
+    let mut sum: f64 = (-r..=r).map(at).sum();
+    let norm = 1.0 / (2 * r + 1) as f64;
+    for i in 0..len as isize {
+        data[start + i as usize * stride] = sum * norm;
+        sum += at(i + r + 1) - at(i - r);
+    }
+}
+
+/// `src` at (`x`, `y`), interpolated. Off the image it reads as black, or
+/// wraps around to the opposite edge when `wrap` is set.
+fn sample_bilinear(
+    src: &[[f64; 3]],
+    width: usize,
+    height: usize,
+    x: f64,
+    y: f64,
+    wrap: bool,
+) -> [f64; 3] {
+    let (x0, x1, y0, y1, fx, fy) = if wrap {
+        let (x, y) = (x.rem_euclid(width as f64), y.rem_euclid(height as f64));
+        let x0 = (x.floor() as usize).min(width - 1);
+        let y0 = (y.floor() as usize).min(height - 1);
+        (x0, (x0 + 1) % width, y0, (y0 + 1) % height, x - x0 as f64, y - y0 as f64)
+    } else {
+        if x < 0.0 || y < 0.0 || x > (width - 1) as f64 || y > (height - 1) as f64 {
+            return [0.0; 3];
+        }
+        let x0 = (x.floor() as usize).min(width - 2);
+        let y0 = (y.floor() as usize).min(height - 2);
+        (x0, x0 + 1, y0, y0 + 1, x - x0 as f64, y - y0 as f64)
+    };
+
+    let p00 = src[y0 * width + x0];
+    let p10 = src[y0 * width + x1];
+    let p01 = src[y1 * width + x0];
+    let p11 = src[y1 * width + x1];
+    std::array::from_fn(|c| {
+        let top = p00[c] + (p10[c] - p00[c]) * fx;
+        let bottom = p01[c] + (p11[c] - p01[c]) * fx;
+        top + (bottom - top) * fy
+    })
+}
+
+/// Apply defocus blur: a uniform disk (circle of confusion) of `radius` pixels.
+///
+/// Each output pixel averages the disk one row span at a time using per-row
+/// prefix sums, so highlights spread into hard-edged discs rather than the
+/// soft falloff of a Gaussian. The disk is cut off at the edges, or reaches
+/// across them when `wrap` is set.
+pub fn apply_defocus(rgb: &mut [[f64; 3]], width: usize, height: usize, radius: f64, wrap: bool) {
+    if radius < 0.5 || width == 0 || height == 0 {
+        return;
+    }
+    let r = radius.ceil() as usize;
+    if wrap {
+        let mut padded = wrap_pad(rgb, width, height, r);
+        apply_defocus(&mut padded, width + 2 * r, height + 2 * r, radius, false);
+        rgb.copy_from_slice(&crop(&padded, width, height, r));
+        return;
+    }
+
+    // prefix[y * (width + 1) + x] = sum of row y over columns 0..x
+    let stride = width + 1;
+    let mut prefix = vec![[0.0f64; 3]; stride * height];
+    for y in 0..height {
+        for x in 0..width {
+            let p = rgb[y * width + x];
+            let prev = prefix[y * stride + x];
+            prefix[y * stride + x + 1] = [prev[0] + p[0], prev[1] + p[1], prev[2] + p[2]];
+        }
+    }
+
+    // Half-width of the disk at each row offset
+    let spans: Vec<usize> = (0..=r)
+        .map(|dy| {
+            let dy = dy as f64;
+            (radius * radius - dy * dy).max(0.0).sqrt().floor() as usize
+        })
+        .collect();
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0f64; 3];
+            let mut count = 0usize;
+            let y_start = y.saturating_sub(r);
+            let y_end = (y + r).min(height - 1);
+            for sy in y_start..=y_end {
+                let half = spans[sy.abs_diff(y)];
+                let x0 = x.saturating_sub(half);
+                let x1 = (x + half).min(width - 1) + 1;
+                let a = prefix[sy * stride + x0];
+                let b = prefix[sy * stride + x1];
+                for c in 0..3 {
+                    sum[c] += b[c] - a[c];
+                }
+                count += x1 - x0;
+            }
+            let inv = 1.0 / count as f64;
+            rgb[y * width + x] = [sum[0] * inv, sum[1] * inv, sum[2] * inv];
+        }
+    }
+}
+
+/// `data` tiled out by `margin` samples past every edge, each border
+/// continuing from the opposite one. A stage run over it and `crop`ped back
+/// wraps around the edges.
+pub(crate) fn wrap_pad<T: Copy>(data: &[T], width: usize, height: usize, margin: usize) -> Vec<T> {
+    let wrap = |i: usize, len: usize| {
+        (i as isize - margin as isize).rem_euclid(len as isize) as usize
+    };
+    let padded_w = width + 2 * margin;
+    (0..height + 2 * margin)
+        .flat_map(|y| (0..padded_w).map(move |x| data[wrap(y, height) * width + wrap(x, width)]))
+        .collect()
+}
+
+/// The `width` x `height` middle of a `wrap_pad`ded image.
+pub(crate) fn crop<T: Copy>(padded: &[T], width: usize, height: usize, margin: usize) -> Vec<T> {
+    let padded_w = width + 2 * margin;
+    (margin..margin + height)
+        .flat_map(|y| padded[y * padded_w + margin..][..width].iter().copied())
+        .collect()
+}
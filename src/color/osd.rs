@@ -0,0 +1,317 @@
+//! On-screen display, in two places.
+//!
+//! The frame overlay is furniture burned into the finished output, as a
+//! monitor or viewfinder shows it: the picture cropped by a CRT's overscan,
+//! broadcast safe-area markers, and OSD text in the bitmap font. It works on
+//! the RGB8 output after compositing, so it is never glitched itself.
+//!
+//! The camera OSD is the camcorder's own display (a quartz date stamp, the
+//! battery and REC indicators), drawn as light into the electron image
+//! before readout, so it smears, blooms and glitches along with the scene.
+
+use crate::bitmap_font;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameOverlay {
+    /// Fraction of each edge hidden behind the bezel; the rest of the picture
+    /// is scaled up to fill the frame.
+    pub overscan: f64,
+    /// Outline the action-safe (90%) and title-safe (80%) areas.
+    pub safe_area: bool,
+    /// Burn in a clock, advancing with the frames of a sequence.
+    pub timestamp: bool,
+    /// Seconds past midnight the clock reads on the first frame.
+    pub clock_start: f64,
+    /// Burn in a blinking REC indicator.
+    pub rec: bool,
+}
+
+impl Default for FrameOverlay {
+    fn default() -> Self {
+        Self {
+            overscan: 0.0,
+            safe_area: false,
+            timestamp: false,
+            clock_start: 12.0 * 3600.0,
+            rec: false,
+        }
+    }
+}
+
+impl FrameOverlay {
+    /// Whether the overlay leaves the output as it is.
+    pub fn is_off(&self) -> bool {
+        self.overscan <= 0.0 && !self.safe_area && !self.timestamp && !self.rec
+    }
+}
+
+/// NTSC frame rate the clock and REC blink count frames at.
+const FRAME_RATE: f64 = 30000.0 / 1001.0;
+const TEXT: [u8; 3] = [240, 240, 240];
+const SHADOW: [u8; 3] = [0, 0, 0];
+const REC_RED: [u8; 3] = [230, 30, 30];
+const SAFE_GUIDE: [u8; 3] = [200, 200, 200];
+
+/// Burn the overlay into the `width` x `height` RGB8 `bytes` for `frame` of
+/// a sequence (0 for a still).
+pub fn apply_overlay(
+    bytes: &mut [u8],
+    width: usize,
+    height: usize,
+    overlay: &FrameOverlay,
+    frame: u64,
+) {
+    if overlay.is_off() || width == 0 || height == 0 {
+        return;
+    }
+    if overlay.overscan > 0.0 {
+        apply_overscan(bytes, width, height, overlay.overscan.min(0.25));
+    }
+
+    let (w, h) = (width as i64, height as i64);
+    let mut plot = |x: i64, y: i64, color: [u8; 3]| {
+        if x >= 0 && y >= 0 && x < w && y < h {
+            let i = (y * w + x) as usize * 3;
+            bytes[i..i + 3].copy_from_slice(&color);
+        }
+    };
+
+    if overlay.safe_area {
+        for fraction in [0.9, 0.8] {
+            let (left, top) = (
+                (w as f64 * (1.0 - fraction) / 2.0) as i64,
+                (h as f64 * (1.0 - fraction) / 2.0) as i64,
+            );
+            let (right, bottom) = (w - 1 - left, h - 1 - top);
+            // Dashed, so the picture still shows through the guide
+            for x in (left..=right).filter(|x| x % 4 < 2) {
+                plot(x, top, SAFE_GUIDE);
+                plot(x, bottom, SAFE_GUIDE);
+            }
+            for y in (top..=bottom).filter(|y| y % 4 < 2) {
+                plot(left, y, SAFE_GUIDE);
+                plot(right, y, SAFE_GUIDE);
+            }
+        }
+    }
+
+    // Text sits inside the title-safe area, sized to the frame as a
+    // character generator's would be
+    let scale = (height as u32 / 120).max(1);
+    let (margin_x, margin_y) = ((w as f64 * 0.1) as i64 + 1, (h as f64 * 0.1) as i64 + 1);
+    let glyph = (bitmap_font::GLYPH_HEIGHT * scale) as i64;
+    let seconds = frame as f64 / FRAME_RATE;
+
+    // REC blinks once a second, lit for the first half
+    let rec = overlay.rec && seconds.fract() < 0.5;
+    if rec {
+        let radius = glyph / 2;
+        let (cx, cy) = (margin_x + radius, margin_y + radius);
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                if x * x + y * y <= radius * radius {
+                    plot(cx + x, cy + y, REC_RED);
+                }
+            }
+        }
+    }
+
+    let mut text = |text: &str, x: i64, y: i64| {
+        let offset = scale as i64;
+        bitmap_font::for_each_pixel(text, x + offset, y + offset, scale, |px, py| {
+            plot(px, py, SHADOW)
+        });
+        bitmap_font::for_each_pixel(text, x, y, scale, |px, py| plot(px, py, TEXT));
+    };
+    if rec {
+        text("REC", margin_x + glyph + scale as i64 * 3, margin_y);
+    }
+    if overlay.timestamp {
+        let clock = (overlay.clock_start + seconds).rem_euclid(24.0 * 3600.0) as u64;
+        let label = format!("{:02}:{:02}:{:02}", clock / 3600, clock / 60 % 60, clock % 60);
+        let x = w - margin_x - bitmap_font::text_width(&label, scale) as i64;
+        text(&label, x, h - margin_y - glyph);
+    }
+}
+
+/// Scale the middle of the picture up to the full frame, cropping `overscan`
+/// of each edge, with bilinear sampling.
+fn apply_overscan(bytes: &mut [u8], width: usize, height: usize, overscan: f64) {
+    let source = bytes.to_vec();
+    let scale = 1.0 - 2.0 * overscan;
+    let sample = |x: f64, y: f64, c: usize| {
+        let x = x.clamp(0.0, (width - 1) as f64);
+        let y = y.clamp(0.0, (height - 1) as f64);
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+        let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+        let at = |x: usize, y: usize| source[(y * width + x) * 3 + c] as f64;
+        let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * fx;
+        let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * fx;
+        top + (bottom - top) * fy
+    };
+    for y in 0..height {
+        let sy = (y as f64 + 0.5) * scale + overscan * height as f64 - 0.5;
+        for x in 0..width {
+            let sx = (x as f64 + 0.5) * scale + overscan * width as f64 - 0.5;
+            for c in 0..3 {
+                bytes[(y * width + x) * 3 + c] = sample(sx, sy, c).round() as u8;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraOsd {
+    /// Burn in a date stamp in seven-segment digits, like a quartz date back.
+    pub date: bool,
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+    /// Show the battery indicator.
+    pub battery: bool,
+    /// Charge the battery indicator shows, 0 to 1.
+    pub battery_level: f64,
+    /// Show the REC indicator.
+    pub rec: bool,
+    /// Light the display adds, as a fraction of full well; above 1 it blooms.
+    pub brightness: f64,
+}
+
+impl Default for CameraOsd {
+    fn default() -> Self {
+        Self {
+            date: false,
+            year: 1998,
+            month: 12,
+            day: 31,
+            battery: false,
+            battery_level: 1.0,
+            rec: false,
+            brightness: 0.8,
+        }
+    }
+}
+
+impl CameraOsd {
+    /// Whether the display adds nothing to the image.
+    pub fn is_off(&self) -> bool {
+        !(self.date || self.battery || self.rec) || self.brightness <= 0.0
+    }
+}
+
+/// The amber of a quartz date back's LEDs.
+const DATE_AMBER: [f64; 3] = [1.0, 0.5, 0.1];
+const OSD_WHITE: [f64; 3] = [1.0, 1.0, 1.0];
+const OSD_RED: [f64; 3] = [1.0, 0.1, 0.1];
+
+/// Seven-segment digits, segments a (top) to g (middle) as bits 0 to 6.
+const SEGMENT_DIGITS: [u8; 10] = [0x3F, 0x06, 0x5B, 0x4F, 0x66, 0x6D, 0x7D, 0x07, 0x7F, 0x6F];
+/// Segment rectangles as (x, y, width, height) in a 4x7 cell.
+const SEGMENTS: [(i64, i64, i64, i64); 7] = [
+    (0, 0, 4, 1),
+    (3, 0, 1, 4),
+    (3, 3, 1, 4),
+    (0, 6, 4, 1),
+    (0, 3, 1, 4),
+    (0, 0, 1, 4),
+    (0, 3, 4, 1),
+];
+
+/// Add the camera's display to the `width` x `height` RGB electron image.
+pub fn apply_camera_osd(
+    rgb: &mut [[f64; 3]],
+    width: usize,
+    height: usize,
+    osd: &CameraOsd,
+    full_well: f64,
+) {
+    if osd.is_off() || width == 0 || height == 0 {
+        return;
+    }
+    let (w, h) = (width as i64, height as i64);
+    let unit = (height as i64 / 100).max(1);
+    let level = osd.brightness * full_well;
+    let mut fill = |x0: i64, y0: i64, rw: i64, rh: i64, color: [f64; 3]| {
+        for y in y0.max(0)..(y0 + rh).min(h) {
+            for x in x0.max(0)..(x0 + rw).min(w) {
+                let p = &mut rgb[(y * w + x) as usize];
+                for (v, c) in p.iter_mut().zip(color) {
+                    *v += c * level;
+                }
+            }
+        }
+    };
+    // Inside the edges a viewfinder crops
+    let (margin_x, margin_y) = ((w as f64 * 0.06) as i64, (h as f64 * 0.06) as i64);
+
+    if osd.rec {
+        let radius = unit * 3;
+        let (cx, cy) = (margin_x + radius, margin_y + radius);
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                if x * x + y * y <= radius * radius {
+                    fill(cx + x, cy + y, 1, 1, OSD_RED);
+                }
+            }
+        }
+        let x = margin_x + radius * 2 + unit * 2;
+        bitmap_font::for_each_pixel("REC", x, margin_y, unit as u32, |px, py| {
+            fill(px, py, 1, 1, OSD_WHITE)
+        });
+    }
+
+    if osd.battery {
+        // A 12x6 unit outline with a terminal nub and three charge bars
+        let x = w - margin_x - unit * 13;
+        let y = margin_y;
+        let mut cell = |cx: i64, cy: i64, cw: i64, ch: i64| {
+            fill(x + cx * unit, y + cy * unit, cw * unit, ch * unit, OSD_WHITE)
+        };
+        cell(0, 0, 12, 1);
+        cell(0, 5, 12, 1);
+        cell(0, 1, 1, 4);
+        cell(11, 1, 1, 4);
+        cell(12, 2, 1, 2);
+        let bars = (osd.battery_level.clamp(0.0, 1.0) * 3.0).ceil() as i64;
+        for bar in 0..bars {
+            cell(2 + bar * 3, 2, 2, 2);
+        }
+    }
+
+    if osd.date {
+        let year = osd.year % 100;
+        let stamp = format!("'{year:02} {:>2} {:>2}", osd.month.clamp(1, 12), osd.day.clamp(1, 31));
+        let mut x = w - margin_x - seven_segment_width(&stamp) * unit;
+        let y = h - margin_y - 7 * unit;
+        for c in stamp.chars() {
+            match c {
+                '\'' => fill(x, y, unit, 2 * unit, DATE_AMBER),
+                ' ' => {}
+                _ => {
+                    let digit = c.to_digit(10).unwrap_or(8) as usize;
+                    for (bit, (sx, sy, sw, sh)) in SEGMENTS.iter().enumerate() {
+                        if SEGMENT_DIGITS[digit] & (1 << bit) != 0 {
+                            fill(x + sx * unit, y + sy * unit, sw * unit, sh * unit, DATE_AMBER);
+                        }
+                    }
+                }
+            }
+            x += seven_segment_advance(c) * unit;
+        }
+    }
+}
+
+/// Units a seven-segment character moves the pen: digits are 4 wide with a
+/// gap of 2, the apostrophe 1 with a gap of 1.
+fn seven_segment_advance(c: char) -> i64 {
+    match c {
+        '\'' => 2,
+        ' ' => 3,
+        _ => 6,
+    }
+}
+
+fn seven_segment_width(text: &str) -> i64 {
+    (text.chars().map(seven_segment_advance).sum::<i64>() - 2).max(0)
+}
@@ -0,0 +1,267 @@
+//! Final quantization to a few colors, for the look of retro computer
+//! displays: posterized channels, the web-safe cube, an adaptive palette cut
+//! from the image itself, or a palette loaded from a file.
+
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::color::dither::{self, Dither};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaletteMode {
+    Off,
+    /// `levels` evenly spaced values per channel.
+    Posterize,
+    /// The 216-color web-safe cube: six levels per channel.
+    WebSafe,
+    /// `colors` colors chosen from the image by median cut.
+    Adaptive,
+    /// The loaded palette.
+    Custom,
+}
+
+impl PaletteMode {
+    pub const ALL: &[PaletteMode] = &[
+        PaletteMode::Off,
+        PaletteMode::Posterize,
+        PaletteMode::WebSafe,
+        PaletteMode::Adaptive,
+        PaletteMode::Custom,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PaletteMode::Off => "Off",
+            PaletteMode::Posterize => "Posterize",
+            PaletteMode::WebSafe => "Web-Safe",
+            PaletteMode::Adaptive => "Adaptive",
+            PaletteMode::Custom => "Custom",
+        }
+    }
+}
+
+/// A list of colors, as loaded from a palette file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    pub title: String,
+    pub colors: Vec<[f64; 3]>,
+}
+
+impl Palette {
+    /// Parse a GIMP `.gpl` palette, or a plain list of colors one per line
+    /// as `#rrggbb` hex or three 0-255 values. `#` comments and the GIMP
+    /// header lines are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut title = String::new();
+        let mut colors = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if let Some(name) = line.strip_prefix("Name:") {
+                title = name.trim().to_string();
+                continue;
+            }
+            if let Some(hex) = line.strip_prefix('#').filter(|h| is_hex_color(h)) {
+                let channel = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).unwrap_or(0);
+                colors.push([0, 2, 4].map(|at| channel(at) as f64 / 255.0));
+                continue;
+            }
+            if line.is_empty()
+                || line.starts_with('#')
+                || line == "GIMP Palette"
+                || line.starts_with("Columns:")
+            {
+                continue;
+            }
+            // GIMP entries trail a color name after the three values
+            let values: Vec<&str> = line.split_whitespace().take(3).collect();
+            if values.len() < 3 {
+                return Err(format!("Line {}: expected 3 values", i + 1));
+            }
+            let mut color = [0.0; 3];
+            for (c, v) in color.iter_mut().zip(&values) {
+                let value: u8 =
+                    v.parse().map_err(|_| format!("Line {}: invalid color '{line}'", i + 1))?;
+                *c = value as f64 / 255.0;
+            }
+            colors.push(color);
+        }
+        if colors.is_empty() {
+            return Err("Palette has no colors".to_string());
+        }
+        Ok(Self { title, colors })
+    }
+}
+
+fn is_hex_color(text: &str) -> bool {
+    text.len() == 6 && text.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palettize {
+    pub mode: PaletteMode,
+    /// Values per channel when posterizing.
+    pub levels: u32,
+    /// Size of the adaptive palette.
+    pub colors: usize,
+    pub dither: Dither,
+    pub custom: Option<Arc<Palette>>,
+}
+
+impl Default for Palettize {
+    fn default() -> Self {
+        Self {
+            mode: PaletteMode::Off,
+            levels: 4,
+            colors: 16,
+            dither: Dither::None,
+            custom: None,
+        }
+    }
+}
+
+/// Quantize RGB [0..1] to the palette `palettize` selects, dithered.
+pub fn apply_palette(
+    rgb: &mut [[f64; 3]],
+    width: usize,
+    palettize: &Palettize,
+    rng: &mut impl Rng,
+) {
+    let levels = match palettize.mode {
+        PaletteMode::Off => return,
+        PaletteMode::Posterize => Some(palettize.levels.max(2)),
+        PaletteMode::WebSafe => Some(6),
+        PaletteMode::Adaptive | PaletteMode::Custom => None,
+    };
+    if rgb.is_empty() {
+        return;
+    }
+    if let Some(levels) = levels {
+        // A per-channel grid: the shared quantizer does each channel alone
+        let max_code = (levels - 1) as f64;
+        let mut plane = vec![0.0; rgb.len()];
+        for c in 0..3 {
+            for (v, p) in plane.iter_mut().zip(rgb.iter()) {
+                *v = p[c].clamp(0.0, 1.0) * max_code;
+            }
+            dither::quantize(&mut plane, width, max_code, palettize.dither, rng);
+            for (p, v) in rgb.iter_mut().zip(&plane) {
+                p[c] = v / max_code;
+            }
+        }
+        return;
+    }
+
+    let colors = match (palettize.mode, &palettize.custom) {
+        (PaletteMode::Custom, Some(palette)) => palette.colors.clone(),
+        (PaletteMode::Custom, None) => return,
+        _ => median_cut(rgb, palettize.colors.clamp(2, 256)),
+    };
+    // Typical distance between palette colors, which scales noise dithers
+    let spacing = 1.0 / (colors.len() as f64).cbrt();
+    let nearest = |p: [f64; 3]| {
+        colors
+            .iter()
+            .copied()
+            .min_by(|a, b| distance(*a, p).total_cmp(&distance(*b, p)))
+            .unwrap_or(p)
+    };
+    let width = width.max(1);
+    let height = rgb.len() / width;
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            let mut target = rgb[i];
+            match palettize.dither {
+                Dither::None | Dither::ErrorDiffusion => {}
+                Dither::Triangular => {
+                    for v in target.iter_mut() {
+                        *v += (rng.random::<f64>() - rng.random::<f64>()) * spacing;
+                    }
+                }
+                Dither::BlueNoise => {
+                    let threshold = dither::blue_noise(x, y) * spacing;
+                    for v in target.iter_mut() {
+                        *v += threshold;
+                    }
+                }
+            }
+            let chosen = nearest(target);
+            if palettize.dither == Dither::ErrorDiffusion {
+                let err: [f64; 3] = std::array::from_fn(|c| target[c] - chosen[c]);
+                let mut spread = |j: usize, weight: f64| {
+                    for (v, e) in rgb[j].iter_mut().zip(err) {
+                        *v += e * weight;
+                    }
+                };
+                if x + 1 < width {
+                    spread(i + 1, 7.0 / 16.0);
+                }
+                if y + 1 < height {
+                    if x > 0 {
+                        spread(i + width - 1, 3.0 / 16.0);
+                    }
+                    spread(i + width, 5.0 / 16.0);
+                    if x + 1 < width {
+                        spread(i + width + 1, 1.0 / 16.0);
+                    }
+                }
+            }
+            rgb[i] = chosen;
+        }
+    }
+}
+
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|c| (a[c] - b[c]) * (a[c] - b[c])).sum()
+}
+
+/// Pixels sampled for the adaptive palette at most.
+const MAX_SAMPLES: usize = 1 << 16;
+
+/// Heckbert's median cut: split the box of colors with the widest channel
+/// at its median until there are `count` boxes, and average each.
+fn median_cut(rgb: &[[f64; 3]], count: usize) -> Vec<[f64; 3]> {
+    let step = rgb.len().div_ceil(MAX_SAMPLES).max(1);
+    let samples = rgb.iter().step_by(step).map(|p| p.map(|v| v.clamp(0.0, 1.0)));
+    let mut boxes = vec![samples.collect::<Vec<_>>()];
+    while boxes.len() < count {
+        let range = |colors: &[[f64; 3]], c: usize| {
+            let (lo, hi) = colors
+                .iter()
+                .fold((f64::MAX, f64::MIN), |(lo, hi), p| (lo.min(p[c]), hi.max(p[c])));
+            hi - lo
+        };
+        let widest = |colors: &[[f64; 3]]| {
+            (0..3)
+                .map(|c| (c, range(colors, c)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap_or((0, 0.0))
+        };
+        // The box with the widest spread that can still split
+        let Some((index, channel)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(i, colors)| (i, widest(colors)))
+            .filter(|(_, (_, spread))| *spread > 0.0)
+            .max_by(|a, b| a.1.1.total_cmp(&b.1.1))
+            .map(|(i, (c, _))| (i, c))
+        else {
+            break;
+        };
+        let mut colors = boxes.swap_remove(index);
+        colors.sort_unstable_by(|a, b| a[channel].total_cmp(&b[channel]));
+        let upper = colors.split_off(colors.len() / 2);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+    boxes
+        .iter()
+        .filter(|colors| !colors.is_empty())
+        .map(|colors| {
+            let n = colors.len() as f64;
+            std::array::from_fn(|c| colors.iter().map(|p| p[c]).sum::<f64>() / n)
+        })
+        .collect()
+}
@@ -1,3 +1,8 @@
+use rand::Rng;
+
+use crate::color::dither::{self, Dither};
+use crate::color::optics::box_blur_line;
+
 /// Apply white balance: multiply each channel by its respective multiplier.
 pub fn apply_white_balance(rgb: &mut [[f64; 3]], wb_r: f64, wb_g: f64, wb_b: f64) {
     for pixel in rgb.iter_mut() {
@@ -7,6 +12,51 @@ pub fn apply_white_balance(rgb: &mut [[f64; 3]], wb_r: f64, wb_g: f64, wb_b: f64
     }
 }
 
+/// Rec. 709 luma of a linear RGB pixel.
+fn luma(pixel: &[f64; 3]) -> f64 {
+    0.2126 * pixel[0] + 0.7152 * pixel[1] + 0.0722 * pixel[2]
+}
+
+/// Apply saturation and vibrance around each pixel's luma.
+/// saturation: 0.0 = grayscale, 1.0 = unchanged, >1.0 = boosted
+/// vibrance: -1.0 to 1.0, like saturation but weighted toward muted colors
+pub fn apply_saturation_vibrance(rgb: &mut [[f64; 3]], saturation: f64, vibrance: f64) {
+    if saturation == 1.0 && vibrance == 0.0 {
+        return;
+    }
+    for pixel in rgb.iter_mut() {
+        let max = pixel[0].max(pixel[1]).max(pixel[2]);
+        let min = pixel[0].min(pixel[1]).min(pixel[2]);
+        let current = if max > 0.0 { (max - min) / max } else { 0.0 };
+        let amount = (saturation * (1.0 + vibrance * (1.0 - current))).max(0.0);
+        let y = luma(pixel);
+        for v in pixel.iter_mut() {
+            *v = y + (*v - y) * amount;
+        }
+    }
+}
+
+/// Rotate hue by `degrees` around the neutral (R = G = B) axis.
+/// Grays stay gray; only chroma is rotated.
+pub fn apply_hue_rotation(rgb: &mut [[f64; 3]], degrees: f64) {
+    if degrees % 360.0 == 0.0 {
+        return;
+    }
+    let (sin, cos) = libm::sincos(degrees.to_radians());
+    // Rodrigues rotation about the unit vector (1, 1, 1) / sqrt(3)
+    let a = cos + (1.0 - cos) / 3.0;
+    let b = (1.0 - cos) / 3.0 - sin / 3f64.sqrt();
+    let c = (1.0 - cos) / 3.0 + sin / 3f64.sqrt();
+    for pixel in rgb.iter_mut() {
+        let [r, g, bl] = *pixel;
+        *pixel = [
+            a * r + b * g + c * bl,
+            c * r + a * g + b * bl,
+            b * r + c * g + a * bl,
+        ];
+    }
+}
+
 /// Apply sRGB gamma correction (linear → gamma-compressed).
 /// Standard sRGB transfer function with linear toe.
 pub fn apply_gamma(rgb: &mut [[f64; 3]], gamma: f64) {
@@ -21,7 +71,7 @@ pub fn apply_gamma(rgb: &mut [[f64; 3]], gamma: f64) {
             pixel[c] = if v <= 0.0031308 {
                 12.92 * v
             } else {
-                1.055 * v.powf(inv_gamma) - 0.055
+                1.055 * libm::pow(v, inv_gamma) - 0.055
             };
         }
     }
@@ -43,13 +93,196 @@ pub fn apply_brightness_contrast(rgb: &mut [[f64; 3]], brightness: f64, contrast
     }
 }
 
+/// A tone curve through (input, output) control points in [0, 1],
+/// kept sorted by input. The first and last points pin the ends at 0 and 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToneCurve {
+    pub points: Vec<[f64; 2]>,
+}
+
+impl Default for ToneCurve {
+    fn default() -> Self {
+        Self { points: vec![[0.0, 0.0], [1.0, 1.0]] }
+    }
+}
+
+impl ToneCurve {
+    pub fn is_identity(&self) -> bool {
+        self.points.iter().all(|p| p[0] == p[1])
+    }
+
+    /// Evaluate the curve at `x` with a Catmull-Rom spline through the points.
+    pub fn eval(&self, x: f64) -> f64 {
+        let pts = &self.points;
+        if pts.len() < 2 {
+            return x;
+        }
+        let x = x.clamp(pts[0][0], pts[pts.len() - 1][0]);
+        let i = pts.windows(2).position(|w| x <= w[1][0]).unwrap_or(pts.len() - 2);
+        let (p1, p2) = (pts[i], pts[i + 1]);
+        let h = p2[0] - p1[0];
+        if h <= 0.0 {
+            return p1[1];
+        }
+
+        // Catmull-Rom tangents (finite differences for uneven spacing)
+        let slope = |j: usize| {
+            let a = pts[j.saturating_sub(1)];
+            let b = pts[(j + 1).min(pts.len() - 1)];
+            if b[0] > a[0] { (b[1] - a[1]) / (b[0] - a[0]) } else { 0.0 }
+        };
+        let (m1, m2) = (slope(i), slope(i + 1));
+
+        let t = (x - p1[0]) / h;
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let y = (2.0 * t3 - 3.0 * t2 + 1.0) * p1[1]
+            + (t3 - 2.0 * t2 + t) * h * m1
+            + (-2.0 * t3 + 3.0 * t2) * p2[1]
+            + (t3 - t2) * h * m2;
+        y.clamp(0.0, 1.0)
+    }
+
+    /// Sample the curve into a lookup table of `size` evenly spaced entries.
+    fn lut(&self, size: usize) -> Vec<f64> {
+        (0..size).map(|i| self.eval(i as f64 / (size - 1) as f64)).collect()
+    }
+}
+
+/// Master and per-channel tone curves. The master curve runs first.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ToneCurves {
+    pub master: ToneCurve,
+    pub red: ToneCurve,
+    pub green: ToneCurve,
+    pub blue: ToneCurve,
+}
+
+impl ToneCurves {
+    pub fn is_identity(&self) -> bool {
+        self.master.is_identity()
+            && self.red.is_identity()
+            && self.green.is_identity()
+            && self.blue.is_identity()
+    }
+}
+
+/// Apply master then per-channel tone curves to gamma-encoded RGB [0..1].
+pub fn apply_tone_curves(rgb: &mut [[f64; 3]], curves: &ToneCurves) {
+    if curves.is_identity() {
+        return;
+    }
+    const LUT_SIZE: usize = 1024;
+    let master = curves.master.lut(LUT_SIZE);
+    let channels = [
+        curves.red.lut(LUT_SIZE),
+        curves.green.lut(LUT_SIZE),
+        curves.blue.lut(LUT_SIZE),
+    ];
+    let lookup = |lut: &[f64], v: f64| {
+        let pos = v.clamp(0.0, 1.0) * (LUT_SIZE - 1) as f64;
+        let i = (pos as usize).min(LUT_SIZE - 2);
+        let f = pos - i as f64;
+        lut[i] + (lut[i + 1] - lut[i]) * f
+    };
+    for pixel in rgb.iter_mut() {
+        for (v, lut) in pixel.iter_mut().zip(&channels) {
+            *v = lookup(lut, lookup(&master, *v));
+        }
+    }
+}
+
+/// Unsharp masking on the display-encoded image, as in-camera processing
+/// sharpens before encoding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sharpen {
+    /// Multiple of the detail added back; 0 disables sharpening.
+    pub amount: f64,
+    /// Blur radius in pixels that sets how wide an edge's overshoot runs.
+    pub radius: f64,
+    /// Detail below this, in [0..1] luma, is left alone so flat noise is
+    /// not sharpened.
+    pub threshold: f64,
+    /// Over-sharpen: add a band from a blur three times wider too, so a
+    /// broad light and dark ring follows every edge.
+    pub halo: bool,
+}
+
+impl Default for Sharpen {
+    fn default() -> Self {
+        Self {
+            amount: 0.0,
+            radius: 1.0,
+            threshold: 0.0,
+            halo: false,
+        }
+    }
+}
+
+/// Sharpen gamma-encoded RGB [0..1] on its luma, so edges get light and
+/// dark overshoot without color fringes. With `wrap` the blur it subtracts
+/// reaches across the edges.
+pub fn apply_sharpen(
+    rgb: &mut [[f64; 3]],
+    width: usize,
+    height: usize,
+    sharpen: &Sharpen,
+    wrap: bool,
+) {
+    if sharpen.amount <= 0.0 || width == 0 || height == 0 {
+        return;
+    }
+    let luma: Vec<f64> = rgb.iter().map(luma).collect();
+    let blur = |radius: f64| {
+        // Three box passes approach a Gaussian
+        let r = radius.round().max(1.0) as usize;
+        let mut plane = luma.clone();
+        let mut line = Vec::new();
+        for _ in 0..3 {
+            for y in 0..height {
+                box_blur_line(&mut plane, y * width, 1, width, r, wrap, &mut line);
+            }
+            for x in 0..width {
+                box_blur_line(&mut plane, x, width, height, r, wrap, &mut line);
+            }
+        }
+        plane
+    };
+    let near = blur(sharpen.radius);
+    let wide = if sharpen.halo { blur(sharpen.radius * 3.0) } else { Vec::new() };
+
+    for (i, pixel) in rgb.iter_mut().enumerate() {
+        let mut detail = luma[i] - near[i];
+        if detail.abs() < sharpen.threshold {
+            detail = 0.0;
+        }
+        if let Some(wide) = wide.get(i) {
+            detail += near[i] - wide;
+        }
+        for v in pixel.iter_mut() {
+            *v = (*v + sharpen.amount * detail).clamp(0.0, 1.0);
+        }
+    }
+}
+
 /// Convert floating-point RGB [0..1] to 8-bit sRGB image buffer.
-pub fn rgb_to_bytes(rgb: &[[f64; 3]], width: usize, height: usize) -> Vec<u8> {
-    let mut bytes = Vec::with_capacity(width * height * 3);
-    for pixel in rgb.iter() {
-        bytes.push((pixel[0].clamp(0.0, 1.0) * 255.0).round() as u8);
-        bytes.push((pixel[1].clamp(0.0, 1.0) * 255.0).round() as u8);
-        bytes.push((pixel[2].clamp(0.0, 1.0) * 255.0).round() as u8);
+pub fn rgb_to_bytes(
+    rgb: &[[f64; 3]],
+    width: usize,
+    height: usize,
+    dither: Dither,
+    rng: &mut impl Rng,
+) -> Vec<u8> {
+    let mut bytes = vec![0u8; width * height * 3];
+    let mut plane = vec![0.0f64; rgb.len()];
+    for c in 0..3 {
+        for (v, pixel) in plane.iter_mut().zip(rgb) {
+            *v = pixel[c].clamp(0.0, 1.0) * 255.0;
+        }
+        dither::quantize(&mut plane, width, 255.0, dither, rng);
+        for (i, v) in plane.iter().enumerate() {
+            bytes[i * 3 + c] = *v as u8;
+        }
     }
     bytes
 }
@@ -0,0 +1,117 @@
+use eframe::egui;
+
+use crate::color::spectral::ToneCurve;
+
+const EDITOR_BG: egui::Color32 = egui::Color32::from_rgb(6, 8, 16);
+const GRID: egui::Color32 = egui::Color32::from_rgb(30, 36, 48);
+const DIAGONAL: egui::Color32 = egui::Color32::from_rgb(60, 60, 75);
+const HANDLE_RADIUS: f32 = 4.0;
+/// Minimum input spacing kept between neighboring control points.
+const MIN_GAP: f64 = 0.01;
+
+/// Interactive tone curve editor.
+///
+/// Drag a point to move it, click empty space to add one, right-click a point
+/// to remove it. The end points can only move vertically.
+pub fn curve_editor(ui: &mut egui::Ui, curve: &mut ToneCurve, color: egui::Color32) -> bool {
+    let size = ui.available_width().min(220.0);
+    let (response, painter) =
+        ui.allocate_painter(egui::vec2(size, size), egui::Sense::click_and_drag());
+    let rect = response.rect.shrink(HANDLE_RADIUS);
+
+    let to_screen = |p: [f64; 2]| {
+        egui::pos2(
+            rect.left() + p[0] as f32 * rect.width(),
+            rect.bottom() - p[1] as f32 * rect.height(),
+        )
+    };
+    let from_screen = |pos: egui::Pos2| {
+        [
+            ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0) as f64,
+            ((rect.bottom() - pos.y) / rect.height()).clamp(0.0, 1.0) as f64,
+        ]
+    };
+    let nearest = |pos: egui::Pos2, points: &[[f64; 2]]| {
+        points
+            .iter()
+            .position(|&p| to_screen(p).distance(pos) <= HANDLE_RADIUS * 2.5)
+    };
+
+    let mut changed = false;
+    let drag_id = response.id.with("drag_point");
+
+    if response.drag_started()
+        && let Some(pos) = response.interact_pointer_pos()
+    {
+        let index = nearest(pos, &curve.points);
+        ui.data_mut(|d| d.insert_temp(drag_id, index));
+    }
+    let dragging: Option<usize> = ui.data(|d| d.get_temp(drag_id)).flatten();
+    if let (Some(i), Some(pos)) = (dragging, response.interact_pointer_pos())
+        && response.dragged()
+        && i < curve.points.len()
+    {
+        let [x, y] = from_screen(pos);
+        let last = curve.points.len() - 1;
+        let x = if i == 0 || i == last {
+            curve.points[i][0]
+        } else {
+            x.clamp(curve.points[i - 1][0] + MIN_GAP, curve.points[i + 1][0] - MIN_GAP)
+        };
+        if curve.points[i] != [x, y] {
+            curve.points[i] = [x, y];
+            changed = true;
+        }
+    }
+    if response.drag_stopped() {
+        ui.data_mut(|d| d.remove::<Option<usize>>(drag_id));
+    }
+
+    if response.clicked()
+        && let Some(pos) = response.interact_pointer_pos()
+        && nearest(pos, &curve.points).is_none()
+    {
+        let point = from_screen(pos);
+        let at = curve.points.partition_point(|p| p[0] < point[0]);
+        let fits = at > 0
+            && at < curve.points.len()
+            && point[0] - curve.points[at - 1][0] >= MIN_GAP
+            && curve.points[at][0] - point[0] >= MIN_GAP;
+        if fits {
+            curve.points.insert(at, point);
+            changed = true;
+        }
+    }
+    if response.secondary_clicked()
+        && let Some(pos) = response.interact_pointer_pos()
+        && let Some(i) = nearest(pos, &curve.points)
+        && i > 0
+        && i < curve.points.len() - 1
+    {
+        curve.points.remove(i);
+        changed = true;
+    }
+
+    // Background, quarter grid, and identity diagonal
+    painter.rect_filled(response.rect, 2.0, EDITOR_BG);
+    for q in 1..4 {
+        let t = q as f64 / 4.0;
+        painter.line_segment([to_screen([t, 0.0]), to_screen([t, 1.0])], (1.0, GRID));
+        painter.line_segment([to_screen([0.0, t]), to_screen([1.0, t])], (1.0, GRID));
+    }
+    painter.line_segment([to_screen([0.0, 0.0]), to_screen([1.0, 1.0])], (1.0, DIAGONAL));
+
+    let samples: Vec<egui::Pos2> = (0..=64)
+        .map(|i| {
+            let x = i as f64 / 64.0;
+            to_screen([x, curve.eval(x)])
+        })
+        .collect();
+    painter.add(egui::Shape::line(samples, egui::Stroke::new(1.5, color)));
+
+    for &p in &curve.points {
+        painter.circle(to_screen(p), HANDLE_RADIUS, EDITOR_BG, egui::Stroke::new(1.5, color));
+    }
+
+    changed
+}
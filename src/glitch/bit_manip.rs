@@ -29,11 +29,15 @@ pub fn apply_bit_rotation(grid: &mut [f64], bit_depth: u8, amount: i32) {
 }
 
 /// Apply random bit-plane swaps: swap two bit planes across the image.
-pub fn apply_bit_plane_swap(grid: &mut [f64], bit_depth: u8, swap_count: u32) {
+pub fn apply_bit_plane_swap(
+    grid: &mut [f64],
+    bit_depth: u8,
+    swap_count: u32,
+    rng: &mut impl Rng,
+) {
     if swap_count == 0 {
         return;
     }
-    let mut rng = rand::rng();
     let max_code = ((1u64 << bit_depth) - 1) as f64;
 
     for _ in 0..swap_count {
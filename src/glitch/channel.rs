@@ -88,35 +88,46 @@ pub fn apply_channel_swap(rgb: &mut [[f64; 3]], swap: ChannelSwap) {
 }
 
 /// Apply chromatic aberration simulation by offsetting color channels spatially.
+///
+/// `r_offset` and `b_offset` are (x, y) pixel shifts of the red and blue
+/// channels. Offsets sample past the edges by clamping, or by wrapping when
+/// `wrap` is set.
 pub fn apply_chromatic_aberration(
     rgb: &mut [[f64; 3]],
     width: usize,
     height: usize,
-    r_offset_x: i32,
-    r_offset_y: i32,
-    b_offset_x: i32,
-    b_offset_y: i32,
+    r_offset: (i32, i32),
+    b_offset: (i32, i32),
+    wrap: bool,
 ) {
-    if r_offset_x == 0 && r_offset_y == 0 && b_offset_x == 0 && b_offset_y == 0 {
+    if r_offset == (0, 0) && b_offset == (0, 0) {
         return;
     }
+    let ((r_offset_x, r_offset_y), (b_offset_x, b_offset_y)) = (r_offset, b_offset);
 
     let original: Vec<[f64; 3]> = rgb.to_vec();
+    let edge = |v: i32, len: usize| {
+        if wrap {
+            v.rem_euclid(len as i32) as usize
+        } else {
+            v.clamp(0, len as i32 - 1) as usize
+        }
+    };
 
     for y in 0..height {
         for x in 0..width {
             let idx = y * width + x;
 
             // Red channel from offset position
-            let rx = (x as i32 + r_offset_x).clamp(0, width as i32 - 1) as usize;
-            let ry = (y as i32 + r_offset_y).clamp(0, height as i32 - 1) as usize;
+            let rx = edge(x as i32 + r_offset_x, width);
+            let ry = edge(y as i32 + r_offset_y, height);
             rgb[idx][0] = original[ry * width + rx][0];
 
             // Green stays in place
 
             // Blue channel from offset position
-            let bx = (x as i32 + b_offset_x).clamp(0, width as i32 - 1) as usize;
-            let by = (y as i32 + b_offset_y).clamp(0, height as i32 - 1) as usize;
+            let bx = edge(x as i32 + b_offset_x, width);
+            let by = edge(y as i32 + b_offset_y, height);
             rgb[idx][2] = original[by * width + bx][2];
         }
     }
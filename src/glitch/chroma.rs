@@ -0,0 +1,82 @@
+/// Chroma subsampling as a video digitizer or DV codec stores color: full
+/// resolution luma, color difference averaged over blocks of pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChromaSubsampling {
+    /// 4:4:4, chroma at full resolution.
+    None,
+    /// 4:2:2, chroma over pairs of pixels along the row.
+    S422,
+    /// 4:2:0, chroma over 2x2 blocks.
+    S420,
+    /// 4:1:1, chroma over runs of four pixels along the row, as in NTSC DV.
+    S411,
+}
+
+impl ChromaSubsampling {
+    pub const ALL: &[ChromaSubsampling] = &[
+        ChromaSubsampling::None,
+        ChromaSubsampling::S422,
+        ChromaSubsampling::S420,
+        ChromaSubsampling::S411,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ChromaSubsampling::None => "4:4:4",
+            ChromaSubsampling::S422 => "4:2:2",
+            ChromaSubsampling::S420 => "4:2:0",
+            ChromaSubsampling::S411 => "4:1:1",
+        }
+    }
+
+    /// Pixels across and down each chroma sample covers.
+    fn block(self) -> (usize, usize) {
+        match self {
+            ChromaSubsampling::None => (1, 1),
+            ChromaSubsampling::S422 => (2, 1),
+            ChromaSubsampling::S420 => (2, 2),
+            ChromaSubsampling::S411 => (4, 1),
+        }
+    }
+}
+
+/// Convert to Rec. 601 YCbCr, average Cb and Cr over each block, spread the
+/// averages back over their blocks and convert back: color bleeds across
+/// edges in steps while luma stays sharp.
+pub fn apply_chroma_subsampling(
+    rgb: &mut [[f64; 3]],
+    width: usize,
+    height: usize,
+    scheme: ChromaSubsampling,
+) {
+    let (bw, bh) = scheme.block();
+    if (bw, bh) == (1, 1) {
+        return;
+    }
+    for by in (0..height).step_by(bh) {
+        for bx in (0..width).step_by(bw) {
+            let rows = by..(by + bh).min(height);
+            let cols = bx..(bx + bw).min(width);
+            let (mut cb, mut cr, mut count) = (0.0, 0.0, 0.0);
+            for y in rows.clone() {
+                for p in &rgb[y * width + cols.start..y * width + cols.end] {
+                    let luma = 0.299 * p[0] + 0.587 * p[1] + 0.114 * p[2];
+                    cb += (p[2] - luma) * 0.564;
+                    cr += (p[0] - luma) * 0.713;
+                    count += 1.0;
+                }
+            }
+            let (cb, cr) = (cb / count, cr / count);
+            for y in rows {
+                for p in &mut rgb[y * width + cols.start..y * width + cols.end] {
+                    let luma = 0.299 * p[0] + 0.587 * p[1] + 0.114 * p[2];
+                    *p = [
+                        luma + 1.403 * cr,
+                        luma - 0.344 * cb - 0.714 * cr,
+                        luma + 1.773 * cb,
+                    ];
+                }
+            }
+        }
+    }
+}
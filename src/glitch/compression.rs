@@ -0,0 +1,198 @@
+//! Codec artifacts, as if the CCD's output went through an MPEG or DV
+//! encoder afterward: 8x8 DCT blocks quantized by a quality setting, mosquito
+//! noise swarming around edges, blocks whose DC level flickers, and
+//! macroblocks smeared in from the wrong place by bad motion compensation.
+//!
+//! Works on display-encoded RGB as a codec would, through Rec. 601 YCbCr with
+//! the JPEG quantization tables. Everything random draws from the frame's
+//! RNG, so in sequences the mosquitoes and flicker change every frame.
+
+use rand::Rng;
+
+use crate::ccd::noise::gaussian;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Compression {
+    pub enabled: bool,
+    /// Encoder quality, 1 (worst) to 100, scaling the quantization tables
+    /// as the IJG JPEG encoder does.
+    pub quality: f64,
+    /// RMS noise on the high frequencies of blocks holding edges, in
+    /// quantizer steps.
+    pub mosquito: f64,
+    /// RMS offset of each block's DC level, in 8-bit levels.
+    pub dc_flicker: f64,
+    /// Chance per 16x16 macroblock of being copied from a wrong motion
+    /// vector.
+    pub block_smear: f64,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quality: 50.0,
+            mosquito: 0.0,
+            dc_flicker: 0.0,
+            block_smear: 0.0,
+        }
+    }
+}
+
+/// JPEG Annex K luminance and chrominance quantization tables.
+const LUMA_TABLE: [f64; 64] = [
+    16., 11., 10., 16., 24., 40., 51., 61., 12., 12., 14., 19., 26., 58., 60., 55., 14., 13., 16.,
+    24., 40., 57., 69., 56., 14., 17., 22., 29., 51., 87., 80., 62., 18., 22., 37., 56., 68., 109.,
+    103., 77., 24., 35., 55., 64., 81., 104., 113., 92., 49., 64., 78., 87., 103., 121., 120., 101.,
+    72., 92., 95., 98., 112., 100., 103., 99.,
+];
+const CHROMA_TABLE: [f64; 64] = [
+    17., 18., 24., 47., 99., 99., 99., 99., 18., 21., 26., 66., 99., 99., 99., 99., 24., 26., 56.,
+    99., 99., 99., 99., 99., 47., 66., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99.,
+    99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99., 99.,
+    99., 99., 99., 99., 99., 99., 99.,
+];
+/// Quantizer steps of AC energy that mark a block as holding an edge.
+const EDGE_ENERGY: f64 = 4.0;
+const MACROBLOCK: usize = 16;
+
+/// Apply the codec to RGB [0..1] in place. With `wrap` partial blocks pad
+/// from the opposite edge and smeared blocks copy across it, so the result
+/// tiles when the size is a multiple of the 16-pixel macroblock.
+pub fn apply_compression(
+    rgb: &mut [[f64; 3]],
+    width: usize,
+    height: usize,
+    compression: &Compression,
+    wrap: bool,
+    rng: &mut impl Rng,
+) {
+    if !compression.enabled || width == 0 || height == 0 {
+        return;
+    }
+    if compression.block_smear > 0.0 {
+        smear_macroblocks(rgb, width, height, compression.block_smear, wrap, rng);
+    }
+    let edge = |i: usize, len: usize| if wrap { i % len } else { i.min(len - 1) };
+
+    // IJG quality scaling
+    let quality = compression.quality.clamp(1.0, 100.0);
+    let scale = if quality < 50.0 { 50.0 / quality } else { 2.0 - quality / 50.0 };
+    let tables = [LUMA_TABLE, CHROMA_TABLE]
+        .map(|table| table.map(|q| (q * scale).round().clamp(1.0, 255.0)));
+    let cosines: [[f64; 8]; 8] = std::array::from_fn(|x| {
+        std::array::from_fn(|u| {
+            let norm = if u == 0 { (1.0f64 / 8.0).sqrt() } else { (2.0f64 / 8.0).sqrt() };
+            norm * ((2 * x + 1) as f64 * u as f64 * std::f64::consts::PI / 16.0).cos()
+        })
+    });
+
+    let mut planes: [Vec<f64>; 3] = std::array::from_fn(|_| vec![0.0; rgb.len()]);
+    for (i, p) in rgb.iter().enumerate() {
+        let [r, g, b] = p.map(|v| v.clamp(0.0, 1.0) * 255.0);
+        let y = 0.299 * r + 0.587 * g + 0.114 * b;
+        planes[0][i] = y - 128.0;
+        planes[1][i] = (b - y) * 0.564;
+        planes[2][i] = (r - y) * 0.713;
+    }
+
+    let mut block = [0.0; 64];
+    for (c, plane) in planes.iter_mut().enumerate() {
+        let table = &tables[c.min(1)];
+        for by in (0..height).step_by(8) {
+            for bx in (0..width).step_by(8) {
+                // Edge pixels repeat into partial blocks, as encoders pad
+                for (j, v) in block.iter_mut().enumerate() {
+                    let y = edge(by + j / 8, height);
+                    let x = edge(bx + j % 8, width);
+                    *v = plane[y * width + x];
+                }
+                let mut coefficients = transform(&block, &cosines, false);
+                let mut energy = 0.0;
+                for (k, coef) in coefficients.iter_mut().enumerate() {
+                    let level = (*coef / table[k]).round();
+                    if k > 0 {
+                        energy += level.abs();
+                    }
+                    *coef = level * table[k];
+                }
+                if c == 0 && compression.dc_flicker > 0.0 {
+                    // The DC basis is 1/8 per pixel
+                    coefficients[0] += compression.dc_flicker * 8.0 * gaussian(rng);
+                }
+                if compression.mosquito > 0.0 && energy >= EDGE_ENERGY {
+                    for (k, coef) in coefficients.iter_mut().enumerate() {
+                        if k / 8 + k % 8 >= 4 {
+                            *coef += compression.mosquito * table[k] * gaussian(rng);
+                        }
+                    }
+                }
+                block = transform(&coefficients, &cosines, true);
+                for (j, &v) in block.iter().enumerate() {
+                    let (y, x) = (by + j / 8, bx + j % 8);
+                    if y < height && x < width {
+                        plane[y * width + x] = v;
+                    }
+                }
+            }
+        }
+    }
+
+    for (i, p) in rgb.iter_mut().enumerate() {
+        let y = planes[0][i] + 128.0;
+        let (cb, cr) = (planes[1][i], planes[2][i]);
+        *p = [y + 1.403 * cr, y - 0.344 * cb - 0.714 * cr, y + 1.773 * cb]
+            .map(|v| (v / 255.0).clamp(0.0, 1.0));
+    }
+}
+
+/// Separable 8x8 DCT-II, or its inverse, with orthonormal scaling.
+fn transform(block: &[f64; 64], cosines: &[[f64; 8]; 8], inverse: bool) -> [f64; 64] {
+    let basis = |i: usize, k: usize| if inverse { cosines[k][i] } else { cosines[i][k] };
+    let mut rows = [0.0; 64];
+    for y in 0..8 {
+        for k in 0..8 {
+            rows[y * 8 + k] = (0..8).map(|i| block[y * 8 + i] * basis(i, k)).sum();
+        }
+    }
+    let mut out = [0.0; 64];
+    for x in 0..8 {
+        for k in 0..8 {
+            out[k * 8 + x] = (0..8).map(|i| rows[i * 8 + x] * basis(i, k)).sum();
+        }
+    }
+    out
+}
+
+/// Replace a `chance` of the 16x16 macroblocks with the picture a few
+/// pixels off, as from a corrupt motion vector.
+fn smear_macroblocks(
+    rgb: &mut [[f64; 3]],
+    width: usize,
+    height: usize,
+    chance: f64,
+    wrap: bool,
+    rng: &mut impl Rng,
+) {
+    let source = rgb.to_vec();
+    let edge = |i: i64, len: usize| {
+        let len = len as i64;
+        (if wrap { i.rem_euclid(len) } else { i.clamp(0, len - 1) }) as usize
+    };
+    for by in (0..height).step_by(MACROBLOCK) {
+        for bx in (0..width).step_by(MACROBLOCK) {
+            if rng.random::<f64>() >= chance {
+                continue;
+            }
+            let dx = rng.random_range(-8i64..=8);
+            let dy = rng.random_range(-8i64..=8);
+            for y in by..(by + MACROBLOCK).min(height) {
+                for x in bx..(bx + MACROBLOCK).min(width) {
+                    let sx = edge(x as i64 + dx, width);
+                    let sy = edge(y as i64 + dy, height);
+                    rgb[y * width + x] = source[sy * width + sx];
+                }
+            }
+        }
+    }
+}
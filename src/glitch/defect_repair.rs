@@ -0,0 +1,87 @@
+//! In-camera defect correction misfiring ("repair gone wrong").
+//!
+//! Cameras map out bad rows and columns at the factory and patch them at
+//! readout by averaging the same-color lines two photosites away. When the
+//! defect map is wrong, good lines get patched instead, softening thin
+//! stripes of the image. When the patch offset is wrong too, a line is
+//! rebuilt from one side only and from the wrong CFA color, and each patched
+//! line feeds the next so a defect cluster smears one neighbor across it.
+
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepairMode {
+    /// Average the same-color lines on both sides.
+    Interpolate,
+    /// Take both samples from one side at odd (wrong-color) offsets.
+    WrongOffset,
+}
+
+impl RepairMode {
+    pub const ALL: &[RepairMode] = &[RepairMode::Interpolate, RepairMode::WrongOffset];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RepairMode::Interpolate => "Interpolate",
+            RepairMode::WrongOffset => "Wrong Offset",
+        }
+    }
+}
+
+/// Patch random rows and columns of a Bayer mosaic from their neighbors.
+/// `amount`: 0.0 = off, 1.0 = about 1% of lines patched, in clusters of 1-3.
+pub fn apply_defect_repair(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    amount: f64,
+    mode: RepairMode,
+    rng: &mut impl Rng,
+) {
+    if amount <= 0.0 || width < 8 || height < 8 {
+        return;
+    }
+    let clusters = ((width + height) as f64 * amount * 0.005).ceil() as usize;
+
+    for _ in 0..clusters {
+        // Columns are picked in proportion to how many there are
+        let vertical = rng.random_bool(width as f64 / (width + height) as f64);
+        let (lines, span) = if vertical { (width, height) } else { (height, width) };
+        let index = |line: usize, i: usize| if vertical { i * width + line } else { line * width + i };
+
+        let run = rng.random_range(1..=3usize);
+        let start = rng.random_range(0..lines - run);
+        let from_below: bool = rng.random();
+        let (near, far): (isize, isize) = match (mode, from_below) {
+            (RepairMode::Interpolate, _) => (-2, 2),
+            (RepairMode::WrongOffset, true) => (1, 3),
+            (RepairMode::WrongOffset, false) => (-1, -3),
+        };
+
+        // Walk away from the source side so each patched line feeds the next
+        let order: Vec<usize> = if from_below {
+            (start..start + run).rev().collect()
+        } else {
+            (start..start + run).collect()
+        };
+        for line in order {
+            let a = reflect(line as isize + near, lines);
+            let b = reflect(line as isize + far, lines);
+            for i in 0..span {
+                grid[index(line, i)] = 0.5 * (grid[index(a, i)] + grid[index(b, i)]);
+            }
+        }
+    }
+}
+
+/// Mirror an out-of-range line index back into `0..len`.
+fn reflect(i: isize, len: usize) -> usize {
+    let last = len as isize - 1;
+    if i < 0 {
+        (-i) as usize
+    } else if i > last {
+        (2 * last - i) as usize
+    } else {
+        i as usize
+    }
+}
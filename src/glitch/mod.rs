@@ -1,4 +1,45 @@
 pub mod bit_manip;
 pub mod channel;
+pub mod chroma;
+pub mod compression;
+pub mod defect_repair;
 pub mod pixel_shift;
+pub mod plugin;
+pub mod raw_pack;
 pub mod scan_line;
+pub mod sync;
+
+/// Run `f` on each of the four 2x2 CFA sites of a Bayer mosaic as its own
+/// half-resolution plane, writing the result back in place.
+///
+/// Glitches applied this way tear each color plane independently, so the
+/// damage shows up as colored fringing after demosaic rather than luminance.
+pub fn for_each_cfa_plane(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    mut f: impl FnMut(&mut [f64], usize, usize),
+) {
+    let mut plane = Vec::new();
+    for (ox, oy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        let plane_w = (width + 1 - ox) / 2;
+        let plane_h = (height + 1 - oy) / 2;
+        if plane_w == 0 || plane_h == 0 {
+            continue;
+        }
+
+        plane.clear();
+        for y in (oy..height).step_by(2) {
+            plane.extend((ox..width).step_by(2).map(|x| grid[y * width + x]));
+        }
+
+        f(&mut plane, plane_w, plane_h);
+
+        let mut values = plane.iter();
+        for y in (oy..height).step_by(2) {
+            for x in (ox..width).step_by(2) {
+                grid[y * width + x] = *values.next().unwrap();
+            }
+        }
+    }
+}
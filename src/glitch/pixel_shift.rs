@@ -7,11 +7,11 @@ pub fn apply_pixel_shift(
     width: usize,
     height: usize,
     amount: f64,
+    rng: &mut impl Rng,
 ) {
     if amount <= 0.0 {
         return;
     }
-    let mut rng = rand::rng();
     let max_shift = (width as f64 * amount * 0.1).ceil() as usize;
     if max_shift == 0 {
         return;
@@ -46,11 +46,11 @@ pub fn apply_block_shift(
     width: usize,
     height: usize,
     amount: f64,
+    rng: &mut impl Rng,
 ) {
     if amount <= 0.0 {
         return;
     }
-    let mut rng = rand::rng();
     let num_blocks = (amount * 5.0).ceil() as usize;
     let max_shift = (width as f64 * amount * 0.15).ceil() as usize;
 
@@ -0,0 +1,243 @@
+//! External glitch stages loaded from shared libraries.
+//!
+//! A plugin is a shared library, written in any language, that exports one C
+//! symbol, `ccd_glitch_plugin_v1`, returning a pointer to a static
+//! `PluginDescriptor`. The layout is fixed by `include/ccd_glitch_plugin.h`
+//! and only ever grows behind a new `abi_version`. Each plugin runs at one of
+//! two points: on the raw mosaic after the built-in glitch stages (one
+//! channel, ADU codes up to `max_value`), or on linear RGB after the channel
+//! effects (three interleaved channels, 0..1). Plugins are discovered by
+//! scanning a directory and only run in native builds.
+
+use std::ffi::{c_char, CStr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// ABI version this build understands.
+pub const ABI_VERSION: u32 = 1;
+/// Name of the exported descriptor function, nul-terminated.
+pub const ENTRY_SYMBOL: &[u8] = b"ccd_glitch_plugin_v1\0";
+/// Environment variable overriding the plugin directory.
+pub const DIR_ENV: &str = "CCD_GLITCH_PLUGIN_DIR";
+
+/// `process` callback: edit `data` (`width * height * channels` doubles,
+/// row-major, channels interleaved) in place. Returns 0 on success.
+pub type ProcessFn = unsafe extern "C" fn(
+    data: *mut f64,
+    width: usize,
+    height: usize,
+    channels: usize,
+    max_value: f64,
+    amount: f64,
+    seed: u64,
+) -> i32;
+
+/// Descriptor a plugin returns from `ccd_glitch_plugin_v1`. Must stay valid
+/// for as long as the library is loaded.
+#[repr(C)]
+pub struct PluginDescriptor {
+    pub abi_version: u32,
+    /// 0 = mosaic, 1 = RGB.
+    pub stage: u32,
+    pub name: *const c_char,
+    /// May be null.
+    pub description: *const c_char,
+    pub process: Option<ProcessFn>,
+}
+
+/// Where in the pipeline a plugin runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PluginStage {
+    /// Bayer mosaic in ADU, after the built-in glitch stages.
+    Mosaic,
+    /// Demosaiced linear RGB in 0..1, after the channel effects.
+    Rgb,
+}
+
+impl PluginStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            PluginStage::Mosaic => "Mosaic",
+            PluginStage::Rgb => "RGB",
+        }
+    }
+}
+
+/// A loaded plugin. The library stays mapped while any clone of the
+/// `Arc<Plugin>` holding it is alive.
+pub struct Plugin {
+    pub name: String,
+    pub description: String,
+    pub stage: PluginStage,
+    pub path: PathBuf,
+    process: ProcessFn,
+    #[cfg(not(target_arch = "wasm32"))]
+    _library: libloading::Library,
+}
+
+impl std::fmt::Debug for Plugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Plugin")
+            .field("name", &self.name)
+            .field("stage", &self.stage)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl Plugin {
+    /// Load the plugin in the shared library at `path`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let fail = |e: &dyn std::fmt::Display| format!("{}: {e}", path.display());
+
+        // SAFETY: loading runs the library's initializers; plugins are trusted
+        // native code the user placed in the plugin directory.
+        // libloading errors already name the library
+        let library = unsafe { libloading::Library::new(path) }.map_err(|e| e.to_string())?;
+        // SAFETY: the symbol type is fixed by the plugin ABI.
+        let descriptor = unsafe {
+            let entry = library
+                .get::<unsafe extern "C" fn() -> *const PluginDescriptor>(ENTRY_SYMBOL)
+                .map_err(|e| e.to_string())?;
+            entry()
+        };
+        if descriptor.is_null() {
+            return Err(fail(&"descriptor is null"));
+        }
+        // SAFETY: non-null and static for the library's lifetime per the ABI.
+        let descriptor = unsafe { &*descriptor };
+        if descriptor.abi_version != ABI_VERSION {
+            return Err(fail(&format!(
+                "ABI version {} (expected {ABI_VERSION})",
+                descriptor.abi_version
+            )));
+        }
+        let stage = match descriptor.stage {
+            0 => PluginStage::Mosaic,
+            1 => PluginStage::Rgb,
+            other => return Err(fail(&format!("unknown stage {other}"))),
+        };
+        let process = descriptor.process.ok_or_else(|| fail(&"process is null"))?;
+        // SAFETY: the ABI requires nul-terminated strings (or null).
+        let name = unsafe { c_string(descriptor.name) };
+        let description = unsafe { c_string(descriptor.description) };
+        let name = if name.is_empty() {
+            path.file_stem().map_or_else(String::new, |s| s.to_string_lossy().into_owned())
+        } else {
+            name
+        };
+
+        Ok(Self {
+            name,
+            description,
+            stage,
+            path: path.to_path_buf(),
+            process,
+            _library: library,
+        })
+    }
+}
+
+/// A plugin enabled in the pipeline, with its controls.
+#[derive(Debug, Clone)]
+pub struct PluginSlot {
+    pub plugin: Arc<Plugin>,
+    /// Passed through to the plugin; 1.0 is its nominal strength.
+    pub amount: f64,
+    pub seed: u64,
+}
+
+impl PluginSlot {
+    pub fn new(plugin: Arc<Plugin>) -> Self {
+        Self { plugin, amount: 1.0, seed: 0 }
+    }
+
+    /// Run the plugin over `data`, which holds `channels` interleaved values
+    /// per pixel.
+    pub fn run(
+        &self,
+        data: &mut [f64],
+        width: usize,
+        height: usize,
+        channels: usize,
+        max_value: f64,
+    ) -> Result<(), String> {
+        assert_eq!(data.len(), width * height * channels);
+        let process = self.plugin.process;
+        // SAFETY: `data` is exactly the size the callback is told about.
+        let status = unsafe {
+            process(data.as_mut_ptr(), width, height, channels, max_value, self.amount, self.seed)
+        };
+        if status == 0 {
+            Ok(())
+        } else {
+            Err(format!("plugin {} failed with status {status}", self.plugin.name))
+        }
+    }
+}
+
+/// Run each enabled plugin for `stage`, in order, over `data`. Failures are
+/// logged and leave the remaining plugins to run.
+pub fn apply_plugins(
+    slots: &[PluginSlot],
+    stage: PluginStage,
+    data: &mut [f64],
+    width: usize,
+    height: usize,
+    channels: usize,
+    max_value: f64,
+) {
+    for slot in slots.iter().filter(|s| s.plugin.stage == stage) {
+        if let Err(e) = slot.run(data, width, height, channels, max_value) {
+            log::warn!("{e}");
+        }
+    }
+}
+
+/// Directory scanned for plugins: `$CCD_GLITCH_PLUGIN_DIR`, else `plugins`
+/// in the working directory.
+pub fn plugin_dir() -> PathBuf {
+    std::env::var_os(DIR_ENV).map_or_else(|| PathBuf::from("plugins"), PathBuf::from)
+}
+
+/// Load every shared library in `dir`, sorted by file name. Returns the
+/// plugins that loaded and one message per library that did not. A missing
+/// directory is not an error.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn discover(dir: &Path) -> (Vec<Arc<Plugin>>, Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == std::env::consts::DLL_EXTENSION))
+        .collect();
+    paths.sort();
+
+    let mut plugins = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match Plugin::load(&path) {
+            Ok(plugin) => plugins.push(Arc::new(plugin)),
+            Err(e) => errors.push(e),
+        }
+    }
+    (plugins, errors)
+}
+
+/// Dynamic loading is unavailable on the web.
+#[cfg(target_arch = "wasm32")]
+pub fn discover(_dir: &Path) -> (Vec<Arc<Plugin>>, Vec<String>) {
+    (Vec::new(), Vec::new())
+}
+
+/// Copy a C string, treating null as empty.
+unsafe fn c_string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        String::new()
+    } else {
+        // SAFETY: caller guarantees a nul-terminated string.
+        unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+    }
+}
@@ -8,12 +8,11 @@ pub fn apply_scan_line_corruption(
     height: usize,
     frequency: f64,
     max_value: f64,
+    rng: &mut impl Rng,
 ) {
     if frequency <= 0.0 {
         return;
     }
-    let mut rng = rand::rng();
-
     let num_bands = (height as f64 * frequency * 0.05).ceil() as usize;
 
     for _ in 0..num_bands {
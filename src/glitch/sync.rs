@@ -0,0 +1,119 @@
+//! Faults in the video signal carrying the digitized frame: segments where
+//! the receiver loses horizontal sync and free-runs, and lines that drop out
+//! entirely.
+
+use rand::Rng;
+
+/// How the lengths of fault bursts are distributed around their mean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BurstLength {
+    /// Every burst is the mean length.
+    Fixed,
+    /// Mostly short bursts with an exponential tail, as from independent
+    /// per-line failures.
+    Geometric,
+    /// Mostly single lines with rare long bursts (Pareto, alpha 1.5), as
+    /// from a failing connector or tape dropout.
+    HeavyTail,
+}
+
+impl BurstLength {
+    pub const ALL: &[BurstLength] =
+        &[BurstLength::Fixed, BurstLength::Geometric, BurstLength::HeavyTail];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BurstLength::Fixed => "Fixed",
+            BurstLength::Geometric => "Geometric",
+            BurstLength::HeavyTail => "Heavy Tail",
+        }
+    }
+
+    /// Draw a burst length in rows with the given mean.
+    fn sample(self, mean: f64, rng: &mut impl Rng) -> usize {
+        let mean = mean.max(1.0);
+        // Open interval, so the logs and powers below stay finite
+        let u = rng.random::<f64>().max(f64::MIN_POSITIVE);
+        let rows = match self {
+            BurstLength::Fixed => mean.round(),
+            BurstLength::Geometric if mean <= 1.0 => 1.0,
+            BurstLength::Geometric => (libm::log(u) / libm::log(1.0 - 1.0 / mean)).ceil(),
+            BurstLength::HeavyTail => (mean / 3.0 * libm::pow(u, -1.0 / 1.5)).ceil(),
+        };
+        rows.max(1.0) as usize
+    }
+}
+
+/// Sync loss and dropout settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncFaults {
+    /// Expected sync-loss segments per frame; 0 disables them.
+    pub loss_rate: f64,
+    /// Mean rows per sync-loss segment.
+    pub loss_length: f64,
+    /// Expected line dropout bursts per frame; 0 disables them.
+    pub dropout_rate: f64,
+    /// Mean rows per dropout burst.
+    pub dropout_length: f64,
+    pub burst: BurstLength,
+}
+
+impl Default for SyncFaults {
+    fn default() -> Self {
+        Self {
+            loss_rate: 0.0,
+            loss_length: 12.0,
+            dropout_rate: 0.0,
+            dropout_length: 2.0,
+            burst: BurstLength::Geometric,
+        }
+    }
+}
+
+/// Apply sync loss and line dropouts to full rows of the frame.
+///
+/// In a sync-loss segment the line timing free-runs: each row is wrapped
+/// around by an offset that starts anywhere and slides a random amount per
+/// row, tearing the segment diagonally. Dropped lines carry no picture and
+/// are replaced with noise across the full code range.
+pub fn apply_sync_faults(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    faults: &SyncFaults,
+    max_value: f64,
+    rng: &mut impl Rng,
+) {
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    for _ in 0..event_count(faults.loss_rate, rng) {
+        let start = rng.random_range(0..height);
+        let rows = faults.burst.sample(faults.loss_length, rng).min(height - start);
+        let offset = rng.random_range(0.0..width as f64);
+        let slide = rng.random_range(-0.1..=0.1) * width as f64;
+        for k in 0..rows {
+            let shift = (offset + slide * k as f64).rem_euclid(width as f64) as usize;
+            let row = (start + k) * width;
+            grid[row..row + width].rotate_right(shift.min(width - 1));
+        }
+    }
+
+    for _ in 0..event_count(faults.dropout_rate, rng) {
+        let start = rng.random_range(0..height);
+        let rows = faults.burst.sample(faults.dropout_length, rng).min(height - start);
+        for v in &mut grid[start * width..(start + rows) * width] {
+            *v = (rng.random::<f64>() * max_value).round();
+        }
+    }
+}
+
+/// Whole events from an expected count: the integer part always, plus one
+/// more with the fractional part's probability.
+fn event_count(rate: f64, rng: &mut impl Rng) -> usize {
+    if rate <= 0.0 {
+        return 0;
+    }
+    rate.floor() as usize + usize::from(rng.random::<f64>() < rate.fract())
+}
@@ -1,31 +1,279 @@
-use image::{DynamicImage, GenericImageView, ImageBuffer, Rgb, RgbImage};
+use image::{DynamicImage, GenericImageView, GrayImage, ImageBuffer, Luma, Rgb, RgbImage, RgbaImage};
 
+/// Load an image from disk. OpenEXR and 32-bit float TIFF load as float
+/// images whose values may exceed 1.0; see `resize_hdr_to_sensor`.
 #[cfg(not(target_arch = "wasm32"))]
 pub fn load_image(path: &std::path::Path) -> Result<DynamicImage, String> {
     image::open(path).map_err(|e| format!("Failed to load image: {e}"))
 }
 
-/// Resize image to fit within sensor dimensions, preserving aspect ratio.
-/// Letterboxes/pillarboxes remaining area with black.
-pub fn resize_to_sensor(img: &DynamicImage, sensor_w: u32, sensor_h: u32) -> RgbImage {
-    let (iw, ih) = img.dimensions();
+/// True for float images (EXR, float TIFF) that can carry values above 1.0.
+pub fn is_hdr(img: &DynamicImage) -> bool {
+    matches!(img, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_))
+}
+
+/// Resize a float image onto the sensor without clipping highlights.
+///
+/// Returns per-pixel exposure where 1.0 is full well; overexposed HDR values
+/// map above it so they drive blooming and the anti-blooming gate. Values are
+/// taken as linear light. Returns `None` for non-float images. See
+/// `resize_filter` for `strict`.
+pub fn resize_hdr_to_sensor(
+    img: &DynamicImage,
+    sensor_w: u32,
+    sensor_h: u32,
+    fit: FitMode,
+    strict: bool,
+) -> Option<Vec<[f64; 3]>> {
+    if !is_hdr(img) {
+        return None;
+    }
+
+    // The resampler clamps float samples to [0, 1], so scale by the peak
+    // first and restore it afterwards.
+    let mut rgb = img.to_rgb32f();
+    let peak = rgb
+        .pixels()
+        .flat_map(|p| p.0)
+        .filter(|v| v.is_finite())
+        .fold(1.0f32, f32::max);
+    for p in rgb.pixels_mut() {
+        for v in p.0.iter_mut() {
+            *v = if v.is_finite() { v.max(0.0) / peak } else { 0.0 };
+        }
+    }
+
+    let rgb = DynamicImage::ImageRgb32F(rgb);
+    let resized = resize_to_fit(&rgb, sensor_w, sensor_h, fit, strict).to_rgb32f();
+    let placed = letterbox(&resized, sensor_w, sensor_h, Rgb([0.0f32; 3]));
+    Some(
+        placed
+            .pixels()
+            .map(|p| p.0.map(|v| v as f64 * peak as f64))
+            .collect(),
+    )
+}
+
+/// How a source image is mapped onto the sensor when aspect ratios differ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitMode {
+    /// Scale each axis independently to fill the sensor (distorts aspect ratio).
+    Stretch,
+    /// Scale to cover the sensor and crop the overflow, centered.
+    Crop,
+    /// Scale to fit inside the sensor and pad the remaining area.
+    Letterbox,
+}
+
+impl FitMode {
+    pub const ALL: &[FitMode] = &[FitMode::Stretch, FitMode::Crop, FitMode::Letterbox];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FitMode::Stretch => "Stretch",
+            FitMode::Crop => "Crop to Fill",
+            FitMode::Letterbox => "Letterbox",
+        }
+    }
+}
+
+/// Resize image onto the sensor using `fit`.
+/// Letterboxed/pillarboxed area is black; see `content_rect` to locate it.
+pub fn resize_to_sensor(
+    img: &DynamicImage,
+    sensor_w: u32,
+    sensor_h: u32,
+    fit: FitMode,
+    strict: bool,
+) -> RgbImage {
+    let resized = resize_to_fit(img, sensor_w, sensor_h, fit, strict).to_rgb8();
+    letterbox(&resized, sensor_w, sensor_h, Rgb([0u8, 0, 0]))
+}
+
+/// Resize the alpha channel exactly like `resize_to_sensor` resizes color.
+/// Letterbox area is fully transparent. Returns `None` if the image has no alpha.
+pub fn resize_alpha_to_sensor(
+    img: &DynamicImage,
+    sensor_w: u32,
+    sensor_h: u32,
+    fit: FitMode,
+    strict: bool,
+) -> Option<GrayImage> {
+    if !img.color().has_alpha() {
+        return None;
+    }
+    let resized = resize_to_fit(img, sensor_w, sensor_h, fit, strict).to_rgba8();
+    let alpha = GrayImage::from_fn(resized.width(), resized.height(), |x, y| {
+        Luma([resized.get_pixel(x, y)[3]])
+    });
+    Some(letterbox(&alpha, sensor_w, sensor_h, Luma([0u8])))
+}
+
+/// Sensor area covered by image content as (x, y, width, height).
+///
+/// Covers the whole sensor except in letterbox mode.
+pub fn content_rect(img_w: u32, img_h: u32, sensor_w: u32, sensor_h: u32, fit: FitMode) -> (u32, u32, u32, u32) {
+    if fit != FitMode::Letterbox {
+        return (0, 0, sensor_w, sensor_h);
+    }
     let scale = f64::min(
+        sensor_w as f64 / img_w as f64,
+        sensor_h as f64 / img_h as f64,
+    );
+    let new_w = ((img_w as f64 * scale).round() as u32).min(sensor_w);
+    let new_h = ((img_h as f64 * scale).round() as u32).min(sensor_h);
+    ((sensor_w - new_w) / 2, (sensor_h - new_h) / 2, new_w, new_h)
+}
+
+/// Lanczos, or with `strict` Catmull-Rom: its kernel is a polynomial, where
+/// Lanczos evaluates sines that round differently between platforms.
+fn resize_filter(strict: bool) -> image::imageops::FilterType {
+    if strict {
+        image::imageops::FilterType::CatmullRom
+    } else {
+        image::imageops::FilterType::Lanczos3
+    }
+}
+
+fn resize_to_fit(
+    img: &DynamicImage,
+    sensor_w: u32,
+    sensor_h: u32,
+    fit: FitMode,
+    strict: bool,
+) -> DynamicImage {
+    let filter = resize_filter(strict);
+    let (iw, ih) = img.dimensions();
+    match fit {
+        FitMode::Stretch => img.resize_exact(sensor_w, sensor_h, filter),
+        FitMode::Crop => {
+            // Crop the source to the sensor aspect ratio first, then scale
+            let (x, y, crop_w, crop_h) = crop_region(iw, ih, sensor_w, sensor_h);
+            img.crop_imm(x, y, crop_w, crop_h)
+                .resize_exact(sensor_w, sensor_h, filter)
+        }
+        FitMode::Letterbox => {
+            let (_, _, new_w, new_h) = content_rect(iw, ih, sensor_w, sensor_h, fit);
+            img.resize_exact(new_w, new_h, filter)
+        }
+    }
+}
+
+/// Centered region of the source with the sensor's aspect ratio, as (x, y, width, height).
+fn crop_region(iw: u32, ih: u32, sensor_w: u32, sensor_h: u32) -> (u32, u32, u32, u32) {
+    let scale = f64::max(
         sensor_w as f64 / iw as f64,
         sensor_h as f64 / ih as f64,
     );
-    let new_w = (iw as f64 * scale).round() as u32;
-    let new_h = (ih as f64 * scale).round() as u32;
+    let crop_w = ((sensor_w as f64 / scale).round() as u32).clamp(1, iw);
+    let crop_h = ((sensor_h as f64 / scale).round() as u32).clamp(1, ih);
+    ((iw - crop_w) / 2, (ih - crop_h) / 2, crop_w, crop_h)
+}
+
+/// Downscale by summing every source pixel into the sensor pixel it lands on.
+///
+/// Returns per-pixel exposure where 1.0 is one fully exposed source pixel,
+/// so a sensor pixel covering N source pixels can reach N. Like on-chip
+/// binning, charge adds rather than averages: small highlights keep their
+/// energy (and can bloom), and the overall level rises with the downscale
+/// ratio. Returns `None` when the source is not larger than the sensor area
+/// it maps to, since there is nothing to sum.
+pub fn resize_area_sum(
+    img: &DynamicImage,
+    sensor_w: u32,
+    sensor_h: u32,
+    fit: FitMode,
+) -> Option<Vec<[f64; 3]>> {
+    let (iw, ih) = img.dimensions();
+    let (cx, cy, cw, ch) = content_rect(iw, ih, sensor_w, sensor_h, fit);
+    let (sx, sy, sw, sh) = match fit {
+        FitMode::Crop => crop_region(iw, ih, sensor_w, sensor_h),
+        FitMode::Stretch | FitMode::Letterbox => (0, 0, iw, ih),
+    };
+    if sw < cw || sh < ch {
+        return None;
+    }
+
+    // Float conversion keeps HDR values above 1.0
+    let rgb = img.to_rgb32f();
+    let mut output = vec![[0.0f64; 3]; (sensor_w * sensor_h) as usize];
+    for y in 0..sh {
+        let ty = cy + (y as u64 * ch as u64 / sh as u64) as u32;
+        for x in 0..sw {
+            let tx = cx + (x as u64 * cw as u64 / sw as u64) as u32;
+            let p = rgb.get_pixel(sx + x, sy + y);
+            let out = &mut output[(ty * sensor_w + tx) as usize];
+            for c in 0..3 {
+                out[c] += p[c] as f64;
+            }
+        }
+    }
+    Some(output)
+}
+
+/// Resample each row of a `width` x `height` plane to `new_width` with a
+/// triangle filter, widened when shrinking so every input sample contributes.
+/// Values are not clamped, so electron counts and HDR levels survive.
+pub fn resample_width<const N: usize>(
+    data: &[[f64; N]],
+    width: usize,
+    height: usize,
+    new_width: usize,
+) -> Vec<[f64; N]> {
+    if new_width == width || width == 0 || new_width == 0 {
+        return data.to_vec();
+    }
+    let scale = width as f64 / new_width as f64;
+    let support = scale.max(1.0);
 
-    let resized = img.resize_exact(new_w, new_h, image::imageops::FilterType::Lanczos3);
-    let resized_rgb = resized.to_rgb8();
+    // Taps are the same for every row: (first source column, weights)
+    let taps: Vec<(usize, Vec<f64>)> = (0..new_width)
+        .map(|x| {
+            let center = (x as f64 + 0.5) * scale - 0.5;
+            let start = (center - support).ceil().max(0.0) as usize;
+            let end = ((center + support).floor() as usize).min(width - 1);
+            let mut weights: Vec<f64> = (start..=end)
+                .map(|sx| (1.0 - (sx as f64 - center).abs() / support).max(0.0))
+                .collect();
+            let total: f64 = weights.iter().sum();
+            if total > 0.0 {
+                weights.iter_mut().for_each(|w| *w /= total);
+            }
+            (start, weights)
+        })
+        .collect();
+
+    let mut output = Vec::with_capacity(new_width * height);
+    for row in data.chunks_exact(width).take(height) {
+        for (start, weights) in &taps {
+            let mut acc = [0.0; N];
+            for (i, w) in weights.iter().enumerate() {
+                let p = row[start + i];
+                for c in 0..N {
+                    acc[c] += p[c] * w;
+                }
+            }
+            output.push(acc);
+        }
+    }
+    output
+}
 
-    let mut output = ImageBuffer::from_pixel(sensor_w, sensor_h, Rgb([0u8, 0, 0]));
+/// Center `img` on a `sensor_w` x `sensor_h` canvas filled with `fill`.
+fn letterbox<P: image::Pixel>(
+    img: &ImageBuffer<P, Vec<P::Subpixel>>,
+    sensor_w: u32,
+    sensor_h: u32,
+    fill: P,
+) -> ImageBuffer<P, Vec<P::Subpixel>> {
+    let (new_w, new_h) = img.dimensions();
+    let mut output = ImageBuffer::from_pixel(sensor_w, sensor_h, fill);
     let offset_x = (sensor_w.saturating_sub(new_w)) / 2;
     let offset_y = (sensor_h.saturating_sub(new_h)) / 2;
 
     for y in 0..new_h {
         for x in 0..new_w {
-            let pixel = resized_rgb.get_pixel(x, y);
+            let pixel = img.get_pixel(x, y);
             if x + offset_x < sensor_w && y + offset_y < sensor_h {
                 output.put_pixel(x + offset_x, y + offset_y, *pixel);
             }
@@ -38,3 +286,13 @@ pub fn resize_to_sensor(img: &DynamicImage, sensor_w: u32, sensor_h: u32) -> Rgb
 pub fn save_image(img: &RgbImage, path: &std::path::Path) -> Result<(), String> {
     img.save(path).map_err(|e| format!("Failed to save image: {e}"))
 }
+
+/// Save an RGBA image. Formats without transparency (JPEG) are saved as RGB.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_image_rgba(img: &RgbaImage, path: &std::path::Path) -> Result<(), String> {
+    let result = match image::ImageFormat::from_path(path) {
+        Ok(image::ImageFormat::Jpeg) => DynamicImage::ImageRgba8(img.clone()).to_rgb8().save(path),
+        _ => img.save(path),
+    };
+    result.map_err(|e| format!("Failed to save image: {e}"))
+}
@@ -3,10 +3,15 @@
 //! Provides the CCD simulation pipeline and SPICE circuit modules
 //! for use by the main application and test binaries.
 
+pub mod bitmap_font;
+#[cfg(feature = "capi")]
+pub mod capi;
 pub mod ccd;
 pub mod color;
 pub mod glitch;
 pub mod image_io;
 pub mod pipeline;
+#[cfg(feature = "pyo3")]
+mod python;
 
 pub mod spice;
@@ -0,0 +1,187 @@
+//! In-app log console.
+//!
+//! The logger installed at startup keeps the most recent records in memory,
+//! besides passing them on to the platform logger (stderr natively, the
+//! browser console on the web), so SPICE fallbacks, convergence failures and
+//! timings can be read in the app itself.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use eframe::egui;
+
+/// Records kept before the oldest are dropped.
+const CAPACITY: usize = 2000;
+
+const TIME_DIM: egui::Color32 = egui::Color32::from_rgb(120, 120, 140);
+const ERROR: egui::Color32 = egui::Color32::from_rgb(230, 80, 60);
+const WARN: egui::Color32 = egui::Color32::from_rgb(230, 160, 60);
+const INFO: egui::Color32 = egui::Color32::from_rgb(200, 200, 210);
+const DEBUG: egui::Color32 = egui::Color32::from_rgb(110, 150, 200);
+
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+    /// Seconds since the logger was installed.
+    pub seconds: f64,
+}
+
+static RECORDS: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+
+struct CaptureLogger {
+    inner: Box<dyn log::Log>,
+    start: web_time::Instant,
+}
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        captured(metadata) || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.inner.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+        if !captured(record.metadata()) {
+            return;
+        }
+        if let Ok(mut records) = RECORDS.lock() {
+            if records.len() >= CAPACITY {
+                records.pop_front();
+            }
+            records.push_back(LogRecord {
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+                seconds: self.start.elapsed().as_secs_f64(),
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Info and above from everything, debug from this crate too; trace and
+/// other crates' debug output would drown the console.
+fn captured(metadata: &log::Metadata) -> bool {
+    let ours = metadata.target().starts_with(env!("CARGO_CRATE_NAME"));
+    metadata.level() <= if ours { log::Level::Debug } else { log::Level::Info }
+}
+
+/// Install the capturing logger in front of `inner`, which still receives
+/// every record it is enabled for up to `max_level`.
+pub fn init(inner: Box<dyn log::Log>, max_level: log::LevelFilter) {
+    let logger = CaptureLogger {
+        inner,
+        start: web_time::Instant::now(),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level.max(log::LevelFilter::Debug));
+    }
+}
+
+/// Captured warnings and errors, for flagging them in the toolbar.
+pub fn problem_count() -> usize {
+    RECORDS.lock().map_or(0, |records| {
+        records.iter().filter(|r| r.level <= log::Level::Warn).count()
+    })
+}
+
+pub struct LogConsole {
+    /// Least severe level shown.
+    pub level: log::LevelFilter,
+}
+
+impl Default for LogConsole {
+    fn default() -> Self {
+        Self {
+            level: log::LevelFilter::Info,
+        }
+    }
+}
+
+impl LogConsole {
+    /// Draw the severity filter and the scrolling record list, newest at the
+    /// bottom.
+    pub fn draw(&mut self, ui: &mut egui::Ui) {
+        let Ok(mut records) = RECORDS.lock() else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label("Show");
+            egui::ComboBox::from_id_salt("log_level")
+                .selected_text(level_name(self.level))
+                .show_ui(ui, |ui| {
+                    for level in [
+                        log::LevelFilter::Error,
+                        log::LevelFilter::Warn,
+                        log::LevelFilter::Info,
+                        log::LevelFilter::Debug,
+                    ] {
+                        ui.selectable_value(&mut self.level, level, level_name(level));
+                    }
+                });
+            let shown = records.iter().filter(|r| r.level <= self.level).count();
+            ui.label(
+                egui::RichText::new(format!("{shown} of {}", records.len()))
+                    .small()
+                    .color(TIME_DIM),
+            );
+            if ui.small_button("Clear").clicked() {
+                records.clear();
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .max_height(160.0)
+            .auto_shrink([false, true])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for record in records.iter().filter(|r| r.level <= self.level) {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(format!("{:8.2}s", record.seconds))
+                                .monospace()
+                                .small()
+                                .color(TIME_DIM),
+                        );
+                        ui.label(
+                            egui::RichText::new(format!("{:5}", record.level))
+                                .monospace()
+                                .small()
+                                .color(level_color(record.level)),
+                        );
+                        ui.label(
+                            egui::RichText::new(&record.message)
+                                .monospace()
+                                .small()
+                                .color(level_color(record.level)),
+                        )
+                        .on_hover_text(&record.target);
+                    });
+                }
+            });
+    }
+}
+
+fn level_name(level: log::LevelFilter) -> &'static str {
+    match level {
+        log::LevelFilter::Error => "Errors",
+        log::LevelFilter::Warn => "Warnings",
+        log::LevelFilter::Info => "Info",
+        _ => "Debug",
+    }
+}
+
+fn level_color(level: log::Level) -> egui::Color32 {
+    match level {
+        log::Level::Error => ERROR,
+        log::Level::Warn => WARN,
+        log::Level::Info => INFO,
+        _ => DEBUG,
+    }
+}
@@ -1,16 +1,31 @@
 mod app;
+mod bitmap_font;
 mod ccd;
 mod circuit_display;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
 mod color;
+mod curve_editor;
 mod glitch;
 mod image_io;
+mod log_console;
 mod pipeline;
+mod session;
+mod snapshot_gallery;
 mod spice;
+mod timing_display;
 mod waveform_display;
 
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result {
-    env_logger::init();
+    let logger = env_logger::Builder::from_default_env().build();
+    let max_level = logger.filter();
+    log_console::init(Box::new(logger), max_level);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
 
     let options = eframe::NativeOptions {
         viewport: eframe::egui::ViewportBuilder::default()
@@ -32,7 +47,10 @@ fn main() {
     use wasm_bindgen::JsCast;
 
     console_error_panic_hook::set_once();
-    eframe::WebLogger::init(log::LevelFilter::Debug).ok();
+    log_console::init(
+        Box::new(eframe::WebLogger::new(log::LevelFilter::Debug)),
+        log::LevelFilter::Debug,
+    );
 
     let web_options = eframe::WebOptions::default();
 
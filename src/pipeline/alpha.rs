@@ -0,0 +1,183 @@
+//! Alpha channel handling.
+//!
+//! The CCD pipeline only carries RGB charge; alpha is resized alongside it
+//! and re-attached to the output.
+
+use super::{PipelineParams, StageBypass};
+use super::determinism::Stream;
+use crate::ccd::{blooming, readout, transfer};
+use crate::ccd::transfer::{LineScan, ReadoutFrame};
+use crate::color::optics;
+use crate::image_io;
+
+/// What happens to the source image's alpha channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    /// Drop alpha; output is opaque RGB.
+    Discard,
+    /// Resize alpha to the sensor, move it with the image and attach it.
+    Passthrough,
+    /// Treat alpha as charge and run it through blooming and CTE trailing,
+    /// so transparency smears along with the image.
+    Glitched,
+}
+
+impl AlphaMode {
+    pub const ALL: &[AlphaMode] = &[AlphaMode::Discard, AlphaMode::Passthrough, AlphaMode::Glitched];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AlphaMode::Discard => "Discard",
+            AlphaMode::Passthrough => "Passthrough",
+            AlphaMode::Glitched => "Glitched",
+        }
+    }
+}
+
+/// Compute the output alpha plane for `source`, one byte per output pixel
+/// (per sensor pixel, widened by the anamorphic resample when enabled and
+/// scaled up with a drizzled stack).
+///
+/// Returns `None` when the source has no alpha or `params.alpha_mode` is
+/// `Discard`. Both other modes move alpha with the image's geometry: the
+/// optics (motion blur, lens distortion, defocus, scan skew), then line
+/// scan or TDI. The glitched mode adds only the deterministic charge stages
+/// (blooming, CTE trailing, parallel smear, ringing); random clock glitches
+/// would not line up with the color planes.
+pub fn process_alpha(source: &image::DynamicImage, params: &PipelineParams) -> Option<Vec<u8>> {
+    if params.alpha_mode == AlphaMode::Discard {
+        return None;
+    }
+    // The parameters the color planes render with, so seeded geometry such
+    // as the line-scan wobble lands in the same place
+    let aged = params.aged();
+    let readout = aged.at_readout_speed();
+    let seeded = readout.seeded();
+    let params = &*seeded;
+    let bypassed = |stage| params.bypass.contains(stage);
+
+    let (w, h) = (params.sensor_width as usize, params.sensor_height as usize);
+    let canvas_w = super::physical_width(params).unwrap_or(params.sensor_width);
+    let alpha = image_io::resize_alpha_to_sensor(
+        source,
+        canvas_w,
+        params.sensor_height,
+        params.fit_mode,
+        params.determinism.strict,
+    )?;
+    let alpha: Vec<[f64; 1]> = alpha.into_raw().into_iter().map(|a| [a as f64 / 255.0]).collect();
+    let alpha = image_io::resample_width(&alpha, canvas_w as usize, h, w);
+    let out_w = super::anamorphic_width(params).unwrap_or(w);
+    let (final_w, final_h) = super::output_size(params, out_w, h);
+    let to_bytes = |plane: &[[f64; 1]]| -> Vec<u8> {
+        let bytes: Vec<u8> = image_io::resample_width(plane, w, h, out_w)
+            .iter()
+            .map(|&[a]| (a.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .collect();
+        if (final_w, final_h) == (out_w, h) {
+            return bytes;
+        }
+        // Drizzled output is larger than a readout
+        let plane = image::GrayImage::from_raw(out_w as u32, h as u32, bytes)
+            .expect("alpha plane matches its size");
+        let filter = image::imageops::FilterType::Triangle;
+        image::imageops::resize(&plane, final_w as u32, final_h as u32, filter).into_raw()
+    };
+
+    // The optics work on RGB; alpha rides along as gray
+    let mut rgb: Vec<[f64; 3]> = alpha.iter().map(|&[a]| [a; 3]).collect();
+    if !bypassed(StageBypass::OPTICS) {
+        let wrap = params.tile_seamless;
+        optics::apply_motion_blur(&mut rgb, w, h, &params.motion_blur, wrap);
+        optics::apply_lens_distortion(&mut rgb, w, h, params.lens_distortion);
+        optics::apply_defocus(&mut rgb, w, h, params.defocus_radius, wrap);
+        super::apply_scan_skew(&mut rgb, w, h, params);
+    }
+
+    let full_well = params.full_well;
+    let mut grid: Vec<f64> = rgb.iter().map(|&[a, _, _]| a * full_well).collect();
+
+    let frame = ReadoutFrame::new(
+        params.sensor_width as usize,
+        params.sensor_height as usize,
+        params.sensor_orientation,
+        params.vertical_direction,
+    );
+    frame.enter(&mut grid);
+    let (width, height) = frame.dims();
+
+    let line_scan = params.line_scan.enabled && !bypassed(StageBypass::V_CLOCK);
+    if line_scan {
+        // The same path down the scene, without the per-line exposure and
+        // noise, which are the color planes' own
+        let scan = LineScan {
+            exposure_jitter: 0.0,
+            line_noise: 0.0,
+            ..params.line_scan
+        };
+        let mut rng = params.determinism.rng(Stream::LineScan);
+        transfer::line_scan_readout(&mut grid, width, height, &scan, &mut rng);
+    } else if params.tdi.enabled && !bypassed(StageBypass::V_CLOCK) {
+        transfer::tdi_integrate(&mut grid, width, height, &params.tdi);
+    }
+
+    if params.alpha_mode == AlphaMode::Glitched {
+        if !bypassed(StageBypass::BLOOMING) {
+            blooming::apply_blooming(
+                &mut grid,
+                width,
+                height,
+                &params.bloom(),
+                params.tile_seamless,
+            );
+        }
+        // A line-scan sensor has no parallel register to trail through
+        if !line_scan && !bypassed(StageBypass::V_CLOCK) {
+            transfer::vertical_transfer(
+                &mut grid,
+                width,
+                height,
+                params.v_cte,
+                0.0,
+                0.0,
+                &params.v_modulation,
+                params.parallel_smear,
+                params.tile_seamless,
+                &transfer::GlitchSchedule::default(),
+                params.v_phases,
+            );
+        }
+        if !bypassed(StageBypass::H_CLOCK) {
+            let mut serial = transfer::SerialTransfer::new(
+                params.h_cte,
+                0.0,
+                params.h_ringing,
+                params.tile_seamless,
+                params.determinism.rng(Stream::HClock),
+            );
+            let direction = params.readout_direction;
+            readout::stream(&mut grid, width, height, direction, &mut [&mut serial]);
+        }
+    }
+    frame.exit(&mut grid);
+
+    let plane: Vec<[f64; 1]> = grid.iter().map(|&e| [e / full_well]).collect();
+    Some(to_bytes(&plane))
+}
+
+/// Interleave packed RGB bytes with an alpha plane into RGBA bytes, or Err
+/// when the plane is not one byte per pixel.
+pub fn merge_alpha(rgb: &[u8], alpha: &[u8]) -> Result<Vec<u8>, String> {
+    if rgb.len() != alpha.len() * 3 {
+        return Err(format!(
+            "Alpha plane has {} pixels, the image {}",
+            alpha.len(),
+            rgb.len() / 3
+        ));
+    }
+    Ok(rgb
+        .chunks_exact(3)
+        .zip(alpha)
+        .flat_map(|(px, &a)| [px[0], px[1], px[2], a])
+        .collect())
+}
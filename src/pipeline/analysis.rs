@@ -0,0 +1,602 @@
+//! Sensor characterization from flat-field frames: per-stage noise, row and
+//! column banding spectra, and a photon transfer estimate of gain, read
+//! noise and full well.
+
+use image::{DynamicImage, Rgb, RgbImage, Rgb32FImage};
+
+use super::{PipelineParams, StageBuffer, StageData, StageUnits};
+use crate::bitmap_font;
+use crate::ccd::adc::CdsMode;
+use crate::color::optics::MotionBlur;
+use crate::image_io::FitMode;
+
+/// Mean and standard deviation of one stage's output on a flat frame.
+#[derive(Debug, Clone)]
+pub struct StageNoise {
+    pub stage: &'static str,
+    pub units: StageUnits,
+    pub mean: f64,
+    pub sigma: f64,
+}
+
+/// Gain, read noise and full well measured from flat and bias frame pairs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PtcEstimate {
+    pub gain_e_per_adu: f64,
+    pub read_noise_e: f64,
+    pub full_well_e: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NoiseReport {
+    /// Flat exposure as a fraction of full well.
+    pub level: f64,
+    pub stages: Vec<StageNoise>,
+    /// Power spectrum of the per-row means of the digitized flat (ADU^2),
+    /// indexed by frequency in cycles per frame; DC is omitted.
+    pub row_spectrum: Vec<f64>,
+    /// Same for the per-column means.
+    pub column_spectrum: Vec<f64>,
+    pub ptc: PtcEstimate,
+}
+
+/// A uniform gray HDR source at `level` x full well (values above 1.0 overexpose).
+pub fn flat_field(width: u32, height: u32, level: f64) -> DynamicImage {
+    let value = level.max(0.0) as f32;
+    DynamicImage::ImageRgb32F(Rgb32FImage::from_pixel(width, height, Rgb([value; 3])))
+}
+
+/// Parameters for characterization runs: the frame is filled edge to edge and
+/// lens distortion, motion blur and scan skew (which only move geometry,
+/// leaving black edges) are off.
+pub(crate) fn analysis_params(params: &PipelineParams) -> PipelineParams {
+    PipelineParams {
+        fit_mode: FitMode::Stretch,
+        area_sum_downscale: false,
+        lens_distortion: 0.0,
+        motion_blur: MotionBlur::default(),
+        scan_skew: 0.0,
+        scan_scale_error: 0.0,
+        scan_wobble: 0.0,
+        ..params.clone()
+    }
+}
+
+/// Process a flat at `level`, returning per-stage statistics and the digitized
+/// mosaic (the first ADU stage, in readout frame orientation).
+pub fn capture_flat(
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    level: f64,
+) -> (Vec<StageNoise>, Vec<f64>, usize, usize) {
+    let source = flat_field(params.sensor_width, params.sensor_height, level);
+    let mut stages = Vec::new();
+    let mut digitized: Option<(Vec<f64>, usize, usize)> = None;
+
+    let mut tap = |stage: &'static str, data: StageData<'_>| {
+        let (mean, sigma) = match data.buffer {
+            StageBuffer::Mosaic(values) => mean_sigma(values.iter().copied()),
+            StageBuffer::Rgb(pixels) => {
+                mean_sigma(pixels.iter().map(|p| (p[0] + p[1] + p[2]) / 3.0))
+            }
+        };
+        stages.push(StageNoise { stage, units: data.units, mean, sigma });
+
+        if let (None, StageBuffer::Mosaic(values), StageUnits::Adu) =
+            (&digitized, &data.buffer, data.units)
+        {
+            digitized = Some((values.to_vec(), data.width, data.height));
+        }
+    };
+    super::process_tapped(&source, params, spice_cache, Some(&mut tap));
+
+    let (frame, width, height) = digitized.unwrap_or_default();
+    (stages, frame, width, height)
+}
+
+/// Characterize the current chain with a flat exposure at `level` x full well.
+pub fn noise_report(
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    level: f64,
+) -> NoiseReport {
+    let params = analysis_params(params);
+    let (stages, frame, width, height) = capture_flat(&params, spice_cache, level);
+
+    let (rows, columns) = row_column_means(&frame, width, height);
+    NoiseReport {
+        level,
+        stages,
+        row_spectrum: power_spectrum(&rows),
+        column_spectrum: power_spectrum(&columns),
+        ptc: estimate_ptc(&params, spice_cache, level),
+    }
+}
+
+/// Parameters for photon transfer frames: shot noise is forced on since the
+/// method relies on it.
+pub(crate) fn ptc_params(params: &PipelineParams) -> PipelineParams {
+    PipelineParams {
+        shot_noise_enabled: true,
+        ..analysis_params(params)
+    }
+}
+
+/// Mean and temporal variance (ADU) of a flat pair at `level`. Differencing
+/// the pair cancels fixed pattern so only temporal noise remains; the second
+/// frame advances the noise seed so seeded sources still differ.
+pub(crate) fn flat_pair(
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    level: f64,
+) -> (f64, f64) {
+    let mut second = params.clone();
+    second.noise.seed = params.noise.seed.wrapping_add(1);
+    let a = capture_flat(params, spice_cache, level).1;
+    let b = capture_flat(&second, spice_cache, level).1;
+    (mean_sigma(a.iter().copied()).0, pair_variance(&a, &b))
+}
+
+/// Two-point photon transfer estimate from flat pairs at a low level and at
+/// `level`. Gain is the slope of mean over variance, the offset comes from
+/// extrapolating the means to zero exposure, and read noise is what is left
+/// of the low-level variance after shot noise. (A zero-exposure bias pair
+/// would under-read, since charge never goes below zero.)
+fn estimate_ptc(
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    level: f64,
+) -> PtcEstimate {
+    let params = ptc_params(params);
+    let floor_e = 10.0 * (params.read_noise + params.reset_noise + 1.0);
+    let low = (floor_e / params.full_well).max(0.01).min(level / 2.0);
+    if low <= 0.0 {
+        return PtcEstimate::default();
+    }
+
+    let (mean_low, var_low) = flat_pair(&params, spice_cache, low);
+    let (mean_high, var_high) = flat_pair(&params, spice_cache, level);
+    let saturated = capture_flat(&params, spice_cache, 4.0).1;
+
+    let (d_mean, d_var) = (mean_high - mean_low, var_high - var_low);
+    if d_mean <= 0.0 || d_var <= 0.0 {
+        return PtcEstimate::default();
+    }
+    let gain = d_mean / d_var;
+    let offset = mean_low - d_mean / (level - low) * low;
+    let read_var = (var_low - (mean_low - offset) / gain).max(0.0);
+    PtcEstimate {
+        gain_e_per_adu: gain,
+        read_noise_e: read_var.sqrt() * gain,
+        full_well_e: (mean_sigma(saturated.iter().copied()).0 - offset) * gain,
+    }
+}
+
+pub(crate) fn mean_sigma(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (mut n, mut sum, mut sum_sq) = (0usize, 0.0, 0.0);
+    for v in values {
+        n += 1;
+        sum += v;
+        sum_sq += v * v;
+    }
+    if n == 0 {
+        return (0.0, 0.0);
+    }
+    let mean = sum / n as f64;
+    (mean, (sum_sq / n as f64 - mean * mean).max(0.0).sqrt())
+}
+
+/// Per-frame temporal variance from a pair: var(a - b) / 2.
+pub(crate) fn pair_variance(a: &[f64], b: &[f64]) -> f64 {
+    let sigma = mean_sigma(a.iter().zip(b).map(|(x, y)| x - y)).1;
+    sigma * sigma / 2.0
+}
+
+fn row_column_means(frame: &[f64], width: usize, height: usize) -> (Vec<f64>, Vec<f64>) {
+    if width == 0 || height == 0 {
+        return (Vec::new(), Vec::new());
+    }
+    let mut rows = vec![0.0; height];
+    let mut columns = vec![0.0; width];
+    for (y, row) in frame.chunks_exact(width).enumerate() {
+        for (x, &v) in row.iter().enumerate() {
+            rows[y] += v;
+            columns[x] += v;
+        }
+    }
+    rows.iter_mut().for_each(|v| *v /= width as f64);
+    columns.iter_mut().for_each(|v| *v /= height as f64);
+    (rows, columns)
+}
+
+/// One-sided power spectrum of `signal` (mean removed), bins 1..=n/2.
+fn power_spectrum(signal: &[f64]) -> Vec<f64> {
+    let n = signal.len();
+    if n < 2 {
+        return Vec::new();
+    }
+    let mean = signal.iter().sum::<f64>() / n as f64;
+    (1..=n / 2)
+        .map(|k| {
+            let (mut re, mut im) = (0.0, 0.0);
+            for (i, &v) in signal.iter().enumerate() {
+                let phase = -2.0 * std::f64::consts::PI * (k * i % n) as f64 / n as f64;
+                re += (v - mean) * phase.cos();
+                im += (v - mean) * phase.sin();
+            }
+            (re * re + im * im) / (n * n) as f64
+        })
+        .collect()
+}
+
+impl NoiseReport {
+    /// CSV with three sections: stage noise, photon transfer, and spectra.
+    pub fn to_csv(&self) -> String {
+        let mut out = format!("# flat at {:.3} x full well\n", self.level);
+        out.push_str("stage,units,mean,sigma\n");
+        for s in &self.stages {
+            out.push_str(&format!("{},{},{:.6},{:.6}\n", s.stage, s.units.name(), s.mean, s.sigma));
+        }
+
+        out.push_str("\ngain_e_per_adu,read_noise_e,full_well_e\n");
+        out.push_str(&format!(
+            "{:.6},{:.6},{:.3}\n",
+            self.ptc.gain_e_per_adu, self.ptc.read_noise_e, self.ptc.full_well_e
+        ));
+
+        out.push_str("\ncycles_per_frame,row_power_adu2,column_power_adu2\n");
+        let bins = self.row_spectrum.len().max(self.column_spectrum.len());
+        for k in 0..bins {
+            let cell = |spectrum: &[f64]| spectrum.get(k).map(|v| format!("{v:.6e}")).unwrap_or_default();
+            out.push_str(&format!(
+                "{},{},{}\n",
+                k + 1,
+                cell(&self.row_spectrum),
+                cell(&self.column_spectrum)
+            ));
+        }
+        out
+    }
+
+    /// Plot both banding spectra (log power) above the PTC numbers.
+    pub fn plot(&self) -> RgbImage {
+        const W: u32 = 640;
+        const PLOT_H: u32 = 240;
+        const MARGIN: i64 = 12;
+        let background = Rgb([12u8, 14, 22]);
+        let text = Rgb([220u8, 220, 230]);
+        let row_color = Rgb([255u8, 180, 40]);
+        let column_color = Rgb([0u8, 190, 255]);
+
+        let mut img = RgbImage::from_pixel(W, PLOT_H + 60, background);
+        let top = 24i64;
+        let bottom = top + PLOT_H as i64 - 24;
+        let (left, right) = (MARGIN, W as i64 - MARGIN);
+
+        let log = |v: f64| v.max(1e-12).log10();
+        let all = self.row_spectrum.iter().chain(&self.column_spectrum).map(|&v| log(v));
+        let (lo, hi) = all.fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        let span = (hi - lo).max(1e-6);
+
+        for (spectrum, color) in [(&self.row_spectrum, row_color), (&self.column_spectrum, column_color)] {
+            if spectrum.len() < 2 {
+                continue;
+            }
+            let points: Vec<(i64, i64)> = spectrum
+                .iter()
+                .enumerate()
+                .map(|(i, &v)| {
+                    let x = left + (i as f64 / (spectrum.len() - 1) as f64 * (right - left) as f64) as i64;
+                    let y = bottom - ((log(v) - lo) / span * (bottom - top) as f64) as i64;
+                    (x, y)
+                })
+                .collect();
+            for pair in points.windows(2) {
+                draw_line(&mut img, pair[0], pair[1], color);
+            }
+        }
+
+        bitmap_font::draw_text(&mut img, "ROW SPECTRUM", MARGIN, 6, 1, row_color);
+        bitmap_font::draw_text(&mut img, "COLUMN SPECTRUM", MARGIN + 90, 6, 1, column_color);
+        bitmap_font::draw_text(
+            &mut img,
+            &format!("LOG10 POWER {:.1} TO {:.1} (ADU SQUARED)", lo, hi),
+            MARGIN + 200,
+            6,
+            1,
+            text,
+        );
+        bitmap_font::draw_text(
+            &mut img,
+            &format!(
+                "GAIN {:.3} E-/ADU   READ NOISE {:.2} E-   FULL WELL {:.0} E-",
+                self.ptc.gain_e_per_adu, self.ptc.read_noise_e, self.ptc.full_well_e
+            ),
+            MARGIN,
+            PLOT_H as i64 + 10,
+            1,
+            text,
+        );
+        bitmap_font::draw_text(
+            &mut img,
+            &format!("FLAT AT {:.0}% FULL WELL", self.level * 100.0),
+            MARGIN,
+            PLOT_H as i64 + 30,
+            1,
+            text,
+        );
+        img
+    }
+}
+
+/// One flat pair of a photon transfer sweep.
+#[derive(Debug, Clone, Copy)]
+pub struct PtcPoint {
+    /// Exposure as a fraction of full well.
+    pub level: f64,
+    /// Mean minus the zero-exposure offset (ADU).
+    pub signal_adu: f64,
+    /// Temporal variance from the flat pair (ADU^2).
+    pub variance_adu2: f64,
+}
+
+/// Gain, read noise and full well either fitted from a sweep or derived
+/// from the configured parameters.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PtcSpecs {
+    pub gain_e_per_adu: f64,
+    pub read_noise_e: f64,
+    pub full_well_e: f64,
+}
+
+/// Photon transfer curve over the sensor's dynamic range.
+#[derive(Debug, Clone)]
+pub struct PtcSweep {
+    pub points: Vec<PtcPoint>,
+    /// Zero-exposure offset (ADU) extrapolated from the linear range.
+    pub offset_adu: f64,
+    pub measured: PtcSpecs,
+    /// What the configured parameters predict for the math pipeline.
+    pub expected: PtcSpecs,
+}
+
+/// Characterization targets implied by the parameters: conversion gain from
+/// the amplifier and ADC gains, input-referred noise floor from every noise
+/// source (including dark shot noise and quantization), and the smaller of
+/// the blooming threshold and the ADC range.
+pub fn expected_specs(params: &PipelineParams) -> PtcSpecs {
+    let aged = params.aged();
+    let params = &*aged.at_readout_speed();
+    let amp_gain = params.amp_gain.max(1e-6);
+    let gain = params.adc_gain.max(0.001) / amp_gain;
+    let cds_leak = match params.cds_mode {
+        CdsMode::On => 0.0,
+        CdsMode::Off => 1.0,
+        CdsMode::Partial => 0.3,
+    };
+    let output_var = params.reset_noise.powi(2) * (1.0 + cds_leak * cds_leak);
+    let read_var = params.read_noise.powi(2)
+        + output_var / (amp_gain * amp_gain)
+        + params.dark_current_rate
+        + gain * gain / 12.0;
+    let max_code = ((1u64 << params.bit_depth) - 1) as f64;
+    PtcSpecs {
+        gain_e_per_adu: gain,
+        read_noise_e: read_var.sqrt(),
+        full_well_e: (params.full_well * params.bloom_threshold.clamp(0.0, 1.0))
+            .min((max_code - params.bias).max(0.0) * gain),
+    }
+}
+
+/// Sweep `steps` log-spaced flat levels from 0.2% to 150% of full well and fit
+/// the shot-noise-limited part (before the variance peak) with
+/// variance = signal / gain + read_variance. Full well is the saturation
+/// plateau of the signal.
+pub fn ptc_sweep(
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    steps: usize,
+) -> PtcSweep {
+    let params = ptc_params(params);
+    let steps = steps.max(4);
+    let (lo, hi) = (0.002f64, 1.5f64);
+    let levels: Vec<f64> = (0..steps)
+        .map(|i| lo * (hi / lo).powf(i as f64 / (steps - 1) as f64))
+        .collect();
+    let raw: Vec<(f64, f64)> = levels
+        .iter()
+        .map(|&level| flat_pair(&params, spice_cache, level))
+        .collect();
+
+    // Past the variance peak the pixels are clipping; fit only below it
+    let peak = raw
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.1.total_cmp(&b.1.1))
+        .map_or(0, |(i, _)| i);
+    let linear = if peak >= 3 { peak } else { raw.len() };
+
+    let (slope, offset) = line_fit(
+        levels[..linear]
+            .iter()
+            .zip(&raw[..linear])
+            .map(|(&l, &(m, _))| (l, m, 1.0)),
+    );
+    let points: Vec<PtcPoint> = levels
+        .iter()
+        .zip(&raw)
+        .map(|(&level, &(mean, variance))| PtcPoint {
+            level,
+            signal_adu: mean - offset,
+            variance_adu2: variance,
+        })
+        .collect();
+
+    // A variance estimate's error scales with the variance itself, so weight
+    // by 1/var^2 to keep the bright points from swamping the read floor
+    let (inv_gain, read_var) = line_fit(
+        points[..linear]
+            .iter()
+            .map(|p| (p.signal_adu, p.variance_adu2, 1.0 / p.variance_adu2.max(1e-12).powi(2))),
+    );
+    let measured = if inv_gain > 0.0 && slope > 0.0 {
+        let gain = 1.0 / inv_gain;
+        PtcSpecs {
+            gain_e_per_adu: gain,
+            read_noise_e: read_var.max(0.0).sqrt() * gain,
+            full_well_e: points.iter().map(|p| p.signal_adu).fold(0.0, f64::max) * gain,
+        }
+    } else {
+        PtcSpecs::default()
+    };
+
+    PtcSweep {
+        points,
+        offset_adu: offset,
+        measured,
+        expected: expected_specs(&params),
+    }
+}
+
+/// Weighted least-squares line through (x, y, weight) samples, returned as
+/// (slope, intercept).
+fn line_fit(samples: impl Iterator<Item = (f64, f64, f64)>) -> (f64, f64) {
+    let (mut count, mut n, mut sx, mut sy, mut sxx, mut sxy) = (0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    for (x, y, w) in samples {
+        count += 1;
+        n += w;
+        sx += w * x;
+        sy += w * y;
+        sxx += w * x * x;
+        sxy += w * x * y;
+    }
+    let denom = n * sxx - sx * sx;
+    if count < 2 || denom.abs() <= f64::EPSILON * n * sxx {
+        return (0.0, if n > 0.0 { sy / n } else { 0.0 });
+    }
+    let slope = (n * sxy - sx * sy) / denom;
+    (slope, (sy - slope * sx) / n)
+}
+
+impl PtcSweep {
+    /// CSV of the sweep points followed by measured vs. expected specs.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("level,signal_adu,variance_adu2,noise_adu\n");
+        for p in &self.points {
+            out.push_str(&format!(
+                "{:.6},{:.6},{:.6},{:.6}\n",
+                p.level,
+                p.signal_adu,
+                p.variance_adu2,
+                p.variance_adu2.max(0.0).sqrt()
+            ));
+        }
+        out.push_str("\nspec,measured,expected\n");
+        for (name, m, e) in self.spec_rows() {
+            out.push_str(&format!("{name},{m:.6},{e:.6}\n"));
+        }
+        out.push_str(&format!("offset_adu,{:.6},\n", self.offset_adu));
+        out
+    }
+
+    /// (name, measured, expected) for each fitted quantity.
+    pub fn spec_rows(&self) -> [(&'static str, f64, f64); 3] {
+        [
+            ("gain_e_per_adu", self.measured.gain_e_per_adu, self.expected.gain_e_per_adu),
+            ("read_noise_e", self.measured.read_noise_e, self.expected.read_noise_e),
+            ("full_well_e", self.measured.full_well_e, self.expected.full_well_e),
+        ]
+    }
+
+    /// Classic log-log PTC: noise vs. signal (ADU), with the fitted read
+    /// noise floor and shot noise line overlaid.
+    pub fn plot(&self) -> RgbImage {
+        const W: u32 = 640;
+        const H: u32 = 420;
+        const MARGIN: i64 = 16;
+        let background = Rgb([12u8, 14, 22]);
+        let text = Rgb([220u8, 220, 230]);
+        let point_color = Rgb([0u8, 220, 110]);
+        let floor_color = Rgb([255u8, 180, 40]);
+        let shot_color = Rgb([0u8, 190, 255]);
+
+        let mut img = RgbImage::from_pixel(W, H, background);
+        let (left, right) = (MARGIN, W as i64 - MARGIN);
+        let (top, bottom) = (28i64, H as i64 - 70);
+
+        let samples: Vec<(f64, f64)> = self
+            .points
+            .iter()
+            .filter(|p| p.signal_adu > 0.0 && p.variance_adu2 > 0.0)
+            .map(|p| (p.signal_adu.log10(), p.variance_adu2.sqrt().log10()))
+            .collect();
+        if samples.is_empty() {
+            bitmap_font::draw_text(&mut img, "NO USABLE PTC POINTS", MARGIN, 8, 1, text);
+            return img;
+        }
+        let range = |axis: fn(&(f64, f64)) -> f64| {
+            samples
+                .iter()
+                .map(axis)
+                .fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)))
+        };
+        let (x_lo, x_hi) = range(|s| s.0);
+        let (y_lo, y_hi) = range(|s| s.1);
+        let (x_lo, x_hi) = (x_lo - 0.1, x_hi + 0.1);
+        let (y_lo, y_hi) = (y_lo - 0.2, y_hi + 0.2);
+        let to_px = |(x, y): (f64, f64)| {
+            (
+                left + ((x - x_lo) / (x_hi - x_lo) * (right - left) as f64) as i64,
+                bottom - ((y - y_lo) / (y_hi - y_lo) * (bottom - top) as f64) as i64,
+            )
+        };
+
+        // Fitted model: read floor (flat) and shot noise (slope 1/2)
+        let m = &self.measured;
+        if m.gain_e_per_adu > 0.0 {
+            let floor = (m.read_noise_e / m.gain_e_per_adu).max(1e-9).log10();
+            draw_line(&mut img, to_px((x_lo, floor)), to_px((x_hi, floor)), floor_color);
+            let shot = |x: f64| 0.5 * (x - m.gain_e_per_adu.log10());
+            draw_line(&mut img, to_px((x_lo, shot(x_lo))), to_px((x_hi, shot(x_hi))), shot_color);
+        }
+        for &s in &samples {
+            let (px, py) = to_px(s);
+            for d in -2..=2 {
+                draw_line(&mut img, (px + d, py - 2), (px + d, py + 2), point_color);
+            }
+        }
+
+        let title = "PHOTON TRANSFER (LOG NOISE VS LOG SIGNAL, ADU)";
+        bitmap_font::draw_text(&mut img, title, MARGIN, 8, 1, text);
+        let rows = self.spec_rows();
+        let labels = ["GAIN E-/ADU", "READ NOISE E-", "FULL WELL E-"];
+        let header = "MEASURED    EXPECTED";
+        bitmap_font::draw_text(&mut img, header, MARGIN + 110, bottom + 14, 1, text);
+        for (i, ((_, measured, expected), label)) in rows.iter().zip(labels).enumerate() {
+            let y = bottom + 26 + i as i64 * 12;
+            bitmap_font::draw_text(&mut img, label, MARGIN, y, 1, text);
+            bitmap_font::draw_text(
+                &mut img,
+                &format!("{:<11.3} {:.3}", measured, expected),
+                MARGIN + 110,
+                y,
+                1,
+                text,
+            );
+        }
+        bitmap_font::draw_text(&mut img, "READ FLOOR", right - 130, bottom + 26, 1, floor_color);
+        bitmap_font::draw_text(&mut img, "SHOT NOISE", right - 130, bottom + 38, 1, shot_color);
+        img
+    }
+}
+
+pub(crate) fn draw_line(img: &mut RgbImage, (x0, y0): (i64, i64), (x1, y1): (i64, i64), color: Rgb<u8>) {
+    let steps = (x1 - x0).abs().max((y1 - y0).abs()).max(1);
+    for i in 0..=steps {
+        let x = x0 + (x1 - x0) * i / steps;
+        let y = y0 + (y1 - y0) * i / steps;
+        if x >= 0 && y >= 0 && (x as u32) < img.width() && (y as u32) < img.height() {
+            img.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Noise prints: the artifacts of one render, captured on their own and
+//! laid over others.
+//!
+//! A print is the render minus its clean reference (see `reference`), a
+//! signed field holding only what the noise and glitch stages changed. It
+//! saves as a 16-bit PNG with mid-gray for no change, so prints can be
+//! painted over or swapped between projects, and adds back onto any render
+//! after compositing at an adjustable strength.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use image::{DynamicImage, ImageBuffer, Rgb};
+
+use super::PipelineParams;
+
+/// The captured artifact field, per pixel and channel in [-1, 1].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtifactLayer {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<[f32; 3]>,
+}
+
+impl ArtifactLayer {
+    /// The artifacts in `render`, the RGB8 output of `params` on `source`:
+    /// its difference from the clean reference.
+    pub fn capture(
+        render: &[u8],
+        width: usize,
+        height: usize,
+        source: &DynamicImage,
+        params: &PipelineParams,
+    ) -> Result<Self, String> {
+        let (rw, rh, clean) = super::process_reference(source, params);
+        if (rw, rh) != (width, height) || render.len() != width * height * 3 {
+            return Err(format!("Render is {width}x{height} but its reference is {rw}x{rh}"));
+        }
+        Ok(Self::difference(render, &clean, width, height))
+    }
+
+    /// `render` minus `clean`, both RGB8 of the same size.
+    pub fn difference(render: &[u8], clean: &[u8], width: usize, height: usize) -> Self {
+        let data = render
+            .chunks_exact(3)
+            .zip(clean.chunks_exact(3))
+            .map(|(r, c)| std::array::from_fn(|i| (r[i] as f32 - c[i] as f32) / 255.0))
+            .collect();
+        Self { width, height, data }
+    }
+
+    /// The print as a 16-bit image, mid-gray where nothing changed.
+    pub fn to_image(&self) -> ImageBuffer<Rgb<u16>, Vec<u16>> {
+        let encode = |v: f32| ((v.clamp(-1.0, 1.0) * 0.5 + 0.5) * 65535.0).round() as u16;
+        ImageBuffer::from_fn(self.width as u32, self.height as u32, |x, y| {
+            Rgb(self.data[y as usize * self.width + x as usize].map(encode))
+        })
+    }
+
+    /// A print from an image saved by `to_image`; 8-bit images work too,
+    /// with coarser steps.
+    pub fn from_image(img: &DynamicImage) -> Self {
+        let img = img.to_rgb16();
+        let decode = |v: u16| (v as f32 / 65535.0 - 0.5) * 2.0;
+        Self {
+            width: img.width() as usize,
+            height: img.height() as usize,
+            data: img.pixels().map(|p| p.0.map(decode)).collect(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        self.to_image()
+            .save(path)
+            .map_err(|e| format!("Failed to save noise print: {e}"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let img = image::open(path).map_err(|e| format!("Failed to load noise print: {e}"))?;
+        Ok(Self::from_image(&img))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtifactOverlay {
+    /// The loaded print.
+    pub layer: Option<Arc<ArtifactLayer>>,
+    /// Multiplier on the print; 1 restores the artifacts as captured.
+    pub strength: f64,
+}
+
+impl Default for ArtifactOverlay {
+    fn default() -> Self {
+        Self {
+            layer: None,
+            strength: 1.0,
+        }
+    }
+}
+
+impl ArtifactOverlay {
+    /// Whether the overlay leaves the output as it is.
+    pub fn is_off(&self) -> bool {
+        self.layer.is_none() || self.strength == 0.0
+    }
+}
+
+/// Add the loaded print to the `width` x `height` RGB8 output `bytes`,
+/// stretched to fit when it was captured at another size. Nearest-neighbor
+/// sampling keeps the grain sharp.
+pub fn apply_artifact_layer(
+    bytes: &mut [u8],
+    width: usize,
+    height: usize,
+    overlay: &ArtifactOverlay,
+) {
+    let Some(layer) = overlay.layer.as_deref().filter(|_| !overlay.is_off()) else {
+        return;
+    };
+    let empty = layer.width == 0 || layer.height == 0 || width == 0 || height == 0;
+    if empty || layer.data.len() != layer.width * layer.height {
+        return;
+    }
+    let strength = overlay.strength as f32 * 255.0;
+    for (y, row) in bytes.chunks_exact_mut(width * 3).take(height).enumerate() {
+        let ly = y * layer.height / height;
+        for (x, pixel) in row.chunks_exact_mut(3).enumerate() {
+            let lx = x * layer.width / width;
+            let delta = layer.data[ly * layer.width + lx];
+            for (v, d) in pixel.iter_mut().zip(delta) {
+                *v = (*v as f32 + d * strength).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+}
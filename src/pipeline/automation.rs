@@ -0,0 +1,93 @@
+//! Parameter automation: control moves recorded with timestamps while the
+//! user performs them, replayed over a rendered frame sequence.
+//!
+//! Recording diffs the parameters against the last capture, so one event is
+//! stored per changed value, named as in `PipelineParams::values`. Replay
+//! holds each value until the next event for it. Edits with no settable
+//! value (tone curves, the output LUT, loaded waveforms) are not recorded.
+
+use image::DynamicImage;
+
+use super::{ParamValue, PipelineParams, TemporalState};
+use crate::spice::{SpiceCache, SpiceMode};
+
+/// One recorded change: `name` took `value` at `time` seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutomationEvent {
+    pub time: f64,
+    pub name: &'static str,
+    pub value: ParamValue,
+}
+
+/// A recorded performance, replayable from the parameters it started with.
+#[derive(Debug, Clone)]
+pub struct Automation {
+    /// Parameters when recording started.
+    base: PipelineParams,
+    pub events: Vec<AutomationEvent>,
+    /// Where replay stands after the last event.
+    current: PipelineParams,
+}
+
+impl Automation {
+    /// Start a recording from `params`.
+    pub fn start(params: &PipelineParams) -> Self {
+        Self {
+            base: params.clone(),
+            events: Vec::new(),
+            current: params.clone(),
+        }
+    }
+
+    /// Record every value in `params` that changed since the last capture,
+    /// `time` seconds into the recording.
+    pub fn capture(&mut self, params: &PipelineParams, time: f64) {
+        for change in params.diff(&self.current) {
+            if self.current.set_value(change.name, &change.to.to_string()).is_ok() {
+                self.events.push(AutomationEvent {
+                    time,
+                    name: change.name,
+                    value: change.to,
+                });
+            }
+        }
+    }
+
+    /// Time of the last event, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.events.last().map_or(0.0, |e| e.time)
+    }
+
+    /// Frames needed to replay the whole recording at `fps`, holding the
+    /// final state for one frame.
+    pub fn frame_count(&self, fps: f64) -> usize {
+        (self.duration() * fps.max(1e-3)).floor() as usize + 1
+    }
+
+    /// Render the recording at `fps` as a sequence through `process_temporal`,
+    /// handing each frame's (index, width, height, RGB8) to `frame`. Stops at
+    /// the first error `frame` returns.
+    pub fn render(
+        &self,
+        source: &DynamicImage,
+        fps: f64,
+        spice_cache: &mut Option<SpiceCache>,
+        mut frame: impl FnMut(usize, usize, usize, Vec<u8>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let mut params = self.base.clone();
+        let mut state = TemporalState::default();
+        let mut pending = self.events.iter().peekable();
+        for index in 0..self.frame_count(fps) {
+            let time = index as f64 / fps.max(1e-3);
+            while let Some(event) = pending.next_if(|e| e.time <= time) {
+                params.set_value(event.name, &event.value.to_string())?;
+            }
+            if params.spice.mode != SpiceMode::Off {
+                crate::spice::simulate_or_cache(&params.spice, &params.spice_sensor(), spice_cache);
+            }
+            let (w, h, bytes) = super::process_temporal(source, &params, spice_cache, &mut state);
+            frame(index, w, h, bytes)?;
+        }
+        Ok(())
+    }
+}
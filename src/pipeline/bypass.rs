@@ -0,0 +1,57 @@
+//! Per-stage bypass: a stage group that is switched off keeps its settings
+//! but is skipped at runtime, so its contribution can be isolated without
+//! zeroing its controls.
+//!
+//! The ADC cannot drop out entirely, since it turns charge into codes;
+//! bypassed, it becomes an ideal quantizer with no noise, DNL, bit errors,
+//! dual-gain, converter faults or output encoding.
+
+/// Set of bypassed stage groups, one bit per group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StageBypass(u32);
+
+impl StageBypass {
+    pub const OPTICS: StageBypass = StageBypass(1 << 0);
+    /// HDR merge, row exposure, flicker and the noise sources.
+    pub const EXPOSURE_NOISE: StageBypass = StageBypass(1 << 1);
+    pub const BLOOMING: StageBypass = StageBypass(1 << 2);
+    pub const V_CLOCK: StageBypass = StageBypass(1 << 3);
+    pub const H_CLOCK: StageBypass = StageBypass(1 << 4);
+    pub const AMPLIFIER: StageBypass = StageBypass(1 << 5);
+    pub const ADC: StageBypass = StageBypass(1 << 6);
+    /// Pre-demosaic glitch effects and mosaic plugins.
+    pub const GLITCH: StageBypass = StageBypass(1 << 7);
+    /// Post-demosaic channel effects and RGB plugins.
+    pub const CHANNEL: StageBypass = StageBypass(1 << 8);
+    /// Color rendering and the output LUT.
+    pub const COLOR: StageBypass = StageBypass(1 << 9);
+
+    pub fn contains(self, stage: StageBypass) -> bool {
+        self.0 & stage.0 != 0
+    }
+
+    pub fn set(&mut self, stage: StageBypass, bypassed: bool) {
+        if bypassed {
+            self.0 |= stage.0;
+        } else {
+            self.0 &= !stage.0;
+        }
+    }
+
+    /// The group that skips the timing stage or graph node `name`.
+    pub fn for_stage(name: &str) -> Option<StageBypass> {
+        Some(match name {
+            "Optics" => Self::OPTICS,
+            "HDR Merge" | "Row Exposure" | "Noise" => Self::EXPOSURE_NOISE,
+            "Blooming" => Self::BLOOMING,
+            "V-Clock" | "Line Scan" | "TDI" => Self::V_CLOCK,
+            "H-Clock" => Self::H_CLOCK,
+            "Amplifier" => Self::AMPLIFIER,
+            "ADC" => Self::ADC,
+            "Glitch" => Self::GLITCH,
+            "Channel" => Self::CHANNEL,
+            "Color" | "Output LUT" | "Palette" => Self::COLOR,
+            _ => return None,
+        })
+    }
+}
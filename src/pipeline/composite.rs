@@ -0,0 +1,105 @@
+//! Final compositing of the processed result over the untouched source, so
+//! a render can be only partly broken without an external editor.
+//!
+//! The blend runs on the 8-bit output after stacking, with the source resized
+//! onto the output the way the sensor saw it. Both are display-encoded, as an
+//! editor's layer blend would see them.
+
+use image::DynamicImage;
+
+use super::PipelineParams;
+use crate::image_io;
+
+/// How the processed layer combines with the source beneath it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    /// The processed image replaces the source.
+    Normal,
+    /// Inverse of multiplying the inverses: only ever brightens.
+    Screen,
+    /// Absolute difference: black where the pipeline left the source alone.
+    Difference,
+    /// The brighter of the two, per channel.
+    Lighten,
+}
+
+impl BlendMode {
+    pub const ALL: &[BlendMode] = &[
+        BlendMode::Normal,
+        BlendMode::Screen,
+        BlendMode::Difference,
+        BlendMode::Lighten,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BlendMode::Normal => "Normal",
+            BlendMode::Screen => "Screen",
+            BlendMode::Difference => "Difference",
+            BlendMode::Lighten => "Lighten",
+        }
+    }
+
+    /// Blend processed `top` over source `base`, both in [0, 1].
+    fn blend(self, base: f64, top: f64) -> f64 {
+        match self {
+            BlendMode::Normal => top,
+            BlendMode::Screen => 1.0 - (1.0 - base) * (1.0 - top),
+            BlendMode::Difference => (top - base).abs(),
+            BlendMode::Lighten => base.max(top),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Composite {
+    pub mode: BlendMode,
+    /// Weight of the blended result over the source; 1 shows it alone.
+    pub opacity: f64,
+}
+
+impl Default for Composite {
+    fn default() -> Self {
+        Self {
+            mode: BlendMode::Normal,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Composite {
+    /// Whether compositing leaves the processed result as it is.
+    pub fn is_identity(&self) -> bool {
+        self.mode == BlendMode::Normal && self.opacity >= 1.0
+    }
+}
+
+/// Blend the `width` x `height` RGB8 output `bytes` over `source` as
+/// `params.composite` describes.
+pub fn apply_composite(
+    bytes: &mut [u8],
+    width: usize,
+    height: usize,
+    source: &DynamicImage,
+    params: &PipelineParams,
+) {
+    let composite = &params.composite;
+    if composite.is_identity() || width == 0 || height == 0 {
+        return;
+    }
+    let base = image_io::resize_to_sensor(
+        source,
+        width as u32,
+        height as u32,
+        params.fit_mode,
+        params.determinism.strict,
+    );
+    let opacity = composite.opacity.clamp(0.0, 1.0);
+    for (out, &under) in bytes.iter_mut().zip(base.as_raw()) {
+        let base = under as f64 / 255.0;
+        let top = *out as f64 / 255.0;
+        let blended = composite.mode.blend(base, top);
+        let mixed = base + (blended - base) * opacity;
+        *out = (mixed.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+}
@@ -0,0 +1,260 @@
+//! Contact sheets: a labeled grid of renders sweeping two parameters.
+//!
+//! One parameter varies across columns and another down rows, so
+//! interactions like `spice.vdd` x `spice.phase_overlap_ns` can be read off
+//! a single image.
+
+use image::{Rgb, RgbImage};
+
+use super::PipelineParams;
+use crate::bitmap_font;
+use crate::spice::SpiceMode;
+
+const LABEL_SCALE: u32 = 2;
+const PADDING: u32 = 8;
+const BACKGROUND: Rgb<u8> = Rgb([24, 24, 24]);
+const LABEL_COLOR: Rgb<u8> = Rgb([220, 220, 220]);
+const TITLE_COLOR: Rgb<u8> = Rgb([140, 180, 255]);
+
+/// One axis of a contact sheet: a parameter name and the values to sweep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetAxis {
+    pub param: String,
+    pub values: Vec<String>,
+}
+
+impl SheetAxis {
+    /// Parse `name=a,b,c` (explicit values) or `name=start:end:steps`
+    /// (`steps` evenly spaced values from `start` to `end` inclusive).
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (param, values) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid axis (expected name=values): {spec}"))?;
+        let param = param.trim().to_string();
+
+        let range: Vec<&str> = values.split(':').collect();
+        let values = if range.len() == 3 {
+            let parse = |v: &str| {
+                v.trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid range in axis: {spec}"))
+            };
+            let start = parse(range[0])?;
+            let end = parse(range[1])?;
+            let steps = range[2]
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid step count in axis: {spec}"))?;
+            if steps < 2 {
+                return Err(format!("Range needs at least 2 steps: {spec}"));
+            }
+            (0..steps)
+                .map(|i| {
+                    let v = start + (end - start) * i as f64 / (steps - 1) as f64;
+                    // Round away float noise so integer params parse cleanly
+                    format!("{}", (v * 1e6).round() / 1e6)
+                })
+                .collect()
+        } else {
+            values
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect::<Vec<_>>()
+        };
+
+        if values.is_empty() {
+            return Err(format!("Axis has no values: {spec}"));
+        }
+        Ok(Self { param, values })
+    }
+}
+
+/// Render `source` once per (column, row) value pair and assemble a labeled grid.
+///
+/// Each cell is scaled to `cell_width` pixels wide, keeping its aspect, and a
+/// row is as tall as its tallest cell, so axes that change the output size
+/// (drizzle, pixel aspect) still tile. Sweeping `spice.*` parameters re-runs
+/// the SPICE simulation per cell when SPICE is enabled.
+pub fn contact_sheet(
+    source: &image::DynamicImage,
+    base: &PipelineParams,
+    columns: &SheetAxis,
+    rows: &SheetAxis,
+    cell_width: u32,
+) -> Result<RgbImage, String> {
+    let cell_width = cell_width.max(16);
+
+    // Validate every value up front so a typo fails before any rendering
+    let mut grid_params = Vec::with_capacity(rows.values.len());
+    for row_value in &rows.values {
+        let mut row_params = Vec::with_capacity(columns.values.len());
+        for col_value in &columns.values {
+            let mut params = base.clone();
+            params.set_value(&columns.param, col_value)?;
+            params.set_value(&rows.param, row_value)?;
+            row_params.push(params);
+        }
+        grid_params.push(row_params);
+    }
+
+    let glyph_h = bitmap_font::GLYPH_HEIGHT * LABEL_SCALE;
+    let row_label_w = rows
+        .values
+        .iter()
+        .map(|v| bitmap_font::text_width(v, LABEL_SCALE))
+        .max()
+        .unwrap_or(0)
+        .max(bitmap_font::text_width(&rows.param, LABEL_SCALE));
+    let left = row_label_w + PADDING * 2;
+    let top = glyph_h * 2 + PADDING * 3;
+
+    // Render every cell first: a cell keeps its render's aspect, and a row
+    // is as tall as its tallest cell
+    let mut spice_cache = None;
+    let mut cells = Vec::with_capacity(grid_params.len());
+    for row_params in &grid_params {
+        let mut row = Vec::with_capacity(row_params.len());
+        for params in row_params {
+            if params.spice.mode != SpiceMode::Off {
+                let sensor = params.spice_sensor();
+                crate::spice::simulate_or_cache(&params.spice, &sensor, &mut spice_cache);
+            }
+            let (w, h, bytes) = super::process(source, params, &spice_cache);
+            let rendered = RgbImage::from_raw(w as u32, h as u32, bytes)
+                .ok_or("Pipeline returned a malformed image")?;
+            row.push(scale_to_width(&rendered, cell_width));
+        }
+        cells.push(row);
+    }
+    if cells.iter().all(Vec::is_empty) {
+        return Err("Contact sheet has no cells".to_string());
+    }
+    let row_heights: Vec<u32> = cells
+        .iter()
+        .map(|row| row.iter().map(RgbImage::height).max().unwrap_or(0))
+        .collect();
+    let row_tops: Vec<u32> = row_heights
+        .iter()
+        .scan(top, |y, &height| {
+            let row_top = *y;
+            *y += height + PADDING;
+            Some(row_top)
+        })
+        .collect();
+
+    let grid_width = (cell_width + PADDING) * columns.values.len() as u32;
+    let title_width = bitmap_font::text_width(&columns.param, LABEL_SCALE) + PADDING;
+    let width = left + grid_width.max(title_width);
+    let height = top + row_heights.iter().map(|h| h + PADDING).sum::<u32>();
+    let mut sheet = RgbImage::from_pixel(width, height, BACKGROUND);
+    for (row, &y) in cells.iter().zip(&row_tops) {
+        for (c, cell) in row.iter().enumerate() {
+            let x = left + (cell_width + PADDING) * c as u32;
+            image::imageops::replace(&mut sheet, cell, x as i64, y as i64);
+        }
+    }
+
+    // Column axis name and values along the top
+    bitmap_font::draw_text(
+        &mut sheet,
+        &columns.param,
+        left as i64,
+        PADDING as i64,
+        LABEL_SCALE,
+        TITLE_COLOR,
+    );
+    for (c, value) in columns.values.iter().enumerate() {
+        let x = left + (cell_width + PADDING) * c as u32;
+        let text_w = bitmap_font::text_width(value, LABEL_SCALE);
+        let x = x + cell_width.saturating_sub(text_w) / 2;
+        let y = PADDING * 2 + glyph_h;
+        bitmap_font::draw_text(
+            &mut sheet,
+            value,
+            x as i64,
+            y as i64,
+            LABEL_SCALE,
+            LABEL_COLOR,
+        );
+    }
+
+    // Row axis name and values down the left
+    bitmap_font::draw_text(
+        &mut sheet,
+        &rows.param,
+        PADDING as i64,
+        (PADDING * 2 + glyph_h) as i64,
+        LABEL_SCALE,
+        TITLE_COLOR,
+    );
+    for ((value, &row_top), &row_height) in rows.values.iter().zip(&row_tops).zip(&row_heights) {
+        let y = row_top + row_height.saturating_sub(glyph_h) / 2;
+        bitmap_font::draw_text(
+            &mut sheet,
+            value,
+            PADDING as i64,
+            y as i64,
+            LABEL_SCALE,
+            LABEL_COLOR,
+        );
+    }
+
+    Ok(sheet)
+}
+
+/// Arrange rendered `cells` left to right, `columns` per row, each scaled to
+/// `cell_width` pixels wide and captioned underneath with its label. A row
+/// is as tall as its tallest cell.
+pub fn labeled_grid(
+    cells: &[(String, RgbImage)],
+    columns: usize,
+    cell_width: u32,
+) -> Result<RgbImage, String> {
+    if cells.is_empty() {
+        return Err("Contact sheet has no cells".to_string());
+    }
+    let cell_width = cell_width.max(16);
+    let columns = columns.clamp(1, cells.len());
+    let scaled: Vec<RgbImage> =
+        cells.iter().map(|(_, image)| scale_to_width(image, cell_width)).collect();
+    let row_heights: Vec<u32> = scaled
+        .chunks(columns)
+        .map(|row| row.iter().map(RgbImage::height).max().unwrap_or(0))
+        .collect();
+    let glyph_h = bitmap_font::GLYPH_HEIGHT * LABEL_SCALE;
+    let pitch_x = cell_width + PADDING;
+    let row_tops: Vec<u32> = row_heights
+        .iter()
+        .scan(PADDING, |y, &height| {
+            let row_top = *y;
+            *y += height + glyph_h + PADDING * 2;
+            Some(row_top)
+        })
+        .collect();
+    let height = PADDING + row_heights.iter().map(|h| h + glyph_h + PADDING * 2).sum::<u32>();
+
+    let mut sheet = RgbImage::from_pixel(PADDING + pitch_x * columns as u32, height, BACKGROUND);
+    for (i, ((label, _), cell)) in cells.iter().zip(&scaled).enumerate() {
+        let x = PADDING + pitch_x * (i % columns) as u32;
+        let y = row_tops[i / columns];
+        image::imageops::replace(&mut sheet, cell, x as i64, y as i64);
+        let text_w = bitmap_font::text_width(label, LABEL_SCALE);
+        bitmap_font::draw_text(
+            &mut sheet,
+            label,
+            (x + cell_width.saturating_sub(text_w) / 2) as i64,
+            (y + row_heights[i / columns] + PADDING) as i64,
+            LABEL_SCALE,
+            LABEL_COLOR,
+        );
+    }
+    Ok(sheet)
+}
+
+/// `image` scaled to `width` pixels wide, keeping its aspect ratio.
+fn scale_to_width(image: &RgbImage, width: u32) -> RgbImage {
+    let height =
+        ((image.height() as f64 * width as f64 / image.width() as f64).round() as u32).max(1);
+    image::imageops::resize(image, width, height, image::imageops::FilterType::Triangle)
+}
@@ -0,0 +1,520 @@
+//! Resolved processing graph for a parameter set.
+//!
+//! `resolve` walks the same decisions as `process_tapped` without touching
+//! any pixels: which stages run, in what order, which engine (math model or
+//! SPICE-derived) handles each, and the parameters each one actually reads.
+//! The result exports to JSON or Graphviz dot, for reproducibility records
+//! and documentation.
+
+use super::{anamorphic_width, ParamValue, PipelineParams, StageBypass};
+use crate::color::palette::PaletteMode;
+use crate::glitch::plugin::PluginStage;
+use crate::spice::{SpiceCache, SpiceMode};
+
+/// What handles a stage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Engine {
+    Math,
+    Spice,
+    /// External plugin stage.
+    Plugin,
+    /// Not run with these parameters.
+    Skipped,
+}
+
+impl Engine {
+    pub fn name(self) -> &'static str {
+        match self {
+            Engine::Math => "math",
+            Engine::Spice => "spice",
+            Engine::Plugin => "plugin",
+            Engine::Skipped => "skipped",
+        }
+    }
+}
+
+/// One stage of the resolved pipeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageNode {
+    /// Stage name, matching the timing and tap labels where they exist.
+    pub name: &'static str,
+    pub engine: Engine,
+    /// Why the stage is skipped, what SPICE replaced, or which plugins ran;
+    /// empty otherwise.
+    pub note: String,
+    /// Effective parameters, including SPICE-derived values.
+    pub params: Vec<(&'static str, ParamValue)>,
+}
+
+/// Stages in execution order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineGraph {
+    pub spice_mode: &'static str,
+    pub nodes: Vec<StageNode>,
+}
+
+/// Parameters read by each math stage. A pattern ending in `.` or `_`
+/// matches every parameter with that prefix.
+const RESIZE: &[&str] = &[
+    "sensor_width",
+    "sensor_height",
+    "pixel_aspect",
+    "full_well",
+    "fit_mode",
+    "letterbox_pad",
+    "area_sum_downscale",
+];
+const HDR_MERGE: &[&str] = &["hdr_merge.", "full_well"];
+const OPTICS: &[&str] = &[
+    "motion_blur.",
+    "lens_distortion",
+    "defocus_radius",
+    "halation.",
+    "scan_",
+];
+const CAMERA_OSD: &[&str] = &["camera_osd."];
+const BAYER: &[&str] = &["bayer_pattern"];
+const ROW_EXPOSURE: &[&str] = &[
+    "row_exposure_depth",
+    "row_exposure.",
+    "flicker.",
+    "sensor_orientation",
+    "vertical_direction",
+];
+const NOISE: &[&str] = &[
+    "dark_current_rate",
+    "dark_structure.",
+    "long_exposure.",
+    "aging.",
+    "shot_noise_enabled",
+    "read_noise",
+    "noise.",
+    "readout_speed",
+    "fast_readout.",
+];
+const FRAME: &[&str] = &["sensor_orientation", "vertical_direction"];
+const BLOOMING: &[&str] = &[
+    "full_well",
+    "use_abg",
+    "abg_strength",
+    "bloom_threshold",
+    "bloom_vertical",
+];
+const V_CLOCK: &[&str] = &[
+    "v_phases",
+    "v_cte",
+    "aging.",
+    "readout_speed",
+    "fast_readout.",
+    "v_glitch_rate",
+    "v_glitch_schedule.",
+    "saturation_clock_coupling",
+    "v_waveform_distortion",
+    "v_modulation.",
+    "parallel_smear",
+    "temporal.",
+];
+const LINE_SCAN: &[&str] = &["line_scan."];
+const TDI: &[&str] = &["tdi."];
+const H_CLOCK: &[&str] = &[
+    "h_cte",
+    "aging.",
+    "readout_speed",
+    "fast_readout.",
+    "h_glitch_rate",
+    "h_ringing",
+    "readout_direction",
+    "serial_defects.",
+    "readout_abort",
+    "abort_fill",
+];
+const AMPLIFIER: &[&str] = &[
+    "conversion_gain",
+    "amp_gain",
+    "nonlinearity",
+    "reset_noise",
+    "reset_lag",
+    "amp_glow",
+    "amp_drift",
+    "supply.",
+    "thermal.",
+    "interference.",
+];
+const ADC: &[&str] = &[
+    "bit_depth",
+    "readout_speed",
+    "fast_readout.",
+    "cds_mode",
+    "adc_gain",
+    "bias",
+    "reset_noise",
+    "dnl_errors",
+    "bit_errors",
+    "adc_jitter",
+    "adc_dither",
+    "adc_overflow",
+    "adc_encoding",
+    "adc_wrong_decode",
+    "compand.",
+    "dual_gain.",
+    "column_adc.",
+    "black_clamp.",
+    "adc_ref_",
+    "adc_clock_error",
+    "adc_line_lock",
+];
+const EXPOSURE: &[&str] = &["auto_exposure", "auto_exposure_"];
+const GLITCH: &[&str] = &[
+    "defect_repair_",
+    "pixel_shift_amount",
+    "block_shift_amount",
+    "scan_line_frequency",
+    "per_plane_glitch",
+    "sync.",
+    "bit_xor_mask",
+    "bit_rotation",
+    "bit_plane_swaps",
+    "raw_",
+    "compression.",
+    "temporal.",
+];
+const DEMOSAIC: &[&str] = &["bayer_pattern", "demosaic_algo"];
+const CHANNEL: &[&str] = &["chroma_subsampling", "channel_", "chromatic_"];
+const COLOR: &[&str] = &[
+    "white_balance_",
+    "saturation",
+    "vibrance",
+    "hue_rotation",
+    "gamma",
+    "brightness",
+    "contrast",
+    "tone_curve.",
+    "output_lut_size",
+    "output_dither",
+    "sharpen.",
+];
+const ANAMORPHIC: &[&str] = &["pixel_aspect", "anamorphic_output"];
+const PALETTE: &[&str] = &["palette."];
+const STACK: &[&str] = &["stack."];
+const THREE_CHIP: &[&str] = &["three_chip."];
+const SOURCE_MATCH: &[&str] = &["source_match."];
+const COMPOSITE: &[&str] = &["composite."];
+const ARTIFACT_LAYER: &[&str] = &["artifact_layer."];
+const FRAME_OVERLAY: &[&str] = &["frame_overlay."];
+
+/// Resolve the stage graph `process` would run for `params`.
+///
+/// SPICE stages only count as run when `spice_cache` holds a simulation;
+/// without one the pipeline falls back to the math stages, and so does the
+/// graph.
+pub fn resolve(params: &PipelineParams, spice_cache: &Option<SpiceCache>) -> PipelineGraph {
+    let values = params.values();
+    let pick = |patterns: &[&str]| -> Vec<(&'static str, ParamValue)> {
+        values
+            .iter()
+            .filter(|(name, _)| {
+                patterns.iter().any(|p| {
+                    if p.ends_with('.') || p.ends_with('_') {
+                        name.starts_with(p)
+                    } else {
+                        name == p
+                    }
+                })
+            })
+            .cloned()
+            .collect()
+    };
+    let node = |name, engine, note: &str, patterns: &[&str]| StageNode {
+        name,
+        engine,
+        note: note.to_string(),
+        params: pick(patterns),
+    };
+    use Engine::*;
+
+    let hdr_merge = if params.hdr_merge.enabled { Math } else { Skipped };
+    let camera_osd = if params.camera_osd.is_off() { Skipped } else { Math };
+    let line_scan = if params.line_scan.enabled { Math } else { Skipped };
+    let tdi = if params.tdi.enabled && !params.line_scan.enabled { Math } else { Skipped };
+    // The math V-clock has no parallel register to clock in line-scan mode
+    let v_clock = || {
+        if params.line_scan.enabled {
+            node("V-Clock", Skipped, "replaced by the line scan", V_CLOCK)
+        } else {
+            node("V-Clock", Math, "", V_CLOCK)
+        }
+    };
+    let mut nodes = vec![
+        node("Resize", Math, "", RESIZE),
+        node("HDR Merge", hdr_merge, "", HDR_MERGE),
+        node("Optics", Math, "", OPTICS),
+        node("Camera OSD", camera_osd, "lit into the scene", CAMERA_OSD),
+        node("Bayer", Math, "", BAYER),
+        node("Row Exposure", Math, "", ROW_EXPOSURE),
+        node("Noise", Math, "", NOISE),
+        node("Readout Frame", Math, "", FRAME),
+        node("Line Scan", line_scan, "", LINE_SCAN),
+        node("TDI", tdi, "", TDI),
+    ];
+
+    let cache = spice_cache.as_ref().filter(|_| params.spice.mode != SpiceMode::Off);
+    match cache {
+        None => nodes.extend([
+            node("Blooming", Math, "", BLOOMING),
+            v_clock(),
+            node("H-Clock", Math, "", H_CLOCK),
+            node("Amplifier", Math, "", AMPLIFIER),
+            node("ADC", Math, "", ADC),
+        ]),
+        Some(cache) => {
+            use ParamValue::{Float, Int};
+            let mut spice = node("SPICE", Spice, "circuit simulation (cached)", &["spice."]);
+            spice.params.extend([
+                ("spice.effective_cte", Float(cache.effective_cte)),
+                ("spice.noise_sigma", Float(cache.noise_sigma)),
+                ("spice.cds_rejection", Float(cache.cds_rejection)),
+                ("spice.fallbacks", Int(cache.fallbacks.count() as i64)),
+            ]);
+            nodes.push(spice);
+
+            let spice_adc = |note: &str| StageNode {
+                name: "ADC",
+                engine: Spice,
+                note: note.to_string(),
+                params: pick(&[
+                    "bit_depth",
+                    "readout_speed",
+                    "fast_readout.",
+                    "full_well",
+                    "adc_overflow",
+                    "column_adc.",
+                    "black_clamp.",
+                    "adc_encoding",
+                    "adc_wrong_decode",
+                    "compand.",
+                    "adc_ref_",
+                    "adc_clock_error",
+                    "adc_line_lock",
+                ]),
+            };
+            let spice_amp = |note: &str| StageNode {
+                name: "Amplifier",
+                engine: Spice,
+                note: note.to_string(),
+                params: pick(&["full_well", "conversion_gain", "reset_lag", "interference."]),
+            };
+            match params.spice.mode {
+                SpiceMode::FullReadout => {
+                    let cte = ("spice.effective_cte", Float(cache.effective_cte));
+                    let mut v_clock = StageNode {
+                        name: "V-Clock",
+                        engine: Spice,
+                        note: "SPICE CTE replaces v_cte; row glitches not applied".to_string(),
+                        params: pick(&["spice.missing_pulse_rate", "tile_seamless"]),
+                    };
+                    v_clock.params.push(cte.clone());
+                    let mut h_clock = StageNode {
+                        name: "H-Clock",
+                        engine: Spice,
+                        note: "SPICE CTE replaces h_cte".to_string(),
+                        params: pick(&[
+                            "readout_direction",
+                            "serial_defects.",
+                            "readout_abort",
+                            "abort_fill",
+                        ]),
+                    };
+                    h_clock.params.push(cte);
+                    nodes.extend([
+                        node("Blooming", Skipped, "not modeled in full SPICE readout", BLOOMING),
+                        v_clock,
+                        h_clock,
+                        spice_amp("pixel + amplifier transfer curve, CDS residual noise"),
+                        spice_adc("SPICE ADC transfer and DNL"),
+                        StageNode {
+                            name: "Clock Ringing",
+                            engine: Spice,
+                            note: "clock driver ringing kernel replaces h_ringing".to_string(),
+                            params: vec![(
+                                "spice.ringing_taps",
+                                Int(cache.ringing_kernel.len() as i64),
+                            )],
+                        },
+                    ]);
+                }
+                SpiceMode::AmplifierOnly => nodes.extend([
+                    node("Blooming", Math, "", BLOOMING),
+                    v_clock(),
+                    node("H-Clock", Math, "", H_CLOCK),
+                    spice_amp("pixel + amplifier transfer curve, CDS residual noise"),
+                    spice_adc("SPICE ADC transfer and DNL"),
+                ]),
+                // Off never reaches here; the cache is ignored above
+                SpiceMode::TransferCurveOnly | SpiceMode::Off => nodes.extend([
+                    node("Blooming", Math, "", BLOOMING),
+                    v_clock(),
+                    node("H-Clock", Math, "", H_CLOCK),
+                    spice_amp("pixel + amplifier transfer curve"),
+                    node("ADC", Math, "", ADC),
+                ]),
+            }
+        }
+    }
+
+    let exposure = if params.auto_exposure { Math } else { Skipped };
+    let lut = if params.output_lut.is_some() { Math } else { Skipped };
+    let palette = if params.palette.mode == PaletteMode::Off { Skipped } else { Math };
+    let anamorphic = if anamorphic_width(params).is_some() { Math } else { Skipped };
+    let stack = if params.stack.frames > 1 { Math } else { Skipped };
+    let three_chip = if params.three_chip.enabled { Math } else { Skipped };
+    let source_match = if params.source_match.is_off() { Skipped } else { Math };
+    let composite = if params.composite.is_identity() { Skipped } else { Math };
+    let artifacts = if params.artifact_layer.is_off() { Skipped } else { Math };
+    let overlay = if params.frame_overlay.is_off() { Skipped } else { Math };
+    let plugins = |name, stage| {
+        let ran: Vec<String> = params
+            .plugins
+            .iter()
+            .filter(|slot| slot.plugin.stage == stage)
+            .map(|slot| format!("{} x{} seed {}", slot.plugin.name, slot.amount, slot.seed))
+            .collect();
+        let engine = if ran.is_empty() { Skipped } else { Plugin };
+        node(name, engine, &ran.join(", "), &[])
+    };
+    nodes.extend([
+        node("Auto Exposure", exposure, "", EXPOSURE),
+        node("Glitch", Math, "", GLITCH),
+        plugins("Mosaic Plugins", PluginStage::Mosaic),
+        node("Demosaic", Math, "", DEMOSAIC),
+        node("Channel", Math, "", CHANNEL),
+        plugins("RGB Plugins", PluginStage::Rgb),
+        node("Color", Math, "", COLOR),
+        node("Output LUT", lut, "", &["output_lut_size"]),
+        node("Anamorphic", anamorphic, "", ANAMORPHIC),
+        node("Palette", palette, "", PALETTE),
+        node("Stack", stack, "combines whole-pipeline subframes", STACK),
+        node("3-CCD", three_chip, "one whole pipeline per channel", THREE_CHIP),
+        node("Source Match", source_match, "matched to the source", SOURCE_MATCH),
+        node("Composite", composite, "blends over the source", COMPOSITE),
+        node("Noise Print", artifacts, "captured artifacts added back", ARTIFACT_LAYER),
+        node("Frame Overlay", overlay, "burned over the output", FRAME_OVERLAY),
+    ]);
+    for node in &mut nodes {
+        let Some(stage) = StageBypass::for_stage(node.name) else {
+            continue;
+        };
+        if !params.bypass.contains(stage) || node.engine == Skipped {
+            continue;
+        }
+        if stage == StageBypass::ADC {
+            node.note = "bypassed: ideal quantizer".to_string();
+        } else {
+            node.engine = Skipped;
+            node.note = "bypassed".to_string();
+        }
+    }
+
+    PipelineGraph {
+        spice_mode: if cache.is_some() { params.spice.mode.name() } else { SpiceMode::Off.name() },
+        nodes,
+    }
+}
+
+impl PipelineGraph {
+    pub fn to_json(&self) -> String {
+        let mut out = format!(
+            "{{\n  \"spice_mode\": {},\n  \"stages\": [\n",
+            json_string(self.spice_mode)
+        );
+        for (i, node) in self.nodes.iter().enumerate() {
+            out.push_str(&format!(
+                "    {{\n      \"name\": {},\n      \"engine\": {},\n      \"note\": {},\n      \
+                 \"params\": {{",
+                json_string(node.name),
+                json_string(node.engine.name()),
+                json_string(&node.note),
+            ));
+            for (j, (name, value)) in node.params.iter().enumerate() {
+                let sep = if j == 0 { "\n" } else { ",\n" };
+                out.push_str(&format!("{sep}        {}: {}", json_string(name), json_value(value)));
+            }
+            if !node.params.is_empty() {
+                out.push_str("\n      ");
+            }
+            let end = if i + 1 < self.nodes.len() { "," } else { "" };
+            out.push_str(&format!("}}\n    }}{end}\n"));
+        }
+        out.push_str("  ]\n}\n");
+        out
+    }
+
+    /// Graphviz digraph: active stages chained in order, skipped stages
+    /// dashed and off the chain, SPICE and plugin stages shaded.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph pipeline {\n  rankdir=TB;\n");
+        out.push_str("  node [shape=box, fontname=\"monospace\"];\n");
+        let title = format!("SPICE mode: {}", self.spice_mode);
+        out.push_str(&format!("  label={};\n", dot_string(&title)));
+        for (i, node) in self.nodes.iter().enumerate() {
+            let mut label = node.name.to_string();
+            if !node.note.is_empty() {
+                label.push_str(&format!("\n({})", node.note));
+            }
+            for (name, value) in &node.params {
+                label.push_str(&format!("\n{name} = {value}"));
+            }
+            let style = match node.engine {
+                Engine::Math => "solid",
+                Engine::Spice | Engine::Plugin => "filled",
+                Engine::Skipped => "dashed",
+            };
+            let fill = if node.engine == Engine::Plugin { "#c0e0ff" } else { "#ffe0c0" };
+            out.push_str(&format!(
+                "  s{i} [label={}, style={style}, fillcolor=\"{fill}\"];\n",
+                dot_string(&label)
+            ));
+        }
+        let active: Vec<usize> = (0..self.nodes.len())
+            .filter(|&i| self.nodes[i].engine != Engine::Skipped)
+            .collect();
+        for pair in active.windows(2) {
+            out.push_str(&format!("  s{} -> s{};\n", pair[0], pair[1]));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub(crate) fn json_value(value: &ParamValue) -> String {
+    match value {
+        // JSON has no NaN or infinity
+        ParamValue::Float(v) if v.is_finite() => format!("{v}"),
+        ParamValue::Float(_) => "null".to_string(),
+        ParamValue::Int(v) => v.to_string(),
+        ParamValue::Bool(v) => v.to_string(),
+        ParamValue::Choice(v) => json_string(v),
+        ParamValue::Data { len, hash } => format!("{{\"len\": {len}, \"hash\": {hash}}}"),
+    }
+}
+
+/// Quote `s` for dot, with newlines as left-justified line breaks.
+fn dot_string(s: &str) -> String {
+    let escaped = s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\l");
+    format!("\"{escaped}\\l\"")
+}
@@ -0,0 +1,245 @@
+//! Peak memory estimates, so an oversized render is refused or downscaled
+//! up front rather than aborting the process when an allocation fails.
+//!
+//! The figures count the large per-pixel buffers a render holds at once:
+//! the resampled source, the electron image, the mosaic and its working
+//! copy, the demosaiced image and the output, with the extra frames a
+//! stack, a drizzle, a 3-CCD split or an alpha plane adds. Small tables and the stage
+//! code's own scratch rows are left out, so the estimate is a floor with
+//! some margin rather than an exact high-water mark.
+
+use image::DynamicImage;
+
+use super::{stack, three_chip, AlphaMode, PipelineParams, StageBypass};
+use crate::spice::SpiceCache;
+
+/// Bytes per pixel of an `Rgb32F` image.
+const RGB_F32: u64 = 12;
+/// Bytes per pixel of an RGB image in `f64` electrons or linear values.
+const RGB_F64: u64 = 24;
+/// Bytes per photosite of a mosaic.
+const MOSAIC: u64 = 8;
+/// Bytes per pixel of the RGB8 output plus the RGBA8 texture showing it.
+const OUTPUT: u64 = 3 + 4;
+/// Fraction of the budget a downscaled render aims for, leaving headroom
+/// for what the estimate leaves out.
+const HEADROOM: f64 = 0.8;
+
+/// Where the bytes of a render go, in pipeline order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryEstimate {
+    pub items: Vec<(&'static str, u64)>,
+}
+
+impl MemoryEstimate {
+    pub fn total(&self) -> u64 {
+        self.items.iter().map(|(_, bytes)| bytes).sum()
+    }
+
+    /// One `name: size` line per item.
+    pub fn breakdown(&self) -> String {
+        self.items
+            .iter()
+            .map(|(name, bytes)| format!("{name}: {}", format_bytes(*bytes)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Estimate the peak memory of rendering `params` from `source`, or from a
+/// source the size of the sensor with no alpha when `None`.
+pub fn estimate(
+    params: &PipelineParams,
+    source: Option<&DynamicImage>,
+    spice_cache: &Option<SpiceCache>,
+) -> MemoryEstimate {
+    let (source_w, source_h) = source.map_or((params.sensor_width, params.sensor_height), |s| {
+        (s.width(), s.height())
+    });
+    let source_pixels = source_w as u64 * source_h as u64;
+
+    let mut items = vec![("Source", source_pixels * RGB_F32)];
+    if params.three_chip.enabled {
+        // The chips render one after another, so the largest pipeline sets
+        // the peak, while each chip's finished frame waits for the recombine
+        let chips: Vec<PipelineParams> = (0..3)
+            .map(|chip| three_chip::chip_params(params, chip).unwrap_or_else(|_| params.clone()))
+            .collect();
+        let largest = chips
+            .iter()
+            .map(|chip| pipeline_items(chip, source_pixels, false))
+            .max_by_key(|items| items.iter().map(|(_, bytes)| bytes).sum::<u64>())
+            .unwrap_or_default();
+        items.extend(largest);
+        items.push(("3-CCD", chips.iter().map(|chip| output_pixels(chip) * 3).sum()));
+    } else {
+        items.extend(pipeline_items(params, source_pixels, true));
+    }
+    let has_alpha = source.is_some_and(|s| s.color().has_alpha());
+    if has_alpha && params.alpha_mode != AlphaMode::Discard {
+        // The alpha plane in f64 and its glitched copy, then the output
+        // alpha and the RGBA frame it is merged into
+        let (width, height) = super::output_size(
+            params,
+            output_width(params) as usize,
+            params.sensor_height as usize,
+        );
+        let pixels = params.sensor_width as u64 * params.sensor_height as u64;
+        items.push(("Alpha", pixels * MOSAIC * 2 + (width * height) as u64 * (1 + 4)));
+    }
+    let spice = spice_bytes(spice_cache);
+    if spice > 0 {
+        items.push(("SPICE cache", spice));
+    }
+    MemoryEstimate { items }
+}
+
+/// Width of the frame a single readout of `params` comes out at.
+fn output_width(params: &PipelineParams) -> u32 {
+    super::anamorphic_width(params).map_or(params.sensor_width, |w| w as u32)
+}
+
+fn output_pixels(params: &PipelineParams) -> u64 {
+    output_width(params) as u64 * params.sensor_height as u64
+}
+
+/// The buffers one pipeline run holds: a single frame, or a stack of them
+/// and, when `drizzle` allows it, the finer grid they drizzle onto.
+fn pipeline_items(
+    params: &PipelineParams,
+    source_pixels: u64,
+    drizzle: bool,
+) -> Vec<(&'static str, u64)> {
+    let pixels = params.sensor_width as u64 * params.sensor_height as u64;
+    let output = output_pixels(params);
+    let mut items = vec![("Exposure", pixels * (RGB_F32 + RGB_F64))];
+    if !params.bypass.contains(StageBypass::OPTICS) {
+        items.push(("Optics", pixels * RGB_F64));
+    }
+    items.push(("Mosaic", pixels * MOSAIC * 2));
+    items.push(("Demosaic", pixels * RGB_F64));
+    if output != pixels {
+        items.push(("Anamorphic", output * RGB_F64));
+    }
+    let stack = &params.stack;
+    let frames = stack.frames as u64;
+    if frames > 1 {
+        // Finished subframes, plus one misregistered copy of the source
+        items.push(("Stack", frames * output * 3 + source_pixels * RGB_F32 * 2));
+    }
+    if drizzle && stack.drizzle && frames > 1 {
+        // The weighted RGB sums and the weights, in f64
+        let (width, height) = stack::drizzled_size(
+            output_width(params) as usize,
+            params.sensor_height as usize,
+            stack,
+        );
+        let drizzled = (width * height) as u64;
+        items.push(("Drizzle", drizzled * (RGB_F64 + 8)));
+        items.push(("Output", drizzled * OUTPUT));
+    } else {
+        items.push(("Output", output * OUTPUT));
+    }
+    items
+}
+
+fn spice_bytes(cache: &Option<SpiceCache>) -> u64 {
+    let Some(c) = cache else {
+        return 0;
+    };
+    let f64s = c.pixel_transfer.len() * 2
+        + c.clock_ringing_kernel.len()
+        + c.clock_waveforms.iter().map(Vec::len).sum::<usize>()
+        + c.amp_transfer_curve.len() * 2
+        + c.adc_transfer.len() * 2
+        + c.adc_dnl.len()
+        + c.transfer_curve.len() * 2
+        + c.ringing_kernel.len();
+    (f64s * size_of::<f64>() + size_of::<SpiceCache>()) as u64
+}
+
+/// Memory a render may use: what the system reports available where it
+/// can be asked, otherwise a fixed ceiling (the browser's practical limit on
+/// the web).
+pub fn budget() -> u64 {
+    const GIB: u64 = 1 << 30;
+    #[cfg(target_arch = "wasm32")]
+    {
+        2 * GIB
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        available_memory().unwrap_or(8 * GIB)
+    }
+}
+
+/// `MemAvailable` from `/proc/meminfo`, on Linux.
+#[cfg(not(target_arch = "wasm32"))]
+fn available_memory() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemAvailable:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Err with a message naming the render's size and need when it would not
+/// fit in `budget` bytes.
+pub fn check(
+    params: &PipelineParams,
+    estimate: &MemoryEstimate,
+    budget: u64,
+) -> Result<(), String> {
+    let need = estimate.total();
+    if need <= budget {
+        return Ok(());
+    }
+    Err(format!(
+        "A {}x{} render needs about {}, more than the {} available; reduce the sensor size",
+        params.sensor_width,
+        params.sensor_height,
+        format_bytes(need),
+        format_bytes(budget),
+    ))
+}
+
+/// The largest sensor size with the same aspect ratio whose render fits in
+/// `budget` bytes, or `None` when even the size-independent part does not.
+pub fn fit_sensor(
+    params: &PipelineParams,
+    estimate: &MemoryEstimate,
+    budget: u64,
+) -> Option<(u32, u32)> {
+    let fixed: u64 = estimate
+        .items
+        .iter()
+        .filter(|(name, _)| matches!(*name, "Source" | "SPICE cache"))
+        .map(|(_, bytes)| bytes)
+        .sum();
+    let target = budget as f64 * HEADROOM - fixed as f64;
+    let scaled = estimate.total().saturating_sub(fixed) as f64;
+    if target <= 0.0 || scaled <= 0.0 {
+        return None;
+    }
+    let scale = (target / scaled).sqrt().min(1.0);
+    // Even sizes keep the CFA phase
+    let fit = |side: u32| ((side as f64 * scale) as u32 & !1).max(2);
+    Some((fit(params.sensor_width), fit(params.sensor_height)))
+}
+
+/// Bytes as a short human-readable size.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    if value < 1024.0 {
+        return format!("{bytes} B");
+    }
+    let mut unit = "";
+    for name in UNITS {
+        value /= 1024.0;
+        unit = name;
+        if value < 1024.0 {
+            break;
+        }
+    }
+    format!("{value:.1} {unit}")
+}
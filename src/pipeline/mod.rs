@@ -0,0 +1,2543 @@
+use crate::ccd::adc::{
+    self, AdcEncoding, AdcOverflow, BlackClamp, CdsMode, ColumnAdc, Companding, DualGain,
+};
+use crate::ccd::aging::Aging;
+use crate::ccd::amplifier::{self, Interference};
+use crate::ccd::noise::{NoiseSource, NoiseTexture};
+use crate::ccd::blooming;
+use crate::ccd::sensor::{self, DarkStructure, FlickerParams, HdrMerge, LongExposure, ShutterMode};
+use crate::ccd::supply::{SupplyFeedback, SupplyLoad};
+use crate::ccd::thermal::{self, AmpHeating, HeatMap};
+use crate::ccd::transfer::{
+    self, AbortFill, ClockPhases, GlitchSchedule, LineScan, Modulation, ModulationShape,
+    ReadoutDirection, ScheduleMode, SensorOrientation, SerialDefects, Tdi, VerticalDirection,
+};
+use crate::ccd::units::{AdcGain, Adu, ConversionGain, Electrons};
+use crate::ccd::readout::RowStage;
+use crate::ccd::{readout, ReadoutProfile, ReadoutSpeed};
+use crate::color::bayer::{self, BayerPattern};
+use crate::color::demosaic::{self, DemosaicAlgo};
+use crate::color::dither::Dither;
+use crate::color::lut::{self, Lut3d};
+use crate::color::optics::{self, Halation, MotionBlur, Trajectory};
+use crate::color::osd::{self, CameraOsd, FrameOverlay};
+use crate::color::palette::{self, PaletteMode, Palettize};
+use crate::color::spectral::{self, Sharpen, ToneCurves};
+use crate::glitch::{self, bit_manip};
+use crate::glitch::channel::{self, ChannelSwap};
+use crate::glitch::chroma::{self, ChromaSubsampling};
+use crate::glitch::compression::{self, Compression};
+use crate::glitch::defect_repair::{self, RepairMode};
+use crate::glitch::pixel_shift;
+use crate::glitch::plugin::{self, PluginSlot, PluginStage};
+use crate::glitch::raw_pack::{self, RawPacking};
+use crate::glitch::scan_line;
+use crate::glitch::sync::{self, BurstLength, SyncFaults};
+use crate::image_io::{self, FitMode};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+pub mod alpha;
+pub mod analysis;
+pub mod artifact_layer;
+pub mod automation;
+pub mod bypass;
+pub mod composite;
+pub mod contact_sheet;
+pub mod determinism;
+pub mod graph;
+pub mod memory;
+pub mod morph;
+pub mod mtf;
+pub mod preset;
+pub mod reference;
+pub mod schema;
+pub mod source_match;
+pub mod stack;
+pub mod temporal;
+pub mod test_patterns;
+pub mod three_chip;
+pub mod validation;
+pub mod variations;
+
+pub use alpha::{merge_alpha, process_alpha, AlphaMode};
+pub use artifact_layer::{ArtifactLayer, ArtifactOverlay};
+pub use bypass::StageBypass;
+pub use composite::{BlendMode, Composite};
+pub use contact_sheet::{contact_sheet, SheetAxis};
+pub use determinism::{Determinism, Stream};
+pub use reference::{difference_view, metrics, process_reference, Metrics};
+pub use source_match::SourceMatch;
+pub use stack::{Combiner, StackParams};
+pub use temporal::{GlitchFrame, TemporalParams, TemporalState};
+pub use three_chip::ThreeChip;
+pub use validation::BufferIssue;
+
+/// All pipeline parameters controlled by the user.
+#[derive(Debug, Clone)]
+pub struct PipelineParams {
+    // Sensor
+    pub sensor_width: u32,
+    pub sensor_height: u32,
+    /// Photosite width / height (1.0 = square pixels).
+    pub pixel_aspect: f64,
+    /// Resample the output horizontally so non-square photosites display
+    /// with correct geometry.
+    pub anamorphic_output: bool,
+    pub full_well: f64,
+    /// Floating diffusion conversion gain in µV/e-.
+    pub conversion_gain: f64,
+    /// Slow uses the parameters as set; fast applies `fast_readout`.
+    pub readout_speed: ReadoutSpeed,
+    pub fast_readout: ReadoutProfile,
+    /// Age and radiation dose, from which `aged` derives defects.
+    pub aging: Aging,
+    pub use_abg: bool,
+    pub fit_mode: FitMode,
+    /// Charge in electrons placed in the letterbox padding.
+    pub letterbox_pad: f64,
+    /// Sum source pixels into each sensor pixel when downscaling (charge binning)
+    /// instead of resampling, so point highlights keep their energy.
+    pub area_sum_downscale: bool,
+    /// Wrap spatial stages around the image edges so the output tiles seamlessly.
+    pub tile_seamless: bool,
+    pub alpha_mode: AlphaMode,
+
+    // Optics
+    /// Radial distortion coefficient: positive = barrel, negative = pincushion.
+    pub lens_distortion: f64,
+    /// Defocus disk radius in sensor pixels.
+    pub defocus_radius: f64,
+    /// Scene motion during the exposure (tracking error, star trails).
+    pub motion_blur: MotionBlur,
+    /// Highlight glow scattered off the cover glass, added in electrons.
+    pub halation: Halation,
+    /// Horizontal shift added per row by a tilted sensor or scan, in pixels.
+    pub scan_skew: f64,
+    /// Horizontal scale error reached by the last row (0.01 = 1% wider).
+    pub scan_scale_error: f64,
+    /// Amplitude of a sinusoidal row shift, in pixels.
+    pub scan_wobble: f64,
+    /// Wobble cycles over the frame height.
+    pub scan_wobble_frequency: f64,
+    /// Camcorder date stamp and indicators, drawn into the scene's light.
+    pub camera_osd: CameraOsd,
+
+    // Exposure & Noise
+    pub dark_current_rate: f64,
+    /// Edge glow, hot clusters and tree rings in the dark rate.
+    pub dark_structure: DarkStructure,
+    /// Time-sliced integration with cosmic rays and stray clock pulses.
+    pub long_exposure: LongExposure,
+    pub read_noise: f64,
+    pub shot_noise_enabled: bool,
+    /// Bracketed exposures merged to HDR before the readout chain.
+    pub hdr_merge: HdrMerge,
+    /// Generator behind the dark, shot and read noise.
+    pub noise: NoiseTexture,
+    /// Depth of the per-row exposure modulation; 0 disables it.
+    pub row_exposure_depth: f64,
+    /// Signal the row exposure follows over the readout, in cycles per frame.
+    pub row_exposure: Modulation,
+    /// Flickering light source, timed against each row's exposure window.
+    pub flicker: FlickerParams,
+
+    // Blooming
+    pub abg_strength: f64,
+    pub bloom_threshold: f64,
+    pub bloom_vertical: bool,
+
+    // V-Clock
+    /// Vertical clock phase count, which the SPICE clock circuit also drives.
+    pub v_phases: ClockPhases,
+    pub v_cte: f64,
+    pub v_glitch_rate: f64,
+    pub v_glitch_schedule: GlitchSchedule,
+    /// V-clock fault rate per unit of saturated fraction in a row; faults
+    /// cluster around bloomed regions.
+    pub saturation_clock_coupling: f64,
+    pub v_waveform_distortion: f64,
+    pub v_modulation: Modulation,
+    pub parallel_smear: f64,
+    /// A single swept sensor row in place of the parallel register.
+    pub line_scan: LineScan,
+    /// Time-delay integration: charge shifted along with a moving scene.
+    pub tdi: Tdi,
+
+    // H-Clock
+    pub h_cte: f64,
+    pub h_glitch_rate: f64,
+    pub h_ringing: f64,
+    pub readout_direction: ReadoutDirection,
+    pub serial_defects: SerialDefects,
+    /// Fraction of rows read before readout aborts (1.0 = complete frame).
+    pub readout_abort: f64,
+    pub abort_fill: AbortFill,
+    pub vertical_direction: VerticalDirection,
+    pub sensor_orientation: SensorOrientation,
+
+    // Amplifier
+    pub amp_gain: f64,
+    pub nonlinearity: f64,
+    pub reset_noise: f64,
+    /// Fraction of the previous pixel's FD level left by an incomplete reset.
+    pub reset_lag: f64,
+    pub amp_glow: f64,
+    /// Random walk of the output baseline, electrons RMS per row time.
+    pub amp_drift: f64,
+    /// Supply sag under the readout current, on top of `spice.supply_droop`.
+    pub supply: SupplyLoad,
+    /// Dark current collected while rows wait to be read, and the amplifier
+    /// heating it and its glow up through the readout.
+    pub thermal: AmpHeating,
+    pub interference: Interference,
+
+    // ADC
+    pub bit_depth: u8,
+    pub cds_mode: CdsMode,
+    pub adc_gain: f64,
+    pub bias: f64,
+    pub dnl_errors: f64,
+    pub bit_errors: f64,
+    /// Sample clock phase noise: standard deviation of each sample's
+    /// position in pixel periods.
+    pub adc_jitter: f64,
+    pub adc_dither: Dither,
+    /// Response to inputs beyond the ADC's full scale.
+    pub adc_overflow: AdcOverflow,
+    /// Bit encoding of the ADC output word.
+    pub adc_encoding: AdcEncoding,
+    /// Decode the output as the wrong encoding.
+    pub adc_wrong_decode: bool,
+    pub compand: Companding,
+    pub dual_gain: DualGain,
+    pub column_adc: ColumnAdc,
+    pub black_clamp: BlackClamp,
+    /// Steady sag of the ADC reference under full-scale samples, as a
+    /// fraction of the code.
+    pub adc_ref_droop: f64,
+    /// Reference recovery time constant in pixels.
+    pub adc_ref_recovery: f64,
+    /// Fractional error of the ADC sample clock against the pixel clock.
+    pub adc_clock_error: f64,
+    /// Resync the sample clock phase at each row, leaving only sub-sample
+    /// shimmer; off, the phase runs free and columns lean.
+    pub adc_line_lock: bool,
+    /// Rescale the image so this percentile of the digitized frame lands at
+    /// `auto_exposure_target` of full scale.
+    pub auto_exposure: bool,
+    pub auto_exposure_percentile: f64,
+    pub auto_exposure_target: f64,
+
+    // Glitch
+    /// Misfiring defect correction: rows/columns patched from neighbors.
+    pub defect_repair_amount: f64,
+    pub defect_repair_mode: RepairMode,
+    pub pixel_shift_amount: f64,
+    pub block_shift_amount: f64,
+    pub scan_line_frequency: f64,
+    /// Run the shift and scan line glitches separately on each CFA plane.
+    pub per_plane_glitch: bool,
+    /// Video signal sync loss and line dropouts, on full rows.
+    pub sync: SyncFaults,
+    pub bit_xor_mask: u16,
+    pub bit_rotation: i32,
+    pub bit_plane_swaps: u32,
+    pub raw_packing: RawPacking,
+    pub raw_bit_offset: u32,
+    pub raw_stride_error: i32,
+    /// Codec blocking and noise on the finished image.
+    pub compression: Compression,
+    /// External plugin stages, run in order after the built-in stages of
+    /// their kind.
+    pub plugins: Vec<PluginSlot>,
+    /// Glitch persistence, drift and decay across frames (`process_temporal`).
+    pub temporal: TemporalParams,
+    /// Strict determinism: bit-identical output from the same parameters
+    /// (`determinism.seed`).
+    pub determinism: Determinism,
+    /// Scan every stage's output for NaN, infinite and negative electron
+    /// values, listing the offenders in the timings.
+    pub validate_buffers: bool,
+    /// Stage groups skipped at runtime with their settings kept.
+    pub bypass: StageBypass,
+    /// Misregistered subframes combined per render (`process`).
+    pub stack: StackParams,
+    /// One independent sensor per color channel.
+    pub three_chip: ThreeChip,
+
+    // Channel
+    /// Color resolution kept after demosaic, as a video digitizer stores it.
+    pub chroma_subsampling: ChromaSubsampling,
+    pub channel_swap: ChannelSwap,
+    pub channel_r_gain: f64,
+    pub channel_g_gain: f64,
+    pub channel_b_gain: f64,
+    pub channel_r_offset: f64,
+    pub channel_g_offset: f64,
+    pub channel_b_offset: f64,
+    pub chromatic_r_x: i32,
+    pub chromatic_r_y: i32,
+    pub chromatic_b_x: i32,
+    pub chromatic_b_y: i32,
+
+    // Color / Output
+    pub bayer_pattern: BayerPattern,
+    pub demosaic_algo: DemosaicAlgo,
+    pub white_balance_r: f64,
+    pub white_balance_g: f64,
+    pub white_balance_b: f64,
+    pub saturation: f64,
+    pub vibrance: f64,
+    /// Hue rotation in degrees.
+    pub hue_rotation: f64,
+    pub gamma: f64,
+    pub brightness: f64,
+    pub contrast: f64,
+    pub tone_curves: ToneCurves,
+    /// Dithering for the final 8-bit conversion.
+    pub output_dither: Dither,
+    /// Unsharp mask after color rendering.
+    pub sharpen: Sharpen,
+    /// Final quantization to a few colors.
+    pub palette: Palettize,
+    /// 3D LUT applied as the final color step.
+    pub output_lut: Option<Arc<Lut3d>>,
+    /// White balance and tones matched back to the source.
+    pub source_match: SourceMatch,
+    /// Blend of the finished render over the untouched source.
+    pub composite: Composite,
+    /// A captured noise print added over the output.
+    pub artifact_layer: ArtifactOverlay,
+    /// Overscan, safe-area markers and OSD text burned into the output.
+    pub frame_overlay: FrameOverlay,
+
+    // SPICE simulation
+    pub spice: crate::spice::SpiceParams,
+}
+
+impl Default for PipelineParams {
+    fn default() -> Self {
+        Self {
+            sensor_width: 3072,
+            sensor_height: 2048,
+            pixel_aspect: 1.0,
+            anamorphic_output: true,
+            full_well: 40_000.0,
+            conversion_gain: 16.0,
+            readout_speed: ReadoutSpeed::Slow,
+            fast_readout: ReadoutProfile::default(),
+            aging: Aging::default(),
+            use_abg: true,
+            fit_mode: FitMode::Letterbox,
+            letterbox_pad: 0.0,
+            area_sum_downscale: false,
+            tile_seamless: false,
+            alpha_mode: AlphaMode::Passthrough,
+            lens_distortion: 0.0,
+            defocus_radius: 0.0,
+            motion_blur: MotionBlur::default(),
+            halation: Halation::default(),
+            scan_skew: 0.0,
+            scan_scale_error: 0.0,
+            scan_wobble: 0.0,
+            scan_wobble_frequency: 4.0,
+            camera_osd: CameraOsd::default(),
+
+            dark_current_rate: 0.0,
+            dark_structure: DarkStructure::default(),
+            long_exposure: LongExposure::default(),
+            read_noise: 0.0,
+            shot_noise_enabled: false,
+            hdr_merge: HdrMerge::default(),
+            noise: NoiseTexture::default(),
+            row_exposure_depth: 0.0,
+            row_exposure: Modulation::default(),
+            flicker: FlickerParams::default(),
+
+            abg_strength: 1.0,
+            bloom_threshold: 0.8,
+            bloom_vertical: true,
+
+            v_cte: 0.999999,
+            v_glitch_rate: 0.0,
+            v_glitch_schedule: GlitchSchedule::default(),
+            saturation_clock_coupling: 0.0,
+            v_phases: ClockPhases::Three,
+            v_waveform_distortion: 0.0,
+            v_modulation: Modulation::default(),
+            parallel_smear: 0.0,
+            line_scan: LineScan::default(),
+            tdi: Tdi::default(),
+
+            h_cte: 0.999999,
+            h_glitch_rate: 0.0,
+            h_ringing: 0.0,
+            readout_direction: ReadoutDirection::LeftToRight,
+            serial_defects: SerialDefects::default(),
+            readout_abort: 1.0,
+            abort_fill: AbortFill::Bias,
+            vertical_direction: VerticalDirection::BottomToTop,
+            sensor_orientation: SensorOrientation::Deg0,
+
+            amp_gain: 1.0,
+            nonlinearity: 0.0,
+            reset_noise: 0.0,
+            reset_lag: 0.0,
+            amp_glow: 0.0,
+            amp_drift: 0.0,
+            supply: SupplyLoad::default(),
+            thermal: AmpHeating::default(),
+            interference: Interference::default(),
+
+            bit_depth: 16,
+            cds_mode: CdsMode::On,
+            adc_gain: 1.0,
+            bias: 0.0,
+            dnl_errors: 0.0,
+            bit_errors: 0.0,
+            adc_jitter: 0.0,
+            adc_dither: Dither::None,
+            adc_overflow: AdcOverflow::Clip,
+            adc_encoding: AdcEncoding::Binary,
+            adc_wrong_decode: false,
+            compand: Companding::default(),
+            dual_gain: DualGain::default(),
+            column_adc: ColumnAdc::default(),
+            black_clamp: BlackClamp::default(),
+            adc_ref_droop: 0.0,
+            adc_ref_recovery: 64.0,
+            adc_clock_error: 0.0,
+            adc_line_lock: true,
+            auto_exposure: false,
+            auto_exposure_percentile: 99.0,
+            auto_exposure_target: 0.9,
+
+            pixel_shift_amount: 0.0,
+            block_shift_amount: 0.0,
+            scan_line_frequency: 0.0,
+            defect_repair_amount: 0.0,
+            defect_repair_mode: RepairMode::Interpolate,
+            per_plane_glitch: false,
+            sync: SyncFaults::default(),
+            bit_xor_mask: 0,
+            bit_rotation: 0,
+            bit_plane_swaps: 0,
+            raw_packing: RawPacking::Off,
+            raw_bit_offset: 0,
+            raw_stride_error: 0,
+            compression: Compression::default(),
+            plugins: Vec::new(),
+            temporal: TemporalParams::default(),
+            determinism: Determinism::default(),
+            validate_buffers: false,
+            bypass: StageBypass::default(),
+            stack: StackParams::default(),
+            three_chip: ThreeChip::default(),
+
+            chroma_subsampling: ChromaSubsampling::None,
+            channel_swap: ChannelSwap::None,
+            channel_r_gain: 1.0,
+            channel_g_gain: 1.0,
+            channel_b_gain: 1.0,
+            channel_r_offset: 0.0,
+            channel_g_offset: 0.0,
+            channel_b_offset: 0.0,
+            chromatic_r_x: 0,
+            chromatic_r_y: 0,
+            chromatic_b_x: 0,
+            chromatic_b_y: 0,
+
+            bayer_pattern: BayerPattern::Rggb,
+            demosaic_algo: DemosaicAlgo::MalvarHeCutler,
+            white_balance_r: 1.0,
+            white_balance_g: 1.0,
+            white_balance_b: 1.0,
+            saturation: 1.0,
+            vibrance: 0.0,
+            hue_rotation: 0.0,
+            gamma: 2.2,
+            brightness: 0.0,
+            contrast: 1.0,
+            tone_curves: ToneCurves::default(),
+            output_dither: Dither::None,
+            sharpen: Sharpen::default(),
+            palette: Palettize::default(),
+            source_match: SourceMatch::default(),
+            composite: Composite::default(),
+            artifact_layer: ArtifactOverlay::default(),
+            frame_overlay: FrameOverlay::default(),
+            output_lut: None,
+
+            spice: crate::spice::SpiceParams::default(),
+        }
+    }
+}
+
+/// A single parameter value, type-erased for comparison and display.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    Float(f64),
+    Int(i64),
+    Bool(bool),
+    Choice(&'static str),
+    /// Loaded data (a curve, LUT, palette or plugin list) that `set_value`
+    /// can't carry: its length and a hash of its contents.
+    Data { len: usize, hash: u64 },
+}
+
+impl std::fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamValue::Float(v) => write!(f, "{}", v),
+            ParamValue::Int(v) => write!(f, "{}", v),
+            ParamValue::Bool(v) => write!(f, "{}", if *v { "on" } else { "off" }),
+            ParamValue::Choice(v) => write!(f, "{}", v),
+            ParamValue::Data { len, hash } => write!(f, "{} #{:08x}", len, *hash as u32),
+        }
+    }
+}
+
+/// FNV-1a over `words`, for `ParamValue::Data`: stable across runs and
+/// cheap enough to run on every `values()` call.
+fn content_hash(words: impl IntoIterator<Item = u64>) -> u64 {
+    words
+        .into_iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, word| (hash ^ word).wrapping_mul(0x100_0000_01b3))
+}
+
+/// `len` entries whose contents are the floats `values`.
+fn data_value<'a>(len: usize, values: impl IntoIterator<Item = &'a f64>) -> ParamValue {
+    let hash = content_hash(values.into_iter().map(|v| v.to_bits()));
+    ParamValue::Data { len, hash }
+}
+
+/// One parameter that differs between two `PipelineParams`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamChange {
+    pub name: &'static str,
+    pub from: ParamValue,
+    pub to: ParamValue,
+}
+
+impl std::fmt::Display for ParamChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.name, self.from, self.to)?;
+        if let ParamValue::Data { .. } = self.to {
+            write!(f, " (not exportable)")?;
+        }
+        Ok(())
+    }
+}
+
+impl PipelineParams {
+    /// The parameters in effect once `aging` has worn the sensor: charge
+    /// transfer loss, dark current, hot pixels and traps raised by the age
+    /// and dose; as set when new.
+    pub fn aged(&self) -> Cow<'_, PipelineParams> {
+        let aging = &self.aging;
+        if aging.is_new() {
+            return Cow::Borrowed(self);
+        }
+        let cte = |cte: f64| (cte - aging.cti()).clamp(0.0, 1.0);
+        Cow::Owned(PipelineParams {
+            v_cte: cte(self.v_cte),
+            h_cte: cte(self.h_cte),
+            dark_current_rate: self.dark_current_rate * aging.dark_scale() + aging.dark_rate(),
+            dark_structure: DarkStructure {
+                hot_pixels: (self.dark_structure.hot_pixels + aging.hot_pixels()).min(1.0),
+                ..self.dark_structure
+            },
+            serial_defects: SerialDefects {
+                count: self.serial_defects.count + aging.serial_traps(),
+                ..self.serial_defects
+            },
+            long_exposure: LongExposure {
+                traps: self.long_exposure.traps + aging.pumped_traps(),
+                ..self.long_exposure
+            },
+            ..self.clone()
+        })
+    }
+
+    /// The parameters the readout speed leaves in effect: as set when slow,
+    /// with noise, charge transfer loss and ringing raised and the bit depth
+    /// capped by `fast_readout` when fast.
+    pub fn at_readout_speed(&self) -> Cow<'_, PipelineParams> {
+        if self.readout_speed == ReadoutSpeed::Slow {
+            return Cow::Borrowed(self);
+        }
+        let fast = &self.fast_readout;
+        let cte = |cte: f64| (1.0 - (1.0 - cte) * fast.cte_loss.max(0.0)).clamp(0.0, 1.0);
+        Cow::Owned(PipelineParams {
+            read_noise: self.read_noise * fast.read_noise.max(0.0),
+            v_cte: cte(self.v_cte),
+            h_cte: cte(self.h_cte),
+            h_ringing: (self.h_ringing + fast.ringing).clamp(0.0, 1.0),
+            bit_depth: self.bit_depth.min(fast.bit_depth.max(1)),
+            ..self.clone()
+        })
+    }
+
+    /// How excess charge spills in `apply_blooming`.
+    pub fn bloom(&self) -> blooming::Bloom {
+        blooming::Bloom {
+            full_well: Electrons(self.full_well),
+            abg_strength: self.abg_strength,
+            threshold: self.bloom_threshold,
+            vertical: self.bloom_vertical,
+        }
+    }
+
+    /// The ADC settings the `Converter` converts with.
+    pub fn adc_params(&self) -> adc::AdcParams {
+        adc::AdcParams {
+            bit_depth: self.bit_depth,
+            cds_mode: self.cds_mode,
+            gain: AdcGain::e_per_adu(self.adc_gain),
+            bias: Adu(self.bias),
+            reset_noise: Electrons(self.reset_noise),
+            dnl_errors: self.dnl_errors,
+            bit_errors: self.bit_errors,
+            dither: self.adc_dither,
+            overflow: self.adc_overflow,
+        }
+    }
+
+    /// The sensor the SPICE circuits are built around.
+    pub fn spice_sensor(&self) -> crate::spice::SpiceSensor {
+        crate::spice::SpiceSensor {
+            full_well: self.full_well,
+            conversion_gain: ConversionGain::uv_per_e(self.conversion_gain),
+            clock_phases: self.v_phases.count(),
+        }
+    }
+
+    /// All parameters as (name, value) pairs, in UI section order.
+    /// SPICE parameters are prefixed with `spice.`. Loaded curves, LUTs,
+    /// palettes and plugins are listed as `Data`, so `diff` sees edits to them.
+    pub fn values(&self) -> Vec<(&'static str, ParamValue)> {
+        use ParamValue::*;
+        let tone_curve =
+            |c: &spectral::ToneCurve| data_value(c.points.len(), c.points.iter().flatten());
+        let s = &self.spice;
+        vec![
+            ("sensor_width", Int(self.sensor_width as i64)),
+            ("sensor_height", Int(self.sensor_height as i64)),
+            ("pixel_aspect", Float(self.pixel_aspect)),
+            ("anamorphic_output", Bool(self.anamorphic_output)),
+            ("full_well", Float(self.full_well)),
+            ("conversion_gain", Float(self.conversion_gain)),
+            ("readout_speed", Choice(self.readout_speed.name())),
+            ("fast_readout.read_noise", Float(self.fast_readout.read_noise)),
+            ("fast_readout.cte_loss", Float(self.fast_readout.cte_loss)),
+            ("fast_readout.ringing", Float(self.fast_readout.ringing)),
+            ("fast_readout.bit_depth", Int(self.fast_readout.bit_depth as i64)),
+            ("aging.age_years", Float(self.aging.age_years)),
+            ("aging.radiation_dose", Float(self.aging.radiation_dose)),
+            ("use_abg", Bool(self.use_abg)),
+            ("fit_mode", Choice(self.fit_mode.name())),
+            ("letterbox_pad", Float(self.letterbox_pad)),
+            ("area_sum_downscale", Bool(self.area_sum_downscale)),
+            ("tile_seamless", Bool(self.tile_seamless)),
+            ("alpha_mode", Choice(self.alpha_mode.name())),
+            ("lens_distortion", Float(self.lens_distortion)),
+            ("defocus_radius", Float(self.defocus_radius)),
+            ("motion_blur.length", Float(self.motion_blur.length)),
+            ("motion_blur.angle", Float(self.motion_blur.angle)),
+            ("motion_blur.trajectory", Choice(self.motion_blur.trajectory.name())),
+            ("motion_blur.pivot_x", Float(self.motion_blur.pivot.0)),
+            ("motion_blur.pivot_y", Float(self.motion_blur.pivot.1)),
+            ("halation.strength", Float(self.halation.strength)),
+            ("halation.threshold", Float(self.halation.threshold)),
+            ("halation.radius", Float(self.halation.radius)),
+            ("halation.tint_r", Float(self.halation.tint[0])),
+            ("halation.tint_g", Float(self.halation.tint[1])),
+            ("halation.tint_b", Float(self.halation.tint[2])),
+            ("scan_skew", Float(self.scan_skew)),
+            ("scan_scale_error", Float(self.scan_scale_error)),
+            ("scan_wobble", Float(self.scan_wobble)),
+            ("scan_wobble_frequency", Float(self.scan_wobble_frequency)),
+            ("camera_osd.date", Bool(self.camera_osd.date)),
+            ("camera_osd.year", Int(self.camera_osd.year as i64)),
+            ("camera_osd.month", Int(self.camera_osd.month as i64)),
+            ("camera_osd.day", Int(self.camera_osd.day as i64)),
+            ("camera_osd.battery", Bool(self.camera_osd.battery)),
+            ("camera_osd.battery_level", Float(self.camera_osd.battery_level)),
+            ("camera_osd.rec", Bool(self.camera_osd.rec)),
+            ("camera_osd.brightness", Float(self.camera_osd.brightness)),
+            ("dark_current_rate", Float(self.dark_current_rate)),
+            ("dark_structure.edge_glow", Float(self.dark_structure.edge_glow)),
+            ("dark_structure.edge_width", Float(self.dark_structure.edge_width)),
+            ("dark_structure.clusters", Int(self.dark_structure.clusters as i64)),
+            ("dark_structure.cluster_gain", Float(self.dark_structure.cluster_gain)),
+            ("dark_structure.cluster_radius", Float(self.dark_structure.cluster_radius)),
+            ("dark_structure.rings", Float(self.dark_structure.rings)),
+            ("dark_structure.ring_period", Float(self.dark_structure.ring_period)),
+            ("dark_structure.hot_pixels", Float(self.dark_structure.hot_pixels)),
+            ("dark_structure.seed", Int(self.dark_structure.seed as i64)),
+            ("long_exposure.slices", Int(self.long_exposure.slices as i64)),
+            ("long_exposure.cosmic_rate", Float(self.long_exposure.cosmic_rate)),
+            ("long_exposure.clock_glitch_rate", Float(self.long_exposure.clock_glitch_rate)),
+            ("long_exposure.traps", Int(self.long_exposure.traps as i64)),
+            ("long_exposure.pump_charge", Float(self.long_exposure.pump_charge)),
+            ("long_exposure.seed", Int(self.long_exposure.seed as i64)),
+            ("read_noise", Float(self.read_noise)),
+            ("shot_noise_enabled", Bool(self.shot_noise_enabled)),
+            ("hdr_merge.enabled", Bool(self.hdr_merge.enabled)),
+            ("hdr_merge.exposures", Int(self.hdr_merge.exposures as i64)),
+            ("hdr_merge.ev_step", Float(self.hdr_merge.ev_step)),
+            ("hdr_merge.boost", Float(self.hdr_merge.boost)),
+            ("noise.source", Choice(self.noise.source.name())),
+            ("noise.seed", Int(self.noise.seed as i64)),
+            ("noise.scale", Float(self.noise.scale)),
+            ("row_exposure_depth", Float(self.row_exposure_depth)),
+            ("row_exposure.shape", Choice(self.row_exposure.shape.name())),
+            ("row_exposure.frequency", Float(self.row_exposure.frequency)),
+            ("row_exposure.phase", Float(self.row_exposure.phase)),
+            (
+                "row_exposure.curve_points",
+                data_value(self.row_exposure.curve.len(), &self.row_exposure.curve),
+            ),
+            ("flicker.depth", Float(self.flicker.depth)),
+            ("flicker.frequency_hz", Float(self.flicker.frequency_hz)),
+            ("flicker.duty_cycle", Float(self.flicker.duty_cycle)),
+            ("flicker.phase", Float(self.flicker.phase)),
+            ("flicker.exposure_ms", Float(self.flicker.exposure_ms)),
+            ("flicker.row_time_us", Float(self.flicker.row_time_us)),
+            ("flicker.shutter", Choice(self.flicker.shutter.name())),
+            ("abg_strength", Float(self.abg_strength)),
+            ("bloom_threshold", Float(self.bloom_threshold)),
+            ("bloom_vertical", Bool(self.bloom_vertical)),
+            ("v_phases", Choice(self.v_phases.name())),
+            ("v_cte", Float(self.v_cte)),
+            ("v_glitch_rate", Float(self.v_glitch_rate)),
+            ("v_glitch_schedule.mode", Choice(self.v_glitch_schedule.mode.name())),
+            ("v_glitch_schedule.burst_length", Int(self.v_glitch_schedule.burst_length as i64)),
+            ("v_glitch_schedule.period", Int(self.v_glitch_schedule.period as i64)),
+            ("v_glitch_schedule.offset", Int(self.v_glitch_schedule.offset as i64)),
+            ("v_glitch_schedule.seed", Int(self.v_glitch_schedule.seed as i64)),
+            ("saturation_clock_coupling", Float(self.saturation_clock_coupling)),
+            ("v_waveform_distortion", Float(self.v_waveform_distortion)),
+            ("v_modulation.shape", Choice(self.v_modulation.shape.name())),
+            ("v_modulation.frequency", Float(self.v_modulation.frequency)),
+            ("v_modulation.phase", Float(self.v_modulation.phase)),
+            (
+                "v_modulation.curve_points",
+                data_value(self.v_modulation.curve.len(), &self.v_modulation.curve),
+            ),
+            ("parallel_smear", Float(self.parallel_smear)),
+            ("line_scan.enabled", Bool(self.line_scan.enabled)),
+            ("line_scan.speed", Float(self.line_scan.speed)),
+            ("line_scan.wobble", Float(self.line_scan.wobble)),
+            ("line_scan.wobble_frequency", Float(self.line_scan.wobble_frequency)),
+            ("line_scan.exposure_jitter", Float(self.line_scan.exposure_jitter)),
+            ("line_scan.line_noise", Float(self.line_scan.line_noise)),
+            ("tdi.enabled", Bool(self.tdi.enabled)),
+            ("tdi.stages", Int(self.tdi.stages as i64)),
+            ("tdi.sync_error", Float(self.tdi.sync_error)),
+            ("tdi.drift", Float(self.tdi.drift)),
+            ("h_cte", Float(self.h_cte)),
+            ("h_glitch_rate", Float(self.h_glitch_rate)),
+            ("h_ringing", Float(self.h_ringing)),
+            ("readout_direction", Choice(self.readout_direction.name())),
+            ("serial_defects.count", Int(self.serial_defects.count as i64)),
+            ("serial_defects.trap_cte", Float(self.serial_defects.trap_cte)),
+            ("serial_defects.block_level", Float(self.serial_defects.block_level)),
+            ("serial_defects.seed", Int(self.serial_defects.seed as i64)),
+            ("readout_abort", Float(self.readout_abort)),
+            ("abort_fill", Choice(self.abort_fill.name())),
+            ("vertical_direction", Choice(self.vertical_direction.name())),
+            ("sensor_orientation", Choice(self.sensor_orientation.name())),
+            ("amp_gain", Float(self.amp_gain)),
+            ("nonlinearity", Float(self.nonlinearity)),
+            ("reset_noise", Float(self.reset_noise)),
+            ("reset_lag", Float(self.reset_lag)),
+            ("amp_glow", Float(self.amp_glow)),
+            ("amp_drift", Float(self.amp_drift)),
+            ("supply.load", Float(self.supply.load)),
+            ("supply.recovery", Float(self.supply.recovery)),
+            ("thermal.readout_dark", Float(self.thermal.readout_dark)),
+            ("thermal.heating", Float(self.thermal.heating)),
+            ("thermal.diffusion", Float(self.thermal.diffusion)),
+            ("interference.amplitude", Float(self.interference.amplitude)),
+            ("interference.frequency", Float(self.interference.frequency)),
+            ("interference.jitter", Float(self.interference.jitter)),
+            ("interference.locked", Bool(self.interference.locked)),
+            ("bit_depth", Int(self.bit_depth as i64)),
+            ("cds_mode", Choice(self.cds_mode.name())),
+            ("adc_gain", Float(self.adc_gain)),
+            ("bias", Float(self.bias)),
+            ("dnl_errors", Float(self.dnl_errors)),
+            ("bit_errors", Float(self.bit_errors)),
+            ("adc_jitter", Float(self.adc_jitter)),
+            ("adc_dither", Choice(self.adc_dither.name())),
+            ("adc_overflow", Choice(self.adc_overflow.name())),
+            ("adc_encoding", Choice(self.adc_encoding.name())),
+            ("adc_wrong_decode", Bool(self.adc_wrong_decode)),
+            ("compand.knees", Int(self.compand.knees as i64)),
+            ("compand.ratio", Float(self.compand.ratio)),
+            ("compand.mismatch", Float(self.compand.mismatch)),
+            ("dual_gain.enabled", Bool(self.dual_gain.enabled)),
+            ("dual_gain.ratio", Float(self.dual_gain.ratio)),
+            ("dual_gain.threshold", Float(self.dual_gain.threshold)),
+            ("dual_gain.stitch_error", Float(self.dual_gain.stitch_error)),
+            ("column_adc.enabled", Bool(self.column_adc.enabled)),
+            ("column_adc.offset", Float(self.column_adc.offset)),
+            ("column_adc.gain", Float(self.column_adc.gain)),
+            ("column_adc.dead_rate", Float(self.column_adc.dead_rate)),
+            ("column_adc.seed", Int(self.column_adc.seed as i64)),
+            ("black_clamp.enabled", Bool(self.black_clamp.enabled)),
+            ("black_clamp.loop_gain", Float(self.black_clamp.loop_gain)),
+            ("black_clamp.leak", Float(self.black_clamp.leak)),
+            ("black_clamp.noise", Float(self.black_clamp.noise)),
+            ("adc_ref_droop", Float(self.adc_ref_droop)),
+            ("adc_ref_recovery", Float(self.adc_ref_recovery)),
+            ("adc_clock_error", Float(self.adc_clock_error)),
+            ("adc_line_lock", Bool(self.adc_line_lock)),
+            ("auto_exposure", Bool(self.auto_exposure)),
+            ("auto_exposure_percentile", Float(self.auto_exposure_percentile)),
+            ("auto_exposure_target", Float(self.auto_exposure_target)),
+            ("pixel_shift_amount", Float(self.pixel_shift_amount)),
+            ("block_shift_amount", Float(self.block_shift_amount)),
+            ("scan_line_frequency", Float(self.scan_line_frequency)),
+            ("defect_repair_amount", Float(self.defect_repair_amount)),
+            ("defect_repair_mode", Choice(self.defect_repair_mode.name())),
+            ("per_plane_glitch", Bool(self.per_plane_glitch)),
+            ("sync.loss_rate", Float(self.sync.loss_rate)),
+            ("sync.loss_length", Float(self.sync.loss_length)),
+            ("sync.dropout_rate", Float(self.sync.dropout_rate)),
+            ("sync.dropout_length", Float(self.sync.dropout_length)),
+            ("sync.burst", Choice(self.sync.burst.name())),
+            ("bit_xor_mask", Int(self.bit_xor_mask as i64)),
+            ("bit_rotation", Int(self.bit_rotation as i64)),
+            ("bit_plane_swaps", Int(self.bit_plane_swaps as i64)),
+            ("raw_packing", Choice(self.raw_packing.name())),
+            ("raw_bit_offset", Int(self.raw_bit_offset as i64)),
+            ("raw_stride_error", Int(self.raw_stride_error as i64)),
+            ("compression.enabled", Bool(self.compression.enabled)),
+            ("compression.quality", Float(self.compression.quality)),
+            ("compression.mosquito", Float(self.compression.mosquito)),
+            ("compression.dc_flicker", Float(self.compression.dc_flicker)),
+            ("compression.block_smear", Float(self.compression.block_smear)),
+            (
+                "plugin_stages",
+                Data {
+                    len: self.plugins.len(),
+                    hash: content_hash(self.plugins.iter().flat_map(|slot| {
+                        let path = slot.plugin.path.as_os_str().as_encoded_bytes();
+                        let path = content_hash(path.iter().map(|&b| b as u64));
+                        [path, slot.amount.to_bits(), slot.seed]
+                    })),
+                },
+            ),
+            ("temporal.persistence", Int(self.temporal.persistence as i64)),
+            ("temporal.drift", Float(self.temporal.drift)),
+            ("temporal.decay", Float(self.temporal.decay)),
+            ("determinism.strict", Bool(self.determinism.strict)),
+            ("determinism.seed", Int(self.determinism.seed as i64)),
+            ("validate_buffers", Bool(self.validate_buffers)),
+            ("bypass.optics", Bool(self.bypass.contains(StageBypass::OPTICS))),
+            ("bypass.exposure_noise", Bool(self.bypass.contains(StageBypass::EXPOSURE_NOISE))),
+            ("bypass.blooming", Bool(self.bypass.contains(StageBypass::BLOOMING))),
+            ("bypass.v_clock", Bool(self.bypass.contains(StageBypass::V_CLOCK))),
+            ("bypass.h_clock", Bool(self.bypass.contains(StageBypass::H_CLOCK))),
+            ("bypass.amplifier", Bool(self.bypass.contains(StageBypass::AMPLIFIER))),
+            ("bypass.adc", Bool(self.bypass.contains(StageBypass::ADC))),
+            ("bypass.glitch", Bool(self.bypass.contains(StageBypass::GLITCH))),
+            ("bypass.channel", Bool(self.bypass.contains(StageBypass::CHANNEL))),
+            ("bypass.color", Bool(self.bypass.contains(StageBypass::COLOR))),
+            ("stack.frames", Int(self.stack.frames as i64)),
+            ("stack.combiner", Choice(self.stack.combiner.name())),
+            ("stack.translate_jitter", Float(self.stack.translate_jitter)),
+            ("stack.rotate_jitter", Float(self.stack.rotate_jitter)),
+            ("stack.drizzle", Bool(self.stack.drizzle)),
+            ("stack.pixfrac", Float(self.stack.pixfrac)),
+            ("stack.drizzle_scale", Float(self.stack.drizzle_scale)),
+            ("three_chip.enabled", Bool(self.three_chip.enabled)),
+            ("three_chip.red_dx", Float(self.three_chip.red.dx)),
+            ("three_chip.red_dy", Float(self.three_chip.red.dy)),
+            ("three_chip.red_rotation", Float(self.three_chip.red.rotation)),
+            ("three_chip.red_skew", Int(self.three_chip.red.skew)),
+            ("three_chip.blue_dx", Float(self.three_chip.blue.dx)),
+            ("three_chip.blue_dy", Float(self.three_chip.blue.dy)),
+            ("three_chip.blue_rotation", Float(self.three_chip.blue.rotation)),
+            ("three_chip.blue_skew", Int(self.three_chip.blue.skew)),
+            ("chroma_subsampling", Choice(self.chroma_subsampling.name())),
+            ("channel_swap", Choice(self.channel_swap.name())),
+            ("channel_r_gain", Float(self.channel_r_gain)),
+            ("channel_g_gain", Float(self.channel_g_gain)),
+            ("channel_b_gain", Float(self.channel_b_gain)),
+            ("channel_r_offset", Float(self.channel_r_offset)),
+            ("channel_g_offset", Float(self.channel_g_offset)),
+            ("channel_b_offset", Float(self.channel_b_offset)),
+            ("chromatic_r_x", Int(self.chromatic_r_x as i64)),
+            ("chromatic_r_y", Int(self.chromatic_r_y as i64)),
+            ("chromatic_b_x", Int(self.chromatic_b_x as i64)),
+            ("chromatic_b_y", Int(self.chromatic_b_y as i64)),
+            ("bayer_pattern", Choice(self.bayer_pattern.name())),
+            ("demosaic_algo", Choice(self.demosaic_algo.name())),
+            ("white_balance_r", Float(self.white_balance_r)),
+            ("white_balance_g", Float(self.white_balance_g)),
+            ("white_balance_b", Float(self.white_balance_b)),
+            ("saturation", Float(self.saturation)),
+            ("vibrance", Float(self.vibrance)),
+            ("hue_rotation", Float(self.hue_rotation)),
+            ("gamma", Float(self.gamma)),
+            ("brightness", Float(self.brightness)),
+            ("contrast", Float(self.contrast)),
+            ("tone_curve.master_points", tone_curve(&self.tone_curves.master)),
+            ("tone_curve.red_points", tone_curve(&self.tone_curves.red)),
+            ("tone_curve.green_points", tone_curve(&self.tone_curves.green)),
+            ("tone_curve.blue_points", tone_curve(&self.tone_curves.blue)),
+            ("output_dither", Choice(self.output_dither.name())),
+            ("sharpen.amount", Float(self.sharpen.amount)),
+            ("sharpen.radius", Float(self.sharpen.radius)),
+            ("sharpen.threshold", Float(self.sharpen.threshold)),
+            ("sharpen.halo", Bool(self.sharpen.halo)),
+            ("palette.mode", Choice(self.palette.mode.name())),
+            ("palette.levels", Int(self.palette.levels as i64)),
+            ("palette.colors", Int(self.palette.colors as i64)),
+            ("palette.dither", Choice(self.palette.dither.name())),
+            (
+                "palette.custom_size",
+                match &self.palette.custom {
+                    Some(p) => data_value(p.colors.len(), p.colors.iter().flatten()),
+                    None => data_value(0, []),
+                },
+            ),
+            (
+                "output_lut_size",
+                match &self.output_lut {
+                    Some(lut) => {
+                        let domain = lut.domain_min.iter().chain(&lut.domain_max);
+                        data_value(lut.size, domain.chain(lut.data.iter().flatten()))
+                    }
+                    None => data_value(0, []),
+                },
+            ),
+            ("source_match.white_balance", Bool(self.source_match.white_balance)),
+            ("source_match.tonality", Bool(self.source_match.tonality)),
+            ("source_match.strength", Float(self.source_match.strength)),
+            ("composite.mode", Choice(self.composite.mode.name())),
+            ("composite.opacity", Float(self.composite.opacity)),
+            ("artifact_layer.strength", Float(self.artifact_layer.strength)),
+            ("frame_overlay.overscan", Float(self.frame_overlay.overscan)),
+            ("frame_overlay.safe_area", Bool(self.frame_overlay.safe_area)),
+            ("frame_overlay.timestamp", Bool(self.frame_overlay.timestamp)),
+            ("frame_overlay.clock_start", Float(self.frame_overlay.clock_start)),
+            ("frame_overlay.rec", Bool(self.frame_overlay.rec)),
+            ("spice.mode", Choice(s.mode.name())),
+            ("spice.vdd", Float(s.vdd)),
+            ("spice.clock_freq_mhz", Float(s.clock_freq_mhz)),
+            ("spice.temperature_k", Float(s.temperature_k)),
+            ("spice.shift_register_stages", Int(s.shift_register_stages as i64)),
+            ("spice.transfer_function_resolution", Int(s.transfer_function_resolution as i64)),
+            ("spice.supply_droop", Float(s.supply_droop)),
+            ("spice.phase_overlap_ns", Float(s.phase_overlap_ns)),
+            ("spice.missing_pulse_rate", Float(s.missing_pulse_rate)),
+            ("spice.charge_injection", Float(s.charge_injection)),
+            ("spice.substrate_noise", Float(s.substrate_noise)),
+        ]
+    }
+
+    /// Set a parameter by its `values()` name from a string.
+    ///
+    /// Numbers parse as their field type, booleans accept `on`/`off`,
+    /// `true`/`false` or `1`/`0`, and choices match their display name
+    /// case-insensitively.
+    pub fn set_value(&mut self, name: &str, value: &str) -> Result<(), String> {
+        fn num<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+            value
+                .trim()
+                .parse::<T>()
+                .map_err(|_| format!("Invalid value for {name}: {value}"))
+        }
+        fn flag(name: &str, value: &str) -> Result<bool, String> {
+            match value.trim().to_ascii_lowercase().as_str() {
+                "on" | "true" | "1" => Ok(true),
+                "off" | "false" | "0" => Ok(false),
+                _ => Err(format!("Invalid value for {name}: {value}")),
+            }
+        }
+        fn choice<T: Copy>(
+            name: &str,
+            value: &str,
+            all: &[T],
+            label: impl Fn(T) -> &'static str,
+        ) -> Result<T, String> {
+            all.iter()
+                .copied()
+                .find(|&v| label(v).eq_ignore_ascii_case(value.trim()))
+                .ok_or_else(|| {
+                    let options: Vec<_> = all.iter().map(|&v| label(v)).collect();
+                    format!("Invalid value for {name}: {value} (expected one of: {})", options.join(", "))
+                })
+        }
+
+        let s = &mut self.spice;
+        match name {
+            "sensor_width" => self.sensor_width = num(name, value)?,
+            "sensor_height" => self.sensor_height = num(name, value)?,
+            "pixel_aspect" => self.pixel_aspect = num(name, value)?,
+            "anamorphic_output" => self.anamorphic_output = flag(name, value)?,
+            "full_well" => self.full_well = num(name, value)?,
+            "conversion_gain" => self.conversion_gain = num(name, value)?,
+            "readout_speed" => {
+                self.readout_speed = choice(name, value, ReadoutSpeed::ALL, |v| v.name())?
+            }
+            "fast_readout.read_noise" => self.fast_readout.read_noise = num(name, value)?,
+            "fast_readout.cte_loss" => self.fast_readout.cte_loss = num(name, value)?,
+            "fast_readout.ringing" => self.fast_readout.ringing = num(name, value)?,
+            "fast_readout.bit_depth" => self.fast_readout.bit_depth = num(name, value)?,
+            "aging.age_years" => self.aging.age_years = num(name, value)?,
+            "aging.radiation_dose" => self.aging.radiation_dose = num(name, value)?,
+            "use_abg" => self.use_abg = flag(name, value)?,
+            "fit_mode" => self.fit_mode = choice(name, value, FitMode::ALL, |v| v.name())?,
+            "letterbox_pad" => self.letterbox_pad = num(name, value)?,
+            "area_sum_downscale" => self.area_sum_downscale = flag(name, value)?,
+            "tile_seamless" => self.tile_seamless = flag(name, value)?,
+            "alpha_mode" => self.alpha_mode = choice(name, value, AlphaMode::ALL, |v| v.name())?,
+            "lens_distortion" => self.lens_distortion = num(name, value)?,
+            "defocus_radius" => self.defocus_radius = num(name, value)?,
+            "motion_blur.length" => self.motion_blur.length = num(name, value)?,
+            "motion_blur.angle" => self.motion_blur.angle = num(name, value)?,
+            "motion_blur.trajectory" => {
+                self.motion_blur.trajectory = choice(name, value, Trajectory::ALL, |v| v.name())?
+            }
+            "motion_blur.pivot_x" => self.motion_blur.pivot.0 = num(name, value)?,
+            "motion_blur.pivot_y" => self.motion_blur.pivot.1 = num(name, value)?,
+            "halation.strength" => self.halation.strength = num(name, value)?,
+            "halation.threshold" => self.halation.threshold = num(name, value)?,
+            "halation.radius" => self.halation.radius = num(name, value)?,
+            "halation.tint_r" => self.halation.tint[0] = num(name, value)?,
+            "halation.tint_g" => self.halation.tint[1] = num(name, value)?,
+            "halation.tint_b" => self.halation.tint[2] = num(name, value)?,
+            "scan_skew" => self.scan_skew = num(name, value)?,
+            "scan_scale_error" => self.scan_scale_error = num(name, value)?,
+            "scan_wobble" => self.scan_wobble = num(name, value)?,
+            "scan_wobble_frequency" => self.scan_wobble_frequency = num(name, value)?,
+            "camera_osd.date" => self.camera_osd.date = flag(name, value)?,
+            "camera_osd.year" => self.camera_osd.year = num(name, value)?,
+            "camera_osd.month" => self.camera_osd.month = num(name, value)?,
+            "camera_osd.day" => self.camera_osd.day = num(name, value)?,
+            "camera_osd.battery" => self.camera_osd.battery = flag(name, value)?,
+            "camera_osd.battery_level" => self.camera_osd.battery_level = num(name, value)?,
+            "camera_osd.rec" => self.camera_osd.rec = flag(name, value)?,
+            "camera_osd.brightness" => self.camera_osd.brightness = num(name, value)?,
+            "dark_current_rate" => self.dark_current_rate = num(name, value)?,
+            "dark_structure.edge_glow" => self.dark_structure.edge_glow = num(name, value)?,
+            "dark_structure.edge_width" => self.dark_structure.edge_width = num(name, value)?,
+            "dark_structure.clusters" => self.dark_structure.clusters = num(name, value)?,
+            "dark_structure.cluster_gain" => self.dark_structure.cluster_gain = num(name, value)?,
+            "dark_structure.cluster_radius" => {
+                self.dark_structure.cluster_radius = num(name, value)?
+            }
+            "dark_structure.rings" => self.dark_structure.rings = num(name, value)?,
+            "dark_structure.ring_period" => self.dark_structure.ring_period = num(name, value)?,
+            "dark_structure.hot_pixels" => self.dark_structure.hot_pixels = num(name, value)?,
+            "dark_structure.seed" => self.dark_structure.seed = num(name, value)?,
+            "long_exposure.slices" => self.long_exposure.slices = num(name, value)?,
+            "long_exposure.cosmic_rate" => self.long_exposure.cosmic_rate = num(name, value)?,
+            "long_exposure.clock_glitch_rate" => {
+                self.long_exposure.clock_glitch_rate = num(name, value)?
+            }
+            "long_exposure.traps" => self.long_exposure.traps = num(name, value)?,
+            "long_exposure.pump_charge" => self.long_exposure.pump_charge = num(name, value)?,
+            "long_exposure.seed" => self.long_exposure.seed = num(name, value)?,
+            "read_noise" => self.read_noise = num(name, value)?,
+            "shot_noise_enabled" => self.shot_noise_enabled = flag(name, value)?,
+            "hdr_merge.enabled" => self.hdr_merge.enabled = flag(name, value)?,
+            "hdr_merge.exposures" => self.hdr_merge.exposures = num(name, value)?,
+            "hdr_merge.ev_step" => self.hdr_merge.ev_step = num(name, value)?,
+            "hdr_merge.boost" => self.hdr_merge.boost = num(name, value)?,
+            "noise.source" => {
+                self.noise.source = choice(name, value, NoiseSource::ALL, |v| v.name())?
+            }
+            "noise.seed" => self.noise.seed = num(name, value)?,
+            "noise.scale" => self.noise.scale = num(name, value)?,
+            "row_exposure_depth" => self.row_exposure_depth = num(name, value)?,
+            "row_exposure.shape" => {
+                self.row_exposure.shape = choice(name, value, ModulationShape::ALL, |v| v.name())?
+            }
+            "row_exposure.frequency" => self.row_exposure.frequency = num(name, value)?,
+            "row_exposure.phase" => self.row_exposure.phase = num(name, value)?,
+            "row_exposure.curve_points" => {
+                return Err(format!("{name} is read-only; load a curve instead"))
+            }
+            "flicker.depth" => self.flicker.depth = num(name, value)?,
+            "flicker.frequency_hz" => self.flicker.frequency_hz = num(name, value)?,
+            "flicker.duty_cycle" => self.flicker.duty_cycle = num(name, value)?,
+            "flicker.phase" => self.flicker.phase = num(name, value)?,
+            "flicker.exposure_ms" => self.flicker.exposure_ms = num(name, value)?,
+            "flicker.row_time_us" => self.flicker.row_time_us = num(name, value)?,
+            "flicker.shutter" => {
+                self.flicker.shutter = choice(name, value, ShutterMode::ALL, |v| v.name())?
+            }
+            "abg_strength" => self.abg_strength = num(name, value)?,
+            "bloom_threshold" => self.bloom_threshold = num(name, value)?,
+            "bloom_vertical" => self.bloom_vertical = flag(name, value)?,
+            "v_phases" => self.v_phases = choice(name, value, ClockPhases::ALL, |v| v.name())?,
+            "v_cte" => self.v_cte = num(name, value)?,
+            "v_glitch_rate" => self.v_glitch_rate = num(name, value)?,
+            "v_glitch_schedule.mode" => {
+                self.v_glitch_schedule.mode = choice(name, value, ScheduleMode::ALL, |v| v.name())?
+            }
+            "v_glitch_schedule.burst_length" => self.v_glitch_schedule.burst_length = num(name, value)?,
+            "v_glitch_schedule.period" => self.v_glitch_schedule.period = num(name, value)?,
+            "v_glitch_schedule.offset" => self.v_glitch_schedule.offset = num(name, value)?,
+            "v_glitch_schedule.seed" => self.v_glitch_schedule.seed = num(name, value)?,
+            "saturation_clock_coupling" => self.saturation_clock_coupling = num(name, value)?,
+            "v_waveform_distortion" => self.v_waveform_distortion = num(name, value)?,
+            "v_modulation.shape" => {
+                self.v_modulation.shape = choice(name, value, ModulationShape::ALL, |v| v.name())?
+            }
+            "v_modulation.frequency" => self.v_modulation.frequency = num(name, value)?,
+            "v_modulation.phase" => self.v_modulation.phase = num(name, value)?,
+            "v_modulation.curve_points" => return Err(format!("{name} is read-only; load a curve instead")),
+            "parallel_smear" => self.parallel_smear = num(name, value)?,
+            "line_scan.enabled" => self.line_scan.enabled = flag(name, value)?,
+            "line_scan.speed" => self.line_scan.speed = num(name, value)?,
+            "line_scan.wobble" => self.line_scan.wobble = num(name, value)?,
+            "line_scan.wobble_frequency" => self.line_scan.wobble_frequency = num(name, value)?,
+            "line_scan.exposure_jitter" => self.line_scan.exposure_jitter = num(name, value)?,
+            "line_scan.line_noise" => self.line_scan.line_noise = num(name, value)?,
+            "tdi.enabled" => self.tdi.enabled = flag(name, value)?,
+            "tdi.stages" => self.tdi.stages = num(name, value)?,
+            "tdi.sync_error" => self.tdi.sync_error = num(name, value)?,
+            "tdi.drift" => self.tdi.drift = num(name, value)?,
+            "h_cte" => self.h_cte = num(name, value)?,
+            "h_glitch_rate" => self.h_glitch_rate = num(name, value)?,
+            "h_ringing" => self.h_ringing = num(name, value)?,
+            "readout_direction" => {
+                self.readout_direction = choice(name, value, ReadoutDirection::ALL, |v| v.name())?
+            }
+            "serial_defects.count" => self.serial_defects.count = num(name, value)?,
+            "serial_defects.trap_cte" => self.serial_defects.trap_cte = num(name, value)?,
+            "serial_defects.block_level" => self.serial_defects.block_level = num(name, value)?,
+            "serial_defects.seed" => self.serial_defects.seed = num(name, value)?,
+            "readout_abort" => self.readout_abort = num(name, value)?,
+            "abort_fill" => self.abort_fill = choice(name, value, AbortFill::ALL, |v| v.name())?,
+            "vertical_direction" => {
+                self.vertical_direction = choice(name, value, VerticalDirection::ALL, |v| v.name())?
+            }
+            "sensor_orientation" => {
+                self.sensor_orientation = choice(name, value, SensorOrientation::ALL, |v| v.name())?
+            }
+            "amp_gain" => self.amp_gain = num(name, value)?,
+            "nonlinearity" => self.nonlinearity = num(name, value)?,
+            "reset_noise" => self.reset_noise = num(name, value)?,
+            "reset_lag" => self.reset_lag = num(name, value)?,
+            "amp_glow" => self.amp_glow = num(name, value)?,
+            "amp_drift" => self.amp_drift = num(name, value)?,
+            "supply.load" => self.supply.load = num(name, value)?,
+            "supply.recovery" => self.supply.recovery = num(name, value)?,
+            "thermal.readout_dark" => self.thermal.readout_dark = num(name, value)?,
+            "thermal.heating" => self.thermal.heating = num(name, value)?,
+            "thermal.diffusion" => self.thermal.diffusion = num(name, value)?,
+            "interference.amplitude" => self.interference.amplitude = num(name, value)?,
+            "interference.frequency" => self.interference.frequency = num(name, value)?,
+            "interference.jitter" => self.interference.jitter = num(name, value)?,
+            "interference.locked" => self.interference.locked = flag(name, value)?,
+            "bit_depth" => self.bit_depth = num(name, value)?,
+            "cds_mode" => self.cds_mode = choice(name, value, CdsMode::ALL, |v| v.name())?,
+            "adc_gain" => self.adc_gain = num(name, value)?,
+            "bias" => self.bias = num(name, value)?,
+            "dnl_errors" => self.dnl_errors = num(name, value)?,
+            "bit_errors" => self.bit_errors = num(name, value)?,
+            "adc_jitter" => self.adc_jitter = num(name, value)?,
+            "adc_dither" => self.adc_dither = choice(name, value, Dither::ALL, |v| v.name())?,
+            "adc_overflow" => {
+                self.adc_overflow = choice(name, value, AdcOverflow::ALL, |v| v.name())?
+            }
+            "adc_encoding" => {
+                self.adc_encoding = choice(name, value, AdcEncoding::ALL, |v| v.name())?
+            }
+            "adc_wrong_decode" => self.adc_wrong_decode = flag(name, value)?,
+            "compand.knees" => self.compand.knees = num(name, value)?,
+            "compand.ratio" => self.compand.ratio = num(name, value)?,
+            "compand.mismatch" => self.compand.mismatch = num(name, value)?,
+            "dual_gain.enabled" => self.dual_gain.enabled = flag(name, value)?,
+            "dual_gain.ratio" => self.dual_gain.ratio = num(name, value)?,
+            "dual_gain.threshold" => self.dual_gain.threshold = num(name, value)?,
+            "dual_gain.stitch_error" => self.dual_gain.stitch_error = num(name, value)?,
+            "column_adc.enabled" => self.column_adc.enabled = flag(name, value)?,
+            "column_adc.offset" => self.column_adc.offset = num(name, value)?,
+            "column_adc.gain" => self.column_adc.gain = num(name, value)?,
+            "column_adc.dead_rate" => self.column_adc.dead_rate = num(name, value)?,
+            "column_adc.seed" => self.column_adc.seed = num(name, value)?,
+            "black_clamp.enabled" => self.black_clamp.enabled = flag(name, value)?,
+            "black_clamp.loop_gain" => self.black_clamp.loop_gain = num(name, value)?,
+            "black_clamp.leak" => self.black_clamp.leak = num(name, value)?,
+            "black_clamp.noise" => self.black_clamp.noise = num(name, value)?,
+            "adc_ref_droop" => self.adc_ref_droop = num(name, value)?,
+            "adc_ref_recovery" => self.adc_ref_recovery = num(name, value)?,
+            "adc_clock_error" => self.adc_clock_error = num(name, value)?,
+            "adc_line_lock" => self.adc_line_lock = flag(name, value)?,
+            "auto_exposure" => self.auto_exposure = flag(name, value)?,
+            "auto_exposure_percentile" => self.auto_exposure_percentile = num(name, value)?,
+            "auto_exposure_target" => self.auto_exposure_target = num(name, value)?,
+            "pixel_shift_amount" => self.pixel_shift_amount = num(name, value)?,
+            "block_shift_amount" => self.block_shift_amount = num(name, value)?,
+            "scan_line_frequency" => self.scan_line_frequency = num(name, value)?,
+            "defect_repair_amount" => self.defect_repair_amount = num(name, value)?,
+            "defect_repair_mode" => {
+                self.defect_repair_mode = choice(name, value, RepairMode::ALL, |v| v.name())?
+            }
+            "per_plane_glitch" => self.per_plane_glitch = flag(name, value)?,
+            "sync.loss_rate" => self.sync.loss_rate = num(name, value)?,
+            "sync.loss_length" => self.sync.loss_length = num(name, value)?,
+            "sync.dropout_rate" => self.sync.dropout_rate = num(name, value)?,
+            "sync.dropout_length" => self.sync.dropout_length = num(name, value)?,
+            "sync.burst" => {
+                self.sync.burst = choice(name, value, BurstLength::ALL, |v| v.name())?
+            }
+            "bit_xor_mask" => self.bit_xor_mask = num(name, value)?,
+            "bit_rotation" => self.bit_rotation = num(name, value)?,
+            "bit_plane_swaps" => self.bit_plane_swaps = num(name, value)?,
+            "raw_packing" => self.raw_packing = choice(name, value, RawPacking::ALL, |v| v.name())?,
+            "raw_bit_offset" => self.raw_bit_offset = num(name, value)?,
+            "raw_stride_error" => self.raw_stride_error = num(name, value)?,
+            "compression.enabled" => self.compression.enabled = flag(name, value)?,
+            "compression.quality" => self.compression.quality = num(name, value)?,
+            "compression.mosquito" => self.compression.mosquito = num(name, value)?,
+            "compression.dc_flicker" => self.compression.dc_flicker = num(name, value)?,
+            "compression.block_smear" => self.compression.block_smear = num(name, value)?,
+            "plugin_stages" => return Err(format!("{name} is read-only; enable plugins instead")),
+            "temporal.persistence" => self.temporal.persistence = num(name, value)?,
+            "temporal.drift" => self.temporal.drift = num(name, value)?,
+            "temporal.decay" => self.temporal.decay = num(name, value)?,
+            "determinism.strict" => self.determinism.strict = flag(name, value)?,
+            "determinism.seed" => self.determinism.seed = num(name, value)?,
+            "validate_buffers" => self.validate_buffers = flag(name, value)?,
+            "bypass.optics" => self.bypass.set(StageBypass::OPTICS, flag(name, value)?),
+            "bypass.exposure_noise" => {
+                self.bypass.set(StageBypass::EXPOSURE_NOISE, flag(name, value)?)
+            }
+            "bypass.blooming" => self.bypass.set(StageBypass::BLOOMING, flag(name, value)?),
+            "bypass.v_clock" => self.bypass.set(StageBypass::V_CLOCK, flag(name, value)?),
+            "bypass.h_clock" => self.bypass.set(StageBypass::H_CLOCK, flag(name, value)?),
+            "bypass.amplifier" => self.bypass.set(StageBypass::AMPLIFIER, flag(name, value)?),
+            "bypass.adc" => self.bypass.set(StageBypass::ADC, flag(name, value)?),
+            "bypass.glitch" => self.bypass.set(StageBypass::GLITCH, flag(name, value)?),
+            "bypass.channel" => self.bypass.set(StageBypass::CHANNEL, flag(name, value)?),
+            "bypass.color" => self.bypass.set(StageBypass::COLOR, flag(name, value)?),
+            "stack.frames" => self.stack.frames = num(name, value)?,
+            "stack.combiner" => {
+                self.stack.combiner = choice(name, value, Combiner::ALL, |v| v.name())?
+            }
+            "stack.translate_jitter" => self.stack.translate_jitter = num(name, value)?,
+            "stack.rotate_jitter" => self.stack.rotate_jitter = num(name, value)?,
+            "stack.drizzle" => self.stack.drizzle = flag(name, value)?,
+            "stack.pixfrac" => self.stack.pixfrac = num(name, value)?,
+            "stack.drizzle_scale" => self.stack.drizzle_scale = num(name, value)?,
+            "three_chip.enabled" => self.three_chip.enabled = flag(name, value)?,
+            "three_chip.red" => self.set_chip_overrides(0, value)?,
+            "three_chip.green" => self.set_chip_overrides(1, value)?,
+            "three_chip.blue" => self.set_chip_overrides(2, value)?,
+            "three_chip.red_dx" => self.three_chip.red.dx = num(name, value)?,
+            "three_chip.red_dy" => self.three_chip.red.dy = num(name, value)?,
+            "three_chip.red_rotation" => self.three_chip.red.rotation = num(name, value)?,
+            "three_chip.red_skew" => self.three_chip.red.skew = num(name, value)?,
+            "three_chip.blue_dx" => self.three_chip.blue.dx = num(name, value)?,
+            "three_chip.blue_dy" => self.three_chip.blue.dy = num(name, value)?,
+            "three_chip.blue_rotation" => self.three_chip.blue.rotation = num(name, value)?,
+            "three_chip.blue_skew" => self.three_chip.blue.skew = num(name, value)?,
+            "chroma_subsampling" => {
+                self.chroma_subsampling = choice(name, value, ChromaSubsampling::ALL, |v| v.name())?
+            }
+            "channel_swap" => self.channel_swap = choice(name, value, ChannelSwap::ALL, |v| v.name())?,
+            "channel_r_gain" => self.channel_r_gain = num(name, value)?,
+            "channel_g_gain" => self.channel_g_gain = num(name, value)?,
+            "channel_b_gain" => self.channel_b_gain = num(name, value)?,
+            "channel_r_offset" => self.channel_r_offset = num(name, value)?,
+            "channel_g_offset" => self.channel_g_offset = num(name, value)?,
+            "channel_b_offset" => self.channel_b_offset = num(name, value)?,
+            "chromatic_r_x" => self.chromatic_r_x = num(name, value)?,
+            "chromatic_r_y" => self.chromatic_r_y = num(name, value)?,
+            "chromatic_b_x" => self.chromatic_b_x = num(name, value)?,
+            "chromatic_b_y" => self.chromatic_b_y = num(name, value)?,
+            "bayer_pattern" => self.bayer_pattern = choice(name, value, BayerPattern::ALL, |v| v.name())?,
+            "demosaic_algo" => self.demosaic_algo = choice(name, value, DemosaicAlgo::ALL, |v| v.name())?,
+            "white_balance_r" => self.white_balance_r = num(name, value)?,
+            "white_balance_g" => self.white_balance_g = num(name, value)?,
+            "white_balance_b" => self.white_balance_b = num(name, value)?,
+            "saturation" => self.saturation = num(name, value)?,
+            "vibrance" => self.vibrance = num(name, value)?,
+            "hue_rotation" => self.hue_rotation = num(name, value)?,
+            "gamma" => self.gamma = num(name, value)?,
+            "brightness" => self.brightness = num(name, value)?,
+            "contrast" => self.contrast = num(name, value)?,
+            "tone_curve.master_points"
+            | "tone_curve.red_points"
+            | "tone_curve.green_points"
+            | "tone_curve.blue_points" => {
+                return Err(format!("{name} is read-only; edit the curve instead"));
+            }
+            "output_dither" => self.output_dither = choice(name, value, Dither::ALL, |v| v.name())?,
+            "sharpen.amount" => self.sharpen.amount = num(name, value)?,
+            "sharpen.radius" => self.sharpen.radius = num(name, value)?,
+            "sharpen.threshold" => self.sharpen.threshold = num(name, value)?,
+            "sharpen.halo" => self.sharpen.halo = flag(name, value)?,
+            "palette.mode" => self.palette.mode = choice(name, value, PaletteMode::ALL, |v| v.name())?,
+            "palette.levels" => self.palette.levels = num(name, value)?,
+            "palette.colors" => self.palette.colors = num(name, value)?,
+            "palette.dither" => {
+                self.palette.dither = choice(name, value, Dither::ALL, |v| v.name())?
+            }
+            "palette.custom_size" => return Err(format!("{name} is read-only; load a palette instead")),
+            "output_lut_size" => return Err(format!("{name} is read-only; load a LUT instead")),
+            "source_match.white_balance" => self.source_match.white_balance = flag(name, value)?,
+            "source_match.tonality" => self.source_match.tonality = flag(name, value)?,
+            "source_match.strength" => self.source_match.strength = num(name, value)?,
+            "composite.mode" => self.composite.mode = choice(name, value, BlendMode::ALL, |v| v.name())?,
+            "composite.opacity" => self.composite.opacity = num(name, value)?,
+            "artifact_layer.strength" => self.artifact_layer.strength = num(name, value)?,
+            "frame_overlay.overscan" => self.frame_overlay.overscan = num(name, value)?,
+            "frame_overlay.safe_area" => self.frame_overlay.safe_area = flag(name, value)?,
+            "frame_overlay.timestamp" => self.frame_overlay.timestamp = flag(name, value)?,
+            "frame_overlay.clock_start" => self.frame_overlay.clock_start = num(name, value)?,
+            "frame_overlay.rec" => self.frame_overlay.rec = flag(name, value)?,
+            "spice.mode" => s.mode = choice(name, value, crate::spice::SpiceMode::ALL, |v| v.name())?,
+            "spice.vdd" => s.vdd = num(name, value)?,
+            "spice.clock_freq_mhz" => s.clock_freq_mhz = num(name, value)?,
+            "spice.temperature_k" => s.temperature_k = num(name, value)?,
+            "spice.shift_register_stages" => s.shift_register_stages = num(name, value)?,
+            "spice.transfer_function_resolution" => s.transfer_function_resolution = num(name, value)?,
+            "spice.supply_droop" => s.supply_droop = num(name, value)?,
+            "spice.phase_overlap_ns" => s.phase_overlap_ns = num(name, value)?,
+            "spice.missing_pulse_rate" => s.missing_pulse_rate = num(name, value)?,
+            "spice.charge_injection" => s.charge_injection = num(name, value)?,
+            "spice.substrate_noise" => s.substrate_noise = num(name, value)?,
+            _ => return Err(format!("Unknown parameter: {name}")),
+        }
+        Ok(())
+    }
+
+    /// List every parameter whose value differs from `other`.
+    ///
+    /// `from` holds the value in `other`, `to` the value in `self`, so
+    /// `params.diff(&PipelineParams::default())` reads as "changed from default".
+    pub fn diff(&self, other: &PipelineParams) -> Vec<ParamChange> {
+        self.values()
+            .into_iter()
+            .zip(other.values())
+            .filter(|((_, to), (_, from))| to != from)
+            .map(|((name, to), (_, from))| ParamChange { name, from, to })
+            .collect()
+    }
+}
+
+/// Wall-clock time spent in each pipeline stage, in execution order.
+#[derive(Debug, Clone, Default)]
+pub struct StageTimings {
+    pub stages: Vec<(&'static str, f64)>,
+    /// Stages whose output failed validation, in execution order; empty
+    /// unless `validate_buffers` is on.
+    pub issues: Vec<BufferIssue>,
+}
+
+impl StageTimings {
+    /// Sum of all stage times in milliseconds.
+    pub fn total_ms(&self) -> f64 {
+        self.stages.iter().map(|(_, ms)| ms).sum()
+    }
+}
+
+/// What the values in a tapped stage buffer measure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StageUnits {
+    Electrons,
+    Adu,
+    /// Normalized display range [0, 1].
+    Normalized,
+}
+
+impl StageUnits {
+    pub fn name(self) -> &'static str {
+        match self {
+            StageUnits::Electrons => "e-",
+            StageUnits::Adu => "ADU",
+            StageUnits::Normalized => "norm",
+        }
+    }
+}
+
+/// Working buffer as it leaves a stage.
+pub enum StageBuffer<'a> {
+    Mosaic(&'a [f64]),
+    Rgb(&'a [[f64; 3]]),
+}
+
+/// A stage's output handed to a `process_tapped` callback. Readout stages
+/// (Blooming through ADC) are in the sensor's readout frame, so rows are
+/// readout rows even when the sensor is rotated.
+pub struct StageData<'a> {
+    pub buffer: StageBuffer<'a>,
+    pub width: usize,
+    pub height: usize,
+    pub units: StageUnits,
+}
+
+impl<'a> StageData<'a> {
+    fn mosaic(data: &'a [f64], width: usize, height: usize, units: StageUnits) -> Self {
+        Self { buffer: StageBuffer::Mosaic(data), width, height, units }
+    }
+
+    fn rgb(data: &'a [[f64; 3]], width: usize, height: usize, units: StageUnits) -> Self {
+        Self { buffer: StageBuffer::Rgb(data), width, height, units }
+    }
+}
+
+/// Callback receiving each stage's output from `process_tapped`.
+pub type StageTap<'t> = &'t mut dyn FnMut(&'static str, StageData<'_>);
+
+/// Lap timer that records the time since the previous lap under a stage name,
+/// optionally passing the stage output to a tap and validating it.
+struct StageTimer<'t> {
+    timings: StageTimings,
+    last: web_time::Instant,
+    tap: Option<StageTap<'t>>,
+    validate: bool,
+}
+
+impl<'t> StageTimer<'t> {
+    fn start(tap: Option<StageTap<'t>>, validate: bool) -> Self {
+        Self {
+            timings: StageTimings::default(),
+            last: web_time::Instant::now(),
+            tap,
+            validate,
+        }
+    }
+
+    fn lap(&mut self, name: &'static str) {
+        let now = web_time::Instant::now();
+        let ms = (now - self.last).as_secs_f64() * 1000.0;
+        self.timings.stages.push((name, ms));
+        self.last = now;
+    }
+
+    /// Validate the stage output and hand it to the tap, keeping the cost of
+    /// both out of the timings.
+    fn tap(&mut self, name: &'static str, data: StageData<'_>) {
+        debug_assert!(!validation::has_non_finite(&data), "{name} produced NaN or infinite values");
+        if self.validate {
+            self.timings.issues.extend(validation::scan(name, &data));
+            self.last = web_time::Instant::now();
+        }
+        if let Some(tap) = self.tap.as_mut() {
+            tap(name, data);
+            self.last = web_time::Instant::now();
+        }
+    }
+}
+
+/// Run the full CCD processing pipeline on an input image.
+/// Returns the final RGB image as (width, height, rgb_bytes).
+pub fn process(
+    source: &image::DynamicImage,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+) -> (usize, usize, Vec<u8>) {
+    let (w, h, bytes, _) = process_timed(source, params, spice_cache);
+    (w, h, bytes)
+}
+
+/// Run the full pipeline like `process`, also returning per-stage timings.
+pub fn process_timed(
+    source: &image::DynamicImage,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+) -> (usize, usize, Vec<u8>, StageTimings) {
+    let (w, h, mut bytes, timings) = render_timed(source, params, spice_cache, None);
+    source_match::apply_source_match(&mut bytes, source, params);
+    composite::apply_composite(&mut bytes, w, h, source, params);
+    artifact_layer::apply_artifact_layer(&mut bytes, w, h, &params.artifact_layer);
+    osd::apply_overlay(&mut bytes, w, h, &params.frame_overlay, 0);
+    (w, h, bytes, timings)
+}
+
+/// `process_timed` before source matching and compositing: the chips, stack
+/// or single frame, each holding `temporal`'s glitch pattern if given.
+fn render_timed(
+    source: &image::DynamicImage,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    temporal: Option<&GlitchFrame>,
+) -> (usize, usize, Vec<u8>, StageTimings) {
+    if params.three_chip.enabled {
+        return three_chip::process_three_chip(source, params, spice_cache, temporal);
+    }
+    if params.stack.frames > 1 {
+        return stack::process_stacked(source, params, spice_cache, temporal);
+    }
+    process_frame(source, params, spice_cache, None, temporal)
+}
+
+/// Run the pipeline on one frame of a sequence like `process`, holding the
+/// glitch pattern across frames as `params.temporal` describes and advancing
+/// `state` to the next frame. Three-chip and stacked renders hold the same
+/// pattern in every chip and subframe. In strict determinism each frame
+/// draws from its own seed, so noise still changes from frame to frame.
+pub fn process_temporal(
+    source: &image::DynamicImage,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    state: &mut TemporalState,
+) -> (usize, usize, Vec<u8>) {
+    let mut params = Cow::Borrowed(params);
+    if params.determinism.strict {
+        let seed = params.determinism.seed.wrapping_add(state.frame);
+        params.to_mut().determinism.seed = seed;
+    }
+    let params = &*params;
+    let index = state.frame;
+    let frame = state.next_frame(&params.temporal);
+    let (w, h, mut bytes, _) = render_timed(source, params, spice_cache, Some(&frame));
+    source_match::apply_source_match(&mut bytes, source, params);
+    composite::apply_composite(&mut bytes, w, h, source, params);
+    artifact_layer::apply_artifact_layer(&mut bytes, w, h, &params.artifact_layer);
+    osd::apply_overlay(&mut bytes, w, h, &params.frame_overlay, index);
+    (w, h, bytes)
+}
+
+/// `GlitchFrame::apply` streams: the V-clock faults, then one per CFA plane
+/// for the shift and scan line glitches, then the sync faults.
+const V_CLOCK_STREAM: u64 = 0;
+const SPATIAL_STREAM: u64 = 1;
+const SYNC_STREAM: u64 = SPATIAL_STREAM + 4;
+
+/// Parallel transfer. With a temporal frame, the glitch rows come from its
+/// held pattern instead of a fresh draw.
+fn v_clock_transfer(
+    mosaic: &mut [f64],
+    width: usize,
+    height: usize,
+    params: &PipelineParams,
+    temporal: Option<&GlitchFrame>,
+) {
+    let rate = if temporal.is_some() { 0.0 } else { params.v_glitch_rate };
+    transfer::vertical_transfer(
+        mosaic,
+        width,
+        height,
+        params.v_cte,
+        rate,
+        params.v_waveform_distortion,
+        &params.v_modulation,
+        params.parallel_smear,
+        params.tile_seamless,
+        &params.v_glitch_schedule,
+        params.v_phases,
+    );
+    if let Some(frame) = temporal.filter(|_| params.v_glitch_rate > 0.0) {
+        frame.apply(V_CLOCK_STREAM, mosaic, width, height, |grid, rng| {
+            transfer::apply_v_glitches(
+                grid,
+                width,
+                height,
+                params.v_glitch_rate,
+                &params.v_glitch_schedule,
+                params.v_phases,
+                rng,
+            )
+        });
+    }
+}
+
+/// Pixel shift, block shift and scan line corruption on one grid.
+fn spatial_glitch(
+    grid: &mut [f64],
+    width: usize,
+    height: usize,
+    params: &PipelineParams,
+    max_code: f64,
+    rng: &mut impl rand::Rng,
+) {
+    pixel_shift::apply_pixel_shift(grid, width, height, params.pixel_shift_amount, rng);
+    pixel_shift::apply_block_shift(grid, width, height, params.block_shift_amount, rng);
+    scan_line::apply_scan_line_corruption(
+        grid,
+        width,
+        height,
+        params.scan_line_frequency,
+        max_code,
+        rng,
+    );
+}
+
+/// Run the full pipeline like `process_timed`, passing each stage's output
+/// buffer to `tap` (used by the analysis tools).
+pub fn process_tapped(
+    source: &image::DynamicImage,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    tap: Option<StageTap<'_>>,
+) -> (usize, usize, Vec<u8>, StageTimings) {
+    process_frame(source, params, spice_cache, tap, None)
+}
+
+/// The pipeline itself; glitch stages draw fresh randomness unless
+/// `temporal` holds a pattern from a sequence or strict determinism is on.
+fn process_frame(
+    source: &image::DynamicImage,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    tap: Option<StageTap<'_>>,
+    temporal: Option<&GlitchFrame>,
+) -> (usize, usize, Vec<u8>, StageTimings) {
+    let aged = params.aged();
+    let readout = aged.at_readout_speed();
+    let seeded = readout.seeded();
+    let params = &*seeded;
+    let strict_frame = params.determinism.glitch_frame(&params.temporal);
+    let temporal = temporal.or(strict_frame.as_ref());
+    let w = params.sensor_width;
+    let h = params.sensor_height;
+    let width = w as usize;
+    let height = h as usize;
+    let mut timer = StageTimer::start(tap, params.validate_buffers);
+    let bypassed = |stage| params.bypass.contains(stage);
+
+    // Step 1: Resize image to sensor dimensions and convert to electron counts
+    let mut rgb_electrons = sensor_exposure(source, params);
+    timer.lap("Resize");
+    timer.tap("Resize", StageData::rgb(&rgb_electrons, width, height, StageUnits::Electrons));
+
+    // Step 1a: Exposure-series HDR merge
+    if params.hdr_merge.enabled && !bypassed(StageBypass::EXPOSURE_NOISE) {
+        sensor::merge_exposure_series(&mut rgb_electrons, params.full_well, &params.hdr_merge);
+        timer.lap("HDR Merge");
+        let merged = StageData::rgb(&rgb_electrons, width, height, StageUnits::Electrons);
+        timer.tap("HDR Merge", merged);
+    }
+
+    // Step 1b: Motion blur, lens distortion, defocus, halation and scan skew
+    if !bypassed(StageBypass::OPTICS) {
+        let rgb = &mut rgb_electrons;
+        let wrap = params.tile_seamless;
+        optics::apply_motion_blur(rgb, width, height, &params.motion_blur, wrap);
+        optics::apply_lens_distortion(rgb, width, height, params.lens_distortion);
+        optics::apply_defocus(rgb, width, height, params.defocus_radius, wrap);
+        optics::apply_halation(rgb, width, height, &params.halation, params.full_well, wrap);
+        apply_scan_skew(rgb, width, height, params);
+        timer.lap("Optics");
+        timer.tap("Optics", StageData::rgb(&rgb_electrons, width, height, StageUnits::Electrons));
+    }
+
+    // Step 1c: Camera OSD, lit into the scene before readout so it glitches along with it
+    if !params.camera_osd.is_off() {
+        let (osd, full_well) = (&params.camera_osd, params.full_well);
+        osd::apply_camera_osd(&mut rgb_electrons, width, height, osd, full_well);
+        timer.lap("Camera OSD");
+        let lit = StageData::rgb(&rgb_electrons, width, height, StageUnits::Electrons);
+        timer.tap("Camera OSD", lit);
+    }
+
+    // Step 2: Apply Bayer CFA
+    let mut mosaic = bayer::apply_bayer(&rgb_electrons, width, height, params.bayer_pattern);
+    timer.lap("Bayer");
+    timer.tap("Bayer", StageData::mosaic(&mosaic, width, height, StageUnits::Electrons));
+
+    // Readout stages run in the sensor's own frame (orientation + parallel direction)
+    let frame = transfer::ReadoutFrame::new(
+        width,
+        height,
+        params.sensor_orientation,
+        params.vertical_direction,
+    );
+
+    if !bypassed(StageBypass::EXPOSURE_NOISE) {
+        // Step 2b: Per-row exposure modulation and light flicker, timed by each row's readout
+        sensor::apply_exposure_modulation(
+            &mut mosaic,
+            width,
+            height,
+            &frame,
+            params.row_exposure_depth,
+            &params.row_exposure,
+        );
+        sensor::apply_flicker(&mut mosaic, width, height, &frame, &params.flicker);
+        timer.lap("Row Exposure");
+        timer.tap("Row Exposure", StageData::mosaic(&mosaic, width, height, StageUnits::Electrons));
+
+        // Step 3: Dark current, long-exposure time slices, shot noise, read noise
+        let dark_rate = Electrons(params.dark_current_rate);
+        sensor::add_dark_current(
+            &mut mosaic,
+            width,
+            height,
+            dark_rate,
+            &params.dark_structure,
+            &params.noise,
+        );
+        sensor::integrate_long_exposure(
+            &mut mosaic,
+            width,
+            height,
+            &frame,
+            params.full_well,
+            &params.long_exposure,
+        );
+        if params.shot_noise_enabled {
+            sensor::add_shot_noise(&mut mosaic, width, &params.noise);
+        }
+        sensor::add_read_noise(&mut mosaic, width, Electrons(params.read_noise), &params.noise);
+        timer.lap("Noise");
+        timer.tap("Noise", StageData::mosaic(&mosaic, width, height, StageUnits::Electrons));
+    }
+
+    frame.enter(&mut mosaic);
+    let (frame_w, frame_h) = frame.dims();
+
+    // Step 3a: A line-scan sensor sweeps the scene one line at a time and
+    // has no parallel register for the V-clock stage to model
+    let line_scan = params.line_scan.enabled && !bypassed(StageBypass::V_CLOCK);
+    if line_scan {
+        transfer::line_scan_readout(
+            &mut mosaic,
+            frame_w,
+            frame_h,
+            &params.line_scan,
+            &mut params.determinism.rng(Stream::LineScan),
+        );
+        timer.lap("Line Scan");
+        timer.tap(
+            "Line Scan",
+            StageData::mosaic(&mosaic, frame_w, frame_h, StageUnits::Electrons),
+        );
+    } else if params.tdi.enabled && !bypassed(StageBypass::V_CLOCK) {
+        // Or the parallel clocks shift the charge along with the scene as
+        // it integrates
+        transfer::tdi_integrate(&mut mosaic, frame_w, frame_h, &params.tdi);
+        timer.lap("TDI");
+        timer.tap("TDI", StageData::mosaic(&mosaic, frame_w, frame_h, StageUnits::Electrons));
+    }
+
+    // Step 3b: Dark current collected while rows wait to be read, warmed by
+    // the amplifier toward its corner
+    let heat = (!bypassed(StageBypass::AMPLIFIER) && !params.thermal.is_off())
+        .then(|| HeatMap::simulate(frame_w, frame_h, &params.thermal));
+    if let Some(heat) = &heat {
+        thermal::add_readout_dark(
+            &mut mosaic,
+            frame_w,
+            frame_h,
+            &params.thermal,
+            heat,
+            &mut params.determinism.rng(Stream::ReadoutDark),
+        );
+        timer.lap("Readout Dark");
+        timer.tap(
+            "Readout Dark",
+            StageData::mosaic(&mosaic, frame_w, frame_h, StageUnits::Electrons),
+        );
+    }
+
+    // SPICE branch: replace mathematical pipeline stages with circuit-derived processing
+    let spice_handled = process_spice_branch(
+        &mut mosaic,
+        frame_w,
+        frame_h,
+        params,
+        spice_cache,
+        temporal,
+        bypassed,
+    );
+    if spice_handled {
+        timer.lap("SPICE");
+        timer.tap("SPICE", StageData::mosaic(&mosaic, frame_w, frame_h, StageUnits::Adu));
+    }
+
+    if !spice_handled {
+        // Step 4: Blooming
+        if !bypassed(StageBypass::BLOOMING) {
+            blooming::apply_blooming(
+                &mut mosaic,
+                frame_w,
+                frame_h,
+                &params.bloom(),
+                params.tile_seamless,
+            );
+            timer.lap("Blooming");
+            timer.tap(
+                "Blooming",
+                StageData::mosaic(&mosaic, frame_w, frame_h, StageUnits::Electrons),
+            );
+        }
+
+        // Step 5: Vertical (parallel) transfer
+        if !bypassed(StageBypass::V_CLOCK) && !line_scan {
+            transfer::apply_saturation_faults(
+                &mut mosaic,
+                frame_w,
+                frame_h,
+                params.saturation_clock_coupling,
+                params.full_well * params.bloom_threshold,
+                params.v_phases,
+                &mut params.determinism.rng(Stream::Saturation),
+            );
+            v_clock_transfer(&mut mosaic, frame_w, frame_h, params, temporal);
+            timer.lap("V-Clock");
+            timer.tap(
+                "V-Clock",
+                StageData::mosaic(&mosaic, frame_w, frame_h, StageUnits::Electrons),
+            );
+        }
+
+        // Step 6: Horizontal (serial) transfer
+        if !bypassed(StageBypass::H_CLOCK) {
+            let mut serial = serial_transfer(params);
+            stream_serial(&mut mosaic, frame_w, frame_h, params, &mut serial);
+            timer.lap("H-Clock");
+            timer.tap(
+                "H-Clock",
+                StageData::mosaic(&mosaic, frame_w, frame_h, StageUnits::Electrons),
+            );
+        }
+
+        // Step 6b: Readout abort (power loss mid-frame)
+        apply_readout_abort(&mut mosaic, frame_w, frame_h, params);
+
+        // Step 7: Output amplifier
+        if !bypassed(StageBypass::AMPLIFIER) {
+            let mut output = amplifier::OutputAmplifier::new(
+                frame_h,
+                ConversionGain::uv_per_e(params.conversion_gain),
+                params.amp_gain,
+                params.nonlinearity,
+                params.reset_noise,
+                params.amp_glow,
+                params.determinism.rng(Stream::Amplifier),
+            )
+            .with_drift(params.amp_drift)
+            .with_glow_scale(heat.as_ref().map_or(&[], HeatMap::amp_scale));
+            stream_amplifier(&mut mosaic, frame_w, frame_h, params, &mut output);
+            timer.lap("Amplifier");
+            timer.tap(
+                "Amplifier",
+                StageData::mosaic(&mosaic, frame_w, frame_h, StageUnits::Electrons),
+            );
+        }
+
+        // Step 8: ADC, an ideal quantizer when bypassed
+        let ideal = bypassed(StageBypass::ADC);
+        stream_adc(&mut mosaic, frame_w, frame_h, params, ideal);
+        timer.lap("ADC");
+        timer.tap("ADC", StageData::mosaic(&mosaic, frame_w, frame_h, StageUnits::Adu));
+    }
+
+    if !bypassed(StageBypass::ADC) {
+        // Step 8b: column converter errors, black clamp, ADC reference droop,
+        // sample jitter and clock mismatch, on the code stream of either path
+        adc::apply_column_adc(&mut mosaic, frame_w, params.bit_depth, &params.column_adc);
+        let mut clamp = adc::ClampLoop::new(
+            params.bit_depth,
+            Adu(params.bias),
+            &params.black_clamp,
+            params.determinism.rng(Stream::BlackClamp),
+        );
+        let (droop, recovery) = (params.adc_ref_droop, params.adc_ref_recovery);
+        let mut droop = adc::ReferenceDroop::new(params.bit_depth, droop, recovery);
+        let mut jitter =
+            adc::SampleJitter::new(params.adc_jitter, params.determinism.rng(Stream::SampleJitter));
+        readout::stream(
+            &mut mosaic,
+            frame_w,
+            frame_h,
+            params.readout_direction,
+            &mut [&mut clamp, &mut droop, &mut jitter],
+        );
+        adc::apply_sample_clock_error(
+            &mut mosaic,
+            frame_w,
+            frame_h,
+            params.adc_clock_error,
+            params.adc_line_lock,
+            params.readout_direction,
+            params.bias.round(),
+        );
+        // Step 8c: output word companding and encoding, as the receiver decodes it
+        adc::compand(&mut mosaic, params.bit_depth, &params.compand);
+        adc::apply_output_encoding(
+            &mut mosaic,
+            params.bit_depth,
+            params.adc_encoding,
+            params.adc_wrong_decode,
+        );
+        adc::decompand(&mut mosaic, params.bit_depth, &params.compand);
+    }
+    frame.exit(&mut mosaic);
+
+    // Exposure compensation is measured on the clean digitized frame, before
+    // the glitch stages scramble codes
+    let max_code = ((1u64 << params.bit_depth) - 1) as f64;
+    let exposure_gain = if params.auto_exposure {
+        auto_exposure_gain(&mosaic, params, max_code)
+    } else {
+        1.0
+    };
+
+    // Step 9a: Pre-demosaic glitch effects
+    if !bypassed(StageBypass::GLITCH) {
+        defect_repair::apply_defect_repair(
+            &mut mosaic,
+            width,
+            height,
+            params.defect_repair_amount,
+            params.defect_repair_mode,
+            &mut params.determinism.rng(Stream::DefectRepair),
+        );
+
+        // Each CFA plane gets its own stream of the held pattern
+        let mut plane = 0;
+        let mut spatial_glitches = |grid: &mut [f64], w: usize, h: usize| {
+            match temporal {
+                Some(frame) => frame.apply(SPATIAL_STREAM + plane, grid, w, h, |grid, rng| {
+                    spatial_glitch(grid, w, h, params, max_code, rng)
+                }),
+                None => spatial_glitch(grid, w, h, params, max_code, &mut rand::rng()),
+            }
+            plane += 1;
+        };
+        if params.per_plane_glitch {
+            glitch::for_each_cfa_plane(&mut mosaic, width, height, spatial_glitches);
+        } else {
+            spatial_glitches(&mut mosaic, width, height);
+        }
+        let sync = &params.sync;
+        match temporal {
+            Some(frame) => frame.apply(SYNC_STREAM, &mut mosaic, width, height, |grid, rng| {
+                sync::apply_sync_faults(grid, width, height, sync, max_code, rng)
+            }),
+            None => {
+                let rng = &mut rand::rng();
+                sync::apply_sync_faults(&mut mosaic, width, height, sync, max_code, rng)
+            }
+        }
+        bit_manip::apply_bit_xor(&mut mosaic, max_code, params.bit_xor_mask);
+        bit_manip::apply_bit_rotation(&mut mosaic, params.bit_depth, params.bit_rotation);
+        bit_manip::apply_bit_plane_swap(
+            &mut mosaic,
+            params.bit_depth,
+            params.bit_plane_swaps,
+            &mut params.determinism.rng(Stream::BitPlanes),
+        );
+        raw_pack::apply_raw_packing(
+            &mut mosaic,
+            width,
+            height,
+            params.bit_depth,
+            params.raw_packing,
+            params.raw_bit_offset,
+            params.raw_stride_error,
+        );
+        let (plugins, stage) = (&params.plugins, PluginStage::Mosaic);
+        plugin::apply_plugins(plugins, stage, &mut mosaic, width, height, 1, max_code);
+    }
+    timer.lap("Glitch");
+    timer.tap("Glitch", StageData::mosaic(&mosaic, width, height, StageUnits::Adu));
+
+    // Step 10: Demosaicing
+    let mut rgb = demosaic::demosaic(
+        &mosaic,
+        width,
+        height,
+        params.bayer_pattern,
+        params.demosaic_algo,
+        params.tile_seamless,
+    );
+    timer.lap("Demosaic");
+    timer.tap("Demosaic", StageData::rgb(&rgb, width, height, StageUnits::Adu));
+
+    // Normalize from ADC counts to [0, 1] range, applying auto-exposure
+    // around the bias level
+    if max_code > 0.0 {
+        let black = params.bias;
+        for pixel in rgb.iter_mut() {
+            for c in 0..3 {
+                let code = black + (pixel[c] - black) * exposure_gain;
+                pixel[c] = (code / max_code).clamp(0.0, 1.0);
+            }
+        }
+    }
+
+    // Step 9b: Post-demosaic channel effects
+    if !bypassed(StageBypass::CHANNEL) {
+        chroma::apply_chroma_subsampling(&mut rgb, width, height, params.chroma_subsampling);
+        channel::apply_channel_gain_offset(
+            &mut rgb,
+            params.channel_r_gain,
+            params.channel_g_gain,
+            params.channel_b_gain,
+            params.channel_r_offset,
+            params.channel_g_offset,
+            params.channel_b_offset,
+        );
+        channel::apply_channel_swap(&mut rgb, params.channel_swap);
+        channel::apply_chromatic_aberration(
+            &mut rgb,
+            width,
+            height,
+            (params.chromatic_r_x, params.chromatic_r_y),
+            (params.chromatic_b_x, params.chromatic_b_y),
+            params.tile_seamless,
+        );
+        let (plugins, rgb) = (&params.plugins, rgb.as_flattened_mut());
+        plugin::apply_plugins(plugins, PluginStage::Rgb, rgb, width, height, 3, 1.0);
+    }
+    timer.lap("Channel");
+    timer.tap("Channel", StageData::rgb(&rgb, width, height, StageUnits::Normalized));
+
+    // Step 11: Color rendering, then the optional output LUT
+    if !bypassed(StageBypass::COLOR) {
+        render_color(&mut rgb, params);
+        if let Some(output_lut) = &params.output_lut {
+            lut::apply_lut(&mut rgb, output_lut);
+        }
+        spectral::apply_sharpen(&mut rgb, width, height, &params.sharpen, params.tile_seamless);
+    }
+
+    // Step 12: Stretch non-square photosites to square output pixels
+    let (mut rgb, width) = match anamorphic_width(params) {
+        Some(out_w) => (image_io::resample_width(&rgb, width, height, out_w), out_w),
+        None => (rgb, width),
+    };
+
+    // Step 13: Codec artifacts on the finished picture, then the palette
+    if !bypassed(StageBypass::GLITCH) {
+        compression::apply_compression(
+            &mut rgb,
+            width,
+            height,
+            &params.compression,
+            params.tile_seamless,
+            &mut params.determinism.rng(Stream::Compression),
+        );
+    }
+    if !bypassed(StageBypass::COLOR) {
+        let rng = &mut params.determinism.rng(Stream::Palette);
+        palette::apply_palette(&mut rgb, width, &params.palette, rng);
+    }
+
+    let rng = &mut params.determinism.rng(Stream::OutputDither);
+    let bytes = spectral::rgb_to_bytes(&rgb, width, height, params.output_dither, rng);
+    timer.lap("Color");
+    timer.tap("Color", StageData::rgb(&rgb, width, height, StageUnits::Normalized));
+
+    (width, height, bytes, timer.timings)
+}
+
+/// Gain that maps the `auto_exposure_percentile` code of the digitized frame
+/// (above bias) to `auto_exposure_target` of full scale, limited to +/-6 stops.
+/// Large frames are subsampled for the percentile.
+fn auto_exposure_gain(mosaic: &[f64], params: &PipelineParams, max_code: f64) -> f64 {
+    const MAX_SAMPLES: usize = 1 << 18;
+    let step = mosaic.len().div_ceil(MAX_SAMPLES).max(1);
+    let mut samples: Vec<f64> = mosaic.iter().step_by(step).copied().collect();
+    if samples.is_empty() {
+        return 1.0;
+    }
+    let rank = params.auto_exposure_percentile.clamp(0.0, 100.0) / 100.0;
+    let index = ((samples.len() - 1) as f64 * rank).round() as usize;
+    let (_, &mut level, _) = samples.select_nth_unstable_by(index, f64::total_cmp);
+
+    let signal = level - params.bias;
+    let target = params.auto_exposure_target.clamp(0.01, 1.0) * max_code - params.bias;
+    if signal <= 0.0 || target <= 0.0 {
+        return 1.0;
+    }
+    (target / signal).clamp(1.0 / 64.0, 64.0)
+}
+
+/// Output width after the anamorphic resample, or `None` when the output
+/// keeps one pixel per photosite.
+pub fn anamorphic_width(params: &PipelineParams) -> Option<usize> {
+    let aspect = params.pixel_aspect.clamp(0.25, 4.0);
+    if !params.anamorphic_output || (aspect - 1.0).abs() < 1e-3 {
+        return None;
+    }
+    Some(((params.sensor_width as f64 * aspect).round() as usize).max(1))
+}
+
+/// Size of the frame `process` returns when a single readout comes out
+/// `width` x `height`: drizzling a stack makes it larger.
+pub fn output_size(params: &PipelineParams, width: usize, height: usize) -> (usize, usize) {
+    let stack = &params.stack;
+    if stack.drizzle && stack.frames > 1 && !params.three_chip.enabled {
+        stack::drizzled_size(width, height, stack)
+    } else {
+        (width, height)
+    }
+}
+
+/// Width of a square-pixel canvas with the sensor's physical aspect ratio,
+/// when it differs from the photosite grid. Crop and letterbox fit against
+/// this so framing matches what the anamorphic output shows.
+fn physical_width(params: &PipelineParams) -> Option<u32> {
+    let aspect = params.pixel_aspect.clamp(0.25, 4.0);
+    if params.fit_mode == FitMode::Stretch || (aspect - 1.0).abs() < 1e-3 {
+        return None;
+    }
+    Some(((params.sensor_width as f64 * aspect).round() as u32).max(1))
+}
+
+fn apply_scan_skew(rgb: &mut [[f64; 3]], width: usize, height: usize, params: &PipelineParams) {
+    optics::apply_scan_skew(
+        rgb,
+        width,
+        height,
+        params.scan_skew,
+        params.scan_scale_error,
+        params.scan_wobble,
+        params.scan_wobble_frequency,
+    );
+}
+
+/// Resize `source` onto the sensor (fit mode, area-sum, HDR) and convert to
+/// electrons per photosite.
+fn sensor_exposure(source: &image::DynamicImage, params: &PipelineParams) -> Vec<[f64; 3]> {
+    let (w, h) = (params.sensor_width, params.sensor_height);
+    if let Some(canvas_w) = physical_width(params) {
+        let square = sensor_exposure(
+            source,
+            &PipelineParams {
+                sensor_width: canvas_w,
+                pixel_aspect: 1.0,
+                ..params.clone()
+            },
+        );
+        return image_io::resample_width(&square, canvas_w as usize, h as usize, w as usize);
+    }
+    let area_sum = if params.area_sum_downscale {
+        image_io::resize_area_sum(source, w, h, params.fit_mode)
+    } else {
+        None
+    };
+    let strict = params.determinism.strict;
+    let exposure = area_sum
+        .or_else(|| image_io::resize_hdr_to_sensor(source, w, h, params.fit_mode, strict));
+    let mut rgb_electrons = match exposure {
+        Some(exposure) => exposure
+            .iter()
+            .map(|e| e.map(|v| v * params.full_well))
+            .collect(),
+        None => {
+            let resized = image_io::resize_to_sensor(source, w, h, params.fit_mode, strict);
+            sensor::image_to_electrons(&resized, params.full_well).0
+        }
+    };
+    if params.fit_mode == FitMode::Letterbox && params.letterbox_pad > 0.0 {
+        let (cx, cy, cw, ch) = image_io::content_rect(source.width(), source.height(), w, h, params.fit_mode);
+        for y in 0..h {
+            for x in 0..w {
+                let inside = x >= cx && x < cx + cw && y >= cy && y < cy + ch;
+                if !inside {
+                    rgb_electrons[(y * w + x) as usize] = [params.letterbox_pad; 3];
+                }
+            }
+        }
+    }
+    rgb_electrons
+}
+
+/// A rectangle of the sensor, in photosites.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Roi {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Roi {
+    /// Clamp to a `sensor_w` x `sensor_h` sensor and snap the origin to even
+    /// coordinates so the crop keeps the full frame's CFA phase.
+    pub fn fit(self, sensor_w: u32, sensor_h: u32) -> Self {
+        let x = (self.x.min(sensor_w.saturating_sub(2))) & !1;
+        let y = (self.y.min(sensor_h.saturating_sub(2))) & !1;
+        Self {
+            x,
+            y,
+            width: self.width.clamp(2, sensor_w - x),
+            height: self.height.clamp(2, sensor_h - y),
+        }
+    }
+}
+
+/// Render only `roi` of the sensor at full resolution, for fast previews
+/// while tuning. The result keeps one pixel per photosite (no anamorphic
+/// resample).
+///
+/// The full frame is exposed and passed through the optics, then the crop
+/// runs through the rest of the chain as a sensor of its own. Effects that
+/// depend on position in the full frame (CTE trailing accumulated over the
+/// whole column, readout abort, banding phase) only approximate the
+/// full-frame render.
+pub fn process_roi(
+    source: &image::DynamicImage,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    roi: Roi,
+) -> (usize, usize, Vec<u8>, StageTimings) {
+    let roi = roi.fit(params.sensor_width, params.sensor_height);
+    let (sensor_w, sensor_h) = (params.sensor_width as usize, params.sensor_height as usize);
+    let mut exposure = sensor_exposure(source, params);
+    let (blur, wrap) = (&params.motion_blur, params.tile_seamless);
+    optics::apply_motion_blur(&mut exposure, sensor_w, sensor_h, blur, wrap);
+    optics::apply_lens_distortion(&mut exposure, sensor_w, sensor_h, params.lens_distortion);
+    optics::apply_defocus(&mut exposure, sensor_w, sensor_h, params.defocus_radius, wrap);
+    let (halation, full_well) = (&params.halation, params.full_well);
+    optics::apply_halation(&mut exposure, sensor_w, sensor_h, halation, full_well, wrap);
+    apply_scan_skew(&mut exposure, sensor_w, sensor_h, params);
+
+    // Hand the crop over as an HDR source at exactly the crop size, so the
+    // resize in step 1 is a straight copy
+    let crop = image::Rgb32FImage::from_fn(roi.width, roi.height, |x, y| {
+        let e = exposure[(roi.y + y) as usize * sensor_w + (roi.x + x) as usize];
+        image::Rgb(e.map(|v| (v / params.full_well) as f32))
+    });
+    let crop_params = PipelineParams {
+        sensor_width: roi.width,
+        sensor_height: roi.height,
+        anamorphic_output: false,
+        fit_mode: FitMode::Stretch,
+        area_sum_downscale: false,
+        lens_distortion: 0.0,
+        defocus_radius: 0.0,
+        motion_blur: MotionBlur::default(),
+        halation: Halation::default(),
+        scan_skew: 0.0,
+        scan_scale_error: 0.0,
+        scan_wobble: 0.0,
+        ..params.clone()
+    };
+    process_timed(&image::DynamicImage::ImageRgb32F(crop), &crop_params, spice_cache)
+}
+
+/// Replace columns from `split_x` rightward of an RGB8 frame with `right`'s,
+/// for side-by-side comparisons of two renders of the same source.
+pub fn split_composite(left: &mut [u8], right: &[u8], width: usize, split_x: usize) {
+    let split_x = split_x.min(width);
+    for (row, other) in left.chunks_exact_mut(width * 3).zip(right.chunks_exact(width * 3)) {
+        row[split_x * 3..].copy_from_slice(&other[split_x * 3..]);
+    }
+}
+
+/// Color rendering from linear demosaiced RGB [0..1] to display RGB:
+/// white balance, saturation/hue, gamma, brightness/contrast, tone curves.
+pub fn render_color(rgb: &mut [[f64; 3]], params: &PipelineParams) {
+    spectral::apply_white_balance(
+        rgb,
+        params.white_balance_r,
+        params.white_balance_g,
+        params.white_balance_b,
+    );
+    spectral::apply_saturation_vibrance(rgb, params.saturation, params.vibrance);
+    spectral::apply_hue_rotation(rgb, params.hue_rotation);
+
+    // Clamp before gamma
+    for pixel in rgb.iter_mut() {
+        for c in 0..3 {
+            pixel[c] = pixel[c].clamp(0.0, 1.0);
+        }
+    }
+
+    spectral::apply_gamma(rgb, params.gamma);
+    spectral::apply_brightness_contrast(rgb, params.brightness, params.contrast);
+    spectral::apply_tone_curves(rgb, &params.tone_curves);
+}
+
+/// Bake `render_color` into a `size`^3 LUT (linear input, display output)
+/// so the current look can be reused in other tools.
+pub fn color_transform_lut(params: &PipelineParams, size: usize) -> Lut3d {
+    let mut lut = Lut3d::from_fn("physical_ccd_glitch color", size, |rgb| rgb);
+    render_color(&mut lut.data, params);
+    lut
+}
+
+/// Process using SPICE-derived transfer function and timing artifacts.
+///
+/// Returns true if SPICE processing was applied (replacing math pipeline stages),
+/// false if SPICE mode is Off or no cache is available. Bypassed stages are
+/// skipped as in the math pipeline, a bypassed ADC quantizing ideally.
+fn process_spice_branch(
+    mosaic: &mut [f64],
+    width: usize,
+    height: usize,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    temporal: Option<&GlitchFrame>,
+    bypassed: impl Fn(StageBypass) -> bool,
+) -> bool {
+    use crate::spice::{SpiceMode, transfer_function};
+
+    let cache = match spice_cache {
+        Some(c) if params.spice.mode != SpiceMode::Off => c,
+        _ => return false,
+    };
+    // Full readout takes the clocking from the circuits too; the other modes
+    // keep the mathematical blooming and transfer
+    let full = params.spice.mode == SpiceMode::FullReadout;
+
+    if !bypassed(StageBypass::V_CLOCK) {
+        transfer_function::apply_missing_pulses(
+            mosaic,
+            width,
+            height,
+            params.spice.missing_pulse_rate,
+        );
+    }
+    if !full && !bypassed(StageBypass::BLOOMING) {
+        crate::ccd::blooming::apply_blooming(
+            mosaic,
+            width,
+            height,
+            &params.bloom(),
+            params.tile_seamless,
+        );
+    }
+
+    // Vertical transfer, with the SPICE-derived CTE in full readout
+    if full && !bypassed(StageBypass::V_CLOCK) {
+        apply_spice_parallel_cte(mosaic, width, height, cache.effective_cte, params);
+    } else if !full && !bypassed(StageBypass::V_CLOCK) && !params.line_scan.enabled {
+        crate::ccd::transfer::apply_saturation_faults(
+            mosaic,
+            width,
+            height,
+            params.saturation_clock_coupling,
+            params.full_well * params.bloom_threshold,
+            params.v_phases,
+            &mut params.determinism.rng(Stream::Saturation),
+        );
+        v_clock_transfer(mosaic, width, height, params, temporal);
+    }
+
+    // Horizontal transfer, through the SPICE-derived CTE in full readout
+    if !bypassed(StageBypass::H_CLOCK) {
+        if full {
+            let mut serial = transfer::SerialTransfer::new(
+                cache.effective_cte,
+                0.0,
+                0.0,
+                params.tile_seamless,
+                params.determinism.rng(Stream::HClock),
+            );
+            stream_serial(mosaic, width, height, params, &mut serial);
+        } else {
+            stream_serial(mosaic, width, height, params, &mut serial_transfer(params));
+        }
+    }
+    apply_readout_abort(mosaic, width, height, params);
+
+    // SPICE transfer curve (composed pixel -> amp) as the output amplifier
+    if !bypassed(StageBypass::AMPLIFIER) {
+        let full_well = Electrons(params.full_well);
+        let mut output = transfer_function::TransferCurve::new(&cache.transfer_curve, full_well);
+        stream_amplifier(mosaic, width, height, params, &mut output);
+    }
+
+    // ADC: SPICE-derived after CDS residual noise, except that the transfer
+    // curve mode keeps the mathematical ADC
+    let ideal = bypassed(StageBypass::ADC);
+    if ideal || params.spice.mode == SpiceMode::TransferCurveOnly {
+        stream_adc(mosaic, width, height, params, ideal);
+    } else {
+        apply_spice_cds_noise(mosaic, cache.cds_rejection, cache.noise_sigma);
+        apply_spice_adc(mosaic, &cache.adc_transfer, &cache.adc_dnl, params);
+    }
+
+    // Ringing from clock driver
+    if full && !bypassed(StageBypass::H_CLOCK) {
+        transfer_function::apply_ringing(
+            mosaic,
+            width,
+            height,
+            &cache.ringing_kernel,
+            params.tile_seamless,
+        );
+    }
+
+    true
+}
+
+/// Truncate readout after `params.readout_abort` of the rows.
+fn apply_readout_abort(mosaic: &mut [f64], width: usize, height: usize, params: &PipelineParams) {
+    transfer::apply_readout_abort(
+        mosaic,
+        width,
+        height,
+        params.readout_abort,
+        params.abort_fill,
+        params.read_noise.max(params.full_well * 0.01),
+        &mut params.determinism.rng(Stream::ReadoutAbort),
+    );
+}
+
+/// The serial register's clocking as set, for `stream_serial`.
+fn serial_transfer(params: &PipelineParams) -> transfer::SerialTransfer<rand::rngs::StdRng> {
+    transfer::SerialTransfer::new(
+        params.h_cte,
+        params.h_glitch_rate,
+        params.h_ringing,
+        params.tile_seamless,
+        params.determinism.rng(Stream::HClock),
+    )
+}
+
+/// Clock each row out through `serial` and the serial register's defects.
+fn stream_serial(
+    mosaic: &mut [f64],
+    width: usize,
+    height: usize,
+    params: &PipelineParams,
+    serial: &mut dyn RowStage,
+) {
+    let defects = &params.serial_defects;
+    let mut defects = transfer::SerialDefectStage::new(defects, width, params.full_well);
+    let stages: &mut [&mut dyn RowStage] = &mut [serial, &mut defects];
+    readout::stream(mosaic, width, height, params.readout_direction, stages);
+}
+
+/// Read each row through `output` on the sagging supply, followed by reset
+/// lag and interference pickup.
+fn stream_amplifier(
+    mosaic: &mut [f64],
+    width: usize,
+    height: usize,
+    params: &PipelineParams,
+    output: &mut dyn RowStage,
+) {
+    let mut supply =
+        SupplyFeedback::new(&params.supply, params.spice.effective_vdd(), params.full_well);
+    let mut lag = amplifier::ResetLag::new(params.reset_lag);
+    let mut pickup =
+        amplifier::Pickup::new(&params.interference, params.determinism.rng(Stream::Interference));
+    let stages: &mut [&mut dyn RowStage] = &mut [&mut supply, output, &mut lag, &mut pickup];
+    readout::stream(mosaic, width, height, params.readout_direction, stages);
+}
+
+/// Convert each row through the dual-gain paths and the ADC, or an ideal
+/// quantizer without dual gain when `ideal`.
+fn stream_adc(
+    mosaic: &mut [f64],
+    width: usize,
+    height: usize,
+    params: &PipelineParams,
+    ideal: bool,
+) {
+    let (converter, dual) = if ideal {
+        (params.adc_params().ideal(), DualGain::default())
+    } else {
+        (params.adc_params(), params.dual_gain)
+    };
+    let rng = params.determinism.rng(Stream::Adc);
+    let mut converter = adc::Converter::new(&converter, &dual, rng);
+    readout::stream(mosaic, width, height, params.readout_direction, &mut [&mut converter]);
+}
+
+/// Apply vertical (parallel) charge trailing using the SPICE-derived CTE.
+fn apply_spice_parallel_cte(
+    mosaic: &mut [f64],
+    width: usize,
+    height: usize,
+    cte: f64,
+    params: &PipelineParams,
+) {
+    if cte >= 1.0 {
+        return;
+    }
+
+    let loss = 1.0 - cte;
+    for x in 0..width {
+        let mut trail = 0.0;
+        for y in 0..height {
+            let idx = y * width + x;
+            let lost = mosaic[idx] * loss;
+            mosaic[idx] -= lost;
+            mosaic[idx] += trail;
+            trail = lost;
+        }
+        if params.tile_seamless {
+            mosaic[x] += trail;
+        }
+    }
+}
+
+/// Apply CDS residual noise: Gaussian noise scaled by (1 - rejection).
+fn apply_spice_cds_noise(mosaic: &mut [f64], rejection: f64, noise_sigma: f64) {
+    let effective_noise = noise_sigma * (1.0 - rejection).max(0.0);
+    if effective_noise < 0.01 {
+        return;
+    }
+
+    // Simple deterministic noise based on index (reproducible)
+    for (i, val) in mosaic.iter_mut().enumerate() {
+        let hash = ((i as f64 * 0.6180339887).fract() * 2.0 - 1.0) * 2.0;
+        *val += hash * effective_noise;
+    }
+}
+
+/// Apply SPICE-derived ADC quantization.
+///
+/// Uses the 4-bit SPICE transfer function scaled to target bit depth,
+/// with DNL applied.
+fn apply_spice_adc(
+    mosaic: &mut [f64],
+    adc_transfer: &[(f64, u16)],
+    adc_dnl: &[f64],
+    params: &PipelineParams,
+) {
+    let max_code = ((1u64 << params.bit_depth) - 1) as f64;
+    let full_well = params.full_well;
+
+    if adc_transfer.is_empty() {
+        // Simple quantization fallback
+        for val in mosaic.iter_mut() {
+            let normalized = (*val / full_well).clamp(0.0, 1.0);
+            *val = (normalized * max_code).round();
+        }
+        return;
+    }
+
+    // ADC transfer is 4-bit (0..15). Scale to target bit depth.
+    let adc_max_code = adc_transfer.iter().map(|(_, c)| *c).max().unwrap_or(15) as f64;
+    let v_max = adc_transfer.last().map(|(v, _)| *v).unwrap_or(1.0);
+
+    for val in mosaic.iter_mut() {
+        let normalized = (*val / full_well).clamp(0.0, 1.0);
+        let v_equiv = normalized * v_max;
+
+        // Look up in ADC transfer function
+        let adc_code = lookup_adc_transfer(adc_transfer, v_equiv);
+
+        // Scale from 4-bit to target bit depth
+        let scaled = (adc_code as f64 / adc_max_code) * max_code;
+
+        // Apply DNL
+        let code_idx = (adc_code as usize).min(adc_dnl.len().saturating_sub(1));
+        let dnl_offset = if !adc_dnl.is_empty() {
+            adc_dnl[code_idx] * (max_code / adc_max_code)
+        } else {
+            0.0
+        };
+
+        *val = (scaled + dnl_offset).round().clamp(0.0, max_code);
+    }
+}
+
+/// Look up output code from ADC transfer function via interpolation.
+fn lookup_adc_transfer(transfer: &[(f64, u16)], v_in: f64) -> u16 {
+    if transfer.is_empty() {
+        return 0;
+    }
+
+    for i in (0..transfer.len()).rev() {
+        if v_in >= transfer[i].0 {
+            return transfer[i].1;
+        }
+    }
+    transfer[0].1
+}
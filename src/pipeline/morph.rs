@@ -0,0 +1,72 @@
+//! Parameter morphing: every settable value interpolated between two
+//! parameter sets, live or rendered as a transition.
+//!
+//! Numbers blend linearly, or geometrically where the schema makes the
+//! control logarithmic and both ends are positive, so a morph crosses a
+//! log-scaled range at an even pace. Integers round; flags and choices switch
+//! at the midpoint. Values with no settable form (curves, LUTs, waveforms)
+//! come from whichever end is nearer.
+
+use image::DynamicImage;
+
+use super::{ParamValue, PipelineParams, TemporalState, schema};
+use crate::spice::{SpiceCache, SpiceMode};
+
+/// The parameters `t` of the way from `from` (0) to `to` (1).
+pub fn interpolate(from: &PipelineParams, to: &PipelineParams, t: f64) -> PipelineParams {
+    let t = t.clamp(0.0, 1.0);
+    let mut params = if t < 0.5 { from.clone() } else { to.clone() };
+    for change in to.diff(from) {
+        let value = match (change.from, change.to) {
+            (ParamValue::Float(a), ParamValue::Float(b)) => {
+                ParamValue::Float(blend(change.name, a, b, t))
+            }
+            (ParamValue::Int(a), ParamValue::Int(b)) => {
+                ParamValue::Int(blend(change.name, a as f64, b as f64, t).round() as i64)
+            }
+            (a, b) => {
+                if t < 0.5 {
+                    a
+                } else {
+                    b
+                }
+            }
+        };
+        // `Data` entries fail here; `params` already holds the nearer side's data
+        let _ = params.set_value(change.name, &value.to_string());
+    }
+    params
+}
+
+fn blend(name: &str, a: f64, b: f64, t: f64) -> f64 {
+    let logarithmic = schema::find(name).is_some_and(|spec| spec.logarithmic);
+    if logarithmic && a > 0.0 && b > 0.0 {
+        a * (b / a).powf(t)
+    } else {
+        a + (b - a) * t
+    }
+}
+
+/// Render the morph in `frames` steps, both ends included, as a sequence
+/// through `process_temporal`, handing each frame's (index, width, height,
+/// RGB8) to `frame`. Stops at the first error `frame` returns.
+pub fn render(
+    source: &DynamicImage,
+    from: &PipelineParams,
+    to: &PipelineParams,
+    frames: usize,
+    spice_cache: &mut Option<SpiceCache>,
+    mut frame: impl FnMut(usize, usize, usize, Vec<u8>) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut state = TemporalState::default();
+    let steps = frames.max(2) - 1;
+    for index in 0..frames.max(1) {
+        let params = interpolate(from, to, index as f64 / steps as f64);
+        if params.spice.mode != SpiceMode::Off {
+            crate::spice::simulate_or_cache(&params.spice, &params.spice_sensor(), spice_cache);
+        }
+        let (w, h, bytes) = super::process_temporal(source, &params, spice_cache, &mut state);
+        frame(index, w, h, bytes)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//! Slanted-edge MTF (ISO 12233 style) of the processed image.
+//!
+//! A slanted-edge test pattern is pushed through the chain and measured at
+//! the Channel stage: linear RGB after readout, demosaic and channel effects,
+//! before the nonlinear color rendering. Rows across the edge are projected
+//! onto the edge normal into a 4x oversampled edge spread function, whose
+//! derivative (the line spread function) is Fourier transformed.
+
+use image::{DynamicImage, Rgb, RgbImage};
+
+use super::analysis::{analysis_params, draw_line};
+use super::test_patterns::{self, TestPattern};
+use super::{PipelineParams, StageBuffer, StageData};
+use crate::bitmap_font;
+
+/// ESF bins per pixel.
+const OVERSAMPLE: usize = 4;
+/// Half-width in pixels of the window sampled on each side of the edge.
+const HALF_WINDOW: usize = 32;
+/// Highest frequency reported, in cycles per pixel (Nyquist is 0.5).
+const MAX_FREQUENCY: f64 = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct MtfCurve {
+    pub channel: &'static str,
+    /// Modulation at each of `MtfReport::frequencies`, 1.0 at DC.
+    pub mtf: Vec<f64>,
+    /// Frequency (cycles/pixel) where modulation drops to 50%, if it does.
+    pub mtf50: Option<f64>,
+    /// Modulation at Nyquist (0.5 cycles/pixel).
+    pub at_nyquist: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MtfReport {
+    /// Edge angle off vertical as found in the output, in degrees.
+    pub edge_angle_deg: f64,
+    /// Spatial frequencies in cycles per pixel.
+    pub frequencies: Vec<f64>,
+    /// Luma first, then red, green and blue.
+    pub curves: Vec<MtfCurve>,
+}
+
+/// Process a slanted edge through `params` and measure its MTF.
+pub fn measure_mtf(
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+) -> Result<MtfReport, String> {
+    let params = analysis_params(params);
+    let source = edge_source(&params);
+
+    let mut captured: Option<(Vec<[f64; 3]>, usize, usize)> = None;
+    let mut tap = |stage: &'static str, data: StageData<'_>| {
+        if let ("Channel", StageBuffer::Rgb(pixels)) = (stage, &data.buffer) {
+            captured = Some((pixels.to_vec(), data.width, data.height));
+        }
+    };
+    super::process_tapped(&source, &params, spice_cache, Some(&mut tap));
+    let (rgb, width, height) = captured.ok_or("Pipeline produced no Channel stage")?;
+
+    measure_edge(&rgb, width, height)
+}
+
+/// The slanted-edge pattern at the sensor size, scaled so the light side
+/// sits below the blooming threshold (blooming would flatten the edge
+/// profile and read as a sharpness change).
+fn edge_source(params: &PipelineParams) -> DynamicImage {
+    let scale = (0.75 * params.bloom_threshold.clamp(0.1, 1.0) / test_patterns::LIGHT) as f32;
+    let mut img = test_patterns::generate(
+        TestPattern::SlantedEdge,
+        params.sensor_width,
+        params.sensor_height,
+    )
+    .into_rgb32f();
+    for v in img.iter_mut() {
+        *v *= scale;
+    }
+    DynamicImage::ImageRgb32F(img)
+}
+
+/// Measure the MTF of a near-vertical edge in a linear RGB frame.
+pub fn measure_edge(rgb: &[[f64; 3]], width: usize, height: usize) -> Result<MtfReport, String> {
+    if width < 16 || height < 16 || rgb.len() != width * height {
+        return Err(format!("Frame too small for edge measurement: {width}x{height}"));
+    }
+    let luma: Vec<f64> = rgb.iter().map(|p| (p[0] + p[1] + p[2]) / 3.0).collect();
+    let half = HALF_WINDOW.min(width / 4);
+
+    // Locate the edge in each row near where the pattern put it, then fit a line
+    let tan = test_patterns::SLANTED_EDGE_ANGLE_DEG.to_radians().tan();
+    let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0);
+    let rows: Vec<usize> = (height / 8..height - height / 8).collect();
+    let mut centroids = Vec::new();
+    for &y in &rows {
+        let guess = cx + (y as f64 + 0.5 - cy) * tan;
+        let (x0, x1) = window(guess, half, width);
+        let row = &luma[y * width..(y + 1) * width];
+        let (mut sum, mut weight) = (0.0, 0.0);
+        for x in x0.max(1)..x1.min(width - 1) {
+            let d = (row[x + 1] - row[x - 1]).abs();
+            sum += d * x as f64;
+            weight += d;
+        }
+        if weight > f64::EPSILON {
+            centroids.push((y as f64, sum / weight));
+        }
+    }
+    if centroids.len() < rows.len() / 2 {
+        return Err("Edge not found (too little contrast across the edge)".to_string());
+    }
+    let (slope, intercept) = fit_line(&centroids);
+    let cos = slope.atan().cos();
+
+    let bins = 2 * half * OVERSAMPLE;
+    let project = |values: &dyn Fn(usize) -> f64| {
+        let mut sums = vec![0.0; bins];
+        let mut counts = vec![0usize; bins];
+        for &y in &rows {
+            let edge = intercept + slope * y as f64;
+            let (x0, x1) = window(edge, half, width);
+            for x in x0..x1 {
+                let distance = (x as f64 - edge) * cos;
+                let bin = ((distance + half as f64) * OVERSAMPLE as f64).floor();
+                if bin >= 0.0 && (bin as usize) < bins {
+                    sums[bin as usize] += values(y * width + x);
+                    counts[bin as usize] += 1;
+                }
+            }
+        }
+        esf_to_mtf(&fill_empty(&sums, &counts))
+    };
+
+    let (frequencies, luma_mtf) = project(&|i| luma[i]);
+    let mut curves = vec![curve("Luma", luma_mtf, &frequencies)];
+    for (c, channel) in ["Red", "Green", "Blue"].into_iter().enumerate() {
+        let (_, mtf) = project(&|i| rgb[i][c]);
+        curves.push(curve(channel, mtf, &frequencies));
+    }
+
+    Ok(MtfReport {
+        edge_angle_deg: slope.atan().to_degrees(),
+        frequencies,
+        curves,
+    })
+}
+
+/// Column range of `half` pixels either side of `center`, clamped to the row.
+fn window(center: f64, half: usize, width: usize) -> (usize, usize) {
+    let center = center.round().clamp(0.0, width as f64) as usize;
+    (center.saturating_sub(half), (center + half).min(width))
+}
+
+/// Least-squares x = intercept + slope * y through (y, x) samples.
+fn fit_line(points: &[(f64, f64)]) -> (f64, f64) {
+    let n = points.len() as f64;
+    let (sy, sx) = points.iter().fold((0.0, 0.0), |(a, b), p| (a + p.0, b + p.1));
+    let (my, mx) = (sy / n, sx / n);
+    let (mut cov, mut var) = (0.0, 0.0);
+    for &(y, x) in points {
+        cov += (y - my) * (x - mx);
+        var += (y - my) * (y - my);
+    }
+    let slope = if var > 0.0 { cov / var } else { 0.0 };
+    (slope, mx - slope * my)
+}
+
+/// Bin averages, with empty bins copied from the nearest filled one to the left
+/// (or right, at the start).
+fn fill_empty(sums: &[f64], counts: &[usize]) -> Vec<f64> {
+    let mut esf: Vec<Option<f64>> = sums
+        .iter()
+        .zip(counts)
+        .map(|(&s, &n)| (n > 0).then(|| s / n as f64))
+        .collect();
+    let first = esf.iter().flatten().next().copied().unwrap_or(0.0);
+    let mut last = first;
+    for v in esf.iter_mut() {
+        last = *v.get_or_insert(last);
+    }
+    esf.into_iter().map(|v| v.unwrap_or(first)).collect()
+}
+
+/// Differentiate the ESF, window the LSF, and return (frequencies, MTF).
+fn esf_to_mtf(esf: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let n = esf.len();
+    let mut lsf = vec![0.0; n];
+    for i in 1..n - 1 {
+        lsf[i] = (esf[i + 1] - esf[i - 1]) / 2.0;
+    }
+
+    // Hamming window centered on the LSF centroid
+    let total: f64 = lsf.iter().map(|v| v.abs()).sum();
+    let center = if total > 0.0 {
+        lsf.iter().enumerate().map(|(i, v)| i as f64 * v.abs()).sum::<f64>() / total
+    } else {
+        n as f64 / 2.0
+    };
+    let half = (n as f64 / 2.0).max(1.0);
+    for (i, v) in lsf.iter_mut().enumerate() {
+        let t = ((i as f64 - center) / half).clamp(-1.0, 1.0);
+        *v *= 0.54 + 0.46 * (std::f64::consts::PI * t).cos();
+    }
+
+    let bin = 1.0 / OVERSAMPLE as f64;
+    let df = 1.0 / (n as f64 * bin);
+    let count = (MAX_FREQUENCY / df).floor() as usize + 1;
+    let magnitude = |k: usize| {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (i, &v) in lsf.iter().enumerate() {
+            let phase = std::f64::consts::TAU * (k * i) as f64 / n as f64;
+            re += v * phase.cos();
+            im -= v * phase.sin();
+        }
+        (re * re + im * im).sqrt()
+    };
+    let dc = magnitude(0).max(f64::EPSILON);
+
+    let frequencies: Vec<f64> = (0..count).map(|k| k as f64 * df).collect();
+    let mtf = frequencies
+        .iter()
+        .enumerate()
+        .map(|(k, &f)| {
+            // Undo the central difference's own sinc rolloff
+            let x = std::f64::consts::TAU * f * bin;
+            let derivative = if x > 0.0 { (x.sin() / x).max(0.1) } else { 1.0 };
+            magnitude(k) / dc / derivative
+        })
+        .collect();
+    (frequencies, mtf)
+}
+
+fn curve(channel: &'static str, mtf: Vec<f64>, frequencies: &[f64]) -> MtfCurve {
+    let mtf50 = frequencies
+        .windows(2)
+        .zip(mtf.windows(2))
+        .find(|(_, m)| m[0] >= 0.5 && m[1] < 0.5)
+        .map(|(f, m)| f[0] + (f[1] - f[0]) * (m[0] - 0.5) / (m[0] - m[1]));
+    let at_nyquist = interpolate(frequencies, &mtf, 0.5);
+    MtfCurve { channel, mtf, mtf50, at_nyquist }
+}
+
+fn interpolate(xs: &[f64], ys: &[f64], x: f64) -> f64 {
+    match xs.iter().position(|&v| v >= x) {
+        Some(0) | None => ys.first().copied().unwrap_or(0.0),
+        Some(i) => {
+            let t = (x - xs[i - 1]) / (xs[i] - xs[i - 1]);
+            ys[i - 1] + (ys[i] - ys[i - 1]) * t
+        }
+    }
+}
+
+impl MtfReport {
+    /// One row per frequency, one column per channel, then the summary values.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("cycles_per_pixel");
+        for c in &self.curves {
+            out.push_str(&format!(",{}", c.channel.to_lowercase()));
+        }
+        out.push('\n');
+        for (k, f) in self.frequencies.iter().enumerate() {
+            out.push_str(&format!("{f:.6}"));
+            for c in &self.curves {
+                out.push_str(&format!(",{:.6}", c.mtf[k]));
+            }
+            out.push('\n');
+        }
+        out.push_str("\nchannel,mtf50_cycles_per_pixel,mtf_at_nyquist\n");
+        for c in &self.curves {
+            let mtf50 = c.mtf50.map(|v| format!("{v:.6}")).unwrap_or_default();
+            out.push_str(&format!("{},{mtf50},{:.6}\n", c.channel.to_lowercase(), c.at_nyquist));
+        }
+        out.push_str(&format!("\nedge_angle_deg,{:.3}\n", self.edge_angle_deg));
+        out
+    }
+
+    /// MTF curves from DC to 1 cycle/pixel, with Nyquist and 50% markers.
+    pub fn plot(&self) -> RgbImage {
+        const W: u32 = 640;
+        const H: u32 = 360;
+        const MARGIN: i64 = 16;
+        let background = Rgb([12u8, 14, 22]);
+        let text = Rgb([220u8, 220, 230]);
+        let guide = Rgb([60u8, 60, 75]);
+        let colors = [
+            Rgb([230u8, 230, 230]),
+            Rgb([255u8, 80, 80]),
+            Rgb([80u8, 220, 80]),
+            Rgb([90u8, 140, 255]),
+        ];
+
+        let mut img = RgbImage::from_pixel(W, H, background);
+        let (left, right) = (MARGIN, W as i64 - MARGIN);
+        let (top, bottom) = (28i64, H as i64 - 80);
+        let y_max = self
+            .curves
+            .iter()
+            .flat_map(|c| c.mtf.iter().copied())
+            .fold(1.0f64, f64::max)
+            .min(2.0);
+        let to_px = |f: f64, m: f64| {
+            (
+                left + (f / MAX_FREQUENCY * (right - left) as f64) as i64,
+                bottom - (m.clamp(0.0, y_max) / y_max * (bottom - top) as f64) as i64,
+            )
+        };
+
+        draw_line(&mut img, to_px(0.5, 0.0), to_px(0.5, y_max), guide);
+        draw_line(&mut img, to_px(0.0, 0.5), to_px(MAX_FREQUENCY, 0.5), guide);
+        draw_line(&mut img, to_px(0.0, 0.0), to_px(MAX_FREQUENCY, 0.0), guide);
+        for (c, &color) in self.curves.iter().zip(&colors).rev() {
+            let points: Vec<(i64, i64)> =
+                self.frequencies.iter().zip(&c.mtf).map(|(&f, &m)| to_px(f, m)).collect();
+            for pair in points.windows(2) {
+                draw_line(&mut img, pair[0], pair[1], color);
+            }
+        }
+
+        let title = format!("SLANTED EDGE MTF   EDGE {:.2} DEG", self.edge_angle_deg);
+        bitmap_font::draw_text(&mut img, &title, MARGIN, 8, 1, text);
+        let (nx, _) = to_px(0.5, 0.0);
+        bitmap_font::draw_text(&mut img, "NYQUIST", nx + 4, top, 1, guide);
+        bitmap_font::draw_text(&mut img, "0 TO 1 CYCLES/PIXEL", right - 120, bottom + 6, 1, guide);
+        bitmap_font::draw_text(&mut img, "MTF50      AT NYQUIST", MARGIN + 60, bottom + 22, 1, text);
+        for (i, (c, &color)) in self.curves.iter().zip(&colors).enumerate() {
+            let y = bottom + 34 + i as i64 * 10;
+            let mtf50 = c.mtf50.map(|v| format!("{v:.3}")).unwrap_or_else(|| "-".to_string());
+            let name = c.channel.to_uppercase();
+            bitmap_font::draw_text(&mut img, &name, MARGIN, y, 1, color);
+            let values = format!("{mtf50:<10} {:.3}", c.at_nyquist);
+            bitmap_font::draw_text(&mut img, &values, MARGIN + 60, y, 1, text);
+        }
+        img
+    }
+}
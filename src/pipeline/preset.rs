@@ -0,0 +1,227 @@
+//! Partial parameter presets.
+//!
+//! A preset stores only the parameters that differ from the defaults, one
+//! `name = value` line each (names as in `PipelineParams::values`), so it
+//! stays readable and leaves everything else alone. Presets layer: applying
+//! a sensor preset and then a glitch preset sets both groups, the later one
+//! winning where they overlap. Export can be limited to one parameter group.
+
+use std::path::Path;
+
+use super::{schema, ParamChange, ParamValue, PipelineParams};
+
+/// Parameter groups a preset can be limited to, each a set of schema
+/// categories.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PresetGroup {
+    All,
+    /// Framing, sensor config, optics, exposure and noise.
+    Sensor,
+    /// Blooming through the ADC, and the SPICE circuit parameters.
+    Readout,
+    Glitch,
+    /// Channel effects, color and the final composite.
+    Color,
+}
+
+impl PresetGroup {
+    pub const ALL: &[PresetGroup] = &[
+        PresetGroup::All,
+        PresetGroup::Sensor,
+        PresetGroup::Readout,
+        PresetGroup::Glitch,
+        PresetGroup::Color,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PresetGroup::All => "All",
+            PresetGroup::Sensor => "Sensor",
+            PresetGroup::Readout => "Readout",
+            PresetGroup::Glitch => "Glitch",
+            PresetGroup::Color => "Color",
+        }
+    }
+
+    /// Schema categories whose parameters belong to the group. Stacking,
+    /// 3-CCD, temporal, determinism and bypass settings are only in `All`.
+    fn categories(self) -> &'static [&'static str] {
+        match self {
+            PresetGroup::All => &[],
+            PresetGroup::Sensor => &["framing", "sensor", "optics", "camera_osd", "exposure"],
+            PresetGroup::Readout => {
+                &["blooming", "v_clock", "h_clock", "amplifier", "adc", "spice"]
+            }
+            PresetGroup::Glitch => &["glitch"],
+            PresetGroup::Color => &["channel", "color", "composite", "overlay"],
+        }
+    }
+
+    /// Names of every parameter in the group.
+    fn names(self) -> Vec<&'static str> {
+        if self == PresetGroup::All {
+            let params = PipelineParams::default();
+            return params.values().into_iter().map(|(name, _)| name).collect();
+        }
+        self.categories()
+            .iter()
+            .flat_map(|&category| schema::category(category))
+            .map(|spec| spec.name)
+            .collect()
+    }
+}
+
+/// Built-in glitch looks, as (name, values). Each touches only glitch and
+/// readout fault parameters, so it layers over any sensor preset.
+const GLITCH_PRESETS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Clock Fault",
+        &[
+            ("v_glitch_rate", "0.05"),
+            ("h_glitch_rate", "0.01"),
+            ("saturation_clock_coupling", "1.5"),
+            ("v_waveform_distortion", "0.3"),
+        ],
+    ),
+    (
+        "Sync Loss",
+        &[
+            ("sync.loss_rate", "2"),
+            ("sync.loss_length", "40"),
+            ("sync.dropout_rate", "5"),
+            ("sync.dropout_length", "6"),
+        ],
+    ),
+    (
+        "Bit Rot",
+        &[("bit_errors", "0.05"), ("dnl_errors", "0.3"), ("bit_xor_mask", "16")],
+    ),
+    (
+        "Tired CTE",
+        &[
+            ("v_cte", "0.9995"),
+            ("h_cte", "0.9998"),
+            ("parallel_smear", "0.2"),
+            ("h_ringing", "0.3"),
+        ],
+    ),
+    (
+        "Torn Frame",
+        &[
+            ("pixel_shift_amount", "0.6"),
+            ("block_shift_amount", "0.8"),
+            ("scan_line_frequency", "0.5"),
+        ],
+    ),
+    (
+        "Raw Misread",
+        &[("raw_packing", "12-bit"), ("raw_bit_offset", "3"), ("raw_stride_error", "4")],
+    ),
+];
+
+/// A named set of parameter values, applied in order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Preset {
+    pub name: String,
+    pub values: Vec<(String, String)>,
+}
+
+impl Preset {
+    /// The built-in glitch presets.
+    pub fn glitch_presets() -> Vec<Preset> {
+        GLITCH_PRESETS
+            .iter()
+            .map(|(name, values)| Preset {
+                name: name.to_string(),
+                values: values.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect(),
+            })
+            .collect()
+    }
+
+    /// The parameters of `params` in `group` that differ from the defaults.
+    /// Values with no settable form (curves, LUTs, waveforms) are left out;
+    /// `not_exportable` lists them.
+    pub fn from_params(name: &str, params: &PipelineParams, group: PresetGroup) -> Preset {
+        let defaults = PipelineParams::default();
+        let names = group.names();
+        let values = params
+            .diff(&defaults)
+            .into_iter()
+            .filter(|change| names.contains(&change.name))
+            .map(|change| (change.name, change.to.to_string()))
+            .filter(|(name, value)| defaults.clone().set_value(name, value).is_ok())
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        Preset {
+            name: name.to_string(),
+            values,
+        }
+    }
+
+    /// The changes `from_params` has to leave out of every preset: loaded
+    /// curves, LUTs, palettes and plugins.
+    pub fn not_exportable(params: &PipelineParams) -> Vec<ParamChange> {
+        params
+            .diff(&PipelineParams::default())
+            .into_iter()
+            .filter(|change| matches!(change.to, ParamValue::Data { .. }))
+            .collect()
+    }
+
+    /// Set every value on `params`; later presets applied on top win.
+    pub fn apply(&self, params: &mut PipelineParams) -> Result<(), String> {
+        for (name, value) in &self.values {
+            params
+                .set_value(name, value)
+                .map_err(|e| format!("Preset {}: {e}", self.name))?;
+        }
+        Ok(())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("# preset: {}\n", self.name);
+        for (name, value) in &self.values {
+            text.push_str(&format!("{name} = {value}\n"));
+        }
+        text
+    }
+
+    /// Parse `name = value` lines. Lines starting with `#` are comments; a
+    /// `# preset:` comment names the preset, otherwise it is `fallback_name`.
+    pub fn parse(text: &str, fallback_name: &str) -> Result<Preset, String> {
+        let mut preset = Preset {
+            name: fallback_name.to_string(),
+            values: Vec::new(),
+        };
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(name) = comment.trim().strip_prefix("preset:") {
+                    preset.name = name.trim().to_string();
+                }
+                continue;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Line {}: expected name = value", number + 1))?;
+            preset.values.push((name.trim().to_string(), value.trim().to_string()));
+        }
+        Ok(preset)
+    }
+
+    /// Read a preset file; it is named after the file unless it says otherwise.
+    pub fn load(path: &Path) -> Result<Preset, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let stem = path.file_stem().map_or(String::new(), |s| s.to_string_lossy().into_owned());
+        Preset::parse(&text, &stem)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_text())
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+}
@@ -0,0 +1,184 @@
+//! The clean reference render: the same source through the same sensor
+//! geometry, levels and color rendering with every glitch and noise source
+//! off, to compare a render against, isolate what its stages changed and
+//! measure how far it has strayed.
+
+use super::PipelineParams;
+
+/// Parameters the reference keeps from the render it stands beside: the
+/// framing, the signal levels and the color rendering. Everything else
+/// returns to its clean default. Names ending in `.` or `_` match a prefix.
+const KEEP: &[&str] = &[
+    "sensor_width",
+    "sensor_height",
+    "pixel_aspect",
+    "anamorphic_output",
+    "fit_mode",
+    "letterbox_pad",
+    "area_sum_downscale",
+    "tile_seamless",
+    "full_well",
+    "conversion_gain",
+    "use_abg",
+    "sensor_orientation",
+    "bit_depth",
+    "adc_gain",
+    "bias",
+    "auto_exposure",
+    "auto_exposure_",
+    "bayer_pattern",
+    "demosaic_algo",
+    "white_balance_",
+    "saturation",
+    "vibrance",
+    "hue_rotation",
+    "gamma",
+    "brightness",
+    "contrast",
+    "determinism.",
+];
+
+impl PipelineParams {
+    /// The clean reference for these parameters; see the module docs.
+    pub fn clean_reference(&self) -> PipelineParams {
+        let mut clean = PipelineParams::default();
+        for (name, value) in self.values() {
+            let kept = KEEP.iter().any(|p| {
+                if p.ends_with('.') || p.ends_with('_') {
+                    name.starts_with(p)
+                } else {
+                    name == *p
+                }
+            });
+            if kept {
+                // Every value `values` reports parses back
+                let _ = clean.set_value(name, &value.to_string());
+            }
+        }
+        clean.tone_curves = self.tone_curves.clone();
+        clean.output_lut = self.output_lut.clone();
+        clean
+    }
+}
+
+/// Render the clean reference of `params` for `source`, without SPICE. The
+/// reference is a single readout, so when `params` drizzles a stack it is
+/// resampled up to the drizzled size.
+pub fn process_reference(
+    source: &image::DynamicImage,
+    params: &PipelineParams,
+) -> (usize, usize, Vec<u8>) {
+    let (w, h, bytes) = super::process(source, &params.clean_reference(), &None);
+    let (out_w, out_h) = super::output_size(params, w, h);
+    if (out_w, out_h) == (w, h) {
+        return (w, h, bytes);
+    }
+    let clean = image::RgbImage::from_raw(w as u32, h as u32, bytes)
+        .expect("reference render matches its size");
+    let filter = image::imageops::FilterType::Triangle;
+    let resized = image::imageops::resize(&clean, out_w as u32, out_h as u32, filter);
+    (out_w, out_h, resized.into_raw())
+}
+
+/// Replace an RGB8 render with `gain` times its absolute difference from
+/// `reference`, so the pixels the stages touched light up against black.
+/// Fails when the two are not the same size.
+pub fn difference_view(bytes: &mut [u8], reference: &[u8], gain: f64) -> Result<(), String> {
+    if bytes.len() != reference.len() {
+        return Err(format!(
+            "Render has {} bytes but its reference {}",
+            bytes.len(),
+            reference.len()
+        ));
+    }
+    for (out, &clean) in bytes.iter_mut().zip(reference) {
+        let diff = (*out as f64 - clean as f64).abs() * gain;
+        *out = diff.round().min(255.0) as u8;
+    }
+    Ok(())
+}
+
+/// How far a render has strayed from its clean reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    /// Peak signal-to-noise ratio over every channel, in dB; infinite when
+    /// the two are identical.
+    pub psnr: f64,
+    /// Mean structural similarity of the luma over 8x8 windows: 1 for
+    /// identical images, falling toward 0 as structure is lost.
+    pub ssim: f64,
+}
+
+impl Metrics {
+    pub fn summary(&self) -> String {
+        if self.psnr.is_finite() {
+            format!("PSNR {:.2} dB, SSIM {:.4}", self.psnr, self.ssim)
+        } else {
+            format!("PSNR inf, SSIM {:.4}", self.ssim)
+        }
+    }
+}
+
+/// SSIM window side and the step between windows.
+const SSIM_WINDOW: usize = 8;
+const SSIM_STEP: usize = 4;
+
+/// Compare a `width` x `height` RGB8 render with its reference.
+pub fn metrics(processed: &[u8], reference: &[u8], width: usize, height: usize) -> Metrics {
+    let n = processed.len().min(reference.len());
+    let squared: f64 = processed
+        .iter()
+        .zip(reference)
+        .map(|(&a, &b)| (a as f64 - b as f64).powi(2))
+        .sum();
+    let mse = squared / n.max(1) as f64;
+    let psnr = if mse > 0.0 { 10.0 * (255.0 * 255.0 / mse).log10() } else { f64::INFINITY };
+
+    let luma = |bytes: &[u8]| -> Vec<f64> {
+        bytes
+            .chunks_exact(3)
+            .take(width * height)
+            .map(|p| 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64)
+            .collect()
+    };
+    Metrics {
+        psnr,
+        ssim: ssim(&luma(processed), &luma(reference), width, height),
+    }
+}
+
+/// Mean SSIM of two luma planes, windows stepping across both.
+fn ssim(a: &[f64], b: &[f64], width: usize, height: usize) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+    if a.len() < width * height || b.len() < width * height || width == 0 || height == 0 {
+        return 1.0;
+    }
+    let (win_w, win_h) = (SSIM_WINDOW.min(width), SSIM_WINDOW.min(height));
+    let mut total = 0.0;
+    let mut windows = 0;
+    for y0 in (0..=height - win_h).step_by(SSIM_STEP) {
+        for x0 in (0..=width - win_w).step_by(SSIM_STEP) {
+            let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+            for y in y0..y0 + win_h {
+                for x in x0..x0 + win_w {
+                    let (va, vb) = (a[y * width + x], b[y * width + x]);
+                    sa += va;
+                    sb += vb;
+                    saa += va * va;
+                    sbb += vb * vb;
+                    sab += va * vb;
+                }
+            }
+            let count = (win_w * win_h) as f64;
+            let (ma, mb) = (sa / count, sb / count);
+            let var_a = saa / count - ma * ma;
+            let var_b = sbb / count - mb * mb;
+            let cov = sab / count - ma * mb;
+            total += ((2.0 * ma * mb + C1) * (2.0 * cov + C2))
+                / ((ma * ma + mb * mb + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+    total / windows as f64
+}
@@ -0,0 +1,808 @@
+//! Parameter schema: the type, range, label, tooltip and category of every
+//! settable parameter, named as in `PipelineParams::values`. The app builds
+//! its controls from it, the OpenFX plugin its host parameters, and the CLI
+//! its parameter listing, so none of them can drift from the pipeline, and
+//! user-facing parameter text lives in one table. Defaults come from
+//! `PipelineParams::default()`.
+
+use super::PipelineParams;
+use super::alpha::AlphaMode;
+use super::composite::BlendMode;
+use super::stack::Combiner;
+use crate::ccd::ReadoutSpeed;
+use crate::ccd::adc::{AdcEncoding, AdcOverflow, CdsMode};
+use crate::ccd::noise::NoiseSource;
+use crate::ccd::sensor::ShutterMode;
+use crate::ccd::transfer::{
+    AbortFill, ClockPhases, ModulationShape, ReadoutDirection, ScheduleMode, SensorOrientation,
+    VerticalDirection,
+};
+use crate::color::bayer::BayerPattern;
+use crate::color::demosaic::DemosaicAlgo;
+use crate::color::dither::Dither;
+use crate::color::optics::Trajectory;
+use crate::color::palette::PaletteMode;
+use crate::glitch::channel::ChannelSwap;
+use crate::glitch::chroma::ChromaSubsampling;
+use crate::glitch::defect_repair::RepairMode;
+use crate::glitch::raw_pack::RawPacking;
+use crate::glitch::sync::BurstLength;
+use crate::image_io::FitMode;
+use crate::spice::SpiceMode;
+
+/// A parameter's type and the range its controls span.
+#[derive(Clone, Copy)]
+pub enum ParamKind {
+    Float { min: f64, max: f64 },
+    Int { min: i32, max: i32 },
+    Bool,
+    Choice(fn() -> Vec<&'static str>),
+}
+
+/// A parameter by its `values()` name, with its control label, tooltip and
+/// category.
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub label: &'static str,
+    /// What the parameter does, and its typical values where they help.
+    pub doc: &'static str,
+    pub category: &'static str,
+    pub kind: ParamKind,
+    /// Sliders span the range logarithmically.
+    pub logarithmic: bool,
+    /// Fixed display precision, for values that live close to a bound.
+    pub decimals: Option<usize>,
+}
+
+impl ParamSpec {
+    const fn log(self) -> Self {
+        Self {
+            logarithmic: true,
+            ..self
+        }
+    }
+
+    const fn doc(self, doc: &'static str) -> Self {
+        Self { doc, ..self }
+    }
+
+    const fn decimals(self, decimals: usize) -> Self {
+        Self {
+            decimals: Some(decimals),
+            ..self
+        }
+    }
+}
+
+const fn spec(
+    name: &'static str,
+    label: &'static str,
+    category: &'static str,
+    kind: ParamKind,
+) -> ParamSpec {
+    ParamSpec {
+        name,
+        label,
+        doc: "",
+        category,
+        kind,
+        logarithmic: false,
+        decimals: None,
+    }
+}
+
+const fn double(
+    name: &'static str,
+    label: &'static str,
+    category: &'static str,
+    min: f64,
+    max: f64,
+) -> ParamSpec {
+    spec(name, label, category, ParamKind::Float { min, max })
+}
+
+const fn int(
+    name: &'static str,
+    label: &'static str,
+    category: &'static str,
+    min: i32,
+    max: i32,
+) -> ParamSpec {
+    spec(name, label, category, ParamKind::Int { min, max })
+}
+
+const fn boolean(name: &'static str, label: &'static str, category: &'static str) -> ParamSpec {
+    spec(name, label, category, ParamKind::Bool)
+}
+
+const fn choice(
+    name: &'static str,
+    label: &'static str,
+    category: &'static str,
+    options: fn() -> Vec<&'static str>,
+) -> ParamSpec {
+    spec(name, label, category, ParamKind::Choice(options))
+}
+
+/// Parameter categories, as (name, label), in pipeline order. Each is one
+/// section of the app, except `framing`, which shares Sensor Config.
+pub const CATEGORIES: &[(&str, &str)] = &[
+    ("framing", "Framing"),
+    ("sensor", "Sensor Config"),
+    ("optics", "Optics"),
+    ("camera_osd", "Camera OSD"),
+    ("exposure", "Exposure & Noise"),
+    ("blooming", "Blooming"),
+    ("v_clock", "V-Clock"),
+    ("h_clock", "H-Clock"),
+    ("amplifier", "Amplifier"),
+    ("adc", "ADC"),
+    ("stack", "Stacking"),
+    ("three_chip", "3-CCD"),
+    ("glitch", "Glitch"),
+    ("channel", "Channel"),
+    ("color", "Color"),
+    ("composite", "Compositing"),
+    ("overlay", "Frame Overlay"),
+    ("temporal", "Temporal"),
+    ("determinism", "Determinism"),
+    ("bypass", "Bypass"),
+    ("spice", "SPICE"),
+];
+
+/// Every settable parameter, in `values()` order. Curves, the output LUT,
+/// the loaded palette and noise print and plugin slots have no settable
+/// value and are left out, as are the
+/// per-chip override lists (`three_chip.red` and so on).
+#[rustfmt::skip]
+const PARAMS: &[ParamSpec] = &[
+    int("sensor_width", "Width", "framing", 64, 8192)
+        .doc("Photosites across the sensor; the source is fitted to it"),
+    int("sensor_height", "Height", "framing", 64, 8192)
+        .doc("Photosites down the sensor; the source is fitted to it"),
+    double("pixel_aspect", "Pixel Aspect (W/H)", "framing", 0.5, 2.0)
+        .doc("Photosite width over height; 1 is square, interline video sensors run 0.6-1.6"),
+    boolean("anamorphic_output", "Anamorphic Output", "framing")
+        .doc("Resample to square output pixels for non-square photosites"),
+    double("full_well", "Full Well (e-)", "sensor", 1000.0, 500_000.0).log()
+        .doc("Charge a photosite holds before it blooms: 20-40 ke- for small pixels, 100 ke- and \
+              up for large ones"),
+    double("conversion_gain", "Conversion Gain (µV/e-)", "sensor", 1.0, 50.0).log()
+        .doc("Sense node µV per electron; higher gain compresses with less charge"),
+    choice("readout_speed", "Readout Speed", "sensor", || {
+        names(ReadoutSpeed::ALL, ReadoutSpeed::name)
+    })
+        .doc("Slow reads as set; fast applies the preset's fast readout penalties below"),
+    double("fast_readout.read_noise", "Fast Read Noise x", "sensor", 1.0, 5.0)
+        .doc("Read noise multiplier at fast readout"),
+    double("fast_readout.cte_loss", "Fast CTE Loss x", "sensor", 1.0, 100.0).log()
+        .doc("Multiplier on the charge left behind per transfer at fast readout"),
+    double("fast_readout.ringing", "Fast Added Ringing", "sensor", 0.0, 0.5)
+        .doc("Horizontal ringing added at fast readout"),
+    int("fast_readout.bit_depth", "Fast Bit Depth Cap", "sensor", 8, 16)
+        .doc("ADC bit depth cap at fast readout"),
+    double("aging.age_years", "Age (years)", "sensor", 0.0, 50.0)
+        .doc("Years in service: leakage grows and the neutron background slowly adds traps \
+              and hot pixels"),
+    double("aging.radiation_dose", "Radiation Dose (krad)", "sensor", 0.0, 100.0)
+        .doc("Accumulated dose: lower CTE, more dark current, hot pixels and traps; about 10 \
+              krad per few years in low orbit"),
+    boolean("use_abg", "Anti-Blooming Gate", "sensor")
+        .doc("Overflow drains beside each pixel: less full well, far less blooming"),
+    choice("fit_mode", "Fit", "framing", || names(FitMode::ALL, FitMode::name))
+        .doc("Stretch distorts to fill, crop covers and trims, letterbox fits and pads"),
+    double("letterbox_pad", "Pad (e-)", "framing", 0.0, 500_000.0)
+        .doc("Charge placed in the letterbox bars; raise it to bloom and trail the bars too"),
+    boolean("area_sum_downscale", "Area-Sum Downscale (bin charge)", "framing")
+        .doc("Sum source pixels into each photosite, as binning does, so point highlights keep \
+              their energy"),
+    boolean("tile_seamless", "Seamless Tiling (wrap edges)", "framing")
+        .doc("Wrap spatial stages (blur, halation, charge trails, demosaic, sharpen, codec blocks) \
+              around the edges so the output tiles; lens distortion and scan skew still do not"),
+    choice("alpha_mode", "Alpha", "framing", || names(AlphaMode::ALL, AlphaMode::name))
+        .doc("Discard drops alpha, passthrough moves it with the optics and scan geometry, \
+              glitched also runs it through blooming and CTE trails like charge"),
+    double("lens_distortion", "Distortion (+barrel / -pincushion)", "optics", -0.5, 0.5)
+        .doc("Radial distortion: positive bows lines out (barrel), negative in (pincushion)"),
+    double("defocus_radius", "Defocus Radius (px)", "optics", 0.0, 20.0)
+        .doc("Disk blur radius in photosites; a few pixels softens, 10+ is badly out of focus"),
+    double("motion_blur.length", "Motion Blur (px)", "optics", 0.0, 200.0).log()
+        .doc("Scene motion during the exposure: tracking error or star trails"),
+    double("motion_blur.angle", "Motion Angle (deg)", "optics", -180.0, 180.0)
+        .doc("Direction of a linear trail, counterclockwise from the +x axis"),
+    choice("motion_blur.trajectory", "Trajectory", "optics", || {
+        names(Trajectory::ALL, Trajectory::name)
+    })
+        .doc("Linear for drift or shake, arc for rotation about the pivot like star trails"),
+    double("motion_blur.pivot_x", "Arc Pivot X (frame)", "optics", -1.0, 2.0)
+        .doc("Arc center as a fraction of the frame width; may lie outside the frame"),
+    double("motion_blur.pivot_y", "Arc Pivot Y (frame)", "optics", -1.0, 2.0)
+        .doc("Arc center as a fraction of the frame height; may lie outside the frame"),
+    double("halation.strength", "Halation", "optics", 0.0, 1.0)
+        .doc("Glow around highlights scattered off the cover glass, added before blooming"),
+    double("halation.threshold", "Halation Threshold (x full well)", "optics", 0.0, 2.0)
+        .doc("Charge, as a fraction of full well, above which pixels glow"),
+    double("halation.radius", "Halation Radius (px)", "optics", 1.0, 200.0).log()
+        .doc("Reach of the glow in photosites"),
+    double("halation.tint_r", "Halation Tint R", "optics", 0.0, 2.0)
+        .doc("Red weight of the glow; longer wavelengths scatter further"),
+    double("halation.tint_g", "Halation Tint G", "optics", 0.0, 2.0)
+        .doc("Green weight of the glow"),
+    double("halation.tint_b", "Halation Tint B", "optics", 0.0, 2.0)
+        .doc("Blue weight of the glow"),
+    double("scan_skew", "Scan Skew (px/row)", "optics", -2.0, 2.0)
+        .doc("Tilted sensor or misaligned linear scan: each row shifts sideways"),
+    double("scan_scale_error", "Scan Scale Error", "optics", -0.2, 0.2)
+        .doc("Row width drifts by this fraction down the frame, like an off-frequency clock"),
+    double("scan_wobble", "Scan Wobble (px)", "optics", 0.0, 20.0)
+        .doc("Sinusoidal sideways row shift in pixels, like a vibrating scan mirror"),
+    double("scan_wobble_frequency", "Wobble Frequency (cycles/frame)", "optics", 0.25, 64.0).log()
+        .doc("Wobble cycles over the frame height"),
+    boolean("camera_osd.date", "Date Stamp", "camera_osd")
+        .doc("Seven-segment amber date in the corner, like a quartz date back"),
+    int("camera_osd.year", "Year", "camera_osd", 1970, 2099)
+        .doc("Year of the date stamp; only the last two digits show"),
+    int("camera_osd.month", "Month", "camera_osd", 1, 12)
+        .doc("Month of the date stamp"),
+    int("camera_osd.day", "Day", "camera_osd", 1, 31)
+        .doc("Day of the date stamp"),
+    boolean("camera_osd.battery", "Battery Indicator", "camera_osd")
+        .doc("Battery icon in the top right corner"),
+    double("camera_osd.battery_level", "Battery Level", "camera_osd", 0.0, 1.0)
+        .doc("Charge the battery icon shows, in thirds"),
+    boolean("camera_osd.rec", "REC Indicator", "camera_osd")
+        .doc("Red dot and REC in the top left corner"),
+    double("camera_osd.brightness", "OSD Brightness (x full well)", "camera_osd", 0.0, 4.0)
+        .doc("Light the display adds to the scene; above 1 it saturates and blooms"),
+    double("dark_current_rate", "Dark Current (e-)", "exposure", 0.0, 1000.0).log()
+        .doc("Thermal electrons per pixel over the exposure: under 1 cooled, hundreds warm"),
+    double("dark_structure.edge_glow", "Edge Glow", "exposure", 0.0, 10.0)
+        .doc("Extra dark rate toward the sensor edges, as a multiple of the uniform rate"),
+    double("dark_structure.edge_width", "Edge Width", "exposure", 0.01, 0.5)
+        .doc("Width of the edge glow falloff as a fraction of the shorter side"),
+    int("dark_structure.clusters", "Hot Clusters", "exposure", 0, 50)
+        .doc("Hot regions with raised dark current, like crystal defects"),
+    double("dark_structure.cluster_gain", "Cluster Gain", "exposure", 1.0, 200.0).log()
+        .doc("Dark rate multiplier at the center of a hot region"),
+    double("dark_structure.cluster_radius", "Cluster Radius (px)", "exposure", 1.0, 100.0).log()
+        .doc("Typical hot region radius; each one varies around it"),
+    double("dark_structure.rings", "Tree Rings", "exposure", 0.0, 5.0)
+        .doc("Concentric dark current striations from the wafer's crystal growth"),
+    double("dark_structure.ring_period", "Ring Period (px)", "exposure", 4.0, 200.0).log()
+        .doc("Tree ring spacing in photosites"),
+    double("dark_structure.hot_pixels", "Hot Pixels (fraction)", "exposure", 0.0, 0.05).log()
+        .doc("Fraction of isolated pixels leaking 10-1000x the dark rate"),
+    int("dark_structure.seed", "Dark Pattern Seed", "exposure", 1, 9999)
+        .doc("Seed for hot cluster placement and the ring center"),
+    int("long_exposure.slices", "Exposure Slices", "exposure", 1, 64)
+        .doc("Integrate the exposure in this many time slices, so events during it land in the \
+              charge collected by then"),
+    double("long_exposure.cosmic_rate", "Cosmic Rays (per MP)", "exposure", 0.0, 500.0)
+        .doc("Cosmic ray hits per megapixel over the exposure: a few per minute on the ground"),
+    double("long_exposure.clock_glitch_rate", "Stray Clock Pulses", "exposure", 0.0, 1.0)
+        .doc("Chance per slice boundary of a stray parallel pulse: the charge so far steps a \
+              row and traps pump dipoles"),
+    int("long_exposure.traps", "Pumped Traps", "exposure", 0, 5000).log()
+        .doc("Trap sites that pump charge into the next row at each stray pulse"),
+    double("long_exposure.pump_charge", "Pump Charge (e-)", "exposure", 0.0, 10_000.0).log()
+        .doc("Electrons each trap moves per stray pulse"),
+    int("long_exposure.seed", "Long Exposure Seed (0 = random)", "exposure", 0, 9999)
+        .doc("Seed for hits, traps and pulses; 0 draws a fresh pattern every render"),
+    double("read_noise", "Read Noise (e-)", "exposure", 0.0, 100.0)
+        .doc("Amplifier noise per read: 2-5 e- for scientific CCDs, 10-30 e- for video ones"),
+    boolean("shot_noise_enabled", "Shot Noise", "exposure")
+        .doc("Poisson photon noise: grain that grows with the square root of the signal"),
+    boolean("hdr_merge.enabled", "HDR Merge", "exposure")
+        .doc("Merge a bracket of 8-bit exposures to HDR and boost it past full well, so highlight \
+              detail reaches the blooming stage"),
+    int("hdr_merge.exposures", "Bracket Exposures", "exposure", 2, 9)
+        .doc("Captures in the bracket, centred on the source exposure"),
+    double("hdr_merge.ev_step", "Bracket Step (EV)", "exposure", 0.5, 4.0)
+        .doc("Exposure difference between neighbouring captures, in stops"),
+    double("hdr_merge.boost", "HDR Boost (EV)", "exposure", 0.0, 6.0)
+        .doc("Gain applied to the merged radiance before readout, in stops"),
+    choice("noise.source", "Noise Source", "exposure", || {
+        names(NoiseSource::ALL, NoiseSource::name)
+    })
+        .doc("Seeded sources repeat the same grain every run; blue and value noise trade exact \
+              statistics for an even or blotchy texture"),
+    int("noise.seed", "Noise Seed", "exposure", 1, 9999)
+        .doc("Seed for every source but random; each stage derives its own stream"),
+    double("noise.scale", "Noise Scale (px)", "exposure", 1.0, 32.0).log()
+        .doc("Value noise cell size in pixels; larger is blotchier"),
+    double("row_exposure_depth", "Row Exposure Mod", "exposure", 0.0, 1.0)
+        .doc("Scale each row's exposure by a signal over the readout: flicker bands, slit-scan \
+              sweeps, or a loaded CSV/WAV waveform"),
+    choice("row_exposure.shape", "Row Exposure Shape", "exposure", || {
+        names(ModulationShape::ALL, ModulationShape::name)
+    })
+        .doc("Signal the row exposure follows over the readout"),
+    double("row_exposure.frequency", "Row Exposure Freq (cycles/frame)", "exposure",
+        0.25, 64.0).log()
+        .doc("Cycles of the signal over the frame"),
+    double("row_exposure.phase", "Row Exposure Phase (cycles)", "exposure", 0.0, 1.0)
+        .doc("Offset of the signal at the first row, in cycles"),
+    double("flicker.depth", "Light Flicker", "exposure", 0.0, 1.0)
+        .doc("PWM LED or mains lamp flicker, integrated over each row's exposure window"),
+    double("flicker.frequency_hz", "Flicker Freq (Hz)", "exposure", 1.0, 5000.0).log()
+        .doc("Light pulse rate: 100/120 Hz for mains lamps, hundreds to thousands for PWM LEDs"),
+    double("flicker.duty_cycle", "Flicker Duty Cycle", "exposure", 0.01, 1.0)
+        .doc("Fraction of each period the light is on"),
+    double("flicker.phase", "Flicker Phase (cycles)", "exposure", 0.0, 1.0)
+        .doc("Where in the cycle the first row's exposure starts, in cycles"),
+    double("flicker.exposure_ms", "Flicker Exposure (ms)", "exposure", 0.0, 100.0).log()
+        .doc("Integration time of each row; exposures spanning many periods average out"),
+    double("flicker.row_time_us", "Flicker Row Time (us)", "exposure", 1.0, 500.0).log()
+        .doc("Delay between the exposure starts of consecutive rows (rolling shutter only)"),
+    choice("flicker.shutter", "Flicker Shutter", "exposure", || {
+        names(ShutterMode::ALL, ShutterMode::name)
+    })
+        .doc("Global shifts the whole frame's brightness; rolling staggers rows into bands"),
+    double("abg_strength", "ABG Strength", "blooming", 0.0, 1.0)
+        .doc("How much excess charge the anti-blooming drain removes; 1 stops blooming"),
+    double("bloom_threshold", "Bloom Threshold", "blooming", 0.1, 1.0)
+        .doc("Fraction of full well at which charge starts to spill"),
+    boolean("bloom_vertical", "Vertical Bloom", "blooming")
+        .doc("Spill along columns as in a real CCD; off spills along rows"),
+    choice("v_phases", "V-Clock Phases", "sensor", || names(ClockPhases::ALL, ClockPhases::name))
+        .doc("Vertical clock phases: more phases hold more charge, and a bad pulse can split \
+              a row or step it backward"),
+    double("v_cte", "CTE", "v_clock", 0.99, 1.0).decimals(6)
+        .doc("Charge kept per vertical transfer: 0.99999+ for good sensors, lower trails \
+              charge down the columns"),
+    double("v_glitch_rate", "Glitch Rate", "v_clock", 0.0, 0.5)
+        .doc("Chance per row of a clock fault that stalls, skips or shifts the row"),
+    choice("v_glitch_schedule.mode", "Glitch Schedule", "v_clock", || {
+        names(ScheduleMode::ALL, ScheduleMode::name)
+    })
+        .doc("Random rows, bursts of rows, every Nth row, or a rate ramping down the frame"),
+    int("v_glitch_schedule.burst_length", "Burst Length", "v_clock", 1, 256)
+        .doc("Rows each burst lasts"),
+    int("v_glitch_schedule.period", "Period (rows)", "v_clock", 2, 512)
+        .doc("Rows between periodic glitches"),
+    int("v_glitch_schedule.offset", "Offset (rows)", "v_clock", 0, 511)
+        .doc("First row of the periodic glitches"),
+    int("v_glitch_schedule.seed", "Glitch Seed (0 = random)", "v_clock", 0, 9999)
+        .doc("Seed for reproducible glitches; 0 draws a fresh pattern every render"),
+    double("saturation_clock_coupling", "Saturation Coupling", "v_clock", 0.0, 5.0)
+        .doc("Saturated charge disturbs the clocks: faults cluster around blown-out areas"),
+    double("v_waveform_distortion", "Waveform Distortion", "v_clock", 0.0, 1.0)
+        .doc("Clock amplitude varies over the readout, scaling the charge moved per row"),
+    choice("v_modulation.shape", "Clock Modulation", "v_clock", || {
+        names(ModulationShape::ALL, ModulationShape::name)
+    })
+        .doc("Signal the clock distortion follows over the readout"),
+    double("v_modulation.frequency", "Clock Mod Freq (cycles/frame)", "v_clock", 0.25, 64.0).log()
+        .doc("Cycles of the clock distortion over the frame"),
+    double("v_modulation.phase", "Clock Mod Phase (cycles)", "v_clock", 0.0, 1.0)
+        .doc("Offset of the clock distortion at the first row, in cycles"),
+    double("parallel_smear", "Parallel Smear", "v_clock", 0.0, 1.0)
+        .doc("Light collected during transfer: bright columns streak over the whole frame"),
+    boolean("line_scan.enabled", "Line Scan", "v_clock")
+        .doc("One sensor row swept across the scene and read after every line, in place of the \
+              parallel register: photo-finish style"),
+    double("line_scan.speed", "Scan Speed (rows/line)", "v_clock", 0.1, 4.0).log()
+        .doc("Scene rows the scan advances per line; below 1 stretches the image, above 1 \
+              squeezes it"),
+    double("line_scan.wobble", "Speed Wobble", "v_clock", 0.0, 1.0)
+        .doc("Peak fractional deviation of the scan speed: bands of stretched and squeezed rows"),
+    double("line_scan.wobble_frequency", "Wobble Cycles / Frame", "v_clock", 0.25, 64.0).log()
+        .doc("Cycles of speed wobble over the frame"),
+    double("line_scan.exposure_jitter", "Line Exposure Jitter", "v_clock", 0.0, 0.5)
+        .doc("RMS variation of each line's exposure: rows brighter or darker across the frame"),
+    double("line_scan.line_noise", "Line Noise (e-)", "v_clock", 0.0, 5000.0)
+        .doc("RMS offset shared by all of a line's pixels: horizontal streaks"),
+    boolean("tdi.enabled", "TDI", "v_clock")
+        .doc("Time-delay integration: the clocks shift charge along with a moving scene, \
+              integrating each point over many rows"),
+    int("tdi.stages", "TDI Stages", "v_clock", 1, 256)
+        .doc("Rows each packet integrates over; the smear grows with them"),
+    double("tdi.sync_error", "Sync Error", "v_clock", -0.5, 0.5)
+        .doc("Scene speed error against the row shifts: smears along the columns by \
+              stages x error rows"),
+    double("tdi.drift", "Cross Drift (px/row)", "v_clock", -1.0, 1.0)
+        .doc("Scene motion across the columns per row shift: smears diagonally"),
+    double("h_cte", "CTE", "h_clock", 0.99, 1.0).decimals(6)
+        .doc("Charge kept per serial transfer: below 0.9999 bright pixels trail along the row"),
+    double("h_glitch_rate", "Glitch Rate", "h_clock", 0.0, 0.1)
+        .doc("Chance per pixel of a serial clock fault that displaces it"),
+    double("h_ringing", "Ringing", "h_clock", 0.0, 1.0)
+        .doc("Overshoot and oscillation after sharp edges from the output line"),
+    choice("readout_direction", "Readout Dir", "h_clock", || {
+        names(ReadoutDirection::ALL, ReadoutDirection::name)
+    })
+        .doc("Serial output side; alternating reads every other row backward"),
+    int("serial_defects.count", "Defective Stages", "h_clock", 0, 32)
+        .doc("Broken stages in the serial register, each spoiling one column"),
+    double("serial_defects.trap_cte", "Trap CTE", "h_clock", 0.0, 1.0)
+        .doc("Fraction a trapping stage passes on; the rest smears into following pixels"),
+    double("serial_defects.block_level", "Block Level (FW)", "h_clock", 0.0, 1.0)
+        .doc("Capacity of a blocked stage as a fraction of full well; charge above it drains"),
+    int("serial_defects.seed", "Defect Seed", "h_clock", 1, 9999)
+        .doc("Placement seed; the same seed always picks the same columns"),
+    double("readout_abort", "Abort At", "h_clock", 0.0, 1.0)
+        .doc("Fraction of rows read before readout stops; 1 reads the complete frame"),
+    choice("abort_fill", "Abort Fill", "h_clock", || names(AbortFill::ALL, AbortFill::name))
+        .doc("What the unread rows show: the bias level, static, or the last row repeated"),
+    choice("vertical_direction", "Transfer Dir", "v_clock", || {
+        names(VerticalDirection::ALL, VerticalDirection::name)
+    })
+        .doc("Edge the serial register sits on, which sets the direction charge shifts"),
+    choice("sensor_orientation", "Orientation (deg)", "sensor", || {
+        names(SensorOrientation::ALL, SensorOrientation::name)
+    })
+        .doc("Rotation of the sensor against the image, so columns and rows swap roles"),
+    double("amp_gain", "Gain", "amplifier", 0.1, 10.0).log()
+        .doc("Output amplifier voltage gain"),
+    double("nonlinearity", "Nonlinearity", "amplifier", 0.0, 1.0)
+        .doc("S-curve compression over the amplifier swing; 0 is linear, 1 strong"),
+    double("reset_noise", "Reset Noise (e-)", "amplifier", 0.0, 500.0)
+        .doc("kTC noise of the floating diffusion reset; CDS removes most of it"),
+    double("reset_lag", "Reset Lag", "amplifier", 0.0, 0.9)
+        .doc("Fraction of the previous pixel left after reset: comet tails after bright pixels"),
+    double("amp_glow", "Amp Glow", "amplifier", 0.0, 1.0)
+        .doc("Infrared glow from the output amplifier, brightening the corner near it"),
+    double("amp_drift", "Baseline Drift (e-)", "amplifier", 0.0, 500.0)
+        .doc("Random walk of the output baseline per row time, continuing through blanking: \
+              soft wandering row bands"),
+    double("supply.load", "Supply Load (V)", "amplifier", 0.0, 10.0)
+        .doc("Volts VDD sags by while full-well rows are read: bright rows dim and clip \
+              early, with a shadow trailing them in readout order"),
+    double("supply.recovery", "Supply Recovery (rows)", "amplifier", 0.1, 500.0).log()
+        .doc("Rows for the supply rail to recover 63% of a step in readout current"),
+    double("thermal.readout_dark", "Readout Dark (e-)", "amplifier", 0.0, 1000.0).log()
+        .doc("Dark current per pixel over one readout: rows read later wait longer and \
+              collect more"),
+    double("thermal.heating", "Amp Heating (K)", "amplifier", 0.0, 30.0)
+        .doc("Kelvin the amplifier corner warms by the end of readout; dark current doubles \
+              every 6.3 K and the amp glow follows"),
+    double("thermal.diffusion", "Heat Spread", "amplifier", 0.01, 1.0)
+        .doc("How far the heat diffuses by the end of readout, as a fraction of the frame \
+              diagonal"),
+    double("interference.amplitude", "Interference (e-)", "amplifier", 0.0, 5000.0)
+        .doc("Peak pickup from an unsynchronized disturbance on the video line"),
+    double("interference.frequency", "Interference Cycles / Row", "amplifier", 0.0, 200.0)
+        .doc("Cycles per row; whole numbers give vertical bars, fractions diagonal bands"),
+    double("interference.jitter", "Interference Jitter (cycles)", "amplifier", 0.0, 0.5)
+        .doc("Per-row phase noise of the disturbance, in cycles"),
+    boolean("interference.locked", "Interference Locked to Frame", "amplifier")
+        .doc("Unlocked bands drift between renders"),
+    int("bit_depth", "Bit Depth", "adc", 4, 16)
+        .doc("ADC resolution: 12-16 bits for scientific cameras, 8-10 for video"),
+    choice("cds_mode", "CDS Mode", "adc", || names(CdsMode::ALL, CdsMode::name))
+        .doc("Correlated double sampling subtracts the reset level: off leaves reset noise in, \
+              partial some of it"),
+    double("adc_gain", "Gain (e-/ADU)", "adc", 0.1, 10.0).log()
+        .doc("Electrons per output code; higher gain coarsens the steps"),
+    double("bias", "Bias", "adc", 0.0, 1000.0)
+        .doc("Offset added before conversion so noise below zero is kept"),
+    double("dnl_errors", "DNL Errors", "adc", 0.0, 1.0)
+        .doc("Uneven code widths: some levels are skipped or repeated, banding smooth gradients"),
+    double("bit_errors", "Bit Errors", "adc", 0.0, 1.0)
+        .doc("Random bit flips in the output words"),
+    double("adc_jitter", "ADC Jitter (px)", "adc", 0.0, 1.0)
+        .doc("Sample clock phase noise: each sample lands off position, so edges go ragged"),
+    choice("adc_dither", "ADC Dither", "adc", || names(Dither::ALL, Dither::name))
+        .doc("Noise before rounding to break up banding at low bit depths"),
+    choice("adc_overflow", "Over Range", "adc", || names(AdcOverflow::ALL, AdcOverflow::name))
+        .doc("Beyond full scale: clip, fold back down, or wrap to the bottom code"),
+    choice("adc_encoding", "Output Encoding", "adc", || names(AdcEncoding::ALL, AdcEncoding::name))
+        .doc("Bit layout of the output word"),
+    boolean("adc_wrong_decode", "Wrong Decode", "adc")
+        .doc("Read the output as a mismatched encoding: mid-scale tearing or scrambled bands"),
+    int("compand.knees", "Compand Knees", "adc", 0, 4)
+        .doc("Piecewise-linear output companding: highlights share coarser steps"),
+    double("compand.ratio", "Knee Slope Ratio", "adc", 1.0, 16.0).log()
+        .doc("Slope ratio between consecutive segments"),
+    double("compand.mismatch", "Decompand Mismatch", "adc", -0.2, 0.2)
+        .doc("Receiver knees off by this fraction: contour bands around each knee"),
+    boolean("dual_gain.enabled", "Dual-Gain HDR", "adc")
+        .doc("Digitize highlights through a low-gain path and stitch them to the high-gain one"),
+    double("dual_gain.ratio", "Gain Ratio", "adc", 1.0, 32.0).log()
+        .doc("Gain ratio between the paths; low-gain codes are this many high-gain codes wide"),
+    double("dual_gain.threshold", "Switch Point", "adc", 0.0, 1.0)
+        .doc("Fraction of full scale above which the low-gain reading is used"),
+    double("dual_gain.stitch_error", "Stitch Error", "adc", -0.2, 0.2)
+        .doc("Low-gain path mismatch: a seam at the switch level"),
+    boolean("column_adc.enabled", "Column ADC", "adc")
+        .doc("One converter per column with its own offset and gain: vertical stripe FPN"),
+    double("column_adc.offset", "Column Offset (ADU)", "adc", 0.0, 50.0)
+        .doc("Standard deviation of the per-column offset, in codes"),
+    double("column_adc.gain", "Column Gain Spread", "adc", 0.0, 0.1)
+        .doc("Standard deviation of the per-column gain error, as a fraction"),
+    double("column_adc.dead_rate", "Dead Columns", "adc", 0.0, 0.05)
+        .doc("Fraction of columns whose converter has failed, stuck black or white"),
+    int("column_adc.seed", "Column Seed", "adc", 1, 9999)
+        .doc("Pattern seed; the same seed always gives the same columns"),
+    boolean("black_clamp.enabled", "Black Clamp", "adc")
+        .doc("Servo the black level to the overscan each row: slow loops tail, fast ones band"),
+    double("black_clamp.loop_gain", "Clamp Loop Gain", "adc", 0.01, 2.0)
+        .doc("1 settles in one row; lower leaves tails, above 1 overshoots into banding"),
+    double("black_clamp.leak", "Overscan Leak", "adc", 0.0, 0.2)
+        .doc("Fraction of the row's signal leaking into its overscan, dimming bright rows"),
+    double("black_clamp.noise", "Overscan Noise (ADU)", "adc", 0.0, 10.0)
+        .doc("Noise on the overscan average, in codes: row-to-row streaks"),
+    double("adc_ref_droop", "Reference Droop", "adc", 0.0, 0.5)
+        .doc("Bright samples sag the ADC reference: a dark wake trails them along the row"),
+    double("adc_ref_recovery", "Reference Recovery (px)", "adc", 1.0, 1000.0).log()
+        .doc("Reference recovery time constant in pixels; longer wakes trail further"),
+    double("adc_clock_error", "Sample Clock Error", "adc", -0.01, 0.01)
+        .doc("ADC sample clock off the pixel clock: rows resample, columns lean or shimmer"),
+    boolean("adc_line_lock", "Line Lock", "adc")
+        .doc("Resync each row: columns shimmer in place instead of leaning"),
+    boolean("auto_exposure", "Auto Exposure", "adc")
+        .doc("Rescale so a percentile of the digitized frame hits a target level"),
+    double("auto_exposure_percentile", "AE Percentile", "adc", 50.0, 100.0)
+        .doc("Percentile of the frame placed at the target level"),
+    double("auto_exposure_target", "AE Target", "adc", 0.1, 1.0)
+        .doc("Level the percentile is placed at, as a fraction of full scale"),
+    double("pixel_shift_amount", "Pixel Shift", "glitch", 0.0, 2.0)
+        .doc("Rows or runs of pixels displaced sideways"),
+    double("block_shift_amount", "Block Shift", "glitch", 0.0, 2.0)
+        .doc("Rectangular blocks displaced, as from a corrupted frame buffer"),
+    double("scan_line_frequency", "Scan Line Corruption", "glitch", 0.0, 2.0)
+        .doc("Horizontal bands of corrupted data"),
+    double("defect_repair_amount", "Repair Misfires", "glitch", 0.0, 2.0)
+        .doc("Rows and columns wrongly patched from their neighbours by defect correction"),
+    choice("defect_repair_mode", "Repair Mode", "glitch", || {
+        names(RepairMode::ALL, RepairMode::name)
+    })
+        .doc("Interpolate from both sides, or copy from one side at a wrong-color offset"),
+    boolean("per_plane_glitch", "Per CFA Plane", "glitch")
+        .doc("Run the shift and scan line glitches separately on each color plane"),
+    double("sync.loss_rate", "Sync Loss (per frame)", "glitch", 0.0, 10.0)
+        .doc("Segments where line sync is lost: rows wrap around and tear"),
+    double("sync.loss_length", "Sync Loss Length (rows)", "glitch", 1.0, 200.0).log()
+        .doc("Mean rows per sync loss segment"),
+    double("sync.dropout_rate", "Line Dropouts (per frame)", "glitch", 0.0, 20.0)
+        .doc("Lines lost entirely, replaced with noise"),
+    double("sync.dropout_length", "Dropout Length (rows)", "glitch", 1.0, 50.0).log()
+        .doc("Mean rows per dropout burst"),
+    choice("sync.burst", "Burst Lengths", "glitch", || names(BurstLength::ALL, BurstLength::name))
+        .doc("Fixed, geometric (mostly short), or heavy-tailed (rare long bursts) lengths"),
+    int("bit_xor_mask", "XOR Mask", "glitch", 0, 65535)
+        .doc("Bit planes flipped in every code, as a bitmask"),
+    int("bit_rotation", "Bit Rotation", "glitch", -8, 8)
+        .doc("Rotate each code's bits by this many places"),
+    int("bit_plane_swaps", "Bit Plane Swaps", "glitch", 0, 8)
+        .doc("Random pairs of bit planes exchanged"),
+    choice("raw_packing", "Raw Packing", "glitch", || names(RawPacking::ALL, RawPacking::name))
+        .doc("Pack codes into a 10, 12 or 14-bit raw stream and unpack it, so bit offset and \
+              stride errors apply"),
+    int("raw_bit_offset", "Bit Offset", "glitch", 0, 32)
+        .doc("Start unpacking this many bits late: diagonal striping and shredded rows"),
+    int("raw_stride_error", "Stride Error (bytes)", "glitch", -16, 16)
+        .doc("Row stride off by this many bytes: rows shear and wrap"),
+    boolean("compression.enabled", "Codec Artifacts", "glitch")
+        .doc("Pass the finished image through an MPEG-style 8x8 DCT encoder"),
+    double("compression.quality", "Codec Quality", "glitch", 1.0, 100.0)
+        .doc("Encoder quality scaling the quantization tables; low values block up"),
+    double("compression.mosquito", "Mosquito Noise", "glitch", 0.0, 0.5)
+        .doc("Noise on the high frequencies of blocks holding edges, in quantizer steps: \
+              a shimmering swarm around edges"),
+    double("compression.dc_flicker", "DC Flicker", "glitch", 0.0, 20.0)
+        .doc("RMS offset of each block's brightness, in 8-bit levels, redrawn every frame"),
+    double("compression.block_smear", "Block Smear", "glitch", 0.0, 1.0)
+        .doc("Chance per 16x16 macroblock of being copied from a wrong motion vector"),
+    int("temporal.persistence", "Persistence (frames)", "temporal", 1, 120)
+        .doc("Frames each glitch pattern is held for; 1 draws a new one every frame"),
+    double("temporal.drift", "Drift (rows/frame)", "temporal", -20.0, 20.0)
+        .doc("Rows per frame a held pattern moves down the frame (negative: up)"),
+    double("temporal.decay", "Decay", "temporal", 0.0, 1.0)
+        .doc("Fraction of a held pattern's strength lost each frame"),
+    boolean("determinism.strict", "Strict Determinism", "determinism")
+        .doc("Seed every random draw and avoid platform-dependent float math, so the same \
+              parameters render bit-identical output natively and on the web (SPICE off)"),
+    int("determinism.seed", "Seed", "determinism", 0, 9999)
+        .doc("Seed every random stream derives from in strict mode"),
+    boolean("validate_buffers", "Validate Buffers", "determinism")
+        .doc("Scan every stage's output for NaN, infinite and negative electron values and \
+              report the first stage producing them, to explain black frames"),
+    boolean("bypass.optics", "Bypass Optics", "bypass")
+        .doc("Skip the optics stage, keeping its settings"),
+    boolean("bypass.exposure_noise", "Bypass Exposure & Noise", "bypass")
+        .doc("Skip exposure modulation and noise, keeping their settings"),
+    boolean("bypass.blooming", "Bypass Blooming", "bypass")
+        .doc("Skip blooming, keeping its settings"),
+    boolean("bypass.v_clock", "Bypass V-Clock", "bypass")
+        .doc("Skip vertical transfer faults, keeping their settings"),
+    boolean("bypass.h_clock", "Bypass H-Clock", "bypass")
+        .doc("Skip serial readout faults, keeping their settings"),
+    boolean("bypass.amplifier", "Bypass Amplifier", "bypass")
+        .doc("Skip the output amplifier, keeping its settings"),
+    boolean("bypass.adc", "Bypass ADC (Ideal)", "bypass")
+        .doc("Convert with an ideal quantizer, keeping the ADC settings"),
+    boolean("bypass.glitch", "Bypass Glitch", "bypass")
+        .doc("Skip the digital glitches, keeping their settings"),
+    boolean("bypass.channel", "Bypass Channel", "bypass")
+        .doc("Skip channel gains, swaps and shifts, keeping their settings"),
+    boolean("bypass.color", "Bypass Color", "bypass")
+        .doc("Skip color processing, keeping its settings"),
+    int("stack.frames", "Subframes", "stack", 1, 32)
+        .doc("Render the whole pipeline this many times and combine: each subframe gets its own \
+              noise and glitches"),
+    choice("stack.combiner", "Combiner", "stack", || names(Combiner::ALL, Combiner::name))
+        .doc("Mean averages transient artifacts down, median rejects those in under half \
+              the subframes, max keeps every one"),
+    double("stack.translate_jitter", "Translation Jitter (px)", "stack", 0.0, 10.0)
+        .doc("Standard deviation of each subframe's offset, in photosites"),
+    double("stack.rotate_jitter", "Rotation Jitter (deg)", "stack", 0.0, 2.0)
+        .doc("Standard deviation of each subframe's rotation about the center, in degrees"),
+    boolean("stack.drizzle", "Drizzle", "stack")
+        .doc("Undo each subframe's offset and rain its pixels onto a finer grid as shrunken \
+              drops, instead of combining pixel by pixel"),
+    double("stack.pixfrac", "Pixfrac", "stack", 0.01, 1.0)
+        .doc("Drop side as a fraction of a pixel; small drops with few subframes leave a \
+              lattice of holes"),
+    double("stack.drizzle_scale", "Drizzle Scale", "stack", 0.25, 4.0)
+        .doc("Output pixels per input pixel along each side; the output grows by this much \
+              and beats against drops that cannot fill it"),
+    boolean("three_chip.enabled", "Three Chips", "three_chip")
+        .doc("Render each color on its own sensor with its own seeds, as in 3-CCD cameras: \
+              noise and glitches land independently per channel"),
+    double("three_chip.red_dx", "Red Shift X (px)", "three_chip", -10.0, 10.0)
+        .doc("Horizontal offset of the red chip from green, in photosites: color fringes on \
+              vertical edges"),
+    double("three_chip.red_dy", "Red Shift Y (px)", "three_chip", -10.0, 10.0)
+        .doc("Vertical offset of the red chip from green, in photosites"),
+    double("three_chip.red_rotation", "Red Rotation (deg)", "three_chip", -2.0, 2.0)
+        .doc("Rotation of the red chip about the frame center, growing the fringes toward \
+              the corners"),
+    int("three_chip.red_skew", "Red Readout Skew (rows)", "three_chip", -200, 200)
+        .doc("Rows the red chip's readout runs behind green's: its glitch bands and row \
+              effects land on other rows while the image stays registered"),
+    double("three_chip.blue_dx", "Blue Shift X (px)", "three_chip", -10.0, 10.0)
+        .doc("Horizontal offset of the blue chip from green, in photosites: color fringes on \
+              vertical edges"),
+    double("three_chip.blue_dy", "Blue Shift Y (px)", "three_chip", -10.0, 10.0)
+        .doc("Vertical offset of the blue chip from green, in photosites"),
+    double("three_chip.blue_rotation", "Blue Rotation (deg)", "three_chip", -2.0, 2.0)
+        .doc("Rotation of the blue chip about the frame center, growing the fringes toward \
+              the corners"),
+    int("three_chip.blue_skew", "Blue Readout Skew (rows)", "three_chip", -200, 200)
+        .doc("Rows the blue chip's readout runs behind green's: its glitch bands and row \
+              effects land on other rows while the image stays registered"),
+    choice("chroma_subsampling", "Chroma Subsampling", "channel", || {
+        names(ChromaSubsampling::ALL, ChromaSubsampling::name)
+    })
+        .doc("Average color over blocks of pixels after demosaic, as DV and VHS digitizers \
+              store it: color bleeds past edges in steps"),
+    choice("channel_swap", "Channel Swap", "channel", || {
+        names(ChannelSwap::ALL, ChannelSwap::name)
+    })
+        .doc("Exchange color channels, as from miswired video"),
+    double("channel_r_gain", "R Gain", "channel", 0.0, 3.0)
+        .doc("Red channel gain"),
+    double("channel_g_gain", "G Gain", "channel", 0.0, 3.0)
+        .doc("Green channel gain"),
+    double("channel_b_gain", "B Gain", "channel", 0.0, 3.0)
+        .doc("Blue channel gain"),
+    double("channel_r_offset", "R Offset", "channel", -0.5, 0.5)
+        .doc("Red channel offset"),
+    double("channel_g_offset", "G Offset", "channel", -0.5, 0.5)
+        .doc("Green channel offset"),
+    double("channel_b_offset", "B Offset", "channel", -0.5, 0.5)
+        .doc("Blue channel offset"),
+    int("chromatic_r_x", "R Shift X", "channel", -20, 20)
+        .doc("Red channel shift right in pixels: color fringing"),
+    int("chromatic_r_y", "R Shift Y", "channel", -20, 20)
+        .doc("Red channel shift down in pixels"),
+    int("chromatic_b_x", "B Shift X", "channel", -20, 20)
+        .doc("Blue channel shift right in pixels"),
+    int("chromatic_b_y", "B Shift Y", "channel", -20, 20)
+        .doc("Blue channel shift down in pixels"),
+    choice("bayer_pattern", "Bayer Pattern", "color", || {
+        names(BayerPattern::ALL, BayerPattern::name)
+    })
+        .doc("Color filter layout; a mismatch with the source sensor scrambles colors"),
+    choice("demosaic_algo", "Demosaic", "color", || names(DemosaicAlgo::ALL, DemosaicAlgo::name))
+        .doc("Bilinear is soft with color fringes; Malvar-He-Cutler is sharper"),
+    double("white_balance_r", "White Balance R", "color", 0.0, 3.0)
+        .doc("Red multiplier after demosaicing"),
+    double("white_balance_g", "White Balance G", "color", 0.0, 3.0)
+        .doc("Green multiplier after demosaicing"),
+    double("white_balance_b", "White Balance B", "color", 0.0, 3.0)
+        .doc("Blue multiplier after demosaicing"),
+    double("saturation", "Saturation", "color", 0.0, 3.0)
+        .doc("Color intensity; 0 is grayscale"),
+    double("vibrance", "Vibrance", "color", -1.0, 1.0)
+        .doc("Saturation weighted toward muted colors"),
+    double("hue_rotation", "Hue Rotation (deg)", "color", -180.0, 180.0)
+        .doc("Rotate every hue by this many degrees"),
+    double("gamma", "Gamma", "color", 0.1, 4.0)
+        .doc("Output gamma; 2.2 is close to sRGB"),
+    double("brightness", "Brightness", "color", -1.0, 1.0)
+        .doc("Offset added to the output"),
+    double("contrast", "Contrast", "color", 0.0, 3.0)
+        .doc("Spread around mid-gray; 1 leaves it unchanged"),
+    choice("output_dither", "Output Dither", "color", || names(Dither::ALL, Dither::name))
+        .doc("Dithering for the final 8-bit conversion"),
+    double("sharpen.amount", "Sharpen", "color", 0.0, 5.0)
+        .doc("Unsharp mask strength on luma: edges get light and dark overshoot halos"),
+    double("sharpen.radius", "Sharpen Radius (px)", "color", 0.5, 10.0)
+        .doc("Blur radius of the mask; wider radii spread the halos"),
+    double("sharpen.threshold", "Sharpen Threshold", "color", 0.0, 0.2)
+        .doc("Detail below this is left alone, sparing flat areas and noise"),
+    boolean("sharpen.halo", "Over-Sharpen Halos", "color")
+        .doc("Also boost a band three radii wide, as over-sharpening cameras do: a broad \
+              bright and dark ring around every edge"),
+    choice("palette.mode", "Palette", "color", || names(PaletteMode::ALL, PaletteMode::name))
+        .doc("Quantize the output to a few colors: posterized channels, the web-safe cube, an \
+              adaptive median-cut palette or a loaded one"),
+    int("palette.levels", "Posterize Levels", "color", 2, 32)
+        .doc("Values per channel when posterizing"),
+    int("palette.colors", "Adaptive Colors", "color", 2, 256)
+        .doc("Colors the adaptive palette cuts from the image"),
+    choice("palette.dither", "Palette Dither", "color", || names(Dither::ALL, Dither::name))
+        .doc("Dithering toward the palette colors; error diffusion gives the classic \
+              retro-display grain"),
+    boolean("source_match.white_balance", "Auto WB From Source", "composite")
+        .doc("Balance the channels of the finished render to the source's average color"),
+    boolean("source_match.tonality", "Match Source Tonality", "composite")
+        .doc("Map the render's tones onto the source's histogram, keeping its overall \
+              brightness and contrast"),
+    double("source_match.strength", "Match Strength", "composite", 0.0, 1.0)
+        .doc("How much of the white balance and tone match shows"),
+    choice("composite.mode", "Blend", "composite", || names(BlendMode::ALL, BlendMode::name))
+        .doc("How the finished render combines with the untouched source: normal replaces it, \
+              difference shows only what changed"),
+    double("composite.opacity", "Opacity", "composite", 0.0, 1.0)
+        .doc("Weight of the blend over the source; lower leaves the image partly intact"),
+    double("artifact_layer.strength", "Noise Print Strength", "composite", -2.0, 4.0)
+        .doc("Multiplier on the loaded noise print added over the output; negative inverts it"),
+    double("frame_overlay.overscan", "Overscan", "overlay", 0.0, 0.25)
+        .doc("Fraction of each edge a CRT's bezel hides; the rest is scaled up to fill the frame"),
+    boolean("frame_overlay.safe_area", "Safe-Area Markers", "overlay")
+        .doc("Dashed outlines of the action-safe (90%) and title-safe (80%) areas"),
+    boolean("frame_overlay.timestamp", "Timestamp", "overlay")
+        .doc("Burn in an OSD clock that advances with the frames of a sequence"),
+    double("frame_overlay.clock_start", "Clock Start (s)", "overlay", 0.0, 86399.0)
+        .doc("Seconds past midnight the clock reads on the first frame"),
+    boolean("frame_overlay.rec", "REC Indicator", "overlay")
+        .doc("Burn in a REC indicator blinking once a second"),
+    choice("spice.mode", "Mode", "spice", || names(SpiceMode::ALL, |m| m.name()))
+        .doc("Full readout simulates pixel to ADC, amplifier only the output stages, transfer \
+              curve only the amplifier's simulated response"),
+    double("spice.vdd", "VDD (V)", "spice", 5.0, 20.0)
+        .doc("Supply voltage; lower swings transfer charge less completely"),
+    double("spice.clock_freq_mhz", "Clock (MHz)", "spice", 0.1, 50.0)
+        .doc("Pixel clock; faster clocks leave less settling time and ring more"),
+    double("spice.temperature_k", "Temp (K)", "spice", 200.0, 400.0)
+        .doc("Circuit temperature; 300 K is room temperature"),
+    int("spice.shift_register_stages", "SR Stages", "spice", 2, 16)
+        .doc("Serial register stages simulated for the transfer response"),
+    int("spice.transfer_function_resolution", "TF Resolution", "spice", 8, 128)
+        .doc("Points sampled on the simulated transfer curve"),
+    double("spice.supply_droop", "Supply Droop", "spice", 0.0, 0.8)
+        .doc("Supply sag under readout current: smaller clock swings, more ringing"),
+    double("spice.phase_overlap_ns", "Phase Overlap (ns)", "spice", 0.0, 100.0)
+        .doc("Clock phase overlap from driver skew: charge sharing between wells"),
+    double("spice.missing_pulse_rate", "Missing Pulses", "spice", 0.0, 0.5)
+        .doc("Fraction of clock pulses dropped, stalling charge in place"),
+    double("spice.charge_injection", "Charge Injection", "spice", 0.0, 2.0)
+        .doc("Channel charge dumped into the well as a clock gate turns off: a \
+              signal-dependent offset"),
+    double("spice.substrate_noise", "Substrate Noise", "spice", 0.0, 1.0)
+        .doc("Switching noise coupled through the substrate, rising with temperature"),
+];
+
+fn names<T: Copy>(all: &[T], name: fn(T) -> &'static str) -> Vec<&'static str> {
+    all.iter().map(|&v| name(v)).collect()
+}
+
+impl PipelineParams {
+    /// Metadata for every settable parameter, in `values()` order.
+    pub fn schema() -> &'static [ParamSpec] {
+        PARAMS
+    }
+}
+
+/// The schema entry for `name`, if it has one.
+pub fn find(name: &str) -> Option<&'static ParamSpec> {
+    PARAMS.iter().find(|spec| spec.name == name)
+}
+
+/// Entries in `category`, in table order.
+pub fn category(category: &str) -> impl Iterator<Item = &'static ParamSpec> {
+    PARAMS.iter().filter(move |spec| spec.category == category)
+}
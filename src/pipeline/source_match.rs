@@ -0,0 +1,118 @@
+//! Matching the finished render back to the source's color and tonality, so
+//! extreme settings can wreck the image while it keeps the original's
+//! overall mood.
+//!
+//! Both estimates come from the whole frame and run on the 8-bit output
+//! before compositing: white balance scales each channel so their means sit
+//! in the source's ratios, and the tone match maps the render's luma
+//! histogram onto the source's with one curve applied to every channel.
+
+use image::DynamicImage;
+
+use super::PipelineParams;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourceMatch {
+    /// Balance the channels to the source's average color.
+    pub white_balance: bool,
+    /// Map the render's tones onto the source's histogram.
+    pub tonality: bool,
+    /// How much of the match shows; 1 applies it fully.
+    pub strength: f64,
+}
+
+impl Default for SourceMatch {
+    fn default() -> Self {
+        Self {
+            white_balance: false,
+            tonality: false,
+            strength: 1.0,
+        }
+    }
+}
+
+impl SourceMatch {
+    /// Whether matching leaves the render as it is.
+    pub fn is_off(&self) -> bool {
+        !(self.white_balance || self.tonality) || self.strength <= 0.0
+    }
+}
+
+/// Match the RGB8 output `bytes` to `source` as `params.source_match`
+/// describes.
+pub fn apply_source_match(bytes: &mut [u8], source: &DynamicImage, params: &PipelineParams) {
+    let matching = &params.source_match;
+    if matching.is_off() || bytes.is_empty() {
+        return;
+    }
+    let reference = source.to_rgb8();
+    let original = bytes.to_vec();
+
+    if matching.white_balance {
+        let target = channel_means(reference.as_raw());
+        let current = channel_means(bytes);
+        let luma = |m: [f64; 3]| 0.299 * m[0] + 0.587 * m[1] + 0.114 * m[2];
+        // Take on the source's channel ratios at the render's own brightness
+        let level = luma(current) / luma(target).max(1e-6);
+        let gains: [f64; 3] = std::array::from_fn(|c| target[c] * level / current[c].max(1e-6));
+        for pixel in bytes.chunks_exact_mut(3) {
+            for (value, gain) in pixel.iter_mut().zip(gains) {
+                *value = (*value as f64 * gain).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    if matching.tonality {
+        let curve = match_histogram(&luma_histogram(bytes), &luma_histogram(reference.as_raw()));
+        for value in bytes.iter_mut() {
+            *value = curve[*value as usize];
+        }
+    }
+
+    let strength = matching.strength.min(1.0);
+    for (out, &before) in bytes.iter_mut().zip(&original) {
+        let mixed = before as f64 + (*out as f64 - before as f64) * strength;
+        *out = mixed.round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+fn channel_means(bytes: &[u8]) -> [f64; 3] {
+    let mut sums = [0.0; 3];
+    for pixel in bytes.chunks_exact(3) {
+        for (sum, &value) in sums.iter_mut().zip(pixel) {
+            *sum += value as f64;
+        }
+    }
+    let count = (bytes.len() / 3).max(1) as f64;
+    sums.map(|sum| sum / count)
+}
+
+fn luma_histogram(bytes: &[u8]) -> [f64; 256] {
+    let mut histogram = [0.0; 256];
+    for p in bytes.chunks_exact(3) {
+        let luma = 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64;
+        histogram[(luma.round() as usize).min(255)] += 1.0;
+    }
+    histogram
+}
+
+/// The curve taking each level of `from` to the level of `to` at the same
+/// cumulative fraction.
+fn match_histogram(from: &[f64; 256], to: &[f64; 256]) -> [u8; 256] {
+    let cumulative = |histogram: &[f64; 256]| {
+        let total = histogram.iter().sum::<f64>().max(1.0);
+        let mut sum = 0.0;
+        histogram.map(|count| {
+            sum += count;
+            sum / total
+        })
+    };
+    let (from, to) = (cumulative(from), cumulative(to));
+    let mut level = 0;
+    std::array::from_fn(|i| {
+        while level < 255 && to[level] < from[i] {
+            level += 1;
+        }
+        level as u8
+    })
+}
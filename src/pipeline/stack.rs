@@ -0,0 +1,278 @@
+//! Stacking: several exposures of the same source, each run through the
+//! full pipeline with its own noise and glitches, combined into one frame.
+//!
+//! Each subframe's source is nudged by a random translation and rotation, as
+//! residual misregistration between frames, so stacked edges soften and
+//! double. The combiner decides what survives: the mean averages transient
+//! artifacts down, the median rejects anything present in under half the
+//! subframes (glitched rows, bit flips, dropouts), and the maximum keeps
+//! every one of them.
+//!
+//! Drizzle instead undoes each subframe's offset and rains its pixels onto a
+//! finer grid, shrunk to drops. Set up well it recovers resolution; with
+//! drops too small for the grid and too few subframes to fill it, the
+//! output is a lattice of holes and beats: the gridded moiré of a
+//! misconfigured astro stack.
+
+use std::time::Instant;
+
+use image::{DynamicImage, Rgb, Rgb32FImage};
+
+use super::determinism::Stream;
+use super::{GlitchFrame, PipelineParams, StageTimings};
+use crate::ccd::noise::gaussian;
+
+/// How subframes are merged per pixel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combiner {
+    Mean,
+    Median,
+    Max,
+}
+
+impl Combiner {
+    pub const ALL: &[Combiner] = &[Combiner::Mean, Combiner::Median, Combiner::Max];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Combiner::Mean => "Mean",
+            Combiner::Median => "Median",
+            Combiner::Max => "Max",
+        }
+    }
+}
+
+/// Stacking controls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StackParams {
+    /// Subframes per render; 1 renders a single exposure.
+    pub frames: usize,
+    pub combiner: Combiner,
+    /// Standard deviation of each subframe's offset, in sensor pixels.
+    pub translate_jitter: f64,
+    /// Standard deviation of each subframe's rotation about the frame
+    /// center, in degrees.
+    pub rotate_jitter: f64,
+    /// Drizzle the subframes instead of combining them pixel by pixel.
+    pub drizzle: bool,
+    /// Side of each drizzle drop as a fraction of a pixel's.
+    pub pixfrac: f64,
+    /// Output pixels per input pixel along each side; the drizzled frame
+    /// is this much larger.
+    pub drizzle_scale: f64,
+}
+
+impl Default for StackParams {
+    fn default() -> Self {
+        Self {
+            frames: 1,
+            combiner: Combiner::Median,
+            translate_jitter: 0.0,
+            rotate_jitter: 0.0,
+            drizzle: false,
+            pixfrac: 1.0,
+            drizzle_scale: 1.0,
+        }
+    }
+}
+
+/// Render `params.stack.frames` misregistered subframes and combine them.
+/// Stage timings are summed over the subframes, plus the combine itself.
+pub fn process_stacked(
+    source: &DynamicImage,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    temporal: Option<&GlitchFrame>,
+) -> (usize, usize, Vec<u8>, StageTimings) {
+    let stack = &params.stack;
+    let mut rng = params.determinism.rng(Stream::Stack);
+    let shift = stack.translate_jitter.max(0.0);
+    let turn = stack.rotate_jitter.max(0.0);
+    // Jitter is given on the sensor; the source may be a different size
+    let to_source = source.width() as f64 / params.sensor_width.max(1) as f64;
+
+    let mut subframes = Vec::with_capacity(stack.frames);
+    let mut offsets = Vec::with_capacity(stack.frames);
+    let mut timings = StageTimings::default();
+    let (mut width, mut height) = (0, 0);
+    // Each subframe advances the noise and strict seeds, or seeded sources
+    // would stack identical grain
+    let mut subframe = params.clone();
+    for i in 0..stack.frames.max(1) {
+        let dx = shift * gaussian(&mut rng) * to_source;
+        let dy = shift * gaussian(&mut rng) * to_source;
+        let degrees = turn * gaussian(&mut rng);
+        let jittered = misregister(source, dx, dy, degrees);
+        subframe.noise.seed = params.noise.seed.wrapping_add(i as u64);
+        subframe.determinism.seed = params.determinism.seed.wrapping_add(i as u64);
+        let (w, h, bytes, t) =
+            super::process_frame(&jittered, &subframe, spice_cache, None, temporal);
+        (width, height) = (w, h);
+        subframes.push(bytes);
+        // The same offset in output pixels, for drizzle to undo
+        offsets.push(Offset {
+            dx: dx * w as f64 / source.width() as f64,
+            dy: dy * h as f64 / source.height() as f64,
+            degrees,
+        });
+        if timings.stages.is_empty() {
+            timings = t;
+        } else {
+            for ((_, total), (_, ms)) in timings.stages.iter_mut().zip(t.stages) {
+                *total += ms;
+            }
+            timings.issues.extend(t.issues);
+        }
+    }
+
+    let start = Instant::now();
+    let combined = if stack.drizzle {
+        let drizzled = drizzle(&subframes, &offsets, width, height, stack);
+        (width, height) = (drizzled.0, drizzled.1);
+        drizzled.2
+    } else {
+        combine(&subframes, stack.combiner)
+    };
+    timings.stages.push(("Stack", start.elapsed().as_secs_f64() * 1000.0));
+    (width, height, combined, timings)
+}
+
+/// A subframe's misregistration in output pixels, as `misregister` applied
+/// it.
+#[derive(Debug, Clone, Copy)]
+struct Offset {
+    dx: f64,
+    dy: f64,
+    degrees: f64,
+}
+
+/// Drizzle `width` x `height` RGB8 subframes onto a grid
+/// `stack.drizzle_scale` times finer: each pixel shrinks to a square drop
+/// `stack.pixfrac` of its side, goes back to where its subframe's offset
+/// took it from, and spreads over the output pixels it covers in proportion
+/// to the area. Output pixels no drop reaches stay black.
+fn drizzle(
+    subframes: &[Vec<u8>],
+    offsets: &[Offset],
+    width: usize,
+    height: usize,
+    stack: &StackParams,
+) -> (usize, usize, Vec<u8>) {
+    let scale = drizzle_factor(stack);
+    let (out_w, out_h) = drizzled_size(width, height, stack);
+    let half = stack.pixfrac.clamp(0.01, 1.0) * scale / 2.0;
+    let mut sum = vec![0.0; out_w * out_h * 3];
+    let mut weight = vec![0.0; out_w * out_h];
+    let (cx, cy) = ((width as f64 - 1.0) / 2.0, (height as f64 - 1.0) / 2.0);
+
+    // The output pixels along one side a drop spanning [lo, hi) covers, and
+    // by how much
+    let cover = |lo: f64, hi: f64, len: usize| {
+        let first = lo.floor().max(0.0) as usize;
+        let end = (hi.ceil().max(0.0) as usize).min(len);
+        (first..end).map(move |i| (i, (hi.min(i as f64 + 1.0) - lo.max(i as f64)).max(0.0)))
+    };
+    for (bytes, offset) in subframes.iter().zip(offsets) {
+        let (sin, cos) = libm::sincos(offset.degrees.to_radians());
+        for y in 0..height {
+            for x in 0..width {
+                let (ox, oy) = (x as f64 - cx - offset.dx, y as f64 - cy - offset.dy);
+                let sx = (cx + ox * cos + oy * sin + 0.5) * scale;
+                let sy = (cy - ox * sin + oy * cos + 0.5) * scale;
+                let pixel = &bytes[(y * width + x) * 3..][..3];
+                for (row, wy) in cover(sy - half, sy + half, out_h) {
+                    for (col, wx) in cover(sx - half, sx + half, out_w) {
+                        let area = wx * wy;
+                        let i = row * out_w + col;
+                        weight[i] += area;
+                        for c in 0..3 {
+                            sum[i * 3 + c] += pixel[c] as f64 * area;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let bytes = sum
+        .iter()
+        .enumerate()
+        .map(|(i, &total)| {
+            let w = weight[i / 3];
+            if w > 0.0 { (total / w).round().min(255.0) as u8 } else { 0 }
+        })
+        .collect();
+    (out_w, out_h, bytes)
+}
+
+fn drizzle_factor(stack: &StackParams) -> f64 {
+    stack.drizzle_scale.clamp(0.1, 8.0)
+}
+
+/// Size of the grid `width` x `height` subframes drizzle onto.
+pub(super) fn drizzled_size(width: usize, height: usize, stack: &StackParams) -> (usize, usize) {
+    let scale = drizzle_factor(stack);
+    let side = |len: usize| ((len as f64 * scale).round() as usize).max(1);
+    (side(width), side(height))
+}
+
+/// Translate the source by (`dx`, `dy`) pixels and rotate it by `degrees`
+/// about its center, reading black outside the frame.
+pub(super) fn misregister(source: &DynamicImage, dx: f64, dy: f64, degrees: f64) -> DynamicImage {
+    let src = source.to_rgb32f();
+    let (w, h) = src.dimensions();
+    if w < 2 || h < 2 {
+        return DynamicImage::ImageRgb32F(src);
+    }
+    let (cx, cy) = ((w - 1) as f64 / 2.0, (h - 1) as f64 / 2.0);
+    let (sin, cos) = libm::sincos(degrees.to_radians());
+    DynamicImage::ImageRgb32F(Rgb32FImage::from_fn(w, h, |x, y| {
+        // Inverse map: where this output pixel came from
+        let (ox, oy) = (x as f64 - cx - dx, y as f64 - cy - dy);
+        let sx = cx + ox * cos + oy * sin;
+        let sy = cy - ox * sin + oy * cos;
+        if sx < 0.0 || sy < 0.0 || sx > (w - 1) as f64 || sy > (h - 1) as f64 {
+            return Rgb([0.0; 3]);
+        }
+        let x0 = (sx.floor() as u32).min(w - 2);
+        let y0 = (sy.floor() as u32).min(h - 2);
+        let (fx, fy) = ((sx - x0 as f64) as f32, (sy - y0 as f64) as f32);
+        let p = |x, y| src.get_pixel(x, y).0;
+        let (p00, p10, p01, p11) = (p(x0, y0), p(x0 + 1, y0), p(x0, y0 + 1), p(x0 + 1, y0 + 1));
+        Rgb(std::array::from_fn(|c| {
+            let top = p00[c] + (p10[c] - p00[c]) * fx;
+            let bottom = p01[c] + (p11[c] - p01[c]) * fx;
+            top + (bottom - top) * fy
+        }))
+    }))
+}
+
+/// Merge equally sized RGB8 subframes byte by byte.
+fn combine(subframes: &[Vec<u8>], combiner: Combiner) -> Vec<u8> {
+    let Some(first) = subframes.first() else {
+        return Vec::new();
+    };
+    let mut values = Vec::with_capacity(subframes.len());
+    (0..first.len())
+        .map(|i| {
+            values.clear();
+            values.extend(subframes.iter().map(|f| f[i]));
+            match combiner {
+                Combiner::Mean => {
+                    let sum: u32 = values.iter().map(|&v| v as u32).sum();
+                    ((sum as f64 / values.len() as f64).round()) as u8
+                }
+                Combiner::Median => {
+                    values.sort_unstable();
+                    let mid = values.len() / 2;
+                    if values.len().is_multiple_of(2) {
+                        (values[mid - 1] as u16 + values[mid] as u16).div_ceil(2) as u8
+                    } else {
+                        values[mid]
+                    }
+                }
+                Combiner::Max => values.iter().copied().max().unwrap_or(0),
+            }
+        })
+        .collect()
+}
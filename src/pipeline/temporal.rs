@@ -0,0 +1,131 @@
+//! Glitch coherence across the frames of a sequence.
+//!
+//! Frames processed independently draw a fresh glitch pattern each time, so
+//! a video of them flickers. `process_temporal` instead holds each pattern
+//! for `persistence` frames: the same rows glitch, the pattern rolls down the
+//! frame by `drift` rows per frame, and it fades by `decay` per frame until
+//! the next pattern replaces it. Patterns derive from a base seed and the
+//! frame index alone, so a host can render any frame again out of order.
+//!
+//! The held stages are the V-clock row faults and the pixel shift, block
+//! shift and scan line glitches; noise stays independent per frame.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Temporal controls for sequence processing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemporalParams {
+    /// Frames each glitch pattern is held for; 1 draws a new one every frame.
+    pub persistence: usize,
+    /// Rows per frame a held pattern moves down the frame (negative: up).
+    pub drift: f64,
+    /// Fraction of a held pattern's strength lost each frame.
+    pub decay: f64,
+}
+
+impl Default for TemporalParams {
+    fn default() -> Self {
+        Self {
+            persistence: 1,
+            drift: 0.0,
+            decay: 0.0,
+        }
+    }
+}
+
+/// Glitch state carried between `process_temporal` calls.
+///
+/// The seeds it hands out replace `v_glitch_schedule.seed`; seed the state
+/// instead for a reproducible sequence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemporalState {
+    /// Base seed every pattern derives from.
+    pub seed: u64,
+    /// Index of the next frame.
+    pub frame: u64,
+}
+
+impl Default for TemporalState {
+    fn default() -> Self {
+        Self::seeded(rand::rng().random())
+    }
+}
+
+impl TemporalState {
+    /// Start at frame 0 with a fixed base seed.
+    pub fn seeded(seed: u64) -> Self {
+        Self { seed, frame: 0 }
+    }
+
+    /// Pattern for the current frame, advancing to the next.
+    pub fn next_frame(&mut self, params: &TemporalParams) -> GlitchFrame {
+        let frame = self.frame_at(self.frame, params);
+        self.frame += 1;
+        frame
+    }
+
+    /// Pattern for any frame index, without advancing.
+    pub fn frame_at(&self, frame: u64, params: &TemporalParams) -> GlitchFrame {
+        let persistence = params.persistence.max(1) as u64;
+        let age = frame % persistence;
+        GlitchFrame {
+            seed: mix(self.seed, frame / persistence),
+            drift_rows: params.drift * age as f64,
+            strength: (1.0 - params.decay.clamp(0.0, 1.0)).powi(age as i32),
+        }
+    }
+}
+
+/// One frame's view of the held glitch pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlitchFrame {
+    /// Seed of the held pattern.
+    pub seed: u64,
+    /// Rows the pattern has moved since it was drawn.
+    pub drift_rows: f64,
+    /// Remaining strength of the pattern, 0..1.
+    pub strength: f64,
+}
+
+impl GlitchFrame {
+    /// Run glitch stage `f` with this frame's pattern. `f` draws from an RNG
+    /// seeded for `stream` (one per stage or CFA plane), on rows rolled by
+    /// the drift, and its effect is blended back toward the input by the
+    /// remaining strength.
+    pub fn apply(
+        &self,
+        stream: u64,
+        grid: &mut [f64],
+        width: usize,
+        height: usize,
+        f: impl FnOnce(&mut [f64], &mut StdRng),
+    ) {
+        if width == 0 || height == 0 || self.strength <= 0.0 {
+            return;
+        }
+        let mut rng = StdRng::seed_from_u64(mix(self.seed, stream));
+        let before = (self.strength < 1.0).then(|| grid.to_vec());
+
+        // Rolling rows up by the drift moves the glitched rows down
+        let roll = (self.drift_rows.round() as i64).rem_euclid(height as i64) as usize * width;
+        grid.rotate_left(roll);
+        f(grid, &mut rng);
+        grid.rotate_right(roll);
+
+        if let Some(before) = before {
+            for (v, b) in grid.iter_mut().zip(before) {
+                *v = b + (*v - b) * self.strength;
+            }
+        }
+    }
+}
+
+/// SplitMix64 finalizer over `a` and `b`, so neighboring pattern indices
+/// and streams get unrelated seeds.
+pub(super) fn mix(a: u64, b: u64) -> u64 {
+    let mut z = a ^ b.wrapping_add(0x9e37_79b9_7f4a_7c15).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
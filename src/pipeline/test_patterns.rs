@@ -0,0 +1,236 @@
+//! Synthetic sources for exercising the pipeline without loading an image.
+//!
+//! Patterns are rendered as linear HDR (`Rgb32F`) at the requested size, so
+//! a pattern generated at the sensor size maps one-to-one onto photosites.
+//! Geometric patterns are supersampled 4x4 so edges carry sub-pixel
+//! coverage rather than staircase aliasing.
+
+use image::{DynamicImage, Rgb32FImage};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestPattern {
+    /// Horizontal linear ramp from black to white.
+    Gradient,
+    /// Alternating dark/light squares, twelve to the short side.
+    Checkerboard,
+    /// Dark/light edge tilted 5 degrees off vertical, for MTF measurement.
+    SlantedEdge,
+    /// 36-cycle radial star: spatial frequency rises toward the center.
+    SiemensStar,
+    /// 75% color bars over a grayscale step wedge.
+    ColorBars,
+    /// Single-pixel points on black, brightening left to right past full well.
+    HotPoints,
+    /// USAF 1951-style three-bar resolution groups.
+    Usaf,
+}
+
+impl TestPattern {
+    pub const ALL: &[TestPattern] = &[
+        TestPattern::Gradient,
+        TestPattern::Checkerboard,
+        TestPattern::SlantedEdge,
+        TestPattern::SiemensStar,
+        TestPattern::ColorBars,
+        TestPattern::HotPoints,
+        TestPattern::Usaf,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TestPattern::Gradient => "Gradient",
+            TestPattern::Checkerboard => "Checkerboard",
+            TestPattern::SlantedEdge => "Slanted Edge",
+            TestPattern::SiemensStar => "Siemens Star",
+            TestPattern::ColorBars => "Color Bars",
+            TestPattern::HotPoints => "Hot Points",
+            TestPattern::Usaf => "USAF Target",
+        }
+    }
+
+    /// Command-line spelling, e.g. `siemens-star`.
+    pub fn cli_name(self) -> &'static str {
+        match self {
+            TestPattern::Gradient => "gradient",
+            TestPattern::Checkerboard => "checkerboard",
+            TestPattern::SlantedEdge => "slanted-edge",
+            TestPattern::SiemensStar => "siemens-star",
+            TestPattern::ColorBars => "color-bars",
+            TestPattern::HotPoints => "hot-points",
+            TestPattern::Usaf => "usaf",
+        }
+    }
+
+    pub fn from_cli_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|p| p.cli_name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|p| p.cli_name()).collect();
+                format!("Unknown test pattern '{name}' (expected one of: {})", names.join(", "))
+            })
+    }
+}
+
+/// Low and high levels for the two-tone patterns, kept off the rails so
+/// ringing and undershoot stay visible instead of clipping.
+pub const DARK: f64 = 0.1;
+pub const LIGHT: f64 = 0.9;
+
+/// Slanted-edge tilt off vertical, in degrees.
+pub const SLANTED_EDGE_ANGLE_DEG: f64 = 5.0;
+
+/// Render `pattern` at `width` x `height`.
+pub fn generate(pattern: TestPattern, width: u32, height: u32) -> DynamicImage {
+    let (w, h) = (width.max(1) as f64, height.max(1) as f64);
+    let short = w.min(h);
+    let img = match pattern {
+        TestPattern::Gradient => render(width, height, 1, |x, _| {
+            gray(x / (w - 1.0).max(1.0))
+        }),
+        TestPattern::Checkerboard => {
+            let block = (short / 12.0).floor().max(1.0);
+            render(width, height, 1, move |x, y| {
+                let odd = ((x / block).floor() + (y / block).floor()) as i64 % 2 != 0;
+                gray(if odd { DARK } else { LIGHT })
+            })
+        }
+        TestPattern::SlantedEdge => {
+            let (sin, cos) = SLANTED_EDGE_ANGLE_DEG.to_radians().sin_cos();
+            let (cx, cy) = (w / 2.0, h / 2.0);
+            render(width, height, 4, move |x, y| {
+                // Signed distance from a line through the center, tilted off vertical
+                let d = (x - cx) * cos - (y - cy) * sin;
+                gray(if d < 0.0 { DARK } else { LIGHT })
+            })
+        }
+        TestPattern::SiemensStar => {
+            let (cx, cy) = (w / 2.0, h / 2.0);
+            let radius = short * 0.45;
+            let cycles = 36.0;
+            render(width, height, 4, move |x, y| {
+                let (dx, dy) = (x - cx, y - cy);
+                if dx * dx + dy * dy > radius * radius {
+                    return gray(0.5);
+                }
+                let phase = libm::atan2(dy, dx) * cycles / std::f64::consts::TAU;
+                gray(if phase.rem_euclid(1.0) < 0.5 { DARK } else { LIGHT })
+            })
+        }
+        TestPattern::ColorBars => render(width, height, 1, move |x, y| color_bar(x / w, y / h)),
+        TestPattern::HotPoints => hot_points(width, height),
+        TestPattern::Usaf => usaf(width, height),
+    };
+    DynamicImage::ImageRgb32F(img)
+}
+
+fn gray(v: f64) -> [f64; 3] {
+    [v; 3]
+}
+
+/// Evaluate `f` at pixel-space coordinates, averaging `ss` x `ss` samples per pixel.
+fn render(
+    width: u32,
+    height: u32,
+    ss: u32,
+    f: impl Fn(f64, f64) -> [f64; 3],
+) -> Rgb32FImage {
+    let ss = ss.max(1);
+    let inv = 1.0 / (ss * ss) as f64;
+    Rgb32FImage::from_fn(width, height, |px, py| {
+        let mut sum = [0.0f64; 3];
+        for sy in 0..ss {
+            for sx in 0..ss {
+                let x = px as f64 + (sx as f64 + 0.5) / ss as f64;
+                let y = py as f64 + (sy as f64 + 0.5) / ss as f64;
+                let v = f(x, y);
+                for c in 0..3 {
+                    sum[c] += v[c];
+                }
+            }
+        }
+        image::Rgb(sum.map(|s| (s * inv) as f32))
+    })
+}
+
+/// SMPTE-style bars (white, yellow, cyan, green, magenta, red, blue at 75%)
+/// over an eleven-step gray wedge in the bottom quarter.
+fn color_bar(u: f64, v: f64) -> [f64; 3] {
+    const BARS: [[f64; 3]; 7] = [
+        [0.75, 0.75, 0.75],
+        [0.75, 0.75, 0.0],
+        [0.0, 0.75, 0.75],
+        [0.0, 0.75, 0.0],
+        [0.75, 0.0, 0.75],
+        [0.75, 0.0, 0.0],
+        [0.0, 0.0, 0.75],
+    ];
+    if v >= 0.75 {
+        let step = (u * 11.0).floor().min(10.0);
+        return gray(step / 10.0);
+    }
+    BARS[((u * 7.0) as usize).min(6)]
+}
+
+/// Point sources on a 32-pixel grid. Each column of points is brighter than
+/// the last, from 1/8x to 8x full well, so the right side shows blooming.
+fn hot_points(width: u32, height: u32) -> Rgb32FImage {
+    const SPACING: u32 = 32;
+    let mut img = Rgb32FImage::new(width, height);
+    let columns = width.div_ceil(SPACING).max(1);
+    for (i, x) in (SPACING / 2..width).step_by(SPACING as usize).enumerate() {
+        let t = if columns > 1 { i as f64 / (columns - 1) as f64 } else { 0.0 };
+        let level = libm::exp2(-3.0 + 6.0 * t) as f32;
+        for y in (SPACING / 2..height).step_by(SPACING as usize) {
+            img.put_pixel(x, y, image::Rgb([level; 3]));
+        }
+    }
+    img
+}
+
+/// Three-bar resolution elements shrinking by 2^(1/6) per step, laid out
+/// left to right in rows. Each element has horizontal bars on the left and
+/// vertical bars on the right, dark on a light field.
+fn usaf(width: u32, height: u32) -> Rgb32FImage {
+    let (w, h) = (width as f64, height as f64);
+    // (x, y, bar width) of each element's top-left corner
+    let mut elements = Vec::new();
+    let mut bar = (w.min(h) / 24.0).max(0.5);
+    let (mut x, mut y) = (bar * 2.0, bar * 2.0);
+    let mut row_height = 0.0f64;
+    while bar >= 0.25 {
+        let (ew, eh) = (bar * 11.0, bar * 5.0);
+        if x + ew > w - bar {
+            x = bar * 2.0;
+            y += row_height;
+            row_height = 0.0;
+        }
+        if y + eh > h {
+            break;
+        }
+        elements.push((x, y, bar));
+        x += ew + bar * 3.0;
+        row_height = row_height.max(eh + bar * 3.0);
+        bar /= libm::exp2(1.0 / 6.0);
+    }
+
+    render(width, height, 4, move |px, py| {
+        for &(ex, ey, b) in &elements {
+            let (lx, ly) = (px - ex, py - ey);
+            if !(0.0..b * 11.0).contains(&lx) || !(0.0..b * 5.0).contains(&ly) {
+                continue;
+            }
+            let on_bar = if lx < b * 5.0 {
+                // Horizontal bars: rows 0, 2, 4 of five bar widths
+                ((ly / b) as u32).is_multiple_of(2)
+            } else if lx >= b * 6.0 {
+                (((lx - b * 6.0) / b) as u32).is_multiple_of(2)
+            } else {
+                false
+            };
+            return gray(if on_bar { DARK } else { LIGHT });
+        }
+        gray(LIGHT)
+    })
+}
@@ -0,0 +1,184 @@
+//! Three-chip rendering: a prism splits the image onto three sensors, one per
+//! color, as in 3-CCD cameras. Each chip is a whole simulated sensor with its
+//! own seeds and optionally its own settings, so its noise and glitches land
+//! independently of the others and come apart in color when recombined.
+//!
+//! Every chip renders the full frame and the recombined output keeps each
+//! chip's own channel, so with nothing random and no overrides the result
+//! matches a single-chip render.
+//!
+//! The red and blue chips can sit off the green one, as a prism block drifts
+//! out of registration: shifted and rotated, so edges fringe in color. They
+//! can also start their readout a few rows off green's, so everything timed
+//! by the readout (glitch bands, flicker, row noise, sag) lands on different
+//! rows in each channel while the image itself stays registered: color
+//! tearing as from a mistimed 3-tube or 3-CCD camera.
+
+use std::time::Instant;
+
+use image::DynamicImage;
+
+use super::{GlitchFrame, PipelineParams, StageTimings};
+
+/// Channel of each chip, in chip order.
+pub const CHIPS: [&str; 3] = ["red", "green", "blue"];
+
+/// Where a chip sits relative to the green chip.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChipAlignment {
+    /// Offset of the image on the chip, in photosites.
+    pub dx: f64,
+    pub dy: f64,
+    /// Rotation of the image about the frame center, in degrees.
+    pub rotation: f64,
+    /// Rows the chip's readout runs behind green's; negative runs ahead.
+    pub skew: i64,
+}
+
+/// Three-chip controls.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThreeChip {
+    pub enabled: bool,
+    /// Per chip, `name=value` settings layered on the shared parameters,
+    /// separated by commas or semicolons.
+    pub overrides: [String; 3],
+    pub red: ChipAlignment,
+    pub blue: ChipAlignment,
+}
+
+impl ThreeChip {
+    /// Alignment of chip `chip`; green is the reference.
+    pub fn alignment(&self, chip: usize) -> ChipAlignment {
+        match chip {
+            0 => self.red,
+            2 => self.blue,
+            _ => ChipAlignment::default(),
+        }
+    }
+}
+
+/// Parse an override list into (name, value) pairs.
+pub fn parse_overrides(text: &str) -> Result<Vec<(String, String)>, String> {
+    text.split([',', ';'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let (name, value) = item
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid override (expected name=value): {item}"))?;
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// The parameters chip `chip` renders with: the shared ones with its seeds
+/// advanced and its overrides applied.
+pub fn chip_params(params: &PipelineParams, chip: usize) -> Result<PipelineParams, String> {
+    let mut chip_params = params.clone();
+    chip_params.three_chip.enabled = false;
+    let offset = chip as u64;
+    chip_params.noise.seed = params.noise.seed.wrapping_add(offset);
+    chip_params.determinism.seed = params.determinism.seed.wrapping_add(offset);
+    for (name, value) in parse_overrides(&params.three_chip.overrides[chip])? {
+        if name.starts_with("three_chip.") {
+            return Err(format!("{name} cannot be overridden per chip"));
+        }
+        chip_params.set_value(&name, &value)?;
+    }
+    Ok(chip_params)
+}
+
+impl PipelineParams {
+    /// Set chip `chip`'s override list, refusing one that does not parse or
+    /// apply.
+    pub(super) fn set_chip_overrides(&mut self, chip: usize, text: &str) -> Result<(), String> {
+        let previous = std::mem::replace(&mut self.three_chip.overrides[chip], text.to_string());
+        if let Err(e) = chip_params(self, chip) {
+            self.three_chip.overrides[chip] = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+/// Render each chip through the whole pipeline and interleave their own
+/// channels. Stage timings are summed over the chips, plus the recombine.
+pub fn process_three_chip(
+    source: &DynamicImage,
+    params: &PipelineParams,
+    spice_cache: &Option<crate::spice::SpiceCache>,
+    temporal: Option<&GlitchFrame>,
+) -> (usize, usize, Vec<u8>, StageTimings) {
+    let mut chips = Vec::with_capacity(CHIPS.len());
+    let mut timings = StageTimings::default();
+    // Offsets are given on the sensor; the source may be a different size
+    let to_source_x = source.width() as f64 / params.sensor_width.max(1) as f64;
+    let to_source_y = source.height() as f64 / params.sensor_height.max(1) as f64;
+    for chip in 0..CHIPS.len() {
+        // Overrides were checked when set; a bad one renders as shared
+        let chip_params = chip_params(params, chip).unwrap_or_else(|_| {
+            let mut shared = params.clone();
+            shared.three_chip.enabled = false;
+            shared
+        });
+        let align = params.three_chip.alignment(chip);
+        let (w, h, bytes, t) = if align == ChipAlignment::default() {
+            super::render_timed(source, &chip_params, spice_cache, temporal)
+        } else {
+            // Roll the source against the skew and the output back, so the
+            // image registers but the readout starts `skew` rows later
+            let dx = align.dx * to_source_x;
+            let dy = align.dy * to_source_y;
+            let moved = super::stack::misregister(source, dx, dy, align.rotation);
+            let rows = (align.skew as f64 * to_source_y).round() as i64;
+            let moved = roll_rows(&moved, -rows);
+            let (w, h, mut bytes, t) =
+                super::render_timed(&moved, &chip_params, spice_cache, temporal);
+            roll(&mut bytes, w * 3, align.skew);
+            (w, h, bytes, t)
+        };
+        chips.push((w, h, bytes));
+        if timings.stages.is_empty() {
+            timings = t;
+        } else {
+            for ((_, total), (_, ms)) in timings.stages.iter_mut().zip(t.stages) {
+                *total += ms;
+            }
+            timings.issues.extend(t.issues);
+        }
+    }
+
+    let start = Instant::now();
+    let (width, height) = (chips[0].0, chips[0].1);
+    let mut combined = chips[0].2.clone();
+    for (c, (w, h, bytes)) in chips.iter().enumerate().skip(1) {
+        // An override that resizes a chip cannot register with the others
+        if (*w, *h) != (width, height) {
+            continue;
+        }
+        for (out, pixel) in combined.chunks_exact_mut(3).zip(bytes.chunks_exact(3)) {
+            out[c] = pixel[c];
+        }
+    }
+    timings.stages.push(("3-CCD", start.elapsed().as_secs_f64() * 1000.0));
+    (width, height, combined, timings)
+}
+
+/// Rotate an image's rows down by `rows`, wrapping those pushed off the
+/// bottom back to the top.
+fn roll_rows(source: &DynamicImage, rows: i64) -> DynamicImage {
+    let mut img = source.to_rgb32f();
+    let row_len = img.width() as usize * 3;
+    roll(&mut img, row_len, rows);
+    DynamicImage::ImageRgb32F(img)
+}
+
+/// `roll_rows` on a row-major buffer of `row_len` values per row.
+fn roll<T>(values: &mut [T], row_len: usize, rows: i64) {
+    let height = values.len().checked_div(row_len).unwrap_or(0);
+    if height == 0 {
+        return;
+    }
+    let shift = rows.rem_euclid(height as i64) as usize;
+    values[..height * row_len].rotate_right(shift * row_len);
+}
@@ -0,0 +1,120 @@
+//! Buffer checks between stages, for tracking down renders that come out
+//! black: extreme parameter combinations can overflow to infinity or divide
+//! zero by zero somewhere upstream, and every later stage then passes the
+//! NaN along silently.
+//!
+//! With `validate_buffers` on, each stage's output is scanned as it is
+//! tapped, and the stages holding NaN, infinite or negative electron values
+//! are listed in `StageTimings::issues`, first offender first.
+
+use super::{StageBuffer, StageData, StageUnits};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IssueKind {
+    NaN,
+    Infinite,
+    /// Below zero in a buffer of electrons, which no charge can be.
+    NegativeElectrons,
+}
+
+impl IssueKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            IssueKind::NaN => "NaN",
+            IssueKind::Infinite => "infinite",
+            IssueKind::NegativeElectrons => "negative electron",
+        }
+    }
+}
+
+/// Bad values in one stage's output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferIssue {
+    pub stage: &'static str,
+    /// The worst kind found: NaN over infinite over negative.
+    pub kind: IssueKind,
+    /// Values of that kind in the buffer.
+    pub count: usize,
+    /// First offending pixel, in the stage's own frame.
+    pub x: usize,
+    pub y: usize,
+    /// Channel of the first offending value, for RGB buffers.
+    pub channel: Option<usize>,
+    pub value: f64,
+}
+
+impl std::fmt::Display for BufferIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} {} value(s), first {} at ({}, {})",
+            self.stage,
+            self.count,
+            self.kind.name(),
+            self.value,
+            self.x,
+            self.y
+        )?;
+        if let Some(c) = self.channel {
+            write!(f, " in {}", ["R", "G", "B"][c])?;
+        }
+        Ok(())
+    }
+}
+
+fn classify(value: f64, units: StageUnits) -> Option<IssueKind> {
+    if value.is_nan() {
+        Some(IssueKind::NaN)
+    } else if value.is_infinite() {
+        Some(IssueKind::Infinite)
+    } else if value < 0.0 && units == StageUnits::Electrons {
+        Some(IssueKind::NegativeElectrons)
+    } else {
+        None
+    }
+}
+
+/// The worst problem in `data`, the output of `stage`, if any.
+pub fn scan(stage: &'static str, data: &StageData<'_>) -> Option<BufferIssue> {
+    // (index, channel, value) of every value
+    let values: Box<dyn Iterator<Item = (usize, Option<usize>, f64)>> = match data.buffer {
+        StageBuffer::Mosaic(grid) => Box::new(grid.iter().enumerate().map(|(i, &v)| (i, None, v))),
+        StageBuffer::Rgb(rgb) => Box::new(
+            rgb.iter()
+                .enumerate()
+                .flat_map(|(i, p)| p.iter().enumerate().map(move |(c, &v)| (i, Some(c), v))),
+        ),
+    };
+    let mut worst: Option<BufferIssue> = None;
+    for (i, channel, value) in values {
+        let Some(kind) = classify(value, data.units) else {
+            continue;
+        };
+        match &mut worst {
+            Some(issue) if issue.kind == kind => issue.count += 1,
+            // Earlier kinds in the enum are worse
+            Some(issue) if (issue.kind as u8) < (kind as u8) => {}
+            _ => {
+                let width = data.width.max(1);
+                worst = Some(BufferIssue {
+                    stage,
+                    kind,
+                    count: 1,
+                    x: i % width,
+                    y: i / width,
+                    channel,
+                    value,
+                });
+            }
+        }
+    }
+    worst
+}
+
+/// Whether `data` holds any NaN or infinite value.
+pub fn has_non_finite(data: &StageData<'_>) -> bool {
+    match data.buffer {
+        StageBuffer::Mosaic(grid) => grid.iter().any(|v| !v.is_finite()),
+        StageBuffer::Rgb(rgb) => rgb.iter().flatten().any(|v| !v.is_finite()),
+    }
+}
@@ -0,0 +1,48 @@
+//! Batch variations: the same parameters rendered under several seeds.
+//!
+//! The glitch stages draw a fresh pattern every run, so one setting can give
+//! a clean frame or a wrecked one. Rendering a batch of rolls side by side
+//! lets the best be picked; each roll's seed is the base seed of the held
+//! glitch patterns (`TemporalState`), so `--stream --seed` on a single frame
+//! draws the same glitches again. Noise and other per-pixel randomness still
+//! differs between runs.
+
+use image::{DynamicImage, RgbImage};
+
+use super::{PipelineParams, TemporalState};
+use crate::spice::SpiceCache;
+
+/// Render `count` variations of `params` with seeds `base_seed`,
+/// `base_seed + 1`, ..., handing each (index, seed, image) to `save`, and
+/// return a contact sheet of all of them labeled by seed, `cell_width` wide
+/// per render. Stops at the first error `save` returns.
+pub fn render(
+    source: &DynamicImage,
+    params: &PipelineParams,
+    count: usize,
+    base_seed: u64,
+    cell_width: u32,
+    spice_cache: &Option<SpiceCache>,
+    mut save: impl FnMut(usize, u64, &RgbImage) -> Result<(), String>,
+) -> Result<RgbImage, String> {
+    let mut cells = Vec::with_capacity(count);
+    for index in 0..count {
+        let seed = base_seed.wrapping_add(index as u64);
+        let mut state = TemporalState::seeded(seed);
+        let (w, h, bytes) = super::process_temporal(source, params, spice_cache, &mut state);
+        let image = RgbImage::from_raw(w as u32, h as u32, bytes)
+            .ok_or("Pipeline returned a malformed image")?;
+        save(index, seed, &image)?;
+        // Only the sheet-sized copy is kept, so large batches stay small
+        let thumb_h = ((h as f64 * cell_width as f64 / w as f64).round() as u32).max(1);
+        let thumb = image::imageops::resize(
+            &image,
+            cell_width,
+            thumb_h,
+            image::imageops::FilterType::Triangle,
+        );
+        cells.push((format!("seed {seed}"), thumb));
+    }
+    let columns = (count as f64).sqrt().ceil() as usize;
+    super::contact_sheet::labeled_grid(&cells, columns, cell_width)
+}
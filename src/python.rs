@@ -0,0 +1,239 @@
+//! Python bindings (`pyo3` feature).
+//!
+//! Builds as the `physical_ccd_glitch` extension module with maturin.
+//! Parameters are addressed by the same names as `PipelineParams::values()`
+//! and the CLI's `--set`; images go in and out as `uint8` numpy arrays of
+//! shape `(height, width, channels)`.
+//!
+//! ```python
+//! import physical_ccd_glitch as ccd
+//! params = ccd.Params({"v_glitch_rate": 0.02, "spice.mode": "Off"})
+//! out = ccd.process(image, params)
+//! ```
+
+use numpy::{PyArray1, PyArray3, PyArrayMethods, PyReadonlyArrayDyn, PyUntypedArrayMethods};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::IntoPyObjectExt;
+
+use crate::pipeline::test_patterns::{self, TestPattern};
+use crate::pipeline::{self, ParamValue, PipelineParams};
+use crate::spice::{self, SpiceCache};
+
+fn value_error(e: String) -> PyErr {
+    PyValueError::new_err(e)
+}
+
+/// Pipeline parameters. Starts from the defaults; `values` may override any
+/// of them by name.
+#[pyclass(name = "Params", module = "physical_ccd_glitch")]
+pub struct PyParams {
+    inner: PipelineParams,
+}
+
+#[pymethods]
+impl PyParams {
+    #[new]
+    #[pyo3(signature = (values=None))]
+    fn new(values: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut params = Self { inner: PipelineParams::default() };
+        if let Some(values) = values {
+            for (name, value) in values.iter() {
+                params.set(&name.extract::<String>()?, &value)?;
+            }
+        }
+        Ok(params)
+    }
+
+    /// Set a parameter from a number, bool or choice name.
+    fn set(&mut self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let text = value.str()?.to_string();
+        self.inner.set_value(name, &text).map_err(value_error)
+    }
+
+    fn get(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        let value = self
+            .inner
+            .values()
+            .into_iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v)
+            .ok_or_else(|| value_error(format!("Unknown parameter: {name}")))?;
+        to_python(py, value)
+    }
+
+    fn __getitem__(&self, py: Python<'_>, name: &str) -> PyResult<PyObject> {
+        self.get(py, name)
+    }
+
+    fn __setitem__(&mut self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.set(name, value)
+    }
+
+    /// Every parameter name, in `values()` order.
+    fn names(&self) -> Vec<&'static str> {
+        self.inner.values().into_iter().map(|(name, _)| name).collect()
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for (name, value) in self.inner.values() {
+            dict.set_item(name, to_python(py, value)?)?;
+        }
+        Ok(dict)
+    }
+
+    /// Parameters that differ from the defaults, as `name: from -> to` lines.
+    fn changes(&self) -> Vec<String> {
+        PipelineParams::default()
+            .diff(&self.inner)
+            .iter()
+            .map(|c| c.to_string())
+            .collect()
+    }
+
+    /// The resolved stage graph as JSON, or Graphviz dot with `dot=True`.
+    #[pyo3(signature = (spice_cache=None, dot=false))]
+    fn graph(&self, spice_cache: Option<PyRef<'_, PySpiceCache>>, dot: bool) -> String {
+        let no_cache = None;
+        let cache = spice_cache.as_ref().map_or(&no_cache, |c| &c.inner);
+        let graph = pipeline::graph::resolve(&self.inner, cache);
+        if dot { graph.to_dot() } else { graph.to_json() }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Params([{}])", self.changes().join(", "))
+    }
+}
+
+fn to_python(py: Python<'_>, value: ParamValue) -> PyResult<PyObject> {
+    match value {
+        ParamValue::Float(v) => v.into_py_any(py),
+        ParamValue::Int(v) => v.into_py_any(py),
+        ParamValue::Bool(v) => v.into_py_any(py),
+        ParamValue::Choice(v) => v.into_py_any(py),
+        ParamValue::Data { len, hash } => (len, hash).into_py_any(py),
+    }
+}
+
+/// Circuit simulation results for a parameter set. Building one runs the
+/// SPICE simulations (seconds); reuse it across `process` calls while the
+/// `spice.*` parameters and full well are unchanged.
+#[pyclass(name = "SpiceCache", module = "physical_ccd_glitch")]
+pub struct PySpiceCache {
+    inner: Option<SpiceCache>,
+}
+
+#[pymethods]
+impl PySpiceCache {
+    #[new]
+    fn new(py: Python<'_>, params: PyRef<'_, PyParams>) -> Self {
+        let params = &params.inner;
+        let mut inner = None;
+        let sensor = params.spice_sensor();
+        py.allow_threads(|| spice::simulate_or_cache(&params.spice, &sensor, &mut inner));
+        Self { inner }
+    }
+
+    #[getter]
+    fn effective_cte(&self) -> Option<f64> {
+        self.inner.as_ref().map(|c| c.effective_cte)
+    }
+
+    #[getter]
+    fn noise_sigma(&self) -> Option<f64> {
+        self.inner.as_ref().map(|c| c.noise_sigma)
+    }
+
+    #[getter]
+    fn cds_rejection(&self) -> Option<f64> {
+        self.inner.as_ref().map(|c| c.cds_rejection)
+    }
+
+    /// Number of stages that fell back to analytical models.
+    #[getter]
+    fn fallbacks(&self) -> Option<usize> {
+        self.inner.as_ref().map(|c| c.fallbacks.count())
+    }
+
+    /// Composed (electrons in, electrons out) transfer curve.
+    fn transfer_curve(&self) -> Vec<(f64, f64)> {
+        self.inner.as_ref().map_or_else(Vec::new, |c| c.transfer_curve.clone())
+    }
+}
+
+/// Run the pipeline on an `(H, W)`, `(H, W, 1)`, `(H, W, 3)` or `(H, W, 4)`
+/// uint8 image. Returns `(H', W', 3)` uint8 at the sensor (or anamorphic
+/// output) size. SPICE modes need a `SpiceCache`; without one the math stages
+/// run instead.
+#[pyfunction]
+#[pyo3(signature = (image, params, spice_cache=None))]
+fn process<'py>(
+    py: Python<'py>,
+    image: PyReadonlyArrayDyn<'py, u8>,
+    params: PyRef<'py, PyParams>,
+    spice_cache: Option<PyRef<'py, PySpiceCache>>,
+) -> PyResult<Bound<'py, PyArray3<u8>>> {
+    let source = to_image(&image)?;
+    let params = &params.inner;
+    let no_cache = None;
+    let cache = spice_cache.as_ref().map_or(&no_cache, |c| &c.inner);
+    let (width, height, bytes) = py.allow_threads(|| pipeline::process(&source, params, cache));
+    to_array(py, bytes, width, height)
+}
+
+/// Generate a synthetic test pattern (`gradient`, `checkerboard`,
+/// `slanted-edge`, `siemens-star`, `color-bars`, `hot-points`, `usaf`) as an
+/// `(H, W, 3)` uint8 array.
+#[pyfunction]
+fn test_pattern<'py>(
+    py: Python<'py>,
+    name: &str,
+    width: u32,
+    height: u32,
+) -> PyResult<Bound<'py, PyArray3<u8>>> {
+    let pattern = TestPattern::from_cli_name(name).map_err(value_error)?;
+    let rgb = test_patterns::generate(pattern, width, height).to_rgb8();
+    to_array(py, rgb.into_raw(), width as usize, height as usize)
+}
+
+fn to_image(array: &PyReadonlyArrayDyn<'_, u8>) -> PyResult<image::DynamicImage> {
+    let shape = array.shape();
+    let (height, width, channels) = match *shape {
+        [h, w] => (h, w, 1),
+        [h, w, c] if matches!(c, 1 | 3 | 4) => (h, w, c),
+        _ => {
+            return Err(value_error(format!(
+                "Expected an (H, W), (H, W, 1), (H, W, 3) or (H, W, 4) array, got {shape:?}"
+            )));
+        }
+    };
+    // Iterating the view copies in logical order, whatever the strides
+    let data: Vec<u8> = array.as_array().iter().copied().collect();
+    let (w, h) = (width as u32, height as u32);
+    let image = match channels {
+        1 => image::GrayImage::from_raw(w, h, data).map(image::DynamicImage::ImageLuma8),
+        3 => image::RgbImage::from_raw(w, h, data).map(image::DynamicImage::ImageRgb8),
+        _ => image::RgbaImage::from_raw(w, h, data).map(image::DynamicImage::ImageRgba8),
+    };
+    image.ok_or_else(|| value_error("Image buffer does not match its shape".to_string()))
+}
+
+fn to_array(
+    py: Python<'_>,
+    bytes: Vec<u8>,
+    width: usize,
+    height: usize,
+) -> PyResult<Bound<'_, PyArray3<u8>>> {
+    PyArray1::from_vec(py, bytes).reshape([height, width, 3])
+}
+
+#[pymodule]
+fn physical_ccd_glitch(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyParams>()?;
+    m.add_class::<PySpiceCache>()?;
+    m.add_function(wrap_pyfunction!(process, m)?)?;
+    m.add_function(wrap_pyfunction!(test_pattern, m)?)?;
+    Ok(())
+}
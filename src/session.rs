@@ -0,0 +1,207 @@
+//! Crash-safe autosave of the session.
+//!
+//! The app saves its parameters (seed included) and where the source image
+//! came from through eframe's storage every few seconds, marked as running
+//! until it closes normally. A session still marked running at the next
+//! launch was cut short, and the app offers to restore it.
+//!
+//! The session is written in the preset text format, with the sensor preset
+//! and the source in `#` comment lines, so it is readable and a saved session
+//! loads as a preset too. Like a preset it only holds values with a text
+//! form: tone curves, loaded curves, palettes and LUTs, and plugin state are
+//! left out.
+
+use std::path::PathBuf;
+
+use image::DynamicImage;
+
+use crate::ccd::SensorPreset;
+use crate::pipeline::PipelineParams;
+use crate::pipeline::preset::{Preset, PresetGroup};
+use crate::pipeline::test_patterns::{self, TestPattern};
+
+const SESSION_KEY: &str = "ccd_glitch_session";
+const STATE_KEY: &str = "ccd_glitch_session_state";
+const RUNNING: &str = "running";
+const CLOSED: &str = "closed";
+
+/// Largest source image kept as bytes; browser storage holds only a few
+/// megabytes and base64 inflates by a third.
+const MAX_STORED_BYTES: usize = 2 * 1024 * 1024;
+
+/// Where the source image came from, so a restored session can load it again.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionSource {
+    /// An image file, reopened from disk.
+    Path(PathBuf),
+    /// A test pattern, regenerated at the sensor size.
+    Pattern(TestPattern),
+    /// Image bytes with no path, as browsers hand over files.
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub sensor_preset: Option<SensorPreset>,
+    /// Every settable parameter that differs from the defaults.
+    pub preset: Preset,
+    pub source: Option<SessionSource>,
+}
+
+impl Session {
+    pub fn capture(
+        params: &PipelineParams,
+        sensor_preset: SensorPreset,
+        source: Option<&SessionSource>,
+    ) -> Self {
+        Self {
+            sensor_preset: Some(sensor_preset),
+            preset: Preset::from_params("session", params, PresetGroup::All),
+            source: source.cloned(),
+        }
+    }
+
+    /// The saved parameters, set on top of the defaults.
+    pub fn params(&self) -> Result<PipelineParams, String> {
+        let mut params = PipelineParams::default();
+        self.preset.apply(&mut params)?;
+        Ok(params)
+    }
+
+    /// Load the source image again; `None` when the session had none or its
+    /// bytes were too large to keep.
+    pub fn load_source(&self, params: &PipelineParams) -> Result<Option<DynamicImage>, String> {
+        let image = match &self.source {
+            None => return Ok(None),
+            Some(SessionSource::Path(path)) => crate::image_io::load_image(path)?,
+            Some(SessionSource::Pattern(pattern)) => {
+                test_patterns::generate(*pattern, params.sensor_width, params.sensor_height)
+            }
+            Some(SessionSource::Bytes(bytes)) => image::load_from_memory(bytes)
+                .map_err(|e| format!("Failed to decode saved image: {e}"))?,
+        };
+        Ok(Some(image))
+    }
+
+    /// A line for the restore prompt saying what the session held.
+    pub fn summary(&self) -> String {
+        let source = match &self.source {
+            None => "no image".to_string(),
+            Some(SessionSource::Path(path)) => path.display().to_string(),
+            Some(SessionSource::Pattern(pattern)) => format!("{} pattern", pattern.name()),
+            Some(SessionSource::Bytes(bytes)) => format!("image ({} KB)", bytes.len() / 1024),
+        };
+        let sensor = self.sensor_preset.map_or("unknown sensor", |p| p.name());
+        format!("{sensor}, {source}, {} changed parameter(s)", self.preset.values.len())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        if let Some(preset) = self.sensor_preset {
+            text.push_str(&format!("# sensor: {}\n", preset.name()));
+        }
+        match &self.source {
+            None => {}
+            Some(SessionSource::Path(path)) => {
+                text.push_str(&format!("# source: {}\n", path.display()));
+            }
+            Some(SessionSource::Pattern(pattern)) => {
+                text.push_str(&format!("# source: pattern:{}\n", pattern.cli_name()));
+            }
+            Some(SessionSource::Bytes(bytes)) if bytes.len() <= MAX_STORED_BYTES => {
+                text.push_str(&format!("# source-bytes: {}\n", base64_encode(bytes)));
+            }
+            Some(SessionSource::Bytes(bytes)) => {
+                log::info!("Source image too large to autosave ({} bytes)", bytes.len());
+            }
+        }
+        text.push_str(&self.preset.to_text());
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut sensor_preset = None;
+        let mut source = None;
+        for line in text.lines() {
+            let Some(comment) = line.trim().strip_prefix('#').map(str::trim) else {
+                continue;
+            };
+            if let Some(name) = comment.strip_prefix("sensor:") {
+                sensor_preset = SensorPreset::ALL.iter().copied().find(|p| p.name() == name.trim());
+            } else if let Some(bytes) = comment.strip_prefix("source-bytes:") {
+                source = Some(SessionSource::Bytes(base64_decode(bytes.trim())?));
+            } else if let Some(path) = comment.strip_prefix("source:") {
+                let path = path.trim();
+                source = Some(match path.strip_prefix("pattern:") {
+                    Some(name) => SessionSource::Pattern(TestPattern::from_cli_name(name)?),
+                    None => SessionSource::Path(PathBuf::from(path)),
+                });
+            }
+        }
+        Ok(Self {
+            sensor_preset,
+            preset: Preset::parse(text, "session")?,
+            source,
+        })
+    }
+}
+
+/// The session saved by a run that never closed, if the last one crashed.
+pub fn crashed_session(storage: &dyn eframe::Storage) -> Option<Session> {
+    if storage.get_string(STATE_KEY).as_deref() != Some(RUNNING) {
+        return None;
+    }
+    let text = storage.get_string(SESSION_KEY)?;
+    match Session::parse(&text) {
+        Ok(session) => Some(session),
+        Err(e) => {
+            log::warn!("Autosaved session is unreadable: {e}");
+            None
+        }
+    }
+}
+
+/// Save `session`, marked running unless the app is closing.
+pub fn store(storage: &mut dyn eframe::Storage, session: &Session, closing: bool) {
+    storage.set_string(SESSION_KEY, session.to_text());
+    storage.set_string(STATE_KEY, if closing { CLOSED } else { RUNNING }.to_string());
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    for chunk in text.trim_end_matches('=').as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return Err("Invalid base64 in saved image".to_string()),
+            };
+            n |= (value as u32) << (18 - 6 * i);
+        }
+        for i in 0..chunk.len().saturating_sub(1) {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,95 @@
+use eframe::egui;
+
+use crate::pipeline::PipelineParams;
+
+const THUMB_HEIGHT: u32 = 72;
+const SELECTED: egui::Color32 = egui::Color32::from_rgb(255, 200, 0);
+const LABEL_DIM: egui::Color32 = egui::Color32::from_rgb(120, 120, 140);
+/// Most parameter changes listed in a thumbnail's tooltip.
+const MAX_LISTED_CHANGES: usize = 12;
+
+/// A pinned result: a thumbnail plus the full parameters that produced it.
+pub struct Snapshot {
+    pub params: PipelineParams,
+    pub texture: egui::TextureHandle,
+}
+
+impl Snapshot {
+    /// Thumbnail an RGB8 render and keep `params` alongside it.
+    pub fn new(
+        ctx: &egui::Context,
+        params: &PipelineParams,
+        width: usize,
+        height: usize,
+        rgb: &[u8],
+    ) -> Option<Self> {
+        let img = image::RgbImage::from_raw(width as u32, height as u32, rgb.to_vec())?;
+        let thumb_w = (width as u32 * THUMB_HEIGHT / height.max(1) as u32).max(1);
+        let thumb = image::imageops::thumbnail(&img, thumb_w, THUMB_HEIGHT);
+        let texture = ctx.load_texture(
+            "snapshot",
+            egui::ColorImage::from_rgb([thumb.width() as usize, thumb.height() as usize], &thumb),
+            egui::TextureOptions::LINEAR,
+        );
+        Some(Self { params: params.clone(), texture })
+    }
+}
+
+pub enum GalleryAction {
+    Restore(usize),
+    Remove(usize),
+}
+
+/// Draw the snapshot strip. Click a thumbnail to restore its parameters,
+/// right-click to remove it. Thumbnails whose parameters match `current`
+/// are outlined; hovering lists what restoring would change.
+pub fn draw_gallery(
+    ui: &mut egui::Ui,
+    snapshots: &[Snapshot],
+    current: &PipelineParams,
+) -> Option<GalleryAction> {
+    let mut action = None;
+    egui::ScrollArea::horizontal().show(ui, |ui| {
+        ui.horizontal(|ui| {
+            for (i, snapshot) in snapshots.iter().enumerate() {
+                // Listed as "current -> snapshot", what restoring would change
+                let changes = snapshot.params.diff(current);
+                let size = snapshot.texture.size_vec2();
+                let button = egui::ImageButton::new(egui::load::SizedTexture::new(
+                    snapshot.texture.id(),
+                    size,
+                ))
+                .selected(changes.is_empty());
+                let response = ui.add(button).on_hover_ui(|ui| {
+                    ui.label(format!("Snapshot {}", i + 1));
+                    if changes.is_empty() {
+                        let text = "Matches current parameters";
+                        ui.label(egui::RichText::new(text).small().color(LABEL_DIM));
+                    }
+                    for change in changes.iter().take(MAX_LISTED_CHANGES) {
+                        ui.label(egui::RichText::new(change.to_string()).small().monospace());
+                    }
+                    if changes.len() > MAX_LISTED_CHANGES {
+                        let more = format!("... and {} more", changes.len() - MAX_LISTED_CHANGES);
+                        ui.label(egui::RichText::new(more).small().color(LABEL_DIM));
+                    }
+                });
+                if changes.is_empty() {
+                    ui.painter().rect_stroke(
+                        response.rect,
+                        2.0,
+                        (1.5, SELECTED),
+                        egui::StrokeKind::Outside,
+                    );
+                }
+                if response.clicked() {
+                    action = Some(GalleryAction::Restore(i));
+                }
+                if response.secondary_clicked() {
+                    action = Some(GalleryAction::Remove(i));
+                }
+            }
+        });
+    });
+    action
+}
@@ -2,19 +2,20 @@
 //!
 //! This is the core analog stage that converts charge to voltage.
 
-use super::SpiceParams;
+use super::{SpiceParams, SpiceSensor};
+use crate::ccd::units::{ConversionGain, Volts};
 
 /// Build a JSON circuit for the output amplifier with a given FD voltage.
 ///
 /// Components:
 /// - Reset MOSFET (NMOS, W/L = 2u/0.5u) driven by phi_reset clock
 /// - Source follower MOSFET (NMOS, W/L = 10u/1u) with resistive load (10k)
-/// - Floating diffusion capacitor C_fd = 10fF
+/// - Floating diffusion capacitor C_fd = q / conversion gain
 /// - VDD = effective VDD, V_rd (reset drain) = VDD * 0.8
-pub fn build_amplifier_json(params: &SpiceParams, v_fd: f64) -> String {
+pub fn build_amplifier_json(params: &SpiceParams, sensor: &SpiceSensor, v_fd: f64) -> String {
     let vdd = params.effective_vdd();
     let v_rd = vdd * 0.8; // Reset drain voltage
-    let c_fd = 10e-15; // 10 fF floating diffusion
+    let c_fd = sensor.conversion_gain.capacitance();
     let r_load = 10_000.0; // 10k load resistor
     let g_load = 1.0 / r_load;
 
@@ -58,13 +59,13 @@ pub fn analytical_sf_gain(vdd: f64) -> f64 {
     gm * r_load / (1.0 + gm * r_load)
 }
 
-/// Estimate kTC reset noise in electrons.
-pub fn ktc_noise_electrons(temperature_k: f64) -> f64 {
+/// Estimate kTC reset noise in electrons on a floating diffusion of the
+/// given conversion gain: a smaller node resets with more voltage noise but
+/// fewer electrons of it.
+pub fn ktc_noise_electrons(temperature_k: f64, gain: ConversionGain) -> f64 {
     let k = 1.38e-23;
-    let c_fd = 10e-15;
-    let q = 1.6e-19;
-    let ktc_voltage = (k * temperature_k / c_fd).sqrt();
-    ktc_voltage * c_fd / q
+    let ktc_voltage = (k * temperature_k / gain.capacitance()).sqrt();
+    gain.electrons(Volts(ktc_voltage)).0
 }
 
 /// Run amplifier simulation: sweep FD voltage and extract output transfer curve + noise.
@@ -73,7 +74,7 @@ pub fn ktc_noise_electrons(temperature_k: f64) -> f64 {
 /// Falls back to analytical on SPICE failure.
 pub fn run_amplifier_simulation(
     params: &SpiceParams,
-    full_well: f64,
+    sensor: &SpiceSensor,
     n_points: usize,
 ) -> (Vec<(f64, f64)>, f64, bool) {
     use std::panic;
@@ -81,7 +82,7 @@ pub fn run_amplifier_simulation(
     // Try full amplifier circuit first
     let params_clone = params.clone();
     let full_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-        try_full_amplifier(&params_clone, full_well, n_points)
+        try_full_amplifier(&params_clone, sensor, n_points)
     }));
 
     if let Ok(Some((ref curve, noise))) = full_result {
@@ -98,7 +99,7 @@ pub fn run_amplifier_simulation(
     // Try simpler source follower circuit
     let params_clone = params.clone();
     let sf_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-        try_simple_sf(&params_clone, full_well, n_points)
+        try_simple_sf(&params_clone, sensor, n_points)
     }));
 
     if let Ok(Some((ref curve, noise))) = sf_result {
@@ -113,7 +114,7 @@ pub fn run_amplifier_simulation(
     }
 
     log::warn!("All amplifier SPICE simulations failed, falling back to analytical");
-    let (curve, noise) = analytical_amplifier(params.effective_vdd(), params.temperature_k, full_well, n_points);
+    let (curve, noise) = analytical_amplifier(params, sensor, n_points);
     (curve, noise, true)
 }
 
@@ -132,7 +133,7 @@ fn is_valid_amp_curve(curve: &[(f64, f64)]) -> bool {
 
 fn try_full_amplifier(
     params: &SpiceParams,
-    full_well: f64,
+    sensor: &SpiceSensor,
     n_points: usize,
 ) -> Option<(Vec<(f64, f64)>, f64)> {
     use spice21::circuit::Ckt;
@@ -144,7 +145,7 @@ fn try_full_amplifier(
 
     for i in 0..n_points {
         let v_fd = v_fd_max * i as f64 / (n_points - 1).max(1) as f64;
-        let json = build_amplifier_json(params, v_fd);
+        let json = build_amplifier_json(params, sensor, v_fd);
 
         let ckt = Ckt::from_json(&json).ok()?;
         let opts = spice21::analysis::TranOptions {
@@ -164,8 +165,8 @@ fn try_full_amplifier(
     }
 
     let mid_v_fd = v_fd_max * 0.5;
-    let noise_sigma = measure_amp_noise(params, mid_v_fd, full_well)
-        .unwrap_or_else(|| ktc_noise_electrons(params.temperature_k));
+    let noise_sigma = measure_amp_noise(params, sensor, mid_v_fd)
+        .unwrap_or_else(|| ktc_noise_electrons(params.temperature_k, sensor.conversion_gain));
 
     Some((curve, noise_sigma))
 }
@@ -175,7 +176,7 @@ fn try_full_amplifier(
 /// Sweeps v_fd from 0 to VDD*0.7 as a signal voltage applied to the SF gate.
 fn try_simple_sf(
     params: &SpiceParams,
-    _full_well: f64,
+    sensor: &SpiceSensor,
     n_points: usize,
 ) -> Option<(Vec<(f64, f64)>, f64)> {
     use spice21::circuit::Ckt;
@@ -221,14 +222,14 @@ fn try_simple_sf(
         curve.push((v_fd, out_voltage));
     }
 
-    let noise_sigma = ktc_noise_electrons(params.temperature_k);
+    let noise_sigma = ktc_noise_electrons(params.temperature_k, sensor.conversion_gain);
     Some((curve, noise_sigma))
 }
 
-fn measure_amp_noise(params: &SpiceParams, v_fd: f64, _full_well: f64) -> Option<f64> {
+fn measure_amp_noise(params: &SpiceParams, sensor: &SpiceSensor, v_fd: f64) -> Option<f64> {
     use spice21::circuit::Ckt;
 
-    let json = build_amplifier_json(params, v_fd);
+    let json = build_amplifier_json(params, sensor, v_fd);
     let ckt = Ckt::from_json(&json).ok()?;
     let opts = spice21::analysis::TranOptions {
         tstep: 1e-10,
@@ -251,23 +252,21 @@ fn measure_amp_noise(params: &SpiceParams, v_fd: f64, _full_well: f64) -> Option
     let sigma_v = variance.sqrt();
 
     // Convert voltage noise to electron-equivalent
-    let c_fd = 10e-15;
-    let q = 1.6e-19;
-    let v_per_electron = q / c_fd;
-    let sigma_electrons = sigma_v / v_per_electron;
+    let sigma_electrons = sensor.conversion_gain.electrons(Volts(sigma_v)).0;
 
     // Add substrate noise contribution if present
     let _substrate = params.substrate_noise * 20.0;
 
-    Some(sigma_electrons.max(ktc_noise_electrons(params.temperature_k) * 0.5))
+    let ktc = ktc_noise_electrons(params.temperature_k, sensor.conversion_gain);
+    Some(sigma_electrons.max(ktc * 0.5))
 }
 
 fn analytical_amplifier(
-    vdd: f64,
-    temperature_k: f64,
-    _full_well: f64,
+    params: &SpiceParams,
+    sensor: &SpiceSensor,
     n_points: usize,
 ) -> (Vec<(f64, f64)>, f64) {
+    let vdd = params.effective_vdd();
     let gain = analytical_sf_gain(vdd);
     let v_fd_max = vdd * 0.7;
 
@@ -279,6 +278,6 @@ fn analytical_amplifier(
         })
         .collect();
 
-    let noise = ktc_noise_electrons(temperature_k);
+    let noise = ktc_noise_electrons(params.temperature_k, sensor.conversion_gain);
     (curve, noise)
 }
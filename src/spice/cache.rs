@@ -3,12 +3,16 @@
 //! Caches transfer curves, ringing kernels, and noise parameters
 //! to avoid re-running SPICE simulations on every frame.
 
-use super::{SpiceCache, SpiceParams};
+use super::{SpiceCache, SpiceParams, SpiceSensor};
 
 /// Check if the cache is still valid for the given parameters.
-pub fn is_cache_valid(cache: &Option<SpiceCache>, params: &SpiceParams) -> bool {
+pub fn is_cache_valid(
+    cache: &Option<SpiceCache>,
+    params: &SpiceParams,
+    sensor: &SpiceSensor,
+) -> bool {
     match cache {
-        Some(c) => c.is_valid_for(params),
+        Some(c) => c.is_valid_for(params, sensor),
         None => false,
     }
 }
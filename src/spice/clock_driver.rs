@@ -1,15 +1,15 @@
 //! CMOS clock driver circuit.
 //!
-//! Generates 3-phase non-overlapping clocks at configurable frequency.
+//! Generates 2-, 3- or 4-phase clocks at configurable frequency.
 //! Glitch effects: supply droop reduces swing, phase overlap, ringing from LC.
 
-use super::SpiceParams;
+use super::{SpiceParams, SpiceSensor};
 
 /// Build a JSON circuit for a CMOS clock driver.
 ///
 /// PMOS/NMOS push-pull driver per phase.
 /// C_load = 100pF (clock bus capacitance).
-pub fn build_clock_driver_json(params: &SpiceParams) -> String {
+pub fn build_clock_driver_json(params: &SpiceParams, sensor: &SpiceSensor) -> String {
     let vdd = params.effective_vdd();
     let c_load = 100e-12; // 100 pF clock bus capacitance
 
@@ -22,8 +22,8 @@ pub fn build_clock_driver_json(params: &SpiceParams) -> String {
         vdd
     ));
 
-    // Three phase drivers
-    for phase in 1..=3u32 {
+    // One driver per phase
+    for phase in 1..=sensor.clock_phases.clamp(2, 4) {
         let input = format!("drv_in{}", phase);
         let output = format!("clk_out{}", phase);
         signals.push(input.clone());
@@ -72,14 +72,17 @@ pub fn build_clock_driver_json(params: &SpiceParams) -> String {
 
 /// Run clock driver simulation to extract ringing kernel and clock waveforms.
 ///
-/// Returns (ringing_kernel, [phi1_waveform, phi2_waveform, phi3_waveform], analytical_fallback).
+/// Returns (ringing_kernel, one waveform per phase, analytical_fallback).
 /// Falls back to analytical models on SPICE failure.
-pub fn run_clock_simulation(params: &SpiceParams) -> (Vec<f64>, [Vec<f64>; 3], bool) {
+pub fn run_clock_simulation(
+    params: &SpiceParams,
+    sensor: &SpiceSensor,
+) -> (Vec<f64>, Vec<Vec<f64>>, bool) {
     use std::panic;
 
     let params = params.clone();
     let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-        try_clock_simulation(&params)
+        try_clock_simulation(&params, sensor)
     }));
 
     match result {
@@ -92,23 +95,27 @@ pub fn run_clock_simulation(params: &SpiceParams) -> (Vec<f64>, [Vec<f64>; 3], b
         }
         _ => {
             log::warn!("Clock driver SPICE simulation failed, falling back to analytical");
-            let kernel = analytical_ringing_kernel(&params);
-            let (phi1, phi2, phi3) = generate_clock_pattern(
+            let kernel = analytical_ringing_kernel(&params, sensor);
+            let waveforms = generate_clock_pattern(
+                sensor.clock_phases,
                 4,
                 64,
                 params.effective_vdd(),
                 params.phase_overlap_ns,
                 1.0 / (params.clock_freq_mhz * 1e6),
             );
-            (kernel, [phi1, phi2, phi3], true)
+            (kernel, waveforms, true)
         }
     }
 }
 
-fn try_clock_simulation(params: &SpiceParams) -> Option<(Vec<f64>, [Vec<f64>; 3])> {
+fn try_clock_simulation(
+    params: &SpiceParams,
+    sensor: &SpiceSensor,
+) -> Option<(Vec<f64>, Vec<Vec<f64>>)> {
     use spice21::circuit::Ckt;
 
-    let json = build_clock_driver_json(params);
+    let json = build_clock_driver_json(params, sensor);
     let ckt = Ckt::from_json(&json).ok()?;
 
     let opts = spice21::analysis::TranOptions {
@@ -120,8 +127,8 @@ fn try_clock_simulation(params: &SpiceParams) -> Option<(Vec<f64>, [Vec<f64>; 3]
     let result = spice21::analysis::tran(ckt, None, Some(opts)).ok()?;
 
     let clk1 = result.map.get("clk_out1")?.clone();
-    let clk2 = result.map.get("clk_out2").cloned().unwrap_or_default();
-    let clk3 = result.map.get("clk_out3").cloned().unwrap_or_default();
+    let others = (2..=sensor.clock_phases.clamp(2, 4))
+        .map(|phase| result.map.get(&format!("clk_out{phase}")).cloned().unwrap_or_default());
 
     if clk1.len() < 10 {
         return None;
@@ -142,13 +149,14 @@ fn try_clock_simulation(params: &SpiceParams) -> Option<(Vec<f64>, [Vec<f64>; 3]
         kernel.iter().map(|v| v / max_abs * 0.1).collect()
     } else {
         // Fallback: no significant ringing detected
-        analytical_ringing_kernel(params)
+        analytical_ringing_kernel(params, sensor)
     };
 
-    Some((normalized_kernel, [clk1, clk2, clk3]))
+    let waveforms = std::iter::once(clk1).chain(others).collect();
+    Some((normalized_kernel, waveforms))
 }
 
-fn analytical_ringing_kernel(params: &SpiceParams) -> Vec<f64> {
+fn analytical_ringing_kernel(params: &SpiceParams, sensor: &SpiceSensor) -> Vec<f64> {
     let kernel_len = 8;
     let ring_freq_pixels = 0.3;
     let omega = 2.0 * std::f64::consts::PI * ring_freq_pixels;
@@ -165,7 +173,10 @@ fn analytical_ringing_kernel(params: &SpiceParams) -> Vec<f64> {
     let overlap_amp_boost = 1.0 + overlap_fraction * 2.0;
     let overlap_damping_factor = 1.0 - overlap_fraction * 0.5;
 
-    let ring_amplitude = (0.02 + params.supply_droop * 0.1) * overlap_amp_boost;
+    // Each phase edge couples into the bus, so more phases ring harder
+    let phase_factor = sensor.clock_phases.clamp(2, 4) as f64 / 3.0;
+
+    let ring_amplitude = (0.02 + params.supply_droop * 0.1) * overlap_amp_boost * phase_factor;
     let effective_damping = damping * overlap_damping_factor.max(0.1);
 
     (0..kernel_len)
@@ -187,41 +198,38 @@ pub fn ringing_params(c_load: f64, l_bond: f64, r_driver: f64) -> (f64, f64) {
     (freq, zeta)
 }
 
-/// Generate a 3-phase non-overlapping clock pattern.
+/// Generate an `n_phases` clock pattern (clamped to 2..=4).
 ///
-/// Returns (phi1, phi2, phi3) as vectors of voltage values at each time step.
+/// 2- and 3-phase clocks are non-overlapping, each phase high for 1/n of the
+/// cycle; 4-phase clocks run at 50% duty in quadrature, so two gates are
+/// always high. `phase_overlap_ns` widens every pulse on both sides.
+/// Returns one vector of voltage values per phase.
 pub fn generate_clock_pattern(
+    n_phases: usize,
     n_cycles: usize,
     samples_per_cycle: usize,
     vdd: f64,
     phase_overlap_ns: f64,
     clock_period_s: f64,
-) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+) -> Vec<Vec<f64>> {
+    let n_phases = n_phases.clamp(2, 4);
     let total_samples = n_cycles * samples_per_cycle;
-    let mut phi1 = vec![0.0; total_samples];
-    let mut phi2 = vec![0.0; total_samples];
-    let mut phi3 = vec![0.0; total_samples];
-
     let overlap_fraction = phase_overlap_ns * 1e-9 / clock_period_s;
-
-    for i in 0..total_samples {
-        let t = (i % samples_per_cycle) as f64 / samples_per_cycle as f64;
-
-        // Phase 1: 0.0 - 0.333
-        let p1_start = 0.0;
-        let p1_end = 1.0 / 3.0 + overlap_fraction;
-        phi1[i] = if t >= p1_start && t < p1_end { vdd } else { 0.0 };
-
-        // Phase 2: 0.333 - 0.667
-        let p2_start = 1.0 / 3.0 - overlap_fraction;
-        let p2_end = 2.0 / 3.0 + overlap_fraction;
-        phi2[i] = if t >= p2_start && t < p2_end { vdd } else { 0.0 };
-
-        // Phase 3: 0.667 - 1.0
-        let p3_start = 2.0 / 3.0 - overlap_fraction;
-        let p3_end = 1.0;
-        phi3[i] = if t >= p3_start && t < p3_end { vdd } else { 0.0 };
-    }
-
-    (phi1, phi2, phi3)
+    let step = 1.0 / n_phases as f64;
+    let duty = if n_phases == 4 { 0.5 } else { step };
+
+    (0..n_phases)
+        .map(|p| {
+            let start = p as f64 * step - overlap_fraction;
+            let end = p as f64 * step + duty + overlap_fraction;
+            (0..total_samples)
+                .map(|i| {
+                    let t = (i % samples_per_cycle) as f64 / samples_per_cycle as f64;
+                    // Test the sample and its wrapped copies so pulses cross the cycle edge
+                    let high = [t - 1.0, t, t + 1.0].iter().any(|&t| t >= start && t < end);
+                    if high { vdd } else { 0.0 }
+                })
+                .collect()
+        })
+        .collect()
 }
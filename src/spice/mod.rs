@@ -13,10 +13,12 @@ pub mod models;
 pub mod pixel;
 pub mod shift_register;
 pub mod transfer_function;
+pub mod validation;
 
 // Internal ADC module (not the ccd::adc)
 pub mod adc;
 
+use crate::ccd::units::ConversionGain;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
@@ -95,10 +97,36 @@ impl Default for SpiceParams {
     }
 }
 
+/// The sensor the circuits are built around. These are pipeline parameters
+/// (`full_well`, `conversion_gain`, `v_phases`); build one with
+/// `PipelineParams::spice_sensor`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpiceSensor {
+    pub full_well: f64,
+    /// Floating diffusion charge-to-voltage gain.
+    pub conversion_gain: ConversionGain,
+    /// Vertical clock phases driven (2, 3 or 4).
+    pub clock_phases: usize,
+}
+
+impl Default for SpiceSensor {
+    fn default() -> Self {
+        Self {
+            full_well: 40_000.0,
+            conversion_gain: ConversionGain::uv_per_e(16.0),
+            clock_phases: 3,
+        }
+    }
+}
+
 impl SpiceParams {
-    /// Compute a hash for cache invalidation.
-    pub fn param_hash(&self) -> u64 {
+    /// Compute a hash of these parameters and the sensor for cache
+    /// invalidation.
+    pub fn param_hash(&self, sensor: &SpiceSensor) -> u64 {
         let mut hasher = DefaultHasher::new();
+        sensor.full_well.to_bits().hash(&mut hasher);
+        sensor.conversion_gain.uv_per_e.to_bits().hash(&mut hasher);
+        sensor.clock_phases.hash(&mut hasher);
         (self.mode as u8).hash(&mut hasher);
         self.vdd.to_bits().hash(&mut hasher);
         self.clock_freq_mhz.to_bits().hash(&mut hasher);
@@ -159,8 +187,8 @@ pub struct SpiceCache {
     pub effective_cte: f64,
     /// Ringing kernel from clock driver simulation.
     pub clock_ringing_kernel: Vec<f64>,
-    /// Clock waveform shapes [phi1, phi2, phi3].
-    pub clock_waveforms: [Vec<f64>; 3],
+    /// Clock waveform shapes, one per phase.
+    pub clock_waveforms: Vec<Vec<f64>>,
     /// FD voltage -> amp output voltage transfer curve.
     pub amp_transfer_curve: Vec<(f64, f64)>,
     /// Amplifier noise sigma in electrons.
@@ -190,26 +218,30 @@ pub struct SpiceCache {
 }
 
 impl SpiceCache {
-    pub fn is_valid_for(&self, params: &SpiceParams) -> bool {
-        self.params_hash == params.param_hash()
+    pub fn is_valid_for(&self, params: &SpiceParams, sensor: &SpiceSensor) -> bool {
+        self.params_hash == params.param_hash(sensor)
     }
 }
 
 /// Run the SPICE simulation (or return cached results).
 pub fn simulate_or_cache(
     params: &SpiceParams,
-    full_well: f64,
+    sensor: &SpiceSensor,
     cache: &mut Option<SpiceCache>,
 ) {
     if let Some(c) = &*cache {
-        if c.is_valid_for(params) {
+        if c.is_valid_for(params, sensor) {
             return;
         }
     }
 
     let start = web_time::Instant::now();
-    let new_cache = run_simulation(params, full_well);
+    let new_cache = run_simulation(params, sensor);
     let sim_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+    log::info!(
+        "SPICE simulation took {sim_time_ms:.0} ms, {} of 6 stages fell back to analytical",
+        new_cache.fallbacks.count()
+    );
 
     *cache = Some(SpiceCache {
         sim_time_ms,
@@ -217,23 +249,26 @@ pub fn simulate_or_cache(
     });
 }
 
-fn run_simulation(params: &SpiceParams, full_well: f64) -> SpiceCache {
+fn run_simulation(params: &SpiceParams, sensor: &SpiceSensor) -> SpiceCache {
+    for warning in params.validate(sensor) {
+        log::warn!("SPICE parameters: {warning}");
+    }
     let glitch_params = glitch::apply_glitches(params);
     let n_points = params.transfer_function_resolution;
 
     // 1. Pixel simulation: charge -> FD voltage (analytical Q/C)
-    let (pixel_transfer, fb_pixel) = pixel::run_pixel_simulation(&glitch_params, full_well, n_points);
+    let (pixel_transfer, fb_pixel) = pixel::run_pixel_simulation(sensor, n_points);
 
     // 2. Shift register: extract effective CTE
     let (effective_cte, fb_sr) = shift_register::run_shift_register_simulation(&glitch_params);
 
     // 3. Clock driver: ringing kernel + clock waveforms
     let (clock_ringing_kernel, clock_waveforms, fb_clk) =
-        clock_driver::run_clock_simulation(&glitch_params);
+        clock_driver::run_clock_simulation(&glitch_params, sensor);
 
     // 4. Amplifier: transfer curve + noise
     let (amp_transfer_curve, amp_noise_sigma, fb_amp) =
-        amplifier::run_amplifier_simulation(&glitch_params, full_well, n_points);
+        amplifier::run_amplifier_simulation(&glitch_params, sensor, n_points);
 
     // 5. CDS: noise rejection factor
     let (cds_rejection, fb_cds) = cds::run_cds_simulation(&glitch_params);
@@ -245,7 +280,7 @@ fn run_simulation(params: &SpiceParams, full_well: f64) -> SpiceCache {
     let transfer_curve = build_transfer_curve(
         &amp_transfer_curve,
         &glitch_params,
-        full_well,
+        sensor,
         n_points,
     );
 
@@ -277,7 +312,7 @@ fn run_simulation(params: &SpiceParams, full_well: f64) -> SpiceCache {
             cds: fb_cds,
             adc: fb_adc,
         },
-        params_hash: params.param_hash(),
+        params_hash: params.param_hash(sensor),
         sim_time_ms: 0.0,
     }
 }
@@ -291,10 +326,10 @@ fn run_simulation(params: &SpiceParams, full_well: f64) -> SpiceCache {
 fn build_transfer_curve(
     amp_transfer: &[(f64, f64)],
     params: &SpiceParams,
-    full_well: f64,
+    sensor: &SpiceSensor,
     n_points: usize,
 ) -> Vec<(f64, f64)> {
-    let mut curve = transfer_function::analytical_transfer_function(params, full_well, n_points);
+    let mut curve = transfer_function::analytical_transfer_function(params, sensor, n_points);
 
     // If we have a valid SPICE amp curve, extract gain deviation and modulate
     if amp_transfer.len() >= 2 {
@@ -312,7 +347,7 @@ fn build_transfer_curve(
                 if gain_ratio > 0.5 && gain_ratio < 2.0 {
                     for (_, v) in curve.iter_mut() {
                         *v *= gain_ratio;
-                        *v = v.clamp(0.0, full_well);
+                        *v = v.clamp(0.0, sensor.full_well);
                     }
                 }
             }
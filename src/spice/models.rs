@@ -3,6 +3,13 @@
 //! Provides JSON circuit fragments with Mos1 NMOS/PMOS model definitions
 //! and instance parameters appropriate for CCD operation.
 
+/// Threshold of the `nmos_tg` transfer and reset gates in volts, as in
+/// `mos_model_defs_json`.
+pub const NMOS_TG_VT0: f64 = 0.7;
+/// Threshold of the `nmos_sf` source follower and comparator devices in
+/// volts, as in `mos_model_defs_json`.
+pub const NMOS_SF_VT0: f64 = 0.5;
+
 /// Generate JSON defs array for CCD MOS models.
 ///
 /// Returns a JSON string fragment for the `defs` array of a spice21 circuit,
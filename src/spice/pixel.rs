@@ -1,6 +1,7 @@
 //! CCD pixel circuit: photodiode + transfer gate + floating diffusion.
 
-use super::SpiceParams;
+use super::{SpiceParams, SpiceSensor};
+use crate::ccd::units::{ConversionGain, ELEMENTARY_CHARGE, Electrons, Volts};
 
 /// Build a JSON circuit for a CCD pixel readout.
 ///
@@ -8,12 +9,16 @@ use super::SpiceParams;
 /// - Photodiode: initial voltage on pixel capacitor (V = Q*e / C_pixel)
 /// - Transfer gate MOSFET (NMOS, W/L = 9u/0.5u)
 /// - Anti-blooming drain MOSFET (NMOS, W/L = 2u/1u)
-/// - Floating diffusion capacitor at output (10fF)
+/// - Floating diffusion capacitor at output, sized for the conversion gain
 ///
 /// The transfer gate clock drives charge from pixel to floating diffusion.
-pub fn build_pixel_json(charge_electrons: f64, params: &SpiceParams) -> String {
+pub fn build_pixel_json(
+    charge_electrons: f64,
+    params: &SpiceParams,
+    sensor: &SpiceSensor,
+) -> String {
     let c_pixel = 30e-15; // 30 fF
-    let c_fd = 10e-15; // 10 fF
+    let c_fd = sensor.conversion_gain.capacitance();
     let q = 1.6e-19;
     let vdd = params.effective_vdd();
 
@@ -53,35 +58,29 @@ pub fn build_pixel_json(charge_electrons: f64, params: &SpiceParams) -> String {
 }
 
 /// Compute the initial pixel voltage for a given electron count.
-pub fn charge_to_voltage(charge_electrons: f64) -> f64 {
-    let c_pixel = 30e-15;
-    let q = 1.6e-19;
-    charge_electrons * q / c_pixel
+pub fn charge_to_voltage(charge: Electrons) -> Volts {
+    Volts(charge.0 * ELEMENTARY_CHARGE / 30e-15)
 }
 
 /// Compute the floating diffusion voltage for a given charge.
-pub fn charge_to_fd_voltage(charge_electrons: f64) -> f64 {
-    let c_fd = 10e-15;
-    let q = 1.6e-19;
-    charge_electrons * q / c_fd
+pub fn charge_to_fd_voltage(charge: Electrons, gain: ConversionGain) -> Volts {
+    gain.volts(charge)
 }
 
 /// Compute the pixel transfer curve: charge (electrons) → FD signal voltage.
 ///
 /// Uses the analytical Q/C model directly, since the pixel circuit JSON
 /// cannot encode initial charge state (spice21 doesn't support IC on caps).
-/// Returns signal voltage V = Q * e / C_fd (0 at zero charge, ~0.64V at full well).
+/// Returns signal voltage V = Q * gain (0 at zero charge, 0.64V at 40 ke- with the
+/// default 16 µV/e-).
 /// Returns (transfer_curve, analytical_fallback).
-pub fn run_pixel_simulation(
-    _params: &SpiceParams,
-    full_well: f64,
-    n_points: usize,
-) -> (Vec<(f64, f64)>, bool) {
+pub fn run_pixel_simulation(sensor: &SpiceSensor, n_points: usize) -> (Vec<(f64, f64)>, bool) {
+    let gain = sensor.conversion_gain;
     log::info!("Pixel transfer: using analytical Q/C model ({} points)", n_points);
     let curve = (0..n_points)
         .map(|i| {
-            let charge = full_well * i as f64 / (n_points - 1).max(1) as f64;
-            (charge, charge_to_fd_voltage(charge))
+            let charge = sensor.full_well * i as f64 / (n_points - 1).max(1) as f64;
+            (charge, charge_to_fd_voltage(Electrons(charge), gain).0)
         })
         .collect();
     (curve, true) // Always analytical (spice21 can't encode initial charge on caps)
@@ -1,18 +1,23 @@
-//! N-stage CCD shift register with 3-phase clocking.
+//! N-stage CCD shift register with 2-, 3- or 4-phase clocking.
 //!
 //! Each stage consists of a transfer gate MOSFET and a well capacitor.
 //! CTE emerges naturally from MOSFET on-resistance and well capacitance.
 
-use super::SpiceParams;
+use super::{SpiceParams, SpiceSensor};
 
 /// Build a JSON circuit for an N-stage shift register.
 ///
-/// 3-phase clocked charge-coupled stages.
+/// Charge-coupled stages clocked by `sensor.clock_phases` phases.
 /// Each stage: transfer gate MOSFET (NMOS) + well capacitor (20-50fF).
-pub fn build_shift_register_json(n_stages: usize, params: &SpiceParams) -> String {
+pub fn build_shift_register_json(
+    n_stages: usize,
+    params: &SpiceParams,
+    sensor: &SpiceSensor,
+) -> String {
     let vdd = params.effective_vdd();
     let c_well = 30e-15; // 30 fF per well
     let n_stages = n_stages.clamp(2, 16);
+    let n_phases = sensor.clock_phases.clamp(2, 4);
 
     let mut signals = vec!["vdd".to_string()];
     let mut comps = Vec::new();
@@ -23,8 +28,8 @@ pub fn build_shift_register_json(n_stages: usize, params: &SpiceParams) -> Strin
         vdd
     ));
 
-    // Three phase clocks
-    for phase in 0..3 {
+    // Phase clocks
+    for phase in 0..n_phases {
         let sig = format!("phi{}", phase + 1);
         signals.push(sig.clone());
         // DC bias for initial simulation (would be pulsed in full transient)
@@ -38,7 +43,7 @@ pub fn build_shift_register_json(n_stages: usize, params: &SpiceParams) -> Strin
 
     // Build stages
     for i in 0..n_stages {
-        let phase = (i % 3) + 1;
+        let phase = (i % n_phases) + 1;
         let well_node = format!("well{}", i);
         let next_node = if i + 1 < n_stages {
             format!("well{}", i + 1)
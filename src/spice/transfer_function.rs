@@ -3,7 +3,10 @@
 //! Applies cached SPICE-derived (or analytical) transfer curves, ringing
 //! kernels, and timing artifacts to the image pipeline.
 
-use super::SpiceParams;
+use super::{SpiceParams, SpiceSensor};
+use crate::ccd::amplifier::LINEAR_RANGE;
+use crate::ccd::readout::{RowClock, RowStage};
+use crate::ccd::units::Electrons;
 
 /// Analytical fallback transfer function when SPICE simulation fails.
 ///
@@ -12,9 +15,10 @@ use super::SpiceParams;
 /// effects are baked into the curve without needing re-normalization.
 pub fn analytical_transfer_function(
     params: &SpiceParams,
-    full_well: f64,
+    sensor: &SpiceSensor,
     n_points: usize,
 ) -> Vec<(f64, f64)> {
+    let full_well = sensor.full_well;
     let vdd = params.effective_vdd();
     let nominal_vdd = 15.0;
 
@@ -34,11 +38,14 @@ pub fn analytical_transfer_function(
     };
     let overlap_gain_loss = 1.0 - overlap_fraction * 0.15;
     let overlap_pedestal = overlap_fraction * 0.01 * full_well;
+    let conversion_gain = sensor.conversion_gain;
 
     (0..n_points)
         .map(|i| {
             let charge = full_well * i as f64 / (n_points - 1) as f64;
-            let frac = charge / full_well;
+            // Compression follows the voltage the charge puts on the FD
+            let swing = conversion_gain.volts(Electrons(charge)).0 / LINEAR_RANGE.0;
+            let frac = swing.min(1.0);
 
             let linear = charge * responsivity;
             let compression = 0.05 + (1.0 - vdd_ratio).max(0.0) * 0.15;
@@ -88,8 +95,9 @@ pub fn analytical_ringing_kernel(params: &SpiceParams) -> Vec<f64> {
 pub fn apply_transfer_function(
     grid: &mut [f64],
     curve: &[(f64, f64)],
-    full_well: f64,
+    full_well: Electrons,
 ) {
+    let full_well = full_well.0;
     if curve.len() < 2 {
         return;
     }
@@ -104,6 +112,25 @@ pub fn apply_transfer_function(
     }
 }
 
+/// `apply_transfer_function` as a row stage, standing in for the output
+/// amplifier in the serial readout.
+pub struct TransferCurve<'a> {
+    curve: &'a [(f64, f64)],
+    full_well: Electrons,
+}
+
+impl<'a> TransferCurve<'a> {
+    pub fn new(curve: &'a [(f64, f64)], full_well: Electrons) -> Self {
+        Self { curve, full_well }
+    }
+}
+
+impl RowStage for TransferCurve<'_> {
+    fn read_row(&mut self, row: &mut [f64], _clock: &RowClock) {
+        apply_transfer_function(row, self.curve, self.full_well);
+    }
+}
+
 /// Apply missing-pulse artifacts to the image grid.
 ///
 /// When a clock pulse is missing during readout, the affected row has incomplete
@@ -135,11 +162,14 @@ pub fn apply_missing_pulses(
 }
 
 /// Apply ringing convolution along each row.
+///
+/// With `wrap`, the kernel reaches back across the start of the row to its end.
 pub fn apply_ringing(
     grid: &mut [f64],
     width: usize,
     height: usize,
     kernel: &[f64],
+    wrap: bool,
 ) {
     if kernel.is_empty() || kernel.iter().all(|&v| v.abs() < 1e-12) {
         return;
@@ -152,12 +182,23 @@ pub fn apply_ringing(
         let row_start = y * width;
         row_buf.copy_from_slice(&grid[row_start..row_start + width]);
 
-        for x in klen..width {
-            let mut sum = 0.0;
-            for k in 0..klen {
-                sum += row_buf[x - k - 1] * kernel[k];
+        if wrap {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for (k, &coef) in kernel.iter().enumerate() {
+                    let src = (x as isize - k as isize - 1).rem_euclid(width as isize) as usize;
+                    sum += row_buf[src] * coef;
+                }
+                grid[row_start + x] += sum;
+            }
+        } else {
+            for x in klen..width {
+                let mut sum = 0.0;
+                for k in 0..klen {
+                    sum += row_buf[x - k - 1] * kernel[k];
+                }
+                grid[row_start + x] += sum;
             }
-            grid[row_start + x] += sum;
         }
     }
 }
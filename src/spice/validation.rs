@@ -0,0 +1,160 @@
+//! Sanity checks on SPICE parameters before simulating.
+//!
+//! Settings outside what the circuits can run (a supply below the gate
+//! thresholds, clock pulses wider than the clock period) make spice21 fail
+//! or converge on nonsense, and the stage then quietly falls back to its
+//! analytical model. `SpiceParams::validate` names those problems up front
+//! so the UI can warn and the CLI can refuse or clamp.
+
+use super::{SpiceParams, SpiceSensor};
+use super::models::{NMOS_SF_VT0, NMOS_TG_VT0};
+
+/// Effective supply below which the output swing has under ~1 V of
+/// overdrive left above the thresholds.
+const MIN_HEADROOM_VDD: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// The circuit runs but outside its useful range.
+    Warning,
+    /// The circuit is degenerate: the simulation will fail or fall back.
+    Error,
+}
+
+/// One problem `SpiceParams::validate` found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpiceWarning {
+    /// The parameter to change, as `PipelineParams` names it.
+    pub param: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// The nearest value that clears the problem, when there is one.
+    pub safe_value: Option<f64>,
+}
+
+impl std::fmt::Display for SpiceWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{level}: {}: {}", self.param, self.message)
+    }
+}
+
+impl SpiceParams {
+    /// Problems with these parameters on `sensor`, most fundamental first.
+    /// Empty when the circuits can run as set.
+    pub fn validate(&self, sensor: &SpiceSensor) -> Vec<SpiceWarning> {
+        let mut warnings = Vec::new();
+        let mut push = |param, severity, message: String, safe_value| {
+            warnings.push(SpiceWarning { param, severity, message, safe_value });
+        };
+
+        if !(self.vdd.is_finite() && self.vdd > 0.0) {
+            let message = format!("VDD of {} V is not a supply", self.vdd);
+            push("spice.vdd", Severity::Error, message, Some(15.0));
+        }
+        if !(0.0..1.0).contains(&self.supply_droop) {
+            let message = format!("droop of {} leaves no supply", self.supply_droop);
+            push("spice.supply_droop", Severity::Error, message, Some(0.8));
+        }
+        let vdd = self.effective_vdd();
+        if vdd.is_finite() && vdd > 0.0 && (0.0..1.0).contains(&self.supply_droop) {
+            if vdd <= NMOS_TG_VT0 {
+                let message = format!(
+                    "effective VDD of {vdd:.2} V is below the {NMOS_TG_VT0} V transfer gate \
+                     threshold, so no gate turns on"
+                );
+                let safe = MIN_HEADROOM_VDD / (1.0 - self.supply_droop);
+                push("spice.vdd", Severity::Error, message, Some(safe));
+            } else if vdd < MIN_HEADROOM_VDD {
+                let message = format!(
+                    "effective VDD of {vdd:.2} V leaves little overdrive above the {NMOS_TG_VT0} V \
+                     gate and {NMOS_SF_VT0} V source follower thresholds, so the output swing \
+                     collapses"
+                );
+                push("spice.vdd", Severity::Warning, message, None);
+            }
+        }
+
+        if !(self.clock_freq_mhz.is_finite() && self.clock_freq_mhz > 0.0) {
+            let message = format!("clock of {} MHz never ticks", self.clock_freq_mhz);
+            push("spice.clock_freq_mhz", Severity::Error, message, Some(10.0));
+        } else if self.phase_overlap_ns > 0.0 {
+            // Every pulse widens by the overlap on both sides; once that fills
+            // the period all phases stay high and nothing transfers
+            let period_ns = self.clock_period_s() * 1e9;
+            let phases = sensor.clock_phases.clamp(2, 4);
+            let duty = if phases == 4 { 0.5 } else { 1.0 / phases as f64 };
+            let limit = (1.0 - duty) * period_ns / 2.0;
+            if self.phase_overlap_ns >= limit {
+                let message = format!(
+                    "{} ns of overlap widens every pulse past the {period_ns:.1} ns clock period, \
+                     so all phases stay high and no charge transfers",
+                    self.phase_overlap_ns
+                );
+                push("spice.phase_overlap_ns", Severity::Error, message, Some(limit * 0.9));
+            }
+        }
+
+        if !(self.temperature_k.is_finite() && self.temperature_k > 0.0) {
+            let message = format!("{} K is not a temperature", self.temperature_k);
+            push("spice.temperature_k", Severity::Error, message, Some(300.0));
+        }
+        if self.shift_register_stages < 2 {
+            let stages = self.shift_register_stages;
+            let message = format!("{stages} shift register stages cannot transfer");
+            push("spice.shift_register_stages", Severity::Error, message, Some(2.0));
+        }
+        if self.transfer_function_resolution < 2 {
+            let points = self.transfer_function_resolution;
+            let message = format!("{points} points cannot describe a transfer curve");
+            push("spice.transfer_function_resolution", Severity::Error, message, Some(2.0));
+        }
+        if !(2..=4).contains(&sensor.clock_phases) {
+            let phases = sensor.clock_phases;
+            let message =
+                format!("{phases}-phase clocking is simulated as {}-phase", phases.clamp(2, 4));
+            push("v_phases", Severity::Warning, message, None);
+        }
+        if !(0.0..=1.0).contains(&self.missing_pulse_rate) {
+            let rate = self.missing_pulse_rate;
+            let message = format!("missing pulse rate of {rate} is not a probability");
+            let safe = rate.clamp(0.0, 1.0);
+            push("spice.missing_pulse_rate", Severity::Error, message, Some(safe));
+        }
+        let gain = sensor.conversion_gain.uv_per_e;
+        if !(gain.is_finite() && gain > 0.0) {
+            let message = format!("conversion gain of {gain} µV/e- gives no signal");
+            push("conversion_gain", Severity::Error, message, None);
+        }
+        warnings
+    }
+
+    /// Apply the safe value of every problem that has one, returning the
+    /// problems it cleared. Sensor parameters are left alone.
+    pub fn clamp_to_safe(&mut self, sensor: &SpiceSensor) -> Vec<SpiceWarning> {
+        let mut cleared = Vec::new();
+        for warning in self.validate(sensor) {
+            let Some(value) = warning.safe_value else {
+                continue;
+            };
+            match warning.param {
+                "spice.vdd" => self.vdd = value,
+                "spice.supply_droop" => self.supply_droop = value,
+                "spice.clock_freq_mhz" => self.clock_freq_mhz = value,
+                "spice.phase_overlap_ns" => self.phase_overlap_ns = value,
+                "spice.temperature_k" => self.temperature_k = value,
+                "spice.shift_register_stages" => self.shift_register_stages = value as usize,
+                "spice.transfer_function_resolution" => {
+                    self.transfer_function_resolution = value as usize
+                }
+                "spice.missing_pulse_rate" => self.missing_pulse_rate = value,
+                _ => continue,
+            }
+            cleared.push(warning);
+        }
+        cleared
+    }
+}
@@ -0,0 +1,95 @@
+use eframe::egui;
+
+use crate::pipeline::StageTimings;
+
+const BAR_BG: egui::Color32 = egui::Color32::from_rgb(6, 8, 16);
+const BAR_BORDER: egui::Color32 = egui::Color32::from_rgb(35, 45, 40);
+const LABEL_DIM: egui::Color32 = egui::Color32::from_rgb(120, 120, 140);
+const ISSUE: egui::Color32 = egui::Color32::from_rgb(230, 80, 60);
+
+/// Per-stage colors, cycled in stage order.
+const STAGE_COLORS: &[egui::Color32] = &[
+    egui::Color32::from_rgb(0, 190, 255),
+    egui::Color32::from_rgb(0, 220, 110),
+    egui::Color32::from_rgb(255, 220, 0),
+    egui::Color32::from_rgb(255, 80, 200),
+    egui::Color32::from_rgb(255, 180, 40),
+    egui::Color32::from_rgb(140, 110, 255),
+    egui::Color32::from_rgb(255, 80, 40),
+    egui::Color32::from_rgb(80, 255, 220),
+];
+
+/// Draw a stacked horizontal bar showing how processing time splits across stages.
+///
+/// Segments wide enough get an inline label; every segment shows its exact
+/// time on hover.
+pub fn draw_timing_bar(ui: &mut egui::Ui, timings: &StageTimings) {
+    let total = timings.total_ms();
+    if timings.stages.is_empty() || total <= 0.0 {
+        ui.label(egui::RichText::new("No timing data").small().color(LABEL_DIM));
+        return;
+    }
+
+    let width = ui.available_width();
+    let height = 18.0;
+    let (response, painter) = ui.allocate_painter(egui::vec2(width, height), egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, BAR_BG);
+
+    let mut x = rect.left();
+    let mut hovered = None;
+    for (i, (name, ms)) in timings.stages.iter().enumerate() {
+        let seg_w = (ms / total) as f32 * rect.width();
+        let seg = egui::Rect::from_min_max(
+            egui::pos2(x, rect.top()),
+            egui::pos2(x + seg_w, rect.bottom()),
+        );
+        let color = STAGE_COLORS[i % STAGE_COLORS.len()];
+        painter.rect_filled(seg, 0.0, color.gamma_multiply(0.7));
+
+        if seg_w > 40.0 {
+            painter.text(
+                seg.center(),
+                egui::Align2::CENTER_CENTER,
+                *name,
+                egui::FontId::monospace(8.0),
+                egui::Color32::BLACK,
+            );
+        }
+        if response.hover_pos().is_some_and(|p| seg.contains(p)) {
+            hovered = Some((*name, *ms));
+        }
+        x += seg_w;
+    }
+    painter.rect_stroke(rect, 2.0, egui::Stroke::new(1.0, BAR_BORDER), egui::StrokeKind::Outside);
+
+    if let Some((name, ms)) = hovered {
+        response.on_hover_text(format!("{}: {:.1}ms ({:.0}%)", name, ms, ms / total * 100.0));
+    }
+
+    // Legend: top stages by time
+    let mut sorted: Vec<_> = timings.stages.iter().enumerate().collect();
+    sorted.sort_by(|a, b| b.1.1.total_cmp(&a.1.1));
+    ui.horizontal_wrapped(|ui| {
+        for (i, (name, ms)) in sorted.into_iter().take(4) {
+            let color = STAGE_COLORS[i % STAGE_COLORS.len()];
+            ui.label(
+                egui::RichText::new(format!("{} {:.0}ms", name, ms))
+                    .small()
+                    .monospace()
+                    .color(color),
+            );
+        }
+        ui.label(
+            egui::RichText::new(format!("total {:.0}ms", total))
+                .small()
+                .monospace()
+                .color(LABEL_DIM),
+        );
+    });
+
+    // Validation failures, first offender first
+    for issue in &timings.issues {
+        ui.label(egui::RichText::new(issue.to_string()).small().monospace().color(ISSUE));
+    }
+}
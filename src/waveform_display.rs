@@ -1,5 +1,6 @@
 use eframe::egui;
 
+use crate::ccd::transfer::ClockPhases;
 use crate::pipeline::PipelineParams;
 
 // Oscilloscope colors
@@ -182,17 +183,23 @@ fn draw_clock_panel(ui: &mut egui::Ui, params: &PipelineParams) {
 
     draw_scope_bg(&painter, rect);
 
-    // V-Clock: 3-phase, 8 row transfer cycles
+    // V-Clock: one trace per phase, 8 row transfer cycles. 2- and 3-phase
+    // clocks are non-overlapping; 4-phase runs at 50% duty in quadrature.
     let v_cycles = 8;
     let v_samples = v_cycles * 30; // 30 samples per cycle
-    let phase_offsets = [0.0f32, 0.33, 0.67];
-    let pulse_width = 0.36;
-    let colors = [TRACE_GREEN, TRACE_CYAN, TRACE_YELLOW];
-    let labels = ["Φ1", "Φ2", "Φ3"];
-
-    let band_h = (height - 4.0) / 3.0;
-
-    for (p, (&offset, &color)) in phase_offsets.iter().zip(colors.iter()).enumerate() {
+    let n_phases = params.v_phases.count();
+    let pulse_width = match params.v_phases {
+        ClockPhases::Two => 0.52,
+        ClockPhases::Three => 0.36,
+        ClockPhases::Four => 0.5,
+    };
+    let colors = [TRACE_GREEN, TRACE_CYAN, TRACE_YELLOW, TRACE_MAGENTA];
+    let labels = ["Φ1", "Φ2", "Φ3", "Φ4"];
+
+    let band_h = (height - 4.0) / n_phases as f32;
+
+    for (p, &color) in colors.iter().enumerate().take(n_phases) {
+        let offset = p as f32 / n_phases as f32;
         let band_top = rect.min.y + 2.0 + p as f32 * band_h;
         let band = egui::Rect::from_min_size(
             egui::pos2(rect.min.x + 18.0, band_top),
@@ -310,6 +317,8 @@ fn draw_video_panel(ui: &mut egui::Ui, params: &PipelineParams) {
             if params.nonlinearity > 0.0 { effects.push("Nonlinearity"); }
             if params.reset_noise > 0.0 { effects.push("Reset noise"); }
             if params.amp_glow > 0.0 { effects.push("Amp glow"); }
+            if params.amp_drift > 0.0 { effects.push("Baseline drift"); }
+            if !params.thermal.is_off() { effects.push("Amp heating"); }
             if params.bit_depth < 16 { effects.push("Quantization"); }
             if params.dnl_errors > 0.0 { effects.push("DNL errors"); }
             if params.bit_errors > 0.0 { effects.push("Bit errors"); }